@@ -0,0 +1,267 @@
+-- Initial schema for PostgreSQL, equivalent to the
+-- SQLite schema after applying all migrations in
+-- ../migrations up to 2020-08-13_contact_name_and_founding_date.
+--
+-- All integer columns that are mapped to i64 are declared
+-- as BIGINT and the implicit SQLite rowid columns are
+-- replaced by explicit BIGSERIAL primary keys.
+
+-- E-mail addresses are compared case-insensitively
+-- (SQLite: COLLATE NOCASE). Requires PostgreSQL 12+
+-- with ICU support.
+CREATE COLLATION case_insensitive (
+    provider = icu,
+    locale = 'und-u-ks-level2',
+    deterministic = false
+);
+
+CREATE TABLE tags (
+    id TEXT PRIMARY KEY NOT NULL
+);
+
+CREATE TABLE users (
+    id              BIGSERIAL PRIMARY KEY,
+    email           TEXT     NOT NULL COLLATE case_insensitive,
+    email_confirmed BOOLEAN  NOT NULL DEFAULT FALSE,
+    password        TEXT     NOT NULL,
+    role            SMALLINT NOT NULL,
+    UNIQUE (email)
+);
+
+CREATE TABLE user_tokens (
+    id          BIGSERIAL PRIMARY KEY,
+    user_id     BIGINT NOT NULL,
+    expires_at  BIGINT NOT NULL,
+    nonce       TEXT   NOT NULL,
+    UNIQUE      (user_id),
+    UNIQUE      (nonce),
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+CREATE TABLE events (
+    id             BIGSERIAL PRIMARY KEY,
+    uid            TEXT   NOT NULL,
+    title          TEXT   NOT NULL,
+    description    TEXT,
+    start          BIGINT NOT NULL,
+    "end"          BIGINT,
+    lat            DOUBLE PRECISION,
+    lng            DOUBLE PRECISION,
+    street         TEXT,
+    zip            TEXT,
+    city           TEXT,
+    country        TEXT,
+    state          TEXT,
+    email          TEXT,
+    telephone      TEXT,
+    homepage       TEXT,
+    created_by     BIGINT,
+    registration   SMALLINT,
+    organizer      TEXT,
+    archived       BIGINT DEFAULT NULL,
+    image_url      TEXT,
+    image_link_url TEXT,
+    FOREIGN KEY (created_by) REFERENCES users(id)
+);
+
+CREATE UNIQUE INDEX events_uc ON events (uid);
+CREATE INDEX events_fk_created_by ON events (created_by);
+
+CREATE TABLE event_tags (
+    event_id BIGINT NOT NULL,
+    tag      TEXT   NOT NULL,
+    PRIMARY KEY (event_id, tag),
+    FOREIGN KEY (event_id) REFERENCES events(id)
+);
+
+CREATE INDEX event_tag_index ON event_tags (tag);
+
+CREATE TABLE bbox_subscriptions (
+    id              BIGSERIAL PRIMARY KEY,
+    uid             TEXT             NOT NULL,
+    user_id         BIGINT           NOT NULL,
+    south_west_lat  DOUBLE PRECISION NOT NULL,
+    south_west_lng  DOUBLE PRECISION NOT NULL,
+    north_east_lat  DOUBLE PRECISION NOT NULL,
+    north_east_lng  DOUBLE PRECISION NOT NULL,
+    UNIQUE (uid),
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+CREATE TABLE place (
+    rowid       BIGSERIAL PRIMARY KEY,
+    --
+    current_rev BIGINT NOT NULL, -- latest revision (mutable) from place_revision
+    --
+    id          TEXT NOT NULL,
+    license     TEXT NOT NULL,
+    --
+    UNIQUE (id)
+);
+
+CREATE TABLE place_revision (
+    rowid          BIGSERIAL PRIMARY KEY,
+    parent_rowid   BIGINT NOT NULL,
+    --
+    rev            BIGINT NOT NULL,
+    created_at     BIGINT NOT NULL,
+    created_by     BIGINT,
+    --
+    current_status SMALLINT NOT NULL, -- latest status (mutable) from place_revision_review
+    --
+    title          TEXT NOT NULL,
+    description    TEXT NOT NULL,
+    lat            DOUBLE PRECISION NOT NULL,
+    lon            DOUBLE PRECISION NOT NULL,
+    street         TEXT,
+    zip            TEXT,
+    city           TEXT,
+    country        TEXT,
+    state          TEXT,
+    contact_name   TEXT,
+    email          TEXT,
+    phone          TEXT,
+    homepage       TEXT,
+    opening_hours  TEXT,
+    founded_on     DATE,
+    image_url      TEXT,
+    image_link_url TEXT,
+    --
+    UNIQUE (parent_rowid, rev),
+    FOREIGN KEY (parent_rowid) REFERENCES place(rowid),
+    FOREIGN KEY (created_by) REFERENCES users(id)
+);
+
+CREATE INDEX place_revision_idx_created_at ON place_revision (created_at);
+CREATE INDEX place_revision_idx_created_by ON place_revision (created_by);
+
+CREATE TABLE place_revision_review (
+    rowid        BIGSERIAL PRIMARY KEY,
+    parent_rowid BIGINT NOT NULL,
+    --
+    rev          BIGINT NOT NULL,
+    created_at   BIGINT NOT NULL,
+    created_by   BIGINT,
+    --
+    status       SMALLINT NOT NULL,
+    context      TEXT, -- system context, e.g. client IP address, ...
+    comment      TEXT, -- human-written textual comment
+    --
+    UNIQUE (parent_rowid, rev),
+    FOREIGN KEY (parent_rowid) REFERENCES place_revision(rowid),
+    FOREIGN KEY (created_by) REFERENCES users(id)
+);
+
+CREATE INDEX place_revision_review_idx_created_at ON place_revision_review (created_at);
+CREATE INDEX place_revision_review_idx_created_by ON place_revision_review (created_by);
+
+CREATE TABLE place_revision_tag (
+    parent_rowid BIGINT NOT NULL,
+    tag          TEXT   NOT NULL,
+    PRIMARY KEY (parent_rowid, tag),
+    FOREIGN KEY (parent_rowid) REFERENCES place_revision(rowid)
+);
+
+CREATE INDEX place_revision_tag_idx_tag ON place_revision_tag (tag);
+
+CREATE TABLE place_revision_custom_link (
+    parent_rowid BIGINT NOT NULL,
+    --
+    url          TEXT NOT NULL,
+    title        TEXT,
+    description  TEXT,
+    PRIMARY KEY (parent_rowid, url),
+    FOREIGN KEY (parent_rowid) REFERENCES place_revision(rowid)
+);
+
+CREATE TABLE place_rating (
+    rowid        BIGSERIAL PRIMARY KEY,
+    parent_rowid BIGINT NOT NULL,
+    --
+    created_at   BIGINT NOT NULL,
+    created_by   BIGINT,
+    archived_at  BIGINT,
+    archived_by  BIGINT,
+    --
+    id           TEXT     NOT NULL,
+    title        TEXT     NOT NULL,
+    value        SMALLINT NOT NULL,
+    context      TEXT     NOT NULL,
+    source       TEXT,
+    --
+    UNIQUE (id),
+    FOREIGN KEY (parent_rowid) REFERENCES place(rowid),
+    FOREIGN KEY (created_by) REFERENCES users(id),
+    FOREIGN KEY (archived_by) REFERENCES users(id)
+);
+
+CREATE INDEX place_rating_idx_parent_rowid ON place_rating (parent_rowid);
+CREATE INDEX place_rating_idx_created_at ON place_rating (created_at);
+CREATE INDEX place_rating_idx_created_by ON place_rating (created_by);
+CREATE INDEX place_rating_idx_archived_at ON place_rating (archived_at);
+CREATE INDEX place_rating_idx_archived_by ON place_rating (archived_by);
+
+CREATE TABLE place_rating_comment (
+    rowid        BIGSERIAL PRIMARY KEY,
+    parent_rowid BIGINT NOT NULL,
+    --
+    created_at   BIGINT NOT NULL,
+    created_by   BIGINT,
+    archived_at  BIGINT,
+    archived_by  BIGINT,
+    --
+    id           TEXT NOT NULL,
+    text         TEXT NOT NULL,
+    --
+    UNIQUE (id),
+    FOREIGN KEY (parent_rowid) REFERENCES place_rating(rowid),
+    FOREIGN KEY (created_by) REFERENCES users(id),
+    FOREIGN KEY (archived_by) REFERENCES users(id)
+);
+
+CREATE INDEX place_rating_comment_idx_parent_rowid ON place_rating_comment (parent_rowid);
+CREATE INDEX place_rating_comment_idx_created_at ON place_rating_comment (created_at);
+CREATE INDEX place_rating_comment_idx_created_by ON place_rating_comment (created_by);
+CREATE INDEX place_rating_comment_idx_archived_at ON place_rating_comment (archived_at);
+CREATE INDEX place_rating_comment_idx_archived_by ON place_rating_comment (archived_by);
+
+CREATE TABLE organization (
+    rowid     BIGSERIAL PRIMARY KEY,
+    --
+    id        TEXT NOT NULL,
+    name      TEXT NOT NULL,
+    api_token TEXT NOT NULL,
+    --
+    UNIQUE (id)
+);
+
+CREATE TABLE organization_tag (
+    rowid     BIGSERIAL PRIMARY KEY,
+    --
+    org_rowid BIGINT NOT NULL,
+    tag_label TEXT   NOT NULL,
+    --
+    tag_allow_add     SMALLINT NOT NULL,
+    tag_allow_remove  SMALLINT NOT NULL,
+    require_clearance SMALLINT NOT NULL,
+    --
+    UNIQUE (org_rowid, tag_label),
+    FOREIGN KEY (org_rowid) REFERENCES organization(rowid)
+    -- no FK for tag_label (may not yet exist)
+);
+
+CREATE INDEX organization_tag_idx_tag_label ON organization_tag (tag_label);
+
+CREATE TABLE organization_place_clearance (
+    rowid        BIGSERIAL PRIMARY KEY,
+    --
+    org_rowid    BIGINT NOT NULL,
+    place_rowid  BIGINT NOT NULL,
+    --
+    created_at            BIGINT NOT NULL,
+    last_cleared_revision BIGINT, -- last cleared revision number or NULL if the place is new and has not been cleared yet
+    --
+    UNIQUE (org_rowid, place_rowid),
+    FOREIGN KEY (org_rowid) REFERENCES organization(rowid),
+    FOREIGN KEY (place_rowid) REFERENCES place(rowid)
+);
@@ -0,0 +1,3 @@
+DROP TABLE organization_webhook_delivery;
+DROP TABLE organization_webhook_tag;
+DROP TABLE organization_webhook;
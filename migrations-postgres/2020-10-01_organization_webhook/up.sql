@@ -0,0 +1,52 @@
+-- Webhooks of organizations that receive changes of places and events
+CREATE TABLE organization_webhook (
+    rowid      BIGSERIAL PRIMARY KEY,
+    --
+    id         TEXT NOT NULL,
+    org_rowid  BIGINT NOT NULL,
+    --
+    url        TEXT NOT NULL,
+    secret     TEXT NOT NULL,
+    created_at BIGINT NOT NULL,
+    -- optional bounding box that restricts the scope
+    bbox_south_west_lat DOUBLE PRECISION,
+    bbox_south_west_lng DOUBLE PRECISION,
+    bbox_north_east_lat DOUBLE PRECISION,
+    bbox_north_east_lng DOUBLE PRECISION,
+    --
+    UNIQUE (id),
+    FOREIGN KEY (org_rowid) REFERENCES organization(rowid)
+);
+
+CREATE INDEX organization_webhook_idx_org_rowid ON organization_webhook(org_rowid);
+
+-- Optional tags that restrict the scope of a webhook
+CREATE TABLE organization_webhook_tag (
+    webhook_rowid BIGINT NOT NULL,
+    tag           TEXT NOT NULL,
+    --
+    PRIMARY KEY (webhook_rowid, tag),
+    FOREIGN KEY (webhook_rowid) REFERENCES organization_webhook(rowid)
+);
+
+-- Log of all deliveries to webhooks
+CREATE TABLE organization_webhook_delivery (
+    rowid           BIGSERIAL PRIMARY KEY,
+    --
+    id              TEXT NOT NULL,
+    webhook_rowid   BIGINT NOT NULL,
+    --
+    event           TEXT NOT NULL,
+    payload         TEXT NOT NULL,
+    created_at      BIGINT NOT NULL,
+    attempts        BIGINT NOT NULL,
+    last_attempt_at BIGINT,
+    last_status     BIGINT, -- HTTP status code of the last response
+    last_error      TEXT,
+    delivered_at    BIGINT,
+    --
+    UNIQUE (id),
+    FOREIGN KEY (webhook_rowid) REFERENCES organization_webhook(rowid)
+);
+
+CREATE INDEX organization_webhook_delivery_idx_webhook_rowid ON organization_webhook_delivery(webhook_rowid);
@@ -0,0 +1,17 @@
+-- Persistent queue of background jobs
+CREATE TABLE job (
+    rowid      BIGSERIAL PRIMARY KEY,
+    --
+    id         TEXT NOT NULL,
+    kind       TEXT NOT NULL,
+    payload    TEXT NOT NULL,
+    status     TEXT NOT NULL, -- pending, running, failed
+    attempts   BIGINT NOT NULL,
+    created_at BIGINT NOT NULL,
+    run_at     BIGINT NOT NULL,
+    last_error TEXT,
+    --
+    UNIQUE (id)
+);
+
+CREATE INDEX job_idx_status_run_at ON job(status, run_at);
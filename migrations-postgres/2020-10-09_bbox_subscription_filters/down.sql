@@ -0,0 +1,3 @@
+DROP TABLE bbox_subscription_tags;
+ALTER TABLE bbox_subscriptions DROP COLUMN last_digest_at;
+ALTER TABLE bbox_subscriptions DROP COLUMN delivery;
@@ -0,0 +1,13 @@
+-- How changes are delivered: immediate, daily or weekly
+ALTER TABLE bbox_subscriptions ADD COLUMN delivery TEXT NOT NULL DEFAULT 'immediate';
+-- The end of the period covered by the last digest
+ALTER TABLE bbox_subscriptions ADD COLUMN last_digest_at BIGINT;
+
+-- Optional tags and categories that restrict the scope of a subscription
+CREATE TABLE bbox_subscription_tags (
+    subscription_id BIGINT NOT NULL,
+    tag             TEXT NOT NULL,
+    --
+    PRIMARY KEY (subscription_id, tag),
+    FOREIGN KEY (subscription_id) REFERENCES bbox_subscriptions(id)
+);
@@ -0,0 +1,3 @@
+DROP INDEX bbox_subscriptions_idx_user_id;
+ALTER TABLE bbox_subscriptions DROP COLUMN unsubscribe_nonce;
+ALTER TABLE bbox_subscriptions DROP COLUMN title;
@@ -0,0 +1 @@
+ALTER TABLE bbox_subscriptions DROP COLUMN polygon;
@@ -0,0 +1,2 @@
+-- Optional region within the bounding box as WKT
+ALTER TABLE bbox_subscriptions ADD COLUMN polygon TEXT;
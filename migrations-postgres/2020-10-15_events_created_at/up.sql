@@ -0,0 +1,5 @@
+-- Time of creation for sorting events, unknown for existing events
+-- that have not been modified since changes have been tracked
+ALTER TABLE events ADD COLUMN created_at BIGINT;
+
+UPDATE events SET created_at = updated_at;
@@ -0,0 +1,2 @@
+DROP TABLE event_recurrence_override;
+ALTER TABLE events DROP COLUMN recurrence;
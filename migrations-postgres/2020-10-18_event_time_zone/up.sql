@@ -0,0 +1,4 @@
+-- IANA time zone of the event. Existing events still contain
+-- local times and are converted into UTC on startup, assuming
+-- the configured default time zone.
+ALTER TABLE events ADD COLUMN time_zone TEXT;
@@ -0,0 +1,77 @@
+-- Registered users or organizations that own a place
+CREATE TABLE place_owner (
+    rowid       BIGSERIAL PRIMARY KEY,
+    --
+    place_rowid BIGINT NOT NULL,
+    -- either a user or an organization
+    user_rowid  BIGINT,
+    org_rowid   BIGINT,
+    --
+    FOREIGN KEY (place_rowid) REFERENCES place(rowid),
+    FOREIGN KEY (user_rowid) REFERENCES users(id),
+    FOREIGN KEY (org_rowid) REFERENCES organization(rowid)
+);
+
+CREATE INDEX place_owner_idx_place_rowid ON place_owner(place_rowid);
+
+-- Fields of a place that could only be changed by its owners
+CREATE TABLE place_locked_field (
+    place_rowid BIGINT NOT NULL,
+    field       TEXT NOT NULL,
+    --
+    PRIMARY KEY (place_rowid, field),
+    FOREIGN KEY (place_rowid) REFERENCES place(rowid)
+);
+
+-- Changes of locked fields that have been proposed by others
+CREATE TABLE place_proposal (
+    rowid          BIGSERIAL PRIMARY KEY,
+    --
+    id             TEXT NOT NULL,
+    place_rowid    BIGINT NOT NULL,
+    base_rev       BIGINT NOT NULL,
+    created_at     BIGINT NOT NULL,
+    created_by     BIGINT,
+    --
+    title          TEXT NOT NULL,
+    description    TEXT NOT NULL,
+    lat            DOUBLE PRECISION NOT NULL,
+    lon            DOUBLE PRECISION NOT NULL,
+    street         TEXT,
+    zip            TEXT,
+    city           TEXT,
+    country        TEXT,
+    state          TEXT,
+    contact_name   TEXT,
+    email          TEXT,
+    phone          TEXT,
+    homepage       TEXT,
+    opening_hours  TEXT,
+    founded_on     DATE,
+    image_url      TEXT,
+    image_link_url TEXT,
+    --
+    UNIQUE (id),
+    FOREIGN KEY (place_rowid) REFERENCES place(rowid),
+    FOREIGN KEY (created_by) REFERENCES users(id)
+);
+
+CREATE TABLE place_proposal_tag (
+    parent_rowid BIGINT NOT NULL,
+    --
+    tag          TEXT NOT NULL,
+    --
+    PRIMARY KEY (parent_rowid, tag),
+    FOREIGN KEY (parent_rowid) REFERENCES place_proposal(rowid)
+);
+
+CREATE TABLE place_proposal_custom_link (
+    parent_rowid BIGINT NOT NULL,
+    --
+    url          TEXT NOT NULL,
+    title        TEXT,
+    description  TEXT,
+    --
+    PRIMARY KEY (parent_rowid, url),
+    FOREIGN KEY (parent_rowid) REFERENCES place_proposal(rowid)
+);
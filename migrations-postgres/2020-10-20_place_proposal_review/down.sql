@@ -0,0 +1,5 @@
+DROP INDEX place_proposal_idx_status;
+ALTER TABLE place_proposal DROP COLUMN review_comment;
+ALTER TABLE place_proposal DROP COLUMN reviewed_by;
+ALTER TABLE place_proposal DROP COLUMN reviewed_at;
+ALTER TABLE place_proposal DROP COLUMN status;
@@ -0,0 +1,8 @@
+-- Deleted events are recorded to remove them from the
+-- search index when it is updated incrementally
+CREATE TABLE deleted_events (
+    uid        TEXT PRIMARY KEY NOT NULL,
+    deleted_at BIGINT NOT NULL
+);
+
+CREATE INDEX deleted_events_idx_deleted_at ON deleted_events(deleted_at);
@@ -0,0 +1 @@
+ALTER TABLE place_proposal DROP COLUMN org_rowid;
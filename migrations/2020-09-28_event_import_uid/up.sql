@@ -0,0 +1,13 @@
+-- Maps the UIDs of events imported by organizations from
+-- external calendars onto the corresponding events
+CREATE TABLE event_import_uid (
+    org_rowid   INTEGER NOT NULL,
+    import_uid  TEXT NOT NULL,
+    event_rowid INTEGER NOT NULL,
+    --
+    PRIMARY KEY (org_rowid, import_uid),
+    FOREIGN KEY (org_rowid) REFERENCES organization(rowid),
+    FOREIGN KEY (event_rowid) REFERENCES events(id)
+);
+
+CREATE INDEX event_import_uid_idx_event_rowid ON event_import_uid(event_rowid);
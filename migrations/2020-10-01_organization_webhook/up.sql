@@ -0,0 +1,52 @@
+-- Webhooks of organizations that receive changes of places and events
+CREATE TABLE organization_webhook (
+    rowid      INTEGER PRIMARY KEY NOT NULL,
+    --
+    id         TEXT NOT NULL,
+    org_rowid  INTEGER NOT NULL,
+    --
+    url        TEXT NOT NULL,
+    secret     TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    -- optional bounding box that restricts the scope
+    bbox_south_west_lat REAL,
+    bbox_south_west_lng REAL,
+    bbox_north_east_lat REAL,
+    bbox_north_east_lng REAL,
+    --
+    UNIQUE (id),
+    FOREIGN KEY (org_rowid) REFERENCES organization(rowid)
+);
+
+CREATE INDEX organization_webhook_idx_org_rowid ON organization_webhook(org_rowid);
+
+-- Optional tags that restrict the scope of a webhook
+CREATE TABLE organization_webhook_tag (
+    webhook_rowid INTEGER NOT NULL,
+    tag           TEXT NOT NULL,
+    --
+    PRIMARY KEY (webhook_rowid, tag),
+    FOREIGN KEY (webhook_rowid) REFERENCES organization_webhook(rowid)
+);
+
+-- Log of all deliveries to webhooks
+CREATE TABLE organization_webhook_delivery (
+    rowid           INTEGER PRIMARY KEY NOT NULL,
+    --
+    id              TEXT NOT NULL,
+    webhook_rowid   INTEGER NOT NULL,
+    --
+    event           TEXT NOT NULL,
+    payload         TEXT NOT NULL,
+    created_at      INTEGER NOT NULL,
+    attempts        INTEGER NOT NULL,
+    last_attempt_at INTEGER,
+    last_status     INTEGER, -- HTTP status code of the last response
+    last_error      TEXT,
+    delivered_at    INTEGER,
+    --
+    UNIQUE (id),
+    FOREIGN KEY (webhook_rowid) REFERENCES organization_webhook(rowid)
+);
+
+CREATE INDEX organization_webhook_delivery_idx_webhook_rowid ON organization_webhook_delivery(webhook_rowid);
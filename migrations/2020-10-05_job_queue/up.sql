@@ -0,0 +1,17 @@
+-- Persistent queue of background jobs
+CREATE TABLE job (
+    rowid      INTEGER PRIMARY KEY NOT NULL,
+    --
+    id         TEXT NOT NULL,
+    kind       TEXT NOT NULL,
+    payload    TEXT NOT NULL,
+    status     TEXT NOT NULL, -- pending, running, failed
+    attempts   INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    run_at     INTEGER NOT NULL,
+    last_error TEXT,
+    --
+    UNIQUE (id)
+);
+
+CREATE INDEX job_idx_status_run_at ON job(status, run_at);
@@ -0,0 +1 @@
+DROP INDEX events_idx_updated_at;
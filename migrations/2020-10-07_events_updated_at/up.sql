@@ -0,0 +1,4 @@
+-- Track changes of events for incrementally updating the search index
+ALTER TABLE events ADD COLUMN updated_at INTEGER;
+
+CREATE INDEX events_idx_updated_at ON events(updated_at);
@@ -0,0 +1 @@
+DROP TABLE bbox_subscription_tags;
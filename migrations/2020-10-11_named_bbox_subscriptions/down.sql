@@ -0,0 +1 @@
+DROP INDEX bbox_subscriptions_idx_user_id;
@@ -0,0 +1,8 @@
+-- Optional name of a subscription
+ALTER TABLE bbox_subscriptions ADD COLUMN title TEXT;
+-- Secret part of the token for unsubscribing without login
+ALTER TABLE bbox_subscriptions ADD COLUMN unsubscribe_nonce TEXT NOT NULL DEFAULT '';
+
+UPDATE bbox_subscriptions SET unsubscribe_nonce = lower(hex(randomblob(16)));
+
+CREATE INDEX bbox_subscriptions_idx_user_id ON bbox_subscriptions(user_id);
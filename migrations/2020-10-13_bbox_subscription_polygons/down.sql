@@ -0,0 +1 @@
+-- Removing columns from a table is not supported by SQLite
@@ -0,0 +1,2 @@
+-- Removing columns from a table is not supported by SQLite
+DROP TABLE event_recurrence_override;
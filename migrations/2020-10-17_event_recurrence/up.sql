@@ -0,0 +1,17 @@
+-- Optional recurrence rule (RRULE) of events
+ALTER TABLE events ADD COLUMN recurrence TEXT;
+
+-- Cancelled or modified occurrences of recurring events
+CREATE TABLE event_recurrence_override (
+    event_rowid   INTEGER NOT NULL,
+    -- Start of the occurrence according to the rule
+    recurrence_id INTEGER NOT NULL,
+    cancelled     TINYINT NOT NULL,
+    start         INTEGER,
+    end           INTEGER,
+    title         TEXT,
+    description   TEXT,
+    --
+    PRIMARY KEY (event_rowid, recurrence_id),
+    FOREIGN KEY (event_rowid) REFERENCES events(id)
+);
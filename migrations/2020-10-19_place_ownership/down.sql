@@ -0,0 +1,5 @@
+DROP TABLE place_proposal_custom_link;
+DROP TABLE place_proposal_tag;
+DROP TABLE place_proposal;
+DROP TABLE place_locked_field;
+DROP TABLE place_owner;
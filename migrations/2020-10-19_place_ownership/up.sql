@@ -0,0 +1,77 @@
+-- Registered users or organizations that own a place
+CREATE TABLE place_owner (
+    rowid       INTEGER PRIMARY KEY,
+    --
+    place_rowid INTEGER NOT NULL,
+    -- either a user or an organization
+    user_rowid  INTEGER,
+    org_rowid   INTEGER,
+    --
+    FOREIGN KEY (place_rowid) REFERENCES place(rowid),
+    FOREIGN KEY (user_rowid) REFERENCES users(id),
+    FOREIGN KEY (org_rowid) REFERENCES organization(rowid)
+);
+
+CREATE INDEX place_owner_idx_place_rowid ON place_owner(place_rowid);
+
+-- Fields of a place that could only be changed by its owners
+CREATE TABLE place_locked_field (
+    place_rowid INTEGER NOT NULL,
+    field       TEXT NOT NULL,
+    --
+    PRIMARY KEY (place_rowid, field),
+    FOREIGN KEY (place_rowid) REFERENCES place(rowid)
+);
+
+-- Changes of locked fields that have been proposed by others
+CREATE TABLE place_proposal (
+    rowid          INTEGER PRIMARY KEY,
+    --
+    id             TEXT NOT NULL,
+    place_rowid    INTEGER NOT NULL,
+    base_rev       INTEGER NOT NULL,
+    created_at     INTEGER NOT NULL,
+    created_by     INTEGER,
+    --
+    title          TEXT NOT NULL,
+    description    TEXT NOT NULL,
+    lat            FLOAT NOT NULL,
+    lon            FLOAT NOT NULL,
+    street         TEXT,
+    zip            TEXT,
+    city           TEXT,
+    country        TEXT,
+    state          TEXT,
+    contact_name   TEXT,
+    email          TEXT,
+    phone          TEXT,
+    homepage       TEXT,
+    opening_hours  TEXT,
+    founded_on     TEXT,
+    image_url      TEXT,
+    image_link_url TEXT,
+    --
+    UNIQUE (id),
+    FOREIGN KEY (place_rowid) REFERENCES place(rowid),
+    FOREIGN KEY (created_by) REFERENCES users(id)
+);
+
+CREATE TABLE place_proposal_tag (
+    parent_rowid INTEGER NOT NULL,
+    --
+    tag          TEXT NOT NULL,
+    --
+    PRIMARY KEY (parent_rowid, tag),
+    FOREIGN KEY (parent_rowid) REFERENCES place_proposal(rowid)
+);
+
+CREATE TABLE place_proposal_custom_link (
+    parent_rowid INTEGER NOT NULL,
+    --
+    url          TEXT NOT NULL,
+    title        TEXT,
+    description  TEXT,
+    --
+    PRIMARY KEY (parent_rowid, url),
+    FOREIGN KEY (parent_rowid) REFERENCES place_proposal(rowid)
+);
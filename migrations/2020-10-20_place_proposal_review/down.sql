@@ -0,0 +1,2 @@
+DROP INDEX place_proposal_idx_status;
+-- Removing columns from a table is not supported by SQLite
@@ -0,0 +1,8 @@
+-- Proposals are reviewed by scouts, admins or the owners
+-- of a place. All existing proposals are still pending.
+ALTER TABLE place_proposal ADD COLUMN status TEXT NOT NULL DEFAULT 'pending';
+ALTER TABLE place_proposal ADD COLUMN reviewed_at INTEGER;
+ALTER TABLE place_proposal ADD COLUMN reviewed_by INTEGER REFERENCES users(id);
+ALTER TABLE place_proposal ADD COLUMN review_comment TEXT;
+
+CREATE INDEX place_proposal_idx_status ON place_proposal(status);
@@ -0,0 +1,3 @@
+-- Proposals of organizations are reviewed on behalf of
+-- the proposing organization
+ALTER TABLE place_proposal ADD COLUMN org_rowid INTEGER REFERENCES organization(rowid);
@@ -13,7 +13,10 @@ use ofdb_entities as e;
 
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
-use std::convert::{TryFrom, TryInto};
+use std::{
+    collections::HashMap,
+    convert::{TryFrom, TryInto},
+};
 use url::Url;
 
 #[rustfmt::skip]
@@ -37,6 +40,9 @@ pub struct Entry {
     pub telephone      : Option<String>,
     pub homepage       : Option<String>,
     pub opening_hours  : Option<String>,
+    /// The canonical form of valid opening hours
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opening_hours_normalized : Option<String>,
     pub founded_on     : Option<NaiveDate>,
     pub categories     : Vec<String>,
     pub tags           : Vec<String>,
@@ -154,9 +160,18 @@ pub struct Event {
     pub title: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// UTC time stamp in seconds
     pub start: i64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub end: Option<i64>,
+    /// The IANA time zone of the event, e.g. "Europe/Berlin"
+    #[serde(default = "default_time_zone")]
+    pub time_zone: String,
+    /// The local start time in RFC 3339 format, e.g. "2020-10-29T18:00:00+01:00"
+    #[serde(default)]
+    pub start_local: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub end_local: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub lat: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -186,6 +201,38 @@ pub struct Event {
     pub image_url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub image_link_url: Option<String>,
+    /// The distance in meters from the center of a `near` query
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub distance: Option<f64>,
+    /// The recurrence rule (RRULE) of recurring events
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub recurrence: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub recurrence_overrides: Vec<OccurrenceOverride>,
+    /// The original start of a single occurrence of a recurring event
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub recurrence_id: Option<i64>,
+}
+
+fn default_time_zone() -> String {
+    "UTC".to_string()
+}
+
+/// A cancelled or modified occurrence of a recurring event
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct OccurrenceOverride {
+    pub recurrence_id: i64,
+    #[serde(default)]
+    pub cancelled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -283,6 +330,9 @@ pub struct PlaceSearchResult {
     pub categories: Vec<String>,
     pub tags: Vec<String>,
     pub ratings: EntrySearchRatings,
+    /// The distance in meters from the center of a `near` query
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub distance: Option<f64>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -303,6 +353,297 @@ pub enum ReviewStatus {
 pub struct SearchResponse {
     pub visible: Vec<PlaceSearchResult>,
     pub invisible: Vec<PlaceSearchResult>,
+    /// Only if requested
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub facets: Option<SearchFacets>,
+    /// Requests the next page of results, only if a sort
+    /// order or a cursor has been requested
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct SearchFacets {
+    /// The most frequent tags in descending order
+    pub tags: Vec<TagFrequency>,
+    /// The number of places per category id
+    pub categories: HashMap<String, u64>,
+    pub status: Vec<ReviewStatusFrequency>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct ReviewStatusFrequency(pub ReviewStatus, pub u64);
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "extra-derive",
+    derive(Debug, Clone, Copy, PartialEq, Eq, Hash)
+)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchSuggestionKind {
+    Title,
+    Tag,
+    City,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct SearchSuggestion {
+    /// The completed search term
+    pub text: String,
+    pub kind: SearchSuggestionKind,
+    /// The number of matching places
+    pub count: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, Copy, PartialEq, Eq))]
+#[serde(rename_all = "snake_case")]
+pub enum OpeningHoursErrorReason {
+    TooShort,
+    UnexpectedEnd,
+    UnexpectedCharacter,
+    InvalidTime,
+    Unsupported,
+}
+
+impl From<e::opening_hours::OpeningHoursErrorKind> for OpeningHoursErrorReason {
+    fn from(from: e::opening_hours::OpeningHoursErrorKind) -> Self {
+        use e::opening_hours::OpeningHoursErrorKind as K;
+        match from {
+            K::TooShort => Self::TooShort,
+            K::UnexpectedEnd => Self::UnexpectedEnd,
+            K::UnexpectedCharacter => Self::UnexpectedCharacter,
+            K::InvalidTime => Self::InvalidTime,
+            K::Unsupported => Self::Unsupported,
+        }
+    }
+}
+
+/// The response body for rejected opening hours
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct OpeningHoursError {
+    pub message: String,
+    /// The character offset of the error in the trimmed input
+    pub position: usize,
+    pub reason: OpeningHoursErrorReason,
+}
+
+impl From<e::opening_hours::OpeningHoursParseError> for OpeningHoursError {
+    fn from(from: e::opening_hours::OpeningHoursParseError) -> Self {
+        Self {
+            message: format!("Invalid opening hours: {}", from),
+            position: from.position,
+            reason: from.kind.into(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct PlaceCluster {
+    /// The mean position of all places in the cluster
+    pub lat: f64,
+    pub lng: f64,
+    pub count: usize,
+    /// The bounding box of all places in the cluster
+    pub bbox: MapBbox,
+    /// The number of places per category id
+    pub categories: HashMap<String, usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct PlaceImportResult {
+    /// The line number of the record in the imported file
+    pub line: u64,
+    /// The id of the created place, if any
+    pub id: Option<String>,
+    pub error: Option<String>,
+    /// Existing places or preceding records of the same
+    /// file that are likely duplicates
+    pub duplicates: Vec<PlaceSearchResult>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct EventImportResult {
+    /// The UID of the imported VEVENT
+    pub uid: String,
+    /// The id of the created or updated event, if any
+    pub id: Option<String>,
+    /// `true` if a new event has been created and `false`
+    /// if an existing event has been updated
+    pub created: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct NewWebhookSubscription {
+    /// The HTTPS endpoint that receives the payloads
+    pub url: String,
+    /// Only deliver changes within this bounding box
+    pub bbox: Option<MapBbox>,
+    /// Only deliver changes with at least one of these tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct WebhookSubscription {
+    pub id: String,
+    pub url: String,
+    /// The shared secret for verifying the signatures of payloads
+    pub secret: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bbox: Option<MapBbox>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub tags: Vec<String>,
+    pub created_at: i64,
+}
+
+impl From<e::webhook::WebhookSubscription> for WebhookSubscription {
+    fn from(from: e::webhook::WebhookSubscription) -> Self {
+        let e::webhook::WebhookSubscription {
+            id,
+            org_id: _,
+            url,
+            secret,
+            bbox,
+            tags,
+            created_at,
+        } = from;
+        Self {
+            id: id.into(),
+            url: url.into_string(),
+            secret,
+            bbox: bbox.map(Into::into),
+            tags,
+            created_at: created_at.into_inner(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub event: String,
+    pub payload: String,
+    pub created_at: i64,
+    pub attempts: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_attempt_at: Option<i64>,
+    /// The HTTP status code of the last response
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delivered_at: Option<i64>,
+}
+
+impl From<e::webhook::WebhookDelivery> for WebhookDelivery {
+    fn from(from: e::webhook::WebhookDelivery) -> Self {
+        let e::webhook::WebhookDelivery {
+            id,
+            subscription_id: _,
+            event,
+            payload,
+            created_at,
+            attempts,
+            last_attempt_at,
+            last_status,
+            last_error,
+            delivered_at,
+        } = from;
+        Self {
+            id: id.into(),
+            event: event.as_str().to_string(),
+            payload,
+            created_at: created_at.into_inner(),
+            attempts,
+            last_attempt_at: last_attempt_at.map(e::time::TimestampMs::into_inner),
+            last_status,
+            last_error,
+            delivered_at: delivered_at.map(e::time::TimestampMs::into_inner),
+        }
+    }
+}
+
+/// The JSON body that is posted to webhooks.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct WebhookPayload {
+    /// The unique id of the change
+    pub id: String,
+    pub created_at: i64,
+    #[serde(flatten)]
+    pub data: WebhookData,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum WebhookData {
+    PlaceAdded(Entry),
+    PlaceUpdated(Entry),
+    PlaceReviewed(ReviewedPlace),
+    EventCreated(Event),
+    EventUpdated(Event),
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct ReviewedPlace {
+    pub id: String,
+    pub rev: u64,
+    pub status: ReviewStatus,
+}
+
+/// A background job, e.g. for inspecting failed jobs.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct Job {
+    pub id: String,
+    pub kind: String,
+    pub payload: String,
+    pub status: String,
+    pub attempts: u32,
+    pub created_at: i64,
+    pub run_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+impl From<e::job::Job> for Job {
+    fn from(from: e::job::Job) -> Self {
+        let e::job::Job {
+            id,
+            kind,
+            payload,
+            status,
+            attempts,
+            created_at,
+            run_at,
+            last_error,
+        } = from;
+        Self {
+            id: id.into(),
+            kind,
+            payload,
+            status: status.as_str().to_string(),
+            attempts,
+            created_at: created_at.into_inner(),
+            run_at: run_at.into_inner(),
+            last_error,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -322,10 +663,75 @@ pub enum UserRole {
 #[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
 pub struct BboxSubscription {
     pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub title: Option<String>,
     pub south_west_lat: f64,
     pub south_west_lng: f64,
     pub north_east_lat: f64,
     pub north_east_lng: f64,
+    /// The region within the bounding box as WKT
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub polygon: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default = "Default::default")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default = "Default::default")]
+    pub categories: Vec<String>,
+    /// immediate, daily or weekly
+    #[serde(default = "default_subscription_delivery")]
+    pub delivery: String,
+}
+
+fn default_subscription_delivery() -> String {
+    "immediate".into()
+}
+
+impl From<e::subscription::BboxSubscription> for BboxSubscription {
+    fn from(from: e::subscription::BboxSubscription) -> Self {
+        let e::subscription::BboxSubscription {
+            id,
+            title,
+            bbox,
+            polygon,
+            tags,
+            categories,
+            delivery,
+            ..
+        } = from;
+        Self {
+            id: id.into(),
+            title,
+            south_west_lat: bbox.southwest().lat().to_deg(),
+            south_west_lng: bbox.southwest().lng().to_deg(),
+            north_east_lat: bbox.northeast().lat().to_deg(),
+            north_east_lng: bbox.northeast().lng().to_deg(),
+            polygon: polygon.as_ref().map(ToString::to_string),
+            tags,
+            categories: categories.into_iter().map(Into::into).collect(),
+            delivery: delivery.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct NewBboxSubscription {
+    pub title: Option<String>,
+    /// Required unless a polygon is given
+    #[serde(default)]
+    pub bbox: Option<MapBbox>,
+    /// A polygon or multipolygon, either as WKT or as
+    /// serialized GeoJSON geometry. Replaces the bounding box.
+    #[serde(default)]
+    pub polygon: Option<String>,
+    /// Only notify about changes with at least one of these tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Only notify about changes with at least one of these categories
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// immediate, daily or weekly
+    #[serde(default = "default_subscription_delivery")]
+    pub delivery: String,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -342,6 +748,41 @@ pub struct MapPoint {
     pub lng: f64,
 }
 
+impl From<e::geo::MapPoint> for MapPoint {
+    fn from(from: e::geo::MapPoint) -> Self {
+        Self {
+            lat: from.lat().to_deg(),
+            lng: from.lng().to_deg(),
+        }
+    }
+}
+
+impl TryFrom<MapPoint> for e::geo::MapPoint {
+    type Error = e::geo::CoordRangeError;
+
+    fn try_from(from: MapPoint) -> Result<Self, Self::Error> {
+        e::geo::MapPoint::try_from_lat_lng_deg(from.lat, from.lng)
+    }
+}
+
+impl From<e::geo::MapBbox> for MapBbox {
+    fn from(from: e::geo::MapBbox) -> Self {
+        Self {
+            sw: from.southwest().into(),
+            ne: from.northeast().into(),
+        }
+    }
+}
+
+impl TryFrom<MapBbox> for e::geo::MapBbox {
+    type Error = e::geo::CoordRangeError;
+
+    fn try_from(from: MapBbox) -> Result<Self, Self::Error> {
+        let MapBbox { sw, ne } = from;
+        Ok(e::geo::MapBbox::new(sw.try_into()?, ne.try_into()?))
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
 pub struct RequestPasswordReset {
@@ -516,6 +957,9 @@ impl From<RatingValue> for e::rating::RatingValue {
 
 impl From<e::event::Event> for Event {
     fn from(e: e::event::Event) -> Self {
+        let start_local = e.local_start().to_rfc3339();
+        let end_local = e.local_end().map(|end| end.to_rfc3339());
+        let time_zone = e.time_zone.name().to_string();
         let e::event::Event {
             id,
             title,
@@ -529,6 +973,8 @@ impl From<e::event::Event> for Event {
             registration,
             image_url,
             image_link_url,
+            recurrence,
+            recurrence_id,
             ..
         } = e;
 
@@ -570,12 +1016,23 @@ impl From<e::event::Event> for Event {
         let start = start.timestamp();
         let end = end.map(|end| end.timestamp());
 
+        let (recurrence, recurrence_overrides) = match recurrence {
+            Some(e::event::EventRecurrence { rule, overrides }) => (
+                Some(rule.to_string()),
+                overrides.into_iter().map(Into::into).collect(),
+            ),
+            None => (None, vec![]),
+        };
+
         Event {
             id: id.into(),
             title,
             description,
             start,
             end,
+            time_zone,
+            start_local,
+            end_local,
             lat,
             lng,
             street,
@@ -591,6 +1048,31 @@ impl From<e::event::Event> for Event {
             organizer,
             image_url: image_url.map(Url::into_string),
             image_link_url: image_link_url.map(Url::into_string),
+            distance: None,
+            recurrence,
+            recurrence_overrides,
+            recurrence_id: recurrence_id.map(|id| id.timestamp()),
+        }
+    }
+}
+
+impl From<e::event::OccurrenceOverride> for OccurrenceOverride {
+    fn from(from: e::event::OccurrenceOverride) -> Self {
+        let e::event::OccurrenceOverride {
+            recurrence_id,
+            cancelled,
+            start,
+            end,
+            title,
+            description,
+        } = from;
+        Self {
+            recurrence_id: recurrence_id.timestamp(),
+            cancelled,
+            start: start.map(|start| start.timestamp()),
+            end: end.map(|end| end.timestamp()),
+            title,
+            description,
         }
     }
 }
@@ -638,6 +1120,244 @@ impl From<ClearanceForPlace> for e::clearance::ClearanceForPlace {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "extra-derive",
+    derive(Debug, Clone, Copy, PartialEq, Eq, Hash)
+)]
+#[serde(rename_all = "snake_case")]
+pub enum LockableField {
+    Contact,
+    OpeningHours,
+    Links,
+}
+
+impl From<e::ownership::LockableField> for LockableField {
+    fn from(from: e::ownership::LockableField) -> Self {
+        use e::ownership::LockableField as E;
+        match from {
+            E::Contact => Self::Contact,
+            E::OpeningHours => Self::OpeningHours,
+            E::Links => Self::Links,
+        }
+    }
+}
+
+impl From<LockableField> for e::ownership::LockableField {
+    fn from(from: LockableField) -> Self {
+        match from {
+            LockableField::Contact => Self::Contact,
+            LockableField::OpeningHours => Self::OpeningHours,
+            LockableField::Links => Self::Links,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+#[serde(rename_all = "snake_case")]
+pub enum PlaceOwner {
+    /// The e-mail address of a registered user
+    User(String),
+    /// The id of an organization
+    Organization(String),
+}
+
+impl From<e::ownership::PlaceOwner> for PlaceOwner {
+    fn from(from: e::ownership::PlaceOwner) -> Self {
+        use e::ownership::PlaceOwner as E;
+        match from {
+            E::User(email) => Self::User(email.into()),
+            E::Organization(org_id) => Self::Organization(org_id.into()),
+        }
+    }
+}
+
+impl From<PlaceOwner> for e::ownership::PlaceOwner {
+    fn from(from: PlaceOwner) -> Self {
+        match from {
+            PlaceOwner::User(email) => Self::User(email.into()),
+            PlaceOwner::Organization(org_id) => Self::Organization(org_id.into()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct PlaceOwnership {
+    pub owners: Vec<PlaceOwner>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty", default = "Default::default")]
+    pub locked_fields: Vec<LockableField>,
+}
+
+impl From<e::ownership::PlaceOwnership> for PlaceOwnership {
+    fn from(from: e::ownership::PlaceOwnership) -> Self {
+        let e::ownership::PlaceOwnership {
+            owners,
+            locked_fields,
+        } = from;
+        Self {
+            owners: owners.into_iter().map(Into::into).collect(),
+            locked_fields: locked_fields.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<PlaceOwnership> for e::ownership::PlaceOwnership {
+    fn from(from: PlaceOwnership) -> Self {
+        let PlaceOwnership {
+            owners,
+            locked_fields,
+        } = from;
+        Self {
+            owners: owners.into_iter().map(Into::into).collect(),
+            locked_fields: locked_fields.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, Copy, PartialEq, Eq))]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalStatus {
+    Pending,
+    Accepted,
+    Merged,
+    Rejected,
+}
+
+impl From<e::proposal::ProposalStatus> for ProposalStatus {
+    fn from(from: e::proposal::ProposalStatus) -> Self {
+        use e::proposal::ProposalStatus as E;
+        match from {
+            E::Pending => Self::Pending,
+            E::Accepted => Self::Accepted,
+            E::Merged => Self::Merged,
+            E::Rejected => Self::Rejected,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug))]
+pub struct PlaceProposal {
+    pub id: String,
+    pub place: PlaceRoot,
+    pub base_revision: e::revision::RevisionValue,
+    /// The proposed revision
+    pub revision: PlaceRevision,
+    pub status: ProposalStatus,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reviewed: Option<ActivityLog>,
+}
+
+impl From<e::proposal::PlaceProposal> for PlaceProposal {
+    fn from(from: e::proposal::PlaceProposal) -> Self {
+        let e::proposal::PlaceProposal {
+            id,
+            base_revision,
+            place,
+            org_id: _,
+            status,
+            reviewed,
+        } = from;
+        let (place_root, place_revision) = place.into();
+        Self {
+            id: id.into(),
+            place: place_root.into(),
+            base_revision: base_revision.into(),
+            revision: place_revision.into(),
+            status: status.into(),
+            reviewed: reviewed.map(Into::into),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, Copy, PartialEq, Eq))]
+#[serde(rename_all = "snake_case")]
+pub enum PlaceField {
+    Title,
+    Description,
+    Position,
+    Address,
+    Contact,
+    OpeningHours,
+    FoundedOn,
+    Links,
+    Tags,
+}
+
+impl From<e::proposal::PlaceField> for PlaceField {
+    fn from(from: e::proposal::PlaceField) -> Self {
+        use e::proposal::PlaceField as E;
+        match from {
+            E::Title => Self::Title,
+            E::Description => Self::Description,
+            E::Position => Self::Position,
+            E::Address => Self::Address,
+            E::Contact => Self::Contact,
+            E::OpeningHours => Self::OpeningHours,
+            E::FoundedOn => Self::FoundedOn,
+            E::Links => Self::Links,
+            E::Tags => Self::Tags,
+        }
+    }
+}
+
+impl From<PlaceField> for e::proposal::PlaceField {
+    fn from(from: PlaceField) -> Self {
+        match from {
+            PlaceField::Title => Self::Title,
+            PlaceField::Description => Self::Description,
+            PlaceField::Position => Self::Position,
+            PlaceField::Address => Self::Address,
+            PlaceField::Contact => Self::Contact,
+            PlaceField::OpeningHours => Self::OpeningHours,
+            PlaceField::FoundedOn => Self::FoundedOn,
+            PlaceField::Links => Self::Links,
+            PlaceField::Tags => Self::Tags,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, Copy, PartialEq, Eq))]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalDecision {
+    /// Apply all proposed changes
+    Accept,
+    /// Only apply the selected fields onto the current revision
+    Merge,
+    Reject,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct ReviewPlaceProposal {
+    pub decision: ProposalDecision,
+
+    /// The fields that should be merged, all
+    /// changed fields if empty
+    #[serde(skip_serializing_if = "Vec::is_empty", default = "Default::default")]
+    pub fields: Vec<PlaceField>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct RevertPlace {
+    /// The previous revision that should be restored
+    pub revision: e::revision::RevisionValue,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
 #[derive(Serialize, Deserialize)]
 #[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
 pub struct ResultCount {
@@ -0,0 +1,230 @@
+//! Grid-based clustering of positions on a web map.
+
+use crate::tile::project_mercator;
+use ofdb_entities::{geo::*, id::Id};
+use std::collections::{BTreeMap, HashMap};
+
+pub use crate::tile::MAX_ZOOM;
+
+/// The number of grid cells per map tile in each direction,
+/// i.e. a cell covers 64x64 pixels of a 256x256 pixel tile.
+pub const CELLS_PER_TILE: u32 = 4;
+
+/// A cell of the clustering grid at a certain zoom level.
+///
+/// The grid is aligned with the tiles of the Web Mercator
+/// projection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GridCell {
+    pub x: u32,
+    pub y: u32,
+}
+
+impl GridCell {
+    pub fn containing_point(pos: MapPoint, zoom: u8) -> Self {
+        debug_assert!(zoom <= MAX_ZOOM);
+        let num_cells = f64::from(CELLS_PER_TILE) * f64::from(1u32 << zoom);
+        let (x, y) = project_mercator(pos);
+        let to_index = |v: f64| (v * num_cells).floor().max(0.0).min(num_cells - 1.0) as u32;
+        Self {
+            x: to_index(x),
+            y: to_index(y),
+        }
+    }
+}
+
+/// All places within a single grid cell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaceCluster {
+    pub cell: GridCell,
+    /// The mean position of all places
+    pub pos: MapPoint,
+    /// The bounding box of all places
+    pub bbox: MapBbox,
+    pub count: usize,
+    /// The number of places per category
+    pub categories: Vec<(Id, usize)>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct CellAggregate {
+    count: usize,
+    lat_deg_sum: f64,
+    lng_deg_sum: f64,
+    min_lat_deg: f64,
+    min_lng_deg: f64,
+    max_lat_deg: f64,
+    max_lng_deg: f64,
+    categories: BTreeMap<Id, usize>,
+}
+
+impl CellAggregate {
+    fn add_point(&mut self, pos: MapPoint) {
+        let (lat_deg, lng_deg) = pos.to_lat_lng_deg();
+        if self.count == 0 {
+            self.min_lat_deg = lat_deg;
+            self.min_lng_deg = lng_deg;
+            self.max_lat_deg = lat_deg;
+            self.max_lng_deg = lng_deg;
+        } else {
+            self.min_lat_deg = self.min_lat_deg.min(lat_deg);
+            self.min_lng_deg = self.min_lng_deg.min(lng_deg);
+            self.max_lat_deg = self.max_lat_deg.max(lat_deg);
+            self.max_lng_deg = self.max_lng_deg.max(lng_deg);
+        }
+        self.count += 1;
+        self.lat_deg_sum += lat_deg;
+        self.lng_deg_sum += lng_deg;
+    }
+
+    fn merge(&mut self, other: CellAggregate) {
+        if other.count > 0 {
+            if self.count == 0 {
+                self.min_lat_deg = other.min_lat_deg;
+                self.min_lng_deg = other.min_lng_deg;
+                self.max_lat_deg = other.max_lat_deg;
+                self.max_lng_deg = other.max_lng_deg;
+            } else {
+                self.min_lat_deg = self.min_lat_deg.min(other.min_lat_deg);
+                self.min_lng_deg = self.min_lng_deg.min(other.min_lng_deg);
+                self.max_lat_deg = self.max_lat_deg.max(other.max_lat_deg);
+                self.max_lng_deg = self.max_lng_deg.max(other.max_lng_deg);
+            }
+            self.count += other.count;
+            self.lat_deg_sum += other.lat_deg_sum;
+            self.lng_deg_sum += other.lng_deg_sum;
+        }
+        for (category, count) in other.categories {
+            *self.categories.entry(category).or_default() += count;
+        }
+    }
+}
+
+/// Aggregates positions into the cells of the grid at
+/// a certain zoom level.
+#[derive(Debug, Clone)]
+pub struct ClusterGrid {
+    zoom: u8,
+    cells: HashMap<GridCell, CellAggregate>,
+}
+
+impl ClusterGrid {
+    pub fn new(zoom: u8) -> Self {
+        debug_assert!(zoom <= MAX_ZOOM);
+        Self {
+            zoom,
+            cells: Default::default(),
+        }
+    }
+
+    pub const fn zoom(&self) -> u8 {
+        self.zoom
+    }
+
+    pub fn add_point(&mut self, pos: MapPoint) {
+        let cell = GridCell::containing_point(pos, self.zoom);
+        self.cells.entry(cell).or_default().add_point(pos);
+    }
+
+    /// Count a point for the category breakdown of its cell
+    /// without affecting the total count.
+    pub fn add_category_point(&mut self, pos: MapPoint, category: &Id) {
+        let cell = GridCell::containing_point(pos, self.zoom);
+        *self
+            .cells
+            .entry(cell)
+            .or_default()
+            .categories
+            .entry(category.clone())
+            .or_default() += 1;
+    }
+
+    pub fn merge(&mut self, other: ClusterGrid) {
+        debug_assert_eq!(self.zoom, other.zoom);
+        for (cell, aggregate) in other.cells {
+            self.cells.entry(cell).or_default().merge(aggregate);
+        }
+    }
+
+    /// The clusters of all non-empty cells, ordered by
+    /// their position on the grid.
+    pub fn into_clusters(self) -> Vec<PlaceCluster> {
+        let mut clusters: Vec<_> = self
+            .cells
+            .into_iter()
+            .filter(|(_, aggregate)| aggregate.count > 0)
+            .map(|(cell, aggregate)| {
+                let count_f64 = aggregate.count as f64;
+                PlaceCluster {
+                    cell,
+                    pos: MapPoint::from_lat_lng_deg(
+                        aggregate.lat_deg_sum / count_f64,
+                        aggregate.lng_deg_sum / count_f64,
+                    ),
+                    bbox: MapBbox::new(
+                        MapPoint::from_lat_lng_deg(aggregate.min_lat_deg, aggregate.min_lng_deg),
+                        MapPoint::from_lat_lng_deg(aggregate.max_lat_deg, aggregate.max_lng_deg),
+                    ),
+                    count: aggregate.count,
+                    categories: aggregate.categories.into_iter().collect(),
+                }
+            })
+            .collect();
+        clusters.sort_unstable_by_key(|cluster| (cluster.cell.y, cluster.cell.x));
+        clusters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_cells_at_different_zoom_levels() {
+        let stuttgart = MapPoint::from_lat_lng_deg(48.78, 9.18);
+        assert_eq!(
+            GridCell { x: 2, y: 1 },
+            GridCell::containing_point(stuttgart, 0)
+        );
+        // Tile 8/134/88 contains Stuttgart
+        let cell = GridCell::containing_point(stuttgart, 8);
+        assert_eq!(134, cell.x / CELLS_PER_TILE);
+        assert_eq!(88, cell.y / CELLS_PER_TILE);
+        // The poles and the antimeridian are clamped
+        let max_index = CELLS_PER_TILE * 4 - 1;
+        assert_eq!(
+            GridCell { x: max_index, y: 0 },
+            GridCell::containing_point(MapPoint::from_lat_lng_deg(90.0, 180.0), 2)
+        );
+        assert_eq!(
+            GridCell { x: 0, y: max_index },
+            GridCell::containing_point(MapPoint::from_lat_lng_deg(-90.0, -180.0), 2)
+        );
+    }
+
+    #[test]
+    fn cluster_points_with_categories() {
+        let category = Id::from("category");
+        let mut grid = ClusterGrid::new(10);
+        grid.add_point(MapPoint::from_lat_lng_deg(48.0, 9.0));
+        grid.add_point(MapPoint::from_lat_lng_deg(48.01, 9.02));
+        grid.add_category_point(MapPoint::from_lat_lng_deg(48.01, 9.02), &category);
+        let mut other = ClusterGrid::new(10);
+        other.add_point(MapPoint::from_lat_lng_deg(-33.9, 18.4));
+        grid.merge(other);
+
+        let clusters = grid.into_clusters();
+        assert_eq!(2, clusters.len());
+        let cluster = &clusters[0];
+        assert_eq!(2, cluster.count);
+        assert_eq!(vec![(category, 1)], cluster.categories);
+        let (lat, lng) = cluster.pos.to_lat_lng_deg();
+        assert!((lat - 48.005).abs() < 1e-6);
+        assert!((lng - 9.01).abs() < 1e-6);
+        assert!(cluster
+            .bbox
+            .contains_point(MapPoint::from_lat_lng_deg(48.005, 9.01)));
+        assert_eq!(1, clusters[1].count);
+        assert!(clusters[1].categories.is_empty());
+    }
+}
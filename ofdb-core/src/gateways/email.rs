@@ -2,4 +2,17 @@ use ofdb_entities::email::Email;
 
 pub trait EmailGateway {
     fn compose_and_send(&self, recipients: &[Email], subject: &str, body: &str);
+
+    /// Send an e-mail with additional header fields, e.g.
+    /// `List-Unsubscribe`. Gateways that don't support custom
+    /// header fields send the e-mail without them.
+    fn compose_and_send_with_headers(
+        &self,
+        recipients: &[Email],
+        subject: &str,
+        body: &str,
+        _headers: &[(&str, &str)],
+    ) {
+        self.compose_and_send(recipients, subject, body);
+    }
 }
@@ -1,3 +1,4 @@
 pub mod email;
 pub mod geocode;
 pub mod notify;
+pub mod webhook;
@@ -1,17 +1,30 @@
 use ofdb_entities::{
-    category::Category, event::Event, nonce::EmailNonce, place::Place, user::User,
+    category::Category, event::Event, nonce::EmailNonce, place::Place,
+    subscription::BboxSubscription, user::User,
 };
 
 pub trait NotificationGateway {
-    fn place_added(&self, email_addresses: &[String], place: &Place, all_categories: Vec<Category>);
+    fn place_added(
+        &self,
+        subscriptions: &[BboxSubscription],
+        place: &Place,
+        all_categories: Vec<Category>,
+    );
     fn place_updated(
         &self,
-        email_addresses: &[String],
+        subscriptions: &[BboxSubscription],
         place: &Place,
         all_categories: Vec<Category>,
     );
-    fn event_created(&self, email_addresses: &[String], event: &Event);
-    fn event_updated(&self, email_addresses: &[String], event: &Event);
+    fn event_created(&self, subscriptions: &[BboxSubscription], event: &Event);
+    fn event_updated(&self, subscriptions: &[BboxSubscription], event: &Event);
+    /// Send a summary of all changes within a subscription
+    fn subscription_digest(
+        &self,
+        subscription: &BboxSubscription,
+        places: &[Place],
+        events: &[Event],
+    );
     fn user_registered_kvm(&self, user: &User);
     fn user_registered_ofdb(&self, user: &User);
     fn user_registered(&self, user: &User, url: &str);
@@ -0,0 +1,40 @@
+use thiserror::Error;
+use url::Url;
+
+/// A signed request that delivers the JSON payload of a
+/// change to the webhook of an organization.
+#[derive(Debug, Clone)]
+pub struct WebhookRequest<'a> {
+    pub url: &'a Url,
+    /// The shared secret for signing the payload
+    pub secret: &'a str,
+    pub event: &'a str,
+    pub delivery_id: &'a str,
+    pub payload: &'a str,
+}
+
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    #[error("Unexpected response status: {0}")]
+    Status(u16),
+    #[error("Transport failure: {0}")]
+    Transport(String),
+}
+
+impl WebhookError {
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Self::Status(status) => Some(*status),
+            Self::Transport(_) => None,
+        }
+    }
+}
+
+pub trait WebhookGateway {
+    /// Deliver the request and return the status code of the
+    /// successful response.
+    ///
+    /// The request is sent synchronously, i.e. retries need
+    /// to be handled by the caller.
+    fn deliver(&self, request: &WebhookRequest) -> Result<u16, WebhookError>;
+}
@@ -3,8 +3,10 @@
 extern crate test;
 
 pub mod bbox;
+pub mod cluster;
 pub mod gateways;
 pub mod rating;
 pub mod tag;
 pub mod text;
+pub mod tile;
 pub mod user;
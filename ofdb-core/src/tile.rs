@@ -0,0 +1,122 @@
+//! Tiles of the Web Mercator projection as used by web maps.
+
+use ofdb_entities::geo::*;
+use std::f64::consts::PI;
+
+/// The highest supported zoom level.
+pub const MAX_ZOOM: u8 = 22;
+
+// The Web Mercator projection is undefined at the poles
+const MAX_MERCATOR_LAT_DEG: f64 = 85.051_128_78;
+
+/// Project a position onto the unit square of the Web Mercator
+/// projection, i.e. x from west to east and y from north to south.
+///
+/// Latitudes beyond the limits of the projection are clamped.
+pub fn project_mercator(pos: MapPoint) -> (f64, f64) {
+    debug_assert!(pos.is_valid());
+    let (lat_deg, lng_deg) = pos.to_lat_lng_deg();
+    let lat_rad = lat_deg
+        .max(-MAX_MERCATOR_LAT_DEG)
+        .min(MAX_MERCATOR_LAT_DEG)
+        .to_radians();
+    let x = (lng_deg + 180.0) / 360.0;
+    let y = (1.0 - (lat_rad.tan() + 1.0 / lat_rad.cos()).ln() / PI) / 2.0;
+    (x, y)
+}
+
+fn unproject_mercator(x: f64, y: f64) -> MapPoint {
+    let lat_deg = (PI * (1.0 - 2.0 * y)).sinh().atan().to_degrees();
+    let lng_deg = x * 360.0 - 180.0;
+    MapPoint::from_lat_lng_deg(lat_deg, lng_deg)
+}
+
+/// A map tile identified by its zoom level and its
+/// column (x) and row (y) on the grid of all tiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TileId {
+    pub z: u8,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl TileId {
+    /// Returns `None` if the zoom level or the coordinates
+    /// are out of range.
+    pub fn new(z: u8, x: u32, y: u32) -> Option<Self> {
+        if z > MAX_ZOOM {
+            return None;
+        }
+        let num_tiles = Self::num_tiles(z);
+        if x >= num_tiles || y >= num_tiles {
+            return None;
+        }
+        Some(Self { z, x, y })
+    }
+
+    /// The number of tiles in each direction at the given zoom level.
+    pub fn num_tiles(z: u8) -> u32 {
+        debug_assert!(z <= MAX_ZOOM);
+        1 << z
+    }
+
+    pub fn bbox(&self) -> MapBbox {
+        let num_tiles = f64::from(Self::num_tiles(self.z));
+        let west = f64::from(self.x) / num_tiles;
+        let east = f64::from(self.x + 1) / num_tiles;
+        let north = f64::from(self.y) / num_tiles;
+        let south = f64::from(self.y + 1) / num_tiles;
+        MapBbox::new(
+            unproject_mercator(west, south),
+            unproject_mercator(east, north),
+        )
+    }
+
+    /// The pixel coordinates of a position relative to the
+    /// upper left corner of the tile with the given extent.
+    ///
+    /// Positions within the tile are mapped onto the range
+    /// `0..extent` in both directions.
+    pub fn project(&self, pos: MapPoint, extent: u32) -> (i32, i32) {
+        let num_tiles = f64::from(Self::num_tiles(self.z));
+        let (x, y) = project_mercator(pos);
+        let to_pixel = |v: f64, offset: u32| {
+            ((v * num_tiles - f64::from(offset)) * f64::from(extent)).floor() as i32
+        };
+        (to_pixel(x, self.x), to_pixel(y, self.y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_tile_coordinates() {
+        assert!(TileId::new(0, 0, 0).is_some());
+        assert!(TileId::new(0, 1, 0).is_none());
+        assert!(TileId::new(8, 255, 255).is_some());
+        assert!(TileId::new(8, 0, 256).is_none());
+        assert!(TileId::new(MAX_ZOOM + 1, 0, 0).is_none());
+    }
+
+    #[test]
+    fn project_position_onto_tile() {
+        let stuttgart = MapPoint::from_lat_lng_deg(48.78, 9.18);
+        let tile = TileId::new(8, 134, 88).unwrap();
+        assert!(tile.bbox().contains_point(stuttgart));
+        let (x, y) = tile.project(stuttgart, 4096);
+        assert!((0..4096).contains(&x));
+        assert!((0..4096).contains(&y));
+        assert!(!TileId::new(8, 134, 89)
+            .unwrap()
+            .bbox()
+            .contains_point(stuttgart));
+
+        let world = TileId::new(0, 0, 0).unwrap();
+        assert_eq!(
+            (2048, 2048),
+            world.project(MapPoint::from_lat_lng_deg(0.0, 0.0), 4096)
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use crate::id::*;
+use std::fmt;
+
+/// The value by which results are sorted.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortKey {
+    Int(i64),
+    Float(f64),
+    Text(String),
+    /// The match score of a query that depends on the search
+    /// terms and is not comparable with other values
+    Score(f64),
+}
+
+/// Points to the last item of a page.
+///
+/// Items are sorted by their key and items with equal keys
+/// by their id. The next page continues after this position,
+/// even if items have been added or removed in the meantime.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageCursor {
+    pub key: SortKey,
+    pub id: Id,
+}
+
+#[derive(Debug)]
+pub struct PageCursorDecodingError;
+
+impl fmt::Display for PageCursorDecodingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "Invalid cursor")
+    }
+}
+
+// Separates the key from the id that never contains
+// this character
+const ID_SEPARATOR: char = '\0';
+
+impl PageCursor {
+    pub fn encode_to_string(&self) -> String {
+        let key = match self.key {
+            SortKey::Int(i) => format!("i{}", i),
+            // The bits are encoded to preserve the exact value
+            SortKey::Float(f) => format!("f{:x}", f.to_bits()),
+            SortKey::Text(ref s) => format!("s{}", s),
+            SortKey::Score(f) => format!("r{:x}", f.to_bits()),
+        };
+        let concat = format!("{}{}{}", key, ID_SEPARATOR, self.id);
+        bs58::encode(concat).into_string()
+    }
+
+    pub fn decode_from_str(encoded: &str) -> Result<Self, PageCursorDecodingError> {
+        let decoded = bs58::decode(encoded)
+            .into_vec()
+            .map_err(|_| PageCursorDecodingError)?;
+        let concat = String::from_utf8(decoded).map_err(|_| PageCursorDecodingError)?;
+        let mut parts = concat.rsplitn(2, ID_SEPARATOR);
+        let id = parts.next().filter(|id| !id.is_empty());
+        let key = parts.next();
+        let (key, id) = match (key, id) {
+            (Some(key), Some(id)) => (key, id),
+            _ => return Err(PageCursorDecodingError),
+        };
+        let key = if let Some(i) = key.strip_prefix('i') {
+            SortKey::Int(i.parse().map_err(|_| PageCursorDecodingError)?)
+        } else if let Some(f) = key.strip_prefix('f') {
+            SortKey::Float(decode_float(f)?)
+        } else if let Some(s) = key.strip_prefix('s') {
+            SortKey::Text(s.to_owned())
+        } else if let Some(r) = key.strip_prefix('r') {
+            SortKey::Score(decode_float(r)?)
+        } else {
+            return Err(PageCursorDecodingError);
+        };
+        Ok(Self { key, id: id.into() })
+    }
+}
+
+fn decode_float(encoded: &str) -> Result<f64, PageCursorDecodingError> {
+    let bits = u64::from_str_radix(encoded, 16).map_err(|_| PageCursorDecodingError)?;
+    let f = f64::from_bits(bits);
+    if f.is_nan() {
+        return Err(PageCursorDecodingError);
+    }
+    Ok(f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode_page_cursor() {
+        for key in vec![
+            SortKey::Int(-1_603_000_000_000),
+            SortKey::Float(-0.123_456_789),
+            SortKey::Score(1.234_567_8),
+            SortKey::Text("".into()),
+            SortKey::Text("foo bar".into()),
+        ] {
+            let cursor = PageCursor { key, id: Id::new() };
+            let encoded = cursor.encode_to_string();
+            assert_eq!(cursor, PageCursor::decode_from_str(&encoded).unwrap());
+        }
+    }
+
+    #[test]
+    fn decode_invalid_page_cursor() {
+        assert!(PageCursor::decode_from_str("").is_err());
+        assert!(PageCursor::decode_from_str("0OIl").is_err());
+        let without_id = bs58::encode("i42\0").into_string();
+        assert!(PageCursor::decode_from_str(&without_id).is_err());
+        let invalid_key = bs58::encode("x42\0abc").into_string();
+        assert!(PageCursor::decode_from_str(&invalid_key).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{contact::*, id::*, location::*, time::*};
+use crate::{contact::*, id::*, location::*, recurrence::*, time::*};
 use chrono::prelude::*;
 use std::str::FromStr;
 use url::Url;
@@ -31,19 +31,49 @@ pub struct Event {
     pub id           : Id,
     pub title        : String,
     pub description  : Option<String>,
-    // Both start/end time stamps are stored with second precision!
+    // Both start/end time stamps are stored in UTC with second precision!
     pub start        : NaiveDateTime,
     pub end          : Option<NaiveDateTime>,
+    // The local time zone of the event, e.g. for recurrences
+    pub time_zone    : Tz,
     pub location     : Option<Location>,
     pub contact      : Option<Contact>,
     pub tags         : Vec<String>,
     pub homepage     : Option<Url>,
     pub created_by   : Option<String>,
+    // Unknown for events that have been created before
+    // the time of creation has been recorded
+    pub created_at   : Option<TimestampMs>,
     pub registration : Option<RegistrationType>,
     // TODO: Switch archived time stamp to millisecond precision?
     pub archived     : Option<Timestamp>,
     pub image_url     : Option<Url>,
     pub image_link_url: Option<Url>,
+    pub recurrence    : Option<EventRecurrence>,
+    // The original start of a single occurrence
+    // that has been expanded from a recurring event
+    pub recurrence_id : Option<NaiveDateTime>,
+}
+
+#[rustfmt::skip]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventRecurrence {
+    pub rule      : RecurrenceRule,
+    // Cancelled or modified occurrences
+    pub overrides : Vec<OccurrenceOverride>,
+}
+
+/// Deviations of a single occurrence from its recurring event.
+#[rustfmt::skip]
+#[derive(Debug, Clone, PartialEq)]
+pub struct OccurrenceOverride {
+    // The start of the occurrence according to the rule
+    pub recurrence_id : NaiveDateTime,
+    pub cancelled     : bool,
+    pub start         : Option<NaiveDateTime>,
+    pub end           : Option<NaiveDateTime>,
+    pub title         : Option<String>,
+    pub description   : Option<String>,
 }
 
 impl Event {
@@ -68,6 +98,124 @@ impl Event {
         }
     }
 
+    /// Expand a recurring event into its occurrences that start
+    /// within the given bounds (both inclusive), but at most `limit`.
+    ///
+    /// Cancelled occurrences are omitted. Events without a
+    /// recurrence are returned unmodified.
+    pub fn occurrences(
+        &self,
+        start_min: Option<NaiveDateTime>,
+        start_max: Option<NaiveDateTime>,
+        limit: usize,
+    ) -> Vec<Event> {
+        if self.recurrence.is_none() {
+            return vec![self.clone()];
+        }
+        let mut occurrences = Vec::new();
+        for recurrence_id in self.recurrence_ids() {
+            if occurrences.len() >= limit {
+                break;
+            }
+            if start_max.map(|max| recurrence_id > max).unwrap_or(false) {
+                break;
+            }
+            if let Some(occurrence) = self.occurrence(recurrence_id) {
+                if start_min.map(|min| occurrence.start < min).unwrap_or(false)
+                    || start_max.map(|max| occurrence.start > max).unwrap_or(false)
+                {
+                    continue;
+                }
+                occurrences.push(occurrence);
+            }
+        }
+        // Overridden occurrences might have been moved
+        occurrences.sort_by_key(|occurrence| occurrence.start);
+        occurrences
+    }
+
+    /// A single occurrence of a recurring event, i.e.
+    /// `None` if it has been cancelled.
+    pub fn occurrence(&self, recurrence_id: NaiveDateTime) -> Option<Event> {
+        let overridden = self.recurrence.as_ref().and_then(|recurrence| {
+            recurrence
+                .overrides
+                .iter()
+                .find(|o| o.recurrence_id == recurrence_id)
+        });
+        if overridden.map(|o| o.cancelled).unwrap_or(false) {
+            return None;
+        }
+        let start = overridden.and_then(|o| o.start).unwrap_or(recurrence_id);
+        let end = overridden
+            .and_then(|o| o.end)
+            .or_else(|| self.end.map(|end| start + (end - self.start)));
+        let title = overridden
+            .and_then(|o| o.title.clone())
+            .unwrap_or_else(|| self.title.clone());
+        let description = overridden
+            .and_then(|o| o.description.clone())
+            .or_else(|| self.description.clone());
+        Some(Event {
+            start,
+            end,
+            title,
+            description,
+            recurrence_id: Some(recurrence_id),
+            ..self.clone()
+        })
+    }
+
+    /// The start of the last occurrence, i.e. `None` if
+    /// the event recurs infinitely.
+    pub fn last_start(&self) -> Option<NaiveDateTime> {
+        match self.recurrence {
+            Some(ref recurrence) if recurrence.rule.is_finite() => self.recurrence_ids().last(),
+            Some(_) => None,
+            None => Some(self.start),
+        }
+    }
+
+    /// The original start times of all occurrences of a
+    /// recurring event in chronological order.
+    ///
+    /// The rule is applied to the local time of the event, i.e.
+    /// occurrences keep their wall-clock time across daylight
+    /// saving time changes.
+    pub fn recurrence_ids(&self) -> impl Iterator<Item = NaiveDateTime> {
+        let tz = self.time_zone;
+        let local_start = self.local_start().naive_local();
+        self.recurrence
+            .as_ref()
+            .map(|recurrence| {
+                let mut rule = recurrence.rule.clone();
+                // UNTIL is always given in UTC
+                rule.until = rule
+                    .until
+                    .map(|until| tz.from_utc_datetime(&until).naive_local());
+                rule.occurrences(local_start)
+            })
+            .into_iter()
+            .flatten()
+            .map(move |local| local_to_utc(tz, local))
+    }
+
+    /// Check if the given time stamp is the original start
+    /// of an occurrence of a recurring event.
+    pub fn is_recurrence_id(&self, recurrence_id: NaiveDateTime) -> bool {
+        self.recurrence_ids()
+            .take_while(|start| *start <= recurrence_id)
+            .any(|start| start == recurrence_id)
+    }
+
+    pub fn local_start(&self) -> DateTime<Tz> {
+        self.time_zone.from_utc_datetime(&self.start)
+    }
+
+    pub fn local_end(&self) -> Option<DateTime<Tz>> {
+        self.end.map(|end| self.time_zone.from_utc_datetime(&end))
+    }
+
     pub fn is_owned<'a>(&self, moderated_tags: impl IntoIterator<Item = &'a str>) -> bool {
         // Exclusive ownership of events is determined by the associated tags
         moderated_tags
@@ -108,4 +256,117 @@ mod tests {
         assert!(RegistrationType::from_str("foo").is_err());
         assert!(RegistrationType::from_str("").is_err());
     }
+
+    fn dt(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn expand_occurrences_with_overrides() {
+        let event = Event {
+            id: Id::new(),
+            title: "Repair Café".into(),
+            description: None,
+            start: dt("2020-10-01 18:00"),
+            end: Some(dt("2020-10-01 20:00")),
+            time_zone: Tz::UTC,
+            location: None,
+            contact: None,
+            tags: vec![],
+            homepage: None,
+            created_by: None,
+            created_at: None,
+            registration: None,
+            archived: None,
+            image_url: None,
+            image_link_url: None,
+            recurrence: Some(EventRecurrence {
+                rule: "FREQ=WEEKLY".parse().unwrap(),
+                overrides: vec![
+                    OccurrenceOverride {
+                        recurrence_id: dt("2020-10-08 18:00"),
+                        cancelled: true,
+                        start: None,
+                        end: None,
+                        title: None,
+                        description: None,
+                    },
+                    OccurrenceOverride {
+                        recurrence_id: dt("2020-10-15 18:00"),
+                        cancelled: false,
+                        start: Some(dt("2020-10-16 17:00")),
+                        end: None,
+                        title: Some("Repair Café (Friday)".into()),
+                        description: None,
+                    },
+                ],
+            }),
+            recurrence_id: None,
+        };
+        let occurrences = event.occurrences(
+            Some(dt("2020-10-05 00:00")),
+            Some(dt("2020-10-31 00:00")),
+            100,
+        );
+        assert_eq!(
+            vec![
+                dt("2020-10-16 17:00"),
+                dt("2020-10-22 18:00"),
+                dt("2020-10-29 18:00")
+            ],
+            occurrences.iter().map(|e| e.start).collect::<Vec<_>>()
+        );
+        assert_eq!(Some(dt("2020-10-16 19:00")), occurrences[0].end);
+        assert_eq!("Repair Café (Friday)", occurrences[0].title);
+        assert_eq!(Some(dt("2020-10-15 18:00")), occurrences[0].recurrence_id);
+        assert_eq!("Repair Café", occurrences[1].title);
+        assert_eq!(2, event.occurrences(None, None, 2).len());
+        assert_eq!(None, event.last_start());
+    }
+
+    #[test]
+    fn expand_occurrences_across_dst_change() {
+        let event = Event {
+            id: Id::new(),
+            title: "Repair Café".into(),
+            description: None,
+            // 18:00 CEST
+            start: dt("2020-10-15 16:00"),
+            end: Some(dt("2020-10-15 18:00")),
+            time_zone: chrono_tz::Europe::Berlin,
+            location: None,
+            contact: None,
+            tags: vec![],
+            homepage: None,
+            created_by: None,
+            created_at: None,
+            registration: None,
+            archived: None,
+            image_url: None,
+            image_link_url: None,
+            recurrence: Some(EventRecurrence {
+                rule: "FREQ=WEEKLY;COUNT=3".parse().unwrap(),
+                overrides: vec![],
+            }),
+            recurrence_id: None,
+        };
+        let occurrences = event.occurrences(None, None, 100);
+        assert_eq!(
+            vec![
+                dt("2020-10-15 16:00"),
+                dt("2020-10-22 16:00"),
+                // 18:00 CET
+                dt("2020-10-29 17:00")
+            ],
+            occurrences.iter().map(|e| e.start).collect::<Vec<_>>()
+        );
+        assert_eq!(Some(dt("2020-10-29 19:00")), occurrences[2].end);
+        assert_eq!(
+            "2020-10-29T18:00:00+01:00",
+            occurrences[2].local_start().to_rfc3339()
+        );
+        assert_eq!(Some(dt("2020-10-29 17:00")), event.last_start());
+        assert!(event.is_recurrence_id(dt("2020-10-29 17:00")));
+        assert!(!event.is_recurrence_id(dt("2020-10-29 16:00")));
+    }
 }
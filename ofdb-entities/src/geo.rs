@@ -593,11 +593,466 @@ pub enum MapBboxInputError {
     Format(String),
 }
 
+/// A circular area on the surface of the earth
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MapCircle {
+    center: MapPoint,
+    radius: Distance,
+}
+
+impl MapCircle {
+    pub const fn new(center: MapPoint, radius: Distance) -> Self {
+        Self { center, radius }
+    }
+
+    pub const fn center(&self) -> MapPoint {
+        self.center
+    }
+
+    pub const fn radius(&self) -> Distance {
+        self.radius
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.center.is_valid() && self.radius.is_valid() && self.radius.to_meters().is_finite()
+    }
+
+    /// The great-circle distance of the point from the center
+    /// if it is located within the circle.
+    pub fn distance_within(&self, pt: MapPoint) -> Option<Distance> {
+        MapPoint::distance(self.center, pt).filter(|distance| *distance <= self.radius)
+    }
+
+    pub fn contains_point(&self, pt: MapPoint) -> bool {
+        self.distance_within(pt).is_some()
+    }
+
+    /// The smallest bounding box that contains the whole circle.
+    pub fn bounding_bbox(&self) -> MapBbox {
+        debug_assert!(self.is_valid());
+        let (lat_deg, lng_deg) = self.center.to_lat_lng_deg();
+        let angular_radius =
+            self.radius.to_meters() / wgs84_earth_radius(self.center.lat()).to_meters();
+        let sw_lat_deg = lat_deg - angular_radius.to_degrees();
+        let ne_lat_deg = lat_deg + angular_radius.to_degrees();
+        if sw_lat_deg <= LatCoord::min().to_deg() || ne_lat_deg >= LatCoord::max().to_deg() {
+            // One of the poles is covered
+            return MapBbox::new(
+                MapPoint::from_lat_lng_deg(
+                    sw_lat_deg.max(LatCoord::min().to_deg()),
+                    LngCoord::min().to_deg(),
+                ),
+                MapPoint::from_lat_lng_deg(
+                    ne_lat_deg.min(LatCoord::max().to_deg()),
+                    LngCoord::max().to_deg(),
+                ),
+            );
+        }
+        let lng_delta_sin = angular_radius.sin() / self.center.lat().to_rad().cos();
+        if angular_radius >= std::f64::consts::FRAC_PI_2 || lng_delta_sin >= 1.0 {
+            return MapBbox::new(
+                MapPoint::from_lat_lng_deg(sw_lat_deg, LngCoord::min().to_deg()),
+                MapPoint::from_lat_lng_deg(ne_lat_deg, LngCoord::max().to_deg()),
+            );
+        }
+        let lng_delta_deg = lng_delta_sin.asin().to_degrees();
+        let lng_range_deg = LngCoord::max().to_deg() - LngCoord::min().to_deg();
+        let mut sw_lng_deg = lng_deg - lng_delta_deg;
+        if sw_lng_deg < LngCoord::min().to_deg() {
+            // wrap around
+            sw_lng_deg += lng_range_deg;
+        }
+        let mut ne_lng_deg = lng_deg + lng_delta_deg;
+        if ne_lng_deg > LngCoord::max().to_deg() {
+            // wrap around
+            ne_lng_deg -= lng_range_deg;
+        }
+        MapBbox::new(
+            MapPoint::from_lat_lng_deg(sw_lat_deg, sw_lng_deg),
+            MapPoint::from_lat_lng_deg(ne_lat_deg, ne_lng_deg),
+        )
+    }
+}
+
+/// A polygon on a (flat) map, consisting of an exterior ring
+/// and optional interior rings (holes).
+///
+/// Rings are implicitly closed, i.e. the first point is not
+/// repeated at the end. Edges that cross the antimeridian are
+/// not supported.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MapPolygon {
+    exterior: Vec<MapPoint>,
+    interiors: Vec<Vec<MapPoint>>,
+}
+
+// Check if the point is inside of the ring by counting how
+// often a ray in eastern direction crosses its edges.
+fn ring_contains_point(ring: &[MapPoint], pt: MapPoint) -> bool {
+    let (y, x) = pt.to_lat_lng_deg();
+    let mut inside = false;
+    for (i, p1) in ring.iter().enumerate() {
+        let p2 = ring[(i + 1) % ring.len()];
+        let (y1, x1) = p1.to_lat_lng_deg();
+        let (y2, x2) = p2.to_lat_lng_deg();
+        if (y1 > y) != (y2 > y) && x < (x2 - x1) * (y - y1) / (y2 - y1) + x1 {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn is_valid_ring(ring: &[MapPoint]) -> bool {
+    ring.len() >= 3 && ring.iter().all(|pt| pt.is_valid())
+}
+
+impl MapPolygon {
+    pub fn new(exterior: Vec<MapPoint>, interiors: Vec<Vec<MapPoint>>) -> Self {
+        Self {
+            exterior: open_ring(exterior),
+            interiors: interiors.into_iter().map(open_ring).collect(),
+        }
+    }
+
+    pub fn exterior(&self) -> &[MapPoint] {
+        &self.exterior
+    }
+
+    pub fn interiors(&self) -> &[Vec<MapPoint>] {
+        &self.interiors
+    }
+
+    pub fn is_valid(&self) -> bool {
+        is_valid_ring(&self.exterior) && self.interiors.iter().all(|ring| is_valid_ring(ring))
+    }
+
+    pub fn contains_point(&self, pt: MapPoint) -> bool {
+        debug_assert!(self.is_valid());
+        debug_assert!(pt.is_valid());
+        ring_contains_point(&self.exterior, pt)
+            && !self
+                .interiors
+                .iter()
+                .any(|ring| ring_contains_point(ring, pt))
+    }
+
+    /// The smallest bounding box that contains the whole polygon.
+    pub fn bounding_bbox(&self) -> MapBbox {
+        debug_assert!(self.is_valid());
+        bounding_bbox_of_points(self.exterior.iter().copied())
+    }
+}
+
+// Remove the closing point of a ring
+fn open_ring(mut ring: Vec<MapPoint>) -> Vec<MapPoint> {
+    if ring.len() > 1 && ring.first() == ring.last() {
+        ring.pop();
+    }
+    ring
+}
+
+fn bounding_bbox_of_points(points: impl Iterator<Item = MapPoint>) -> MapBbox {
+    let mut bounds: Option<(f64, f64, f64, f64)> = None;
+    for pt in points {
+        let (lat, lng) = pt.to_lat_lng_deg();
+        bounds = Some(match bounds {
+            None => (lat, lng, lat, lng),
+            Some((min_lat, min_lng, max_lat, max_lng)) => (
+                min_lat.min(lat),
+                min_lng.min(lng),
+                max_lat.max(lat),
+                max_lng.max(lng),
+            ),
+        });
+    }
+    let (min_lat, min_lng, max_lat, max_lng) = bounds.unwrap_or_default();
+    MapBbox::new(
+        MapPoint::from_lat_lng_deg(min_lat, min_lng),
+        MapPoint::from_lat_lng_deg(max_lat, max_lng),
+    )
+}
+
+/// A region on a (flat) map that consists of one
+/// or more polygons, e.g. an administrative boundary.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MapMultiPolygon {
+    polygons: Vec<MapPolygon>,
+}
+
+impl MapMultiPolygon {
+    pub fn new(polygons: Vec<MapPolygon>) -> Self {
+        Self { polygons }
+    }
+
+    pub fn polygons(&self) -> &[MapPolygon] {
+        &self.polygons
+    }
+
+    pub fn is_valid(&self) -> bool {
+        !self.polygons.is_empty() && self.polygons.iter().all(MapPolygon::is_valid)
+    }
+
+    pub fn contains_point(&self, pt: MapPoint) -> bool {
+        self.polygons
+            .iter()
+            .any(|polygon| polygon.contains_point(pt))
+    }
+
+    /// The smallest bounding box that contains all polygons.
+    pub fn bounding_bbox(&self) -> MapBbox {
+        debug_assert!(self.is_valid());
+        bounding_bbox_of_points(
+            self.polygons
+                .iter()
+                .flat_map(|polygon| polygon.exterior.iter().copied()),
+        )
+    }
+}
+
+impl From<MapPolygon> for MapMultiPolygon {
+    fn from(from: MapPolygon) -> Self {
+        Self::new(vec![from])
+    }
+}
+
+fn fmt_wkt_ring(f: &mut std::fmt::Formatter, ring: &[MapPoint]) -> Result<(), std::fmt::Error> {
+    f.write_str("(")?;
+    // WKT requires closed rings
+    for (i, pt) in ring.iter().chain(ring.first()).enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{} {}", pt.lng(), pt.lat())?;
+    }
+    f.write_str(")")
+}
+
+fn fmt_wkt_polygon(
+    f: &mut std::fmt::Formatter,
+    polygon: &MapPolygon,
+) -> Result<(), std::fmt::Error> {
+    f.write_str("(")?;
+    fmt_wkt_ring(f, &polygon.exterior)?;
+    for ring in &polygon.interiors {
+        f.write_str(", ")?;
+        fmt_wkt_ring(f, ring)?;
+    }
+    f.write_str(")")
+}
+
+/// Formatted as Well-Known Text (WKT)
+impl std::fmt::Display for MapPolygon {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        f.write_str("POLYGON ")?;
+        fmt_wkt_polygon(f, self)
+    }
+}
+
+/// Formatted as Well-Known Text (WKT)
+impl std::fmt::Display for MapMultiPolygon {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        f.write_str("MULTIPOLYGON (")?;
+        for (i, polygon) in self.polygons.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            fmt_wkt_polygon(f, polygon)?;
+        }
+        f.write_str(")")
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MapPolygonInputError {
+    #[error("point: {0}")]
+    Point(MapPointInputError),
+
+    #[error("invalid polygon")]
+    Polygon,
+
+    #[error("invalid format: '{0}'")]
+    Format(String),
+}
+
+// Split the contents of the outermost parentheses
+// at the top-level commas.
+fn split_wkt_list(s: &str) -> Result<Vec<&str>, MapPolygonInputError> {
+    let format_err = || MapPolygonInputError::Format(s.to_string());
+    let s = s.trim();
+    if !s.starts_with('(') || !s.ends_with(')') {
+        return Err(format_err());
+    }
+    let inner = &s[1..s.len() - 1];
+    let mut items = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Err(format_err()),
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(inner[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return Err(format_err());
+    }
+    items.push(inner[start..].trim());
+    Ok(items)
+}
+
+fn parse_wkt_ring(s: &str) -> Result<Vec<MapPoint>, MapPolygonInputError> {
+    split_wkt_list(s)?
+        .into_iter()
+        .map(|pt| {
+            if let Some((lng_deg_str, lat_deg_str)) = pt.split_whitespace().collect_tuple() {
+                MapPoint::parse_lat_lng_deg(lat_deg_str, lng_deg_str)
+                    .map_err(MapPolygonInputError::Point)
+            } else {
+                Err(MapPolygonInputError::Format(pt.to_string()))
+            }
+        })
+        .collect()
+}
+
+fn parse_wkt_polygon(s: &str) -> Result<MapPolygon, MapPolygonInputError> {
+    let mut rings = split_wkt_list(s)?
+        .into_iter()
+        .map(parse_wkt_ring)
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter();
+    let exterior = rings.next().ok_or(MapPolygonInputError::Polygon)?;
+    let polygon = MapPolygon::new(exterior, rings.collect());
+    if !polygon.is_valid() {
+        return Err(MapPolygonInputError::Polygon);
+    }
+    Ok(polygon)
+}
+
+/// Parsed from Well-Known Text (WKT), either a `POLYGON`
+/// or a `MULTIPOLYGON`
+impl std::str::FromStr for MapMultiPolygon {
+    type Err = MapPolygonInputError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (tag, body) = s
+            .find('(')
+            .map(|i| s.split_at(i))
+            .ok_or_else(|| MapPolygonInputError::Format(s.to_string()))?;
+        match tag.trim().to_uppercase().as_str() {
+            "POLYGON" => Ok(parse_wkt_polygon(body)?.into()),
+            "MULTIPOLYGON" => Ok(Self::new(
+                split_wkt_list(body)?
+                    .into_iter()
+                    .map(parse_wkt_polygon)
+                    .collect::<Result<_, _>>()?,
+            )),
+            _ => Err(MapPolygonInputError::Format(s.to_string())),
+        }
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::unreadable_literal, clippy::float_cmp)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn circle_bounding_bbox() {
+        let center = MapPoint::from_lat_lng_deg(48.5, 9.0);
+        let circle = MapCircle::new(center, Distance::from_meters(10_000.0));
+        let bbox = circle.bounding_bbox();
+        assert!(bbox.is_valid());
+        assert!(bbox.contains_point(center));
+        // Points close to the circle in all four directions
+        for (lat, lng) in &[(0.089, 0.0), (-0.089, 0.0), (0.0, 0.134), (0.0, -0.134)] {
+            let pt = MapPoint::from_lat_lng_deg(48.5 + lat, 9.0 + lng);
+            assert!(circle.contains_point(pt));
+            assert!(bbox.contains_point(pt));
+        }
+        assert!(!bbox.contains_point(MapPoint::from_lat_lng_deg(48.6, 9.0)));
+        assert!(!bbox.contains_point(MapPoint::from_lat_lng_deg(48.5, 9.15)));
+
+        // Wrap around
+        let circle = MapCircle::new(
+            MapPoint::from_lat_lng_deg(0.0, 179.99),
+            Distance::from_meters(10_000.0),
+        );
+        let bbox = circle.bounding_bbox();
+        assert!(bbox.is_valid());
+        assert!(bbox.contains_point(MapPoint::from_lat_lng_deg(0.0, -179.99)));
+        assert!(!bbox.contains_point(MapPoint::from_lat_lng_deg(0.0, 179.0)));
+
+        // Poles
+        let circle = MapCircle::new(
+            MapPoint::from_lat_lng_deg(89.99, 0.0),
+            Distance::from_meters(10_000.0),
+        );
+        let bbox = circle.bounding_bbox();
+        assert!(bbox.is_valid());
+        assert!(bbox.contains_point(MapPoint::from_lat_lng_deg(89.99, 180.0)));
+    }
+
+    #[test]
+    fn circle_contains_point() {
+        let circle = MapCircle::new(
+            MapPoint::from_lat_lng_deg(48.5, 9.0),
+            Distance::from_meters(1_000.0),
+        );
+        assert!(circle.contains_point(MapPoint::from_lat_lng_deg(48.505, 9.0)));
+        assert!(!circle.contains_point(MapPoint::from_lat_lng_deg(48.51, 9.0)));
+        let distance = circle
+            .distance_within(MapPoint::from_lat_lng_deg(48.505, 9.0))
+            .unwrap();
+        assert!(distance.to_meters() > 550.0);
+        assert!(distance.to_meters() < 560.0);
+    }
+
+    #[test]
+    fn polygon_with_hole_contains_point() {
+        let polygon: MapMultiPolygon = "POLYGON ((9 48, 10 48, 10 49, 9 49, 9 48), \
+            (9.4 48.4, 9.6 48.4, 9.6 48.6, 9.4 48.6, 9.4 48.4))"
+            .parse()
+            .unwrap();
+        assert!(polygon.is_valid());
+        assert!(polygon.contains_point(MapPoint::from_lat_lng_deg(48.2, 9.2)));
+        assert!(!polygon.contains_point(MapPoint::from_lat_lng_deg(48.5, 9.5)));
+        assert!(!polygon.contains_point(MapPoint::from_lat_lng_deg(48.5, 10.5)));
+        assert_eq!(
+            MapBbox::new(
+                MapPoint::from_lat_lng_deg(48.0, 9.0),
+                MapPoint::from_lat_lng_deg(49.0, 10.0)
+            ),
+            polygon.bounding_bbox()
+        );
+    }
+
+    #[test]
+    fn multi_polygon_wkt_round_trip() {
+        let wkt = "MULTIPOLYGON (((9 48, 10 48, 9.5 49, 9 48)), ((-1 -1, 1 -1, 1 1, -1 1, -1 -1)))";
+        let region: MapMultiPolygon = wkt.parse().unwrap();
+        assert_eq!(2, region.polygons().len());
+        assert_eq!(3, region.polygons()[0].exterior().len());
+        assert!(region.contains_point(MapPoint::from_lat_lng_deg(48.5, 9.5)));
+        assert!(region.contains_point(MapPoint::from_lat_lng_deg(0.0, 0.0)));
+        assert!(!region.contains_point(MapPoint::from_lat_lng_deg(10.0, 9.5)));
+        assert!(region.to_string().starts_with("MULTIPOLYGON ((("));
+        assert_eq!(region, region.to_string().parse().unwrap());
+        assert!("POINT (1 2)".parse::<MapMultiPolygon>().is_err());
+        assert!("POLYGON ((1 2, 3 4))".parse::<MapMultiPolygon>().is_err());
+        assert!("POLYGON ((1 2, 3 4, 5 6)"
+            .parse::<MapMultiPolygon>()
+            .is_err());
+        assert!("POLYGON ((1 200, 3 4, 5 6))"
+            .parse::<MapMultiPolygon>()
+            .is_err());
+    }
+
     #[test]
     fn latitude() {
         assert!(!LatCoord::default().is_valid());
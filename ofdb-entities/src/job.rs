@@ -0,0 +1,81 @@
+use crate::{id::*, time::*};
+
+use std::{fmt, str::FromStr};
+
+/// The processing state of a background job.
+///
+/// Successfully finished jobs are removed from the queue and
+/// don't need a separate state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    /// Waiting until it is due
+    Pending,
+    /// Currently executed by a worker
+    Running,
+    /// Gave up after the maximum number of attempts
+    Failed,
+}
+
+impl JobStatus {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Running => "running",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+impl fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug)]
+pub struct JobStatusParseError;
+
+impl FromStr for JobStatus {
+    type Err = JobStatusParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let status = match s {
+            "pending" => Self::Pending,
+            "running" => Self::Running,
+            "failed" => Self::Failed,
+            _ => return Err(JobStatusParseError),
+        };
+        Ok(status)
+    }
+}
+
+/// A persistent job that is executed in the background.
+///
+/// The `payload` contains the serialized task and its `kind`
+/// is stored separately for inspecting the queue.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Job {
+    pub id: Id,
+    pub kind: String,
+    pub payload: String,
+    pub status: JobStatus,
+    /// Number of failed attempts
+    pub attempts: u32,
+    pub created_at: TimestampMs,
+    /// The job is not executed before this point in time
+    pub run_at: TimestampMs,
+    pub last_error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn job_status_round_trip() {
+        for status in &[JobStatus::Pending, JobStatus::Running, JobStatus::Failed] {
+            assert_eq!(*status, status.as_str().parse::<JobStatus>().unwrap());
+        }
+        assert!("done".parse::<JobStatus>().is_err());
+    }
+}
@@ -15,23 +15,30 @@ pub mod category;
 pub mod clearance;
 pub mod comment;
 pub mod contact;
+pub mod cursor;
 pub mod email;
 pub mod event;
 pub mod geo;
 pub mod id;
+pub mod job;
 pub mod links;
 pub mod location;
 pub mod nonce;
+pub mod opening_hours;
 pub mod organization;
+pub mod ownership;
 pub mod password;
 pub mod place;
+pub mod proposal;
 pub mod rating;
+pub mod recurrence;
 pub mod review;
 pub mod revision;
 pub mod subscription;
 pub mod tag;
 pub mod time;
 pub mod user;
+pub mod webhook;
 
 #[cfg(any(test, feature = "builders"))]
 pub mod builders;
@@ -1,4 +1,4 @@
-use crate::time::*;
+use crate::{id::*, time::*};
 use std::{fmt, ops::Deref, str::FromStr};
 use uuid::Uuid;
 
@@ -82,35 +82,69 @@ pub enum EmailNonceDecodingError {
     Parse(NonceString, NonceParseError),
 }
 
+fn encode_with_nonce(prefix: &str, nonce: Nonce) -> String {
+    let nonce = nonce.to_string();
+    debug_assert_eq!(Nonce::STR_LEN, nonce.len());
+    let mut concat = String::with_capacity(prefix.len() + nonce.len());
+    concat += prefix;
+    concat += &nonce;
+    bs58::encode(concat).into_string()
+}
+
+fn decode_with_nonce(encoded: &str) -> Result<(String, Nonce), EmailNonceDecodingError> {
+    let decoded = bs58::decode(encoded)
+        .into_vec()
+        .map_err(EmailNonceDecodingError::Bs58)?;
+    let mut concat = String::from_utf8(decoded).map_err(EmailNonceDecodingError::Utf8)?;
+    if concat.len() < Nonce::STR_LEN {
+        return Err(EmailNonceDecodingError::TooShort(concat.len()));
+    }
+    let prefix_len = concat.len() - Nonce::STR_LEN;
+    let nonce_slice: &str = &concat[prefix_len..];
+    let nonce = nonce_slice
+        .parse::<Nonce>()
+        .map_err(|err| EmailNonceDecodingError::Parse(nonce_slice.into(), err))?;
+    concat.truncate(prefix_len);
+    Ok((concat, nonce))
+}
+
 impl EmailNonce {
     pub fn encode_to_string(&self) -> String {
-        let nonce = self.nonce.to_string();
-        debug_assert_eq!(Nonce::STR_LEN, nonce.len());
-        let mut concat = String::with_capacity(self.email.len() + nonce.len());
-        concat += &self.email;
-        concat += &nonce;
-        bs58::encode(concat).into_string()
+        encode_with_nonce(&self.email, self.nonce)
     }
 
     pub fn decode_from_str(encoded: &str) -> Result<EmailNonce, EmailNonceDecodingError> {
-        let decoded = bs58::decode(encoded)
-            .into_vec()
-            .map_err(EmailNonceDecodingError::Bs58)?;
-        let mut concat = String::from_utf8(decoded).map_err(EmailNonceDecodingError::Utf8)?;
-        if concat.len() < Nonce::STR_LEN {
-            return Err(EmailNonceDecodingError::TooShort(concat.len()));
-        }
-        let email_len = concat.len() - Nonce::STR_LEN;
-        let nonce_slice: &str = &concat[email_len..];
-        let nonce = nonce_slice
-            .parse::<Nonce>()
-            .map_err(|err| EmailNonceDecodingError::Parse(nonce_slice.into(), err))?;
-        concat.truncate(email_len);
-        let email = concat;
+        let (email, nonce) = decode_with_nonce(encoded)?;
         Ok(Self { email, nonce })
     }
 }
 
+/// Authorizes the holder to cancel a single subscription
+/// without logging in, e.g. by following the link in an
+/// e-mail.
+///
+/// The nonce is stored together with the subscription and
+/// must match for the token to be valid.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SubscriptionNonce {
+    pub subscription_id: Id,
+    pub nonce: Nonce,
+}
+
+impl SubscriptionNonce {
+    pub fn encode_to_string(&self) -> String {
+        encode_with_nonce(self.subscription_id.as_str(), self.nonce)
+    }
+
+    pub fn decode_from_str(encoded: &str) -> Result<SubscriptionNonce, EmailNonceDecodingError> {
+        let (subscription_id, nonce) = decode_with_nonce(encoded)?;
+        Ok(Self {
+            subscription_id: subscription_id.into(),
+            nonce,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct UserToken {
     pub email_nonce: EmailNonce,
@@ -144,6 +178,18 @@ mod tests {
         assert_eq!(example, decoded);
     }
 
+    #[test]
+    fn encode_decode_subscription_nonce() {
+        let example = SubscriptionNonce {
+            subscription_id: Id::new(),
+            nonce: Nonce::new(),
+        };
+        let encoded = example.encode_to_string();
+        let decoded = SubscriptionNonce::decode_from_str(&encoded).unwrap();
+        assert_eq!(example, decoded);
+        assert!(SubscriptionNonce::decode_from_str("").is_err());
+    }
+
     #[test]
     fn decode_empty_email_nonce() {
         assert!(EmailNonce::decode_from_str("").is_err());
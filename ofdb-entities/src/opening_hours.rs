@@ -0,0 +1,942 @@
+//! Opening hours in the [OpenStreetMap format](https://wiki.openstreetmap.org/wiki/Key:opening_hours/specification).
+//!
+//! Only a subset of the specification is supported:
+//! `24/7`, months, weekdays, public holidays (`PH`), time spans,
+//! rule modifiers (`open`, `off`, `closed`, `unknown`), comments
+//! and the rule separators `;`, `,` and `||`.
+//!
+//! Rules that use other parts of the specification, e.g.
+//! `sunrise-sunset`, school holidays (`SH`), weeks or days of
+//! the month, are skipped when validating opening hours. Values
+//! with such rules are stored as is, but they cannot be evaluated.
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
+use std::{fmt, str::FromStr};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct OpeningHours(String);
+
+impl OpeningHours {
+    pub const fn min_len() -> usize {
+        4
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Parse the rules of the opening hours.
+    ///
+    /// Fails for values that use unsupported syntax and for
+    /// values that have been stored before the syntax has
+    /// been validated.
+    pub fn rules(&self) -> Result<OpeningHoursRules, OpeningHoursParseError> {
+        self.0.parse()
+    }
+
+    /// The canonical form of the opening hours, if valid.
+    pub fn normalized(&self) -> Option<String> {
+        self.rules().ok().map(|rules| rules.to_string())
+    }
+}
+
+impl FromStr for OpeningHours {
+    type Err = OpeningHoursParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        if trimmed.len() < Self::min_len() {
+            return Err(OpeningHoursParseError {
+                position: 0,
+                kind: OpeningHoursErrorKind::TooShort,
+            });
+        }
+        Parser::new(trimmed).validate_rules()?;
+        Ok(Self(trimmed.to_string()))
+    }
+}
+
+// Stored values are not validated again, because
+// they might predate the validation of the syntax.
+impl From<String> for OpeningHours {
+    fn from(from: String) -> Self {
+        Self(from)
+    }
+}
+
+impl From<OpeningHours> for String {
+    fn from(from: OpeningHours) -> Self {
+        from.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum OpeningHoursErrorKind {
+    #[error("too short")]
+    TooShort,
+    #[error("unexpected end")]
+    UnexpectedEnd,
+    #[error("unexpected character")]
+    UnexpectedCharacter,
+    #[error("invalid time")]
+    InvalidTime,
+    #[error("unsupported syntax")]
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("{kind} at position {position}")]
+pub struct OpeningHoursParseError {
+    /// Character offset into the (trimmed) input
+    pub position: usize,
+    pub kind: OpeningHoursErrorKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpeningState {
+    Open,
+    Closed,
+    Unknown,
+}
+
+/// The parsed rules of opening hours.
+///
+/// The [`Display`](fmt::Display) implementation
+/// renders the canonical form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpeningHoursRules(Vec<Rule>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RuleSeparator {
+    // Overrides all previous rules for the matching days
+    Normal,
+    // Adds to the previous rules
+    Additional,
+    // Only applies if no previous rule applies
+    Fallback,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RuleModifier {
+    Open,
+    Closed,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WeekdaySelector {
+    // Zero-based and inclusive, Monday first
+    Range(u32, u32),
+    // Public holidays are unknown and never match
+    PublicHoliday,
+}
+
+// Minutes since midnight
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TimeSpan {
+    start: u32,
+    // Exceeds MINUTES_PER_DAY for spans that end after midnight
+    end: Option<u32>,
+    open_end: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    separator: RuleSeparator,
+    always: bool,
+    // Zero-based and inclusive
+    months: Vec<(u32, u32)>,
+    weekdays: Vec<WeekdaySelector>,
+    times: Vec<TimeSpan>,
+    modifier: Option<RuleModifier>,
+    comment: Option<String>,
+}
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+const WEEKDAYS: [&str; 7] = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"];
+
+const PUBLIC_HOLIDAY: [&str; 1] = ["PH"];
+
+// Valid in the specification but not supported here
+const UNSUPPORTED_KEYWORDS: [&str; 7] =
+    ["sunrise", "sunset", "dawn", "dusk", "week", "easter", "SH"];
+
+fn in_cyclic_range(value: u32, from: u32, to: u32) -> bool {
+    if from <= to {
+        from <= value && value <= to
+    } else {
+        value >= from || value <= to
+    }
+}
+
+impl Rule {
+    fn has_selectors(&self) -> bool {
+        self.always
+            || !self.months.is_empty()
+            || !self.weekdays.is_empty()
+            || !self.times.is_empty()
+    }
+
+    fn matches_day(&self, month0: u32, weekday0: u32) -> bool {
+        let month_matches = self.months.is_empty()
+            || self
+                .months
+                .iter()
+                .any(|&(from, to)| in_cyclic_range(month0, from, to));
+        let weekday_matches = self.weekdays.is_empty()
+            || self.weekdays.iter().any(|selector| match *selector {
+                WeekdaySelector::Range(from, to) => in_cyclic_range(weekday0, from, to),
+                WeekdaySelector::PublicHoliday => false,
+            });
+        month_matches && weekday_matches
+    }
+
+    fn matches_time(&self, minute: u32) -> bool {
+        if self.times.is_empty() {
+            // The whole day
+            return minute < MINUTES_PER_DAY;
+        }
+        self.times
+            .iter()
+            .any(|span| span.start <= minute && minute < span.end.unwrap_or(MINUTES_PER_DAY))
+    }
+
+    fn state(&self) -> OpeningState {
+        match self.modifier {
+            Some(RuleModifier::Open) => OpeningState::Open,
+            Some(RuleModifier::Closed) => OpeningState::Closed,
+            Some(RuleModifier::Unknown) => OpeningState::Unknown,
+            // A rule that consists only of a comment
+            None if !self.has_selectors() => OpeningState::Unknown,
+            None => OpeningState::Open,
+        }
+    }
+}
+
+impl OpeningHoursRules {
+    /// The state at the given local date and time.
+    pub fn state_at(&self, at: NaiveDateTime) -> OpeningState {
+        let date = at.date();
+        let minute = at.hour() * 60 + at.minute();
+        self.state_on_day(date, minute)
+            .or_else(|| {
+                // Time spans of the previous day that end after midnight
+                self.state_on_day(date.pred(), minute + MINUTES_PER_DAY)
+            })
+            .unwrap_or(OpeningState::Closed)
+    }
+
+    pub fn is_open_at(&self, at: NaiveDateTime) -> bool {
+        self.state_at(at) == OpeningState::Open
+    }
+
+    fn state_on_day(&self, date: NaiveDate, minute: u32) -> Option<OpeningState> {
+        let month0 = date.month0();
+        let weekday0 = date.weekday().num_days_from_monday();
+        let mut state = None;
+        for rule in &self.0 {
+            if rule.separator == RuleSeparator::Fallback && state.is_some() {
+                continue;
+            }
+            if !rule.matches_day(month0, weekday0) {
+                continue;
+            }
+            if rule.separator == RuleSeparator::Normal {
+                state = None;
+            }
+            if rule.matches_time(minute) {
+                state = Some(rule.state());
+            }
+        }
+        state
+    }
+}
+
+impl FromStr for OpeningHoursRules {
+    type Err = OpeningHoursParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Parser::new(s).parse_rules().map(Self)
+    }
+}
+
+fn format_range(names: &[&str], from: u32, to: u32) -> String {
+    if from == to {
+        names[from as usize].to_string()
+    } else {
+        format!("{}-{}", names[from as usize], names[to as usize])
+    }
+}
+
+fn format_time(minutes: u32) -> String {
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+impl fmt::Display for TimeSpan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let start = format_time(self.start);
+        match self.end {
+            None => write!(f, "{}+", start),
+            Some(end) => {
+                let end = if end > MINUTES_PER_DAY {
+                    end - MINUTES_PER_DAY
+                } else {
+                    end
+                };
+                let open_end = if self.open_end { "+" } else { "" };
+                write!(f, "{}-{}{}", start, format_time(end), open_end)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts = Vec::new();
+        if self.always {
+            parts.push("24/7".to_string());
+        }
+        if !self.months.is_empty() {
+            let months: Vec<_> = self
+                .months
+                .iter()
+                .map(|&(from, to)| format_range(&MONTHS, from, to))
+                .collect();
+            parts.push(months.join(","));
+        }
+        if !self.weekdays.is_empty() {
+            let weekdays: Vec<_> = self
+                .weekdays
+                .iter()
+                .map(|selector| match *selector {
+                    WeekdaySelector::Range(from, to) => format_range(&WEEKDAYS, from, to),
+                    WeekdaySelector::PublicHoliday => PUBLIC_HOLIDAY[0].to_string(),
+                })
+                .collect();
+            parts.push(weekdays.join(","));
+        }
+        if !self.times.is_empty() {
+            let times: Vec<_> = self.times.iter().map(ToString::to_string).collect();
+            parts.push(times.join(","));
+        }
+        match self.modifier {
+            Some(RuleModifier::Open) if parts.is_empty() => parts.push("open".to_string()),
+            Some(RuleModifier::Open) | None => {}
+            Some(RuleModifier::Closed) => parts.push("off".to_string()),
+            Some(RuleModifier::Unknown) => parts.push("unknown".to_string()),
+        }
+        if let Some(ref comment) = self.comment {
+            parts.push(format!("\"{}\"", comment));
+        }
+        f.write_str(&parts.join(" "))
+    }
+}
+
+impl fmt::Display for OpeningHoursRules {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, rule) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(match rule.separator {
+                    RuleSeparator::Normal => "; ",
+                    RuleSeparator::Additional => ", ",
+                    RuleSeparator::Fallback => " || ",
+                })?;
+            }
+            write!(f, "{}", rule)?;
+        }
+        Ok(())
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn new(input: &str) -> Self {
+        Self {
+            chars: input.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn error(&self, kind: OpeningHoursErrorKind) -> OpeningHoursParseError {
+        OpeningHoursParseError {
+            position: self.pos,
+            kind,
+        }
+    }
+
+    fn unexpected(&self) -> OpeningHoursParseError {
+        let kind = if self.peek().is_none() {
+            OpeningHoursErrorKind::UnexpectedEnd
+        } else if self.is_unsupported_keyword_ahead() {
+            OpeningHoursErrorKind::Unsupported
+        } else {
+            OpeningHoursErrorKind::UnexpectedCharacter
+        };
+        self.error(kind)
+    }
+
+    // Including variable times, e.g. "(sunset-01:00)"
+    fn is_unsupported_keyword_ahead(&self) -> bool {
+        let word: String = self.chars[self.pos..]
+            .iter()
+            .skip_while(|&&c| c == '(')
+            .take_while(|c| c.is_ascii_alphabetic())
+            .collect();
+        UNSUPPORTED_KEYWORDS
+            .iter()
+            .any(|keyword| keyword.eq_ignore_ascii_case(&word))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_is_digit(&self) -> bool {
+        self.peek().map(|c| c.is_ascii_digit()).unwrap_or(false)
+    }
+
+    fn peek_word(&self) -> String {
+        self.chars[self.pos..]
+            .iter()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .collect()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().map(char::is_whitespace).unwrap_or(false) {
+            self.pos += 1;
+        }
+    }
+
+    fn consume(&mut self, expected: &str) -> bool {
+        let len = expected.chars().count();
+        if self.pos + len <= self.chars.len()
+            && self.chars[self.pos..self.pos + len]
+                .iter()
+                .copied()
+                .eq(expected.chars())
+        {
+            self.pos += len;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn consume_name(&mut self, names: &[&str]) -> Option<u32> {
+        let word = self.peek_word();
+        let index = names
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(&word))?;
+        self.pos += word.len();
+        Some(index as u32)
+    }
+
+    // Lists within a rule are separated by commas
+    // that also separate additional rules.
+    fn continues_list(&self, next: impl Fn(&[char]) -> bool) -> bool {
+        if self.peek() != Some(',') {
+            return false;
+        }
+        let rest = &self.chars[self.pos + 1..];
+        let skip = rest.iter().take_while(|c| c.is_whitespace()).count();
+        next(&rest[skip..])
+    }
+
+    fn continues_with_name(&self, names: &[&str]) -> bool {
+        self.continues_list(|rest| {
+            let word: String = rest
+                .iter()
+                .take_while(|c| c.is_ascii_alphabetic())
+                .collect();
+            names.iter().any(|name| name.eq_ignore_ascii_case(&word))
+        })
+    }
+
+    fn skip_list_separator(&mut self) {
+        debug_assert_eq!(Some(','), self.peek());
+        self.pos += 1;
+        self.skip_whitespace();
+    }
+
+    fn parse_rules(&mut self) -> Result<Vec<Rule>, OpeningHoursParseError> {
+        let mut rules = Vec::new();
+        let mut separator = RuleSeparator::Normal;
+        loop {
+            rules.push(self.parse_rule(separator)?);
+            match self.parse_rule_separator()? {
+                Some(next) => separator = next,
+                None => break,
+            }
+        }
+        Ok(rules)
+    }
+
+    // Like parse_rules(), but rules with syntax that is valid
+    // according to the specification and not supported here
+    // are skipped instead of rejected.
+    fn validate_rules(&mut self) -> Result<(), OpeningHoursParseError> {
+        let mut separator = RuleSeparator::Normal;
+        loop {
+            let next = match self
+                .parse_rule(separator)
+                .and_then(|_| self.parse_rule_separator())
+            {
+                Err(err) if err.kind == OpeningHoursErrorKind::Unsupported => {
+                    self.skip_unsupported_rule()
+                }
+                next => next?,
+            };
+            match next {
+                Some(next) => separator = next,
+                None => return Ok(()),
+            }
+        }
+    }
+
+    fn parse_rule_separator(&mut self) -> Result<Option<RuleSeparator>, OpeningHoursParseError> {
+        self.skip_whitespace();
+        let separator = if self.consume(";") {
+            RuleSeparator::Normal
+        } else if self.consume(",") {
+            RuleSeparator::Additional
+        } else if self.consume("||") {
+            RuleSeparator::Fallback
+        } else if self.peek().is_none() {
+            return Ok(None);
+        } else {
+            return Err(self.unexpected());
+        };
+        Ok(Some(separator))
+    }
+
+    // Skips the remainder of a rule up to the next `;` or `||`
+    // outside of comments. Commas are ambiguous, because they
+    // also separate the selectors within a rule.
+    fn skip_unsupported_rule(&mut self) -> Option<RuleSeparator> {
+        let mut in_comment = false;
+        while let Some(c) = self.peek() {
+            if c == '"' {
+                in_comment = !in_comment;
+            } else if !in_comment {
+                if self.consume(";") {
+                    return Some(RuleSeparator::Normal);
+                }
+                if self.consume("||") {
+                    return Some(RuleSeparator::Fallback);
+                }
+            }
+            self.pos += 1;
+        }
+        None
+    }
+
+    fn parse_rule(&mut self, separator: RuleSeparator) -> Result<Rule, OpeningHoursParseError> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let mut rule = Rule {
+            separator,
+            always: false,
+            months: vec![],
+            weekdays: vec![],
+            times: vec![],
+            modifier: None,
+            comment: None,
+        };
+        if self.consume("24/7") {
+            rule.always = true;
+        } else {
+            rule.months = self.parse_months()?;
+            self.skip_whitespace();
+            rule.weekdays = self.parse_weekdays()?;
+            self.skip_whitespace();
+            rule.times = self.parse_times()?;
+        }
+        self.skip_whitespace();
+        rule.modifier = self.parse_modifier();
+        self.skip_whitespace();
+        rule.comment = self.parse_comment()?;
+        if self.pos == start {
+            return Err(self.unexpected());
+        }
+        Ok(rule)
+    }
+
+    fn parse_months(&mut self) -> Result<Vec<(u32, u32)>, OpeningHoursParseError> {
+        let mut months = Vec::new();
+        while let Some(from) = self.consume_name(&MONTHS) {
+            let to = if self.consume("-") {
+                self.consume_name(&MONTHS)
+                    .ok_or_else(|| self.unexpected())?
+            } else {
+                from
+            };
+            if self.is_day_of_month_ahead() {
+                return Err(self.error(OpeningHoursErrorKind::Unsupported));
+            }
+            months.push((from, to));
+            if !self.continues_with_name(&MONTHS) {
+                break;
+            }
+            self.skip_list_separator();
+        }
+        Ok(months)
+    }
+
+    // Days of the month (e.g. "Dec 24") in contrast
+    // to time spans (e.g. "Dec 10:00-12:00").
+    fn is_day_of_month_ahead(&self) -> bool {
+        let mut rest = self.chars[self.pos..]
+            .iter()
+            .skip_while(|c| c.is_whitespace())
+            .peekable();
+        let mut digits = 0;
+        while rest.peek().map(|c| c.is_ascii_digit()).unwrap_or(false) {
+            rest.next();
+            digits += 1;
+        }
+        digits > 0 && rest.peek() != Some(&&':')
+    }
+
+    fn parse_weekdays(&mut self) -> Result<Vec<WeekdaySelector>, OpeningHoursParseError> {
+        let mut weekdays = Vec::new();
+        loop {
+            if self.consume_name(&PUBLIC_HOLIDAY).is_some() {
+                weekdays.push(WeekdaySelector::PublicHoliday);
+            } else if let Some(from) = self.consume_name(&WEEKDAYS) {
+                let to = if self.consume("-") {
+                    self.consume_name(&WEEKDAYS)
+                        .ok_or_else(|| self.unexpected())?
+                } else {
+                    from
+                };
+                if self.peek() == Some('[') {
+                    // The n-th weekday of a month
+                    return Err(self.error(OpeningHoursErrorKind::Unsupported));
+                }
+                weekdays.push(WeekdaySelector::Range(from, to));
+            } else {
+                break;
+            }
+            if !self.continues_with_name(&WEEKDAYS) && !self.continues_with_name(&PUBLIC_HOLIDAY) {
+                break;
+            }
+            self.skip_list_separator();
+        }
+        Ok(weekdays)
+    }
+
+    fn parse_times(&mut self) -> Result<Vec<TimeSpan>, OpeningHoursParseError> {
+        let mut times = Vec::new();
+        while self.peek_is_digit() {
+            times.push(self.parse_time_span()?);
+            if !self
+                .continues_list(|rest| rest.first().map(|c| c.is_ascii_digit()).unwrap_or(false))
+            {
+                break;
+            }
+            self.skip_list_separator();
+        }
+        Ok(times)
+    }
+
+    fn parse_time_span(&mut self) -> Result<TimeSpan, OpeningHoursParseError> {
+        let start_pos = self.pos;
+        let start = self.parse_time()?;
+        if start >= MINUTES_PER_DAY {
+            return Err(OpeningHoursParseError {
+                position: start_pos,
+                kind: OpeningHoursErrorKind::InvalidTime,
+            });
+        }
+        if self.consume("+") {
+            return Ok(TimeSpan {
+                start,
+                end: None,
+                open_end: true,
+            });
+        }
+        if !self.consume("-") {
+            return Err(self.unexpected());
+        }
+        let mut end = self.parse_time()?;
+        if end <= start {
+            // Ends after midnight
+            end += MINUTES_PER_DAY;
+        }
+        let open_end = self.consume("+");
+        Ok(TimeSpan {
+            start,
+            end: Some(end),
+            open_end,
+        })
+    }
+
+    fn parse_time(&mut self) -> Result<u32, OpeningHoursParseError> {
+        let start_pos = self.pos;
+        let invalid_time = OpeningHoursParseError {
+            position: start_pos,
+            kind: OpeningHoursErrorKind::InvalidTime,
+        };
+        let (hours, _) = self.parse_number()?;
+        if !self.consume(":") {
+            return Err(self.unexpected());
+        }
+        let (minutes, minute_digits) = self.parse_number()?;
+        if minute_digits != 2 || minutes >= 60 {
+            return Err(invalid_time);
+        }
+        let time = hours * 60 + minutes;
+        if time > 2 * MINUTES_PER_DAY {
+            return Err(invalid_time);
+        }
+        Ok(time)
+    }
+
+    // Parses up to two digits
+    fn parse_number(&mut self) -> Result<(u32, usize), OpeningHoursParseError> {
+        let digits: String = self.chars[self.pos..]
+            .iter()
+            .take(2)
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        if digits.is_empty() {
+            return Err(self.unexpected());
+        }
+        let number = digits
+            .parse()
+            .map_err(|_| self.error(OpeningHoursErrorKind::InvalidTime))?;
+        self.pos += digits.len();
+        Ok((number, digits.len()))
+    }
+
+    fn parse_modifier(&mut self) -> Option<RuleModifier> {
+        let word = self.peek_word().to_ascii_lowercase();
+        let modifier = match word.as_str() {
+            "open" => RuleModifier::Open,
+            "closed" | "off" => RuleModifier::Closed,
+            "unknown" => RuleModifier::Unknown,
+            _ => return None,
+        };
+        self.pos += word.len();
+        Some(modifier)
+    }
+
+    fn parse_comment(&mut self) -> Result<Option<String>, OpeningHoursParseError> {
+        if !self.consume("\"") {
+            return Ok(None);
+        }
+        let len = self.chars[self.pos..]
+            .iter()
+            .position(|&c| c == '"')
+            .ok_or_else(|| OpeningHoursParseError {
+                position: self.chars.len(),
+                kind: OpeningHoursErrorKind::UnexpectedEnd,
+            })?;
+        let comment = self.chars[self.pos..self.pos + len].iter().collect();
+        self.pos += len + 1;
+        Ok(Some(comment))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn rules(s: &str) -> OpeningHoursRules {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_valid_opening_hours() {
+        for s in &[
+            "24/7",
+            "Mo-Fr 08:00-18:00",
+            "Mo-Fr 08:00-12:00,13:00-17:30; Sa 09:00-13:00; PH off",
+            "Mo,We,Fr 10:00-14:00",
+            "Fr-Sa 22:00-03:00",
+            "Apr-Oct Mo-Su 10:00-18:00; Nov-Mar off",
+            "Mo-Fr 09:00-17:00 || \"by appointment\"",
+            "Mo-Fr 08:00-12:00, We 14:00-18:00",
+            "Sa 18:00+",
+            "unknown \"call us\"",
+            "mo-fr 9:00-17:00",
+        ] {
+            assert!(s.parse::<OpeningHours>().is_ok(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn reject_invalid_opening_hours() {
+        let err = |s: &str| s.parse::<OpeningHours>().unwrap_err();
+        assert_eq!(OpeningHoursErrorKind::TooShort, err(" 24 ").kind);
+        assert_eq!(
+            OpeningHoursParseError {
+                position: 12,
+                kind: OpeningHoursErrorKind::InvalidTime
+            },
+            err("Mo-Fr 08:00-25:61")
+        );
+        assert_eq!(
+            OpeningHoursErrorKind::UnexpectedEnd,
+            err("Mo-Fr 08:00-").kind
+        );
+        assert_eq!(
+            OpeningHoursParseError {
+                position: 6,
+                kind: OpeningHoursErrorKind::UnexpectedCharacter
+            },
+            err("Mo-Fr morning")
+        );
+        assert_eq!(
+            OpeningHoursErrorKind::UnexpectedEnd,
+            err("Mo \"unterminated").kind
+        );
+        assert_eq!(
+            OpeningHoursErrorKind::UnexpectedEnd,
+            err("Mo-Fr 08:00-18:00;").kind
+        );
+    }
+
+    #[test]
+    fn accept_unsupported_opening_hours() {
+        for s in &[
+            "Mo-Fr sunrise-sunset",
+            "Mo-Fr 08:00-(sunset-01:00)",
+            "Mo-Fr 08:00-18:00; SH off",
+            "SH Mo-Fr 10:00-12:00",
+            "week 01-26 Mo-Fr 09:00-17:00",
+            "Dec 24 off",
+            "Mo[1] 10:00-12:00",
+            "SH off; Mo-Fr 08:00-18:00",
+            "Mo 10:00-sunset \"; ignored\"; Tu-Fr 10:00-18:00 || \"by appointment\"",
+        ] {
+            let opening_hours = s.parse::<OpeningHours>().unwrap();
+            assert_eq!(*s, opening_hours.as_str());
+            assert_eq!(
+                OpeningHoursErrorKind::Unsupported,
+                opening_hours.rules().unwrap_err().kind,
+                "{}",
+                s
+            );
+            assert!(opening_hours.normalized().is_none());
+        }
+    }
+
+    #[test]
+    fn reject_invalid_rules_besides_unsupported_rules() {
+        let err = |s: &str| s.parse::<OpeningHours>().unwrap_err();
+        assert_eq!(
+            OpeningHoursParseError {
+                position: 8,
+                kind: OpeningHoursErrorKind::UnexpectedCharacter
+            },
+            err("SH off; garbage")
+        );
+        assert_eq!(
+            OpeningHoursErrorKind::UnexpectedCharacter,
+            err("Mo 10:00-sunset; xyz").kind
+        );
+        assert_eq!(
+            OpeningHoursErrorKind::InvalidTime,
+            err("week 01-26 Mo-Fr 09:00-17:00 || Sa 10:00-12:61").kind
+        );
+        assert_eq!(
+            OpeningHoursErrorKind::UnexpectedEnd,
+            err("Mo-Fr 08:00-18:00; Dec 24 off;").kind
+        );
+        assert_eq!(
+            OpeningHoursErrorKind::UnexpectedCharacter,
+            err("Mo-Fr 08:00- 18:00; sunrise-sunset").kind
+        );
+    }
+
+    #[test]
+    fn normalize_opening_hours() {
+        let normalized = |s: &str| s.parse::<OpeningHours>().unwrap().normalized().unwrap();
+        assert_eq!("24/7", normalized(" 24/7 "));
+        assert_eq!(
+            "Mo-Fr 08:00-12:00,13:00-17:30; Sa 09:00-13:00; PH off",
+            normalized("mo-fr 8:00-12:00, 13:00-17:30;Sa 09:00-13:00 ; PH closed")
+        );
+        assert_eq!("Fr 22:00-03:00", normalized("Fr 22:00-27:00"));
+        assert_eq!(
+            "Mo-Fr 09:00-17:00 || \"by appointment\"",
+            normalized("Mo-Fr 09:00-17:00||\"by appointment\"")
+        );
+        // Legacy values that have been stored without validation
+        assert!(OpeningHours::from("Täglich ab 9 Uhr".to_string())
+            .normalized()
+            .is_none());
+    }
+
+    #[test]
+    fn normalized_opening_hours_are_stable() {
+        for s in &[
+            "Mo-Fr 08:00-12:00,13:00-17:30; Sa 09:00-13:00; PH off",
+            "Apr-Oct Mo-Su 10:00-18:00; Nov-Mar off",
+            "Sa 18:00+",
+            "unknown \"call us\"",
+            "open",
+        ] {
+            let normalized = rules(s).to_string();
+            assert_eq!(rules(s), rules(&normalized));
+            assert_eq!(normalized, rules(&normalized).to_string());
+        }
+    }
+
+    #[test]
+    fn evaluate_opening_hours() {
+        // 2020-10-14 is a Wednesday
+        let rules = rules("Mo-Fr 08:00-12:00,13:00-18:00; We 10:00-12:00; Sa 22:00-02:00");
+        assert!(!rules.is_open_at(at("2020-10-12 07:59")));
+        assert!(rules.is_open_at(at("2020-10-12 08:00")));
+        assert!(!rules.is_open_at(at("2020-10-12 12:30")));
+        assert!(!rules.is_open_at(at("2020-10-12 18:00")));
+        assert!(!rules.is_open_at(at("2020-10-14 08:30")));
+        assert!(rules.is_open_at(at("2020-10-14 10:30")));
+        assert!(!rules.is_open_at(at("2020-10-14 14:00")));
+        assert!(rules.is_open_at(at("2020-10-17 23:00")));
+        assert!(rules.is_open_at(at("2020-10-18 01:59")));
+        assert!(!rules.is_open_at(at("2020-10-18 02:00")));
+    }
+
+    #[test]
+    fn evaluate_months_modifiers_and_fallbacks() {
+        let seasonal = rules("Apr-Oct 10:00-18:00; Nov-Mar off");
+        assert!(seasonal.is_open_at(at("2020-07-01 12:00")));
+        assert!(!seasonal.is_open_at(at("2020-12-01 12:00")));
+
+        let always = rules("24/7; Su off");
+        assert!(always.is_open_at(at("2020-10-17 03:00")));
+        assert!(!always.is_open_at(at("2020-10-18 12:00")));
+
+        let fallback = rules("Mo-Fr 09:00-17:00 || \"by appointment\"");
+        assert_eq!(
+            OpeningState::Open,
+            fallback.state_at(at("2020-10-12 10:00"))
+        );
+        assert_eq!(
+            OpeningState::Unknown,
+            fallback.state_at(at("2020-10-12 20:00"))
+        );
+
+        let additional = rules("Mo-Fr 08:00-12:00, We 14:00-18:00");
+        assert!(additional.is_open_at(at("2020-10-14 09:00")));
+        assert!(additional.is_open_at(at("2020-10-14 15:00")));
+    }
+}
@@ -0,0 +1,176 @@
+use crate::{email::*, id::*, place::*, proposal::PlaceField};
+
+use std::{fmt, str::FromStr};
+
+/// Fields of a place that could be locked by its owners
+/// against changes by others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LockableField {
+    Contact,
+    OpeningHours,
+    Links,
+}
+
+impl LockableField {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Contact => "contact",
+            Self::OpeningHours => "opening_hours",
+            Self::Links => "links",
+        }
+    }
+
+    /// The corresponding field of a place.
+    pub const fn place_field(self) -> PlaceField {
+        match self {
+            Self::Contact => PlaceField::Contact,
+            Self::OpeningHours => PlaceField::OpeningHours,
+            Self::Links => PlaceField::Links,
+        }
+    }
+
+    /// Check if the field differs between two revisions of a place.
+    pub fn is_changed(self, old: &Place, new: &Place) -> bool {
+        self.place_field().is_changed(old, new)
+    }
+}
+
+impl fmt::Display for LockableField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug)]
+pub struct LockableFieldParseError;
+
+impl FromStr for LockableField {
+    type Err = LockableFieldParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let field = match s {
+            "contact" => Self::Contact,
+            "opening_hours" => Self::OpeningHours,
+            "links" => Self::Links,
+            _ => return Err(LockableFieldParseError),
+        };
+        Ok(field)
+    }
+}
+
+/// A registered user or an organization that owns a place.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlaceOwner {
+    User(Email),
+    Organization(Id),
+}
+
+/// The owners of a place and the fields that only
+/// they are allowed to change.
+///
+/// Places without owners could be edited by anyone.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlaceOwnership {
+    pub owners: Vec<PlaceOwner>,
+    pub locked_fields: Vec<LockableField>,
+}
+
+impl PlaceOwnership {
+    pub fn is_owned_by_user(&self, email: &str) -> bool {
+        self.owners.iter().any(|owner| match owner {
+            PlaceOwner::User(owner_email) => AsRef::<str>::as_ref(owner_email) == email,
+            PlaceOwner::Organization(_) => false,
+        })
+    }
+
+    pub fn is_owned_by_org(&self, org_id: &Id) -> bool {
+        self.owners.iter().any(|owner| match owner {
+            PlaceOwner::User(_) => false,
+            PlaceOwner::Organization(owner_org_id) => owner_org_id == org_id,
+        })
+    }
+
+    /// The locked fields that differ between two revisions of a place.
+    pub fn changed_locked_fields(&self, old: &Place, new: &Place) -> Vec<LockableField> {
+        if self.owners.is_empty() {
+            // Fields could only be locked by owners
+            return vec![];
+        }
+        self.locked_fields
+            .iter()
+            .copied()
+            .filter(|field| field.is_changed(old, new))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builders::*, contact::*};
+
+    #[test]
+    fn lockable_field_round_trip() {
+        for field in &[
+            LockableField::Contact,
+            LockableField::OpeningHours,
+            LockableField::Links,
+        ] {
+            assert_eq!(*field, field.as_str().parse::<LockableField>().unwrap());
+        }
+        assert!("title".parse::<LockableField>().is_err());
+    }
+
+    #[test]
+    fn changed_locked_fields() {
+        let old = Place::build().id("foo").finish();
+        let mut new = old.clone();
+        new.title = "changed".into();
+        new.contact = Some(Contact {
+            name: None,
+            email: Some("owner@example.com".into()),
+            phone: None,
+        });
+        let ownership = PlaceOwnership {
+            owners: vec![PlaceOwner::User("owner@example.com".into())],
+            locked_fields: vec![LockableField::Contact, LockableField::Links],
+        };
+        assert_eq!(
+            vec![LockableField::Contact],
+            ownership.changed_locked_fields(&old, &new)
+        );
+        assert!(ownership.changed_locked_fields(&old, &old).is_empty());
+        // Without owners nothing is locked
+        let ownership = PlaceOwnership {
+            owners: vec![],
+            ..ownership
+        };
+        assert!(ownership.changed_locked_fields(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn missing_and_empty_contact_are_equal() {
+        let old = Place::build().id("foo").finish();
+        let mut new = old.clone();
+        new.contact = None;
+        assert!(!LockableField::Contact.is_changed(&old, &new));
+        new.contact = Some(Default::default());
+        assert!(!LockableField::Contact.is_changed(&old, &new));
+    }
+
+    #[test]
+    fn owned_by_user_or_org() {
+        let org_id = Id::new();
+        let ownership = PlaceOwnership {
+            owners: vec![
+                PlaceOwner::User("owner@example.com".into()),
+                PlaceOwner::Organization(org_id.clone()),
+            ],
+            locked_fields: vec![],
+        };
+        assert!(ownership.is_owned_by_user("owner@example.com"));
+        assert!(!ownership.is_owned_by_user("other@example.com"));
+        assert!(ownership.is_owned_by_org(&org_id));
+        assert!(!ownership.is_owned_by_org(&Id::new()));
+    }
+}
@@ -1,7 +1,8 @@
-use crate::{activity::*, contact::*, id::*, links::*, location::*, review::*, revision::*};
+use crate::{
+    activity::*, contact::*, id::*, links::*, location::*, opening_hours::*, review::*, revision::*,
+};
 
 use chrono::NaiveDate;
-use std::str::FromStr;
 
 // Immutable part of a place.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -10,44 +11,6 @@ pub struct PlaceRoot {
     pub license: String,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
-pub struct OpeningHours(String);
-
-#[derive(Debug, Clone, Eq, PartialEq)]
-pub struct OpeningHoursParseError;
-
-impl OpeningHours {
-    pub const fn min_len() -> usize {
-        4
-    }
-}
-
-impl FromStr for OpeningHours {
-    type Err = OpeningHoursParseError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let trimmed = s.trim();
-        if trimmed.len() < Self::min_len() {
-            return Err(OpeningHoursParseError);
-        }
-        Ok(Self(trimmed.to_string()))
-    }
-}
-
-impl From<String> for OpeningHours {
-    fn from(from: String) -> Self {
-        let res = Self(from);
-        debug_assert_eq!(Ok(&res), res.0.as_str().parse().as_ref());
-        res
-    }
-}
-
-impl From<OpeningHours> for String {
-    fn from(from: OpeningHours) -> Self {
-        from.0
-    }
-}
-
 // Mutable part of a place.
 #[derive(Debug, Clone, PartialEq)]
 pub struct PlaceRevision {
@@ -0,0 +1,245 @@
+use crate::{activity::*, id::*, place::*, revision::*};
+
+use std::{fmt, str::FromStr};
+
+/// Changes of a place that have been proposed by someone
+/// who is not entitled to edit the place directly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaceProposal {
+    pub id: Id,
+    /// The current revision of the place when the
+    /// changes have been proposed
+    pub base_revision: Revision,
+    /// The place including all proposed changes.
+    ///
+    /// The revision follows the base revision and the
+    /// activity records who proposed the changes and when.
+    pub place: Place,
+    /// The organization that proposed the changes
+    pub org_id: Option<Id>,
+    pub status: ProposalStatus,
+    /// Who reviewed the proposal, when and why
+    pub reviewed: Option<ActivityLog>,
+}
+
+/// The review state of a proposal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalStatus {
+    /// Waiting for a review
+    Pending,
+    /// All proposed changes have been applied
+    Accepted,
+    /// Only some of the proposed changes have been
+    /// applied onto the current revision
+    Merged,
+    /// None of the proposed changes have been applied
+    Rejected,
+}
+
+impl ProposalStatus {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Accepted => "accepted",
+            Self::Merged => "merged",
+            Self::Rejected => "rejected",
+        }
+    }
+
+    pub fn is_pending(self) -> bool {
+        self == Self::Pending
+    }
+}
+
+impl Default for ProposalStatus {
+    fn default() -> Self {
+        Self::Pending
+    }
+}
+
+impl fmt::Display for ProposalStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug)]
+pub struct ProposalStatusParseError;
+
+impl FromStr for ProposalStatus {
+    type Err = ProposalStatusParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let status = match s {
+            "pending" => Self::Pending,
+            "accepted" => Self::Accepted,
+            "merged" => Self::Merged,
+            "rejected" => Self::Rejected,
+            _ => return Err(ProposalStatusParseError),
+        };
+        Ok(status)
+    }
+}
+
+/// The editable fields of a place that could be
+/// compared and merged individually.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PlaceField {
+    Title,
+    Description,
+    Position,
+    Address,
+    Contact,
+    OpeningHours,
+    FoundedOn,
+    Links,
+    Tags,
+}
+
+impl PlaceField {
+    pub const ALL: [Self; 9] = [
+        Self::Title,
+        Self::Description,
+        Self::Position,
+        Self::Address,
+        Self::Contact,
+        Self::OpeningHours,
+        Self::FoundedOn,
+        Self::Links,
+        Self::Tags,
+    ];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Title => "title",
+            Self::Description => "description",
+            Self::Position => "position",
+            Self::Address => "address",
+            Self::Contact => "contact",
+            Self::OpeningHours => "opening_hours",
+            Self::FoundedOn => "founded_on",
+            Self::Links => "links",
+            Self::Tags => "tags",
+        }
+    }
+
+    /// Check if the field differs between two revisions of a place.
+    ///
+    /// Missing values are considered as empty, i.e. removing
+    /// all contact details is also a change.
+    pub fn is_changed(self, old: &Place, new: &Place) -> bool {
+        match self {
+            Self::Title => old.title != new.title,
+            Self::Description => old.description != new.description,
+            Self::Position => old.location.pos != new.location.pos,
+            Self::Address => {
+                old.location.address.clone().unwrap_or_default()
+                    != new.location.address.clone().unwrap_or_default()
+            }
+            Self::Contact => {
+                old.contact.clone().unwrap_or_default() != new.contact.clone().unwrap_or_default()
+            }
+            Self::OpeningHours => old.opening_hours != new.opening_hours,
+            Self::FoundedOn => old.founded_on != new.founded_on,
+            Self::Links => {
+                old.links.clone().unwrap_or_default() != new.links.clone().unwrap_or_default()
+            }
+            Self::Tags => old.tags != new.tags,
+        }
+    }
+
+    /// Copy the value of this field from one place into another.
+    pub fn copy(self, from: &Place, into: &mut Place) {
+        match self {
+            Self::Title => into.title = from.title.clone(),
+            Self::Description => into.description = from.description.clone(),
+            Self::Position => into.location.pos = from.location.pos,
+            Self::Address => into.location.address = from.location.address.clone(),
+            Self::Contact => into.contact = from.contact.clone(),
+            Self::OpeningHours => into.opening_hours = from.opening_hours.clone(),
+            Self::FoundedOn => into.founded_on = from.founded_on,
+            Self::Links => into.links = from.links.clone(),
+            Self::Tags => into.tags = from.tags.clone(),
+        }
+    }
+
+    /// All fields that differ between two revisions of a place.
+    pub fn changed_fields(old: &Place, new: &Place) -> Vec<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .filter(|field| field.is_changed(old, new))
+            .collect()
+    }
+}
+
+impl fmt::Display for PlaceField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug)]
+pub struct PlaceFieldParseError;
+
+impl FromStr for PlaceField {
+    type Err = PlaceFieldParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|field| field.as_str() == s)
+            .ok_or(PlaceFieldParseError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builders::*, contact::*};
+
+    #[test]
+    fn place_field_round_trip() {
+        for field in &PlaceField::ALL {
+            assert_eq!(*field, field.as_str().parse::<PlaceField>().unwrap());
+        }
+        assert!("license".parse::<PlaceField>().is_err());
+    }
+
+    #[test]
+    fn proposal_status_round_trip() {
+        for status in &[
+            ProposalStatus::Pending,
+            ProposalStatus::Accepted,
+            ProposalStatus::Merged,
+            ProposalStatus::Rejected,
+        ] {
+            assert_eq!(*status, status.as_str().parse::<ProposalStatus>().unwrap());
+        }
+    }
+
+    #[test]
+    fn copy_changed_fields() {
+        let old = Place::build().id("foo").title("old").finish();
+        let mut new = old.clone();
+        new.title = "new".into();
+        new.tags = vec!["bar".into()];
+        new.contact = Some(Contact {
+            name: None,
+            email: None,
+            phone: Some("123".into()),
+        });
+        assert_eq!(
+            vec![PlaceField::Title, PlaceField::Contact, PlaceField::Tags],
+            PlaceField::changed_fields(&old, &new)
+        );
+        let mut merged = old.clone();
+        PlaceField::Tags.copy(&new, &mut merged);
+        assert_eq!(
+            vec![PlaceField::Tags],
+            PlaceField::changed_fields(&old, &merged)
+        );
+        assert_eq!("old", merged.title);
+    }
+}
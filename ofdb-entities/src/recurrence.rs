@@ -0,0 +1,564 @@
+//! Recurrence rules in the [iCalendar format](https://tools.ietf.org/html/rfc5545#section-3.3.10).
+//!
+//! Only a subset of the specification is supported:
+//! the frequencies `DAILY`, `WEEKLY`, `MONTHLY` and `YEARLY`
+//! with the parts `INTERVAL`, `COUNT`, `UNTIL` and `BYDAY`.
+//! Weekdays with an ordinal number like `2TU` or `-1FR` are
+//! only supported for monthly rules.
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Weekday};
+use std::{fmt, str::FromStr};
+use thiserror::Error;
+
+/// Upper limit for the number of periods that are visited
+/// while expanding a rule, e.g. ~27 years of daily events.
+const MAX_PERIODS: u32 = 10_000;
+
+const MAX_INTERVAL: u32 = 1_000;
+
+const UNTIL_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    fn as_str(self) -> &'static str {
+        match self {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        }
+    }
+}
+
+/// A weekday, optionally restricted to the n-th
+/// occurrence within a month (negative = from the end).
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct WeekdayNum {
+    pub ordinal: Option<i8>,
+    pub weekday: Weekday,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    // Inclusive
+    pub until: Option<NaiveDateTime>,
+    pub by_day: Vec<WeekdayNum>,
+}
+
+#[derive(Debug, Error, Clone, Eq, PartialEq)]
+pub enum RecurrenceRuleParseError {
+    #[error("missing frequency")]
+    MissingFrequency,
+    #[error("invalid value of {0}")]
+    InvalidValue(String),
+    #[error("unsupported part {0}")]
+    Unsupported(String),
+    #[error("COUNT and UNTIL must not occur together")]
+    CountAndUntil,
+}
+
+impl RecurrenceRule {
+    /// The rule ends after a fixed number of occurrences
+    /// or at a certain point in time.
+    pub fn is_finite(&self) -> bool {
+        self.count.is_some() || self.until.is_some()
+    }
+
+    /// All occurrences of a series that starts at `start`.
+    ///
+    /// The start of the series is always the first occurrence.
+    pub fn occurrences(&self, start: NaiveDateTime) -> Occurrences {
+        Occurrences {
+            rule: self.clone(),
+            start,
+            period: 0,
+            count: 0,
+            pending: vec![start],
+        }
+    }
+
+    /// The start of the last occurrence of a finite rule.
+    pub fn last_occurrence(&self, start: NaiveDateTime) -> Option<NaiveDateTime> {
+        if self.is_finite() {
+            self.occurrences(start).last()
+        } else {
+            None
+        }
+    }
+
+    // Candidate dates of a single period in no particular order
+    fn candidates(&self, date: NaiveDate, period: u32) -> Vec<NaiveDate> {
+        let step = i64::from(period) * i64::from(self.interval);
+        match self.frequency {
+            Frequency::Daily => date
+                .checked_add_signed(Duration::days(step))
+                .into_iter()
+                .collect(),
+            Frequency::Weekly => {
+                if self.by_day.is_empty() {
+                    return date
+                        .checked_add_signed(Duration::weeks(step))
+                        .into_iter()
+                        .collect();
+                }
+                let monday = date
+                    .checked_sub_signed(Duration::days(
+                        date.weekday().num_days_from_monday().into(),
+                    ))
+                    .and_then(|monday| monday.checked_add_signed(Duration::weeks(step)));
+                monday
+                    .into_iter()
+                    .flat_map(|monday| {
+                        self.by_day.iter().filter_map(move |day| {
+                            monday.checked_add_signed(Duration::days(
+                                day.weekday.num_days_from_monday().into(),
+                            ))
+                        })
+                    })
+                    .collect()
+            }
+            Frequency::Monthly => {
+                let months = i64::from(date.year()) * 12 + i64::from(date.month0()) + step;
+                let year = months.div_euclid(12);
+                if year > i64::from(i32::MAX) {
+                    return vec![];
+                }
+                let year = year as i32;
+                let month = months.rem_euclid(12) as u32 + 1;
+                if self.by_day.is_empty() {
+                    return NaiveDate::from_ymd_opt(year, month, date.day())
+                        .into_iter()
+                        .collect();
+                }
+                self.by_day
+                    .iter()
+                    .flat_map(|day| weekdays_of_month(year, month, *day))
+                    .collect()
+            }
+            Frequency::Yearly => {
+                let year = i64::from(date.year()) + step;
+                if year > i64::from(i32::MAX) {
+                    return vec![];
+                }
+                NaiveDate::from_ymd_opt(year as i32, date.month(), date.day())
+                    .into_iter()
+                    .collect()
+            }
+        }
+    }
+}
+
+fn weekdays_of_month(year: i32, month: u32, day: WeekdayNum) -> Vec<NaiveDate> {
+    let mut dates = Vec::with_capacity(5);
+    let mut next = NaiveDate::from_ymd_opt(year, month, 1);
+    while let Some(date) = next {
+        if date.month() != month {
+            break;
+        }
+        if date.weekday() == day.weekday {
+            dates.push(date);
+        }
+        next = date.succ_opt();
+    }
+    match day.ordinal {
+        None => dates,
+        Some(n) => {
+            let index = if n > 0 {
+                n as usize - 1
+            } else if (n.abs() as usize) <= dates.len() {
+                dates.len() - n.abs() as usize
+            } else {
+                return vec![];
+            };
+            dates.get(index).copied().into_iter().collect()
+        }
+    }
+}
+
+/// Iterator over the occurrences of a recurrence rule
+/// in chronological order.
+#[derive(Debug)]
+pub struct Occurrences {
+    rule: RecurrenceRule,
+    start: NaiveDateTime,
+    period: u32,
+    count: u32,
+    // In reverse chronological order
+    pending: Vec<NaiveDateTime>,
+}
+
+impl Iterator for Occurrences {
+    type Item = NaiveDateTime;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(count) = self.rule.count {
+                if self.count >= count {
+                    return None;
+                }
+            }
+            if let Some(next) = self.pending.pop() {
+                if let Some(until) = self.rule.until {
+                    if next > until {
+                        self.pending.clear();
+                        self.period = MAX_PERIODS;
+                        return None;
+                    }
+                }
+                self.count += 1;
+                return Some(next);
+            }
+            if self.period >= MAX_PERIODS {
+                return None;
+            }
+            let start = self.start;
+            let mut pending: Vec<_> = self
+                .rule
+                .candidates(start.date(), self.period)
+                .into_iter()
+                .map(|date| date.and_time(start.time()))
+                .filter(|date_time| *date_time > start)
+                .collect();
+            pending.sort_unstable_by(|lhs, rhs| rhs.cmp(lhs));
+            pending.dedup();
+            self.pending = pending;
+            self.period += 1;
+        }
+    }
+}
+
+const WEEKDAYS: [(&str, Weekday); 7] = [
+    ("MO", Weekday::Mon),
+    ("TU", Weekday::Tue),
+    ("WE", Weekday::Wed),
+    ("TH", Weekday::Thu),
+    ("FR", Weekday::Fri),
+    ("SA", Weekday::Sat),
+    ("SU", Weekday::Sun),
+];
+
+fn weekday_as_str(weekday: Weekday) -> &'static str {
+    WEEKDAYS[weekday.num_days_from_monday() as usize].0
+}
+
+impl fmt::Display for WeekdayNum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ordinal) = self.ordinal {
+            write!(f, "{}", ordinal)?;
+        }
+        f.write_str(weekday_as_str(self.weekday))
+    }
+}
+
+impl FromStr for WeekdayNum {
+    type Err = RecurrenceRuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || RecurrenceRuleParseError::InvalidValue("BYDAY".into());
+        let s = s.trim();
+        if s.len() < 2 || !s.is_char_boundary(s.len() - 2) {
+            return Err(invalid());
+        }
+        let (ordinal, weekday) = s.split_at(s.len() - 2);
+        let weekday = WEEKDAYS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(weekday))
+            .map(|(_, weekday)| *weekday)
+            .ok_or_else(invalid)?;
+        let ordinal = if ordinal.is_empty() {
+            None
+        } else {
+            let ordinal = ordinal.parse::<i8>().map_err(|_| invalid())?;
+            if ordinal == 0 || ordinal.abs() > 5 {
+                return Err(invalid());
+            }
+            Some(ordinal)
+        };
+        Ok(Self { ordinal, weekday })
+    }
+}
+
+impl fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FREQ={}", self.frequency.as_str())?;
+        if self.interval > 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format(UNTIL_FORMAT))?;
+        }
+        if !self.by_day.is_empty() {
+            f.write_str(";BYDAY=")?;
+            for (i, day) in self.by_day.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(",")?;
+                }
+                write!(f, "{}", day)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_until(s: &str) -> Option<NaiveDateTime> {
+    let s = s.trim_end_matches(|c| c == 'Z' || c == 'z');
+    NaiveDateTime::parse_from_str(s, "%Y%m%dT%H%M%S")
+        .ok()
+        .or_else(|| {
+            // A date without a time includes the whole day
+            NaiveDate::parse_from_str(s, "%Y%m%d")
+                .ok()
+                .map(|date| date.and_hms(23, 59, 59))
+        })
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = RecurrenceRuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use RecurrenceRuleParseError as E;
+        let s = s.trim();
+        let s = match s.get(..6) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("RRULE:") => &s[6..],
+            _ => s,
+        };
+        let mut frequency = None;
+        let mut interval = None;
+        let mut count = None;
+        let mut until = None;
+        let mut by_day = None;
+        for part in s.split(';').map(str::trim).filter(|part| !part.is_empty()) {
+            let mut name_value = part.splitn(2, '=');
+            let name = name_value.next().unwrap_or_default().to_uppercase();
+            let value = name_value
+                .next()
+                .map(str::trim)
+                .ok_or_else(|| E::InvalidValue(name.clone()))?;
+            let invalid = || E::InvalidValue(name.clone());
+            match name.as_str() {
+                "FREQ" if frequency.is_none() => {
+                    frequency = Some(match value.to_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        "SECONDLY" | "MINUTELY" | "HOURLY" => {
+                            return Err(E::Unsupported(format!("FREQ={}", value)));
+                        }
+                        _ => return Err(invalid()),
+                    });
+                }
+                "INTERVAL" if interval.is_none() => {
+                    let value = value.parse::<u32>().map_err(|_| invalid())?;
+                    if !(1..=MAX_INTERVAL).contains(&value) {
+                        return Err(invalid());
+                    }
+                    interval = Some(value);
+                }
+                "COUNT" if count.is_none() => {
+                    let value = value.parse::<u32>().map_err(|_| invalid())?;
+                    if value < 1 {
+                        return Err(invalid());
+                    }
+                    count = Some(value);
+                }
+                "UNTIL" if until.is_none() => {
+                    until = Some(parse_until(value).ok_or_else(invalid)?);
+                }
+                "BYDAY" if by_day.is_none() => {
+                    by_day = Some(
+                        value
+                            .split(',')
+                            .map(str::parse)
+                            .collect::<Result<Vec<WeekdayNum>, _>>()?,
+                    );
+                }
+                // Weeks always start on Monday
+                "WKST" if value.eq_ignore_ascii_case("MO") => {}
+                "FREQ" | "INTERVAL" | "COUNT" | "UNTIL" | "BYDAY" => return Err(invalid()),
+                _ => return Err(E::Unsupported(name)),
+            }
+        }
+        let frequency = frequency.ok_or(E::MissingFrequency)?;
+        if count.is_some() && until.is_some() {
+            return Err(E::CountAndUntil);
+        }
+        let by_day = by_day.unwrap_or_default();
+        match frequency {
+            Frequency::Weekly => {
+                if by_day.iter().any(|day| day.ordinal.is_some()) {
+                    return Err(E::Unsupported("BYDAY".into()));
+                }
+            }
+            Frequency::Monthly => {}
+            Frequency::Daily | Frequency::Yearly => {
+                if !by_day.is_empty() {
+                    return Err(E::Unsupported("BYDAY".into()));
+                }
+            }
+        }
+        Ok(Self {
+            frequency,
+            interval: interval.unwrap_or(1),
+            count,
+            until,
+            by_day,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dt(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn occurrences(rule: &str, start: &str, n: usize) -> Vec<NaiveDateTime> {
+        rule.parse::<RecurrenceRule>()
+            .unwrap()
+            .occurrences(dt(start))
+            .take(n)
+            .collect()
+    }
+
+    #[test]
+    fn parse_and_format() {
+        for (input, expected) in &[
+            ("FREQ=DAILY", "FREQ=DAILY"),
+            ("RRULE:freq=weekly;interval=1", "FREQ=WEEKLY"),
+            (
+                "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,fr;WKST=MO",
+                "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR",
+            ),
+            ("FREQ=MONTHLY;BYDAY=2TU,-1FR", "FREQ=MONTHLY;BYDAY=2TU,-1FR"),
+            ("FREQ=YEARLY;COUNT=3", "FREQ=YEARLY;COUNT=3"),
+            (
+                "FREQ=DAILY;UNTIL=20201231",
+                "FREQ=DAILY;UNTIL=20201231T235959Z",
+            ),
+            (
+                "FREQ=DAILY;UNTIL=20201231T100000Z",
+                "FREQ=DAILY;UNTIL=20201231T100000Z",
+            ),
+        ] {
+            let rule = input.parse::<RecurrenceRule>().unwrap();
+            assert_eq!(*expected, rule.to_string());
+            assert_eq!(rule, expected.parse().unwrap());
+        }
+    }
+
+    #[test]
+    fn parse_invalid_or_unsupported() {
+        use RecurrenceRuleParseError as E;
+        for (input, expected) in &[
+            ("", E::MissingFrequency),
+            ("INTERVAL=2", E::MissingFrequency),
+            ("FREQ=SOMETIMES", E::InvalidValue("FREQ".into())),
+            ("FREQ=HOURLY", E::Unsupported("FREQ=HOURLY".into())),
+            ("FREQ=DAILY;INTERVAL=0", E::InvalidValue("INTERVAL".into())),
+            ("FREQ=DAILY;COUNT=x", E::InvalidValue("COUNT".into())),
+            ("FREQ=DAILY;UNTIL=2020", E::InvalidValue("UNTIL".into())),
+            ("FREQ=DAILY;FREQ=WEEKLY", E::InvalidValue("FREQ".into())),
+            ("FREQ=WEEKLY;BYDAY=XX", E::InvalidValue("BYDAY".into())),
+            ("FREQ=WEEKLY;BYDAY=1MO", E::Unsupported("BYDAY".into())),
+            ("FREQ=MONTHLY;BYDAY=6MO", E::InvalidValue("BYDAY".into())),
+            ("FREQ=DAILY;BYDAY=MO", E::Unsupported("BYDAY".into())),
+            (
+                "FREQ=MONTHLY;BYMONTHDAY=1",
+                E::Unsupported("BYMONTHDAY".into()),
+            ),
+            (
+                "FREQ=DAILY;COUNT=2;UNTIL=20201231",
+                RecurrenceRuleParseError::CountAndUntil,
+            ),
+        ] {
+            assert_eq!(
+                Err(expected.clone()),
+                input.parse::<RecurrenceRule>(),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn weekly_by_day() {
+        // Thursday
+        let start = "2020-10-15 18:00";
+        assert_eq!(
+            vec![
+                dt("2020-10-15 18:00"),
+                dt("2020-10-19 18:00"),
+                dt("2020-10-22 18:00"),
+                dt("2020-10-26 18:00"),
+            ],
+            occurrences("FREQ=WEEKLY;BYDAY=MO,TH", start, 4)
+        );
+        assert_eq!(
+            vec![
+                dt("2020-10-15 18:00"),
+                dt("2020-10-29 18:00"),
+                dt("2020-11-12 18:00"),
+            ],
+            occurrences("FREQ=WEEKLY;INTERVAL=2", start, 10)[..3].to_vec()
+        );
+    }
+
+    #[test]
+    fn monthly_and_yearly() {
+        assert_eq!(
+            vec![
+                dt("2020-01-31 10:00"),
+                dt("2020-03-31 10:00"),
+                dt("2020-05-31 10:00"),
+            ],
+            occurrences("FREQ=MONTHLY", "2020-01-31 10:00", 3)
+        );
+        assert_eq!(
+            vec![
+                dt("2020-10-13 19:00"),
+                dt("2020-10-30 19:00"),
+                dt("2020-11-10 19:00"),
+                dt("2020-11-27 19:00"),
+            ],
+            occurrences("FREQ=MONTHLY;BYDAY=2TU,-1FR", "2020-10-13 19:00", 4)
+        );
+        assert_eq!(
+            vec![dt("2020-02-29 12:00"), dt("2024-02-29 12:00")],
+            occurrences("FREQ=YEARLY", "2020-02-29 12:00", 2)
+        );
+    }
+
+    #[test]
+    fn count_and_until() {
+        let rule = "FREQ=DAILY;COUNT=3".parse::<RecurrenceRule>().unwrap();
+        let start = dt("2020-10-15 18:00");
+        assert_eq!(3, rule.occurrences(start).count());
+        assert_eq!(Some(dt("2020-10-17 18:00")), rule.last_occurrence(start));
+
+        let rule = "FREQ=WEEKLY;UNTIL=20201029T180000Z"
+            .parse::<RecurrenceRule>()
+            .unwrap();
+        assert_eq!(Some(dt("2020-10-29 18:00")), rule.last_occurrence(start));
+
+        let rule = "FREQ=DAILY".parse::<RecurrenceRule>().unwrap();
+        assert!(!rule.is_finite());
+        assert_eq!(None, rule.last_occurrence(start));
+        assert_eq!(MAX_PERIODS as usize, rule.occurrences(start).count());
+    }
+}
@@ -1,8 +1,210 @@
-use crate::{geo::*, id::*};
+use crate::{category::*, geo::*, id::*, nonce::*, time::*};
+
+use std::{fmt, str::FromStr};
+
+/// How the changes of a subscription are delivered to the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionDelivery {
+    /// One e-mail per change
+    Immediate,
+    /// A single e-mail per day with all changes
+    DailyDigest,
+    /// A single e-mail per week with all changes
+    WeeklyDigest,
+}
+
+impl SubscriptionDelivery {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Immediate => "immediate",
+            Self::DailyDigest => "daily",
+            Self::WeeklyDigest => "weekly",
+        }
+    }
+
+    /// The period between two digests in milliseconds.
+    pub const fn digest_period_ms(self) -> Option<i64> {
+        const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+        match self {
+            Self::Immediate => None,
+            Self::DailyDigest => Some(DAY_MS),
+            Self::WeeklyDigest => Some(7 * DAY_MS),
+        }
+    }
+}
+
+impl Default for SubscriptionDelivery {
+    fn default() -> Self {
+        Self::Immediate
+    }
+}
+
+impl fmt::Display for SubscriptionDelivery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug)]
+pub struct SubscriptionDeliveryParseError;
+
+impl FromStr for SubscriptionDelivery {
+    type Err = SubscriptionDeliveryParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let delivery = match s {
+            "immediate" => Self::Immediate,
+            "daily" => Self::DailyDigest,
+            "weekly" => Self::WeeklyDigest,
+            _ => return Err(SubscriptionDeliveryParseError),
+        };
+        Ok(delivery)
+    }
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct BboxSubscription {
     pub id: Id,
     pub user_email: String,
+    /// An optional name chosen by the user
+    pub title: Option<String>,
+    /// The bounding box of the subscription, i.e. the bounding
+    /// box of the polygon if the subscription covers a region
     pub bbox: MapBbox,
+    /// Only changes of entries within this region
+    pub polygon: Option<MapMultiPolygon>,
+    /// Only changes of entries with at least one of these tags
+    pub tags: Vec<String>,
+    /// Only changes of entries with at least one of these categories
+    pub categories: Vec<Id>,
+    pub delivery: SubscriptionDelivery,
+    /// The end of the period that has been covered by the last digest
+    pub last_digest_at: Option<TimestampMs>,
+    /// The secret part of the unsubscribe token
+    pub unsubscribe_nonce: Nonce,
+}
+
+impl BboxSubscription {
+    /// The token for cancelling this subscription without logging in.
+    pub fn unsubscribe_token(&self) -> SubscriptionNonce {
+        SubscriptionNonce {
+            subscription_id: self.id.clone(),
+            nonce: self.unsubscribe_nonce,
+        }
+    }
+
+    /// Check if a change of an entry at the given position
+    /// and with the given tags is in the scope of this
+    /// subscription.
+    ///
+    /// Categories are expected to be passed as tags. If tags
+    /// or categories are given, at least one of each must match.
+    pub fn is_in_scope<'a>(
+        &self,
+        pos: MapPoint,
+        tags: impl IntoIterator<Item = &'a str> + Clone,
+    ) -> bool {
+        if !self.bbox.contains_point(pos) {
+            return false;
+        }
+        if let Some(ref polygon) = self.polygon {
+            if !polygon.contains_point(pos) {
+                return false;
+            }
+        }
+        let category_tags = Category::merge_ids_into_tags(&self.categories, vec![]);
+        let matches_any = |expected: &[String]| {
+            expected.is_empty()
+                || tags
+                    .clone()
+                    .into_iter()
+                    .any(|tag| expected.iter().any(|t| t == tag))
+        };
+        matches_any(&category_tags) && matches_any(&self.tags)
+    }
+
+    /// Check if a digest is due at the given point in time.
+    pub fn is_digest_due(&self, now: TimestampMs) -> bool {
+        match (self.delivery.digest_period_ms(), self.last_digest_at) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(period), Some(last)) => now.into_inner() - last.into_inner() >= period,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscription(tags: Vec<&str>, categories: Vec<&str>) -> BboxSubscription {
+        BboxSubscription {
+            id: Id::new(),
+            user_email: "test@example.com".into(),
+            title: None,
+            bbox: MapBbox::new(
+                MapPoint::from_lat_lng_deg(0.0, 0.0),
+                MapPoint::from_lat_lng_deg(10.0, 10.0),
+            ),
+            polygon: None,
+            tags: tags.into_iter().map(ToString::to_string).collect(),
+            categories: categories.into_iter().map(Id::from).collect(),
+            delivery: SubscriptionDelivery::Immediate,
+            last_digest_at: None,
+            unsubscribe_nonce: Nonce::new(),
+        }
+    }
+
+    #[test]
+    fn subscription_delivery_round_trip() {
+        for delivery in &[
+            SubscriptionDelivery::Immediate,
+            SubscriptionDelivery::DailyDigest,
+            SubscriptionDelivery::WeeklyDigest,
+        ] {
+            assert_eq!(
+                *delivery,
+                delivery.as_str().parse::<SubscriptionDelivery>().unwrap()
+            );
+        }
+        assert!("monthly".parse::<SubscriptionDelivery>().is_err());
+    }
+
+    #[test]
+    fn scope_of_subscription() {
+        let inside = MapPoint::from_lat_lng_deg(5.0, 5.0);
+        let outside = MapPoint::from_lat_lng_deg(15.0, 5.0);
+
+        let s = subscription(vec![], vec![]);
+        assert!(s.is_in_scope(inside, vec![]));
+        assert!(!s.is_in_scope(outside, vec![]));
+
+        let s = subscription(vec!["foo", "bar"], vec![]);
+        assert!(s.is_in_scope(inside, vec!["bar"]));
+        assert!(!s.is_in_scope(inside, vec!["baz"]));
+        assert!(!s.is_in_scope(outside, vec!["foo"]));
+
+        let s = subscription(vec!["foo"], vec![Category::ID_NON_PROFIT]);
+        assert!(s.is_in_scope(inside, vec![Category::TAG_NON_PROFIT, "foo"]));
+        assert!(!s.is_in_scope(inside, vec![Category::TAG_COMMERCIAL, "foo"]));
+        assert!(!s.is_in_scope(inside, vec![Category::TAG_NON_PROFIT]));
+
+        let mut s = subscription(vec![], vec![]);
+        s.polygon = Some("POLYGON ((0 0, 10 0, 0 10, 0 0))".parse().unwrap());
+        assert!(s.is_in_scope(MapPoint::from_lat_lng_deg(2.0, 2.0), vec![]));
+        assert!(!s.is_in_scope(MapPoint::from_lat_lng_deg(8.0, 8.0), vec![]));
+    }
+
+    #[test]
+    fn digest_is_due_after_period() {
+        let mut s = subscription(vec![], vec![]);
+        let now = TimestampMs::now();
+        assert!(!s.is_digest_due(now));
+        s.delivery = SubscriptionDelivery::DailyDigest;
+        assert!(s.is_digest_due(now));
+        s.last_digest_at = Some(now);
+        assert!(!s.is_digest_due(now));
+        let period = s.delivery.digest_period_ms().unwrap();
+        assert!(s.is_digest_due(TimestampMs::from_inner(now.into_inner() + period)));
+    }
 }
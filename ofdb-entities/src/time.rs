@@ -1,4 +1,5 @@
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{offset::LocalResult, DateTime, Duration, NaiveDateTime, Offset, TimeZone, Utc};
+pub use chrono_tz::Tz;
 use std::fmt;
 
 pub trait InnerTimestampConverter: Clone + Copy + PartialEq + Eq + PartialOrd + Ord {
@@ -111,6 +112,22 @@ pub type Timestamp = GenericTimestamp<SecondsTimestampConverter>;
 
 pub type TimestampMs = GenericTimestamp<MillisecondsTimestampConverter>;
 
+/// Convert a local wall-clock time into UTC.
+///
+/// Ambiguous local times (when the clocks are turned back) resolve
+/// to the earlier instant. Local times that do not exist (when the
+/// clocks are turned forward) are shifted by the length of the gap.
+pub fn local_to_utc(tz: Tz, local: NaiveDateTime) -> NaiveDateTime {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(dt) | LocalResult::Ambiguous(dt, _) => dt.naive_utc(),
+        LocalResult::None => {
+            let before = tz.from_utc_datetime(&(local - Duration::days(1)));
+            let offset = before.offset().fix().local_minus_utc();
+            local - Duration::seconds(offset.into())
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -130,4 +147,28 @@ mod tests {
         let t2 = TimestampMs::from_inner(i1);
         assert_eq!(t1, t2);
     }
+
+    #[test]
+    fn convert_local_time_across_dst_changes() {
+        let tz = chrono_tz::Europe::Berlin;
+        let dt = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        assert_eq!(
+            dt("2020-10-24 16:00"),
+            local_to_utc(tz, dt("2020-10-24 18:00"))
+        );
+        assert_eq!(
+            dt("2020-10-25 17:00"),
+            local_to_utc(tz, dt("2020-10-25 18:00"))
+        );
+        // Ambiguous
+        assert_eq!(
+            dt("2020-10-25 00:30"),
+            local_to_utc(tz, dt("2020-10-25 02:30"))
+        );
+        // Skipped
+        assert_eq!(
+            dt("2020-03-29 01:30"),
+            local_to_utc(tz, dt("2020-03-29 02:30"))
+        );
+    }
 }
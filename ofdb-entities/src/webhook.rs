@@ -0,0 +1,170 @@
+use crate::{geo::*, id::*, time::*};
+
+use std::{fmt, str::FromStr};
+use url::Url;
+
+/// The kind of change that is delivered to webhooks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+    PlaceAdded,
+    PlaceUpdated,
+    PlaceReviewed,
+    EventCreated,
+    EventUpdated,
+}
+
+impl WebhookEvent {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::PlaceAdded => "place_added",
+            Self::PlaceUpdated => "place_updated",
+            Self::PlaceReviewed => "place_reviewed",
+            Self::EventCreated => "event_created",
+            Self::EventUpdated => "event_updated",
+        }
+    }
+}
+
+impl fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug)]
+pub struct WebhookEventParseError;
+
+impl FromStr for WebhookEvent {
+    type Err = WebhookEventParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let event = match s {
+            "place_added" => Self::PlaceAdded,
+            "place_updated" => Self::PlaceUpdated,
+            "place_reviewed" => Self::PlaceReviewed,
+            "event_created" => Self::EventCreated,
+            "event_updated" => Self::EventUpdated,
+            _ => return Err(WebhookEventParseError),
+        };
+        Ok(event)
+    }
+}
+
+/// A webhook of an organization that receives changes of
+/// places and events.
+///
+/// The scope of the subscription is restricted by an optional
+/// bounding box and an optional list of tags.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookSubscription {
+    pub id: Id,
+    pub org_id: Id,
+    pub url: Url,
+    /// The shared secret for signing the payloads
+    pub secret: String,
+    pub bbox: Option<MapBbox>,
+    pub tags: Vec<String>,
+    pub created_at: TimestampMs,
+}
+
+impl WebhookSubscription {
+    /// Check if a change of an entity at the given position
+    /// and with the given tags is in the scope of this
+    /// subscription.
+    ///
+    /// Entities without a position never match a bounding box.
+    /// If tags are given at least one of them must match.
+    pub fn is_in_scope<'a>(
+        &self,
+        pos: Option<MapPoint>,
+        tags: impl IntoIterator<Item = &'a str>,
+    ) -> bool {
+        if let Some(bbox) = self.bbox {
+            if !pos.map(|pos| bbox.contains_point(pos)).unwrap_or(false) {
+                return false;
+            }
+        }
+        self.tags.is_empty()
+            || tags
+                .into_iter()
+                .any(|tag| self.tags.iter().any(|t| t == tag))
+    }
+}
+
+/// A single delivery of a webhook payload including
+/// all (retry) attempts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookDelivery {
+    pub id: Id,
+    pub subscription_id: Id,
+    pub event: WebhookEvent,
+    pub payload: String,
+    pub created_at: TimestampMs,
+    pub attempts: u32,
+    pub last_attempt_at: Option<TimestampMs>,
+    /// The HTTP status code of the last response
+    pub last_status: Option<u16>,
+    /// The error of the last failed attempt
+    pub last_error: Option<String>,
+    pub delivered_at: Option<TimestampMs>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscription(bbox: Option<MapBbox>, tags: Vec<&str>) -> WebhookSubscription {
+        WebhookSubscription {
+            id: Id::new(),
+            org_id: Id::new(),
+            url: "https://example.com/hook".parse().unwrap(),
+            secret: "secret".into(),
+            bbox,
+            tags: tags.into_iter().map(ToString::to_string).collect(),
+            created_at: TimestampMs::now(),
+        }
+    }
+
+    #[test]
+    fn scope_of_subscription() {
+        let bbox = MapBbox::new(
+            MapPoint::from_lat_lng_deg(0.0, 0.0),
+            MapPoint::from_lat_lng_deg(10.0, 10.0),
+        );
+        let inside = Some(MapPoint::from_lat_lng_deg(5.0, 5.0));
+        let outside = Some(MapPoint::from_lat_lng_deg(15.0, 5.0));
+
+        let s = subscription(None, vec![]);
+        assert!(s.is_in_scope(None, vec![]));
+        assert!(s.is_in_scope(outside, vec!["foo"]));
+
+        let s = subscription(Some(bbox), vec![]);
+        assert!(s.is_in_scope(inside, vec![]));
+        assert!(!s.is_in_scope(outside, vec![]));
+        assert!(!s.is_in_scope(None, vec![]));
+
+        let s = subscription(None, vec!["foo", "bar"]);
+        assert!(s.is_in_scope(None, vec!["bar"]));
+        assert!(!s.is_in_scope(inside, vec!["baz"]));
+        assert!(!s.is_in_scope(inside, vec![]));
+
+        let s = subscription(Some(bbox), vec!["foo"]);
+        assert!(s.is_in_scope(inside, vec!["foo"]));
+        assert!(!s.is_in_scope(outside, vec!["foo"]));
+        assert!(!s.is_in_scope(inside, vec!["bar"]));
+    }
+
+    #[test]
+    fn webhook_event_from_str() {
+        for event in &[
+            WebhookEvent::PlaceAdded,
+            WebhookEvent::PlaceUpdated,
+            WebhookEvent::PlaceReviewed,
+            WebhookEvent::EventCreated,
+            WebhookEvent::EventUpdated,
+        ] {
+            assert_eq!(*event, event.as_str().parse().unwrap());
+        }
+        assert!("foo".parse::<WebhookEvent>().is_err());
+    }
+}
@@ -6,3 +6,4 @@ pub mod notify;
 pub mod opencage;
 pub mod sendmail;
 pub mod user_communication;
+pub mod webhook;
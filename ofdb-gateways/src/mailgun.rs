@@ -16,7 +16,7 @@ impl Mailgun {
     fn api_url(&self) -> String {
         format!("https://api.mailgun.net/v3/{}/messages", self.domain)
     }
-    fn send(&self, params: Vec<(&'static str, String)>) {
+    fn send(&self, params: Vec<(String, String)>) {
         let url = self.api_url();
         let key = self.api_key.clone();
         thread::spawn(move || {
@@ -28,7 +28,7 @@ impl Mailgun {
 }
 
 #[cfg(not(test))]
-fn send_raw(url: &str, api_key: &str, params: Vec<(&'static str, String)>) -> Result<()> {
+fn send_raw(url: &str, api_key: &str, params: Vec<(String, String)>) -> Result<()> {
     let client = reqwest::blocking::Client::new();
     let res = client
         .post(url)
@@ -54,13 +54,23 @@ fn send_raw(url: &str, api_key: &str, params: Vec<(&'static str, String)>) -> Re
 /// Don't actually send emails while running the tests or
 /// if the `email` feature is disabled.
 #[cfg(test)]
-fn send_raw(_: &str, _: &str, params: Vec<(&'static str, String)>) -> Result<()> {
+fn send_raw(_: &str, _: &str, params: Vec<(String, String)>) -> Result<()> {
     debug!("Would send e-mail: {:?}", params);
     Ok(())
 }
 
 impl EmailGateway for Mailgun {
     fn compose_and_send(&self, recipients: &[Email], subject: &str, body: &str) {
+        self.compose_and_send_with_headers(recipients, subject, body, &[]);
+    }
+
+    fn compose_and_send_with_headers(
+        &self,
+        recipients: &[Email],
+        subject: &str,
+        body: &str,
+        headers: &[(&str, &str)],
+    ) {
         if recipients.is_empty() {
             warn!("No valid email adresses specified");
             return;
@@ -73,12 +83,18 @@ impl EmailGateway for Mailgun {
             .collect::<Vec<_>>()
             .join(",");
 
-        let params = vec![
-            ("from", (*self.from_email).clone()),
-            ("bcc", recipients),
-            ("subject", subject.to_owned()),
-            ("text", body.to_owned()),
+        let mut params = vec![
+            ("from".to_owned(), (*self.from_email).clone()),
+            ("bcc".to_owned(), recipients),
+            ("subject".to_owned(), subject.to_owned()),
+            ("text".to_owned(), body.to_owned()),
         ];
+        // Custom header fields are prefixed with "h:"
+        params.extend(
+            headers
+                .iter()
+                .map(|(name, value)| (format!("h:{}", name), (*value).to_owned())),
+        );
         self.send(params);
     }
 }
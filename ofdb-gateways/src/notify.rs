@@ -1,9 +1,12 @@
 use crate::user_communication;
 use ofdb_core::gateways::{email::EmailGateway, notify::NotificationGateway};
-use ofdb_entities::{category::*, email::*, event::*, nonce::*, place::*, user::*};
+use ofdb_entities::{
+    category::*, email::*, event::*, nonce::*, place::*, subscription::*, user::*,
+};
 
 pub struct Notify {
     email_gw: Box<dyn EmailGateway + Send + Sync + 'static>,
+    unsubscribe_url: Option<String>,
 }
 
 impl Notify {
@@ -13,104 +16,147 @@ impl Notify {
     {
         Self {
             email_gw: Box::new(gw),
+            unsubscribe_url: None,
         }
     }
+
+    /// Include one-click unsubscribe links into the e-mails
+    /// of subscriptions.
+    ///
+    /// The page at the given URL must confirm GET requests and
+    /// unsubscribe on POST requests (RFC 8058). Without this page
+    /// the e-mails don't contain any unsubscribe links.
+    pub fn with_unsubscribe_url(self, unsubscribe_url: impl Into<String>) -> Self {
+        Self {
+            unsubscribe_url: Some(unsubscribe_url.into()),
+            ..self
+        }
+    }
+
+    fn unsubscribe_url(&self, subscription: &BboxSubscription) -> Option<String> {
+        self.unsubscribe_url.as_ref().map(|url| {
+            format!(
+                "{}?token={}",
+                url,
+                subscription.unsubscribe_token().encode_to_string()
+            )
+        })
+    }
+
+    fn send_to_subscriptions(
+        &self,
+        subscriptions: &[BboxSubscription],
+        compose: impl Fn(Option<&str>) -> user_communication::EmailContent,
+    ) {
+        for subscription in subscriptions {
+            let unsubscribe_url = self.unsubscribe_url(subscription);
+            let content = compose(unsubscribe_url.as_deref());
+            let recipients = [Email::from(subscription.user_email.clone())];
+            if let Some(unsubscribe_url) = unsubscribe_url {
+                let list_unsubscribe = format!("<{}>", unsubscribe_url);
+                self.email_gw.compose_and_send_with_headers(
+                    &recipients,
+                    &content.subject,
+                    &content.body,
+                    &[
+                        ("List-Unsubscribe", list_unsubscribe.as_str()),
+                        ("List-Unsubscribe-Post", "List-Unsubscribe=One-Click"),
+                    ],
+                );
+            } else {
+                self.email_gw
+                    .compose_and_send(&recipients, &content.subject, &content.body);
+            }
+        }
+    }
+}
+
+fn category_names(place: &Place, all_categories: Vec<Category>) -> (Place, Vec<String>) {
+    let mut place = place.clone();
+    let (tags, categories) = Category::split_from_tags(place.tags);
+    place.tags = tags;
+    let category_names = all_categories
+        .into_iter()
+        .filter(|c1| categories.iter().any(|c2| c1.id == c2.id))
+        .map(|c| c.name())
+        .collect();
+    (place, category_names)
 }
 
 impl NotificationGateway for Notify {
     fn place_added(
         &self,
-        email_addresses: &[String],
+        subscriptions: &[BboxSubscription],
         place: &Place,
         all_categories: Vec<Category>,
     ) {
-        let mut place = place.clone();
-        let (tags, categories) = Category::split_from_tags(place.tags);
-        place.tags = tags;
-        let category_names: Vec<String> = all_categories
-            .into_iter()
-            .filter(|c1| categories.iter().any(|c2| c1.id == c2.id))
-            .map(|c| c.name())
-            .collect();
-        let content = user_communication::place_created_email(&place, &category_names);
-
-        {
-            info!(
-                "Sending e-mails to {} recipients after new place {} added",
-                email_addresses.len(),
-                place.id,
-            );
-            compose_and_send_emails(
-                &*self.email_gw,
-                email_addresses,
-                &content.subject,
-                &content.body,
-            );
-        }
+        let (place, category_names) = category_names(place, all_categories);
+        info!(
+            "Sending e-mails to {} recipients after new place {} added",
+            subscriptions.len(),
+            place.id,
+        );
+        self.send_to_subscriptions(subscriptions, |unsubscribe_url| {
+            user_communication::place_created_email(&place, &category_names, unsubscribe_url)
+        });
     }
     fn place_updated(
         &self,
-        email_addresses: &[String],
+        subscriptions: &[BboxSubscription],
         place: &Place,
         all_categories: Vec<Category>,
     ) {
-        let mut place = place.clone();
-        let (tags, categories) = Category::split_from_tags(place.tags);
-        place.tags = tags;
-        let category_names: Vec<String> = all_categories
-            .into_iter()
-            .filter(|c1| categories.iter().any(|c2| c1.id == c2.id))
-            .map(|c| c.name())
-            .collect();
-        let content = user_communication::place_updated_email(&place, &category_names);
-
-        {
-            info!(
-                "Sending e-mails to {} recipients after place {} updated",
-                email_addresses.len(),
-                place.id
-            );
-            compose_and_send_emails(
-                &*self.email_gw,
-                email_addresses,
-                &content.subject,
-                &content.body,
-            );
-        }
+        let (place, category_names) = category_names(place, all_categories);
+        info!(
+            "Sending e-mails to {} recipients after place {} updated",
+            subscriptions.len(),
+            place.id
+        );
+        self.send_to_subscriptions(subscriptions, |unsubscribe_url| {
+            user_communication::place_updated_email(&place, &category_names, unsubscribe_url)
+        });
     }
-    fn event_created(&self, email_addresses: &[String], event: &Event) {
-        let content = user_communication::event_created_email(&event);
-
-        {
-            info!(
-                "Sending e-mails to {} recipients after new event {} created",
-                email_addresses.len(),
-                event.id,
-            );
-            compose_and_send_emails(
-                &*self.email_gw,
-                email_addresses,
-                &content.subject,
-                &content.body,
-            );
-        }
+    fn event_created(&self, subscriptions: &[BboxSubscription], event: &Event) {
+        info!(
+            "Sending e-mails to {} recipients after new event {} created",
+            subscriptions.len(),
+            event.id,
+        );
+        self.send_to_subscriptions(subscriptions, |unsubscribe_url| {
+            user_communication::event_created_email(event, unsubscribe_url)
+        });
     }
-    fn event_updated(&self, email_addresses: &[String], event: &Event) {
-        let content = user_communication::event_updated_email(&event);
-
-        {
-            info!(
-                "Sending e-mails to {} recipients after event {} updated",
-                email_addresses.len(),
-                event.id
-            );
-            compose_and_send_emails(
-                &*self.email_gw,
-                email_addresses,
-                &content.subject,
-                &content.body,
-            );
-        }
+    fn event_updated(&self, subscriptions: &[BboxSubscription], event: &Event) {
+        info!(
+            "Sending e-mails to {} recipients after event {} updated",
+            subscriptions.len(),
+            event.id
+        );
+        self.send_to_subscriptions(subscriptions, |unsubscribe_url| {
+            user_communication::event_updated_email(event, unsubscribe_url)
+        });
+    }
+    fn subscription_digest(
+        &self,
+        subscription: &BboxSubscription,
+        places: &[Place],
+        events: &[Event],
+    ) {
+        info!(
+            "Sending {} digest of subscription {} with {} places and {} events",
+            subscription.delivery,
+            subscription.id,
+            places.len(),
+            events.len(),
+        );
+        self.send_to_subscriptions(std::slice::from_ref(subscription), |unsubscribe_url| {
+            user_communication::subscription_digest_email(
+                subscription.delivery,
+                places,
+                events,
+                unsubscribe_url,
+            )
+        });
     }
     fn user_registered_kvm(&self, user: &User) {
         let token = EmailNonce {
@@ -55,9 +55,19 @@ fn send_raw(email: &str) -> Result<()> {
 
 impl EmailGateway for Sendmail {
     fn compose_and_send(&self, recipients: &[Email], subject: &str, body: &str) {
+        self.compose_and_send_with_headers(recipients, subject, body, &[]);
+    }
+
+    fn compose_and_send_with_headers(
+        &self,
+        recipients: &[Email],
+        subject: &str,
+        body: &str,
+        headers: &[(&str, &str)],
+    ) {
         debug!("Sending e-mails to: {:?}", recipients);
         for to in recipients {
-            match compose(&self.from, &[to], subject, body) {
+            match compose(&self.from, &[to], subject, body, headers) {
                 Ok(email) => {
                     self.send(email);
                 }
@@ -135,7 +145,13 @@ fn encode_header_field(name: &str, input: &str) -> String {
     encoded_output
 }
 
-pub fn compose(from: &str, to: &[&str], subject: &str, body: &str) -> Result<String> {
+pub fn compose(
+    from: &str,
+    to: &[&str],
+    subject: &str,
+    body: &str,
+    headers: &[(&str, &str)],
+) -> Result<String> {
     let to: Vec<_> = to.iter().filter(|m| is_valid_email(m)).cloned().collect();
 
     if to.is_empty() {
@@ -147,11 +163,17 @@ pub fn compose(from: &str, to: &[&str], subject: &str, body: &str) -> Result<Str
 
     let now = Local::now();
 
+    let extra_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}{}", name, value, LINE_BREAK))
+        .collect();
+
     let email = format!(
         "Date:{date}\r\n\
          From:{from}\r\n\
          To:{to}\r\n\
          {subject_header}\r\n\
+         {extra_headers}\
          MIME-Version:1.0\r\n\
          Content-Type:text/plain;charset=utf-8\r\n\r\n\
          {body}",
@@ -159,6 +181,7 @@ pub fn compose(from: &str, to: &[&str], subject: &str, body: &str) -> Result<Str
         from = from,
         to = to.join(","),
         subject_header = encode_header_field("Subject", &subject),
+        extra_headers = extra_headers,
         body = body
     );
 
@@ -178,6 +201,7 @@ mod tests {
             &["mail@test.org"],
             "My veeeeerrrrryyyyy looooonnnnnggggg Subject with äöüÄÖÜß Umlaute and even more characters that are distributed onto multiple lines",
             "Hello Mail",
+            &[],
         ).unwrap();
         let expected = "From:\"OFDB\" <from@ofdb.io>\r\n\
              To:mail@test.org\r\n\
@@ -192,7 +216,27 @@ mod tests {
 
     #[test]
     fn check_addresses() {
-        assert!(compose("from@mail.org", &[], "foo", "bar").is_err());
-        assert!(compose("from", &["not-valid"], "foo", "bar").is_err());
+        assert!(compose("from@mail.org", &[], "foo", "bar", &[]).is_err());
+        assert!(compose("from", &["not-valid"], "foo", "bar", &[]).is_err());
+    }
+
+    #[test]
+    fn create_mail_with_additional_headers() {
+        let mail = compose(
+            "from@ofdb.io",
+            &["mail@test.org"],
+            "Subject",
+            "Hello Mail",
+            &[
+                ("List-Unsubscribe", "<https://ofdb.io/unsubscribe>"),
+                ("List-Unsubscribe-Post", "List-Unsubscribe=One-Click"),
+            ],
+        )
+        .unwrap();
+        let expected = "Subject:=?UTF-8?Q?Subject?=\r\n\
+             List-Unsubscribe:<https://ofdb.io/unsubscribe>\r\n\
+             List-Unsubscribe-Post:List-Unsubscribe=One-Click\r\n\
+             MIME-Version:1.0\r\n";
+        assert!(mail.contains(expected));
     }
 }
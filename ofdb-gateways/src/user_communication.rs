@@ -1,4 +1,4 @@
-use ofdb_entities::{address::*, contact::*, event::*, place::*};
+use ofdb_entities::{address::*, contact::*, event::*, place::*, subscription::*};
 use url::Url;
 
 pub struct EmailContent {
@@ -24,6 +24,14 @@ fn subject_entry_updated(entry_title: &str) -> String {
     format!("Kvm - Eintrag verändert: {}", entry_title)
 }
 
+fn subject_digest(delivery: SubscriptionDelivery, count: usize) -> String {
+    let period = match delivery {
+        SubscriptionDelivery::WeeklyDigest => "Wochenübersicht",
+        _ => "Tagesübersicht",
+    };
+    format!("Kvm - {}: {} Änderungen", period, count)
+}
+
 fn address_line(address: Option<&Address>) -> String {
     if let Some(address) = address {
         let Address {
@@ -45,6 +53,20 @@ fn address_line(address: Option<&Address>) -> String {
     }
 }
 
+// The link is omitted if unsubscribing is not supported
+fn unsubscribe_hint(unsubscribe_url: Option<&str>) -> String {
+    if let Some(url) = unsubscribe_url {
+        format!(
+            "Du kannst dieses Abonnement des Kartenbereichs mit einem Klick abbestellen:
+{}\n
+",
+            url
+        )
+    } else {
+        Default::default()
+    }
+}
+
 pub fn user_registration_email(url: &str) -> EmailContent {
     let subject = "Karte von morgen: Bitte bestätige deine Email-Adresse".into();
     let body = format!(
@@ -75,20 +97,33 @@ das Karte von morgen-Team",
     EmailContent { subject, body }
 }
 
-pub fn place_created_email(place: &Place, category_names: &[String]) -> EmailContent {
+pub fn place_created_email(
+    place: &Place,
+    category_names: &[String],
+    unsubscribe_url: Option<&str>,
+) -> EmailContent {
     let subject = subject_entry_created(&place.title);
-    let body = place_email(place, category_names, INTRO_ENTRY_CREATED);
+    let body = place_email(place, category_names, INTRO_ENTRY_CREATED, unsubscribe_url);
     EmailContent { subject, body }
 }
 
 //TODO: calc diff
-pub fn place_updated_email(place: &Place, category_names: &[String]) -> EmailContent {
+pub fn place_updated_email(
+    place: &Place,
+    category_names: &[String],
+    unsubscribe_url: Option<&str>,
+) -> EmailContent {
     let subject = subject_entry_updated(&place.title);
-    let body = place_email(place, category_names, INTRO_ENTRY_UPDATED);
+    let body = place_email(place, category_names, INTRO_ENTRY_UPDATED, unsubscribe_url);
     EmailContent { subject, body }
 }
 
-fn place_email(place: &Place, category_names: &[String], intro_sentence: &str) -> String {
+fn place_email(
+    place: &Place,
+    category_names: &[String],
+    intro_sentence: &str,
+    unsubscribe_url: Option<&str>,
+) -> String {
     let category = if !category_names.is_empty() {
         category_names[0].clone()
     } else {
@@ -117,13 +152,12 @@ fn place_email(place: &Place, category_names: &[String], intro_sentence: &str) -
     Telefon: {phone}\n
 Eintrag anschauen oder bearbeiten:
 https://kartevonmorgen.org/#/?entry={id}\n
-Du kannst dein Abonnement des Kartenbereichs abbestellen,
-indem du dich auf https://kartevonmorgen.org einloggst.\n
-euphorische Grüße,\n
+{unsubscribe_hint}euphorische Grüße,\n
 das Karte von morgen-Team\n
 {outro_text}",
         intro_sentence = intro_sentence,
         outro_text = OUTRO_HINT,
+        unsubscribe_hint = unsubscribe_hint(unsubscribe_url),
         id = &place.id,
         title = &place.title,
         description = &place.description,
@@ -141,20 +175,20 @@ das Karte von morgen-Team\n
     )
 }
 
-pub fn event_created_email(event: &Event) -> EmailContent {
+pub fn event_created_email(event: &Event, unsubscribe_url: Option<&str>) -> EmailContent {
     let subject = subject_entry_created(&event.title);
-    let body = event_email(event, INTRO_ENTRY_CREATED);
+    let body = event_email(event, INTRO_ENTRY_CREATED, unsubscribe_url);
     EmailContent { subject, body }
 }
 
 //TODO: calc diff
-pub fn event_updated_email(event: &Event) -> EmailContent {
+pub fn event_updated_email(event: &Event, unsubscribe_url: Option<&str>) -> EmailContent {
     let subject = subject_entry_updated(&event.title);
-    let body = event_email(event, INTRO_ENTRY_UPDATED);
+    let body = event_email(event, INTRO_ENTRY_UPDATED, unsubscribe_url);
     EmailContent { subject, body }
 }
 
-fn event_email(event: &Event, intro_sentence: &str) -> String {
+fn event_email(event: &Event, intro_sentence: &str, unsubscribe_url: Option<&str>) -> String {
     let Contact {
         name: _,
         email,
@@ -180,19 +214,18 @@ fn event_email(event: &Event, intro_sentence: &str) -> String {
     Telefon: {phone}\n
 Eintrag anschauen oder bearbeiten:
 https://kartevonmorgen.org/#/?entry={id}\n
-Du kannst dein Abonnement des Kartenbereichs abbestellen,
-indem du dich auf https://kartevonmorgen.org einloggst.\n
-euphorische Grüße,\n
+{unsubscribe_hint}euphorische Grüße,\n
 das Karte von morgen-Team\n
 {outro_text}",
         intro_sentence = intro_sentence,
         outro_text = OUTRO_HINT,
+        unsubscribe_hint = unsubscribe_hint(unsubscribe_url),
         category = "Event",
         id = &event.id,
         title = &event.title,
-        start = event.start.format(DATE_TIME_FORMAT),
+        start = event.local_start().format(DATE_TIME_FORMAT),
         end = event
-            .end
+            .local_end()
             .map(|end| end.format(DATE_TIME_FORMAT).to_string())
             .unwrap_or_default(),
         description = event.description.as_deref().unwrap_or(""),
@@ -205,6 +238,57 @@ das Karte von morgen-Team\n
     )
 }
 
+pub fn subscription_digest_email(
+    delivery: SubscriptionDelivery,
+    places: &[Place],
+    events: &[Event],
+    unsubscribe_url: Option<&str>,
+) -> EmailContent {
+    let subject = subject_digest(delivery, places.len() + events.len());
+    let place_lines: Vec<_> = places
+        .iter()
+        .map(|place| {
+            format!(
+                "- {title}\n  {address_line}\n  https://kartevonmorgen.org/#/?entry={id}",
+                title = place.title,
+                address_line = address_line(place.location.address.as_ref()),
+                id = place.id,
+            )
+        })
+        .collect();
+    let event_lines: Vec<_> = events
+        .iter()
+        .map(|event| {
+            format!(
+                "- {title} ({start})\n  {address_line}\n  https://kartevonmorgen.org/#/?entry={id}",
+                title = event.title,
+                start = event.local_start().format(DATE_TIME_FORMAT),
+                address_line =
+                    address_line(event.location.as_ref().and_then(|l| l.address.as_ref())),
+                id = event.id,
+            )
+        })
+        .collect();
+    let body = format!(
+        "Hallo,\n
+folgende Einträge in deinem abonnierten Kartenbereich wurden erstellt oder verändert:\n
+Einträge ({place_count}):
+{places}\n
+Veranstaltungen ({event_count}):
+{events}\n
+{unsubscribe_hint}euphorische Grüße,\n
+das Karte von morgen-Team\n
+{outro_text}",
+        place_count = places.len(),
+        places = place_lines.join("\n"),
+        event_count = events.len(),
+        events = event_lines.join("\n"),
+        outro_text = OUTRO_HINT,
+        unsubscribe_hint = unsubscribe_hint(unsubscribe_url),
+    );
+    EmailContent { subject, body }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -218,6 +302,8 @@ mod tests {
     // cargo test --tests user_communication -- --nocapture
     // ```
 
+    const UNSUBSCRIBE_URL: &str = "https://openfairdb.org/subscriptions/unsubscribe?token=<token>";
+
     fn print_email(email: &EmailContent) {
         // 72 column ruler
         println!(
@@ -271,9 +357,11 @@ mod tests {
         Event {
             id: "<id>".into(),
             created_by: Some("created_by@example.com".into()),
+            created_at: None,
             archived: None,
             start: Utc::now().naive_utc(),
             end: None,
+            time_zone: Tz::UTC,
             registration: None,
             title: "<title>".into(),
             description: Some("<description>".into()),
@@ -295,6 +383,8 @@ mod tests {
             homepage: Some("https://kartevonmorgen.org".parse().unwrap()),
             image_url: None,
             image_link_url: None,
+            recurrence: None,
+            recurrence_id: None,
             tags: vec!["<tag1>".into(), "<tag2>".into()],
         }
     }
@@ -319,44 +409,73 @@ mod tests {
     #[test]
     fn print_place_created_email() {
         let place = new_place();
-        let email = place_created_email(&place, &["<category>".into()]);
+        let email = place_created_email(&place, &["<category>".into()], Some(UNSUBSCRIBE_URL));
         assert!(email.body.contains(INTRO_ENTRY_CREATED));
         assert!(email.body.contains(OUTRO_HINT));
         assert!(email.body.contains(place.id.as_str()));
         assert!(email.body.contains(&place.title));
+        assert!(email.body.contains(UNSUBSCRIBE_URL));
         print_email(&email);
     }
 
     #[test]
     fn print_place_updated_email() {
         let place = new_place();
-        let email = place_updated_email(&place, &["<category>".into()]);
+        let email = place_updated_email(&place, &["<category>".into()], Some(UNSUBSCRIBE_URL));
         assert!(email.body.contains(INTRO_ENTRY_UPDATED));
         assert!(email.body.contains(OUTRO_HINT));
         assert!(email.body.contains(place.id.as_str()));
         assert!(email.body.contains(&place.title));
+        assert!(email.body.contains(UNSUBSCRIBE_URL));
         print_email(&email);
     }
 
     #[test]
     fn print_event_created_email() {
         let event = new_event();
-        let email = event_created_email(&event);
+        let email = event_created_email(&event, Some(UNSUBSCRIBE_URL));
         assert!(email.body.contains(INTRO_ENTRY_CREATED));
         assert!(email.body.contains(OUTRO_HINT));
         assert!(email.body.contains(event.id.as_str()));
         assert!(email.body.contains(&event.title));
+        assert!(email.body.contains(UNSUBSCRIBE_URL));
         print_email(&email);
     }
 
+    #[test]
+    fn print_subscription_digest_email() {
+        let place = new_place();
+        let event = new_event();
+        let email = subscription_digest_email(
+            SubscriptionDelivery::DailyDigest,
+            &[place.clone()],
+            &[event.clone()],
+            Some(UNSUBSCRIBE_URL),
+        );
+        assert!(email.subject.contains("2"));
+        assert!(email.body.contains(OUTRO_HINT));
+        assert!(email.body.contains(&place.title));
+        assert!(email.body.contains(event.id.as_str()));
+        assert!(email.body.contains(UNSUBSCRIBE_URL));
+        print_email(&email);
+    }
+
+    #[test]
+    fn omit_missing_unsubscribe_link() {
+        let email = event_created_email(&new_event(), None);
+        assert!(!email.body.contains("abbestellen"));
+        assert!(email.body.contains(OUTRO_HINT));
+    }
+
     #[test]
     fn print_event_updated_email() {
         let event = new_event();
-        let email = event_updated_email(&event);
+        let email = event_updated_email(&event, Some(UNSUBSCRIBE_URL));
         assert!(email.body.contains(INTRO_ENTRY_UPDATED));
         assert!(email.body.contains(OUTRO_HINT));
         assert!(email.body.contains(event.id.as_str()));
         assert!(email.body.contains(&event.title));
+        assert!(email.body.contains(UNSUBSCRIBE_URL));
         print_email(&email);
     }
 }
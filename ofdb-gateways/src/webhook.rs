@@ -0,0 +1,87 @@
+use hmac::{Hmac, Mac, NewMac};
+use ofdb_core::gateways::webhook::{WebhookError, WebhookGateway, WebhookRequest};
+use reqwest::header::CONTENT_TYPE;
+use sha2::Sha256;
+use std::time::Duration;
+
+pub const SIGNATURE_HEADER: &str = "X-Ofdb-Signature";
+pub const EVENT_HEADER: &str = "X-Ofdb-Event";
+pub const DELIVERY_HEADER: &str = "X-Ofdb-Delivery";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Calculate the signature of a payload, i.e. the hex encoded
+/// HMAC-SHA256 of the payload prefixed with `sha256=`.
+pub fn signature(secret: &str, payload: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_varkey(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(payload.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Delivers webhook payloads via HTTP(S).
+#[derive(Debug, Clone)]
+pub struct Webhooks {
+    client: reqwest::blocking::Client,
+}
+
+impl Webhooks {
+    pub fn new() -> Self {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            // Redirects could bypass the validation of webhook URLs
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("HTTP client");
+        Self { client }
+    }
+}
+
+impl Default for Webhooks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WebhookGateway for Webhooks {
+    fn deliver(&self, request: &WebhookRequest) -> Result<u16, WebhookError> {
+        let WebhookRequest {
+            url,
+            secret,
+            event,
+            delivery_id,
+            payload,
+        } = request;
+        debug!("Delivering {} to webhook {}", event, url);
+        let res = self
+            .client
+            .post(url.as_str())
+            .header(CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, signature(secret, payload))
+            .header(EVENT_HEADER, *event)
+            .header(DELIVERY_HEADER, *delivery_id)
+            .body(payload.to_string())
+            .send()
+            .map_err(|err| WebhookError::Transport(err.to_string()))?;
+        let status = res.status();
+        if status.is_success() {
+            Ok(status.as_u16())
+        } else {
+            Err(WebhookError::Status(status.as_u16()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_payload() {
+        // Test vector from RFC 4231 (test case 2)
+        assert_eq!(
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            signature("Jefe", "what do ya want for nothing?")
+        );
+    }
+}
@@ -1,12 +1,23 @@
-use crate::core::{entities::*, util::time::Timestamp};
+use crate::core::{entities::*, error::ParameterError, usecases, util::time::Timestamp};
 
+use chrono::NaiveDate;
+use std::{convert::TryFrom, io};
 use url::Url;
 
-#[derive(Debug, Serialize)]
+/// A place record with comma-separated categories and tags.
+///
+/// The columns `id`, `created_at`, `created_by`, `version`
+/// and `avg_rating` are only meaningful for exported places
+/// and may be omitted when importing new places.
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CsvRecord {
+    #[serde(default)]
     pub id: String,
+    #[serde(default)]
     pub created_at: i64,
+    #[serde(default)]
     pub created_by: Option<String>,
+    #[serde(default)]
     pub version: u64,
     pub title: String,
     pub description: String,
@@ -22,12 +33,15 @@ pub struct CsvRecord {
     pub contact_email: Option<String>,
     pub contact_phone: Option<String>,
     pub opening_hours: Option<String>,
+    #[serde(default)]
+    pub opening_hours_normalized: Option<String>,
     pub founded_on: Option<String>,
     pub categories: String,
     pub tags: String,
     pub license: String,
     pub image_url: Option<String>,
     pub image_link_url: Option<String>,
+    #[serde(default)]
     pub avg_rating: f64,
 }
 
@@ -104,6 +118,7 @@ impl From<(Place, Vec<Category>, AvgRatingValue)> for CsvRecord {
             contact_name,
             contact_phone,
             contact_email: contact_email.map(Into::into),
+            opening_hours_normalized: opening_hours.as_ref().and_then(OpeningHours::normalized),
             opening_hours: opening_hours.map(Into::into),
             founded_on: founded_on.as_ref().map(ToString::to_string),
             license,
@@ -116,6 +131,98 @@ impl From<(Place, Vec<Category>, AvgRatingValue)> for CsvRecord {
     }
 }
 
+/// Read all place records from CSV data with a header row.
+///
+/// Each record is returned together with its line number to
+/// report errors for individual rows.
+pub fn read_place_records<R: io::Read>(
+    rdr: R,
+) -> Result<Vec<(u64, Result<CsvRecord, ::csv::Error>)>, ::csv::Error> {
+    let mut rdr = ::csv::Reader::from_reader(rdr);
+    let headers = rdr.headers()?.clone();
+    Ok(rdr
+        .records()
+        .map(|record| match record {
+            Ok(record) => {
+                let line = record.position().map_or(0, ::csv::Position::line);
+                (line, record.deserialize(Some(&headers)))
+            }
+            Err(err) => {
+                let line = err.position().map_or(0, ::csv::Position::line);
+                (line, Err(err))
+            }
+        })
+        .collect())
+}
+
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+impl TryFrom<CsvRecord> for usecases::NewPlace {
+    type Error = ParameterError;
+
+    fn try_from(from: CsvRecord) -> Result<Self, Self::Error> {
+        let CsvRecord {
+            title,
+            description,
+            lat,
+            lng,
+            street,
+            zip,
+            city,
+            country,
+            state,
+            homepage,
+            contact_name,
+            contact_email,
+            contact_phone,
+            opening_hours,
+            founded_on,
+            categories,
+            tags,
+            license,
+            image_url,
+            image_link_url,
+            ..
+        } = from;
+        let founded_on = founded_on
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<NaiveDate>())
+            .transpose()
+            .map_err(|_| ParameterError::InvalidDate)?;
+        Ok(usecases::NewPlace {
+            title,
+            description,
+            lat,
+            lng,
+            street,
+            zip,
+            city,
+            country,
+            state,
+            contact_name,
+            email: contact_email,
+            telephone: contact_phone,
+            homepage,
+            opening_hours,
+            founded_on,
+            categories: split_list(&categories),
+            tags: split_list(&tags),
+            license,
+            image_url,
+            image_link_url,
+            custom_links: vec![],
+        })
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct EventRecord {
     pub id: String,
@@ -125,6 +232,7 @@ pub struct EventRecord {
     pub description: Option<String>,
     pub start: i64,
     pub end: Option<i64>,
+    pub time_zone: String,
     pub lat: Option<f64>,
     pub lng: Option<f64>,
     pub street: Option<String>,
@@ -138,6 +246,8 @@ pub struct EventRecord {
     pub image_url: Option<String>,
     pub image_link_url: Option<String>,
     pub tags: String,
+    pub recurrence: Option<String>,
+    pub recurrence_id: Option<i64>,
 }
 
 impl From<Event> for EventRecord {
@@ -149,12 +259,15 @@ impl From<Event> for EventRecord {
             description,
             start,
             end,
+            time_zone,
             location,
             contact,
             homepage,
             image_url,
             image_link_url,
             tags,
+            recurrence,
+            recurrence_id,
             ..
         } = from;
 
@@ -193,6 +306,7 @@ impl From<Event> for EventRecord {
             description,
             start: Timestamp::from(start).into_seconds(),
             end: end.map(|end| Timestamp::from(end).into_seconds()),
+            time_zone: time_zone.name().to_string(),
             lat,
             lng,
             street,
@@ -207,6 +321,42 @@ impl From<Event> for EventRecord {
             image_url: image_url.map(Url::into_string),
             image_link_url: image_link_url.map(Url::into_string),
             tags: tags.join(","),
+            recurrence: recurrence.map(|r| r.rule.to_string()),
+            recurrence_id: recurrence_id.map(|id| Timestamp::from(id).into_seconds()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_new_places_from_csv() {
+        let data = "\
+title,description,lat,lng,street,zip,city,country,state,homepage,contact_name,contact_email,contact_phone,opening_hours,founded_on,categories,tags,license,image_url,image_link_url
+foo,bar,52.5,13.4,,,Berlin,,,,,,,,2011-05-01,\"2cd00bebec0c48ba9db761da48678134, 77b3c33a92554bcf8e8c2c86cedd6f6f\",\"bio,fair\",CC0-1.0,,
+baz,bar,52.5,13.4,,,,,,,,,,,yesterday,,,CC0-1.0,,
+";
+        let mut records = read_place_records(data.as_bytes()).unwrap().into_iter();
+        let (line, record) = records.next().unwrap();
+        assert_eq!(2, line);
+        let new_place = usecases::NewPlace::try_from(record.unwrap()).unwrap();
+        assert_eq!("foo", new_place.title);
+        assert_eq!(Some("Berlin".to_string()), new_place.city);
+        assert_eq!(None, new_place.street);
+        assert_eq!(Some(NaiveDate::from_ymd(2011, 5, 1)), new_place.founded_on);
+        assert_eq!(
+            vec![
+                "2cd00bebec0c48ba9db761da48678134".to_string(),
+                "77b3c33a92554bcf8e8c2c86cedd6f6f".to_string()
+            ],
+            new_place.categories
+        );
+        assert_eq!(vec!["bio".to_string(), "fair".to_string()], new_place.tags);
+        let (line, record) = records.next().unwrap();
+        assert_eq!(3, line);
+        assert!(usecases::NewPlace::try_from(record.unwrap()).is_err());
+        assert!(records.next().is_none());
+    }
+}
@@ -0,0 +1,500 @@
+//! GeoJSON (RFC 7946) representation of places and events.
+
+use crate::core::{entities::*, error::ParameterError, usecases, util::time::Timestamp};
+
+use chrono::NaiveDate;
+use std::convert::TryFrom;
+use url::Url;
+
+pub const MEDIA_TYPE: (&str, &str) = ("application", "geo+json");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FeatureCollectionType {
+    FeatureCollection,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FeatureType {
+    Feature,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Geometry {
+    Point {
+        /// [longitude, latitude] in degrees
+        coordinates: [f64; 2],
+    },
+    Polygon {
+        /// The exterior ring followed by optional holes
+        coordinates: Vec<Vec<[f64; 2]>>,
+    },
+    MultiPolygon {
+        coordinates: Vec<Vec<Vec<[f64; 2]>>>,
+    },
+}
+
+impl From<MapPoint> for Geometry {
+    fn from(from: MapPoint) -> Self {
+        Geometry::Point {
+            coordinates: [from.lng().to_deg(), from.lat().to_deg()],
+        }
+    }
+}
+
+impl TryFrom<&Geometry> for MapPoint {
+    type Error = ParameterError;
+
+    fn try_from(from: &Geometry) -> Result<Self, Self::Error> {
+        match from {
+            Geometry::Point {
+                coordinates: [lng, lat],
+            } => MapPoint::try_from_lat_lng_deg(*lat, *lng)
+                .map_err(|_| ParameterError::InvalidPosition),
+            _ => Err(ParameterError::InvalidPosition),
+        }
+    }
+}
+
+fn polygon_from_coordinates(rings: &[Vec<[f64; 2]>]) -> Result<MapPolygon, ParameterError> {
+    let mut rings = rings.iter().map(|ring| {
+        ring.iter()
+            .map(|[lng, lat]| {
+                MapPoint::try_from_lat_lng_deg(*lat, *lng)
+                    .map_err(|_| ParameterError::InvalidPolygon)
+            })
+            .collect::<Result<Vec<_>, _>>()
+    });
+    let exterior = rings
+        .next()
+        .unwrap_or(Err(ParameterError::InvalidPolygon))?;
+    let interiors = rings.collect::<Result<_, _>>()?;
+    Ok(MapPolygon::new(exterior, interiors))
+}
+
+impl TryFrom<&Geometry> for MapMultiPolygon {
+    type Error = ParameterError;
+
+    fn try_from(from: &Geometry) -> Result<Self, Self::Error> {
+        let polygon = match from {
+            Geometry::Polygon { coordinates } => polygon_from_coordinates(coordinates)?.into(),
+            Geometry::MultiPolygon { coordinates } => MapMultiPolygon::new(
+                coordinates
+                    .iter()
+                    .map(|rings| polygon_from_coordinates(rings))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Geometry::Point { .. } => return Err(ParameterError::InvalidPolygon),
+        };
+        if !polygon.is_valid() {
+            return Err(ParameterError::InvalidPolygon);
+        }
+        Ok(polygon)
+    }
+}
+
+/// Parses a region that is either given as a GeoJSON geometry
+/// or as Well-Known Text (WKT), i.e. a polygon or a multipolygon.
+pub fn parse_polygon(s: &str) -> Result<MapMultiPolygon, ParameterError> {
+    let s = s.trim();
+    if s.starts_with('{') {
+        let geometry: Geometry =
+            serde_json::from_str(s).map_err(|_| ParameterError::InvalidPolygon)?;
+        MapMultiPolygon::try_from(&geometry)
+    } else {
+        s.parse().map_err(|_| ParameterError::InvalidPolygon)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Feature<T> {
+    #[serde(rename = "type")]
+    pub kind: FeatureType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub geometry: Option<Geometry>,
+    pub properties: T,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureCollection<T> {
+    #[serde(rename = "type")]
+    pub kind: FeatureCollectionType,
+    pub features: Vec<Feature<T>>,
+}
+
+impl<T> From<Vec<Feature<T>>> for FeatureCollection<T> {
+    fn from(features: Vec<Feature<T>>) -> Self {
+        Self {
+            kind: FeatureCollectionType::FeatureCollection,
+            features,
+        }
+    }
+}
+
+impl<T, F> std::iter::FromIterator<F> for FeatureCollection<T>
+where
+    F: Into<Feature<T>>,
+{
+    fn from_iter<I: IntoIterator<Item = F>>(iter: I) -> Self {
+        iter.into_iter().map(Into::into).collect::<Vec<_>>().into()
+    }
+}
+
+/// The properties of a place feature.
+///
+/// Contains the same fields as `csv::CsvRecord`, except for
+/// the position that is stored in the geometry of the feature.
+/// All properties that are only meaningful for exported places
+/// are ignored when importing new places.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaceProperties {
+    #[serde(default)]
+    pub created_at: i64,
+    #[serde(default)]
+    pub created_by: Option<String>,
+    #[serde(default)]
+    pub version: u64,
+    pub title: String,
+    pub description: String,
+    pub street: Option<String>,
+    pub zip: Option<String>,
+    pub city: Option<String>,
+    pub country: Option<String>,
+    pub state: Option<String>,
+    pub homepage: Option<String>,
+    pub contact_name: Option<String>,
+    pub contact_email: Option<String>,
+    pub contact_phone: Option<String>,
+    pub opening_hours: Option<String>,
+    pub founded_on: Option<NaiveDate>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub license: String,
+    pub image_url: Option<String>,
+    pub image_link_url: Option<String>,
+    #[serde(default)]
+    pub avg_rating: f64,
+}
+
+pub type PlaceFeature = Feature<PlaceProperties>;
+
+impl From<(Place, Vec<Category>, AvgRatingValue)> for PlaceFeature {
+    fn from(from: (Place, Vec<Category>, AvgRatingValue)) -> Self {
+        let (place, categories, avg_rating) = from;
+
+        let Place {
+            id,
+            license,
+            revision,
+            created:
+                Activity {
+                    at: created_at,
+                    by: created_by,
+                },
+            title,
+            description,
+            location,
+            links,
+            tags,
+            contact,
+            opening_hours,
+            founded_on,
+            ..
+        } = place;
+
+        let Location { pos, address } = location;
+
+        let Address {
+            street,
+            zip,
+            city,
+            country,
+            state,
+        } = address.unwrap_or_default();
+
+        let (homepage_url, image_url, image_link_url) = if let Some(links) = links {
+            (links.homepage, links.image, links.image_href)
+        } else {
+            (None, None, None)
+        };
+
+        let (contact_name, contact_email, contact_phone) = if let Some(contact) = contact {
+            let Contact { name, phone, email } = contact;
+            (name, email, phone)
+        } else {
+            (None, None, None)
+        };
+
+        let properties = PlaceProperties {
+            created_at: created_at.into_seconds(),
+            created_by: created_by.map(Into::into),
+            version: revision.into(),
+            title,
+            description,
+            street,
+            zip,
+            city,
+            country,
+            state,
+            homepage: homepage_url.map(Url::into_string),
+            contact_name,
+            contact_email: contact_email.map(Into::into),
+            contact_phone,
+            opening_hours: opening_hours.map(Into::into),
+            founded_on,
+            categories: categories.into_iter().map(|c| c.id.into()).collect(),
+            tags,
+            license,
+            image_url: image_url.map(Url::into_string),
+            image_link_url: image_link_url.map(Url::into_string),
+            avg_rating: avg_rating.into(),
+        };
+
+        Feature {
+            kind: FeatureType::Feature,
+            id: Some(id.into()),
+            geometry: Some(pos.into()),
+            properties,
+        }
+    }
+}
+
+impl TryFrom<PlaceFeature> for usecases::NewPlace {
+    type Error = ParameterError;
+
+    fn try_from(from: PlaceFeature) -> Result<Self, Self::Error> {
+        let Feature {
+            geometry,
+            properties,
+            ..
+        } = from;
+        let pos = geometry
+            .as_ref()
+            .ok_or(ParameterError::InvalidPosition)
+            .and_then(MapPoint::try_from)?;
+        let PlaceProperties {
+            title,
+            description,
+            street,
+            zip,
+            city,
+            country,
+            state,
+            homepage,
+            contact_name,
+            contact_email,
+            contact_phone,
+            opening_hours,
+            founded_on,
+            categories,
+            tags,
+            license,
+            image_url,
+            image_link_url,
+            ..
+        } = properties;
+        Ok(usecases::NewPlace {
+            title,
+            description,
+            lat: pos.lat().to_deg(),
+            lng: pos.lng().to_deg(),
+            street,
+            zip,
+            city,
+            country,
+            state,
+            contact_name,
+            email: contact_email,
+            telephone: contact_phone,
+            homepage,
+            opening_hours,
+            founded_on,
+            categories,
+            tags,
+            license,
+            image_url,
+            image_link_url,
+            custom_links: vec![],
+        })
+    }
+}
+
+/// The properties of an event feature.
+///
+/// Contains the same fields as `csv::EventRecord`, except for
+/// the position that is stored in the geometry of the feature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventProperties {
+    pub created_by: Option<String>,
+    pub organizer: Option<String>,
+    pub title: String,
+    pub description: Option<String>,
+    pub start: i64,
+    pub end: Option<i64>,
+    pub street: Option<String>,
+    pub zip: Option<String>,
+    pub city: Option<String>,
+    pub country: Option<String>,
+    pub state: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub homepage: Option<String>,
+    pub image_url: Option<String>,
+    pub image_link_url: Option<String>,
+    pub tags: Vec<String>,
+}
+
+pub type EventFeature = Feature<EventProperties>;
+
+impl From<Event> for EventFeature {
+    fn from(from: Event) -> Self {
+        let Event {
+            id,
+            created_by,
+            title,
+            description,
+            start,
+            end,
+            location,
+            contact,
+            homepage,
+            image_url,
+            image_link_url,
+            tags,
+            ..
+        } = from;
+
+        let (pos, address) = location.map_or((None, None), |l| {
+            let Location { pos, address } = l;
+            if pos.is_valid() {
+                (Some(pos), address)
+            } else {
+                (None, address)
+            }
+        });
+
+        let Address {
+            street,
+            zip,
+            city,
+            country,
+            state,
+        } = address.unwrap_or_default();
+
+        let Contact {
+            name: organizer,
+            email,
+            phone,
+        } = contact.unwrap_or_default();
+
+        let properties = EventProperties {
+            created_by,
+            organizer,
+            title,
+            description,
+            start: Timestamp::from(start).into_seconds(),
+            end: end.map(|end| Timestamp::from(end).into_seconds()),
+            street,
+            zip,
+            city,
+            country,
+            state,
+            email: email.map(Into::into),
+            phone,
+            homepage: homepage.map(Url::into_string),
+            image_url: image_url.map(Url::into_string),
+            image_link_url: image_link_url.map(Url::into_string),
+            tags,
+        };
+
+        Feature {
+            kind: FeatureType::Feature,
+            id: Some(id.into()),
+            geometry: pos.map(Into::into),
+            properties,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_point_geometry() {
+        let pos = MapPoint::from_lat_lng_deg(48.5, 9.25);
+        let json = serde_json::to_value(Geometry::from(pos)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "type": "Point", "coordinates": [9.25, 48.5] })
+        );
+    }
+
+    #[test]
+    fn parse_polygon_from_geojson_or_wkt() {
+        let geojson = r#"{
+            "type": "Polygon",
+            "coordinates": [[[9.0, 48.0], [10.0, 48.0], [10.0, 49.0], [9.0, 48.0]]]
+        }"#;
+        let wkt = "POLYGON ((9 48, 10 48, 10 49, 9 48))";
+        assert_eq!(parse_polygon(geojson).unwrap(), parse_polygon(wkt).unwrap());
+
+        let multi_polygon = parse_polygon(
+            r#"{
+            "type": "MultiPolygon",
+            "coordinates": [
+                [[[9.0, 48.0], [10.0, 48.0], [10.0, 49.0], [9.0, 48.0]]],
+                [[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 0.0]]]
+            ]
+        }"#,
+        )
+        .unwrap();
+        assert_eq!(2, multi_polygon.polygons().len());
+
+        assert!(parse_polygon(r#"{ "type": "Point", "coordinates": [9.0, 48.0] }"#).is_err());
+        assert!(parse_polygon(r#"{ "type": "Polygon", "coordinates": [] }"#).is_err());
+        assert!(parse_polygon("POLYGON ((9 48, 10 48))").is_err());
+    }
+
+    #[test]
+    fn deserialize_place_feature_collection_into_new_places() {
+        let json = r#"{
+            "type": "FeatureCollection",
+            "features": [{
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [13.4, 52.5] },
+                "properties": {
+                    "title": "foo",
+                    "description": "bar",
+                    "license": "CC0-1.0",
+                    "tags": ["bio"]
+                }
+            }, {
+                "type": "Feature",
+                "geometry": null,
+                "properties": {
+                    "title": "foo",
+                    "description": "bar",
+                    "license": "CC0-1.0"
+                }
+            }]
+        }"#;
+        let collection: FeatureCollection<PlaceProperties> = serde_json::from_str(json).unwrap();
+        let mut features = collection.features.into_iter();
+        let new_place = usecases::NewPlace::try_from(features.next().unwrap()).unwrap();
+        assert_eq!("foo", new_place.title);
+        assert_eq!(52.5, new_place.lat);
+        assert_eq!(13.4, new_place.lng);
+        assert_eq!(vec!["bio".to_string()], new_place.tags);
+        assert!(usecases::NewPlace::try_from(features.next().unwrap()).is_err());
+    }
+
+    #[test]
+    fn reject_invalid_collection_type() {
+        let json = r#"{ "type": "Feature", "features": [] }"#;
+        assert!(serde_json::from_str::<FeatureCollection<PlaceProperties>>(json).is_err());
+    }
+}
@@ -0,0 +1,1227 @@
+//! iCalendar (RFC 5545) representation of events.
+
+use crate::core::{entities::*, error::ParameterError, usecases, util::time::Timestamp};
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc};
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+pub const MEDIA_TYPE: (&str, &str) = ("text", "calendar");
+
+const PRODID: &str = "-//openFairDB//openFairDB Events//EN";
+
+// Content lines should not be longer than 75 octets (excluding CRLF)
+const MAX_LINE_OCTETS: usize = 75;
+
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+const LOCAL_DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+const DATE_FORMAT: &str = "%Y%m%d";
+
+/// Create an iCalendar object with a VEVENT component for each event.
+///
+/// Start and end times of events in UTC are written in UTC. All other
+/// events are written in their local time with a TZID and a VTIMEZONE
+/// component, otherwise calendar clients would expand recurrence rules
+/// in UTC. The `dtstamp` is the time when the calendar has been created.
+pub fn calendar_from_events(events: impl IntoIterator<Item = Event>, dtstamp: Timestamp) -> String {
+    let events: Vec<_> = events.into_iter().collect();
+    let dtstamp = NaiveDateTime::from(dtstamp);
+    let mut w = ContentLineWriter::default();
+    w.line("BEGIN", "VCALENDAR");
+    w.line("VERSION", "2.0");
+    w.line("PRODID", PRODID);
+    w.line("CALSCALE", "GREGORIAN");
+    w.line("METHOD", "PUBLISH");
+    for (_, (time_zone, years)) in time_zone_years(&events, dtstamp) {
+        write_time_zone(&mut w, time_zone, years);
+    }
+    let dtstamp = format_date_time(dtstamp);
+    for event in events {
+        write_event(&mut w, event, &dtstamp);
+    }
+    w.line("END", "VCALENDAR");
+    w.buf
+}
+
+// The years that need to be covered by the VTIMEZONE
+// component of each time zone
+fn time_zone_years(
+    events: &[Event],
+    dtstamp: NaiveDateTime,
+) -> BTreeMap<&'static str, (Tz, RangeInclusive<i32>)> {
+    let mut years = BTreeMap::new();
+    for event in events.iter().filter(|e| e.time_zone != Tz::UTC) {
+        let first = event.start.year();
+        let last = match event.last_start() {
+            Some(last_start) => last_start.max(event.end.unwrap_or(last_start)).year(),
+            // Infinitely recurring events are covered until next year
+            None => dtstamp.year() + 1,
+        }
+        .max(first);
+        let (_, range) = years
+            .entry(event.time_zone.name())
+            .or_insert((event.time_zone, first..=last));
+        *range = (*range.start()).min(first)..=(*range.end()).max(last);
+    }
+    years
+}
+
+// Write a VTIMEZONE component with all transitions of the
+// UTC offset within the given years (RFC 5545, 3.6.5)
+fn write_time_zone(w: &mut ContentLineWriter, tz: Tz, years: RangeInclusive<i32>) {
+    let utc_offset = |dt: &NaiveDateTime| tz.offset_from_utc_datetime(dt).fix().local_minus_utc();
+    w.line("BEGIN", "VTIMEZONE");
+    w.line("TZID", tz.name());
+    let mut day = NaiveDate::from_ymd(*years.start(), 1, 1).and_hms(0, 0, 0);
+    let end = NaiveDate::from_ymd(*years.end() + 1, 1, 1).and_hms(0, 0, 0);
+    let mut offset = utc_offset(&day);
+    write_observance(w, tz, "STANDARD", day, offset, offset);
+    while day < end {
+        let next_day = day + Duration::days(1);
+        let next_offset = utc_offset(&next_day);
+        if next_offset != offset {
+            // Find the exact time of the transition
+            let (mut before, mut after) = (day, next_day);
+            while after - before > Duration::seconds(1) {
+                let mid = before + (after - before) / 2;
+                if utc_offset(&mid) == offset {
+                    before = mid;
+                } else {
+                    after = mid;
+                }
+            }
+            let kind = if next_offset > offset {
+                "DAYLIGHT"
+            } else {
+                "STANDARD"
+            };
+            write_observance(w, tz, kind, after, offset, next_offset);
+            offset = next_offset;
+        }
+        day = next_day;
+    }
+    w.line("END", "VTIMEZONE");
+}
+
+fn write_observance(
+    w: &mut ContentLineWriter,
+    tz: Tz,
+    kind: &str,
+    at: NaiveDateTime,
+    offset_from: i32,
+    offset_to: i32,
+) {
+    // The start is given in the local time before the transition
+    let local_start = at + Duration::seconds(offset_from.into());
+    let tz_name = tz.from_utc_datetime(&at).format("%Z").to_string();
+    w.line("BEGIN", kind);
+    w.line(
+        "DTSTART",
+        &local_start.format(LOCAL_DATE_TIME_FORMAT).to_string(),
+    );
+    w.line("TZOFFSETFROM", &format_utc_offset(offset_from));
+    w.line("TZOFFSETTO", &format_utc_offset(offset_to));
+    w.line("TZNAME", &escape_text(&tz_name));
+    w.line("END", kind);
+}
+
+fn format_utc_offset(seconds: i32) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let minutes = seconds.abs() / 60;
+    format!("{}{:02}{:02}", sign, minutes / 60, minutes % 60)
+}
+
+fn write_event(w: &mut ContentLineWriter, event: Event, dtstamp: &str) {
+    // Modified occurrences are written as separate components
+    let modified_occurrences: Vec<_> = match (&event.recurrence, event.recurrence_id) {
+        (Some(recurrence), None) => recurrence
+            .overrides
+            .iter()
+            .filter_map(|o| event.occurrence(o.recurrence_id))
+            .collect(),
+        _ => vec![],
+    };
+    let Event {
+        id,
+        title,
+        description,
+        start,
+        end,
+        time_zone,
+        location,
+        contact,
+        tags,
+        homepage,
+        archived,
+        image_url,
+        recurrence,
+        recurrence_id,
+        ..
+    } = event;
+    let date_time = |name: &str, dt: NaiveDateTime| {
+        if time_zone == Tz::UTC {
+            (name.to_string(), format_date_time(dt))
+        } else {
+            (
+                format!("{};TZID={}", name, time_zone.name()),
+                time_zone
+                    .from_utc_datetime(&dt)
+                    .format(LOCAL_DATE_TIME_FORMAT)
+                    .to_string(),
+            )
+        }
+    };
+    w.line("BEGIN", "VEVENT");
+    w.line("UID", &escape_text(id.as_str()));
+    w.line("DTSTAMP", dtstamp);
+    let (name, value) = date_time("DTSTART", start);
+    w.line(&name, &value);
+    if let Some(end) = end {
+        let (name, value) = date_time("DTEND", end);
+        w.line(&name, &value);
+    }
+    if let Some(recurrence_id) = recurrence_id {
+        let (name, value) = date_time("RECURRENCE-ID", recurrence_id);
+        w.line(&name, &value);
+    } else if let Some(EventRecurrence { rule, overrides }) = recurrence {
+        // UNTIL is always written in UTC
+        w.line("RRULE", &rule.to_string());
+        let exdates = overrides
+            .iter()
+            .filter(|o| o.cancelled)
+            .map(|o| date_time("EXDATE", o.recurrence_id).1)
+            .collect::<Vec<_>>();
+        if !exdates.is_empty() {
+            w.line(&date_time("EXDATE", start).0, &exdates.join(","));
+        }
+    }
+    w.line("SUMMARY", &escape_text(&title));
+    if let Some(description) = description {
+        w.line("DESCRIPTION", &escape_text(&description));
+    }
+    if let Some(Location { pos, address }) = location {
+        if let Some(address) = address.filter(|a| !a.is_empty()) {
+            w.line("LOCATION", &escape_text(&address_text(&address)));
+        }
+        if pos.is_valid() {
+            w.line(
+                "GEO",
+                &format!("{};{}", pos.lat().to_deg(), pos.lng().to_deg()),
+            );
+        }
+    }
+    if let Some(Contact { name, email, phone }) = contact {
+        if let Some(email) = &email {
+            let organizer = if let Some(name) = &name {
+                format!("ORGANIZER;CN={}", param_value(name))
+            } else {
+                "ORGANIZER".to_string()
+            };
+            w.line(&organizer, &format!("mailto:{}", email));
+        }
+        let contact = name
+            .into_iter()
+            .chain(email.map(Into::into))
+            .chain(phone)
+            .collect::<Vec<String>>();
+        if !contact.is_empty() {
+            w.line("CONTACT", &escape_text(&contact.join(", ")));
+        }
+    }
+    if let Some(homepage) = homepage {
+        w.line("URL", homepage.as_str());
+    }
+    if let Some(image_url) = image_url {
+        // RFC 7986
+        w.line("IMAGE;VALUE=URI", image_url.as_str());
+    }
+    if !tags.is_empty() {
+        let categories = tags
+            .iter()
+            .map(|t| escape_text(t))
+            .collect::<Vec<_>>()
+            .join(",");
+        w.line("CATEGORIES", &categories);
+    }
+    let status = if archived.is_some() {
+        "CANCELLED"
+    } else {
+        "CONFIRMED"
+    };
+    w.line("STATUS", status);
+    w.line("END", "VEVENT");
+    for occurrence in modified_occurrences {
+        write_event(w, occurrence, dtstamp);
+    }
+}
+
+fn format_date_time(dt: NaiveDateTime) -> String {
+    DateTime::<Utc>::from_utc(dt, Utc)
+        .format(DATE_TIME_FORMAT)
+        .to_string()
+}
+
+fn address_text(address: &Address) -> String {
+    let Address {
+        street,
+        zip,
+        city,
+        country,
+        state,
+    } = address;
+    let zip_city = zip
+        .iter()
+        .chain(city.iter())
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(" ");
+    street
+        .iter()
+        .map(String::as_str)
+        .chain(Some(zip_city.as_str()))
+        .chain(state.iter().map(String::as_str))
+        .chain(country.iter().map(String::as_str))
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Escape a TEXT value (RFC 5545, 3.3.11).
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Quote a parameter value (RFC 5545, 3.2) that must not contain DQUOTE.
+fn param_value(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', ""))
+}
+
+#[derive(Default)]
+struct ContentLineWriter {
+    buf: String,
+}
+
+impl ContentLineWriter {
+    /// Append a content line that is folded after at most
+    /// 75 octets (RFC 5545, 3.1).
+    fn line(&mut self, name: &str, value: &str) {
+        let mut octets = 0;
+        for c in name.chars().chain(Some(':')).chain(value.chars()) {
+            if octets + c.len_utf8() > MAX_LINE_OCTETS {
+                self.buf.push_str("\r\n ");
+                // The leading space counts as the first octet
+                octets = 1;
+            }
+            self.buf.push(c);
+            octets += c.len_utf8();
+        }
+        self.buf.push_str("\r\n");
+    }
+}
+
+/// A property of a calendar component with its name and
+/// parameter names in upper case.
+///
+/// The value is stored as is, i.e. TEXT values are still escaped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Property {
+    pub name: String,
+    pub params: Vec<(String, String)>,
+    pub value: String,
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn text(&self) -> String {
+        unescape_text(&self.value)
+    }
+}
+
+/// The properties of a VEVENT component.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VEvent {
+    pub properties: Vec<Property>,
+}
+
+impl VEvent {
+    fn property(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
+    fn text(&self, name: &str) -> Option<String> {
+        self.property(name)
+            .map(Property::text)
+            .filter(|t| !t.trim().is_empty())
+    }
+
+    /// The globally unique identifier of the event.
+    pub fn uid(&self) -> Option<String> {
+        self.text("UID")
+    }
+
+    /// Modifies a single occurrence of a recurring event
+    /// with the same UID.
+    pub fn is_modified_occurrence(&self) -> bool {
+        self.property("RECURRENCE-ID").is_some()
+    }
+}
+
+/// Parse all VEVENT components of an iCalendar object.
+///
+/// Components that are nested into a VEVENT, e.g. VALARM,
+/// are ignored.
+pub fn parse_events(ics: &str) -> Result<Vec<VEvent>, ParameterError> {
+    let mut events = vec![];
+    let mut components: Vec<String> = vec![];
+    let mut event: Option<VEvent> = None;
+    for line in unfold_lines(ics) {
+        let property = parse_content_line(&line).ok_or(ParameterError::InvalidCalendar)?;
+        match property.name.as_str() {
+            "BEGIN" => {
+                let component = property.value.trim().to_uppercase();
+                if components.is_empty() && component != "VCALENDAR" {
+                    return Err(ParameterError::InvalidCalendar);
+                }
+                if components.len() == 1 && component == "VEVENT" {
+                    event = Some(VEvent::default());
+                }
+                components.push(component);
+            }
+            "END" => {
+                let component = property.value.trim().to_uppercase();
+                if components.pop().as_ref() != Some(&component) {
+                    return Err(ParameterError::InvalidCalendar);
+                }
+                if components.len() == 1 && component == "VEVENT" {
+                    events.extend(event.take());
+                }
+            }
+            _ => {
+                if components.is_empty() {
+                    return Err(ParameterError::InvalidCalendar);
+                }
+                if components.len() == 2 {
+                    if let Some(event) = event.as_mut() {
+                        event.properties.push(property);
+                    }
+                }
+            }
+        }
+    }
+    if !components.is_empty() {
+        return Err(ParameterError::InvalidCalendar);
+    }
+    Ok(events)
+}
+
+/// Unfold content lines (RFC 5545, 3.1). Bare line feeds
+/// are accepted as line breaks.
+fn unfold_lines(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for line in ics.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(&line[1..]);
+                continue;
+            }
+        }
+        if !line.is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+    lines
+}
+
+fn parse_content_line(line: &str) -> Option<Property> {
+    let mut quoted = false;
+    let mut separators = vec![];
+    let mut value_start = None;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted => separators.push(i),
+            ':' if !quoted => {
+                value_start = Some(i);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let value_start = value_start?;
+    let mut starts = vec![0];
+    starts.extend(separators.iter().map(|i| i + 1));
+    let mut ends = separators;
+    ends.push(value_start);
+    let mut parts = starts.into_iter().zip(ends).map(|(s, e)| &line[s..e]);
+    let name = parts.next()?.trim().to_uppercase();
+    if name.is_empty() {
+        return None;
+    }
+    let params = parts
+        .map(|param| {
+            let mut kv = param.splitn(2, '=');
+            let key = kv.next()?.trim().to_uppercase();
+            let value = kv.next()?.trim().replace('"', "");
+            Some((key, value))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(Property {
+        name,
+        params,
+        value: line[value_start + 1..].to_string(),
+    })
+}
+
+fn unescape_text(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => unescaped.push('\n'),
+                Some(c) => unescaped.push(c),
+                None => unescaped.push('\\'),
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+    unescaped
+}
+
+/// Split a list of TEXT values at all unescaped commas.
+fn split_text_list(text: &str) -> Vec<String> {
+    let mut items = vec![];
+    let mut item = String::new();
+    let mut escaped = false;
+    for c in text.chars() {
+        if escaped {
+            item.push('\\');
+            item.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == ',' {
+            items.push(unescape_text(&item));
+            item.clear();
+        } else {
+            item.push(c);
+        }
+    }
+    items.push(unescape_text(&item));
+    items
+        .into_iter()
+        .map(|i| i.trim().to_string())
+        .filter(|i| !i.is_empty())
+        .collect()
+}
+
+/// Parse a DATE or DATE-TIME value into UTC.
+///
+/// Local times with a TZID parameter are converted from their
+/// time zone. Floating times and dates without a time zone are
+/// local times in the given default time zone.
+fn parse_date_time(
+    property: &Property,
+    default_time_zone: Tz,
+) -> Result<NaiveDateTime, ParameterError> {
+    let value = property.value.trim();
+    if is_utc(property) {
+        return NaiveDateTime::parse_from_str(value, DATE_TIME_FORMAT)
+            .map_err(|_| ParameterError::InvalidDate);
+    }
+    let date_time = if property.param("VALUE") == Some("DATE") || !value.contains('T') {
+        parse_date(value)?.and_hms(0, 0, 0)
+    } else {
+        NaiveDateTime::parse_from_str(value, LOCAL_DATE_TIME_FORMAT)
+            .map_err(|_| ParameterError::InvalidDate)?
+    };
+    Ok(local_to_utc(
+        time_zone(property).unwrap_or(default_time_zone),
+        date_time,
+    ))
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, ParameterError> {
+    NaiveDate::parse_from_str(value.trim(), DATE_FORMAT).map_err(|_| ParameterError::InvalidDate)
+}
+
+/// Date and time values in UTC end with a `Z`.
+fn is_utc(property: &Property) -> bool {
+    property.value.trim().ends_with('Z')
+}
+
+/// The IANA time zone of a local date and time, if any.
+fn time_zone(property: &Property) -> Option<Tz> {
+    property
+        .param("TZID")
+        .and_then(|tzid| tzid.trim_matches('"').trim_start_matches('/').parse().ok())
+}
+
+/// Parse a DURATION value (RFC 5545, 3.3.6).
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (negative, value) = if let Some(value) = value.strip_prefix('-') {
+        (true, value)
+    } else {
+        (false, value.strip_prefix('+').unwrap_or(value))
+    };
+    let value = value.strip_prefix('P')?;
+    let mut duration = Duration::zero();
+    let mut number = String::new();
+    let mut time = false;
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' if number.is_empty() => time = true,
+            _ => {
+                let n = number.parse::<i64>().ok()?;
+                number.clear();
+                duration = duration
+                    + match (c, time) {
+                        ('W', false) => Duration::weeks(n),
+                        ('D', false) => Duration::days(n),
+                        ('H', true) => Duration::hours(n),
+                        ('M', true) => Duration::minutes(n),
+                        ('S', true) => Duration::seconds(n),
+                        _ => return None,
+                    };
+            }
+        }
+    }
+    if !number.is_empty() {
+        return None;
+    }
+    Some(if negative { -duration } else { duration })
+}
+
+struct LocationAddress {
+    street: Option<String>,
+    zip: Option<String>,
+    city: Option<String>,
+    state: Option<String>,
+    country: Option<String>,
+}
+
+/// Split the text of a LOCATION property into the parts of an
+/// address, i.e. the inverse of `address_text()`.
+fn parse_location(text: &str) -> LocationAddress {
+    let parts: Vec<_> = text
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect();
+    let zip_len = |p: &str| p.chars().take_while(char::is_ascii_digit).count();
+    let zip_city_index = parts.iter().position(|p| {
+        let len = zip_len(p);
+        (4..=5).contains(&len) && p[len..].chars().next().map_or(true, char::is_whitespace)
+    });
+    let join = |parts: &[&str]| Some(parts.join(", ")).filter(|p| !p.is_empty());
+    if let Some(i) = zip_city_index {
+        let zip_city = parts[i];
+        let len = zip_len(zip_city);
+        let remaining = &parts[i + 1..];
+        let (state, country) = match remaining {
+            [] => (None, None),
+            [country] => (None, Some(country.to_string())),
+            [state @ .., country] => (join(state), Some(country.to_string())),
+        };
+        LocationAddress {
+            street: join(&parts[..i]),
+            zip: Some(zip_city[..len].to_string()),
+            city: Some(zip_city[len..].trim().to_string()).filter(|c| !c.is_empty()),
+            state,
+            country,
+        }
+    } else {
+        let (street, city) = match parts.as_slice() {
+            [] => (None, None),
+            [city] => (None, Some(city.to_string())),
+            [street @ .., city] => (join(street), Some(city.to_string())),
+        };
+        LocationAddress {
+            street,
+            zip: None,
+            city,
+            state: None,
+            country: None,
+        }
+    }
+}
+
+impl VEvent {
+    /// Map the event. Floating times are local times in
+    /// the default time zone.
+    pub fn to_new_event(
+        &self,
+        default_time_zone: usecases::DefaultEventTimeZone,
+    ) -> Result<usecases::NewEvent, ParameterError> {
+        let dtstart = self
+            .property("DTSTART")
+            .ok_or(ParameterError::InvalidDate)?;
+        let time_zone = if is_utc(dtstart) {
+            Tz::UTC
+        } else {
+            time_zone(dtstart).unwrap_or(default_time_zone.0)
+        };
+        let start = parse_date_time(dtstart, time_zone)?;
+        let end = if let Some(dtend) = self.property("DTEND") {
+            Some(parse_date_time(dtend, time_zone)?)
+        } else if let Some(duration) = self.property("DURATION") {
+            Some(start + parse_duration(&duration.value).ok_or(ParameterError::InvalidDate)?)
+        } else {
+            None
+        };
+        let (lat, lng) = if let Some(geo) = self.property("GEO") {
+            let mut lat_lng = geo.value.splitn(2, ';').map(|v| v.trim().parse::<f64>());
+            match (lat_lng.next(), lat_lng.next()) {
+                (Some(Ok(lat)), Some(Ok(lng))) => (Some(lat), Some(lng)),
+                _ => return Err(ParameterError::InvalidPosition),
+            }
+        } else {
+            (None, None)
+        };
+        let address = self
+            .text("LOCATION")
+            .map(|location| parse_location(&location));
+        let (organizer, email) = if let Some(organizer) = self.property("ORGANIZER") {
+            let value = organizer.value.trim();
+            let email = if value.len() > 7 && value[..7].eq_ignore_ascii_case("mailto:") {
+                Some(value[7..].to_string())
+            } else {
+                None
+            };
+            (organizer.param("CN").map(ToString::to_string), email)
+        } else {
+            (None, None)
+        };
+        let image_url = self
+            .properties
+            .iter()
+            .find(|p| {
+                p.name == "IMAGE"
+                    || (p.name == "ATTACH"
+                        && p.param("FMTTYPE")
+                            .map_or(false, |t| t.to_lowercase().starts_with("image/")))
+            })
+            .map(|p| p.value.trim().to_string());
+        let tags: Vec<_> = self
+            .properties
+            .iter()
+            .filter(|p| p.name == "CATEGORIES")
+            .flat_map(|p| split_text_list(&p.value))
+            .collect();
+        let local_start = time_zone.from_utc_datetime(&start).naive_local();
+        let recurrence = self.property("RRULE").map(|p| p.value.trim().to_string());
+        let mut cancelled_occurrences = vec![];
+        for exdate in self.properties.iter().filter(|p| p.name == "EXDATE") {
+            for value in exdate.value.split(',') {
+                let recurrence_id = if exdate.param("VALUE") == Some("DATE") {
+                    // The occurrence on that day
+                    local_to_utc(time_zone, parse_date(value)?.and_time(local_start.time()))
+                } else {
+                    let property = Property {
+                        value: value.to_string(),
+                        ..exdate.clone()
+                    };
+                    parse_date_time(&property, time_zone)?
+                };
+                cancelled_occurrences.push(usecases::NewOccurrenceOverride {
+                    recurrence_id: recurrence_id.timestamp(),
+                    cancelled: true,
+                    ..Default::default()
+                });
+            }
+        }
+        Ok(usecases::NewEvent {
+            title: self.text("SUMMARY").unwrap_or_default(),
+            description: self.text("DESCRIPTION"),
+            start: start.timestamp(),
+            end: end.map(|end| end.timestamp()),
+            time_zone: Some(time_zone.name().to_string()),
+            lat,
+            lng,
+            street: address.as_ref().and_then(|a| a.street.clone()),
+            zip: address.as_ref().and_then(|a| a.zip.clone()),
+            city: address.as_ref().and_then(|a| a.city.clone()),
+            country: address.as_ref().and_then(|a| a.country.clone()),
+            state: address.as_ref().and_then(|a| a.state.clone()),
+            email,
+            telephone: None,
+            homepage: self.property("URL").map(|p| p.value.trim().to_string()),
+            tags: if tags.is_empty() { None } else { Some(tags) },
+            created_by: None,
+            registration: None,
+            organizer,
+            image_url,
+            image_link_url: None,
+            recurrence,
+            recurrence_overrides: if cancelled_occurrences.is_empty() {
+                None
+            } else {
+                Some(cancelled_occurrences)
+            },
+        })
+    }
+
+    /// Map a modified occurrence of a recurring event. Floating
+    /// times are local times in the default time zone.
+    pub fn to_occurrence_override(
+        &self,
+        default_time_zone: usecases::DefaultEventTimeZone,
+    ) -> Result<usecases::NewOccurrenceOverride, ParameterError> {
+        let usecases::DefaultEventTimeZone(tz) = default_time_zone;
+        let parse = |property: &Property| parse_date_time(property, tz);
+        let recurrence_id = self
+            .property("RECURRENCE-ID")
+            .ok_or(ParameterError::InvalidCalendar)
+            .and_then(parse)?;
+        let start = self.property("DTSTART").map(parse).transpose()?;
+        let end = self.property("DTEND").map(parse).transpose()?;
+        let cancelled = self
+            .property("STATUS")
+            .map_or(false, |p| p.value.trim().eq_ignore_ascii_case("CANCELLED"));
+        Ok(usecases::NewOccurrenceOverride {
+            recurrence_id: recurrence_id.timestamp(),
+            cancelled,
+            start: start.map(|start| start.timestamp()),
+            end: end.map(|end| end.timestamp()),
+            title: self.text("SUMMARY"),
+            description: self.text("DESCRIPTION"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn new_event() -> Event {
+        Event {
+            id: "1234".into(),
+            title: "Repair Café".into(),
+            description: Some("Bring your broken stuff;\nwe fix it, together".into()),
+            start: NaiveDate::from_ymd(2020, 10, 1).and_hms(16, 0, 0),
+            end: Some(NaiveDate::from_ymd(2020, 10, 1).and_hms(18, 30, 0)),
+            time_zone: Tz::UTC,
+            location: Some(Location {
+                pos: MapPoint::from_lat_lng_deg(48.5, 9.25),
+                address: Some(Address {
+                    street: Some("Hauptstr. 1".into()),
+                    zip: Some("72072".into()),
+                    city: Some("Tübingen".into()),
+                    country: Some("Germany".into()),
+                    state: None,
+                }),
+            }),
+            contact: Some(Contact {
+                name: Some("Jane Doe".into()),
+                email: Some("jane@example.com".into()),
+                phone: Some("0123".into()),
+            }),
+            tags: vec!["repair".into(), "bar".into()],
+            homepage: Some("https://example.com/repair".parse().unwrap()),
+            created_by: None,
+            created_at: None,
+            registration: None,
+            archived: None,
+            image_url: Some("https://example.com/image.png".parse().unwrap()),
+            image_link_url: None,
+            recurrence: None,
+            recurrence_id: None,
+        }
+    }
+
+    fn utc() -> usecases::DefaultEventTimeZone {
+        usecases::DefaultEventTimeZone(Tz::UTC)
+    }
+
+    fn dtstamp() -> Timestamp {
+        Timestamp::from(NaiveDate::from_ymd(2020, 9, 30).and_hms(12, 0, 0))
+    }
+
+    #[test]
+    fn write_calendar_with_event() {
+        let ics = calendar_from_events(vec![new_event()], dtstamp());
+        let lines: Vec<_> = ics.split("\r\n").collect();
+        assert_eq!(Some(&"BEGIN:VCALENDAR"), lines.first());
+        assert_eq!(Some(&""), lines.last());
+        assert!(lines.contains(&"BEGIN:VEVENT"));
+        assert!(lines.contains(&"UID:1234"));
+        assert!(lines.contains(&"DTSTAMP:20200930T120000Z"));
+        assert!(lines.contains(&"DTSTART:20201001T160000Z"));
+        assert!(lines.contains(&"DTEND:20201001T183000Z"));
+        assert!(lines.contains(&"SUMMARY:Repair Café"));
+        assert!(lines.contains(&"DESCRIPTION:Bring your broken stuff\\;\\nwe fix it\\, together"));
+        assert!(lines.contains(&"LOCATION:Hauptstr. 1\\, 72072 Tübingen\\, Germany"));
+        assert!(lines.contains(&"GEO:48.5;9.25"));
+        assert!(lines.contains(&"ORGANIZER;CN=\"Jane Doe\":mailto:jane@example.com"));
+        assert!(lines.contains(&"CONTACT:Jane Doe\\, jane@example.com\\, 0123"));
+        assert!(lines.contains(&"URL:https://example.com/repair"));
+        assert!(lines.contains(&"IMAGE;VALUE=URI:https://example.com/image.png"));
+        assert!(lines.contains(&"CATEGORIES:repair,bar"));
+        assert!(lines.contains(&"STATUS:CONFIRMED"));
+        assert!(lines.contains(&"END:VEVENT"));
+        assert_eq!(Some(&"END:VCALENDAR"), lines.iter().rev().nth(1));
+    }
+
+    #[test]
+    fn write_archived_event_as_cancelled() {
+        let event = Event {
+            archived: Some(Timestamp::now()),
+            ..new_event()
+        };
+        let ics = calendar_from_events(vec![event], dtstamp());
+        assert!(ics.contains("\r\nSTATUS:CANCELLED\r\n"));
+    }
+
+    #[test]
+    fn fold_long_lines() {
+        let event = Event {
+            description: Some("ö".repeat(100)),
+            ..new_event()
+        };
+        let ics = calendar_from_events(vec![event], dtstamp());
+        for line in ics.split("\r\n") {
+            assert!(line.len() <= MAX_LINE_OCTETS);
+        }
+        let unfolded = ics.replace("\r\n ", "");
+        assert!(unfolded.contains(&format!("\r\nDESCRIPTION:{}\r\n", "ö".repeat(100))));
+    }
+
+    #[test]
+    fn parse_and_map_events() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+                   VERSION:2.0\r\n\
+                   PRODID:-//Example//Example//EN\r\n\
+                   BEGIN:VTIMEZONE\r\n\
+                   TZID:Europe/Berlin\r\n\
+                   END:VTIMEZONE\r\n\
+                   BEGIN:VEVENT\r\n\
+                   UID:abc@example.com\r\n\
+                   DTSTAMP:20200930T120000Z\r\n\
+                   DTSTART:20201001T160000Z\r\n\
+                   DURATION:PT2H30M\r\n\
+                   SUMMARY:Repair Café\r\n\
+                   DESCRIPTION:Bring your broken stuff\\;\\nwe fix it\\, to\r\n \
+                   gether\r\n\
+                   LOCATION:Hauptstr. 1\\, 72072 Tübingen\\, Germany\r\n\
+                   ORGANIZER;CN=\"Doe; Jane\":MAILTO:jane@example.com\r\n\
+                   URL:https://example.com/repair\r\n\
+                   CATEGORIES:repair,fix\\,it\r\n\
+                   CATEGORIES:bar\r\n\
+                   BEGIN:VALARM\r\n\
+                   ACTION:DISPLAY\r\n\
+                   DESCRIPTION:Reminder\r\n\
+                   END:VALARM\r\n\
+                   END:VEVENT\r\n\
+                   BEGIN:VEVENT\r\n\
+                   UID:def@example.com\r\n\
+                   DTSTART;VALUE=DATE:20201002\r\n\
+                   SUMMARY:All day\r\n\
+                   GEO:48.5;9.25\r\n\
+                   LOCATION:Tübingen\r\n\
+                   END:VEVENT\r\n\
+                   END:VCALENDAR\r\n";
+        let events = parse_events(ics).unwrap();
+        assert_eq!(2, events.len());
+        assert_eq!(Some("abc@example.com".to_string()), events[0].uid());
+
+        let e = events[0].to_new_event(utc()).unwrap();
+        let start = NaiveDate::from_ymd(2020, 10, 1).and_hms(16, 0, 0);
+        assert_eq!(start.timestamp(), e.start);
+        assert_eq!(Some(start.timestamp() + 9000), e.end);
+        assert_eq!("Repair Café", e.title);
+        assert_eq!(
+            Some("Bring your broken stuff;\nwe fix it, together".to_string()),
+            e.description
+        );
+        assert_eq!(Some("Hauptstr. 1".to_string()), e.street);
+        assert_eq!(Some("72072".to_string()), e.zip);
+        assert_eq!(Some("Tübingen".to_string()), e.city);
+        assert_eq!(Some("Germany".to_string()), e.country);
+        assert_eq!(Some("Doe; Jane".to_string()), e.organizer);
+        assert_eq!(Some("jane@example.com".to_string()), e.email);
+        assert_eq!(Some("https://example.com/repair".to_string()), e.homepage);
+        assert_eq!(
+            Some(vec!["repair".to_string(), "fix,it".into(), "bar".into()]),
+            e.tags
+        );
+
+        let e = events[1].to_new_event(utc()).unwrap();
+        assert_eq!(
+            NaiveDate::from_ymd(2020, 10, 2)
+                .and_hms(0, 0, 0)
+                .timestamp(),
+            e.start
+        );
+        assert_eq!(None, e.end);
+        assert_eq!(Some(48.5), e.lat);
+        assert_eq!(Some(9.25), e.lng);
+        assert_eq!(None, e.street);
+        assert_eq!(Some("Tübingen".to_string()), e.city);
+    }
+
+    #[test]
+    fn parse_exported_events() {
+        let ics = calendar_from_events(vec![new_event()], dtstamp());
+        let events = parse_events(&ics).unwrap();
+        assert_eq!(1, events.len());
+        let e = events[0].to_new_event(utc()).unwrap();
+        let event = new_event();
+        assert_eq!(Some("1234".to_string()), events[0].uid());
+        assert_eq!(event.title, e.title);
+        assert_eq!(event.description, e.description);
+        assert_eq!(event.start.timestamp(), e.start);
+        assert_eq!(event.end.map(|end| end.timestamp()), e.end);
+        assert_eq!(Some("Hauptstr. 1".to_string()), e.street);
+        assert_eq!(Some("Jane Doe".to_string()), e.organizer);
+        assert_eq!(
+            Some("https://example.com/image.png".to_string()),
+            e.image_url
+        );
+        assert_eq!(Some(event.tags), e.tags);
+    }
+
+    #[test]
+    fn write_and_parse_recurring_event() {
+        let start = new_event().start;
+        let event = Event {
+            recurrence: Some(EventRecurrence {
+                rule: "FREQ=WEEKLY;COUNT=10".parse().unwrap(),
+                overrides: vec![
+                    OccurrenceOverride {
+                        recurrence_id: start + Duration::weeks(1),
+                        cancelled: true,
+                        start: None,
+                        end: None,
+                        title: None,
+                        description: None,
+                    },
+                    OccurrenceOverride {
+                        recurrence_id: start + Duration::weeks(2),
+                        cancelled: false,
+                        start: Some(start + Duration::weeks(2) + Duration::days(1)),
+                        end: None,
+                        title: Some("Repair Café (Friday)".into()),
+                        description: None,
+                    },
+                ],
+            }),
+            ..new_event()
+        };
+        let ics = calendar_from_events(vec![event], dtstamp());
+        let lines: Vec<_> = ics.split("\r\n").collect();
+        assert!(lines.contains(&"RRULE:FREQ=WEEKLY;COUNT=10"));
+        assert!(lines.contains(&"EXDATE:20201008T160000Z"));
+        assert!(lines.contains(&"RECURRENCE-ID:20201015T160000Z"));
+        assert!(lines.contains(&"DTSTART:20201016T160000Z"));
+        assert!(lines.contains(&"DTEND:20201016T183000Z"));
+        assert!(lines.contains(&"SUMMARY:Repair Café (Friday)"));
+
+        let events = parse_events(&ics).unwrap();
+        assert_eq!(2, events.len());
+        assert!(!events[0].is_modified_occurrence());
+        assert!(events[1].is_modified_occurrence());
+        let e = events[0].to_new_event(utc()).unwrap();
+        assert_eq!(Some("FREQ=WEEKLY;COUNT=10".to_string()), e.recurrence);
+        let overrides = e.recurrence_overrides.unwrap();
+        assert_eq!(1, overrides.len());
+        assert!(overrides[0].cancelled);
+        assert_eq!(
+            (start + Duration::weeks(1)).timestamp(),
+            overrides[0].recurrence_id
+        );
+        let o = events[1].to_occurrence_override(utc()).unwrap();
+        assert!(!o.cancelled);
+        assert_eq!((start + Duration::weeks(2)).timestamp(), o.recurrence_id);
+        assert_eq!(Some("Repair Café (Friday)".to_string()), o.title);
+    }
+
+    #[test]
+    fn write_recurring_event_with_time_zone_across_dst_change() {
+        // Thursdays at 18:00 in Berlin, i.e. 17:00 UTC before and
+        // 16:00 UTC after the change to daylight saving time
+        let start = NaiveDate::from_ymd(2021, 3, 11).and_hms(17, 0, 0);
+        let cancelled = NaiveDate::from_ymd(2021, 4, 1).and_hms(16, 0, 0);
+        let event = Event {
+            start,
+            end: Some(start + Duration::hours(2)),
+            time_zone: chrono_tz::Europe::Berlin,
+            recurrence: Some(EventRecurrence {
+                rule: "FREQ=WEEKLY;COUNT=4".parse().unwrap(),
+                overrides: vec![OccurrenceOverride {
+                    recurrence_id: cancelled,
+                    cancelled: true,
+                    start: None,
+                    end: None,
+                    title: None,
+                    description: None,
+                }],
+            }),
+            ..new_event()
+        };
+        assert!(event.is_recurrence_id(cancelled));
+        let ics = calendar_from_events(vec![event.clone()], dtstamp());
+        let lines: Vec<_> = ics.split("\r\n").collect();
+        assert!(lines.contains(&"DTSTART;TZID=Europe/Berlin:20210311T180000"));
+        assert!(lines.contains(&"DTEND;TZID=Europe/Berlin:20210311T200000"));
+        assert!(lines.contains(&"EXDATE;TZID=Europe/Berlin:20210401T180000"));
+        assert!(!lines.iter().any(|l| l.starts_with("EXDATE:")));
+
+        // The time zone definition contains the change to daylight saving time
+        assert!(lines.contains(&"BEGIN:VTIMEZONE"));
+        assert!(lines.contains(&"TZID:Europe/Berlin"));
+        let daylight = lines.iter().position(|l| *l == "BEGIN:DAYLIGHT").unwrap();
+        assert_eq!(
+            &[
+                "DTSTART:20210328T020000",
+                "TZOFFSETFROM:+0100",
+                "TZOFFSETTO:+0200",
+                "TZNAME:CEST",
+                "END:DAYLIGHT",
+            ],
+            &lines[daylight + 1..daylight + 6]
+        );
+
+        // The EXDATE still matches its occurrence after importing
+        let events = parse_events(&ics).unwrap();
+        let e = events[0].to_new_event(utc()).unwrap();
+        assert_eq!(Some("Europe/Berlin".to_string()), e.time_zone);
+        assert_eq!(start.timestamp(), e.start);
+        let overrides = e.recurrence_overrides.unwrap();
+        assert_eq!(1, overrides.len());
+        assert_eq!(cancelled.timestamp(), overrides[0].recurrence_id);
+    }
+
+    #[test]
+    fn parse_local_times_with_time_zone() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+                   BEGIN:VEVENT\r\n\
+                   UID:abc@example.com\r\n\
+                   DTSTART;TZID=Europe/Berlin:20201022T180000\r\n\
+                   DTEND;TZID=Europe/Berlin:20201022T200000\r\n\
+                   RRULE:FREQ=WEEKLY\r\n\
+                   EXDATE;VALUE=DATE:20201029\r\n\
+                   SUMMARY:Repair Café\r\n\
+                   END:VEVENT\r\n\
+                   END:VCALENDAR\r\n";
+        let events = parse_events(ics).unwrap();
+        let e = events[0].to_new_event(utc()).unwrap();
+        assert_eq!(Some("Europe/Berlin".to_string()), e.time_zone);
+        assert_eq!(
+            NaiveDate::from_ymd(2020, 10, 22)
+                .and_hms(16, 0, 0)
+                .timestamp(),
+            e.start
+        );
+        assert_eq!(Some(e.start + 7200), e.end);
+        // Daylight saving time has ended in the meantime
+        assert_eq!(
+            NaiveDate::from_ymd(2020, 10, 29)
+                .and_hms(17, 0, 0)
+                .timestamp(),
+            e.recurrence_overrides.unwrap()[0].recurrence_id
+        );
+    }
+
+    #[test]
+    fn parse_floating_times_in_default_time_zone() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+                   BEGIN:VEVENT\r\n\
+                   UID:abc@example.com\r\n\
+                   DTSTART:20201022T180000\r\n\
+                   DTEND:20201022T200000\r\n\
+                   RRULE:FREQ=WEEKLY\r\n\
+                   EXDATE:20201029T180000\r\n\
+                   SUMMARY:Repair Café\r\n\
+                   END:VEVENT\r\n\
+                   BEGIN:VEVENT\r\n\
+                   UID:def@example.com\r\n\
+                   DTSTART:20201022T180000Z\r\n\
+                   SUMMARY:Repair Café (UTC)\r\n\
+                   END:VEVENT\r\n\
+                   END:VCALENDAR\r\n";
+        let berlin = usecases::DefaultEventTimeZone(chrono_tz::Europe::Berlin);
+        let events = parse_events(ics).unwrap();
+
+        let e = events[0].to_new_event(berlin).unwrap();
+        assert_eq!(Some("Europe/Berlin".to_string()), e.time_zone);
+        assert_eq!(
+            NaiveDate::from_ymd(2020, 10, 22)
+                .and_hms(16, 0, 0)
+                .timestamp(),
+            e.start
+        );
+        assert_eq!(Some(e.start + 7200), e.end);
+        // Daylight saving time has ended in the meantime
+        assert_eq!(
+            NaiveDate::from_ymd(2020, 10, 29)
+                .and_hms(17, 0, 0)
+                .timestamp(),
+            e.recurrence_overrides.unwrap()[0].recurrence_id
+        );
+
+        // Times in UTC are not affected by the default time zone
+        let e = events[1].to_new_event(berlin).unwrap();
+        assert_eq!(Some("UTC".to_string()), e.time_zone);
+        assert_eq!(
+            NaiveDate::from_ymd(2020, 10, 22)
+                .and_hms(18, 0, 0)
+                .timestamp(),
+            e.start
+        );
+    }
+
+    #[test]
+    fn reject_invalid_calendars() {
+        assert!(parse_events("BEGIN:VEVENT\r\nEND:VEVENT\r\n").is_err());
+        assert!(parse_events("BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nEND:VCALENDAR\r\n").is_err());
+        assert!(parse_events("BEGIN:VCALENDAR\r\nno content line\r\nEND:VCALENDAR\r\n").is_err());
+        assert!(parse_events("BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn reject_events_without_start() {
+        let events = parse_events(
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:1\r\nSUMMARY:foo\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+        )
+        .unwrap();
+        assert!(events[0].to_new_event(utc()).is_err());
+    }
+
+    #[test]
+    fn parse_durations() {
+        assert_eq!(Some(Duration::weeks(2)), parse_duration("P2W"));
+        assert_eq!(
+            Some(Duration::days(1) + Duration::minutes(30)),
+            parse_duration("P1DT30M")
+        );
+        assert_eq!(Some(-Duration::seconds(5)), parse_duration("-PT5S"));
+        assert_eq!(None, parse_duration("PT5"));
+        assert_eq!(None, parse_duration("1H"));
+    }
+}
@@ -1,5 +1,9 @@
-use crate::core::{db::IndexedPlace, entities as e, usecases};
+use crate::core::{
+    db::{IndexedPlace, PlaceFacets, PlaceSuggestion, PlaceSuggestionKind},
+    entities as e, usecases,
+};
 
+use ofdb_core::cluster;
 use url::Url;
 
 pub use ofdb_boundary::*;
@@ -11,6 +15,61 @@ impl From<Credentials> for usecases::Login {
     }
 }
 
+impl From<cluster::PlaceCluster> for PlaceCluster {
+    fn from(from: cluster::PlaceCluster) -> Self {
+        let cluster::PlaceCluster {
+            pos,
+            bbox,
+            count,
+            categories,
+            ..
+        } = from;
+        Self {
+            lat: pos.lat().to_deg(),
+            lng: pos.lng().to_deg(),
+            count,
+            bbox: bbox.into(),
+            categories: categories
+                .into_iter()
+                .map(|(id, count)| (id.to_string(), count))
+                .collect(),
+        }
+    }
+}
+
+impl From<PlaceFacets> for SearchFacets {
+    fn from(from: PlaceFacets) -> Self {
+        let PlaceFacets {
+            tags,
+            categories,
+            status,
+        } = from;
+        Self {
+            tags: tags.into_iter().map(Into::into).collect(),
+            categories: categories
+                .into_iter()
+                .map(|(id, count)| (id.to_string(), count))
+                .collect(),
+            status: status
+                .into_iter()
+                .map(|(status, count)| ReviewStatusFrequency(status.into(), count))
+                .collect(),
+        }
+    }
+}
+
+impl From<PlaceSuggestion> for SearchSuggestion {
+    fn from(from: PlaceSuggestion) -> Self {
+        let PlaceSuggestion { kind, text, count } = from;
+        let kind = match kind {
+            PlaceSuggestionKind::Title => SearchSuggestionKind::Title,
+            PlaceSuggestionKind::Tag => SearchSuggestionKind::Tag,
+            PlaceSuggestionKind::City => SearchSuggestionKind::City,
+        };
+        Self { text, kind, count }
+    }
+}
+
 impl From<IndexedPlace> for PlaceSearchResult {
     fn from(from: IndexedPlace) -> Self {
         let IndexedPlace {
@@ -59,6 +118,7 @@ impl From<IndexedPlace> for PlaceSearchResult {
             categories,
             tags,
             ratings,
+            distance: None,
         }
     }
 }
@@ -228,6 +288,8 @@ pub fn entry_from_place_with_ratings(place: e::Place, ratings: Vec<e::Rating>) -
 
     let (tags, categories) = e::Category::split_from_tags(tags);
 
+    let opening_hours_normalized = opening_hours.as_ref().and_then(e::OpeningHours::normalized);
+
     Entry {
         id: id.into(),
         created: created.at.into_seconds(),
@@ -246,6 +308,7 @@ pub fn entry_from_place_with_ratings(place: e::Place, ratings: Vec<e::Rating>) -
         telephone,
         homepage: homepage_url.map(Url::into_string),
         opening_hours: opening_hours.map(Into::into),
+        opening_hours_normalized,
         founded_on: founded_on.map(Into::into),
         categories: categories.into_iter().map(|c| c.id.to_string()).collect(),
         tags,
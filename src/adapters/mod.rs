@@ -1,2 +1,5 @@
 pub mod csv;
+pub mod geojson;
+pub mod ical;
 pub mod json;
+pub mod mvt;
@@ -0,0 +1,295 @@
+//! Mapbox Vector Tiles (MVT 2.1) of places and events.
+//!
+//! The tile is encoded as a Protocol Buffers message with
+//! one layer for places and another layer for events. Each
+//! place or event is represented by a point feature.
+
+use crate::core::{db::IndexedPlace, entities::*};
+use ofdb_core::tile::TileId;
+
+use std::collections::HashMap;
+
+pub const MEDIA_TYPE: (&str, &str) = ("application", "vnd.mapbox-vector-tile");
+
+pub const PLACES_LAYER_NAME: &str = "places";
+
+pub const EVENTS_LAYER_NAME: &str = "events";
+
+const MVT_VERSION: u64 = 2;
+
+const EXTENT: u32 = 4096;
+
+// The number of tags per feature is limited to
+// keep the size of the tiles small
+const MAX_TAGS_PER_FEATURE: usize = 5;
+
+// Protobuf wire types
+const WIRE_TYPE_VARINT: u32 = 0;
+const WIRE_TYPE_64BIT: u32 = 1;
+const WIRE_TYPE_LENGTH_DELIMITED: u32 = 2;
+
+// Geometry types and commands
+const GEOM_TYPE_POINT: u64 = 1;
+const CMD_MOVE_TO: u32 = 1;
+
+/// Encode all places and events that are located within
+/// the tile.
+///
+/// Positions outside of the tile are skipped.
+pub fn encode_tile(tile: TileId, places: Vec<IndexedPlace>, events: Vec<Event>) -> Vec<u8> {
+    let mut places_layer = LayerBuilder::new(PLACES_LAYER_NAME);
+    for place in places {
+        let IndexedPlace {
+            id,
+            pos,
+            title,
+            tags,
+            ratings,
+            ..
+        } = place;
+        let (tags, categories) = Category::split_from_tags(tags);
+        places_layer.add_point(
+            tile.project(pos, EXTENT),
+            vec![
+                ("id", Value::String(id)),
+                ("title", Value::String(title)),
+                ("categories", Value::String(join_categories(&categories))),
+                ("tags", Value::String(join_top_tags(&tags))),
+                ("total_rating", Value::Double(ratings.total().into())),
+            ],
+        );
+    }
+    let mut events_layer = LayerBuilder::new(EVENTS_LAYER_NAME);
+    for event in events {
+        let pos = match event.location {
+            Some(ref location) if location.pos.is_valid() => location.pos,
+            _ => continue,
+        };
+        let Event {
+            id, title, tags, ..
+        } = event;
+        events_layer.add_point(
+            tile.project(pos, EXTENT),
+            vec![
+                ("id", Value::String(id.to_string())),
+                ("title", Value::String(title)),
+                ("categories", Value::String(Category::ID_EVENT.to_string())),
+                ("tags", Value::String(join_top_tags(&tags))),
+            ],
+        );
+    }
+    let mut buf = Vec::new();
+    for layer in &[places_layer, events_layer] {
+        if !layer.is_empty() {
+            write_bytes_field(&mut buf, 3, &layer.encode());
+        }
+    }
+    buf
+}
+
+fn join_categories(categories: &[Category]) -> String {
+    categories
+        .iter()
+        .map(|c| c.id.as_str())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn join_top_tags(tags: &[String]) -> String {
+    tags.iter()
+        .take(MAX_TAGS_PER_FEATURE)
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    String(String),
+    Double(f64),
+}
+
+impl Value {
+    fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        match self {
+            Self::String(s) => write_bytes_field(&mut buf, 1, s.as_bytes()),
+            Self::Double(d) => {
+                write_key(&mut buf, 3, WIRE_TYPE_64BIT);
+                buf.extend_from_slice(&d.to_le_bytes());
+            }
+        }
+        buf
+    }
+}
+
+// Features share the keys and values of their properties
+// that are stored only once per layer.
+struct LayerBuilder {
+    name: &'static str,
+    keys: Vec<&'static str>,
+    key_indexes: HashMap<&'static str, u32>,
+    values: Vec<Vec<u8>>,
+    value_indexes: HashMap<Vec<u8>, u32>,
+    features: Vec<Vec<u8>>,
+}
+
+impl LayerBuilder {
+    fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            keys: Default::default(),
+            key_indexes: Default::default(),
+            values: Default::default(),
+            value_indexes: Default::default(),
+            features: Default::default(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
+    fn key_index(&mut self, key: &'static str) -> u32 {
+        let keys = &mut self.keys;
+        *self.key_indexes.entry(key).or_insert_with(|| {
+            keys.push(key);
+            (keys.len() - 1) as u32
+        })
+    }
+
+    fn value_index(&mut self, value: &Value) -> u32 {
+        let values = &mut self.values;
+        let encoded = value.encode();
+        *self
+            .value_indexes
+            .entry(encoded.clone())
+            .or_insert_with(|| {
+                values.push(encoded);
+                (values.len() - 1) as u32
+            })
+    }
+
+    fn add_point(&mut self, (x, y): (i32, i32), properties: Vec<(&'static str, Value)>) {
+        if x < 0 || y < 0 || x >= EXTENT as i32 || y >= EXTENT as i32 {
+            return;
+        }
+        let mut tags = Vec::with_capacity(2 * properties.len());
+        for (key, value) in properties {
+            tags.push(self.key_index(key));
+            tags.push(self.value_index(&value));
+        }
+        let geometry = [command_integer(CMD_MOVE_TO, 1), zigzag(x), zigzag(y)];
+        let mut feature = Vec::new();
+        write_packed_field(&mut feature, 2, &tags);
+        write_key(&mut feature, 3, WIRE_TYPE_VARINT);
+        write_varint(&mut feature, GEOM_TYPE_POINT);
+        write_packed_field(&mut feature, 4, &geometry);
+        self.features.push(feature);
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_key(&mut buf, 15, WIRE_TYPE_VARINT);
+        write_varint(&mut buf, MVT_VERSION);
+        write_bytes_field(&mut buf, 1, self.name.as_bytes());
+        for feature in &self.features {
+            write_bytes_field(&mut buf, 2, feature);
+        }
+        for key in &self.keys {
+            write_bytes_field(&mut buf, 3, key.as_bytes());
+        }
+        for value in &self.values {
+            write_bytes_field(&mut buf, 4, value);
+        }
+        write_key(&mut buf, 5, WIRE_TYPE_VARINT);
+        write_varint(&mut buf, EXTENT.into());
+        buf
+    }
+}
+
+const fn command_integer(id: u32, count: u32) -> u32 {
+    (id & 0x7) | (count << 3)
+}
+
+const fn zigzag(n: i32) -> u32 {
+    ((n << 1) ^ (n >> 31)) as u32
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn write_key(buf: &mut Vec<u8>, field_number: u32, wire_type: u32) {
+    write_varint(buf, u64::from((field_number << 3) | wire_type));
+}
+
+fn write_bytes_field(buf: &mut Vec<u8>, field_number: u32, bytes: &[u8]) {
+    write_key(buf, field_number, WIRE_TYPE_LENGTH_DELIMITED);
+    write_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+fn write_packed_field(buf: &mut Vec<u8>, field_number: u32, values: &[u32]) {
+    let mut packed = Vec::with_capacity(values.len());
+    for value in values {
+        write_varint(&mut packed, (*value).into());
+    }
+    write_bytes_field(buf, field_number, &packed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_varints() {
+        let mut buf = Vec::new();
+        write_varint(&mut buf, 1);
+        write_varint(&mut buf, 300);
+        assert_eq!(vec![0x01, 0xAC, 0x02], buf);
+    }
+
+    #[test]
+    fn encode_point_geometry() {
+        // Example from the MVT specification
+        assert_eq!(
+            [9, 50, 34],
+            [command_integer(CMD_MOVE_TO, 1), zigzag(25), zigzag(17)]
+        );
+        assert_eq!(1, zigzag(-1));
+    }
+
+    #[test]
+    fn encode_places_within_tile() {
+        let tile = TileId::new(0, 0, 0).unwrap();
+        let place = |id: &str, title: &str| IndexedPlace {
+            id: id.into(),
+            title: title.into(),
+            tags: vec![Category::TAG_NON_PROFIT.into(), "foo".into()],
+            pos: MapPoint::from_lat_lng_deg(48.78, 9.18),
+            ..Default::default()
+        };
+        let data = encode_tile(tile, vec![place("a", "foo"), place("b", "foo")], vec![]);
+        // A single layer
+        assert_eq!((3 << 3) | WIRE_TYPE_LENGTH_DELIMITED, u32::from(data[0]));
+        let contains = |needle: &[u8]| data.windows(needle.len()).any(|w| w == needle);
+        assert!(contains(PLACES_LAYER_NAME.as_bytes()));
+        assert!(!contains(EVENTS_LAYER_NAME.as_bytes()));
+        assert!(contains(Category::ID_NON_PROFIT.as_bytes()));
+        assert!(contains(b"total_rating"));
+        // Values are shared between features and properties
+        assert_eq!(1, data.windows(3).filter(|w| w == b"foo").count());
+
+        assert!(encode_tile(
+            TileId::new(1, 0, 0).unwrap(),
+            vec![place("a", "foo")],
+            vec![]
+        )
+        .is_empty());
+    }
+}
@@ -3,12 +3,14 @@ use super::{
     error::RepoError,
     repositories::*,
     util::{
-        geo::{MapBbox, MapPoint},
+        geo::{MapBbox, MapCircle, MapMultiPolygon, MapPoint},
         time::{Timestamp, TimestampMs},
     },
 };
 
 use anyhow::Result as Fallible;
+use chrono::NaiveDateTime;
+use ofdb_core::cluster::PlaceCluster;
 
 type Result<T> = std::result::Result<T, RepoError>;
 
@@ -31,6 +33,14 @@ pub trait PlaceRepo {
     fn all_places(&self) -> Result<Vec<(Place, ReviewStatus)>>;
     fn count_places(&self) -> Result<usize>;
 
+    // Load the current revisions of all places in chunks,
+    // ordered by their creation
+    fn load_places_chunk(&self, pagination: &Pagination) -> Result<Vec<(Place, ReviewStatus)>>;
+
+    // The ids of all places with a new revision, review or
+    // rating since the given point in time (inclusive)
+    fn changed_place_ids_since(&self, since: TimestampMs) -> Result<Vec<Id>>;
+
     fn recently_changed_places(
         &self,
         params: &RecentlyChangedEntriesParams,
@@ -52,6 +62,10 @@ pub trait PlaceRepo {
 
     fn create_or_update_place(&self, place: Place) -> Result<()>;
 
+    // Create a new revision of a place and record the given activity
+    // instead of the default log entry, e.g. when reverting a place
+    fn create_place_revision_with_log(&self, place: Place, activity: &ActivityLog) -> Result<()>;
+
     fn get_place_history(&self, id: &str, revision: Option<Revision>) -> Result<PlaceHistory>;
 
     fn load_place_revision(&self, id: &str, rev: Revision) -> Result<(Place, ReviewStatus)>;
@@ -67,12 +81,33 @@ pub trait EventGateway {
 
     fn all_events_chronologically(&self) -> Result<Vec<Event>>;
 
+    // Load all archived events that start within the given period,
+    // including recurring events that started before
+    fn archived_events_chronologically(
+        &self,
+        start_min: Timestamp,
+        start_max: Option<Timestamp>,
+    ) -> Result<Vec<Event>>;
+
+    // Load all events that have not been archived in chunks,
+    // ordered by their creation
+    fn load_events_chunk(&self, pagination: &Pagination) -> Result<Vec<Event>>;
+
+    // The ids of all events that have been created, updated or
+    // archived since the given point in time (inclusive)
+    fn changed_event_ids_since(&self, since: TimestampMs) -> Result<Vec<Id>>;
+
     fn count_events(&self) -> Result<usize>;
     // Delete an event, but only if tagged with at least one of the given tags
     // Ok(Some(())) => Found and deleted
     // Ok(None)     => No matching tags
     // TODO: Use explicit result semantics
     fn delete_event_with_matching_tags(&self, id: &str, tags: &[&str]) -> Result<Option<()>>;
+
+    // The UIDs of events that have been imported by an organization
+    // from an external calendar
+    fn get_event_id_by_import_uid(&self, org_id: &Id, import_uid: &str) -> Result<Option<Id>>;
+    fn create_event_import_uid(&self, org_id: &Id, import_uid: &str, event_id: &Id) -> Result<()>;
 }
 
 pub trait UserGateway {
@@ -122,6 +157,73 @@ pub trait PlaceClearanceRepo {
     fn cleanup_pending_clearances_for_places(&self, org_id: &Id) -> Result<u64>;
 }
 
+pub trait PlaceOwnershipRepo {
+    // Places without owners have an empty ownership
+    fn load_place_ownership(&self, place_id: &Id) -> Result<PlaceOwnership>;
+    fn replace_place_ownership(&self, place_id: &Id, ownership: &PlaceOwnership) -> Result<()>;
+}
+
+pub trait PlaceProposalRepo {
+    fn add_place_proposal(&self, proposal: &PlaceProposal) -> Result<()>;
+    fn get_place_proposal(&self, id: &Id) -> Result<PlaceProposal>;
+    fn review_place_proposal(
+        &self,
+        id: &Id,
+        status: ProposalStatus,
+        activity: &ActivityLog,
+    ) -> Result<()>;
+    // All proposals for a single place, oldest first
+    fn list_place_proposals_of_place(&self, place_id: &str) -> Result<Vec<PlaceProposal>>;
+    // Pending proposals for all places
+    fn count_pending_place_proposals(&self) -> Result<u64>;
+    // Oldest proposals first
+    fn list_pending_place_proposals(&self, pagination: &Pagination) -> Result<Vec<PlaceProposal>>;
+    // Pending proposals for places of the given owner
+    fn count_place_proposals_by_owner(&self, owner: &PlaceOwner) -> Result<u64>;
+    // Oldest proposals first
+    fn list_place_proposals_by_owner(
+        &self,
+        owner: &PlaceOwner,
+        pagination: &Pagination,
+    ) -> Result<Vec<PlaceProposal>>;
+}
+
+pub trait WebhookRepo {
+    fn create_webhook_subscription(&self, subscription: &WebhookSubscription) -> Result<()>;
+    fn delete_webhook_subscription(&self, org_id: &Id, id: &Id) -> Result<()>;
+    fn get_webhook_subscription(&self, id: &Id) -> Result<WebhookSubscription>;
+    fn all_webhook_subscriptions(&self) -> Result<Vec<WebhookSubscription>>;
+    fn all_webhook_subscriptions_by_org(&self, org_id: &Id) -> Result<Vec<WebhookSubscription>>;
+
+    fn create_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<()>;
+    fn update_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<()>;
+    fn load_webhook_delivery(&self, id: &Id) -> Result<WebhookDelivery>;
+    // Most recent deliveries first
+    fn recent_webhook_deliveries(
+        &self,
+        subscription_id: &Id,
+        pagination: &Pagination,
+    ) -> Result<Vec<WebhookDelivery>>;
+}
+
+pub trait JobRepo {
+    fn enqueue_job(&self, job: &Job) -> Result<()>;
+    /// Switch the next pending job that is due into the running
+    /// state and return it.
+    fn acquire_next_job(&self, now: TimestampMs) -> Result<Option<Job>>;
+    fn update_job(&self, job: &Job) -> Result<()>;
+    fn delete_job(&self, id: &Id) -> Result<()>;
+    // Oldest jobs first
+    fn load_jobs(&self, status: JobStatus, pagination: &Pagination) -> Result<Vec<Job>>;
+    fn count_jobs(&self, status: JobStatus) -> Result<usize>;
+    fn count_jobs_by_kind(&self, kind: &str, status: JobStatus) -> Result<usize>;
+    /// Reschedule all failed jobs and reset their attempts.
+    fn retry_failed_jobs(&self, run_at: TimestampMs) -> Result<usize>;
+    /// Reschedule all jobs that have been interrupted while running,
+    /// e.g. when the server was stopped.
+    fn release_running_jobs(&self) -> Result<usize>;
+}
+
 //TODO:
 //  - TagGeatway
 //  - SubscriptionGateway
@@ -141,6 +243,10 @@ pub trait Db:
     + RatingRepository
     + UserTokenRepo
     + PlaceClearanceRepo
+    + PlaceOwnershipRepo
+    + PlaceProposalRepo
+    + WebhookRepo
+    + JobRepo
 {
     fn create_tag_if_it_does_not_exist(&self, _: &Tag) -> Result<()>;
 
@@ -155,8 +261,11 @@ pub trait Db:
     fn count_tags(&self) -> Result<usize>;
 
     fn create_bbox_subscription(&self, _: &BboxSubscription) -> Result<()>;
+    fn update_bbox_subscription(&self, _: &BboxSubscription) -> Result<()>;
+    fn get_bbox_subscription(&self, id: &Id) -> Result<BboxSubscription>;
     fn all_bbox_subscriptions(&self) -> Result<Vec<BboxSubscription>>;
     fn all_bbox_subscriptions_by_email(&self, user_email: &str) -> Result<Vec<BboxSubscription>>;
+    fn delete_bbox_subscription(&self, id: &Id) -> Result<()>;
     fn delete_bbox_subscriptions_by_email(&self, user_email: &str) -> Result<()>;
 }
 
@@ -177,6 +286,11 @@ pub struct IndexQuery<'a, 'b> {
     pub status: Option<Vec<ReviewStatus>>,
     pub include_bbox: Option<MapBbox>,
     pub exclude_bbox: Option<MapBbox>,
+    // Only entries within the circle that are ordered by their
+    // distance from the center, i.e. the nearest entries first
+    pub near: Option<MapCircle>,
+    // Only entries within the region, e.g. an administrative boundary
+    pub include_polygon: Option<MapMultiPolygon>,
     pub categories: Vec<&'a str>,
     pub ids: Vec<&'b str>,
     pub hash_tags: Vec<String>,
@@ -186,10 +300,71 @@ pub struct IndexQuery<'a, 'b> {
     pub ts_min_ub: Option<Timestamp>, // upper bound (inclusive)
     pub ts_max_lb: Option<Timestamp>, // lower bound (inclusive)
     pub ts_max_ub: Option<Timestamp>, // upper bound (inclusive)
+    // The last time stamp, e.g. the start of the last occurrence
+    // of a recurring event
+    pub ts_last_lb: Option<Timestamp>, // lower bound (inclusive)
+    // Only places that are open at this local date and time
+    pub open_at: Option<NaiveDateTime>,
+}
+
+/// The order of paged results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexOrder {
+    /// Best matches first, places are additionally boosted
+    /// by their rating
+    Relevance,
+    /// Highest rated first
+    Rating,
+    /// Alphabetically by title
+    Title,
+    /// Most recently created first
+    Created,
+    /// Nearest first, only applicable for queries within a circle
+    Distance,
+    /// Earliest start first
+    Start,
+}
+
+impl IndexOrder {
+    /// Check if the sort key of a cursor matches this order.
+    pub fn accepts_key(self, key: &SortKey) -> bool {
+        match self {
+            Self::Relevance => matches!(key, SortKey::Score(_)),
+            Self::Rating | Self::Distance => matches!(key, SortKey::Float(_)),
+            Self::Created | Self::Start => matches!(key, SortKey::Int(_)),
+            Self::Title => matches!(key, SortKey::Text(_)),
+        }
+    }
+}
+
+/// A single page of results and the cursor for requesting
+/// the next page, if any.
+#[derive(Debug, Clone)]
+pub struct IndexPage<T> {
+    pub items: Vec<T>,
+    pub next: Option<PageCursor>,
 }
 
 pub trait Indexer {
     fn flush_index(&mut self) -> Fallible<()>;
+
+    /// Incremented whenever changes have been flushed, e.g.
+    /// for invalidating cached query results.
+    fn generation(&self) -> u64;
+
+    /// The high-water mark that has been recorded with the
+    /// index, i.e. all changes before this point in time have
+    /// already been indexed. `None` if the index is empty or
+    /// needs to be rebuilt.
+    fn high_water_mark(&self) -> Option<TimestampMs>;
+
+    /// Record a new high-water mark that is committed together
+    /// with the next flush.
+    fn set_high_water_mark(&mut self, high_water_mark: TimestampMs);
+
+    /// Remove all documents and the high-water mark from the
+    /// index with the next flush.
+    fn clear_index(&mut self) -> Fallible<()>;
 }
 
 pub trait IdIndex {
@@ -199,6 +374,17 @@ pub trait IdIndex {
         query: &IndexQuery,
         limit: usize,
     ) -> Fallible<Vec<Id>>;
+
+    /// Query the ids of the page that follows the cursor
+    /// or the first page if no cursor is given.
+    fn query_ids_page(
+        &self,
+        mode: IndexQueryMode,
+        query: &IndexQuery,
+        order: IndexOrder,
+        cursor: Option<&PageCursor>,
+        limit: usize,
+    ) -> Fallible<IndexPage<Id>>;
 }
 
 pub trait IdIndexer: Indexer + IdIndex {
@@ -208,6 +394,7 @@ pub trait IdIndexer: Indexer + IdIndex {
 #[derive(Debug, Default, Clone)]
 pub struct IndexedPlace {
     pub id: String,
+    pub revision: Option<Revision>,
     pub status: Option<ReviewStatus>,
     pub pos: MapPoint,
     pub title: String,
@@ -216,8 +403,68 @@ pub struct IndexedPlace {
     pub ratings: AvgRatings,
 }
 
+/// The number of places per tag, category and review status.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PlaceFacets {
+    /// The most frequent tags in descending order
+    pub tags: Vec<TagFrequency>,
+    pub categories: Vec<(Id, u64)>,
+    pub status: Vec<(ReviewStatus, u64)>,
+}
+
+/// The origin of a completed search term.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PlaceSuggestionKind {
+    Title,
+    Tag,
+    City,
+}
+
+/// A search term that completes a prefix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaceSuggestion {
+    pub kind: PlaceSuggestionKind,
+    pub text: String,
+    /// The number of places that match the query
+    /// and contain the completed search term
+    pub count: u64,
+}
+
 pub trait PlaceIndex {
     fn query_places(&self, query: &IndexQuery, limit: usize) -> Fallible<Vec<IndexedPlace>>;
+
+    /// The ids of all indexed places.
+    fn all_place_ids(&self) -> Fallible<Vec<Id>>;
+
+    /// Query the places of the page that follows the cursor
+    /// or the first page if no cursor is given.
+    fn query_places_page(
+        &self,
+        query: &IndexQuery,
+        order: IndexOrder,
+        cursor: Option<&PageCursor>,
+        limit: usize,
+    ) -> Fallible<IndexPage<IndexedPlace>>;
+
+    /// Aggregate all places that match the query into the
+    /// cells of the clustering grid at the given zoom level.
+    fn cluster_places(&self, query: &IndexQuery, zoom: u8) -> Fallible<Vec<PlaceCluster>>;
+
+    /// Count the tags, categories and review status of all
+    /// places that match the query.
+    fn facet_places(&self, query: &IndexQuery, max_tags: usize) -> Fallible<PlaceFacets>;
+
+    /// Complete the prefix by the titles, tags and cities of
+    /// the places that match the query, most frequent first.
+    ///
+    /// Implementations may only consider a limited number of
+    /// matching places and return estimated counts.
+    fn suggest_places(
+        &self,
+        query: &IndexQuery,
+        prefix: &str,
+        limit: usize,
+    ) -> Fallible<Vec<PlaceSuggestion>>;
 }
 
 pub trait PlaceIndexer: IdIndexer + PlaceIndex {
@@ -229,7 +476,27 @@ pub trait PlaceIndexer: IdIndexer + PlaceIndex {
     ) -> Fallible<()>;
 }
 
-pub trait EventIndexer: IdIndexer {
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct IndexedEvent {
+    pub id: String,
+    pub start: Option<Timestamp>,
+    pub end: Option<Timestamp>,
+    // None if the event recurs infinitely
+    pub last_start: Option<Timestamp>,
+    pub pos: Option<MapPoint>,
+    pub title: String,
+    pub description: String,
+    pub tags: Vec<String>,
+}
+
+pub trait EventIndex {
+    fn query_events(&self, query: &IndexQuery, limit: usize) -> Fallible<Vec<IndexedEvent>>;
+
+    /// The ids of all indexed events.
+    fn all_event_ids(&self) -> Fallible<Vec<Id>>;
+}
+
+pub trait EventIndexer: IdIndexer + EventIndex {
     fn add_or_update_event(&self, event: &Event) -> Fallible<()>;
 }
 
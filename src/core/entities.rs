@@ -1,7 +1,8 @@
 pub use ofdb_entities::{
-    activity::*, address::*, category::*, clearance::*, comment::*, contact::*, email::*, event::*,
-    geo::*, id::*, links::*, location::*, nonce::*, organization::*, password::*, place::*,
-    rating::*, review::*, revision::*, subscription::*, tag::*, time::*, user::*,
+    activity::*, address::*, category::*, clearance::*, comment::*, contact::*, cursor::*,
+    email::*, event::*, geo::*, id::*, job::*, links::*, location::*, nonce::*, opening_hours::*,
+    organization::*, ownership::*, password::*, place::*, proposal::*, rating::*, recurrence::*,
+    review::*, revision::*, subscription::*, tag::*, time::*, user::*, webhook::*,
 };
 
 #[cfg(test)]
@@ -1,3 +1,4 @@
+use super::entities::{OpeningHoursParseError, RecurrenceRuleParseError};
 use std::io;
 use thiserror::Error;
 
@@ -43,14 +44,38 @@ pub enum ParameterError {
     DateTimeOutOfRange,
     #[error("The end date is before the start")]
     EndDateBeforeStart,
+    #[error("Invalid recurrence rule: {0}")]
+    InvalidRecurrenceRule(RecurrenceRuleParseError),
+    #[error("The overridden occurrence does not exist")]
+    InvalidOccurrenceOverride,
     #[error("The tag is owned by an organization")]
     ModeratedTag,
     #[error("Missing the email of the creator")]
     CreatorEmail,
-    #[error("Invalid opening hours")]
-    InvalidOpeningHours,
+    #[error("Invalid opening hours: {0}")]
+    InvalidOpeningHours(OpeningHoursParseError),
     #[error("Invalid position")]
     InvalidPosition,
+    #[error("Invalid radius")]
+    InvalidRadius,
+    #[error("Invalid polygon")]
+    InvalidPolygon,
+    #[error("Invalid zoom level")]
+    InvalidZoom,
+    #[error("Invalid tile")]
+    InvalidTile,
+    #[error("Invalid sort order of events")]
+    InvalidEventOrder,
+    #[error("Invalid sort order of search results")]
+    InvalidSearchOrder,
+    #[error("Invalid cursor")]
+    InvalidCursor,
+    #[error("Invalid date")]
+    InvalidDate,
+    #[error("Invalid time zone")]
+    InvalidTimeZone,
+    #[error("Invalid iCalendar data")]
+    InvalidCalendar,
     #[error("Invalid limit")]
     InvalidLimit,
     #[error("Token invalid")]
@@ -61,6 +86,16 @@ pub enum ParameterError {
     InvalidNonce,
     #[error("Missing id list")]
     EmptyIdList,
+    #[error("The proposal has already been reviewed")]
+    ProposalAlreadyReviewed,
+    #[error("Only previous revisions could be restored")]
+    InvalidRevision,
+    #[error("Invalid category")]
+    InvalidCategory,
+    #[error("Invalid subscription delivery")]
+    InvalidSubscriptionDelivery,
+    #[error("The payload is too large")]
+    PayloadTooLarge,
 }
 
 #[derive(Debug, Error)]
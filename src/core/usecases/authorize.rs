@@ -21,15 +21,14 @@ pub fn authorize_user_by_email(db: &dyn Db, email: &str, min_required_role: Role
 // Returns a list with the ids of other organizations that require
 // clearance of the pending changes.
 //
-// If an organization is provided than this organization is excluded
+// If an organization id is provided than this organization is excluded
 // from both the checks and the pending clearance list.
 pub fn authorize_editing_of_tagged_entry<R: OrganizationRepo>(
     repo: &R,
     old_tags: &[String],
     new_tags: &[String],
-    org: Option<&Organization>,
+    org_id: Option<&Id>,
 ) -> Result<Vec<Id>> {
-    let org_id = org.map(|org| &org.id);
     let moderated_tags_by_org = repo.get_moderated_tags_by_org(org_id)?;
     ofdb_core::tag::moderated::authorize_editing_of_tagged_entry(
         moderated_tags_by_org,
@@ -0,0 +1,210 @@
+use super::prepare_tag_list;
+use crate::core::{
+    prelude::*,
+    util::{
+        geo::{MapBbox, MapMultiPolygon},
+        validate,
+    },
+};
+
+/// The optional name, filters and delivery mode of
+/// a bbox subscription.
+#[derive(Debug, Clone, Default)]
+pub struct BboxSubscriptionOptions {
+    pub title: Option<String>,
+    /// Restricts the subscription to a region. The bounding
+    /// box of the subscription is then replaced by the bounding
+    /// box of the region.
+    pub polygon: Option<MapMultiPolygon>,
+    pub tags: Vec<String>,
+    pub categories: Vec<Id>,
+    pub delivery: SubscriptionDelivery,
+}
+
+fn prepare_subscription(
+    subscription: &mut BboxSubscription,
+    bbox: MapBbox,
+    options: BboxSubscriptionOptions,
+) -> Result<()> {
+    let BboxSubscriptionOptions {
+        title,
+        polygon,
+        tags,
+        categories,
+        delivery,
+    } = options;
+    let bbox = if let Some(ref polygon) = polygon {
+        validate::polygon(polygon)?;
+        polygon.bounding_bbox()
+    } else {
+        bbox
+    };
+    validate::bbox(&bbox)?;
+    if categories
+        .iter()
+        .any(|id| Category::merge_ids_into_tags(std::slice::from_ref(id), vec![]).is_empty())
+    {
+        return Err(Error::Parameter(ParameterError::InvalidCategory));
+    }
+    subscription.title = title
+        .as_deref()
+        .map(str::trim)
+        .filter(|title| !title.is_empty())
+        .map(ToString::to_string);
+    subscription.bbox = bbox;
+    subscription.polygon = polygon;
+    subscription.tags = prepare_tag_list(tags.iter().map(String::as_str));
+    subscription.categories = categories;
+    subscription.delivery = delivery;
+    Ok(())
+}
+
+pub fn create_bbox_subscription(
+    db: &dyn Db,
+    user_email: String,
+    bbox: MapBbox,
+    options: BboxSubscriptionOptions,
+) -> Result<BboxSubscription> {
+    let mut subscription = BboxSubscription {
+        id: Id::new(),
+        user_email,
+        title: None,
+        bbox,
+        polygon: None,
+        tags: vec![],
+        categories: vec![],
+        delivery: Default::default(),
+        // Digests only cover changes after subscribing
+        last_digest_at: Some(TimestampMs::now()),
+        unsubscribe_nonce: Nonce::new(),
+    };
+    prepare_subscription(&mut subscription, bbox, options)?;
+    db.create_bbox_subscription(&subscription)?;
+    Ok(subscription)
+}
+
+/// Replace all existing subscriptions of the user
+/// with a new one.
+pub fn subscribe_to_bbox(
+    db: &dyn Db,
+    user_email: String,
+    bbox: MapBbox,
+    options: BboxSubscriptionOptions,
+) -> Result<()> {
+    validate::bbox(&bbox)?;
+    unsubscribe_all_bboxes(db, &user_email)?;
+    create_bbox_subscription(db, user_email, bbox, options)?;
+    Ok(())
+}
+
+fn get_own_bbox_subscription(db: &dyn Db, user_email: &str, id: &Id) -> Result<BboxSubscription> {
+    let subscription = db.get_bbox_subscription(id)?;
+    if subscription.user_email != user_email {
+        return Err(Error::Parameter(ParameterError::Forbidden));
+    }
+    Ok(subscription)
+}
+
+pub fn update_bbox_subscription(
+    db: &dyn Db,
+    user_email: &str,
+    id: &Id,
+    bbox: MapBbox,
+    options: BboxSubscriptionOptions,
+) -> Result<BboxSubscription> {
+    let mut subscription = get_own_bbox_subscription(db, user_email, id)?;
+    prepare_subscription(&mut subscription, bbox, options)?;
+    db.update_bbox_subscription(&subscription)?;
+    Ok(subscription)
+}
+
+pub fn delete_bbox_subscription(db: &dyn Db, user_email: &str, id: &Id) -> Result<()> {
+    get_own_bbox_subscription(db, user_email, id)?;
+    Ok(db.delete_bbox_subscription(id)?)
+}
+
+/// Look up the subscription that could be cancelled with
+/// the token, e.g. to ask for a confirmation.
+pub fn get_bbox_subscription_by_unsubscribe_token(
+    db: &dyn Db,
+    token: &str,
+) -> Result<BboxSubscription> {
+    let token = SubscriptionNonce::decode_from_str(token)?;
+    let subscription = match db.get_bbox_subscription(&token.subscription_id) {
+        Ok(subscription) => subscription,
+        Err(RepoError::NotFound) => {
+            return Err(Error::Parameter(ParameterError::TokenInvalid));
+        }
+        Err(err) => return Err(err.into()),
+    };
+    if subscription.unsubscribe_nonce != token.nonce {
+        return Err(Error::Parameter(ParameterError::TokenInvalid));
+    }
+    Ok(subscription)
+}
+
+/// Cancel a single subscription with the token from
+/// a notification e-mail.
+pub fn unsubscribe_bbox_with_token(db: &dyn Db, token: &str) -> Result<BboxSubscription> {
+    let subscription = get_bbox_subscription_by_unsubscribe_token(db, token)?;
+    db.delete_bbox_subscription(&subscription.id)?;
+    Ok(subscription)
+}
+
+pub fn unsubscribe_all_bboxes(db: &dyn Db, user_email: &str) -> Result<()> {
+    Ok(db.delete_bbox_subscriptions_by_email(&user_email)?)
+}
+
+pub fn get_bbox_subscriptions(db: &dyn Db, user_email: &str) -> Result<Vec<BboxSubscription>> {
+    Ok(db.all_bbox_subscriptions_by_email(user_email)?)
+}
+
+pub fn bbox_subscriptions_by_coordinate(
+    db: &dyn Db,
+    pos: MapPoint,
+) -> Result<Vec<BboxSubscription>> {
+    Ok(db
+        .all_bbox_subscriptions()?
+        .into_iter()
+        .filter(|s| s.bbox.contains_point(pos))
+        .collect())
+}
+
+fn immediate_subscriptions<'a>(
+    db: &dyn Db,
+    pos: MapPoint,
+    tags: impl IntoIterator<Item = &'a str> + Clone,
+) -> Result<Vec<BboxSubscription>> {
+    Ok(bbox_subscriptions_by_coordinate(db, pos)?
+        .into_iter()
+        .filter(|s| s.delivery == SubscriptionDelivery::Immediate)
+        .filter(|s| s.is_in_scope(pos, tags.clone()))
+        .collect())
+}
+
+/// The subscriptions that need to be notified immediately
+/// about a changed place.
+pub fn subscriptions_for_place(db: &dyn Db, place: &Place) -> Result<Vec<BboxSubscription>> {
+    immediate_subscriptions(
+        db,
+        place.location.pos,
+        place.tags.iter().map(String::as_str),
+    )
+}
+
+/// The subscriptions that need to be notified immediately
+/// about a changed event.
+///
+/// Events without a valid position are never reported.
+pub fn subscriptions_for_event(db: &dyn Db, event: &Event) -> Result<Vec<BboxSubscription>> {
+    let pos = match event.location {
+        Some(ref location) if location.pos.is_valid() => location.pos,
+        _ => return Ok(vec![]),
+    };
+    let tags = event
+        .tags
+        .iter()
+        .map(String::as_str)
+        .chain(std::iter::once(Category::TAG_EVENT));
+    immediate_subscriptions(db, pos, tags)
+}
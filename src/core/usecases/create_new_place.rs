@@ -78,8 +78,12 @@ pub fn prepare_new_place<D: Db>(
             .iter()
             .map(String::as_str),
     );
-    let clearance_org_ids =
-        super::authorize_editing_of_tagged_entry(db, &old_tags, &new_tags, created_by_org)?;
+    let clearance_org_ids = super::authorize_editing_of_tagged_entry(
+        db,
+        &old_tags,
+        &new_tags,
+        created_by_org.map(|org| &org.id),
+    )?;
 
     let address = Address {
         street,
@@ -144,7 +148,7 @@ pub fn prepare_new_place<D: Db>(
         opening_hours: opening_hours
             .map(|s| {
                 s.parse()
-                    .map_err(|_| Error::Parameter(ParameterError::InvalidOpeningHours))
+                    .map_err(|err| Error::Parameter(ParameterError::InvalidOpeningHours(err)))
             })
             .transpose()?,
         founded_on,
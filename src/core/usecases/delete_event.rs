@@ -1,6 +1,6 @@
 use crate::core::prelude::*;
 
-pub fn delete_event<D: Db>(db: &mut D, token: &str, id: &str) -> Result<()> {
+pub fn delete_event<D: Db>(db: &D, token: &str, id: &str) -> Result<()> {
     let org = db.get_org_by_api_token(token).map_err(|e| match e {
         RepoError::NotFound => Error::Parameter(ParameterError::Unauthorized),
         _ => Error::Repo(e),
@@ -45,10 +45,10 @@ const MAX_TEXT_RELATIVE_EDIT_DISTANCE: f64 = 0.3; // max. 30% text difference
 
 const MAX_WORDS_HAMMING_DISTANCE: u32 = 2; // up to 2 words may differ
 
-fn search_nearby_places(
-    place_index: &dyn crate::core::db::PlaceIndex,
-    center: MapPoint,
-) -> Result<Vec<IndexedPlace>> {
+fn search_nearby_places<I>(place_index: &I, center: MapPoint) -> Result<Vec<IndexedPlace>>
+where
+    I: crate::core::db::PlaceIndex + ?Sized,
+{
     let nearby_bbox = nearby_bbox(center);
     let nearby_query = crate::core::db::IndexQuery {
         include_bbox: Some(nearby_bbox),
@@ -59,10 +59,10 @@ fn search_nearby_places(
         .map_err(RepoError::Other)?)
 }
 
-pub fn search_duplicates(
-    place_index: &dyn crate::core::db::PlaceIndex,
-    new_place: &NewPlace,
-) -> Result<Vec<IndexedPlace>> {
+pub fn search_duplicates<I>(place_index: &I, new_place: &NewPlace) -> Result<Vec<IndexedPlace>>
+where
+    I: crate::core::db::PlaceIndex + ?Sized,
+{
     let center = MapPoint::new(
         LatCoord::from_deg(new_place.lat),
         LngCoord::from_deg(new_place.lng),
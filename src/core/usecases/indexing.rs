@@ -2,22 +2,400 @@ use crate::core::prelude::*;
 use ofdb_core::rating::Rated;
 
 use anyhow::Result as Fallible;
+use std::collections::HashSet;
 
-pub fn reindex_place(
-    indexer: &dyn PlaceIndexer,
+pub fn reindex_place<I>(
+    indexer: &I,
     place: &Place,
     status: ReviewStatus,
     ratings: &[Rating],
-) -> Fallible<AvgRatings> {
+) -> Fallible<AvgRatings>
+where
+    I: PlaceIndexer + ?Sized,
+{
     let avg_ratings = place.avg_ratings(ratings);
     indexer.add_or_update_place(place, status, &avg_ratings)?;
     Ok(avg_ratings)
 }
 
-pub fn index_event(indexer: &dyn EventIndexer, event: &Event) -> Fallible<()> {
+pub fn index_event<I>(indexer: &I, event: &Event) -> Fallible<()>
+where
+    I: EventIndexer + ?Sized,
+{
     indexer.add_or_update_event(event)
 }
 
 pub fn unindex_event(indexer: &dyn EventIndexer, id: &Id) -> Fallible<()> {
     indexer.remove_by_id(id)
 }
+
+// Number of places or events that are loaded
+// and indexed at once
+const INDEX_CHUNK_SIZE: u64 = 1000;
+
+/// Bring the index up to date with the database.
+///
+/// Only the places and events that have changed since the
+/// high-water mark of the index are reindexed. The whole index
+/// is rebuilt if no high-water mark has been recorded yet.
+pub fn synchronize_index<D, I>(db: &D, indexer: &mut I) -> Fallible<()>
+where
+    D: PlaceRepo + RatingRepository + EventGateway,
+    I: EventAndPlaceIndexer + ?Sized,
+{
+    match indexer.high_water_mark() {
+        Some(since) => update_index(db, indexer, since),
+        None => rebuild_index(db, indexer),
+    }
+}
+
+/// Remove all documents from the index and index all
+/// places and events in chunks.
+pub fn rebuild_index<D, I>(db: &D, indexer: &mut I) -> Fallible<()>
+where
+    D: PlaceRepo + RatingRepository + EventGateway,
+    I: EventAndPlaceIndexer + ?Sized,
+{
+    // All changes from now on will be picked up by the
+    // next incremental update
+    let high_water_mark = TimestampMs::now();
+    indexer.clear_index()?;
+    let mut place_count = 0;
+    loop {
+        let places = db.load_places_chunk(&Pagination {
+            offset: Some(place_count),
+            limit: Some(INDEX_CHUNK_SIZE),
+        })?;
+        let chunk_size = places.len() as u64;
+        index_places(db, indexer, places)?;
+        indexer.flush_index()?;
+        place_count += chunk_size;
+        if chunk_size < INDEX_CHUNK_SIZE {
+            break;
+        }
+        debug!("Indexed {} places", place_count);
+    }
+    info!("Indexed {} places", place_count);
+    let mut event_count = 0;
+    loop {
+        let events = db.load_events_chunk(&Pagination {
+            offset: Some(event_count),
+            limit: Some(INDEX_CHUNK_SIZE),
+        })?;
+        let chunk_size = events.len() as u64;
+        for event in events {
+            if let Err(err) = index_event(indexer, &event) {
+                error!("Failed to index event {}: {}", event.id, err);
+            }
+        }
+        indexer.flush_index()?;
+        event_count += chunk_size;
+        if chunk_size < INDEX_CHUNK_SIZE {
+            break;
+        }
+        debug!("Indexed {} events", event_count);
+    }
+    info!("Indexed {} events", event_count);
+    indexer.set_high_water_mark(high_water_mark);
+    indexer.flush_index()
+}
+
+/// Reindex all places and events that have changed since
+/// the given point in time.
+pub fn update_index<D, I>(db: &D, indexer: &mut I, since: TimestampMs) -> Fallible<()>
+where
+    D: PlaceRepo + RatingRepository + EventGateway,
+    I: EventAndPlaceIndexer + ?Sized,
+{
+    let high_water_mark = TimestampMs::now();
+    let place_ids = db.changed_place_ids_since(since)?;
+    for ids in place_ids.chunks(INDEX_CHUNK_SIZE as usize) {
+        let ids: Vec<_> = ids.iter().map(Id::as_str).collect();
+        index_places(db, indexer, db.get_places(&ids)?)?;
+        indexer.flush_index()?;
+    }
+    info!("Reindexed {} changed places", place_ids.len());
+    let event_ids = db.changed_event_ids_since(since)?;
+    for ids in event_ids.chunks(INDEX_CHUNK_SIZE as usize) {
+        let ids: Vec<_> = ids.iter().map(Id::as_str).collect();
+        let events = db.get_events_chronologically(&ids)?;
+        for id in &ids {
+            // Archived and deleted events are not loaded and need to be removed
+            if events.iter().all(|event| event.id.as_str() != *id) {
+                if let Err(err) = indexer.remove_by_id(&Id::from(*id)) {
+                    error!("Failed to remove event {} from index: {}", id, err);
+                }
+            }
+        }
+        for event in events {
+            if let Err(err) = index_event(indexer, &event) {
+                error!("Failed to index event {}: {}", event.id, err);
+            }
+        }
+        indexer.flush_index()?;
+    }
+    info!("Reindexed {} changed events", event_ids.len());
+    indexer.set_high_water_mark(high_water_mark);
+    indexer.flush_index()
+}
+
+fn index_places<D, I>(db: &D, indexer: &I, places: Vec<(Place, ReviewStatus)>) -> Fallible<()>
+where
+    D: RatingRepository,
+    I: PlaceIndexer + ?Sized,
+{
+    for (place, status) in places {
+        let ratings = db.load_ratings_of_place(place.id.as_ref())?;
+        if let Err(err) = reindex_place(indexer, &place, status, &ratings) {
+            error!("Failed to index place {}: {}", place.id, err);
+        }
+    }
+    Ok(())
+}
+
+/// A difference between the database and the index.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndexInconsistency {
+    MissingPlace {
+        place_id: Id,
+    },
+    StalePlaceRevision {
+        place_id: Id,
+        indexed: Option<Revision>,
+        current: Revision,
+    },
+    WrongPlaceReviewStatus {
+        place_id: Id,
+        indexed: Option<ReviewStatus>,
+        current: ReviewStatus,
+    },
+    OutdatedPlaceRatings {
+        place_id: Id,
+        indexed: AvgRatings,
+        current: AvgRatings,
+    },
+    OrphanedPlace {
+        place_id: Id,
+    },
+    MissingEvent {
+        event_id: Id,
+    },
+    StaleEvent {
+        event_id: Id,
+    },
+    OrphanedEvent {
+        event_id: Id,
+    },
+}
+
+impl IndexInconsistency {
+    pub fn place_id(&self) -> Option<&Id> {
+        match self {
+            Self::MissingPlace { place_id }
+            | Self::StalePlaceRevision { place_id, .. }
+            | Self::WrongPlaceReviewStatus { place_id, .. }
+            | Self::OutdatedPlaceRatings { place_id, .. } => Some(place_id),
+            _ => None,
+        }
+    }
+
+    pub fn event_id(&self) -> Option<&Id> {
+        match self {
+            Self::MissingEvent { event_id } | Self::StaleEvent { event_id } => Some(event_id),
+            _ => None,
+        }
+    }
+
+    /// The id of a document that needs to be removed from
+    /// the index, because the place or event doesn't exist
+    /// anymore or has been archived.
+    pub fn orphaned_id(&self) -> Option<&Id> {
+        match self {
+            Self::OrphanedPlace { place_id } => Some(place_id),
+            Self::OrphanedEvent { event_id } => Some(event_id),
+            _ => None,
+        }
+    }
+}
+
+// The expected contents of the indexed event
+fn indexed_event(event: &Event) -> IndexedEvent {
+    let mut tags = event.tags.clone();
+    tags.sort_unstable();
+    IndexedEvent {
+        id: event.id.to_string(),
+        start: Some(event.start.into()),
+        end: event.end.map(Into::into),
+        last_start: event.last_start().map(Into::into),
+        pos: event.location.as_ref().map(|location| location.pos),
+        title: event.title.clone(),
+        description: event.description.clone().unwrap_or_default(),
+        tags,
+    }
+}
+
+/// Compare all places and events in the database with
+/// the documents in the index and vice versa.
+pub fn check_index<D, I>(db: &D, index: &I) -> Fallible<Vec<IndexInconsistency>>
+where
+    D: PlaceRepo + RatingRepository + EventGateway,
+    I: PlaceIndex + EventIndex + ?Sized,
+{
+    let mut inconsistencies = Vec::new();
+    let mut place_ids = HashSet::new();
+    let mut place_count = 0;
+    loop {
+        let places = db.load_places_chunk(&Pagination {
+            offset: Some(place_count),
+            limit: Some(INDEX_CHUNK_SIZE),
+        })?;
+        let chunk_size = places.len() as u64;
+        if chunk_size > 0 {
+            let query = IndexQuery {
+                ids: places.iter().map(|(place, _)| place.id.as_str()).collect(),
+                ..Default::default()
+            };
+            let indexed_places = index.query_places(&query, places.len())?;
+            for (place, status) in &places {
+                place_ids.insert(place.id.to_string());
+                let indexed_place = match indexed_places
+                    .iter()
+                    .find(|indexed_place| indexed_place.id == place.id.as_str())
+                {
+                    Some(indexed_place) => indexed_place,
+                    None => {
+                        inconsistencies.push(IndexInconsistency::MissingPlace {
+                            place_id: place.id.clone(),
+                        });
+                        continue;
+                    }
+                };
+                if indexed_place.revision != Some(place.revision) {
+                    inconsistencies.push(IndexInconsistency::StalePlaceRevision {
+                        place_id: place.id.clone(),
+                        indexed: indexed_place.revision,
+                        current: place.revision,
+                    });
+                }
+                if indexed_place.status != Some(*status) {
+                    inconsistencies.push(IndexInconsistency::WrongPlaceReviewStatus {
+                        place_id: place.id.clone(),
+                        indexed: indexed_place.status,
+                        current: *status,
+                    });
+                }
+                let ratings = db.load_ratings_of_place(place.id.as_ref())?;
+                let avg_ratings = place.avg_ratings(&ratings);
+                if indexed_place.ratings != avg_ratings {
+                    inconsistencies.push(IndexInconsistency::OutdatedPlaceRatings {
+                        place_id: place.id.clone(),
+                        indexed: indexed_place.ratings.clone(),
+                        current: avg_ratings,
+                    });
+                }
+            }
+        }
+        place_count += chunk_size;
+        if chunk_size < INDEX_CHUNK_SIZE {
+            break;
+        }
+    }
+    // Archived or deleted places
+    for place_id in index.all_place_ids()? {
+        if !place_ids.contains(place_id.as_str()) {
+            inconsistencies.push(IndexInconsistency::OrphanedPlace { place_id });
+        }
+    }
+    let mut event_ids = HashSet::new();
+    let mut event_count = 0;
+    loop {
+        let events = db.load_events_chunk(&Pagination {
+            offset: Some(event_count),
+            limit: Some(INDEX_CHUNK_SIZE),
+        })?;
+        let chunk_size = events.len() as u64;
+        if chunk_size > 0 {
+            let query = IndexQuery {
+                ids: events.iter().map(|event| event.id.as_str()).collect(),
+                ..Default::default()
+            };
+            let indexed_events = index.query_events(&query, events.len())?;
+            for event in &events {
+                event_ids.insert(event.id.to_string());
+                let mut indexed = match indexed_events
+                    .iter()
+                    .find(|indexed_event| indexed_event.id == event.id.as_str())
+                {
+                    Some(indexed_event) => indexed_event.clone(),
+                    None => {
+                        inconsistencies.push(IndexInconsistency::MissingEvent {
+                            event_id: event.id.clone(),
+                        });
+                        continue;
+                    }
+                };
+                indexed.tags.sort_unstable();
+                if indexed != indexed_event(event) {
+                    inconsistencies.push(IndexInconsistency::StaleEvent {
+                        event_id: event.id.clone(),
+                    });
+                }
+            }
+        }
+        event_count += chunk_size;
+        if chunk_size < INDEX_CHUNK_SIZE {
+            break;
+        }
+    }
+    // Archived or deleted events
+    for event_id in index.all_event_ids()? {
+        if !event_ids.contains(event_id.as_str()) {
+            inconsistencies.push(IndexInconsistency::OrphanedEvent { event_id });
+        }
+    }
+    Ok(inconsistencies)
+}
+
+/// Reindex only the places and events that are affected
+/// by the given inconsistencies.
+pub fn repair_index<D, I>(
+    db: &D,
+    indexer: &mut I,
+    inconsistencies: &[IndexInconsistency],
+) -> Fallible<()>
+where
+    D: PlaceRepo + RatingRepository + EventGateway,
+    I: EventAndPlaceIndexer + ?Sized,
+{
+    let mut place_ids: Vec<_> = inconsistencies
+        .iter()
+        .filter_map(IndexInconsistency::place_id)
+        .map(Id::as_str)
+        .collect();
+    place_ids.sort_unstable();
+    place_ids.dedup();
+    for ids in place_ids.chunks(INDEX_CHUNK_SIZE as usize) {
+        index_places(db, indexer, db.get_places(ids)?)?;
+    }
+    let event_ids: Vec<_> = inconsistencies
+        .iter()
+        .filter_map(IndexInconsistency::event_id)
+        .map(Id::as_str)
+        .collect();
+    for ids in event_ids.chunks(INDEX_CHUNK_SIZE as usize) {
+        for event in db.get_events_chronologically(ids)? {
+            if let Err(err) = index_event(indexer, &event) {
+                error!("Failed to index event {}: {}", event.id, err);
+            }
+        }
+    }
+    for id in inconsistencies
+        .iter()
+        .filter_map(IndexInconsistency::orphaned_id)
+    {
+        if let Err(err) = indexer.remove_by_id(id) {
+            error!("Failed to remove orphaned document {}: {}", id, err);
+        }
+    }
+    indexer.flush_index()
+}
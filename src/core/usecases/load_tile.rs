@@ -0,0 +1,71 @@
+use crate::core::prelude::*;
+use ofdb_core::tile::TileId;
+
+/// The maximum number of places and events per tile respectively.
+pub const MAX_TILE_FEATURES: usize = 2_000;
+
+/// All visible places within the tile, the highest rated first.
+pub fn load_tile_places(index: &dyn PlaceIndex, tile: TileId) -> Result<Vec<IndexedPlace>> {
+    let query = IndexQuery {
+        include_bbox: Some(tile.bbox()),
+        // Only visible places
+        status: Some(vec![]),
+        categories: vec![Category::ID_NON_PROFIT, Category::ID_COMMERCIAL],
+        ..Default::default()
+    };
+    Ok(index
+        .query_places(&query, MAX_TILE_FEATURES)
+        .map_err(RepoError::Other)?)
+}
+
+/// All current events within the tile, i.e. events that
+/// have not ended yet or that start in the future.
+pub fn load_tile_events<D: Db>(
+    db: &D,
+    index: &dyn IdIndex,
+    tile: TileId,
+    now: Timestamp,
+) -> Result<Vec<Event>> {
+    let query = IndexQuery {
+        include_bbox: Some(tile.bbox()),
+        categories: vec![Category::ID_EVENT],
+        ..Default::default()
+    };
+    // Events without an end are only indexed with their start
+    let ongoing_query = IndexQuery {
+        ts_max_lb: Some(now),
+        ..query.clone()
+    };
+    let upcoming_query = IndexQuery {
+        ts_last_lb: Some(now),
+        ..query
+    };
+    let mut event_ids = index
+        .query_ids(
+            IndexQueryMode::WithoutRating,
+            &ongoing_query,
+            MAX_TILE_FEATURES,
+        )
+        .map_err(RepoError::Other)?;
+    for id in index
+        .query_ids(
+            IndexQueryMode::WithoutRating,
+            &upcoming_query,
+            MAX_TILE_FEATURES,
+        )
+        .map_err(RepoError::Other)?
+    {
+        if event_ids.len() >= MAX_TILE_FEATURES {
+            break;
+        }
+        if !event_ids.contains(&id) {
+            event_ids.push(id);
+        }
+    }
+    let event_ids: Vec<_> = event_ids.iter().map(Id::as_str).collect();
+    Ok(db
+        .get_events_chronologically(&event_ids)?
+        .into_iter()
+        .filter(|event| event.archived.is_none())
+        .collect())
+}
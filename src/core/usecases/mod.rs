@@ -2,9 +2,8 @@ use crate::core::{
     error::ParameterError,
     prelude::*,
     util::{
-        geo::{MapBbox, MapPoint},
+        geo::{MapBbox, MapCircle},
         parse::parse_url_param,
-        validate,
     },
 };
 
@@ -12,6 +11,7 @@ mod archive_comments;
 mod archive_events;
 mod archive_ratings;
 mod authorize;
+mod bbox_subscriptions;
 mod change_user_role;
 pub mod clearance;
 mod confirm_email;
@@ -26,25 +26,34 @@ mod filter_place;
 mod find_duplicates;
 mod indexing;
 mod load_places;
+mod load_tile;
 mod login;
+mod place_ownership;
 mod query_events;
 mod rate_place;
 mod register;
+mod revert_place;
+mod review_place_proposal;
 mod review_places;
 mod search;
 mod store_event;
+mod subscription_digests;
 mod update_place;
 mod user_tokens;
+mod webhooks;
 
 #[cfg(test)]
 pub mod tests;
 
 pub use self::{
-    archive_comments::*, archive_events::*, archive_ratings::*, authorize::*, change_user_role::*,
-    confirm_email::*, confirm_email_and_reset_password::*, create_new_place::*, create_new_user::*,
-    delete_event::*, export_event::*, export_place::*, filter_event::*, filter_place::*,
-    find_duplicates::*, indexing::*, load_places::*, login::*, query_events::*, rate_place::*,
-    register::*, review_places::*, search::*, store_event::*, update_place::*, user_tokens::*,
+    archive_comments::*, archive_events::*, archive_ratings::*, authorize::*,
+    bbox_subscriptions::*, change_user_role::*, confirm_email::*,
+    confirm_email_and_reset_password::*, create_new_place::*, create_new_user::*, delete_event::*,
+    export_event::*, export_place::*, filter_event::*, filter_place::*, find_duplicates::*,
+    indexing::*, load_places::*, load_tile::*, login::*, place_ownership::*, query_events::*,
+    rate_place::*, register::*, revert_place::*, review_place_proposal::*, review_places::*,
+    search::*, store_event::*, subscription_digests::*, update_place::*, user_tokens::*,
+    webhooks::*,
 };
 
 //TODO: move usecases into separate files
@@ -69,36 +78,97 @@ pub fn get_event<D: Db>(db: &D, id: &str) -> Result<Event> {
     Ok(db.get_event(id)?)
 }
 
+// Cursors must match the order and ordering by
+// distance requires a circle
+fn validate_page_request(
+    order: IndexOrder,
+    cursor: Option<&PageCursor>,
+    near: Option<&MapCircle>,
+) -> Result<()> {
+    if order == IndexOrder::Distance && near.is_none() {
+        return Err(ParameterError::InvalidPosition.into());
+    }
+    if let Some(cursor) = cursor {
+        if !order.accepts_key(&cursor.key) {
+            return Err(ParameterError::InvalidCursor.into());
+        }
+    }
+    Ok(())
+}
+
+/// The order of events in query results.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventOrder {
+    Chronological,
+    /// Nearest events first, only applicable for
+    /// queries within a circle.
+    Distance,
+    /// Most recently created events first.
+    Created,
+    /// Best matches first.
+    Relevance,
+}
+
+impl From<EventOrder> for IndexOrder {
+    fn from(from: EventOrder) -> Self {
+        match from {
+            EventOrder::Chronological => Self::Start,
+            EventOrder::Distance => Self::Distance,
+            EventOrder::Created => Self::Created,
+            EventOrder::Relevance => Self::Relevance,
+        }
+    }
+}
+
+impl Default for EventOrder {
+    fn default() -> Self {
+        Self::Chronological
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct EventQuery {
     pub bbox: Option<MapBbox>,
+    pub near: Option<MapCircle>,
     pub created_by: Option<Email>,
     pub start_min: Option<Timestamp>,
     pub start_max: Option<Timestamp>,
     pub tags: Option<Vec<String>>,
     pub text: Option<String>,
 
+    pub order: EventOrder,
     pub limit: Option<usize>,
+    /// Return only a single page of results that starts
+    /// after the cursor or with the first result.
+    pub paginate: bool,
+    pub cursor: Option<PageCursor>,
 }
 
 impl EventQuery {
     pub fn is_empty(&self) -> bool {
         let Self {
             ref bbox,
+            ref near,
             ref created_by,
             ref start_min,
             ref start_max,
             ref tags,
             ref text,
+            order: _,
             ref limit,
+            paginate,
+            ref cursor,
         } = self;
         bbox.is_none()
+            && near.is_none()
             && created_by.is_none()
             && start_min.is_none()
             && start_max.is_none()
             && tags.is_none()
             && text.is_none()
             && limit.is_none()
+            && !paginate
+            && cursor.is_none()
     }
 }
 
@@ -109,53 +179,6 @@ pub fn delete_user(db: &dyn Db, login_email: &str, email: &str) -> Result<()> {
     Ok(db.delete_user_by_email(email)?)
 }
 
-pub fn subscribe_to_bbox(db: &dyn Db, user_email: String, bbox: MapBbox) -> Result<()> {
-    validate::bbox(&bbox)?;
-
-    // TODO: support multiple subscriptions in KVM (frontend)
-    // In the meanwhile we just replace existing subscriptions
-    // with a new one.
-    unsubscribe_all_bboxes(db, &user_email)?;
-
-    let id = Id::new();
-    db.create_bbox_subscription(&BboxSubscription {
-        id,
-        user_email,
-        bbox,
-    })?;
-    Ok(())
-}
-
-pub fn unsubscribe_all_bboxes(db: &dyn Db, user_email: &str) -> Result<()> {
-    Ok(db.delete_bbox_subscriptions_by_email(&user_email)?)
-}
-
-pub fn get_bbox_subscriptions(db: &dyn Db, user_email: &str) -> Result<Vec<BboxSubscription>> {
-    Ok(db
-        .all_bbox_subscriptions()?
-        .into_iter()
-        .filter(|s| s.user_email == user_email)
-        .collect())
-}
-
-pub fn bbox_subscriptions_by_coordinate(
-    db: &dyn Db,
-    pos: MapPoint,
-) -> Result<Vec<BboxSubscription>> {
-    Ok(db
-        .all_bbox_subscriptions()?
-        .into_iter()
-        .filter(|s| s.bbox.contains_point(pos))
-        .collect())
-}
-
-pub fn email_addresses_by_coordinate(db: &dyn Db, pos: MapPoint) -> Result<Vec<String>> {
-    Ok(bbox_subscriptions_by_coordinate(db, pos)?
-        .into_iter()
-        .map(|s| s.user_email)
-        .collect())
-}
-
 pub fn prepare_tag_list<'a>(tags: impl IntoIterator<Item = &'a str>) -> Vec<String> {
     let mut tags: Vec<_> = tags
         .into_iter()
@@ -0,0 +1,173 @@
+use crate::core::prelude::*;
+
+// The owners of a place contain e-mail addresses of registered
+// users and are only visible for scouts, admins and the owners
+pub fn load_place_ownership<D: Db>(
+    db: &D,
+    user_email: &str,
+    place_id: &Id,
+) -> Result<PlaceOwnership> {
+    let user = db
+        .try_get_user_by_email(user_email)?
+        .ok_or(ParameterError::Unauthorized)?;
+    let ownership = db.load_place_ownership(place_id)?;
+    if user.role < Role::Scout && !ownership.is_owned_by_user(user_email) {
+        return Err(ParameterError::Forbidden.into());
+    }
+    Ok(ownership)
+}
+
+// Only scouts and admins are entitled to assign owners, the
+// owners themselves are only allowed to change the locked fields
+pub fn change_place_ownership<D: Db>(
+    db: &D,
+    user_email: &str,
+    place_id: &Id,
+    ownership: PlaceOwnership,
+) -> Result<()> {
+    let user = db
+        .try_get_user_by_email(user_email)?
+        .ok_or(ParameterError::Unauthorized)?;
+    let old_ownership = db.load_place_ownership(place_id)?;
+    if user.role < Role::Scout
+        && !(old_ownership.is_owned_by_user(user_email) && old_ownership.owners == ownership.owners)
+    {
+        return Err(ParameterError::Forbidden.into());
+    }
+    for owner in &ownership.owners {
+        if let PlaceOwner::User(email) = owner {
+            if db.try_get_user_by_email(email.as_ref())?.is_none() {
+                return Err(ParameterError::UserDoesNotExist.into());
+            }
+        }
+    }
+    let mut ownership = ownership;
+    ownership.locked_fields.sort_unstable();
+    ownership.locked_fields.dedup();
+    Ok(db.replace_place_ownership(place_id, &ownership)?)
+}
+
+// Owners, scouts and admins are allowed to change locked fields
+pub(crate) fn authorize_editing_of_locked_fields<D: Db>(
+    db: &D,
+    ownership: &PlaceOwnership,
+    created_by_email: Option<&str>,
+    created_by_org: Option<&Organization>,
+) -> Result<bool> {
+    if let Some(org) = created_by_org {
+        if ownership.is_owned_by_org(&org.id) {
+            return Ok(true);
+        }
+    }
+    if let Some(email) = created_by_email {
+        if ownership.is_owned_by_user(email) {
+            return Ok(true);
+        }
+        if let Some(user) = db.try_get_user_by_email(email)? {
+            return Ok(user.role >= Role::Scout);
+        }
+    }
+    Ok(false)
+}
+
+// Scouts and admins are responsible for the pending proposals
+// of all places, the owners only for their own places
+fn is_scout_or_admin<D: Db>(db: &D, owner: &PlaceOwner) -> Result<bool> {
+    if let PlaceOwner::User(email) = owner {
+        if let Some(user) = db.try_get_user_by_email(email)? {
+            return Ok(user.role >= Role::Scout);
+        }
+    }
+    Ok(false)
+}
+
+pub fn count_place_proposals<D: Db>(db: &D, owner: &PlaceOwner) -> Result<u64> {
+    if is_scout_or_admin(db, owner)? {
+        return Ok(db.count_pending_place_proposals()?);
+    }
+    Ok(db.count_place_proposals_by_owner(owner)?)
+}
+
+pub fn list_place_proposals<D: Db>(
+    db: &D,
+    owner: &PlaceOwner,
+    pagination: &Pagination,
+) -> Result<Vec<PlaceProposal>> {
+    if is_scout_or_admin(db, owner)? {
+        return Ok(db.list_pending_place_proposals(pagination)?);
+    }
+    Ok(db.list_place_proposals_by_owner(owner, pagination)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+
+    fn user(email: &str, role: Role) -> User {
+        User {
+            email: email.into(),
+            email_confirmed: true,
+            password: "secret".parse().unwrap(),
+            role,
+        }
+    }
+
+    #[test]
+    fn only_scouts_are_allowed_to_assign_owners() {
+        let place_id = Id::new();
+        let db = MockDb::default();
+        db.users
+            .borrow_mut()
+            .push(user("user@example.com", Role::User));
+        db.users
+            .borrow_mut()
+            .push(user("scout@example.com", Role::Scout));
+        let ownership = PlaceOwnership {
+            owners: vec![PlaceOwner::User("user@example.com".into())],
+            locked_fields: vec![
+                LockableField::Links,
+                LockableField::Contact,
+                LockableField::Links,
+            ],
+        };
+        assert!(
+            change_place_ownership(&db, "user@example.com", &place_id, ownership.clone()).is_err()
+        );
+        assert!(change_place_ownership(&db, "scout@example.com", &place_id, ownership).is_ok());
+        let ownership = load_place_ownership(&db, "user@example.com", &place_id).unwrap();
+        assert_eq!(
+            vec![LockableField::Contact, LockableField::Links],
+            ownership.locked_fields
+        );
+
+        // The owner is allowed to unlock fields...
+        let unlocked = PlaceOwnership {
+            locked_fields: vec![],
+            ..ownership.clone()
+        };
+        assert!(change_place_ownership(&db, "user@example.com", &place_id, unlocked).is_ok());
+        // ...but not to transfer the ownership
+        let transferred = PlaceOwnership {
+            owners: vec![PlaceOwner::User("scout@example.com".into())],
+            ..ownership
+        };
+        assert!(change_place_ownership(&db, "user@example.com", &place_id, transferred).is_err());
+    }
+
+    #[test]
+    fn owners_must_be_registered_users() {
+        let db = MockDb::default();
+        db.users
+            .borrow_mut()
+            .push(user("scout@example.com", Role::Scout));
+        let ownership = PlaceOwnership {
+            owners: vec![PlaceOwner::User("unknown@example.com".into())],
+            locked_fields: vec![],
+        };
+        match change_place_ownership(&db, "scout@example.com", &Id::new(), ownership) {
+            Err(Error::Parameter(ParameterError::UserDoesNotExist)) => {}
+            res => panic!("Unexpected result: {:?}", res),
+        }
+    }
+}
@@ -1,61 +1,110 @@
-use super::EventQuery;
+use super::{validate_page_request, EventOrder, EventQuery};
 use crate::core::{
     prelude::*,
     util::{extract_hash_tags, remove_hash_tags},
 };
+use chrono::NaiveDateTime;
 use ofdb_core::{bbox, tag};
 
 const DEFAULT_RESULT_LIMIT: usize = 100;
 
-#[allow(clippy::absurd_extreme_comparisons)]
+/// Query events and expand recurring events into their
+/// occurrences that start within the requested range.
 pub fn query_events<D: Db>(db: &D, index: &dyn IdIndex, query: EventQuery) -> Result<Vec<Event>> {
+    search_events(db, index, query, true)
+}
+
+/// Query events like `query_events()`, but return recurring
+/// events as a whole, i.e. without expanding them.
+pub fn query_event_series<D: Db>(
+    db: &D,
+    index: &dyn IdIndex,
+    query: EventQuery,
+) -> Result<Vec<Event>> {
+    search_events(db, index, query, false)
+}
+
+/// Query archived events that match the filter criteria and
+/// start within the requested range, i.e. events that have been
+/// cancelled. Without a lower bound only upcoming events are
+/// considered.
+///
+/// Archived events are not indexed and are filtered in memory.
+pub fn query_archived_event_series<D: Db>(db: &D, query: EventQuery) -> Result<Vec<Event>> {
+    let IndexQuery {
+        include_bbox,
+        near,
+        hash_tags,
+        text_tags,
+        ..
+    } = visible_events_query(&query);
+    let EventQuery {
+        start_min,
+        start_max,
+        limit,
+        ..
+    } = query;
+    let start_min = start_min.unwrap_or_else(Timestamp::now);
+    let limit = limit.unwrap_or(DEFAULT_RESULT_LIMIT);
+    let events = db
+        .archived_events_chronologically(start_min, start_max)?
+        .into_iter()
+        .filter(|event| {
+            event
+                .last_start()
+                .map(|last_start| last_start >= NaiveDateTime::from(start_min))
+                .unwrap_or(true)
+        })
+        .filter(|event| {
+            let pos = event.location.as_ref().map(|location| location.pos);
+            include_bbox
+                .as_ref()
+                .map(|bbox| pos.map(|pos| bbox.contains_point(pos)).unwrap_or(false))
+                .unwrap_or(true)
+                && near
+                    .as_ref()
+                    .map(|circle| pos.map(|pos| circle.contains_point(pos)).unwrap_or(false))
+                    .unwrap_or(true)
+        })
+        .filter(|event| hash_tags.iter().all(|tag| event.tags.contains(tag)))
+        .filter(|event| {
+            let text = format!(
+                "{} {} {}",
+                event.title,
+                event.description.as_deref().unwrap_or_default(),
+                event.tags.join(" ")
+            )
+            .to_lowercase();
+            text_tags.iter().all(|tag| text.contains(tag.as_str()))
+        })
+        .take(limit)
+        .collect();
+    Ok(events)
+}
+
+#[allow(clippy::absurd_extreme_comparisons)]
+fn search_events<D: Db>(
+    db: &D,
+    index: &dyn IdIndex,
+    query: EventQuery,
+    expand_recurrences: bool,
+) -> Result<Vec<Event>> {
     if query.is_empty() {
         // Special case for backwards compatibility
         return Ok(db.all_events_chronologically()?);
     }
+    let visible_events_query = visible_events_query(&query);
     let EventQuery {
         bbox: visible_bbox,
+        near,
         created_by,
         start_min,
         start_max,
-        tags,
-        text,
+        order,
         limit,
+        ..
     } = query;
 
-    let mut hash_tags = text.as_deref().map(extract_hash_tags).unwrap_or_default();
-    if let Some(tags) = tags {
-        hash_tags.reserve(hash_tags.len() + tags.len());
-        for hashtag in tags {
-            hash_tags.push(hashtag.to_owned());
-        }
-    }
-
-    let text = text.as_deref().map(remove_hash_tags).and_then(|text| {
-        if text.trim().is_empty() {
-            None
-        } else {
-            Some(text)
-        }
-    });
-
-    let text_tags = text
-        .as_deref()
-        .map(tag::split_text_into_tags)
-        .unwrap_or_default();
-
-    let visible_events_query = IndexQuery {
-        include_bbox: visible_bbox,
-        exclude_bbox: None,
-        categories: vec![Category::ID_EVENT],
-        hash_tags,
-        text_tags,
-        text,
-        ts_min_lb: start_min,
-        ts_min_ub: start_max,
-        ..Default::default()
-    };
-
     let limit = limit.unwrap_or_else(|| {
         info!(
             "No limit requested - Using default limit {} for event search results",
@@ -72,8 +121,10 @@ pub fn query_events<D: Db>(db: &D, index: &dyn IdIndex, query: EventQuery) -> Re
         .map_err(RepoError::Other)?;
 
     // 2nd query: Search for remaining invisible results
+    // Results within a circle are exhaustive, i.e. there are
+    // no invisible results
     let invisible_event_ids = if let Some(visible_bbox) = visible_bbox {
-        if visible_event_ids.len() < limit {
+        if visible_event_ids.len() < limit && near.is_none() {
             let invisible_events_query = IndexQuery {
                 include_bbox: Some(bbox::extend_bbox(&visible_bbox)),
                 exclude_bbox: visible_events_query.include_bbox,
@@ -101,15 +152,143 @@ pub fn query_events<D: Db>(db: &D, index: &dyn IdIndex, query: EventQuery) -> Re
     let mut events = db.get_events_chronologically(&event_ids)?;
 
     if let Some(ref email) = created_by {
-        if let Some(user) = db.try_get_user_by_email(email)? {
-            events = events
-                .into_iter()
-                .filter(|e| e.created_by.as_ref() == Some(&user.email))
-                .collect();
-        } else {
-            events = vec![];
-        }
+        events = filter_created_by(db, email, events)?;
+    }
+
+    // Expand recurring events into their occurrences within the range
+    if expand_recurrences && events.iter().any(|event| event.recurrence.is_some()) {
+        let start_min = start_min.map(NaiveDateTime::from);
+        let start_max = start_max.map(NaiveDateTime::from);
+        events = events
+            .into_iter()
+            .flat_map(|event| event.occurrences(start_min, start_max, limit))
+            .collect();
+        events.sort_by_key(|event| event.start);
+        events.truncate(limit);
+    }
+
+    if let (EventOrder::Distance, Some(circle)) = (order, near) {
+        let distance = |event: &Event| {
+            event
+                .location
+                .as_ref()
+                .and_then(|location| MapPoint::distance(circle.center(), location.pos))
+                .unwrap_or_else(Distance::infinite)
+        };
+        // The sort is stable, i.e. events with the same
+        // distance remain in chronological order
+        events.sort_by(|lhs, rhs| {
+            distance(lhs)
+                .partial_cmp(&distance(rhs))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
     }
 
     Ok(events)
 }
+
+/// Query a single page of events in the requested order.
+///
+/// Unlike `query_events()` the results are restricted to the
+/// bounding box, i.e. no invisible results are included, and
+/// recurring events are not expanded.
+pub fn query_events_page<D: Db>(
+    db: &D,
+    index: &dyn IdIndex,
+    query: EventQuery,
+) -> Result<(Vec<Event>, Option<PageCursor>)> {
+    let order = IndexOrder::from(query.order);
+    validate_page_request(order, query.cursor.as_ref(), query.near.as_ref())?;
+    let visible_events_query = visible_events_query(&query);
+    let EventQuery {
+        created_by,
+        limit,
+        cursor,
+        ..
+    } = query;
+    let limit = limit.unwrap_or(DEFAULT_RESULT_LIMIT);
+    let IndexPage {
+        items: event_ids,
+        next,
+    } = index
+        .query_ids_page(
+            IndexQueryMode::WithoutRating,
+            &visible_events_query,
+            order,
+            cursor.as_ref(),
+            limit,
+        )
+        .map_err(RepoError::Other)?;
+    let event_ids: Vec<_> = event_ids.iter().map(Id::as_str).collect();
+    let mut events = db.get_events_chronologically(&event_ids)?;
+    // Restore the order of the index
+    events.sort_by_key(|event| {
+        event_ids
+            .iter()
+            .position(|id| *id == event.id.as_str())
+            .unwrap_or(event_ids.len())
+    });
+    if let Some(ref email) = created_by {
+        events = filter_created_by(db, email, events)?;
+    }
+    Ok((events, next))
+}
+
+fn filter_created_by<D: Db>(db: &D, email: &str, events: Vec<Event>) -> Result<Vec<Event>> {
+    if let Some(user) = db.try_get_user_by_email(email)? {
+        Ok(events
+            .into_iter()
+            .filter(|e| e.created_by.as_ref() == Some(&user.email))
+            .collect())
+    } else {
+        Ok(vec![])
+    }
+}
+
+fn visible_events_query(query: &EventQuery) -> IndexQuery<'static, 'static> {
+    let EventQuery {
+        bbox: visible_bbox,
+        near,
+        start_min,
+        start_max,
+        tags,
+        text,
+        ..
+    } = query.clone();
+
+    let mut hash_tags = text.as_deref().map(extract_hash_tags).unwrap_or_default();
+    if let Some(tags) = tags {
+        hash_tags.reserve(hash_tags.len() + tags.len());
+        for hashtag in tags {
+            hash_tags.push(hashtag.to_owned());
+        }
+    }
+
+    let text = text.as_deref().map(remove_hash_tags).and_then(|text| {
+        if text.trim().is_empty() {
+            None
+        } else {
+            Some(text)
+        }
+    });
+
+    let text_tags = text
+        .as_deref()
+        .map(tag::split_text_into_tags)
+        .unwrap_or_default();
+
+    IndexQuery {
+        include_bbox: visible_bbox,
+        exclude_bbox: None,
+        near,
+        categories: vec![Category::ID_EVENT],
+        hash_tags,
+        text_tags,
+        text,
+        // Recurring events that started before might
+        // still have occurrences within the range
+        ts_last_lb: start_min,
+        ts_min_ub: start_max,
+        ..Default::default()
+    }
+}
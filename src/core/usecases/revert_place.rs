@@ -0,0 +1,166 @@
+use super::Storable;
+
+use crate::core::{prelude::*, util::validate::Validate};
+
+/// The context of the activity log entry of reverted places
+pub const REVERT_PLACE_CONTEXT: &str = "revert";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevertPlace {
+    pub reviewer_email: Email,
+    /// The previous revision that should be restored
+    pub revision: Revision,
+    pub comment: Option<String>,
+}
+
+/// Restore the contents of a previous revision as a new revision.
+///
+/// Only scouts and admins are entitled to revert places and
+/// changes of moderated tags still need to be authorized.
+pub fn prepare_reverted_place<D: Db>(
+    db: &D,
+    place_id: &str,
+    revert: RevertPlace,
+) -> Result<Storable> {
+    let RevertPlace {
+        reviewer_email,
+        revision,
+        comment,
+    } = revert;
+    super::authorize_user_by_email(db, &reviewer_email, Role::Scout)?;
+    let (current, _) = db.get_place(place_id)?;
+    if revision >= current.revision {
+        return Err(ParameterError::InvalidRevision.into());
+    }
+    let (mut place, _) = db.load_place_revision(place_id, revision)?;
+    let activity = Activity::now(Some(reviewer_email));
+    let last_cleared_revision = current.revision;
+    place.revision = last_cleared_revision.next();
+    // The license is immutable
+    place.license = current.license;
+    place.created = activity.clone();
+    place.validate()?;
+    let clearance_org_ids =
+        super::authorize_editing_of_tagged_entry(db, &current.tags, &place.tags, None)?;
+    info!(
+        "Reverting place {} from revision {} to {}",
+        place.id,
+        u64::from(last_cleared_revision),
+        u64::from(revision)
+    );
+    let activity_log = ActivityLog {
+        activity,
+        context: Some(REVERT_PLACE_CONTEXT.into()),
+        comment,
+    };
+    Ok(
+        Storable::revision(place, clearance_org_ids, last_cleared_revision)
+            .with_activity_log(activity_log),
+    )
+}
+
+pub fn store_reverted_place<D: Db>(db: &D, s: Storable) -> Result<(Place, Vec<Rating>)> {
+    match super::store_updated_place(db, s)? {
+        StoredPlace::Revision(place, ratings) => Ok((place, ratings)),
+        StoredPlace::Proposal(_) => unreachable!("reverted places are never proposed"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+
+    fn place_with_history(mock_db: &mut MockDb) -> Id {
+        let place_id = Id::new();
+        let first = Place::build()
+            .id(place_id.as_ref())
+            .revision(0)
+            .title("first")
+            .description("foo")
+            .license("CC0-1.0")
+            .finish();
+        let mut second = first.clone();
+        second.revision = Revision::from(1);
+        second.title = "second".into();
+        second.description = "vandalized".into();
+        mock_db.entries = vec![(second, ReviewStatus::Created)].into();
+        mock_db.place_revisions = vec![(first, ReviewStatus::Created)].into();
+        mock_db.users = vec![
+            User {
+                email: "scout@example.com".into(),
+                email_confirmed: true,
+                password: "secret".parse().unwrap(),
+                role: Role::Scout,
+            },
+            User {
+                email: "user@example.com".into(),
+                email_confirmed: true,
+                password: "secret".parse().unwrap(),
+                role: Role::User,
+            },
+        ]
+        .into();
+        place_id
+    }
+
+    fn revert(email: &str, revision: u64) -> RevertPlace {
+        RevertPlace {
+            reviewer_email: email.into(),
+            revision: Revision::from(revision),
+            comment: None,
+        }
+    }
+
+    #[test]
+    fn revert_place_to_previous_revision() {
+        let mut mock_db = MockDb::default();
+        let place_id = place_with_history(&mut mock_db);
+        let reverted =
+            prepare_reverted_place(&mock_db, place_id.as_ref(), revert("scout@example.com", 0))
+                .unwrap();
+        let (place, _) = store_reverted_place(&mock_db, reverted).unwrap();
+        assert_eq!(Revision::from(2), place.revision);
+        let (x, _) = mock_db.get_place(place_id.as_ref()).unwrap();
+        assert_eq!(Revision::from(2), x.revision);
+        assert_eq!("first", x.title);
+        assert_eq!("foo", x.description);
+        assert_eq!(
+            Some("scout@example.com"),
+            x.created.by.as_ref().map(Email::as_ref)
+        );
+        // The reverted revision is still part of the history
+        let (y, _) = mock_db
+            .load_place_revision(place_id.as_ref(), Revision::from(1))
+            .unwrap();
+        assert_eq!("vandalized", y.description);
+    }
+
+    #[test]
+    fn only_scouts_and_admins_can_revert_places() {
+        let mut mock_db = MockDb::default();
+        let place_id = place_with_history(&mut mock_db);
+        match prepare_reverted_place(&mock_db, place_id.as_ref(), revert("user@example.com", 0)) {
+            Err(Error::Parameter(ParameterError::Unauthorized)) => {}
+            Err(err) => panic!("Unexpected error: {:?}", err),
+            Ok(_) => panic!("Unexpected success"),
+        }
+    }
+
+    #[test]
+    fn only_previous_revisions_can_be_restored() {
+        let mut mock_db = MockDb::default();
+        let place_id = place_with_history(&mut mock_db);
+        for revision in &[1, 2] {
+            match prepare_reverted_place(
+                &mock_db,
+                place_id.as_ref(),
+                revert("scout@example.com", *revision),
+            ) {
+                Err(Error::Parameter(ParameterError::InvalidRevision)) => {}
+                Err(err) => panic!("Unexpected error: {:?}", err),
+                Ok(_) => panic!("Unexpected success"),
+            }
+        }
+    }
+}
@@ -0,0 +1,362 @@
+use super::Storable;
+
+use crate::core::{prelude::*, util::validate::Validate};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProposalDecision {
+    /// Apply all proposed changes
+    Accept,
+    /// Only apply the given fields onto the current
+    /// revision or all changed fields if empty
+    Merge(Vec<PlaceField>),
+    Reject,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProposalReview {
+    pub reviewer_email: Email,
+    pub decision: ProposalDecision,
+    pub comment: Option<String>,
+}
+
+pub struct ReviewedProposal {
+    proposal_id: Id,
+    status: ProposalStatus,
+    activity_log: ActivityLog,
+    storable: Option<Storable>,
+}
+
+// Scouts, admins and the owners of a place
+// are entitled to review proposals
+fn authorize_reviewer<D: Db>(db: &D, email: &str, place_id: &Id) -> Result<()> {
+    let user = db
+        .try_get_user_by_email(email)?
+        .ok_or(ParameterError::Unauthorized)?;
+    if user.role >= Role::Scout {
+        return Ok(());
+    }
+    if db.load_place_ownership(place_id)?.is_owned_by_user(email) {
+        return Ok(());
+    }
+    Err(ParameterError::Forbidden.into())
+}
+
+pub fn load_place_proposals<D: Db>(
+    db: &D,
+    user_email: &str,
+    place_id: &Id,
+) -> Result<Vec<PlaceProposal>> {
+    authorize_reviewer(db, user_email, place_id)?;
+    Ok(db.list_place_proposals_of_place(place_id.as_str())?)
+}
+
+pub fn prepare_reviewed_place_proposal<D: Db>(
+    db: &D,
+    proposal_id: &Id,
+    review: ProposalReview,
+) -> Result<ReviewedProposal> {
+    let ProposalReview {
+        reviewer_email,
+        decision,
+        comment,
+    } = review;
+    let proposal = db.get_place_proposal(proposal_id)?;
+    authorize_reviewer(db, &reviewer_email, &proposal.place.id)?;
+    if !proposal.status.is_pending() {
+        return Err(ParameterError::ProposalAlreadyReviewed.into());
+    }
+    let activity = Activity::now(Some(reviewer_email));
+    // The new revision is authored by the proposer and
+    // the reviewer is only recorded in the review log
+    let proposed = proposal.place.created.clone();
+    let (current, _) = db.get_place(proposal.place.id.as_str())?;
+    let (status, new_place) = match decision {
+        ProposalDecision::Reject => (ProposalStatus::Rejected, None),
+        ProposalDecision::Accept => {
+            if current.revision != proposal.base_revision {
+                // The place has been changed in the meantime
+                // and the proposal needs to be merged instead
+                return Err(RepoError::InvalidVersion.into());
+            }
+            (ProposalStatus::Accepted, Some(proposal.place))
+        }
+        ProposalDecision::Merge(fields) => {
+            let fields = if fields.is_empty() {
+                let (base, _) =
+                    db.load_place_revision(current.id.as_str(), proposal.base_revision)?;
+                PlaceField::changed_fields(&base, &proposal.place)
+            } else {
+                fields
+            };
+            let mut merged = current.clone();
+            for field in fields {
+                field.copy(&proposal.place, &mut merged);
+            }
+            (ProposalStatus::Merged, Some(merged))
+        }
+    };
+    let storable = if let Some(mut place) = new_place {
+        let last_cleared_revision = current.revision;
+        place.revision = last_cleared_revision.next();
+        place.license = current.license;
+        place.created = proposed;
+        place.validate()?;
+        let clearance_org_ids = super::authorize_editing_of_tagged_entry(
+            db,
+            &current.tags,
+            &place.tags,
+            proposal.org_id.as_ref(),
+        )?;
+        Some(Storable::revision(
+            place,
+            clearance_org_ids,
+            last_cleared_revision,
+        ))
+    } else {
+        None
+    };
+    Ok(ReviewedProposal {
+        proposal_id: proposal.id,
+        status,
+        activity_log: ActivityLog {
+            activity,
+            context: None,
+            comment,
+        },
+        storable,
+    })
+}
+
+pub fn store_reviewed_place_proposal<D: Db>(
+    db: &D,
+    reviewed: ReviewedProposal,
+) -> Result<Option<(Place, Vec<Rating>)>> {
+    let ReviewedProposal {
+        proposal_id,
+        status,
+        activity_log,
+        storable,
+    } = reviewed;
+    info!("Reviewing proposal {} as {}", proposal_id, status);
+    db.review_place_proposal(&proposal_id, status, &activity_log)?;
+    let storable = if let Some(storable) = storable {
+        storable
+    } else {
+        return Ok(None);
+    };
+    match super::store_updated_place(db, storable)? {
+        StoredPlace::Revision(place, ratings) => Ok(Some((place, ratings))),
+        StoredPlace::Proposal(_) => unreachable!("reviewed changes are never proposed again"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+
+    fn place_with_proposal(mock_db: &mut MockDb) -> (Id, Id) {
+        let place_id = Id::new();
+        let place = Place::build()
+            .id(place_id.as_ref())
+            .revision(1)
+            .title("foo")
+            .description("bar")
+            .license("CC0-1.0")
+            .finish();
+        let mut proposed = place.clone();
+        proposed.revision = Revision::from(2);
+        proposed.created = Activity::now(Some("proposer@example.com".into()));
+        proposed.title = "proposed title".into();
+        proposed.description = "proposed description".into();
+        let proposal = PlaceProposal {
+            id: Id::new(),
+            base_revision: Revision::from(1),
+            place: proposed,
+            org_id: None,
+            status: ProposalStatus::Pending,
+            reviewed: None,
+        };
+        let proposal_id = proposal.id.clone();
+        mock_db.entries = vec![(place, ReviewStatus::Created)].into();
+        mock_db.proposals = vec![proposal].into();
+        mock_db.users = vec![
+            User {
+                email: "scout@example.com".into(),
+                email_confirmed: true,
+                password: "secret".parse().unwrap(),
+                role: Role::Scout,
+            },
+            User {
+                email: "user@example.com".into(),
+                email_confirmed: true,
+                password: "secret".parse().unwrap(),
+                role: Role::User,
+            },
+        ]
+        .into();
+        (place_id, proposal_id)
+    }
+
+    fn review(email: &str, decision: ProposalDecision) -> ProposalReview {
+        ProposalReview {
+            reviewer_email: email.into(),
+            decision,
+            comment: Some("reviewed".into()),
+        }
+    }
+
+    #[test]
+    fn accept_proposal() {
+        let mut mock_db = MockDb::default();
+        let (place_id, proposal_id) = place_with_proposal(&mut mock_db);
+        assert!(prepare_reviewed_place_proposal(
+            &mock_db,
+            &proposal_id,
+            review("user@example.com", ProposalDecision::Accept)
+        )
+        .is_err());
+        let reviewed = prepare_reviewed_place_proposal(
+            &mock_db,
+            &proposal_id,
+            review("scout@example.com", ProposalDecision::Accept),
+        )
+        .unwrap();
+        let (place, _) = store_reviewed_place_proposal(&mock_db, reviewed)
+            .unwrap()
+            .unwrap();
+        assert_eq!(Revision::from(2), place.revision);
+        let (x, _) = mock_db.get_place(place_id.as_ref()).unwrap();
+        assert_eq!("proposed title", x.title);
+        assert_eq!("proposed description", x.description);
+        assert_eq!(
+            Some("proposer@example.com"),
+            x.created.by.as_ref().map(Email::as_ref)
+        );
+        let proposal = mock_db.get_place_proposal(&proposal_id).unwrap();
+        assert_eq!(ProposalStatus::Accepted, proposal.status);
+        assert_eq!(
+            Some("scout@example.com"),
+            proposal
+                .reviewed
+                .as_ref()
+                .and_then(|r| r.activity.by.as_ref())
+                .map(Email::as_ref)
+        );
+        assert_eq!(
+            Some("reviewed"),
+            proposal
+                .reviewed
+                .as_ref()
+                .and_then(|r| r.comment.as_ref())
+                .map(String::as_str)
+        );
+
+        // Proposals could only be reviewed once
+        match prepare_reviewed_place_proposal(
+            &mock_db,
+            &proposal_id,
+            review("scout@example.com", ProposalDecision::Reject),
+        ) {
+            Err(Error::Parameter(ParameterError::ProposalAlreadyReviewed)) => {}
+            Err(err) => panic!("Unexpected error: {:?}", err),
+            Ok(_) => panic!("Unexpected success"),
+        }
+    }
+
+    #[test]
+    fn accept_proposal_of_organization_with_moderated_tag() {
+        let mut mock_db = MockDb::default();
+        let (place_id, proposal_id) = place_with_proposal(&mut mock_db);
+        let org_id = Id::new();
+        mock_db.orgs = vec![Organization {
+            id: org_id.clone(),
+            name: "org".into(),
+            api_token: "token".into(),
+            moderated_tags: vec![ModeratedTag {
+                label: "org-tag".into(),
+                allow_add: false,
+                allow_remove: false,
+                require_clearance: false,
+            }],
+        }];
+        {
+            let mut proposals = mock_db.proposals.borrow_mut();
+            proposals[0].place.tags = vec!["org-tag".into()];
+            proposals[0].org_id = Some(org_id);
+        }
+        let reviewed = prepare_reviewed_place_proposal(
+            &mock_db,
+            &proposal_id,
+            review("scout@example.com", ProposalDecision::Accept),
+        )
+        .unwrap();
+        assert!(store_reviewed_place_proposal(&mock_db, reviewed)
+            .unwrap()
+            .is_some());
+        let (x, _) = mock_db.get_place(place_id.as_ref()).unwrap();
+        assert_eq!(vec!["org-tag".to_string()], x.tags);
+    }
+
+    #[test]
+    fn merge_selected_fields_of_outdated_proposal() {
+        let mut mock_db = MockDb::default();
+        let (place_id, proposal_id) = place_with_proposal(&mut mock_db);
+        // The place has been changed after the proposal
+        mock_db.entries.borrow_mut()[0].0.revision = Revision::from(2);
+
+        // Accepting all changes would override the current revision
+        match prepare_reviewed_place_proposal(
+            &mock_db,
+            &proposal_id,
+            review("scout@example.com", ProposalDecision::Accept),
+        ) {
+            Err(Error::Repo(RepoError::InvalidVersion)) => {}
+            Err(err) => panic!("Unexpected error: {:?}", err),
+            Ok(_) => panic!("Unexpected success"),
+        }
+
+        let reviewed = prepare_reviewed_place_proposal(
+            &mock_db,
+            &proposal_id,
+            review(
+                "scout@example.com",
+                ProposalDecision::Merge(vec![PlaceField::Title]),
+            ),
+        )
+        .unwrap();
+        assert!(store_reviewed_place_proposal(&mock_db, reviewed)
+            .unwrap()
+            .is_some());
+        let (x, _) = mock_db.get_place(place_id.as_ref()).unwrap();
+        assert_eq!(Revision::from(3), x.revision);
+        assert_eq!("proposed title", x.title);
+        assert_eq!("bar", x.description);
+        assert_eq!(
+            ProposalStatus::Merged,
+            mock_db.get_place_proposal(&proposal_id).unwrap().status
+        );
+    }
+
+    #[test]
+    fn reject_proposal() {
+        let mut mock_db = MockDb::default();
+        let (place_id, proposal_id) = place_with_proposal(&mut mock_db);
+        let reviewed = prepare_reviewed_place_proposal(
+            &mock_db,
+            &proposal_id,
+            review("scout@example.com", ProposalDecision::Reject),
+        )
+        .unwrap();
+        assert!(store_reviewed_place_proposal(&mock_db, reviewed)
+            .unwrap()
+            .is_none());
+        let (x, _) = mock_db.get_place(place_id.as_ref()).unwrap();
+        assert_eq!(Revision::from(1), x.revision);
+        assert_eq!("foo", x.title);
+        assert_eq!(
+            ProposalStatus::Rejected,
+            mock_db.get_place_proposal(&proposal_id).unwrap().status
+        );
+    }
+}
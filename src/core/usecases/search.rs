@@ -1,6 +1,11 @@
 use crate::core::{prelude::*, util};
-use ofdb_core::{bbox, tag};
-use ofdb_entities::geo::MapBbox;
+use chrono::NaiveDateTime;
+use ofdb_core::{
+    bbox,
+    cluster::{PlaceCluster, MAX_ZOOM},
+    tag,
+};
+use ofdb_entities::geo::{MapBbox, MapCircle, MapMultiPolygon};
 
 use std::collections::HashMap;
 
@@ -8,12 +13,15 @@ use std::collections::HashMap;
 #[derive(Debug, Clone)]
 pub struct SearchRequest<'a> {
     pub bbox       : MapBbox,
+    pub near       : Option<MapCircle>,
+    pub polygon    : Option<MapMultiPolygon>,
     pub ids        : Vec<&'a str>,
     pub categories : Vec<&'a str>,
     pub org_tag   :  Option<&'a str>,
     pub hash_tags  : Vec<&'a str>,
     pub text       : Option<&'a str>,
     pub status     : Vec<ReviewStatus>,
+    pub open_at    : Option<NaiveDateTime>,
 }
 
 pub fn clear_search_results<D: Db>(
@@ -62,6 +70,7 @@ pub fn clear_search_results<D: Db>(
                 // Replace the actual/current search result item with the last cleared revision
                 place = IndexedPlace {
                     id: id.into(),
+                    revision: Some(*last_cleared_revision),
                     description,
                     pos,
                     ratings,
@@ -79,21 +88,20 @@ pub fn clear_search_results<D: Db>(
     Ok(cleared_results)
 }
 
-pub fn search<D: Db>(
-    db: &D,
-    index: &dyn PlaceIndex,
-    req: SearchRequest,
-    limit: usize,
-) -> Result<(Vec<IndexedPlace>, Vec<IndexedPlace>)> {
+// The index query for all places within the bbox of the request
+fn visible_places_query<'a>(req: &SearchRequest<'a>) -> IndexQuery<'a, 'a> {
     let SearchRequest {
         bbox: visible_bbox,
+        near,
+        polygon,
         ids,
         categories,
         org_tag,
         hash_tags: req_hash_tags,
         text,
         status,
-    } = req;
+        open_at,
+    } = req.clone();
 
     let mut hash_tags = text.map(util::extract_hash_tags).unwrap_or_default();
     hash_tags.reserve(req_hash_tags.len() + 1);
@@ -117,17 +125,35 @@ pub fn search<D: Db>(
         .map(tag::split_text_into_tags)
         .unwrap_or_default();
 
-    let visible_places_query = IndexQuery {
+    IndexQuery {
         include_bbox: Some(visible_bbox),
         exclude_bbox: None,
+        near,
+        include_polygon: polygon,
         categories,
         ids,
         hash_tags,
         text_tags,
         text,
         status: Some(status),
+        open_at,
         ..Default::default()
-    };
+    }
+}
+
+pub fn search<D: Db>(
+    db: &D,
+    index: &dyn PlaceIndex,
+    req: SearchRequest,
+    limit: usize,
+) -> Result<(Vec<IndexedPlace>, Vec<IndexedPlace>)> {
+    let visible_places_query = visible_places_query(&req);
+    let SearchRequest {
+        bbox: visible_bbox,
+        near,
+        org_tag,
+        ..
+    } = req;
 
     // 1st query: Search for visible results only
     // This is required to reliably retrieve all available results!
@@ -145,7 +171,9 @@ pub fn search<D: Db>(
     }
 
     // 2nd query: Search for remaining invisible results
-    let mut invisible_places = if visible_places.len() < limit {
+    // Results within a circle are exhaustive, i.e. there are
+    // no invisible results
+    let mut invisible_places = if visible_places.len() < limit && near.is_none() {
         let invisible_places_query = IndexQuery {
             include_bbox: Some(bbox::extend_bbox(&visible_bbox)),
             exclude_bbox: visible_places_query.include_bbox,
@@ -169,6 +197,88 @@ pub fn search<D: Db>(
     Ok((visible_places, invisible_places))
 }
 
+/// Search for a single page of places in the requested order.
+///
+/// Unlike `search()` the results are restricted to the visible
+/// bounding box, i.e. no invisible results are included.
+pub fn search_page<D: Db>(
+    db: &D,
+    index: &dyn PlaceIndex,
+    req: SearchRequest,
+    order: IndexOrder,
+    cursor: Option<&PageCursor>,
+    limit: usize,
+) -> Result<(Vec<IndexedPlace>, Option<PageCursor>)> {
+    if order == IndexOrder::Start {
+        // Places don't have a start
+        return Err(Error::Parameter(ParameterError::InvalidSearchOrder));
+    }
+    super::validate_page_request(order, cursor, req.near.as_ref())?;
+    let query = visible_places_query(&req);
+    let IndexPage { mut items, next } = index
+        .query_places_page(&query, order, cursor, limit)
+        .map_err(RepoError::Other)?;
+    if let Some(org_tag) = req.org_tag {
+        if let Some(org_id) = db.map_tag_to_clearance_org_id(org_tag)? {
+            items = clear_search_results(db, &org_id, org_tag, items)?;
+        }
+    }
+    Ok((items, next))
+}
+
+/// Aggregate all places that match the search request
+/// into grid-based clusters for the given zoom level.
+///
+/// Pending clearances of moderated tags are not considered,
+/// i.e. the clusters are based on the current revisions.
+pub fn cluster_places(
+    index: &dyn PlaceIndex,
+    req: SearchRequest,
+    zoom: u8,
+) -> Result<Vec<PlaceCluster>> {
+    if zoom > MAX_ZOOM {
+        return Err(Error::Parameter(ParameterError::InvalidZoom));
+    }
+    let query = visible_places_query(&req);
+    Ok(index
+        .cluster_places(&query, zoom)
+        .map_err(RepoError::Other)?)
+}
+
+/// Count the tags, categories and review status of all
+/// places that match the search request.
+///
+/// Like clusters the facets are based on the current revisions.
+pub fn search_facets(
+    index: &dyn PlaceIndex,
+    req: SearchRequest,
+    max_tags: usize,
+) -> Result<PlaceFacets> {
+    let query = visible_places_query(&req);
+    Ok(index
+        .facet_places(&query, max_tags)
+        .map_err(RepoError::Other)?)
+}
+
+/// Complete the prefix of a search term by the titles, tags
+/// and cities of all visible places that match the request.
+pub fn suggest_search_terms(
+    index: &dyn PlaceIndex,
+    req: SearchRequest,
+    prefix: &str,
+    limit: usize,
+) -> Result<Vec<PlaceSuggestion>> {
+    // Hash tags are completed like any other tags
+    let prefix = prefix.trim().trim_start_matches('#');
+    if prefix.is_empty() || limit == 0 {
+        return Ok(vec![]);
+    }
+    let query = visible_places_query(&req);
+    Ok(index
+        .suggest_places(&query, prefix, limit)
+        .map_err(RepoError::Other)?)
+}
+
 /// The global search usecase is like the one
 /// of usual internet search engines that exists
 /// of only one single search input.
@@ -2,7 +2,7 @@ use crate::core::{
     prelude::*,
     usecases::create_user_from_email,
     util::{
-        parse::parse_url_param,
+        parse::{parse_time_zone, parse_url_param},
         validate::{AutoCorrect, Validate},
     },
 };
@@ -14,8 +14,11 @@ use std::str::FromStr;
 pub struct NewEvent {
     pub title        : String,
     pub description  : Option<String>,
+    // Both start/end are UTC time stamps
     pub start        : i64,
     pub end          : Option<i64>,
+    // IANA time zone, e.g. "Europe/Berlin" (see DefaultEventTimeZone)
+    pub time_zone    : Option<String>,
     pub lat          : Option<f64>,
     pub lng          : Option<f64>,
     pub street       : Option<String>,
@@ -32,6 +35,97 @@ pub struct NewEvent {
     pub organizer    : Option<String>,
     pub image_url     : Option<String>,
     pub image_link_url: Option<String>,
+    // RRULE, e.g. "FREQ=WEEKLY;BYDAY=TH"
+    pub recurrence    : Option<String>,
+    pub recurrence_overrides: Option<Vec<NewOccurrenceOverride>>,
+}
+
+/// Cancels or modifies a single occurrence of a recurring event.
+#[rustfmt::skip]
+#[derive(Deserialize, Default, Debug, Clone)]
+pub struct NewOccurrenceOverride {
+    // The start of the occurrence according to the recurrence rule
+    pub recurrence_id : i64,
+    #[serde(default)]
+    pub cancelled     : bool,
+    pub start         : Option<i64>,
+    pub end           : Option<i64>,
+    pub title         : Option<String>,
+    pub description   : Option<String>,
+}
+
+impl From<NewOccurrenceOverride> for OccurrenceOverride {
+    fn from(from: NewOccurrenceOverride) -> Self {
+        let NewOccurrenceOverride {
+            recurrence_id,
+            cancelled,
+            start,
+            end,
+            title,
+            description,
+        } = from;
+        Self {
+            recurrence_id: NaiveDateTime::from_timestamp(recurrence_id, 0),
+            cancelled,
+            start: start.map(|x| NaiveDateTime::from_timestamp(x, 0)),
+            end: end.map(|x| NaiveDateTime::from_timestamp(x, 0)),
+            title: title.filter(|x| !x.trim().is_empty()),
+            description: description.filter(|x| !x.trim().is_empty()),
+        }
+    }
+}
+
+/// The time zone of events that are submitted without a time zone.
+///
+/// The start and end times of those events are local times in this
+/// time zone, the same as for all events that have been stored before
+/// time zones were supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DefaultEventTimeZone(pub Tz);
+
+impl NewEvent {
+    /// Convert the local times of an event without a time zone
+    /// into UTC. Events with a time zone are not modified.
+    pub fn with_default_time_zone(self, default_time_zone: DefaultEventTimeZone) -> Self {
+        let has_time_zone = self
+            .time_zone
+            .as_deref()
+            .map_or(false, |tz| !tz.trim().is_empty());
+        if has_time_zone {
+            return self;
+        }
+        let DefaultEventTimeZone(tz) = default_time_zone;
+        let into_utc = |ts: i64| local_to_utc(tz, NaiveDateTime::from_timestamp(ts, 0)).timestamp();
+        let recurrence = self
+            .recurrence
+            .map(|rule| match rule.trim().parse::<RecurrenceRule>() {
+                Ok(mut rule) => {
+                    rule.until = rule.until.map(|until| local_to_utc(tz, until));
+                    rule.to_string()
+                }
+                // Invalid rules are rejected later
+                Err(_) => rule,
+            });
+        let recurrence_overrides = self.recurrence_overrides.map(|overrides| {
+            overrides
+                .into_iter()
+                .map(|o| NewOccurrenceOverride {
+                    recurrence_id: into_utc(o.recurrence_id),
+                    start: o.start.map(into_utc),
+                    end: o.end.map(into_utc),
+                    ..o
+                })
+                .collect()
+        });
+        Self {
+            start: into_utc(self.start),
+            end: self.end.map(into_utc),
+            time_zone: Some(tz.name().to_string()),
+            recurrence,
+            recurrence_overrides,
+            ..self
+        }
+    }
 }
 
 pub enum NewEventMode<'a> {
@@ -53,6 +147,7 @@ pub fn import_new_event<D: Db>(
         description,
         start,
         end,
+        time_zone,
         email,
         telephone,
         lat,
@@ -69,6 +164,8 @@ pub fn import_new_event<D: Db>(
         homepage,
         image_url,
         image_link_url,
+        recurrence,
+        recurrence_overrides,
         ..
     } = e;
     let org = token
@@ -110,7 +207,7 @@ pub fn import_new_event<D: Db>(
                 }
                 new_tags.sort_unstable();
                 new_tags.dedup();
-                super::authorize_editing_of_tagged_entry(db, &[], &new_tags, Some(&org))?
+                super::authorize_editing_of_tagged_entry(db, &[], &new_tags, Some(&org.id))?
             }
             NewEventMode::Update(id) => {
                 let old_tags = db.get_event(id)?.tags;
@@ -136,7 +233,7 @@ pub fn import_new_event<D: Db>(
                     new_tags.dedup();
                 }
                 // Verify that the org is entitled to update this event according to the owned tags
-                super::authorize_editing_of_tagged_entry(db, &old_tags, &new_tags, Some(&org))?
+                super::authorize_editing_of_tagged_entry(db, &old_tags, &new_tags, Some(&org.id))?
             }
         }
     } else {
@@ -197,9 +294,10 @@ pub fn import_new_event<D: Db>(
         None
     };
 
-    let id = match mode {
-        NewEventMode::Create => Id::new(),
-        NewEventMode::Update(id) => Id::from(id),
+    let (id, created_at) = match mode {
+        NewEventMode::Create => (Id::new(), Some(TimestampMs::now())),
+        // The time of creation is not modified when updating an event
+        NewEventMode::Update(id) => (Id::from(id), None),
     };
 
     let created_by = if let Some(ref email) = created_by {
@@ -250,6 +348,12 @@ pub fn import_new_event<D: Db>(
 
     let start = NaiveDateTime::from_timestamp(start, 0);
     let end = end.map(|e| NaiveDateTime::from_timestamp(e, 0));
+    let time_zone = time_zone
+        .as_deref()
+        .filter(|tz| !tz.trim().is_empty())
+        .map(parse_time_zone)
+        .transpose()?
+        .unwrap_or(Tz::UTC);
 
     let homepage = homepage
         .and_then(|ref url| parse_url_param(url).transpose())
@@ -261,21 +365,44 @@ pub fn import_new_event<D: Db>(
         .and_then(|ref url| parse_url_param(url).transpose())
         .transpose()?;
 
+    let recurrence = recurrence
+        .as_deref()
+        .map(str::trim)
+        .filter(|rule| !rule.is_empty())
+        .map(|rule| {
+            let rule = rule
+                .parse::<RecurrenceRule>()
+                .map_err(ParameterError::InvalidRecurrenceRule)?;
+            let mut overrides: Vec<OccurrenceOverride> = vec![];
+            for o in recurrence_overrides.unwrap_or_default() {
+                let o = OccurrenceOverride::from(o);
+                // Later overrides of the same occurrence replace earlier ones
+                overrides.retain(|x| x.recurrence_id != o.recurrence_id);
+                overrides.push(o);
+            }
+            Ok::<_, ParameterError>(EventRecurrence { rule, overrides })
+        })
+        .transpose()?;
+
     let event = Event {
         id,
         title,
         start,
         end,
+        time_zone,
         description,
         location,
         contact,
         homepage,
         tags: new_tags,
         created_by,
+        created_at,
         registration,
         archived: None,
         image_url,
         image_link_url,
+        recurrence,
+        recurrence_id: None,
     };
     let event = event.auto_correct();
     event.validate()?;
@@ -307,6 +434,7 @@ mod tests {
 
     use super::super::tests::MockDb;
     use super::*;
+    use chrono::Duration;
 
     fn create_new_event<D: Db>(db: &D, token: Option<&str>, e: NewEvent) -> Result<Event> {
         let s = import_new_event(db, token, e, NewEventMode::Create)?;
@@ -322,6 +450,7 @@ mod tests {
             description  : Some("bar".into()),
             start        : now,
             end          : None,
+            time_zone    : None,
             lat          : None,
             lng          : None,
             street       : None,
@@ -338,6 +467,8 @@ mod tests {
             organizer    : None,
             image_url     : Some("http://somewhere.com/image_url.jpg".to_string()),
             image_link_url: Some("my.url/test.ext".to_string()),
+            recurrence    : None,
+            recurrence_overrides: None,
         };
         let mock_db = MockDb::default();
         let id = create_new_event(&mock_db, None, x).unwrap().id;
@@ -369,6 +500,7 @@ mod tests {
             description  : Some("bar".into()),
             start        : Utc::now().naive_utc().timestamp(),
             end          : None,
+            time_zone    : None,
             lat          : None,
             lng          : None,
             street       : None,
@@ -385,11 +517,52 @@ mod tests {
             organizer    : None,
             image_url     : None,
             image_link_url: None,
+            recurrence    : None,
+            recurrence_overrides: None,
         };
         let mock_db: MockDb = MockDb::default();
         assert!(create_new_event(&mock_db, None, x).is_err());
     }
 
+    #[test]
+    fn create_recurring_event() {
+        let start = Utc::now().naive_utc().date().and_hms(18, 0, 0);
+        let x = NewEvent {
+            title: "foo".into(),
+            start: start.timestamp(),
+            created_by: Some("foo@bar.com".into()),
+            recurrence: Some(" FREQ=WEEKLY;COUNT=10 ".into()),
+            recurrence_overrides: Some(vec![NewOccurrenceOverride {
+                recurrence_id: (start + Duration::weeks(1)).timestamp(),
+                cancelled: true,
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+        let mock_db = MockDb::default();
+        let event = create_new_event(&mock_db, None, x.clone()).unwrap();
+        let recurrence = event.recurrence.unwrap();
+        assert_eq!("FREQ=WEEKLY;COUNT=10", recurrence.rule.to_string());
+        assert_eq!(1, recurrence.overrides.len());
+        assert!(recurrence.overrides[0].cancelled);
+
+        let invalid_rule = NewEvent {
+            recurrence: Some("FREQ=FORTNIGHTLY".into()),
+            ..x.clone()
+        };
+        assert!(create_new_event(&mock_db, None, invalid_rule).is_err());
+
+        let invalid_override = NewEvent {
+            recurrence_overrides: Some(vec![NewOccurrenceOverride {
+                recurrence_id: (start + Duration::days(1)).timestamp(),
+                cancelled: true,
+                ..Default::default()
+            }]),
+            ..x
+        };
+        assert!(create_new_event(&mock_db, None, invalid_override).is_err());
+    }
+
     #[test]
     fn create_event_with_valid_non_existing_creator_email() {
         #[rustfmt::skip]
@@ -398,6 +571,7 @@ mod tests {
             description  : Some("bar".into()),
             start        : Utc::now().naive_utc().timestamp(),
             end          : None,
+            time_zone    : None,
             lat          : None,
             lng          : None,
             street       : None,
@@ -414,6 +588,8 @@ mod tests {
             organizer    : None,
             image_url     : None,
             image_link_url: None,
+            recurrence    : None,
+            recurrence_overrides: None,
         };
         let mock_db: MockDb = MockDb::default();
         assert!(create_new_event(&mock_db, None, x).is_ok());
@@ -441,6 +617,7 @@ mod tests {
             description  : Some("bar".into()),
             start        : Utc::now().naive_utc().timestamp(),
             end          : None,
+            time_zone    : None,
             lat          : None,
             lng          : None,
             street       : None,
@@ -457,9 +634,50 @@ mod tests {
             organizer    : None,
             image_url     : None,
             image_link_url: None,
+            recurrence    : None,
+            recurrence_overrides: None,
         };
         assert!(create_new_event(&mock_db, None, x).is_ok());
         let users = mock_db.all_users().unwrap();
         assert_eq!(users.len(), 1);
     }
+
+    #[test]
+    fn interpret_times_without_time_zone_as_local_times() {
+        let berlin = DefaultEventTimeZone(chrono_tz::Europe::Berlin);
+        // 18:00 local time in winter (CET) and summer (CEST)
+        let winter = NaiveDate::from_ymd(2021, 3, 11).and_hms(18, 0, 0);
+        let summer = NaiveDate::from_ymd(2021, 4, 1).and_hms(18, 0, 0);
+        let e = NewEvent {
+            title: "foo".into(),
+            start: winter.timestamp(),
+            end: Some(summer.timestamp()),
+            recurrence: Some("FREQ=WEEKLY;COUNT=4".into()),
+            recurrence_overrides: Some(vec![NewOccurrenceOverride {
+                recurrence_id: summer.timestamp(),
+                cancelled: true,
+                ..Default::default()
+            }]),
+            ..Default::default()
+        }
+        .with_default_time_zone(berlin);
+        assert_eq!(Some("Europe/Berlin"), e.time_zone.as_deref());
+        assert_eq!((winter - Duration::hours(1)).timestamp(), e.start);
+        assert_eq!(Some((summer - Duration::hours(2)).timestamp()), e.end);
+        assert_eq!(
+            (summer - Duration::hours(2)).timestamp(),
+            e.recurrence_overrides.unwrap()[0].recurrence_id
+        );
+
+        // Times of events with a time zone are already in UTC
+        let e = NewEvent {
+            title: "foo".into(),
+            start: winter.timestamp(),
+            time_zone: Some("UTC".into()),
+            ..Default::default()
+        }
+        .with_default_time_zone(berlin);
+        assert_eq!(Some("UTC"), e.time_zone.as_deref());
+        assert_eq!(winter.timestamp(), e.start);
+    }
 }
@@ -0,0 +1,83 @@
+use crate::core::prelude::*;
+
+/// All changes within the scope of a subscription that
+/// are reported together.
+#[derive(Debug, Clone)]
+pub struct SubscriptionDigest {
+    pub subscription: BboxSubscription,
+    pub places: Vec<Place>,
+    pub events: Vec<Event>,
+}
+
+impl SubscriptionDigest {
+    pub fn is_empty(&self) -> bool {
+        self.places.is_empty() && self.events.is_empty()
+    }
+}
+
+pub fn due_digest_subscriptions<D: Db>(db: &D, now: TimestampMs) -> Result<Vec<BboxSubscription>> {
+    Ok(db
+        .all_bbox_subscriptions()?
+        .into_iter()
+        .filter(|s| s.is_digest_due(now))
+        .collect())
+}
+
+/// Collect all places and events that have been changed
+/// since the last digest of the subscription.
+pub fn collect_subscription_digest<D: Db>(
+    db: &D,
+    subscription: BboxSubscription,
+) -> Result<SubscriptionDigest> {
+    let since = subscription
+        .last_digest_at
+        .unwrap_or_else(|| TimestampMs::from_inner(0));
+
+    let place_ids = db.changed_place_ids_since(since)?;
+    let place_ids: Vec<_> = place_ids.iter().map(Id::as_str).collect();
+    let places = db
+        .get_places(&place_ids)?
+        .into_iter()
+        .filter(|(_, status)| status.exists())
+        .map(|(place, _)| place)
+        .filter(|place| {
+            subscription.is_in_scope(place.location.pos, place.tags.iter().map(String::as_str))
+        })
+        .collect();
+
+    let event_ids = db.changed_event_ids_since(since)?;
+    let event_ids: Vec<_> = event_ids.iter().map(Id::as_str).collect();
+    let events = db
+        .get_events_chronologically(&event_ids)?
+        .into_iter()
+        .filter(|event| event.archived.is_none())
+        .filter(|event| match event.location {
+            Some(ref location) if location.pos.is_valid() => subscription.is_in_scope(
+                location.pos,
+                event
+                    .tags
+                    .iter()
+                    .map(String::as_str)
+                    .chain(std::iter::once(Category::TAG_EVENT)),
+            ),
+            _ => false,
+        })
+        .collect();
+
+    Ok(SubscriptionDigest {
+        subscription,
+        places,
+        events,
+    })
+}
+
+/// Remember the end of the period that has been
+/// covered by the last digest.
+pub fn finish_subscription_digest<D: Db>(
+    db: &D,
+    mut subscription: BboxSubscription,
+    until: TimestampMs,
+) -> Result<()> {
+    subscription.last_digest_at = Some(until);
+    Ok(db.update_bbox_subscription(&subscription)?)
+}
@@ -4,6 +4,7 @@ use crate::core::{usecases, util::geo};
 
 use anyhow::Result as Fallible;
 use chrono::prelude::*;
+use ofdb_core::cluster::PlaceCluster;
 use std::{cell::RefCell, result};
 
 //TODO: move tests to corresponding usecase
@@ -71,6 +72,8 @@ impl Key for Organization {
 #[derive(Default)]
 pub struct MockDb {
     pub entries: RefCell<Vec<(Place, ReviewStatus)>>,
+    // Previous revisions of all entries
+    pub place_revisions: RefCell<Vec<(Place, ReviewStatus)>>,
     pub events: RefCell<Vec<Event>>,
     pub tags: RefCell<Vec<Tag>>,
     pub users: RefCell<Vec<User>>,
@@ -79,6 +82,8 @@ pub struct MockDb {
     pub bbox_subscriptions: RefCell<Vec<BboxSubscription>>,
     pub orgs: Vec<Organization>,
     pub token: RefCell<Vec<UserToken>>,
+    pub ownerships: RefCell<Vec<(Id, PlaceOwnership)>>,
+    pub proposals: RefCell<Vec<PlaceProposal>>,
 }
 
 impl UserTokenRepo for MockDb {
@@ -129,6 +134,20 @@ impl Indexer for DummySearchEngine {
     fn flush_index(&mut self) -> Fallible<()> {
         Ok(())
     }
+
+    fn generation(&self) -> u64 {
+        0
+    }
+
+    fn high_water_mark(&self) -> Option<TimestampMs> {
+        None
+    }
+
+    fn set_high_water_mark(&mut self, _high_water_mark: TimestampMs) {}
+
+    fn clear_index(&mut self) -> Fallible<()> {
+        Ok(())
+    }
 }
 
 impl IdIndex for DummySearchEngine {
@@ -140,6 +159,17 @@ impl IdIndex for DummySearchEngine {
     ) -> Fallible<Vec<Id>> {
         unimplemented!();
     }
+
+    fn query_ids_page(
+        &self,
+        _mode: IndexQueryMode,
+        _query: &IndexQuery,
+        _order: IndexOrder,
+        _cursor: Option<&PageCursor>,
+        _limit: usize,
+    ) -> Fallible<IndexPage<Id>> {
+        unimplemented!();
+    }
 }
 
 impl IdIndexer for DummySearchEngine {
@@ -152,6 +182,37 @@ impl PlaceIndex for DummySearchEngine {
     fn query_places(&self, _query: &IndexQuery, _limit: usize) -> Fallible<Vec<IndexedPlace>> {
         unimplemented!();
     }
+
+    fn all_place_ids(&self) -> Fallible<Vec<Id>> {
+        unimplemented!();
+    }
+
+    fn query_places_page(
+        &self,
+        _query: &IndexQuery,
+        _order: IndexOrder,
+        _cursor: Option<&PageCursor>,
+        _limit: usize,
+    ) -> Fallible<IndexPage<IndexedPlace>> {
+        unimplemented!();
+    }
+
+    fn cluster_places(&self, _query: &IndexQuery, _zoom: u8) -> Fallible<Vec<PlaceCluster>> {
+        unimplemented!();
+    }
+
+    fn facet_places(&self, _query: &IndexQuery, _max_tags: usize) -> Fallible<PlaceFacets> {
+        unimplemented!();
+    }
+
+    fn suggest_places(
+        &self,
+        _query: &IndexQuery,
+        _prefix: &str,
+        _limit: usize,
+    ) -> Fallible<Vec<PlaceSuggestion>> {
+        unimplemented!();
+    }
 }
 
 impl PlaceIndexer for DummySearchEngine {
@@ -165,6 +226,16 @@ impl PlaceIndexer for DummySearchEngine {
     }
 }
 
+impl EventIndex for DummySearchEngine {
+    fn query_events(&self, _query: &IndexQuery, _limit: usize) -> Fallible<Vec<IndexedEvent>> {
+        unimplemented!();
+    }
+
+    fn all_event_ids(&self) -> Fallible<Vec<Id>> {
+        unimplemented!();
+    }
+}
+
 impl EventIndexer for DummySearchEngine {
     fn add_or_update_event(&self, _event: &Event) -> Fallible<()> {
         Ok(())
@@ -211,11 +282,21 @@ fn update<T: Clone + Key>(objects: &mut Vec<T>, e: &T) -> RepoResult<()> {
 
 impl PlaceRepo for MockDb {
     fn create_or_update_place(&self, place: Place) -> RepoResult<()> {
+        if let Ok(previous) = get(&self.entries.borrow(), place.id.as_ref()) {
+            self.place_revisions.borrow_mut().push(previous);
+        }
         create_or_replace(
             &mut self.entries.borrow_mut(),
             (place, ReviewStatus::Created),
         )
     }
+    fn create_place_revision_with_log(
+        &self,
+        place: Place,
+        _activity: &ActivityLog,
+    ) -> RepoResult<()> {
+        self.create_or_update_place(place)
+    }
     fn get_place(&self, id: &str) -> RepoResult<(Place, ReviewStatus)> {
         get(&self.entries.borrow(), id).and_then(|(p, s)| {
             if s != ReviewStatus::Archived {
@@ -262,6 +343,17 @@ impl PlaceRepo for MockDb {
     fn count_places(&self) -> RepoResult<usize> {
         self.all_places().map(|v| v.len())
     }
+    fn load_places_chunk(&self, pagination: &Pagination) -> RepoResult<Vec<(Place, ReviewStatus)>> {
+        Ok(self
+            .all_places()?
+            .into_iter()
+            .skip(pagination.offset.unwrap_or(0) as usize)
+            .take(pagination.limit.unwrap_or(u64::MAX) as usize)
+            .collect())
+    }
+    fn changed_place_ids_since(&self, _since: TimestampMs) -> RepoResult<Vec<Id>> {
+        unimplemented!();
+    }
 
     fn review_places(
         &self,
@@ -280,8 +372,14 @@ impl PlaceRepo for MockDb {
         unimplemented!();
     }
 
-    fn load_place_revision(&self, _id: &str, _rev: Revision) -> RepoResult<(Place, ReviewStatus)> {
-        unimplemented!();
+    fn load_place_revision(&self, id: &str, rev: Revision) -> RepoResult<(Place, ReviewStatus)> {
+        self.entries
+            .borrow()
+            .iter()
+            .chain(self.place_revisions.borrow().iter())
+            .find(|(p, _)| p.id.as_ref() == id && p.revision == rev)
+            .cloned()
+            .ok_or(RepoError::NotFound)
     }
 }
 
@@ -312,6 +410,28 @@ impl EventGateway for MockDb {
         Ok(events)
     }
 
+    fn archived_events_chronologically(
+        &self,
+        start_min: Timestamp,
+        start_max: Option<Timestamp>,
+    ) -> RepoResult<Vec<Event>> {
+        let mut events: Vec<_> = self
+            .events
+            .borrow()
+            .iter()
+            .filter(|e| e.archived.is_some())
+            .filter(|e| e.recurrence.is_some() || e.start >= NaiveDateTime::from(start_min))
+            .filter(|e| {
+                start_max
+                    .map(|max| e.start <= NaiveDateTime::from(max))
+                    .unwrap_or(true)
+            })
+            .cloned()
+            .collect();
+        events.sort_by(|a, b| a.start.cmp(&b.start));
+        Ok(events)
+    }
+
     fn get_events_chronologically(&self, ids: &[&str]) -> RepoResult<Vec<Event>> {
         let mut events: Vec<_> = self
             .events
@@ -329,6 +449,22 @@ impl EventGateway for MockDb {
         self.all_events_chronologically().map(|v| v.len())
     }
 
+    fn load_events_chunk(&self, pagination: &Pagination) -> RepoResult<Vec<Event>> {
+        Ok(self
+            .events
+            .borrow()
+            .iter()
+            .filter(|e| e.archived.is_none())
+            .skip(pagination.offset.unwrap_or(0) as usize)
+            .take(pagination.limit.unwrap_or(u64::MAX) as usize)
+            .cloned()
+            .collect())
+    }
+
+    fn changed_event_ids_since(&self, _since: TimestampMs) -> RepoResult<Vec<Id>> {
+        unimplemented!();
+    }
+
     fn update_event(&self, e: &Event) -> RepoResult<()> {
         update(&mut self.events.borrow_mut(), e)
     }
@@ -340,6 +476,23 @@ impl EventGateway for MockDb {
     fn delete_event_with_matching_tags(&self, _id: &str, _tags: &[&str]) -> RepoResult<Option<()>> {
         unimplemented!();
     }
+
+    fn get_event_id_by_import_uid(
+        &self,
+        _org_id: &Id,
+        _import_uid: &str,
+    ) -> RepoResult<Option<Id>> {
+        unimplemented!();
+    }
+
+    fn create_event_import_uid(
+        &self,
+        _org_id: &Id,
+        _import_uid: &str,
+        _event_id: &Id,
+    ) -> RepoResult<()> {
+        unimplemented!();
+    }
 }
 
 impl UserGateway for MockDb {
@@ -566,6 +719,193 @@ impl PlaceClearanceRepo for MockDb {
     }
 }
 
+impl PlaceOwnershipRepo for MockDb {
+    fn load_place_ownership(&self, place_id: &Id) -> RepoResult<PlaceOwnership> {
+        Ok(self
+            .ownerships
+            .borrow()
+            .iter()
+            .find(|(id, _)| id == place_id)
+            .map(|(_, ownership)| ownership.clone())
+            .unwrap_or_default())
+    }
+
+    fn replace_place_ownership(&self, place_id: &Id, ownership: &PlaceOwnership) -> RepoResult<()> {
+        let mut ownerships = self.ownerships.borrow_mut();
+        ownerships.retain(|(id, _)| id != place_id);
+        ownerships.push((place_id.clone(), ownership.clone()));
+        Ok(())
+    }
+}
+
+impl PlaceProposalRepo for MockDb {
+    fn add_place_proposal(&self, proposal: &PlaceProposal) -> RepoResult<()> {
+        self.proposals.borrow_mut().push(proposal.clone());
+        Ok(())
+    }
+
+    fn get_place_proposal(&self, id: &Id) -> RepoResult<PlaceProposal> {
+        self.proposals
+            .borrow()
+            .iter()
+            .find(|p| p.id == *id)
+            .cloned()
+            .ok_or(RepoError::NotFound)
+    }
+
+    fn review_place_proposal(
+        &self,
+        id: &Id,
+        status: ProposalStatus,
+        activity: &ActivityLog,
+    ) -> RepoResult<()> {
+        let mut proposals = self.proposals.borrow_mut();
+        let proposal = proposals
+            .iter_mut()
+            .find(|p| p.id == *id)
+            .ok_or(RepoError::NotFound)?;
+        proposal.status = status;
+        proposal.reviewed = Some(activity.clone());
+        Ok(())
+    }
+
+    fn list_place_proposals_of_place(&self, place_id: &str) -> RepoResult<Vec<PlaceProposal>> {
+        Ok(self
+            .proposals
+            .borrow()
+            .iter()
+            .filter(|p| p.place.id.as_str() == place_id)
+            .cloned()
+            .collect())
+    }
+
+    fn count_pending_place_proposals(&self) -> RepoResult<u64> {
+        self.list_pending_place_proposals(&Default::default())
+            .map(|proposals| proposals.len() as u64)
+    }
+
+    fn list_pending_place_proposals(
+        &self,
+        _pagination: &Pagination,
+    ) -> RepoResult<Vec<PlaceProposal>> {
+        Ok(self
+            .proposals
+            .borrow()
+            .iter()
+            .filter(|p| p.status.is_pending())
+            .cloned()
+            .collect())
+    }
+
+    fn count_place_proposals_by_owner(&self, owner: &PlaceOwner) -> RepoResult<u64> {
+        self.list_place_proposals_by_owner(owner, &Default::default())
+            .map(|proposals| proposals.len() as u64)
+    }
+
+    fn list_place_proposals_by_owner(
+        &self,
+        owner: &PlaceOwner,
+        _pagination: &Pagination,
+    ) -> RepoResult<Vec<PlaceProposal>> {
+        let ownerships = self.ownerships.borrow();
+        Ok(self
+            .proposals
+            .borrow()
+            .iter()
+            .filter(|p| {
+                p.status.is_pending()
+                    && ownerships
+                        .iter()
+                        .any(|(id, o)| *id == p.place.id && o.owners.contains(owner))
+            })
+            .cloned()
+            .collect())
+    }
+}
+
+impl WebhookRepo for MockDb {
+    fn create_webhook_subscription(&self, _subscription: &WebhookSubscription) -> RepoResult<()> {
+        unimplemented!();
+    }
+
+    fn delete_webhook_subscription(&self, _org_id: &Id, _id: &Id) -> RepoResult<()> {
+        unimplemented!();
+    }
+
+    fn get_webhook_subscription(&self, _id: &Id) -> RepoResult<WebhookSubscription> {
+        unimplemented!();
+    }
+
+    fn all_webhook_subscriptions(&self) -> RepoResult<Vec<WebhookSubscription>> {
+        Ok(vec![])
+    }
+
+    fn all_webhook_subscriptions_by_org(
+        &self,
+        _org_id: &Id,
+    ) -> RepoResult<Vec<WebhookSubscription>> {
+        Ok(vec![])
+    }
+
+    fn create_webhook_delivery(&self, _delivery: &WebhookDelivery) -> RepoResult<()> {
+        unimplemented!();
+    }
+
+    fn update_webhook_delivery(&self, _delivery: &WebhookDelivery) -> RepoResult<()> {
+        unimplemented!();
+    }
+
+    fn load_webhook_delivery(&self, _id: &Id) -> RepoResult<WebhookDelivery> {
+        unimplemented!();
+    }
+
+    fn recent_webhook_deliveries(
+        &self,
+        _subscription_id: &Id,
+        _pagination: &Pagination,
+    ) -> RepoResult<Vec<WebhookDelivery>> {
+        Ok(vec![])
+    }
+}
+
+impl JobRepo for MockDb {
+    fn enqueue_job(&self, _job: &Job) -> RepoResult<()> {
+        unimplemented!();
+    }
+
+    fn acquire_next_job(&self, _now: TimestampMs) -> RepoResult<Option<Job>> {
+        Ok(None)
+    }
+
+    fn update_job(&self, _job: &Job) -> RepoResult<()> {
+        unimplemented!();
+    }
+
+    fn delete_job(&self, _id: &Id) -> RepoResult<()> {
+        unimplemented!();
+    }
+
+    fn load_jobs(&self, _status: JobStatus, _pagination: &Pagination) -> RepoResult<Vec<Job>> {
+        Ok(vec![])
+    }
+
+    fn count_jobs(&self, _status: JobStatus) -> RepoResult<usize> {
+        Ok(0)
+    }
+
+    fn count_jobs_by_kind(&self, _kind: &str, _status: JobStatus) -> RepoResult<usize> {
+        Ok(0)
+    }
+
+    fn retry_failed_jobs(&self, _run_at: TimestampMs) -> RepoResult<usize> {
+        Ok(0)
+    }
+
+    fn release_running_jobs(&self) -> RepoResult<usize> {
+        Ok(0)
+    }
+}
+
 impl Db for MockDb {
     fn create_tag_if_it_does_not_exist(&self, e: &Tag) -> RepoResult<()> {
         if let Err(err) = create(&mut self.tags.borrow_mut(), e.clone()) {
@@ -583,6 +923,14 @@ impl Db for MockDb {
         create(&mut self.bbox_subscriptions.borrow_mut(), s.clone())
     }
 
+    fn update_bbox_subscription(&self, s: &BboxSubscription) -> RepoResult<()> {
+        update(&mut self.bbox_subscriptions.borrow_mut(), s)
+    }
+
+    fn get_bbox_subscription(&self, id: &Id) -> RepoResult<BboxSubscription> {
+        get(&self.bbox_subscriptions.borrow(), id.as_str())
+    }
+
     fn all_tags(&self) -> RepoResult<Vec<Tag>> {
         Ok(self.tags.borrow().clone())
     }
@@ -607,6 +955,11 @@ impl Db for MockDb {
             .collect())
     }
 
+    fn delete_bbox_subscription(&self, id: &Id) -> RepoResult<()> {
+        self.bbox_subscriptions.borrow_mut().retain(|s| s.id != *id);
+        Ok(())
+    }
+
     fn delete_bbox_subscriptions_by_email(&self, user_email: &str) -> RepoResult<()> {
         self.bbox_subscriptions
             .borrow_mut()
@@ -650,7 +1003,9 @@ fn create_bbox_subscription() {
             role: Role::Guest,
         })
         .is_ok());
-    assert!(usecases::subscribe_to_bbox(&db, "abc@abc.de".into(), bbox_new).is_ok());
+    assert!(
+        usecases::subscribe_to_bbox(&db, "abc@abc.de".into(), bbox_new, Default::default()).is_ok()
+    );
 
     let bbox_subscription = db.all_bbox_subscriptions().unwrap()[0].clone();
     assert_eq!(
@@ -685,11 +1040,18 @@ fn modify_bbox_subscription() {
     let bbox_subscription = BboxSubscription {
         id: "123".into(),
         user_email: "abc@abc.de".into(),
+        title: None,
         bbox: bbox_old,
+        polygon: None,
+        tags: vec![],
+        categories: vec![],
+        delivery: SubscriptionDelivery::Immediate,
+        last_digest_at: None,
+        unsubscribe_nonce: Nonce::new(),
     };
     db.create_bbox_subscription(&bbox_subscription).unwrap();
 
-    usecases::subscribe_to_bbox(&db, "abc@abc.de".into(), bbox_new).unwrap();
+    usecases::subscribe_to_bbox(&db, "abc@abc.de".into(), bbox_new, Default::default()).unwrap();
 
     let bbox_subscriptions: Vec<_> = db
         .all_bbox_subscriptions()
@@ -730,7 +1092,14 @@ fn get_bbox_subscriptions() {
     let bbox_subscription = BboxSubscription {
         id: "1".into(),
         user_email: "a@abc.de".into(),
+        title: None,
         bbox: bbox1,
+        polygon: None,
+        tags: vec![],
+        categories: vec![],
+        delivery: SubscriptionDelivery::Immediate,
+        last_digest_at: None,
+        unsubscribe_nonce: Nonce::new(),
     };
     assert!(db.create_bbox_subscription(&bbox_subscription).is_ok());
 
@@ -745,7 +1114,14 @@ fn get_bbox_subscriptions() {
     let bbox_subscription2 = BboxSubscription {
         id: "2".into(),
         user_email: "b@abc.de".into(),
+        title: None,
         bbox: bbox2,
+        polygon: None,
+        tags: vec![],
+        categories: vec![],
+        delivery: SubscriptionDelivery::Immediate,
+        last_digest_at: None,
+        unsubscribe_nonce: Nonce::new(),
     };
     assert!(db.create_bbox_subscription(&bbox_subscription2).is_ok());
     let bbox_subscriptions = usecases::get_bbox_subscriptions(&db, "b@abc.de");
@@ -754,7 +1130,7 @@ fn get_bbox_subscriptions() {
 }
 
 #[test]
-fn email_addresses_by_coordinate() {
+fn subscriptions_for_place() {
     let db = MockDb::default();
     let bbox_new = geo::MapBbox::new(
         MapPoint::from_lat_lng_deg(0.0, 0.0),
@@ -769,17 +1145,245 @@ fn email_addresses_by_coordinate() {
     })
     .unwrap();
 
-    usecases::subscribe_to_bbox(&db, "abc@abc.de".into(), bbox_new).unwrap();
+    usecases::subscribe_to_bbox(&db, "abc@abc.de".into(), bbox_new, Default::default()).unwrap();
 
-    let email_addresses =
-        usecases::email_addresses_by_coordinate(&db, MapPoint::from_lat_lng_deg(5.0, 5.0)).unwrap();
-    assert_eq!(email_addresses.len(), 1);
-    assert_eq!(email_addresses[0], "abc@abc.de");
+    let inside = Place::build()
+        .pos(MapPoint::from_lat_lng_deg(5.0, 5.0))
+        .finish();
+    let subscriptions = usecases::subscriptions_for_place(&db, &inside).unwrap();
+    assert_eq!(subscriptions.len(), 1);
+    assert_eq!(subscriptions[0].user_email, "abc@abc.de");
+
+    let outside = Place::build()
+        .pos(MapPoint::from_lat_lng_deg(20.0, 20.0))
+        .finish();
+    let no_subscriptions = usecases::subscriptions_for_place(&db, &outside).unwrap();
+    assert_eq!(no_subscriptions.len(), 0);
+}
 
-    let no_email_addresses =
-        usecases::email_addresses_by_coordinate(&db, MapPoint::from_lat_lng_deg(20.0, 20.0))
+#[test]
+fn subscriptions_for_place_within_polygon() {
+    let db = MockDb::default();
+    db.create_user(&User {
+        email: "abc@abc.de".into(),
+        email_confirmed: true,
+        password: "secret".parse::<Password>().unwrap(),
+        role: Role::Guest,
+    })
+    .unwrap();
+
+    // The bounding box is replaced by the bounding box of the polygon
+    let subscription = usecases::create_bbox_subscription(
+        &db,
+        "abc@abc.de".into(),
+        geo::MapBbox::default(),
+        usecases::BboxSubscriptionOptions {
+            polygon: Some("POLYGON ((0 0, 10 0, 0 10, 0 0))".parse().unwrap()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        LatCoord::from_deg(10.0),
+        subscription.bbox.northeast().lat()
+    );
+
+    let inside = Place::build()
+        .pos(MapPoint::from_lat_lng_deg(2.0, 2.0))
+        .finish();
+    assert_eq!(
+        1,
+        usecases::subscriptions_for_place(&db, &inside)
+            .unwrap()
+            .len()
+    );
+    let outside_polygon = Place::build()
+        .pos(MapPoint::from_lat_lng_deg(8.0, 8.0))
+        .finish();
+    assert!(usecases::subscriptions_for_place(&db, &outside_polygon)
+        .unwrap()
+        .is_empty());
+
+    assert!(usecases::create_bbox_subscription(
+        &db,
+        "abc@abc.de".into(),
+        geo::MapBbox::default(),
+        usecases::BboxSubscriptionOptions {
+            polygon: Some(geo::MapMultiPolygon::default()),
+            ..Default::default()
+        },
+    )
+    .is_err());
+}
+
+#[test]
+fn subscriptions_for_place_with_filters_and_digest() {
+    let db = MockDb::default();
+    let bbox = geo::MapBbox::new(
+        MapPoint::from_lat_lng_deg(0.0, 0.0),
+        MapPoint::from_lat_lng_deg(10.0, 10.0),
+    );
+    for email in &["tag@abc.de", "digest@abc.de"] {
+        db.create_user(&User {
+            email: (*email).into(),
+            email_confirmed: true,
+            password: "secret".parse::<Password>().unwrap(),
+            role: Role::Guest,
+        })
+        .unwrap();
+    }
+    usecases::subscribe_to_bbox(
+        &db,
+        "tag@abc.de".into(),
+        bbox,
+        usecases::BboxSubscriptionOptions {
+            tags: vec!["#Organic".into()],
+            categories: vec![Category::ID_NON_PROFIT.into()],
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    usecases::subscribe_to_bbox(
+        &db,
+        "digest@abc.de".into(),
+        bbox,
+        usecases::BboxSubscriptionOptions {
+            delivery: SubscriptionDelivery::DailyDigest,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let pos = MapPoint::from_lat_lng_deg(5.0, 5.0);
+    let matching = Place::build()
+        .pos(pos)
+        .tags(vec!["organic", Category::TAG_NON_PROFIT])
+        .finish();
+    let subscriptions = usecases::subscriptions_for_place(&db, &matching).unwrap();
+    assert_eq!(1, subscriptions.len());
+    assert_eq!("tag@abc.de", subscriptions[0].user_email);
+    let other_category = Place::build()
+        .pos(pos)
+        .tags(vec!["organic", Category::TAG_COMMERCIAL])
+        .finish();
+    assert!(usecases::subscriptions_for_place(&db, &other_category)
+        .unwrap()
+        .is_empty());
+
+    assert!(usecases::subscribe_to_bbox(
+        &db,
+        "tag@abc.de".into(),
+        bbox,
+        usecases::BboxSubscriptionOptions {
+            categories: vec!["unknown".into()],
+            ..Default::default()
+        },
+    )
+    .is_err());
+}
+
+#[test]
+fn manage_multiple_bbox_subscriptions() {
+    let db = MockDb::default();
+    let bbox = geo::MapBbox::new(
+        MapPoint::from_lat_lng_deg(0.0, 0.0),
+        MapPoint::from_lat_lng_deg(10.0, 10.0),
+    );
+    for email in &["a@abc.de", "b@abc.de"] {
+        db.create_user(&User {
+            email: (*email).into(),
+            email_confirmed: true,
+            password: "secret".parse::<Password>().unwrap(),
+            role: Role::Guest,
+        })
+        .unwrap();
+    }
+    let home = usecases::create_bbox_subscription(
+        &db,
+        "a@abc.de".into(),
+        bbox,
+        usecases::BboxSubscriptionOptions {
+            title: Some(" Home ".into()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(Some("Home"), home.title.as_deref());
+    let work = usecases::create_bbox_subscription(&db, "a@abc.de".into(), bbox, Default::default())
+        .unwrap();
+    assert_eq!(
+        2,
+        usecases::get_bbox_subscriptions(&db, "a@abc.de")
+            .unwrap()
+            .len()
+    );
+
+    let updated = usecases::update_bbox_subscription(
+        &db,
+        "a@abc.de",
+        &work.id,
+        bbox,
+        usecases::BboxSubscriptionOptions {
+            title: Some("Work".into()),
+            delivery: SubscriptionDelivery::WeeklyDigest,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(Some("Work"), updated.title.as_deref());
+    assert_eq!(work.unsubscribe_nonce, updated.unsubscribe_nonce);
+    assert_eq!(
+        SubscriptionDelivery::WeeklyDigest,
+        db.get_bbox_subscription(&work.id).unwrap().delivery
+    );
+
+    // Subscriptions of other users cannot be modified
+    assert!(matches!(
+        usecases::update_bbox_subscription(&db, "b@abc.de", &work.id, bbox, Default::default()),
+        Err(Error::Parameter(ParameterError::Forbidden))
+    ));
+    assert!(matches!(
+        usecases::delete_bbox_subscription(&db, "b@abc.de", &work.id),
+        Err(Error::Parameter(ParameterError::Forbidden))
+    ));
+
+    usecases::delete_bbox_subscription(&db, "a@abc.de", &work.id).unwrap();
+    let remaining = usecases::get_bbox_subscriptions(&db, "a@abc.de").unwrap();
+    assert_eq!(1, remaining.len());
+    assert_eq!(home.id, remaining[0].id);
+}
+
+#[test]
+fn unsubscribe_bbox_with_token() {
+    let db = MockDb::default();
+    let bbox = geo::MapBbox::new(
+        MapPoint::from_lat_lng_deg(0.0, 0.0),
+        MapPoint::from_lat_lng_deg(10.0, 10.0),
+    );
+    let subscription =
+        usecases::create_bbox_subscription(&db, "a@abc.de".into(), bbox, Default::default())
             .unwrap();
-    assert_eq!(no_email_addresses.len(), 0);
+
+    let forged = SubscriptionNonce {
+        subscription_id: subscription.id.clone(),
+        nonce: Nonce::new(),
+    };
+    assert!(matches!(
+        usecases::unsubscribe_bbox_with_token(&db, &forged.encode_to_string()),
+        Err(Error::Parameter(ParameterError::TokenInvalid))
+    ));
+    assert!(usecases::unsubscribe_bbox_with_token(&db, "invalid").is_err());
+
+    let token = subscription.unsubscribe_token().encode_to_string();
+    let unsubscribed = usecases::unsubscribe_bbox_with_token(&db, &token).unwrap();
+    assert_eq!(subscription.id, unsubscribed.id);
+    assert!(db.all_bbox_subscriptions().unwrap().is_empty());
+
+    // The token can only be used once
+    assert!(matches!(
+        usecases::unsubscribe_bbox_with_token(&db, &token),
+        Err(Error::Parameter(ParameterError::TokenInvalid))
+    ));
 }
 
 #[test]
@@ -823,15 +1427,19 @@ fn receive_event_with_creators_email() {
         description: None,
         start: NaiveDateTime::from_timestamp(0, 0),
         end: None,
+        time_zone: Tz::UTC,
         contact: None,
         location: None,
         homepage: None,
         tags: vec![],
         created_by: Some("abc@abc.de".into()),
+        created_at: None,
         registration: None,
         archived: None,
         image_url: None,
         image_link_url: None,
+        recurrence: None,
+        recurrence_id: None,
     })
     .unwrap();
     let e = usecases::get_event(&db, "x").unwrap();
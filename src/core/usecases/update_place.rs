@@ -91,10 +91,72 @@ impl From<Place> for UpdatePlace {
     }
 }
 
+/// How edits of places by anonymous visitors and
+/// unprivileged users are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceEditMode {
+    /// All edits create a new revision immediately
+    Direct,
+    /// Edits are stored as proposals that need to be
+    /// reviewed by scouts, admins or the owners
+    Proposal,
+}
+
+impl Default for PlaceEditMode {
+    fn default() -> Self {
+        Self::Direct
+    }
+}
+
 pub struct Storable {
     place: Place,
+    // The organization that edited the place
+    org_id: Option<Id>,
     clearance_org_ids: Vec<Id>,
     last_cleared_revision: Revision,
+    // Changes of locked fields by others than the owners
+    // are only stored as a proposal
+    changed_locked_fields: Vec<LockableField>,
+    // All changes are stored as a proposal
+    review_required: bool,
+    // Replaces the default log entry of the new revision
+    activity_log: Option<ActivityLog>,
+}
+
+impl Storable {
+    // A new revision that doesn't need to be reviewed
+    pub(super) fn revision(
+        place: Place,
+        clearance_org_ids: Vec<Id>,
+        last_cleared_revision: Revision,
+    ) -> Self {
+        Self {
+            place,
+            org_id: None,
+            clearance_org_ids,
+            last_cleared_revision,
+            changed_locked_fields: vec![],
+            review_required: false,
+            activity_log: None,
+        }
+    }
+
+    pub(super) fn with_activity_log(self, activity_log: ActivityLog) -> Self {
+        Self {
+            activity_log: Some(activity_log),
+            ..self
+        }
+    }
+}
+
+/// The outcome of storing an updated place.
+#[derive(Debug)]
+pub enum StoredPlace {
+    /// A new revision of the place
+    Revision(Place, Vec<Rating>),
+    /// Changes that need to be reviewed by scouts,
+    /// admins or the owners of the place
+    Proposal(PlaceProposal),
 }
 
 pub fn prepare_updated_place<D: Db>(
@@ -103,6 +165,7 @@ pub fn prepare_updated_place<D: Db>(
     e: UpdatePlace,
     created_by_email: Option<&str>,
     created_by_org: Option<&Organization>,
+    mode: PlaceEditMode,
 ) -> Result<Storable> {
     let UpdatePlace {
         version,
@@ -143,20 +206,17 @@ pub fn prepare_updated_place<D: Db>(
         Some(address)
     };
 
-    let (revision, last_cleared_revision, old_tags, license) = {
-        let (old_place, _review_status) = db.get_place(place_id.as_str())?;
-        // Check for revision conflict (optimistic locking)
-        let revision = Revision::from(version);
-        if old_place.revision.next() != revision {
-            return Err(RepoError::InvalidVersion.into());
-        }
-        let last_cleared_revision = old_place.revision;
-        // The license is immutable
-        let license = old_place.license;
-        // The existing tags are needed for authorization
-        let old_tags = old_place.tags;
-        (revision, last_cleared_revision, old_tags, license)
-    };
+    let (old_place, _review_status) = db.get_place(place_id.as_str())?;
+    // Check for revision conflict (optimistic locking)
+    let revision = Revision::from(version);
+    if old_place.revision.next() != revision {
+        return Err(RepoError::InvalidVersion.into());
+    }
+    let last_cleared_revision = old_place.revision;
+    // The license is immutable
+    let license = old_place.license.clone();
+    // The existing tags are needed for authorization
+    let old_tags = &old_place.tags;
 
     let categories: Vec<_> = categories.into_iter().map(Id::from).collect();
     let new_tags = super::prepare_tag_list(
@@ -164,8 +224,12 @@ pub fn prepare_updated_place<D: Db>(
             .iter()
             .map(String::as_str),
     );
-    let clearance_org_ids =
-        super::authorize_editing_of_tagged_entry(db, &old_tags, &new_tags, created_by_org)?;
+    let clearance_org_ids = super::authorize_editing_of_tagged_entry(
+        db,
+        old_tags,
+        &new_tags,
+        created_by_org.map(|org| &org.id),
+    )?;
 
     let homepage = homepage
         .and_then(|ref url| parse_url_param(url).transpose())
@@ -209,7 +273,7 @@ pub fn prepare_updated_place<D: Db>(
         opening_hours: opening_hours
             .map(|s| {
                 s.parse()
-                    .map_err(|_| Error::Parameter(ParameterError::InvalidOpeningHours))
+                    .map_err(|err| Error::Parameter(ParameterError::InvalidOpeningHours(err)))
             })
             .transpose()?,
         founded_on,
@@ -217,24 +281,83 @@ pub fn prepare_updated_place<D: Db>(
         tags: new_tags,
     };
     place.validate()?;
+    let ownership = db.load_place_ownership(&place.id)?;
+    let changed_locked_fields = if super::authorize_editing_of_locked_fields(
+        db,
+        &ownership,
+        created_by_email,
+        created_by_org,
+    )? {
+        vec![]
+    } else {
+        ownership.changed_locked_fields(&old_place, &place)
+    };
+    let review_required = match mode {
+        PlaceEditMode::Direct => false,
+        PlaceEditMode::Proposal => {
+            !is_trusted_editor(db, &ownership, created_by_email, created_by_org)?
+        }
+    };
     Ok(Storable {
         place,
+        org_id: created_by_org.map(|org| org.id.clone()),
         clearance_org_ids,
         last_cleared_revision,
+        changed_locked_fields,
+        review_required,
+        activity_log: None,
     })
 }
 
-pub fn store_updated_place<D: Db>(db: &D, s: Storable) -> Result<(Place, Vec<Rating>)> {
+// Organizations, owners, scouts and admins are allowed
+// to edit places without a review
+fn is_trusted_editor<D: Db>(
+    db: &D,
+    ownership: &PlaceOwnership,
+    created_by_email: Option<&str>,
+    created_by_org: Option<&Organization>,
+) -> Result<bool> {
+    if created_by_org.is_some() {
+        return Ok(true);
+    }
+    super::authorize_editing_of_locked_fields(db, ownership, created_by_email, None)
+}
+
+pub fn store_updated_place<D: Db>(db: &D, s: Storable) -> Result<StoredPlace> {
     let Storable {
         place,
+        org_id,
         clearance_org_ids,
         last_cleared_revision,
+        changed_locked_fields,
+        review_required,
+        activity_log,
     } = s;
+    if review_required || !changed_locked_fields.is_empty() {
+        debug!(
+            "Storing proposal for place {} (locked fields: {:?})",
+            place.id, changed_locked_fields
+        );
+        let proposal = PlaceProposal {
+            id: Id::new(),
+            base_revision: last_cleared_revision,
+            place,
+            org_id,
+            status: ProposalStatus::Pending,
+            reviewed: None,
+        };
+        db.add_place_proposal(&proposal)?;
+        return Ok(StoredPlace::Proposal(proposal));
+    }
     debug!("Storing updated place revision: {:?}", place);
     for t in &place.tags {
         db.create_tag_if_it_does_not_exist(&Tag { id: t.clone() })?;
     }
-    db.create_or_update_place(place.clone())?;
+    if let Some(activity_log) = activity_log {
+        db.create_place_revision_with_log(place.clone(), &activity_log)?;
+    } else {
+        db.create_or_update_place(place.clone())?;
+    }
     if !clearance_org_ids.is_empty() {
         let pending_clearance = PendingClearanceForPlace {
             place_id: place.id.clone(),
@@ -244,7 +367,7 @@ pub fn store_updated_place<D: Db>(db: &D, s: Storable) -> Result<(Place, Vec<Rat
         super::clearance::place::add_pending_clearance(db, &clearance_org_ids, &pending_clearance)?;
     }
     let ratings = db.load_ratings_of_place(place.id.as_ref())?;
-    Ok((place, ratings))
+    Ok(StoredPlace::Revision(place, ratings))
 }
 
 #[cfg(test)]
@@ -295,8 +418,15 @@ mod tests {
         let mut mock_db = MockDb::default();
         mock_db.entries = vec![(old, ReviewStatus::Created)].into();
         let now = TimestampMs::now();
-        let storable =
-            prepare_updated_place(&mock_db, id, new, Some("test@example.com"), None).unwrap();
+        let storable = prepare_updated_place(
+            &mock_db,
+            id,
+            new,
+            Some("test@example.com"),
+            None,
+            PlaceEditMode::Direct,
+        )
+        .unwrap();
         assert!(store_updated_place(&mock_db, storable).is_ok());
         assert_eq!(mock_db.entries.borrow().len(), 1);
         let (x, _) = &mock_db.entries.borrow()[0];
@@ -370,7 +500,8 @@ mod tests {
         };
         let mut mock_db = MockDb::default();
         mock_db.entries = vec![(old, ReviewStatus::Created)].into();
-        let err = match prepare_updated_place(&mock_db, id, new, None, None) {
+        let err = match prepare_updated_place(&mock_db, id, new, None, None, PlaceEditMode::Direct)
+        {
             Ok(storable) => store_updated_place(&mock_db, storable).err(),
             Err(err) => Some(err),
         };
@@ -418,7 +549,7 @@ mod tests {
         };
         let mut mock_db = MockDb::default();
         mock_db.entries = vec![].into();
-        let result = prepare_updated_place(&mock_db, id, new, None, None);
+        let result = prepare_updated_place(&mock_db, id, new, None, None, PlaceEditMode::Direct);
         assert!(result.is_err());
         match result.err().unwrap() {
             Error::Repo(err) => match err {
@@ -470,10 +601,153 @@ mod tests {
         let mut mock_db = MockDb::default();
         mock_db.entries = vec![(old, ReviewStatus::Created)].into();
         mock_db.tags = vec![Tag { id: "bio".into() }, Tag { id: "fair".into() }].into();
-        let storable = prepare_updated_place(&mock_db, id.clone(), new, None, None).unwrap();
+        let storable =
+            prepare_updated_place(&mock_db, id.clone(), new, None, None, PlaceEditMode::Direct)
+                .unwrap();
         assert!(store_updated_place(&mock_db, storable).is_ok());
         let (e, _) = mock_db.get_place(id.as_ref()).unwrap();
         assert_eq!(e.tags, vec!["vegan"]);
         assert_eq!(mock_db.tags.borrow().len(), 3);
     }
+
+    #[test]
+    fn propose_changes_of_locked_fields() {
+        let id = Id::new();
+        let old = Place::build()
+            .id(id.as_ref())
+            .revision(1)
+            .title("foo")
+            .license("CC0-1.0")
+            .finish();
+        let mut mock_db = MockDb::default();
+        mock_db.entries = vec![(old.clone(), ReviewStatus::Created)].into();
+        mock_db.ownerships = vec![(
+            id.clone(),
+            PlaceOwnership {
+                owners: vec![PlaceOwner::User("owner@example.com".into())],
+                locked_fields: vec![LockableField::Contact],
+            },
+        )]
+        .into();
+
+        let mut new = UpdatePlace::from(old);
+        new.version = 2;
+        new.email = Some("other@example.com".into());
+
+        // Anonymous changes of locked fields are only proposed
+        let storable = prepare_updated_place(
+            &mock_db,
+            id.clone(),
+            new.clone(),
+            None,
+            None,
+            PlaceEditMode::Direct,
+        )
+        .unwrap();
+        match store_updated_place(&mock_db, storable).unwrap() {
+            StoredPlace::Proposal(proposal) => {
+                assert_eq!(Revision::from(1), proposal.base_revision);
+                assert_eq!(
+                    Some("other@example.com"),
+                    proposal
+                        .place
+                        .contact
+                        .as_ref()
+                        .and_then(|c| c.email.as_ref())
+                        .map(Email::as_ref)
+                );
+            }
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        assert_eq!(1, mock_db.proposals.borrow().len());
+        let (x, _) = mock_db.get_place(id.as_ref()).unwrap();
+        assert_eq!(Revision::from(1), x.revision);
+
+        // The owner is allowed to change locked fields
+        let storable = prepare_updated_place(
+            &mock_db,
+            id.clone(),
+            new.clone(),
+            Some("owner@example.com"),
+            None,
+            PlaceEditMode::Direct,
+        )
+        .unwrap();
+        match store_updated_place(&mock_db, storable).unwrap() {
+            StoredPlace::Revision(place, _) => assert_eq!(Revision::from(2), place.revision),
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        assert_eq!(1, mock_db.proposals.borrow().len());
+
+        // Unlocked fields could be changed by anyone
+        let (x, _) = mock_db.get_place(id.as_ref()).unwrap();
+        let mut new = UpdatePlace::from(x);
+        new.version = 3;
+        new.title = "bar".into();
+        let storable =
+            prepare_updated_place(&mock_db, id, new, None, None, PlaceEditMode::Direct).unwrap();
+        assert!(matches!(
+            store_updated_place(&mock_db, storable).unwrap(),
+            StoredPlace::Revision(..)
+        ));
+    }
+
+    #[test]
+    fn propose_edits_of_unprivileged_users() {
+        let id = Id::new();
+        let old = Place::build()
+            .id(id.as_ref())
+            .revision(1)
+            .title("foo")
+            .license("CC0-1.0")
+            .finish();
+        let mut mock_db = MockDb::default();
+        mock_db.entries = vec![(old.clone(), ReviewStatus::Created)].into();
+        mock_db.users = vec![User {
+            email: "scout@example.com".into(),
+            email_confirmed: true,
+            password: "secret".parse().unwrap(),
+            role: Role::Scout,
+        }]
+        .into();
+
+        let mut new = UpdatePlace::from(old);
+        new.version = 2;
+        new.title = "bar".into();
+
+        // Anonymous edits are only proposed
+        let storable = prepare_updated_place(
+            &mock_db,
+            id.clone(),
+            new.clone(),
+            None,
+            None,
+            PlaceEditMode::Proposal,
+        )
+        .unwrap();
+        assert!(matches!(
+            store_updated_place(&mock_db, storable).unwrap(),
+            StoredPlace::Proposal(_)
+        ));
+        assert_eq!(1, mock_db.proposals.borrow().len());
+        let (x, _) = mock_db.get_place(id.as_ref()).unwrap();
+        assert_eq!("foo", x.title);
+
+        // Scouts are allowed to edit places directly
+        let storable = prepare_updated_place(
+            &mock_db,
+            id.clone(),
+            new,
+            Some("scout@example.com"),
+            None,
+            PlaceEditMode::Proposal,
+        )
+        .unwrap();
+        assert!(matches!(
+            store_updated_place(&mock_db, storable).unwrap(),
+            StoredPlace::Revision(..)
+        ));
+        let (x, _) = mock_db.get_place(id.as_ref()).unwrap();
+        assert_eq!("bar", x.title);
+    }
 }
@@ -0,0 +1,62 @@
+use super::prepare_tag_list;
+use crate::core::{prelude::*, util::validate};
+
+#[derive(Debug, Clone)]
+pub struct NewWebhookSubscription {
+    pub url: String,
+    pub bbox: Option<MapBbox>,
+    pub tags: Vec<String>,
+}
+
+pub fn create_webhook_subscription<D: Db>(
+    db: &D,
+    org: &Organization,
+    new_subscription: NewWebhookSubscription,
+) -> Result<WebhookSubscription> {
+    let NewWebhookSubscription { url, bbox, tags } = new_subscription;
+    let url = validate::webhook_url(&url)?;
+    if let Some(ref bbox) = bbox {
+        validate::bbox(bbox)?;
+    }
+    let subscription = WebhookSubscription {
+        id: Id::new(),
+        org_id: org.id.clone(),
+        url,
+        secret: Nonce::new().to_string(),
+        bbox,
+        tags: prepare_tag_list(tags.iter().map(String::as_str)),
+        created_at: TimestampMs::now(),
+    };
+    db.create_webhook_subscription(&subscription)?;
+    Ok(subscription)
+}
+
+pub fn delete_webhook_subscription<D: Db>(db: &D, org: &Organization, id: &Id) -> Result<()> {
+    Ok(db.delete_webhook_subscription(&org.id, id)?)
+}
+
+pub fn load_webhook_deliveries<D: Db>(
+    db: &D,
+    org: &Organization,
+    subscription_id: &Id,
+    pagination: &Pagination,
+) -> Result<Vec<WebhookDelivery>> {
+    let subscription = db.get_webhook_subscription(subscription_id)?;
+    if subscription.org_id != org.id {
+        // Don't reveal the existence of webhooks of other organizations
+        return Err(RepoError::NotFound.into());
+    }
+    Ok(db.recent_webhook_deliveries(subscription_id, pagination)?)
+}
+
+pub fn webhook_subscriptions_in_scope<'a, D: Db>(
+    db: &D,
+    pos: Option<MapPoint>,
+    tags: impl IntoIterator<Item = &'a str> + Clone,
+) -> Result<Vec<WebhookSubscription>> {
+    Ok(db
+        .all_webhook_subscriptions()?
+        .into_iter()
+        .filter(|s| s.is_in_scope(pos, tags.clone()))
+        .collect())
+}
@@ -1,4 +1,13 @@
-use crate::core::error::ParameterError;
+use crate::core::{
+    entities::PageCursor,
+    error::ParameterError,
+    util::{
+        geo::{Distance, MapCircle, MapPoint},
+        validate,
+    },
+};
+use chrono::{NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
 use url::{ParseError, Url};
 
 /// Completes incomplete URLs before parsing
@@ -23,10 +32,73 @@ pub fn parse_url_param(url: &str) -> Result<Option<Url>, ParameterError> {
     parse_lazy_url(url).map_err(|_| ParameterError::Url)
 }
 
+/// Parses the center `lat,lng` and the radius in meters
+/// of a circular area.
+pub fn parse_near_params(near: &str, radius: &str) -> Result<MapCircle, ParameterError> {
+    let center = near
+        .parse::<MapPoint>()
+        .map_err(|_| ParameterError::InvalidPosition)?;
+    let radius = radius
+        .trim()
+        .parse::<f64>()
+        .map(Distance::from_meters)
+        .map_err(|_| ParameterError::InvalidRadius)?;
+    let circle = MapCircle::new(center, radius);
+    validate::circle(&circle)?;
+    Ok(circle)
+}
+
+/// Parses an opaque cursor that has been returned
+/// together with a page of results.
+pub fn parse_page_cursor(cursor: &str) -> Result<PageCursor, ParameterError> {
+    PageCursor::decode_from_str(cursor.trim()).map_err(|_| ParameterError::InvalidCursor)
+}
+
+/// Parses an IANA time zone name, e.g. `Europe/Berlin`.
+pub fn parse_time_zone(tz: &str) -> Result<Tz, ParameterError> {
+    tz.trim()
+        .parse()
+        .map_err(|_| ParameterError::InvalidTimeZone)
+}
+
+/// Converts a Unix timestamp in seconds into the local
+/// date and time of a time zone.
+pub fn local_date_time(seconds: i64, tz: Tz) -> Result<NaiveDateTime, ParameterError> {
+    let utc =
+        NaiveDateTime::from_timestamp_opt(seconds, 0).ok_or(ParameterError::DateTimeOutOfRange)?;
+    Ok(tz.from_utc_datetime(&utc).naive_local())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn parse_near_params_with_radius() {
+        let circle = parse_near_params("48.5,9.0", "2500").unwrap();
+        assert_eq!(MapPoint::from_lat_lng_deg(48.5, 9.0), circle.center());
+        assert_eq!(Distance::from_meters(2500.0), circle.radius());
+        assert!(parse_near_params("48.5", "2500").is_err());
+        assert!(parse_near_params("48.5,9.0", "").is_err());
+        assert!(parse_near_params("48.5,9.0", "-5").is_err());
+    }
+
+    #[test]
+    fn local_date_time_in_time_zone() {
+        let tz = parse_time_zone(" Europe/Berlin ").unwrap();
+        // Summer time
+        assert_eq!(
+            NaiveDateTime::parse_from_str("2020-10-18 07:46:40", "%Y-%m-%d %H:%M:%S").unwrap(),
+            local_date_time(1_603_000_000, tz).unwrap()
+        );
+        // Winter time
+        assert_eq!(
+            NaiveDateTime::parse_from_str("2020-11-22 00:06:40", "%Y-%m-%d %H:%M:%S").unwrap(),
+            local_date_time(1_606_000_000, tz).unwrap()
+        );
+        assert!(parse_time_zone("Europe/Nowhere").is_err());
+    }
+
     #[test]
     fn parse_url_params() {
         assert_eq!(None, parse_url_param("").unwrap());
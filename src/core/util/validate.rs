@@ -1,10 +1,12 @@
 use super::super::{
     entities::*,
     error::ParameterError,
-    util::geo::{MapBbox, MapPoint},
+    util::geo::{MapBbox, MapCircle, MapMultiPolygon, MapPoint},
 };
 use chrono::{prelude::*, Duration};
 use fast_chemail::is_valid_email;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use url::{Host, Url};
 
 pub trait Validate {
     fn validate(&self) -> Result<(), ParameterError>;
@@ -35,6 +37,69 @@ pub fn bbox(bbox: &MapBbox) -> Result<(), ParameterError> {
     Ok(())
 }
 
+pub fn circle(circle: &MapCircle) -> Result<(), ParameterError> {
+    if !circle.center().is_valid() {
+        return Err(ParameterError::InvalidPosition);
+    }
+    if !circle.is_valid() || circle.radius().to_meters() <= 0.0 {
+        return Err(ParameterError::InvalidRadius);
+    }
+    Ok(())
+}
+
+pub fn polygon(polygon: &MapMultiPolygon) -> Result<(), ParameterError> {
+    if !polygon.is_valid() || polygon.bounding_bbox().is_empty() {
+        return Err(ParameterError::InvalidPolygon);
+    }
+    Ok(())
+}
+
+/// Webhooks require HTTPS and must not target the server
+/// itself or hosts in private networks.
+pub fn webhook_url(url: &str) -> Result<Url, ParameterError> {
+    let url = url.parse::<Url>().map_err(|_| ParameterError::Url)?;
+    if url.scheme() != "https" {
+        return Err(ParameterError::Url);
+    }
+    let is_internal = match url.host() {
+        Some(Host::Domain(domain)) => is_local_domain(domain),
+        Some(Host::Ipv4(addr)) => is_internal_ipv4(addr),
+        Some(Host::Ipv6(addr)) => is_internal_ipv6(addr),
+        None => true,
+    };
+    if is_internal {
+        return Err(ParameterError::Url);
+    }
+    Ok(url)
+}
+
+fn is_local_domain(domain: &str) -> bool {
+    let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+    domain == "localhost" || domain.ends_with(".localhost")
+}
+
+// Loopback, private, link-local and shared (carrier-grade NAT) addresses
+fn is_internal_ipv4(addr: Ipv4Addr) -> bool {
+    let octets = addr.octets();
+    addr.is_unspecified()
+        || addr.is_loopback()
+        || addr.is_private()
+        || addr.is_link_local()
+        || addr.is_broadcast()
+        || (octets[0] == 100 && (octets[1] & 0xc0) == 64)
+}
+
+// Loopback, unique local (fc00::/7), link-local (fe80::/10)
+// and IPv4-mapped internal addresses
+fn is_internal_ipv6(addr: Ipv6Addr) -> bool {
+    let first_segment = addr.segments()[0];
+    addr.is_unspecified()
+        || addr.is_loopback()
+        || (first_segment & 0xfe00) == 0xfc00
+        || (first_segment & 0xffc0) == 0xfe80
+        || addr.to_ipv4().map_or(false, is_internal_ipv4)
+}
+
 impl Validate for Place {
     fn validate(&self) -> Result<(), ParameterError> {
         license(&self.license)?;
@@ -114,6 +179,18 @@ impl Validate for Event {
                 return Err(ParameterError::EndDateBeforeStart);
             }
         }
+        if let Some(ref recurrence) = self.recurrence {
+            for o in &recurrence.overrides {
+                if !self.is_recurrence_id(o.recurrence_id) {
+                    return Err(ParameterError::InvalidOccurrenceOverride);
+                }
+                if let Some(end) = o.end {
+                    if end < o.start.unwrap_or(o.recurrence_id) {
+                        return Err(ParameterError::EndDateBeforeStart);
+                    }
+                }
+            }
+        }
         Ok(())
     }
 }
@@ -191,15 +268,19 @@ mod tests {
             description: None,
             start: NaiveDateTime::from_timestamp(0, 0),
             end: None,
+            time_zone: Tz::UTC,
             location: None,
             contact: None,
             tags: vec![],
             homepage: None,
             created_by: None,
+            created_at: None,
             registration: None,
             archived: None,
             image_url: None,
             image_link_url: None,
+            recurrence: None,
+            recurrence_id: None,
         };
 
         let mut x = e.clone();
@@ -295,15 +376,19 @@ mod tests {
             description: None,
             start: now,
             end: None,
+            time_zone: Tz::UTC,
             location: None,
             contact: None,
             tags: vec![],
             homepage: None,
             created_by: None,
+            created_at: None,
             registration: None,
             archived: None,
             image_url: None,
             image_link_url: None,
+            recurrence: None,
+            recurrence_id: None,
         };
         assert!(e.validate().is_ok());
         assert!(Event {
@@ -340,19 +425,87 @@ mod tests {
             description: None,
             start: NaiveDateTime::from_timestamp(100, 0),
             end: Some(NaiveDateTime::from_timestamp(99, 0)),
+            time_zone: Tz::UTC,
             location: None,
             contact: None,
             tags: vec![],
             homepage: None,
             created_by: None,
+            created_at: None,
             registration: None,
             archived: None,
             image_url: None,
             image_link_url: None,
+            recurrence: None,
+            recurrence_id: None,
         };
         assert!(e.validate().is_err());
     }
 
+    #[test]
+    fn event_with_invalid_occurrence_override() {
+        let start = Utc::now().naive_utc().date().and_hms(18, 0, 0);
+        let o = OccurrenceOverride {
+            recurrence_id: start + Duration::weeks(2),
+            cancelled: true,
+            start: None,
+            end: None,
+            title: None,
+            description: None,
+        };
+        let e = Event {
+            id: "x".into(),
+            title: "foo".into(),
+            description: None,
+            start,
+            end: None,
+            time_zone: Tz::UTC,
+            location: None,
+            contact: None,
+            tags: vec![],
+            homepage: None,
+            created_by: None,
+            created_at: None,
+            registration: None,
+            archived: None,
+            image_url: None,
+            image_link_url: None,
+            recurrence: Some(EventRecurrence {
+                rule: "FREQ=WEEKLY".parse().unwrap(),
+                overrides: vec![o.clone()],
+            }),
+            recurrence_id: None,
+        };
+        assert!(e.validate().is_ok());
+        let not_an_occurrence = OccurrenceOverride {
+            recurrence_id: start + Duration::days(1),
+            ..o.clone()
+        };
+        assert!(Event {
+            recurrence: Some(EventRecurrence {
+                rule: "FREQ=WEEKLY".parse().unwrap(),
+                overrides: vec![not_an_occurrence],
+            }),
+            ..e.clone()
+        }
+        .validate()
+        .is_err());
+        let ends_before_start = OccurrenceOverride {
+            cancelled: false,
+            end: Some(o.recurrence_id - Duration::hours(1)),
+            ..o
+        };
+        assert!(Event {
+            recurrence: Some(EventRecurrence {
+                rule: "FREQ=WEEKLY".parse().unwrap(),
+                overrides: vec![ends_before_start],
+            }),
+            ..e
+        }
+        .validate()
+        .is_err());
+    }
+
     #[test]
     fn bbox_test() {
         let p1 = MapPoint::from_lat_lng_deg(48.123, 5.123);
@@ -365,4 +518,53 @@ mod tests {
         assert!(bbox(&empty_bbox).is_err());
         assert!(bbox(&invalid_bbox).is_err());
     }
+
+    #[test]
+    fn circle_test() {
+        let center = MapPoint::from_lat_lng_deg(48.123, 5.123);
+        let invalid_center = MapPoint::try_from_lat_lng_deg(48.123, 500.123).unwrap_or_default();
+        assert!(circle(&MapCircle::new(center, Distance::from_meters(100.0))).is_ok());
+        assert!(circle(&MapCircle::new(center, Distance::from_meters(0.0))).is_err());
+        assert!(circle(&MapCircle::new(center, Distance::from_meters(-1.0))).is_err());
+        assert!(circle(&MapCircle::new(center, Distance::infinite())).is_err());
+        assert!(circle(&MapCircle::new(
+            invalid_center,
+            Distance::from_meters(100.0)
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn polygon_test() {
+        let valid: MapMultiPolygon = "POLYGON ((9 48, 10 48, 10 49, 9 48))".parse().unwrap();
+        assert!(polygon(&valid).is_ok());
+        let flat: MapMultiPolygon = "POLYGON ((9 48, 10 48, 9.5 48, 9 48))".parse().unwrap();
+        assert!(polygon(&flat).is_err());
+        assert!(polygon(&MapMultiPolygon::default()).is_err());
+    }
+
+    #[test]
+    fn webhook_url_test() {
+        assert!(webhook_url("https://example.com/hook").is_ok());
+        assert!(webhook_url("https://93.184.216.34:8443/hook").is_ok());
+        assert!(webhook_url("https://[2606:2800:220:1::]/hook").is_ok());
+        assert!(webhook_url("http://example.com/hook").is_err());
+        assert!(webhook_url("http://127.0.0.1/hook").is_err());
+        assert!(webhook_url("https://localhost:8080/hook").is_err());
+        assert!(webhook_url("https://api.localhost/hook").is_err());
+        assert!(webhook_url("https://127.0.0.1:8080/hook").is_err());
+        assert!(webhook_url("https://0.0.0.0/hook").is_err());
+        assert!(webhook_url("https://10.0.0.1/hook").is_err());
+        assert!(webhook_url("https://172.16.0.1/hook").is_err());
+        assert!(webhook_url("https://192.168.1.1/hook").is_err());
+        assert!(webhook_url("https://169.254.169.254/latest/meta-data").is_err());
+        assert!(webhook_url("https://100.64.0.1/hook").is_err());
+        assert!(webhook_url("https://[::1]/hook").is_err());
+        assert!(webhook_url("https://[fd00::1]/hook").is_err());
+        assert!(webhook_url("https://[fe80::1]/hook").is_err());
+        assert!(webhook_url("https://[::ffff:127.0.0.1]/hook").is_err());
+        assert!(webhook_url("ftp://example.com/hook").is_err());
+        assert!(webhook_url("mailto:foo@example.com").is_err());
+        assert!(webhook_url("example.com/hook").is_err());
+    }
 }
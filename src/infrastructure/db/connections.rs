@@ -0,0 +1,619 @@
+//! Backend-agnostic database connections.
+//!
+//! The database backend is selected at runtime by the
+//! scheme of the database URL. PostgreSQL is only available
+//! if the crate has been compiled with the `postgres` feature.
+
+#[cfg(feature = "postgres")]
+use super::postgres;
+use super::sqlite;
+
+use crate::core::{db::*, entities::*, repositories::*, util::time::Timestamp};
+
+use anyhow::Result as Fallible;
+use diesel::Connection as _;
+use owning_ref::{RwLockReadGuardRef, RwLockWriteGuardRefMut};
+use std::{
+    ops::{Deref, DerefMut},
+    result,
+    sync::{Arc, RwLock},
+};
+
+type Result<T> = result::Result<T, crate::core::error::RepoError>;
+
+pub type SharedSqliteConnectionPool = Arc<RwLock<sqlite::ConnectionPool>>;
+
+pub enum DbConnection {
+    Sqlite(sqlite::PooledConnection),
+    #[cfg(feature = "postgres")]
+    Postgres(postgres::PooledConnection),
+}
+
+macro_rules! dispatch {
+    ($self:ident, $conn:ident => $expr:expr) => {
+        match $self {
+            DbConnection::Sqlite($conn) => $expr,
+            #[cfg(feature = "postgres")]
+            DbConnection::Postgres($conn) => $expr,
+        }
+    };
+}
+
+impl DbConnection {
+    /// Execute the given function within a database transaction.
+    pub fn transaction<T, E, F>(&self, f: F) -> result::Result<T, E>
+    where
+        F: FnOnce() -> result::Result<T, E>,
+        E: From<diesel::result::Error>,
+    {
+        dispatch!(self, conn => conn.transaction(f))
+    }
+
+    pub fn run_embedded_migrations(&self) -> Fallible<()> {
+        match self {
+            DbConnection::Sqlite(conn) => sqlite::run_embedded_migrations(conn),
+            #[cfg(feature = "postgres")]
+            DbConnection::Postgres(conn) => postgres::run_embedded_migrations(conn),
+        }
+    }
+
+    pub fn migrate_event_time_zones(&self, default_time_zone: Tz) -> Result<usize> {
+        self.transaction(|| match self {
+            DbConnection::Sqlite(conn) => sqlite::migrate_event_time_zones(conn, default_time_zone),
+            #[cfg(feature = "postgres")]
+            DbConnection::Postgres(conn) => {
+                postgres::migrate_event_time_zones(conn, default_time_zone)
+            }
+        })
+    }
+}
+
+pub struct DbReadOnly<'a> {
+    _locked_pool: Option<RwLockReadGuardRef<'a, sqlite::ConnectionPool>>,
+    conn: DbConnection,
+}
+
+impl<'a> DbReadOnly<'a> {
+    fn try_new_sqlite(pool: &'a SharedSqliteConnectionPool) -> Fallible<Self> {
+        let locked_pool = RwLockReadGuardRef::new(pool.read().unwrap_or_else(|err| {
+            error!("Failed to lock database connection pool for read-only access");
+            err.into_inner()
+        }));
+        let conn = locked_pool.get().map_err(|err| {
+            error!("Failed to obtain pooled database connection for read-only access");
+            err
+        })?;
+        Ok(Self {
+            _locked_pool: Some(locked_pool),
+            conn: DbConnection::Sqlite(conn),
+        })
+    }
+
+    #[cfg(feature = "postgres")]
+    fn try_new_postgres(pool: &postgres::ConnectionPool) -> Fallible<Self> {
+        let conn = pool.get().map_err(|err| {
+            error!("Failed to obtain pooled database connection for read-only access");
+            err
+        })?;
+        Ok(Self {
+            _locked_pool: None,
+            conn: DbConnection::Postgres(conn),
+        })
+    }
+}
+
+impl<'a> Deref for DbReadOnly<'a> {
+    type Target = DbConnection;
+
+    fn deref(&self) -> &Self::Target {
+        &self.conn
+    }
+}
+
+pub struct DbReadWrite<'a> {
+    _locked_pool: Option<RwLockWriteGuardRefMut<'a, sqlite::ConnectionPool>>,
+    conn: DbConnection,
+}
+
+impl<'a> DbReadWrite<'a> {
+    fn try_new_sqlite(pool: &'a SharedSqliteConnectionPool) -> Fallible<Self> {
+        let locked_pool = RwLockWriteGuardRefMut::new(pool.write().unwrap_or_else(|err| {
+            error!("Failed to lock database connection pool for read/write access");
+            err.into_inner()
+        }));
+        let conn = locked_pool.get().map_err(|err| {
+            error!("Failed to obtain pooled database connection for read/write access");
+            err
+        })?;
+        Ok(Self {
+            _locked_pool: Some(locked_pool),
+            conn: DbConnection::Sqlite(conn),
+        })
+    }
+
+    #[cfg(feature = "postgres")]
+    fn try_new_postgres(pool: &postgres::ConnectionPool) -> Fallible<Self> {
+        let conn = pool.get().map_err(|err| {
+            error!("Failed to obtain pooled database connection for read/write access");
+            err
+        })?;
+        Ok(Self {
+            _locked_pool: None,
+            conn: DbConnection::Postgres(conn),
+        })
+    }
+}
+
+impl<'a> Deref for DbReadWrite<'a> {
+    type Target = DbConnection;
+
+    fn deref(&self) -> &Self::Target {
+        &self.conn
+    }
+}
+
+impl<'a> DerefMut for DbReadWrite<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.conn
+    }
+}
+
+#[derive(Clone)]
+pub enum Connections {
+    // Only a single connection with write access will be
+    // handed out at a time from the pool. Multiple read
+    // connections can be accessed concurrently. This locking
+    // pattern around the connection pool prevents SQLITE_LOCKED
+    // ("database is locked") errors that are causing internal
+    // server errors and failed requests.
+    Sqlite(SharedSqliteConnectionPool),
+    // PostgreSQL handles concurrent access on its own and
+    // doesn't require any additional locking.
+    #[cfg(feature = "postgres")]
+    Postgres(postgres::ConnectionPool),
+}
+
+impl Connections {
+    pub fn init(url: &str, pool_size: u32) -> Fallible<Self> {
+        #[cfg(feature = "postgres")]
+        {
+            if postgres::is_postgres_url(url) {
+                let pool = postgres::create_connection_pool(url, pool_size)?;
+                return Ok(Connections::Postgres(pool));
+            }
+        }
+        if url.starts_with("postgres:") || url.starts_with("postgresql:") {
+            anyhow::bail!("Support for PostgreSQL requires the feature 'postgres'");
+        }
+        let pool = sqlite::create_connection_pool(url, pool_size)?;
+        Ok(Self::new(pool))
+    }
+
+    pub fn new(pool: sqlite::ConnectionPool) -> Self {
+        Connections::Sqlite(Arc::new(RwLock::new(pool)))
+    }
+
+    pub fn shared<'a>(&'a self) -> Fallible<DbReadOnly<'a>> {
+        match self {
+            Connections::Sqlite(pool) => DbReadOnly::try_new_sqlite(pool),
+            #[cfg(feature = "postgres")]
+            Connections::Postgres(pool) => DbReadOnly::try_new_postgres(pool),
+        }
+    }
+
+    pub fn exclusive<'a>(&'a self) -> Fallible<DbReadWrite<'a>> {
+        match self {
+            Connections::Sqlite(pool) => DbReadWrite::try_new_sqlite(pool),
+            #[cfg(feature = "postgres")]
+            Connections::Postgres(pool) => DbReadWrite::try_new_postgres(pool),
+        }
+    }
+
+    pub fn run_embedded_migrations(&self) -> Fallible<()> {
+        self.exclusive()?.run_embedded_migrations()
+    }
+
+    /// Convert the local times of all events without a time
+    /// zone into UTC, assuming the given time zone.
+    pub fn migrate_event_time_zones(&self, default_time_zone: Tz) -> Fallible<usize> {
+        Ok(self
+            .exclusive()?
+            .migrate_event_time_zones(default_time_zone)?)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////
+// Repositories
+///////////////////////////////////////////////////////////////////////
+
+impl PlaceRepo for DbConnection {
+    fn get_place(&self, id: &str) -> Result<(Place, ReviewStatus)> {
+        dispatch!(self, conn => conn.get_place(id))
+    }
+    fn get_places(&self, ids: &[&str]) -> Result<Vec<(Place, ReviewStatus)>> {
+        dispatch!(self, conn => conn.get_places(ids))
+    }
+    fn all_places(&self) -> Result<Vec<(Place, ReviewStatus)>> {
+        dispatch!(self, conn => conn.all_places())
+    }
+    fn count_places(&self) -> Result<usize> {
+        dispatch!(self, conn => conn.count_places())
+    }
+    fn load_places_chunk(&self, pagination: &Pagination) -> Result<Vec<(Place, ReviewStatus)>> {
+        dispatch!(self, conn => conn.load_places_chunk(pagination))
+    }
+    fn changed_place_ids_since(&self, since: TimestampMs) -> Result<Vec<Id>> {
+        dispatch!(self, conn => conn.changed_place_ids_since(since))
+    }
+    fn recently_changed_places(
+        &self,
+        params: &RecentlyChangedEntriesParams,
+        pagination: &Pagination,
+    ) -> Result<Vec<(Place, ReviewStatus, ActivityLog)>> {
+        dispatch!(self, conn => conn.recently_changed_places(params, pagination))
+    }
+    fn most_popular_place_revision_tags(
+        &self,
+        params: &MostPopularTagsParams,
+        pagination: &Pagination,
+    ) -> Result<Vec<TagFrequency>> {
+        dispatch!(self, conn => conn.most_popular_place_revision_tags(params, pagination))
+    }
+    fn review_places(
+        &self,
+        ids: &[&str],
+        status: ReviewStatus,
+        activity: &ActivityLog,
+    ) -> Result<usize> {
+        dispatch!(self, conn => conn.review_places(ids, status, activity))
+    }
+    fn create_or_update_place(&self, place: Place) -> Result<()> {
+        dispatch!(self, conn => conn.create_or_update_place(place))
+    }
+    fn create_place_revision_with_log(&self, place: Place, activity: &ActivityLog) -> Result<()> {
+        dispatch!(self, conn => conn.create_place_revision_with_log(place, activity))
+    }
+    fn get_place_history(&self, id: &str, revision: Option<Revision>) -> Result<PlaceHistory> {
+        dispatch!(self, conn => conn.get_place_history(id, revision))
+    }
+    fn load_place_revision(&self, id: &str, rev: Revision) -> Result<(Place, ReviewStatus)> {
+        dispatch!(self, conn => conn.load_place_revision(id, rev))
+    }
+}
+
+impl EventGateway for DbConnection {
+    fn create_event(&self, event: Event) -> Result<()> {
+        dispatch!(self, conn => conn.create_event(event))
+    }
+    fn update_event(&self, event: &Event) -> Result<()> {
+        dispatch!(self, conn => conn.update_event(event))
+    }
+    fn archive_events(&self, ids: &[&str], archived: Timestamp) -> Result<usize> {
+        dispatch!(self, conn => conn.archive_events(ids, archived))
+    }
+    fn get_event(&self, id: &str) -> Result<Event> {
+        dispatch!(self, conn => conn.get_event(id))
+    }
+    fn get_events_chronologically(&self, ids: &[&str]) -> Result<Vec<Event>> {
+        dispatch!(self, conn => conn.get_events_chronologically(ids))
+    }
+    fn all_events_chronologically(&self) -> Result<Vec<Event>> {
+        dispatch!(self, conn => conn.all_events_chronologically())
+    }
+    fn archived_events_chronologically(
+        &self,
+        start_min: Timestamp,
+        start_max: Option<Timestamp>,
+    ) -> Result<Vec<Event>> {
+        dispatch!(self, conn => conn.archived_events_chronologically(start_min, start_max))
+    }
+    fn count_events(&self) -> Result<usize> {
+        dispatch!(self, conn => conn.count_events())
+    }
+    fn load_events_chunk(&self, pagination: &Pagination) -> Result<Vec<Event>> {
+        dispatch!(self, conn => conn.load_events_chunk(pagination))
+    }
+    fn changed_event_ids_since(&self, since: TimestampMs) -> Result<Vec<Id>> {
+        dispatch!(self, conn => conn.changed_event_ids_since(since))
+    }
+    fn delete_event_with_matching_tags(&self, id: &str, tags: &[&str]) -> Result<Option<()>> {
+        dispatch!(self, conn => conn.delete_event_with_matching_tags(id, tags))
+    }
+    fn get_event_id_by_import_uid(&self, org_id: &Id, import_uid: &str) -> Result<Option<Id>> {
+        dispatch!(self, conn => conn.get_event_id_by_import_uid(org_id, import_uid))
+    }
+    fn create_event_import_uid(&self, org_id: &Id, import_uid: &str, event_id: &Id) -> Result<()> {
+        dispatch!(self, conn => conn.create_event_import_uid(org_id, import_uid, event_id))
+    }
+}
+
+impl UserGateway for DbConnection {
+    fn create_user(&self, user: &User) -> Result<()> {
+        dispatch!(self, conn => conn.create_user(user))
+    }
+    fn update_user(&self, user: &User) -> Result<()> {
+        dispatch!(self, conn => conn.update_user(user))
+    }
+    fn delete_user_by_email(&self, email: &str) -> Result<()> {
+        dispatch!(self, conn => conn.delete_user_by_email(email))
+    }
+    fn all_users(&self) -> Result<Vec<User>> {
+        dispatch!(self, conn => conn.all_users())
+    }
+    fn count_users(&self) -> Result<usize> {
+        dispatch!(self, conn => conn.count_users())
+    }
+    fn get_user_by_email(&self, email: &str) -> Result<User> {
+        dispatch!(self, conn => conn.get_user_by_email(email))
+    }
+    fn try_get_user_by_email(&self, email: &str) -> Result<Option<User>> {
+        dispatch!(self, conn => conn.try_get_user_by_email(email))
+    }
+}
+
+impl OrganizationRepo for DbConnection {
+    fn create_org(&mut self, org: Organization) -> Result<()> {
+        dispatch!(self, conn => conn.create_org(org))
+    }
+    fn get_org_by_api_token(&self, token: &str) -> Result<Organization> {
+        dispatch!(self, conn => conn.get_org_by_api_token(token))
+    }
+    fn map_tag_to_clearance_org_id(&self, tag: &str) -> Result<Option<Id>> {
+        dispatch!(self, conn => conn.map_tag_to_clearance_org_id(tag))
+    }
+    fn get_moderated_tags_by_org(
+        &self,
+        excluded_org_id: Option<&Id>,
+    ) -> Result<Vec<(Id, ModeratedTag)>> {
+        dispatch!(self, conn => conn.get_moderated_tags_by_org(excluded_org_id))
+    }
+}
+
+impl PlaceClearanceRepo for DbConnection {
+    fn add_pending_clearance_for_places(
+        &self,
+        org_ids: &[Id],
+        pending_clearance: &PendingClearanceForPlace,
+    ) -> Result<usize> {
+        dispatch!(self, conn => conn.add_pending_clearance_for_places(org_ids, pending_clearance))
+    }
+    fn count_pending_clearances_for_places(&self, org_id: &Id) -> Result<u64> {
+        dispatch!(self, conn => conn.count_pending_clearances_for_places(org_id))
+    }
+    fn list_pending_clearances_for_places(
+        &self,
+        org_id: &Id,
+        pagination: &Pagination,
+    ) -> Result<Vec<PendingClearanceForPlace>> {
+        dispatch!(self, conn => conn.list_pending_clearances_for_places(org_id, pagination))
+    }
+    fn load_pending_clearances_for_places(
+        &self,
+        org_id: &Id,
+        place_ids: &[&str],
+    ) -> Result<Vec<PendingClearanceForPlace>> {
+        dispatch!(self, conn => conn.load_pending_clearances_for_places(org_id, place_ids))
+    }
+    fn update_pending_clearances_for_places(
+        &self,
+        org_id: &Id,
+        clearances: &[ClearanceForPlace],
+    ) -> Result<usize> {
+        dispatch!(self, conn => conn.update_pending_clearances_for_places(org_id, clearances))
+    }
+    fn cleanup_pending_clearances_for_places(&self, org_id: &Id) -> Result<u64> {
+        dispatch!(self, conn => conn.cleanup_pending_clearances_for_places(org_id))
+    }
+}
+
+impl PlaceOwnershipRepo for DbConnection {
+    fn load_place_ownership(&self, place_id: &Id) -> Result<PlaceOwnership> {
+        dispatch!(self, conn => conn.load_place_ownership(place_id))
+    }
+    fn replace_place_ownership(&self, place_id: &Id, ownership: &PlaceOwnership) -> Result<()> {
+        dispatch!(self, conn => conn.replace_place_ownership(place_id, ownership))
+    }
+}
+
+impl PlaceProposalRepo for DbConnection {
+    fn add_place_proposal(&self, proposal: &PlaceProposal) -> Result<()> {
+        dispatch!(self, conn => conn.add_place_proposal(proposal))
+    }
+    fn get_place_proposal(&self, id: &Id) -> Result<PlaceProposal> {
+        dispatch!(self, conn => conn.get_place_proposal(id))
+    }
+    fn review_place_proposal(
+        &self,
+        id: &Id,
+        status: ProposalStatus,
+        activity: &ActivityLog,
+    ) -> Result<()> {
+        dispatch!(self, conn => conn.review_place_proposal(id, status, activity))
+    }
+    fn list_place_proposals_of_place(&self, place_id: &str) -> Result<Vec<PlaceProposal>> {
+        dispatch!(self, conn => conn.list_place_proposals_of_place(place_id))
+    }
+    fn count_pending_place_proposals(&self) -> Result<u64> {
+        dispatch!(self, conn => conn.count_pending_place_proposals())
+    }
+    fn list_pending_place_proposals(&self, pagination: &Pagination) -> Result<Vec<PlaceProposal>> {
+        dispatch!(self, conn => conn.list_pending_place_proposals(pagination))
+    }
+    fn count_place_proposals_by_owner(&self, owner: &PlaceOwner) -> Result<u64> {
+        dispatch!(self, conn => conn.count_place_proposals_by_owner(owner))
+    }
+    fn list_place_proposals_by_owner(
+        &self,
+        owner: &PlaceOwner,
+        pagination: &Pagination,
+    ) -> Result<Vec<PlaceProposal>> {
+        dispatch!(self, conn => conn.list_place_proposals_by_owner(owner, pagination))
+    }
+}
+
+impl WebhookRepo for DbConnection {
+    fn create_webhook_subscription(&self, subscription: &WebhookSubscription) -> Result<()> {
+        dispatch!(self, conn => conn.create_webhook_subscription(subscription))
+    }
+    fn delete_webhook_subscription(&self, org_id: &Id, id: &Id) -> Result<()> {
+        dispatch!(self, conn => conn.delete_webhook_subscription(org_id, id))
+    }
+    fn get_webhook_subscription(&self, id: &Id) -> Result<WebhookSubscription> {
+        dispatch!(self, conn => conn.get_webhook_subscription(id))
+    }
+    fn all_webhook_subscriptions(&self) -> Result<Vec<WebhookSubscription>> {
+        dispatch!(self, conn => conn.all_webhook_subscriptions())
+    }
+    fn all_webhook_subscriptions_by_org(&self, org_id: &Id) -> Result<Vec<WebhookSubscription>> {
+        dispatch!(self, conn => conn.all_webhook_subscriptions_by_org(org_id))
+    }
+    fn create_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<()> {
+        dispatch!(self, conn => conn.create_webhook_delivery(delivery))
+    }
+    fn update_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<()> {
+        dispatch!(self, conn => conn.update_webhook_delivery(delivery))
+    }
+    fn load_webhook_delivery(&self, id: &Id) -> Result<WebhookDelivery> {
+        dispatch!(self, conn => conn.load_webhook_delivery(id))
+    }
+    fn recent_webhook_deliveries(
+        &self,
+        subscription_id: &Id,
+        pagination: &Pagination,
+    ) -> Result<Vec<WebhookDelivery>> {
+        dispatch!(self, conn => conn.recent_webhook_deliveries(subscription_id, pagination))
+    }
+}
+
+impl JobRepo for DbConnection {
+    fn enqueue_job(&self, job: &Job) -> Result<()> {
+        dispatch!(self, conn => conn.enqueue_job(job))
+    }
+    fn acquire_next_job(&self, now: TimestampMs) -> Result<Option<Job>> {
+        dispatch!(self, conn => conn.acquire_next_job(now))
+    }
+    fn update_job(&self, job: &Job) -> Result<()> {
+        dispatch!(self, conn => conn.update_job(job))
+    }
+    fn delete_job(&self, id: &Id) -> Result<()> {
+        dispatch!(self, conn => conn.delete_job(id))
+    }
+    fn load_jobs(&self, status: JobStatus, pagination: &Pagination) -> Result<Vec<Job>> {
+        dispatch!(self, conn => conn.load_jobs(status, pagination))
+    }
+    fn count_jobs(&self, status: JobStatus) -> Result<usize> {
+        dispatch!(self, conn => conn.count_jobs(status))
+    }
+    fn count_jobs_by_kind(&self, kind: &str, status: JobStatus) -> Result<usize> {
+        dispatch!(self, conn => conn.count_jobs_by_kind(kind, status))
+    }
+    fn retry_failed_jobs(&self, run_at: TimestampMs) -> Result<usize> {
+        dispatch!(self, conn => conn.retry_failed_jobs(run_at))
+    }
+    fn release_running_jobs(&self) -> Result<usize> {
+        dispatch!(self, conn => conn.release_running_jobs())
+    }
+}
+
+impl CommentRepository for DbConnection {
+    fn create_comment(&self, comment: Comment) -> Result<()> {
+        dispatch!(self, conn => conn.create_comment(comment))
+    }
+    fn load_comment(&self, id: &str) -> Result<Comment> {
+        dispatch!(self, conn => conn.load_comment(id))
+    }
+    fn load_comments(&self, ids: &[&str]) -> Result<Vec<Comment>> {
+        dispatch!(self, conn => conn.load_comments(ids))
+    }
+    fn load_comments_of_rating(&self, rating_id: &str) -> Result<Vec<Comment>> {
+        dispatch!(self, conn => conn.load_comments_of_rating(rating_id))
+    }
+    fn archive_comments(&self, ids: &[&str], activity: &Activity) -> Result<usize> {
+        dispatch!(self, conn => conn.archive_comments(ids, activity))
+    }
+    fn archive_comments_of_ratings(
+        &self,
+        rating_ids: &[&str],
+        activity: &Activity,
+    ) -> Result<usize> {
+        dispatch!(self, conn => conn.archive_comments_of_ratings(rating_ids, activity))
+    }
+    fn archive_comments_of_places(&self, place_ids: &[&str], activity: &Activity) -> Result<usize> {
+        dispatch!(self, conn => conn.archive_comments_of_places(place_ids, activity))
+    }
+}
+
+impl RatingRepository for DbConnection {
+    fn create_rating(&self, rating: Rating) -> Result<()> {
+        dispatch!(self, conn => conn.create_rating(rating))
+    }
+    fn load_rating(&self, id: &str) -> Result<Rating> {
+        dispatch!(self, conn => conn.load_rating(id))
+    }
+    fn load_ratings(&self, ids: &[&str]) -> Result<Vec<Rating>> {
+        dispatch!(self, conn => conn.load_ratings(ids))
+    }
+    fn load_ratings_of_place(&self, place_id: &str) -> Result<Vec<Rating>> {
+        dispatch!(self, conn => conn.load_ratings_of_place(place_id))
+    }
+    fn archive_ratings(&self, ids: &[&str], activity: &Activity) -> Result<usize> {
+        dispatch!(self, conn => conn.archive_ratings(ids, activity))
+    }
+    fn archive_ratings_of_places(&self, place_ids: &[&str], activity: &Activity) -> Result<usize> {
+        dispatch!(self, conn => conn.archive_ratings_of_places(place_ids, activity))
+    }
+    fn load_place_ids_of_ratings(&self, ids: &[&str]) -> Result<Vec<String>> {
+        dispatch!(self, conn => conn.load_place_ids_of_ratings(ids))
+    }
+}
+
+impl UserTokenRepo for DbConnection {
+    fn replace_user_token(&self, user_token: UserToken) -> Result<EmailNonce> {
+        dispatch!(self, conn => conn.replace_user_token(user_token))
+    }
+    fn consume_user_token(&self, email_nonce: &EmailNonce) -> Result<UserToken> {
+        dispatch!(self, conn => conn.consume_user_token(email_nonce))
+    }
+    fn delete_expired_user_tokens(&self, expired_before: Timestamp) -> Result<usize> {
+        dispatch!(self, conn => conn.delete_expired_user_tokens(expired_before))
+    }
+    fn get_user_token_by_email(&self, email: &str) -> Result<UserToken> {
+        dispatch!(self, conn => conn.get_user_token_by_email(email))
+    }
+}
+
+impl Db for DbConnection {
+    fn create_tag_if_it_does_not_exist(&self, tag: &Tag) -> Result<()> {
+        dispatch!(self, conn => conn.create_tag_if_it_does_not_exist(tag))
+    }
+    fn all_categories(&self) -> Result<Vec<Category>> {
+        dispatch!(self, conn => conn.all_categories())
+    }
+    fn all_tags(&self) -> Result<Vec<Tag>> {
+        dispatch!(self, conn => conn.all_tags())
+    }
+    fn count_tags(&self) -> Result<usize> {
+        dispatch!(self, conn => conn.count_tags())
+    }
+    fn create_bbox_subscription(&self, subscription: &BboxSubscription) -> Result<()> {
+        dispatch!(self, conn => conn.create_bbox_subscription(subscription))
+    }
+    fn update_bbox_subscription(&self, subscription: &BboxSubscription) -> Result<()> {
+        dispatch!(self, conn => conn.update_bbox_subscription(subscription))
+    }
+    fn get_bbox_subscription(&self, id: &Id) -> Result<BboxSubscription> {
+        dispatch!(self, conn => conn.get_bbox_subscription(id))
+    }
+    fn all_bbox_subscriptions(&self) -> Result<Vec<BboxSubscription>> {
+        dispatch!(self, conn => conn.all_bbox_subscriptions())
+    }
+    fn all_bbox_subscriptions_by_email(&self, user_email: &str) -> Result<Vec<BboxSubscription>> {
+        dispatch!(self, conn => conn.all_bbox_subscriptions_by_email(user_email))
+    }
+    fn delete_bbox_subscription(&self, id: &Id) -> Result<()> {
+        dispatch!(self, conn => conn.delete_bbox_subscription(id))
+    }
+    fn delete_bbox_subscriptions_by_email(&self, user_email: &str) -> Result<()> {
+        dispatch!(self, conn => conn.delete_bbox_subscriptions_by_email(user_email))
+    }
+}
@@ -1,2 +1,44 @@
+#[cfg(feature = "postgres")]
+pub(crate) mod postgres;
 pub(crate) mod sqlite;
 pub(crate) mod tantivy;
+
+mod connections;
+
+pub use self::connections::*;
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// Name of the environment variable with the URL of an
+    /// (empty) PostgreSQL database for running the tests.
+    #[cfg(feature = "postgres")]
+    pub const POSTGRES_URL_ENV: &str = "OFDB_TEST_POSTGRES_URL";
+
+    /// Create connections to a new and fully migrated database.
+    ///
+    /// Tests run against an in-memory SQLite database unless
+    /// the `postgres` feature is enabled and a PostgreSQL
+    /// database URL is provided by the environment.
+    pub fn connections() -> Connections {
+        let connections = new_connections();
+        connections.run_embedded_migrations().unwrap();
+        connections
+    }
+
+    #[cfg(feature = "postgres")]
+    fn new_connections() -> Connections {
+        match std::env::var(POSTGRES_URL_ENV) {
+            Ok(url) => {
+                Connections::Postgres(postgres::create_isolated_connection_pool(&url, 1).unwrap())
+            }
+            Err(_) => Connections::init(":memory:", 1).unwrap(),
+        }
+    }
+
+    #[cfg(not(feature = "postgres"))]
+    fn new_connections() -> Connections {
+        Connections::init(":memory:", 1).unwrap()
+    }
+}
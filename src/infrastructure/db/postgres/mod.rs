@@ -0,0 +1,84 @@
+// The repository implementation, the Diesel schema and all
+// models are shared with the SQLite backend. Backend-specific
+// parts are resolved through the `Connection` type alias and
+// the `insert_or_ignore_into!` macro defined in this module.
+
+// Insert a record or a batch of records and silently skip all
+// records that would violate a uniqueness constraint.
+// Needs to be defined before the modules that are using it.
+macro_rules! insert_or_ignore_into {
+    ($table:expr, $records:expr) => {
+        diesel::insert_into($table)
+            .values($records)
+            .on_conflict_do_nothing()
+    };
+}
+
+#[path = "../sqlite/connection.rs"]
+mod connection;
+#[path = "../sqlite/models.rs"]
+mod models;
+#[path = "../sqlite/schema.rs"]
+mod schema;
+#[path = "../sqlite/util.rs"]
+mod util;
+
+pub use connection::migrate_event_time_zones;
+
+use anyhow::Result as Fallible;
+use diesel::{pg::PgConnection, r2d2};
+
+pub type Connection = PgConnection;
+
+pub type ConnectionManager = r2d2::ConnectionManager<Connection>;
+pub type ConnectionPool = r2d2::Pool<ConnectionManager>;
+pub type PooledConnection = r2d2::PooledConnection<ConnectionManager>;
+
+embed_migrations!("migrations-postgres");
+
+pub const URL_SCHEMES: [&str; 2] = ["postgres://", "postgresql://"];
+
+pub fn is_postgres_url(url: &str) -> bool {
+    URL_SCHEMES.iter().any(|scheme| url.starts_with(scheme))
+}
+
+pub fn create_connection_pool(url: &str, pool_size: u32) -> Fallible<ConnectionPool> {
+    let manager = ConnectionManager::new(url);
+    let pool = ConnectionPool::builder()
+        .max_size(pool_size)
+        .build(manager)?;
+    Ok(pool)
+}
+
+pub fn run_embedded_migrations(conn: &Connection) -> Fallible<()> {
+    Ok(embedded_migrations::run(conn)?)
+}
+
+// Each test runs in a separate, newly created schema of the
+// test database to avoid interference between concurrently
+// running tests.
+#[cfg(test)]
+pub fn create_isolated_connection_pool(url: &str, pool_size: u32) -> Fallible<ConnectionPool> {
+    use diesel::Connection as _;
+    let schema = format!("ofdb_test_{}", uuid::Uuid::new_v4().to_simple());
+    Connection::establish(url)?.batch_execute(&format!("CREATE SCHEMA {}", schema))?;
+    let manager = ConnectionManager::new(url);
+    let pool = ConnectionPool::builder()
+        .max_size(pool_size)
+        .connection_customizer(Box::new(SearchPath(schema)))
+        .build(manager)?;
+    Ok(pool)
+}
+
+#[cfg(test)]
+#[derive(Debug)]
+struct SearchPath(String);
+
+#[cfg(test)]
+impl r2d2::CustomizeConnection<Connection, r2d2::Error> for SearchPath {
+    fn on_acquire(&self, conn: &mut Connection) -> Result<(), r2d2::Error> {
+        use diesel::Connection as _;
+        conn.batch_execute(&format!("SET search_path TO {}", self.0))
+            .map_err(r2d2::Error::QueryError)
+    }
+}
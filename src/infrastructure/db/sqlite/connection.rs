@@ -1,4 +1,4 @@
-use super::{util::load_url, *};
+use super::{util::load_url, Connection, *};
 use crate::core::prelude::*;
 use anyhow::anyhow;
 use chrono::prelude::*;
@@ -17,10 +17,7 @@ fn load_review_status(status: ReviewStatusPrimitive) -> Result<ReviewStatus> {
         .ok_or_else(|| RepoError::Other(anyhow!("Invalid review status: {}", status)))
 }
 
-fn load_place_revision_tags(
-    conn: &SqliteConnection,
-    place_revision_rowid: i64,
-) -> Result<Vec<String>> {
+fn load_place_revision_tags(conn: &Connection, place_revision_rowid: i64) -> Result<Vec<String>> {
     use schema::place_revision_tag::dsl;
     Ok(schema::place_revision_tag::table
         .filter(dsl::parent_rowid.eq(&place_revision_rowid))
@@ -36,7 +33,7 @@ fn load_place_revision_tags(
 }
 
 fn load_place_revision_custom_links(
-    conn: &SqliteConnection,
+    conn: &Connection,
     place_revision_rowid: i64,
 ) -> Result<Vec<CustomLink>> {
     use schema::place_revision_custom_link::dsl;
@@ -69,7 +66,7 @@ fn load_place_revision_custom_links(
 }
 
 fn load_place(
-    conn: &SqliteConnection,
+    conn: &Connection,
     place: models::JoinedPlaceRevision,
 ) -> Result<(Place, ReviewStatus)> {
     let models::JoinedPlaceRevision {
@@ -158,7 +155,7 @@ fn load_place(
 }
 
 fn load_place_with_status_review(
-    conn: &SqliteConnection,
+    conn: &Connection,
     place_with_status_review: models::JoinedPlaceRevisionWithStatusReview,
 ) -> Result<(Place, ReviewStatus, ActivityLog)> {
     let models::JoinedPlaceRevisionWithStatusReview {
@@ -286,7 +283,7 @@ struct TagCountRow {
     count: i64,
 }
 
-fn resolve_organization_rowid(conn: &SqliteConnection, id: &Id) -> Result<i64> {
+fn resolve_organization_rowid(conn: &Connection, id: &Id) -> Result<i64> {
     use schema::organization::dsl;
     Ok(schema::organization::table
         .select(dsl::rowid)
@@ -298,7 +295,31 @@ fn resolve_organization_rowid(conn: &SqliteConnection, id: &Id) -> Result<i64> {
         })?)
 }
 
-fn resolve_place_rowid(conn: &SqliteConnection, id: &Id) -> Result<i64> {
+fn resolve_webhook_rowid(conn: &Connection, id: &Id) -> Result<i64> {
+    use schema::organization_webhook::dsl;
+    Ok(schema::organization_webhook::table
+        .select(dsl::rowid)
+        .filter(dsl::id.eq(id.as_str()))
+        .first::<i64>(conn)
+        .map_err(|e| {
+            log::warn!("Failed to resolve webhook id '{}': {}", id, e);
+            e
+        })?)
+}
+
+fn resolve_bbox_subscription_rowid(conn: &Connection, id: &Id) -> Result<i64> {
+    use schema::bbox_subscriptions::dsl;
+    Ok(schema::bbox_subscriptions::table
+        .select(dsl::id)
+        .filter(dsl::uid.eq(id.as_str()))
+        .first::<i64>(conn)
+        .map_err(|e| {
+            log::warn!("Failed to resolve bbox subscription id '{}': {}", id, e);
+            e
+        })?)
+}
+
+fn resolve_place_rowid(conn: &Connection, id: &Id) -> Result<i64> {
     use schema::place::dsl;
     Ok(schema::place::table
         .select(dsl::rowid)
@@ -311,7 +332,7 @@ fn resolve_place_rowid(conn: &SqliteConnection, id: &Id) -> Result<i64> {
 }
 
 fn resolve_place_rowid_verify_revision(
-    conn: &SqliteConnection,
+    conn: &Connection,
     id: &Id,
     revision: Revision,
 ) -> Result<i64> {
@@ -336,7 +357,7 @@ fn resolve_place_rowid_verify_revision(
 }
 
 fn resolve_place_rowid_with_current_revision(
-    conn: &SqliteConnection,
+    conn: &Connection,
     id: &Id,
 ) -> Result<(i64, Revision)> {
     use schema::place::dsl;
@@ -351,7 +372,7 @@ fn resolve_place_rowid_with_current_revision(
         .map(|(id, rev)| (id, Revision::from(rev as u64)))?)
 }
 
-fn resolve_rating_rowid(conn: &SqliteConnection, id: &str) -> Result<i64> {
+fn resolve_rating_rowid(conn: &Connection, id: &str) -> Result<i64> {
     use schema::place_rating::dsl;
     Ok(schema::place_rating::table
         .select(dsl::rowid)
@@ -364,7 +385,7 @@ fn resolve_rating_rowid(conn: &SqliteConnection, id: &str) -> Result<i64> {
 }
 
 fn into_new_place_revision(
-    conn: &SqliteConnection,
+    conn: &Connection,
     place: Place,
 ) -> Result<(Id, models::NewPlaceRevision, Vec<String>, Vec<CustomLink>)> {
     let Place {
@@ -463,76 +484,94 @@ fn into_new_place_revision(
     Ok((place_id, new_place, tags, custom_links))
 }
 
-impl PlaceRepo for SqliteConnection {
-    fn create_or_update_place(&self, place: Place) -> Result<()> {
-        let (_place_id, new_place, tags, custom_links) = into_new_place_revision(self, place)?;
-        diesel::insert_into(schema::place_revision::table)
-            .values(&new_place)
-            .execute(self)?;
+fn insert_place_revision(
+    conn: &Connection,
+    place: Place,
+    context: Option<&str>,
+    comment: Option<&str>,
+) -> Result<()> {
+    let (_place_id, new_place, tags, custom_links) = into_new_place_revision(conn, place)?;
+    diesel::insert_into(schema::place_revision::table)
+        .values(&new_place)
+        .execute(conn)?;
 
-        use schema::place_revision::dsl;
-        let parent_rowid = schema::place_revision::table
-            .select(dsl::rowid)
-            .filter(dsl::parent_rowid.eq(new_place.parent_rowid))
-            .filter(dsl::rev.eq(new_place.rev))
-            .first::<i64>(self)
-            .map_err(|e| {
-                log::warn!(
-                    "Newly inserted place {} revision {} not found: {}",
-                    new_place.parent_rowid,
-                    new_place.rev,
-                    e
-                );
+    use schema::place_revision::dsl;
+    let parent_rowid = schema::place_revision::table
+        .select(dsl::rowid)
+        .filter(dsl::parent_rowid.eq(new_place.parent_rowid))
+        .filter(dsl::rev.eq(new_place.rev))
+        .first::<i64>(conn)
+        .map_err(|e| {
+            log::warn!(
+                "Newly inserted place {} revision {} not found: {}",
+                new_place.parent_rowid,
+                new_place.rev,
                 e
-            })?;
+            );
+            e
+        })?;
+
+    // Insert into place_revision_review
+    let new_review = models::NewPlaceReviewedRevision {
+        parent_rowid,
+        rev: u64::from(Revision::initial()) as i64,
+        created_at: new_place.created_at,
+        created_by: new_place.created_by,
+        status: new_place.current_status,
+        context,
+        comment,
+    };
+    diesel::insert_into(schema::place_revision_review::table)
+        .values(new_review)
+        .execute(conn)?;
 
-        // Insert into place_revision_review
-        let new_review = models::NewPlaceReviewedRevision {
+    // Insert into place_revision_tag
+    let insertable_tags: Vec<_> = tags
+        .iter()
+        .map(|tag| models::NewPlaceRevisionTag {
             parent_rowid,
-            rev: u64::from(Revision::initial()) as i64,
-            created_at: new_place.created_at,
-            created_by: new_place.created_by,
-            status: new_place.current_status,
-            context: None,
-            comment: Some("created"),
-        };
-        diesel::insert_into(schema::place_revision_review::table)
-            .values(new_review)
-            .execute(self)?;
+            tag: tag.as_str(),
+        })
+        .collect();
+    diesel::insert_into(schema::place_revision_tag::table)
+        .values(&insertable_tags)
+        .execute(conn)?;
 
-        // Insert into place_revision_tag
-        let insertable_tags: Vec<_> = tags
-            .iter()
-            .map(|tag| models::NewPlaceRevisionTag {
+    // Insert into place_revision_custom_link
+    let insertable_custom_links: Vec<_> = custom_links
+        .iter()
+        .map(
+            |CustomLink {
+                 url,
+                 title,
+                 description,
+             }| models::NewPlaceRevisionCustomLink {
                 parent_rowid,
-                tag: tag.as_str(),
-            })
-            .collect();
-        diesel::insert_into(schema::place_revision_tag::table)
-            .values(&insertable_tags)
-            .execute(self)?;
+                url: url.as_str(),
+                title: title.as_ref().map(String::as_str),
+                description: description.as_ref().map(String::as_str),
+            },
+        )
+        .collect();
+    diesel::insert_into(schema::place_revision_custom_link::table)
+        .values(&insertable_custom_links)
+        .execute(conn)?;
 
-        // Insert into place_revision_custom_link
-        let insertable_custom_links: Vec<_> = custom_links
-            .iter()
-            .map(
-                |CustomLink {
-                     url,
-                     title,
-                     description,
-                 }| models::NewPlaceRevisionCustomLink {
-                    parent_rowid,
-                    url: url.as_str(),
-                    title: title.as_ref().map(String::as_str),
-                    description: description.as_ref().map(String::as_str),
-                },
-            )
-            .collect();
-        diesel::insert_into(schema::place_revision_custom_link::table)
-            .values(&insertable_custom_links)
-            .execute(self)?;
+    Ok(())
+}
 
-        Ok(())
+impl PlaceRepo for Connection {
+    fn create_or_update_place(&self, place: Place) -> Result<()> {
+        insert_place_revision(self, place, None, Some("created"))
+    }
+
+    fn create_place_revision_with_log(&self, place: Place, activity: &ActivityLog) -> Result<()> {
+        let ActivityLog {
+            activity: _,
+            context,
+            comment,
+        } = activity;
+        insert_place_revision(self, place, context.as_deref(), comment.as_deref())
     }
 
     fn review_places(
@@ -763,12 +802,12 @@ impl PlaceRepo for SqliteConnection {
             .to_string();
         if params.min_count.is_some() || params.max_count.is_some() {
             if let Some(min_count) = params.min_count {
-                sql.push_str(&format!(" HAVING count>={}", min_count));
+                sql.push_str(&format!(" HAVING COUNT(*)>={}", min_count));
                 if let Some(max_count) = params.max_count {
-                    sql.push_str(&format!(" AND count<={}", max_count));
+                    sql.push_str(&format!(" AND COUNT(*)<={}", max_count));
                 }
             } else if let Some(max_count) = params.max_count {
-                sql.push_str(&format!(" HAVING count<={}", max_count));
+                sql.push_str(&format!(" HAVING COUNT(*)<={}", max_count));
             }
         }
         sql.push_str(" ORDER BY count DESC, tag");
@@ -802,6 +841,66 @@ impl PlaceRepo for SqliteConnection {
             .first::<i64>(self)? as usize)
     }
 
+    fn load_places_chunk(&self, pagination: &Pagination) -> Result<Vec<(Place, ReviewStatus)>> {
+        use schema::place::dsl;
+        let mut query = schema::place::table
+            .select(dsl::id)
+            .order_by(dsl::rowid)
+            .into_boxed();
+        let offset = pagination.offset.unwrap_or(0);
+        if offset > 0 {
+            query = query.offset(offset as i64);
+        }
+        if let Some(limit) = pagination.limit {
+            query = query.limit(limit as i64);
+        }
+        let ids = query.load::<String>(self)?;
+        if ids.is_empty() {
+            // Loading places without ids would load all places
+            return Ok(vec![]);
+        }
+        let ids: Vec<_> = ids.iter().map(String::as_str).collect();
+        self.get_places(&ids)
+    }
+
+    fn changed_place_ids_since(&self, since: TimestampMs) -> Result<Vec<Id>> {
+        use schema::place::dsl;
+        use schema::place_rating::dsl as rating_dsl;
+        use schema::place_revision::dsl as rev_dsl;
+        use schema::place_revision_review::dsl as review_dsl;
+        let since = since.into_inner();
+        let mut ids = schema::place_revision::table
+            .inner_join(schema::place::table)
+            .select(dsl::id)
+            .filter(rev_dsl::created_at.ge(since))
+            .distinct()
+            .load::<String>(self)?;
+        ids.extend(
+            schema::place_revision_review::table
+                .inner_join(schema::place_revision::table.inner_join(schema::place::table))
+                .select(dsl::id)
+                .filter(review_dsl::created_at.ge(since))
+                .distinct()
+                .load::<String>(self)?,
+        );
+        ids.extend(
+            schema::place_rating::table
+                .inner_join(schema::place::table)
+                .select(dsl::id)
+                .filter(
+                    // Ratings are created with a timestamp in seconds
+                    rating_dsl::created_at
+                        .ge(since / 1000)
+                        .or(rating_dsl::archived_at.ge(since)),
+                )
+                .distinct()
+                .load::<String>(self)?,
+        );
+        ids.sort_unstable();
+        ids.dedup();
+        Ok(ids.into_iter().map(Id::from).collect())
+    }
+
     fn get_place_history(&self, id: &str, revision: Option<Revision>) -> Result<PlaceHistory> {
         use schema::place::dsl;
         use schema::place_revision::dsl as rev_dsl;
@@ -939,7 +1038,7 @@ impl PlaceRepo for SqliteConnection {
 }
 
 fn into_new_event_with_tags(
-    conn: &SqliteConnection,
+    conn: &Connection,
     event: Event,
 ) -> Result<(models::NewEvent, Vec<String>)> {
     let Event {
@@ -947,16 +1046,19 @@ fn into_new_event_with_tags(
         title,
         start,
         end,
+        time_zone,
         description,
         location,
         contact,
         homepage,
         created_by,
+        created_at,
         registration,
         archived,
         image_url,
         image_link_url,
         tags,
+        recurrence,
         ..
     } = event;
 
@@ -1022,12 +1124,93 @@ fn into_new_event_with_tags(
             archived: archived.map(Timestamp::into_inner),
             image_url: image_url.map(Url::into_string),
             image_link_url: image_link_url.map(Url::into_string),
+            updated_at: TimestampMs::now().into_inner(),
+            created_at: created_at.map(TimestampMs::into_inner),
+            recurrence: recurrence.map(|r| r.rule.to_string()),
+            time_zone: time_zone.name().to_owned(),
         },
         tags,
     ))
 }
 
-fn resolve_event_id(conn: &SqliteConnection, uid: &str) -> Result<i64> {
+fn replace_event_recurrence_overrides(
+    conn: &Connection,
+    event_rowid: i64,
+    overrides: &[OccurrenceOverride],
+) -> result::Result<(), diesel::result::Error> {
+    use schema::event_recurrence_override::dsl;
+    diesel::delete(dsl::event_recurrence_override.filter(dsl::event_rowid.eq(event_rowid)))
+        .execute(conn)?;
+    let rows: Vec<_> = overrides
+        .iter()
+        .map(|o| util::occurrence_override_into_row(event_rowid, o))
+        .collect();
+    diesel::insert_into(dsl::event_recurrence_override)
+        .values(&rows)
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Convert the start and end times of all events that have been
+/// stored as local times without a time zone into UTC.
+///
+/// Returns the number of converted events.
+pub fn migrate_event_time_zones(conn: &Connection, default_time_zone: Tz) -> Result<usize> {
+    use schema::{event_recurrence_override::dsl as ero_dsl, events::dsl as e_dsl};
+    let rows = e_dsl::events
+        .select((e_dsl::id, e_dsl::start, e_dsl::end, e_dsl::recurrence))
+        .filter(e_dsl::time_zone.is_null())
+        .load::<(i64, i64, Option<i64>, Option<String>)>(conn)?;
+    let into_utc =
+        |ts: i64| local_to_utc(default_time_zone, NaiveDateTime::from_timestamp(ts, 0)).timestamp();
+    // Changed events will be reindexed
+    let updated_at = TimestampMs::now().into_inner();
+    for (id, start, end, recurrence) in &rows {
+        let recurrence = recurrence
+            .as_ref()
+            .map(|rule| match rule.parse::<RecurrenceRule>() {
+                Ok(mut rule) => {
+                    rule.until = rule
+                        .until
+                        .map(|until| local_to_utc(default_time_zone, until));
+                    rule.to_string()
+                }
+                Err(_) => rule.clone(),
+            });
+        diesel::update(e_dsl::events.filter(e_dsl::id.eq(id)))
+            .set((
+                e_dsl::start.eq(into_utc(*start)),
+                e_dsl::end.eq(end.map(into_utc)),
+                e_dsl::recurrence.eq(recurrence),
+                e_dsl::time_zone.eq(default_time_zone.name()),
+                e_dsl::updated_at.eq(updated_at),
+            ))
+            .execute(conn)?;
+        let overrides: Vec<_> = ero_dsl::event_recurrence_override
+            .filter(ero_dsl::event_rowid.eq(id))
+            .load::<models::EventRecurrenceOverride>(conn)?
+            .into_iter()
+            .map(|o| models::EventRecurrenceOverride {
+                recurrence_id: into_utc(o.recurrence_id),
+                start: o.start.map(into_utc),
+                end: o.end.map(into_utc),
+                ..o
+            })
+            .collect();
+        if overrides.is_empty() {
+            continue;
+        }
+        // The recurrence id is part of the primary key
+        diesel::delete(ero_dsl::event_recurrence_override.filter(ero_dsl::event_rowid.eq(id)))
+            .execute(conn)?;
+        diesel::insert_into(ero_dsl::event_recurrence_override)
+            .values(&overrides)
+            .execute(conn)?;
+    }
+    Ok(rows.len())
+}
+
+fn resolve_event_id(conn: &Connection, uid: &str) -> Result<i64> {
     use schema::events::dsl;
     Ok(dsl::events
         .select(dsl::id)
@@ -1035,8 +1218,13 @@ fn resolve_event_id(conn: &SqliteConnection, uid: &str) -> Result<i64> {
         .first(conn)?)
 }
 
-impl EventGateway for SqliteConnection {
+impl EventGateway for Connection {
     fn create_event(&self, e: Event) -> Result<()> {
+        let overrides = e
+            .recurrence
+            .as_ref()
+            .map(|r| r.overrides.clone())
+            .unwrap_or_default();
         let (new_event, tags) = into_new_event_with_tags(self, e)?;
         self.transaction::<_, diesel::result::Error, _>(|| {
             // Insert event
@@ -1058,9 +1246,8 @@ impl EventGateway for SqliteConnection {
                     tag: &tag,
                 })
                 .collect();
-            diesel::insert_or_ignore_into(schema::event_tags::table)
-                .values(&tags)
-                .execute(self)?;
+            insert_or_ignore_into!(schema::event_tags::table, &tags).execute(self)?;
+            replace_event_recurrence_overrides(self, id, &overrides)?;
             Ok(())
         })?;
         Ok(())
@@ -1076,6 +1263,17 @@ impl EventGateway for SqliteConnection {
             diesel::update(e_dsl::events.filter(e_dsl::id.eq(&id)))
                 .set(&new_event)
                 .execute(self)?;
+            // Missing values are ignored by the changeset, i.e. a
+            // removed recurrence needs to be reset explicitly
+            diesel::update(e_dsl::events.filter(e_dsl::id.eq(&id)))
+                .set(e_dsl::recurrence.eq(new_event.recurrence.as_deref()))
+                .execute(self)?;
+            let overrides = event
+                .recurrence
+                .as_ref()
+                .map(|r| r.overrides.as_slice())
+                .unwrap_or_default();
+            replace_event_recurrence_overrides(self, id, overrides)?;
             // Update event tags
             let tags_diff = {
                 let old_tags = et_dsl::event_tags
@@ -1099,9 +1297,7 @@ impl EventGateway for SqliteConnection {
                         tag: &tag,
                     })
                     .collect();
-                diesel::insert_or_ignore_into(et_dsl::event_tags)
-                    .values(&new_tags)
-                    .execute(self)?;
+                insert_or_ignore_into!(et_dsl::event_tags, &new_tags).execute(self)?;
             }
             Ok(())
         })?;
@@ -1136,6 +1332,9 @@ impl EventGateway for SqliteConnection {
                 e_dsl::archived,
                 e_dsl::image_url,
                 e_dsl::image_link_url,
+                e_dsl::created_at,
+                e_dsl::recurrence,
+                e_dsl::time_zone,
                 u_dsl::email.nullable(),
             ))
             .filter(e_dsl::uid.eq_any(ids))
@@ -1167,6 +1366,9 @@ impl EventGateway for SqliteConnection {
                 archived,
                 image_url,
                 image_link_url,
+                created_at,
+                recurrence,
+                time_zone,
                 created_by_email,
                 ..
             } = row;
@@ -1217,21 +1419,35 @@ impl EventGateway for SqliteConnection {
 
             let registration = registration.map(util::registration_type_from_i16);
 
+            let recurrence = if let Some(rule) = recurrence {
+                use schema::event_recurrence_override::dsl as ero_dsl;
+                let overrides = ero_dsl::event_recurrence_override
+                    .filter(ero_dsl::event_rowid.eq(id))
+                    .load::<models::EventRecurrenceOverride>(self)?;
+                util::load_event_recurrence(rule, &overrides)
+            } else {
+                None
+            };
+
             let event = Event {
                 id: uid.into(),
                 title,
                 start: NaiveDateTime::from_timestamp(start, 0),
                 end: end.map(|x| NaiveDateTime::from_timestamp(x, 0)),
+                time_zone: util::load_time_zone(time_zone),
                 description,
                 location,
                 contact,
                 homepage: homepage.and_then(load_url),
                 tags,
                 created_by: created_by_email,
+                created_at: created_at.map(TimestampMs::from_inner),
                 registration,
                 archived: archived.map(Timestamp::from_inner),
                 image_url: image_url.and_then(load_url),
                 image_link_url: image_link_url.and_then(load_url),
+                recurrence,
+                recurrence_id: None,
             };
             events.push(event);
         }
@@ -1272,15 +1488,85 @@ impl EventGateway for SqliteConnection {
                 e_dsl::archived,
                 e_dsl::image_url,
                 e_dsl::image_link_url,
+                e_dsl::created_at,
+                e_dsl::recurrence,
+                e_dsl::time_zone,
                 u_dsl::email.nullable(),
             ))
             .filter(e_dsl::archived.is_null())
             .order_by(e_dsl::start)
             .load::<models::EventEntity>(self)?;
         let tag_rels = et_dsl::event_tags.load(self)?;
+        let override_rels = schema::event_recurrence_override::table.load(self)?;
+        Ok(events
+            .into_iter()
+            .map(|e| util::event_from_event_entity_and_tags(e, &tag_rels, &override_rels))
+            .collect())
+    }
+
+    fn archived_events_chronologically(
+        &self,
+        start_min: Timestamp,
+        start_max: Option<Timestamp>,
+    ) -> Result<Vec<Event>> {
+        use schema::{
+            event_recurrence_override::dsl as ero_dsl, event_tags::dsl as et_dsl,
+            events::dsl as e_dsl, users::dsl as u_dsl,
+        };
+        let mut query = e_dsl::events
+            .left_outer_join(u_dsl::users)
+            .select((
+                e_dsl::id,
+                e_dsl::uid,
+                e_dsl::title,
+                e_dsl::description,
+                e_dsl::start,
+                e_dsl::end,
+                e_dsl::lat,
+                e_dsl::lng,
+                e_dsl::street,
+                e_dsl::zip,
+                e_dsl::city,
+                e_dsl::country,
+                e_dsl::state,
+                e_dsl::email,
+                e_dsl::telephone,
+                e_dsl::homepage,
+                e_dsl::created_by,
+                e_dsl::registration,
+                e_dsl::organizer,
+                e_dsl::archived,
+                e_dsl::image_url,
+                e_dsl::image_link_url,
+                e_dsl::created_at,
+                e_dsl::recurrence,
+                e_dsl::time_zone,
+                u_dsl::email.nullable(),
+            ))
+            .filter(e_dsl::archived.is_not_null())
+            // Recurring events that started before might
+            // still have occurrences within the period
+            .filter(
+                e_dsl::start
+                    .ge(start_min.into_inner())
+                    .or(e_dsl::recurrence.is_not_null()),
+            )
+            .order_by(e_dsl::start)
+            .into_boxed();
+        if let Some(start_max) = start_max {
+            query = query.filter(e_dsl::start.le(start_max.into_inner()));
+        }
+        let events = query.load::<models::EventEntity>(self)?;
+        let event_ids: Vec<_> = events.iter().map(|e| e.id).collect();
+        let tag_rels = et_dsl::event_tags
+            .filter(et_dsl::event_id.eq_any(&event_ids))
+            .load(self)?;
+        let override_rels = ero_dsl::event_recurrence_override
+            .filter(ero_dsl::event_rowid.eq_any(&event_ids))
+            .load(self)?;
         Ok(events
             .into_iter()
-            .map(|e| util::event_from_event_entity_and_tags(e, &tag_rels))
+            .map(|e| util::event_from_event_entity_and_tags(e, &tag_rels, &override_rels))
             .collect())
     }
 
@@ -1292,6 +1578,41 @@ impl EventGateway for SqliteConnection {
             .first::<i64>(self)? as usize)
     }
 
+    fn load_events_chunk(&self, pagination: &Pagination) -> Result<Vec<Event>> {
+        use schema::events::dsl;
+        let mut query = dsl::events
+            .select(dsl::uid)
+            .filter(dsl::archived.is_null())
+            .order_by(dsl::id)
+            .into_boxed();
+        let offset = pagination.offset.unwrap_or(0);
+        if offset > 0 {
+            query = query.offset(offset as i64);
+        }
+        if let Some(limit) = pagination.limit {
+            query = query.limit(limit as i64);
+        }
+        let ids = query.load::<String>(self)?;
+        let ids: Vec<_> = ids.iter().map(String::as_str).collect();
+        self.get_events_chronologically(&ids)
+    }
+
+    fn changed_event_ids_since(&self, since: TimestampMs) -> Result<Vec<Id>> {
+        use schema::{deleted_events::dsl as de_dsl, events::dsl};
+        let mut ids = dsl::events
+            .select(dsl::uid)
+            .filter(dsl::updated_at.ge(since.into_inner()))
+            .load::<String>(self)?;
+        // Deleted events have changed, too
+        ids.extend(
+            de_dsl::deleted_events
+                .select(de_dsl::uid)
+                .filter(de_dsl::deleted_at.ge(since.into_inner()))
+                .load::<String>(self)?,
+        );
+        Ok(ids.into_iter().map(Id::from).collect())
+    }
+
     fn archive_events(&self, ids: &[&str], archived: Timestamp) -> Result<usize> {
         use schema::events::dsl;
         let count = diesel::update(
@@ -1299,7 +1620,10 @@ impl EventGateway for SqliteConnection {
                 .filter(dsl::uid.eq_any(ids))
                 .filter(dsl::archived.is_null()),
         )
-        .set(dsl::archived.eq(Some(archived.into_inner())))
+        .set((
+            dsl::archived.eq(Some(archived.into_inner())),
+            dsl::updated_at.eq(Some(TimestampMs::now().into_inner())),
+        ))
         .execute(self)?;
         debug_assert!(count <= ids.len());
         Ok(count)
@@ -1322,12 +1646,56 @@ impl EventGateway for SqliteConnection {
             debug_assert_eq!(id, *ids.first().unwrap());
         }
         diesel::delete(et_dsl::event_tags.filter(et_dsl::event_id.eq(id))).execute(self)?;
+        diesel::delete(
+            schema::event_recurrence_override::table
+                .filter(schema::event_recurrence_override::dsl::event_rowid.eq(id)),
+        )
+        .execute(self)?;
+        diesel::delete(
+            schema::event_import_uid::table
+                .filter(schema::event_import_uid::dsl::event_rowid.eq(id)),
+        )
+        .execute(self)?;
+        let uid = e_dsl::events
+            .select(e_dsl::uid)
+            .filter(e_dsl::id.eq(id))
+            .first::<String>(self)?;
         diesel::delete(e_dsl::events.filter(e_dsl::id.eq(id))).execute(self)?;
+        let deleted_event = models::DeletedEvent {
+            uid: &uid,
+            deleted_at: TimestampMs::now().into_inner(),
+        };
+        insert_or_ignore_into!(schema::deleted_events::table, &deleted_event).execute(self)?;
         Ok(Some(()))
     }
+
+    fn get_event_id_by_import_uid(&self, org_id: &Id, import_uid: &str) -> Result<Option<Id>> {
+        use schema::{event_import_uid::dsl, events::dsl as e_dsl, organization::dsl as org_dsl};
+        Ok(dsl::event_import_uid
+            .inner_join(org_dsl::organization)
+            .inner_join(e_dsl::events)
+            .select(e_dsl::uid)
+            .filter(org_dsl::id.eq(org_id.as_str()))
+            .filter(dsl::import_uid.eq(import_uid))
+            .first::<String>(self)
+            .optional()?
+            .map(Into::into))
+    }
+
+    fn create_event_import_uid(&self, org_id: &Id, import_uid: &str, event_id: &Id) -> Result<()> {
+        let new_import_uid = models::NewEventImportUid {
+            org_rowid: resolve_organization_rowid(self, org_id)?,
+            import_uid,
+            event_rowid: resolve_event_id(self, event_id.as_str())?,
+        };
+        diesel::insert_into(schema::event_import_uid::table)
+            .values(&new_import_uid)
+            .execute(self)?;
+        Ok(())
+    }
 }
 
-fn resolve_user_created_by_email(conn: &SqliteConnection, email: &str) -> Result<i64> {
+fn resolve_user_created_by_email(conn: &Connection, email: &str) -> Result<i64> {
     use schema::users::dsl;
     Ok(dsl::users
         .select(dsl::id)
@@ -1339,7 +1707,7 @@ fn resolve_user_created_by_email(conn: &SqliteConnection, email: &str) -> Result
         })?)
 }
 
-impl UserGateway for SqliteConnection {
+impl UserGateway for Connection {
     fn create_user(&self, u: &User) -> Result<()> {
         let new_user = models::NewUser::from(u);
         diesel::insert_into(schema::users::table)
@@ -1397,7 +1765,7 @@ impl UserGateway for SqliteConnection {
     }
 }
 
-impl RatingRepository for SqliteConnection {
+impl RatingRepository for Connection {
     fn create_rating(&self, rating: Rating) -> Result<()> {
         let Rating {
             id,
@@ -1547,7 +1915,7 @@ impl RatingRepository for SqliteConnection {
     }
 }
 
-impl CommentRepository for SqliteConnection {
+impl CommentRepository for Connection {
     fn create_comment(&self, comment: Comment) -> Result<()> {
         let Comment {
             id,
@@ -1718,7 +2086,7 @@ impl CommentRepository for SqliteConnection {
     }
 }
 
-impl Db for SqliteConnection {
+impl Db for Connection {
     fn create_tag_if_it_does_not_exist(&self, t: &Tag) -> Result<()> {
         let res = diesel::insert_into(schema::tags::table)
             .values(&models::Tag::from(t.clone()))
@@ -1754,61 +2122,82 @@ impl Db for SqliteConnection {
             south_west_lng,
             north_east_lat,
             north_east_lng,
+            delivery: new.delivery.as_str(),
+            last_digest_at: new.last_digest_at.map(TimestampMs::into_inner),
+            title: new.title.as_deref(),
+            unsubscribe_nonce: new.unsubscribe_nonce.to_string(),
+            polygon: new.polygon.as_ref().map(ToString::to_string),
         };
         diesel::insert_into(schema::bbox_subscriptions::table)
             .values(&insertable)
             .execute(self)?;
+        let rowid = resolve_bbox_subscription_rowid(self, &new.id)?;
+        insert_bbox_subscription_tags(self, rowid, new)?;
         Ok(())
     }
 
-    fn all_bbox_subscriptions(&self) -> Result<Vec<BboxSubscription>> {
+    fn update_bbox_subscription(&self, subscription: &BboxSubscription) -> Result<()> {
+        use schema::bbox_subscription_tags::dsl as t_dsl;
         use schema::bbox_subscriptions::dsl as s_dsl;
-        use schema::users::dsl as u_dsl;
-        Ok(s_dsl::bbox_subscriptions
-            .inner_join(u_dsl::users)
-            .select((
-                s_dsl::id,
-                s_dsl::uid,
-                s_dsl::user_id,
-                s_dsl::south_west_lat,
-                s_dsl::south_west_lng,
-                s_dsl::north_east_lat,
-                s_dsl::north_east_lng,
-                u_dsl::email,
-            ))
-            .load::<models::BboxSubscriptionEntity>(self)?
+        let rowid = resolve_bbox_subscription_rowid(self, &subscription.id)?;
+        let (south_west_lat, south_west_lng) = subscription.bbox.southwest().to_lat_lng_deg();
+        let (north_east_lat, north_east_lng) = subscription.bbox.northeast().to_lat_lng_deg();
+        let changeset = models::UpdateBboxSubscription {
+            south_west_lat,
+            south_west_lng,
+            north_east_lat,
+            north_east_lng,
+            delivery: subscription.delivery.as_str(),
+            last_digest_at: subscription.last_digest_at.map(TimestampMs::into_inner),
+            title: subscription.title.as_deref(),
+            polygon: subscription.polygon.as_ref().map(ToString::to_string),
+        };
+        diesel::update(s_dsl::bbox_subscriptions.filter(s_dsl::id.eq(rowid)))
+            .set(&changeset)
+            .execute(self)?;
+        diesel::delete(t_dsl::bbox_subscription_tags.filter(t_dsl::subscription_id.eq(rowid)))
+            .execute(self)?;
+        insert_bbox_subscription_tags(self, rowid, subscription)?;
+        Ok(())
+    }
+
+    fn get_bbox_subscription(&self, id: &Id) -> Result<BboxSubscription> {
+        load_bbox_subscriptions(self, None, Some(id))?
             .into_iter()
-            .map(BboxSubscription::from)
-            .collect())
+            .next()
+            .ok_or(RepoError::NotFound)
+    }
+    fn all_bbox_subscriptions(&self) -> Result<Vec<BboxSubscription>> {
+        load_bbox_subscriptions(self, None, None)
     }
     fn all_bbox_subscriptions_by_email(&self, email: &str) -> Result<Vec<BboxSubscription>> {
+        load_bbox_subscriptions(self, Some(email), None)
+    }
+    fn delete_bbox_subscription(&self, id: &Id) -> Result<()> {
+        use schema::bbox_subscription_tags::dsl as t_dsl;
         use schema::bbox_subscriptions::dsl as s_dsl;
-        use schema::users::dsl as u_dsl;
-        Ok(s_dsl::bbox_subscriptions
-            .inner_join(u_dsl::users)
-            .filter(u_dsl::email.eq(email))
-            .select((
-                s_dsl::id,
-                s_dsl::uid,
-                s_dsl::user_id,
-                s_dsl::south_west_lat,
-                s_dsl::south_west_lng,
-                s_dsl::north_east_lat,
-                s_dsl::north_east_lng,
-                u_dsl::email,
-            ))
-            .load::<models::BboxSubscriptionEntity>(self)?
-            .into_iter()
-            .map(BboxSubscription::from)
-            .collect())
+        let rowid = resolve_bbox_subscription_rowid(self, id)?;
+        diesel::delete(t_dsl::bbox_subscription_tags.filter(t_dsl::subscription_id.eq(rowid)))
+            .execute(self)?;
+        diesel::delete(s_dsl::bbox_subscriptions.filter(s_dsl::id.eq(rowid))).execute(self)?;
+        Ok(())
     }
     fn delete_bbox_subscriptions_by_email(&self, email: &str) -> Result<()> {
+        use schema::bbox_subscription_tags::dsl as t_dsl;
         use schema::bbox_subscriptions::dsl as s_dsl;
         use schema::users::dsl as u_dsl;
         let users_id = u_dsl::users
             .select(u_dsl::id)
             .filter(u_dsl::email.eq(email));
-        diesel::delete(s_dsl::bbox_subscriptions.filter(s_dsl::user_id.eq_any(users_id)))
+        let subscription_ids = s_dsl::bbox_subscriptions
+            .select(s_dsl::id)
+            .filter(s_dsl::user_id.eq_any(users_id))
+            .load::<i64>(self)?;
+        diesel::delete(
+            t_dsl::bbox_subscription_tags.filter(t_dsl::subscription_id.eq_any(&subscription_ids)),
+        )
+        .execute(self)?;
+        diesel::delete(s_dsl::bbox_subscriptions.filter(s_dsl::id.eq_any(&subscription_ids)))
             .execute(self)?;
         Ok(())
     }
@@ -1826,7 +2215,7 @@ impl Db for SqliteConnection {
     }
 }
 
-impl OrganizationRepo for SqliteConnection {
+impl OrganizationRepo for Connection {
     fn create_org(&mut self, mut o: Organization) -> Result<()> {
         let org_id = o.id.clone();
         let moderated_tags = std::mem::replace(&mut o.moderated_tags, vec![]);
@@ -1931,7 +2320,7 @@ impl OrganizationRepo for SqliteConnection {
     }
 }
 
-impl PlaceClearanceRepo for SqliteConnection {
+impl PlaceClearanceRepo for Connection {
     fn add_pending_clearance_for_places(
         &self,
         org_ids: &[Id],
@@ -1956,8 +2345,7 @@ impl PlaceClearanceRepo for SqliteConnection {
                 last_cleared_revision,
             };
             insert_count +=
-                diesel::insert_or_ignore_into(schema::organization_place_clearance::table)
-                    .values(&insertable)
+                insert_or_ignore_into!(schema::organization_place_clearance::table, &insertable)
                     .execute(self)?;
         }
         Ok(insert_count)
@@ -2116,7 +2504,1178 @@ impl PlaceClearanceRepo for SqliteConnection {
     }
 }
 
-impl UserTokenRepo for SqliteConnection {
+fn resolve_place_owner_rowids(
+    conn: &Connection,
+    owner: &PlaceOwner,
+) -> Result<(Option<i64>, Option<i64>)> {
+    let rowids = match owner {
+        PlaceOwner::User(email) => (
+            Some(resolve_user_created_by_email(conn, email.as_ref())?),
+            None,
+        ),
+        PlaceOwner::Organization(org_id) => (None, Some(resolve_organization_rowid(conn, org_id)?)),
+    };
+    Ok(rowids)
+}
+
+fn load_owned_place_rowids(conn: &Connection, owner: &PlaceOwner) -> Result<Vec<i64>> {
+    use schema::place_owner::dsl;
+    let owned_place_rowids = match resolve_place_owner_rowids(conn, owner)? {
+        (Some(user_rowid), _) => schema::place_owner::table
+            .select(dsl::place_rowid)
+            .filter(dsl::user_rowid.eq(user_rowid))
+            .load::<i64>(conn)?,
+        (_, Some(org_rowid)) => schema::place_owner::table
+            .select(dsl::place_rowid)
+            .filter(dsl::org_rowid.eq(org_rowid))
+            .load::<i64>(conn)?,
+        (None, None) => vec![],
+    };
+    Ok(owned_place_rowids)
+}
+
+fn load_place_owner(conn: &Connection, owner: models::PlaceOwnerEntity) -> Result<PlaceOwner> {
+    let models::PlaceOwnerEntity {
+        user_rowid,
+        org_rowid,
+        ..
+    } = owner;
+    if let Some(user_rowid) = user_rowid {
+        use schema::users::dsl;
+        let email = schema::users::table
+            .select(dsl::email)
+            .filter(dsl::id.eq(user_rowid))
+            .first::<String>(conn)?;
+        return Ok(PlaceOwner::User(email.into()));
+    }
+    if let Some(org_rowid) = org_rowid {
+        use schema::organization::dsl;
+        let org_id = schema::organization::table
+            .select(dsl::id)
+            .filter(dsl::rowid.eq(org_rowid))
+            .first::<String>(conn)?;
+        return Ok(PlaceOwner::Organization(org_id.into()));
+    }
+    Err(RepoError::Other(anyhow!(
+        "Place owner is neither a user nor an organization"
+    )))
+}
+
+impl PlaceOwnershipRepo for Connection {
+    fn load_place_ownership(&self, place_id: &Id) -> Result<PlaceOwnership> {
+        let place_rowid = resolve_place_rowid(self, place_id)?;
+        let owners = {
+            use schema::place_owner::dsl;
+            schema::place_owner::table
+                .filter(dsl::place_rowid.eq(place_rowid))
+                .order_by(dsl::rowid)
+                .load::<models::PlaceOwnerEntity>(self)?
+                .into_iter()
+                .map(|owner| load_place_owner(self, owner))
+                .collect::<Result<Vec<_>>>()?
+        };
+        let locked_fields = {
+            use schema::place_locked_field::dsl;
+            schema::place_locked_field::table
+                .select(dsl::field)
+                .filter(dsl::place_rowid.eq(place_rowid))
+                .load::<String>(self)?
+                .into_iter()
+                .filter_map(|field| {
+                    field
+                        .parse()
+                        .map_err(|_| {
+                            log::error!("Failed to load invalid locked field: {}", field);
+                        })
+                        .ok()
+                })
+                .collect::<Vec<LockableField>>()
+        };
+        Ok(PlaceOwnership {
+            owners,
+            locked_fields,
+        })
+    }
+
+    fn replace_place_ownership(&self, place_id: &Id, ownership: &PlaceOwnership) -> Result<()> {
+        let PlaceOwnership {
+            owners,
+            locked_fields,
+        } = ownership;
+        let place_rowid = resolve_place_rowid(self, place_id)?;
+        {
+            use schema::place_owner::dsl;
+            diesel::delete(schema::place_owner::table.filter(dsl::place_rowid.eq(place_rowid)))
+                .execute(self)?;
+        }
+        for owner in owners {
+            let (user_rowid, org_rowid) = resolve_place_owner_rowids(self, owner)?;
+            let new_owner = models::NewPlaceOwner {
+                place_rowid,
+                user_rowid,
+                org_rowid,
+            };
+            diesel::insert_into(schema::place_owner::table)
+                .values(&new_owner)
+                .execute(self)?;
+        }
+        {
+            use schema::place_locked_field::dsl;
+            diesel::delete(
+                schema::place_locked_field::table.filter(dsl::place_rowid.eq(place_rowid)),
+            )
+            .execute(self)?;
+        }
+        for field in locked_fields {
+            let new_field = models::NewPlaceLockedField {
+                place_rowid,
+                field: field.as_str(),
+            };
+            insert_or_ignore_into!(schema::place_locked_field::table, &new_field).execute(self)?;
+        }
+        Ok(())
+    }
+}
+
+fn load_place_proposal_tags(conn: &Connection, proposal_rowid: i64) -> Result<Vec<String>> {
+    use schema::place_proposal_tag::dsl;
+    Ok(schema::place_proposal_tag::table
+        .filter(dsl::parent_rowid.eq(proposal_rowid))
+        .load::<models::PlaceProposalTag>(conn)?
+        .into_iter()
+        .map(|models::PlaceProposalTag { tag, .. }| tag)
+        .collect())
+}
+
+fn load_place_proposal_custom_links(
+    conn: &Connection,
+    proposal_rowid: i64,
+) -> Result<Vec<CustomLink>> {
+    use schema::place_proposal_custom_link::dsl;
+    Ok(schema::place_proposal_custom_link::table
+        .filter(dsl::parent_rowid.eq(proposal_rowid))
+        .load::<models::PlaceProposalCustomLink>(conn)?
+        .into_iter()
+        .filter_map(
+            |models::PlaceProposalCustomLink {
+                 url,
+                 title,
+                 description,
+                 ..
+             }| {
+                load_url(url).map(|url| CustomLink {
+                    url,
+                    title,
+                    description,
+                })
+            },
+        )
+        .collect())
+}
+
+fn load_place_proposal(
+    conn: &Connection,
+    proposal: models::JoinedPlaceProposal,
+) -> Result<PlaceProposal> {
+    let models::JoinedPlaceProposal {
+        rowid,
+        id,
+        base_rev,
+        created_at,
+        created_by: created_by_id,
+        title,
+        description,
+        lat,
+        lon,
+        street,
+        zip,
+        city,
+        country,
+        state,
+        contact_name,
+        email,
+        phone,
+        homepage,
+        opening_hours,
+        founded_on,
+        image_url,
+        image_link_url,
+        status,
+        reviewed_at,
+        reviewed_by: reviewed_by_id,
+        review_comment,
+        org_rowid,
+        place_id,
+        place_license: license,
+    } = proposal;
+
+    let load_user_email = |user_id: i64| {
+        use schema::users::dsl;
+        schema::users::table
+            .select(dsl::email)
+            .filter(dsl::id.eq(&user_id))
+            .first::<String>(conn)
+    };
+    let created_by = created_by_id.map(load_user_email).transpose()?;
+    let org_id = org_rowid
+        .map(|org_rowid| {
+            use schema::organization::dsl;
+            schema::organization::table
+                .select(dsl::id)
+                .filter(dsl::rowid.eq(&org_rowid))
+                .first::<String>(conn)
+        })
+        .transpose()?;
+    let status = status.parse::<ProposalStatus>().map_err(|_| {
+        log::warn!("Invalid status of place proposal '{}': {}", id, status);
+        RepoError::Other(anyhow!("Invalid proposal status: {}", status))
+    })?;
+    let reviewed = if let Some(reviewed_at) = reviewed_at {
+        Some(ActivityLog {
+            activity: Activity {
+                at: TimestampMs::from_inner(reviewed_at),
+                by: reviewed_by_id
+                    .map(load_user_email)
+                    .transpose()?
+                    .map(Into::into),
+            },
+            context: None,
+            comment: review_comment,
+        })
+    } else {
+        None
+    };
+
+    let base_revision = Revision::from(base_rev as u64);
+    let place = Place {
+        id: place_id.into(),
+        license,
+        revision: base_revision.next(),
+        created: Activity {
+            at: TimestampMs::from_inner(created_at),
+            by: created_by.map(Into::into),
+        },
+        title,
+        description,
+        location: Location {
+            pos: MapPoint::try_from_lat_lng_deg(lat, lon).unwrap_or_default(),
+            address: Some(Address {
+                street,
+                zip,
+                city,
+                country,
+                state,
+            }),
+        },
+        contact: Some(Contact {
+            name: contact_name,
+            email: email.map(Into::into),
+            phone,
+        }),
+        opening_hours: opening_hours.map(Into::into),
+        founded_on,
+        links: Some(Links {
+            homepage: homepage.and_then(load_url),
+            image: image_url.and_then(load_url),
+            image_href: image_link_url.and_then(load_url),
+            custom: load_place_proposal_custom_links(conn, rowid)?,
+        }),
+        tags: load_place_proposal_tags(conn, rowid)?,
+    };
+
+    Ok(PlaceProposal {
+        id: id.into(),
+        base_revision,
+        place,
+        org_id: org_id.map(Into::into),
+        status,
+        reviewed,
+    })
+}
+
+enum PlaceProposalFilter<'a> {
+    Id(&'a str),
+    Place(i64),
+    Pending,
+    PendingOfPlaces(Vec<i64>),
+}
+
+// Oldest proposals first
+fn load_place_proposals(
+    conn: &Connection,
+    filter: PlaceProposalFilter,
+    pagination: &Pagination,
+) -> Result<Vec<PlaceProposal>> {
+    use schema::place::dsl as place_dsl;
+    use schema::place_proposal::dsl;
+    let mut query = schema::place_proposal::table
+        .inner_join(schema::place::table)
+        .select((
+            dsl::rowid,
+            dsl::id,
+            dsl::base_rev,
+            dsl::created_at,
+            dsl::created_by,
+            dsl::title,
+            dsl::description,
+            dsl::lat,
+            dsl::lon,
+            dsl::street,
+            dsl::zip,
+            dsl::city,
+            dsl::country,
+            dsl::state,
+            dsl::contact_name,
+            dsl::email,
+            dsl::phone,
+            dsl::homepage,
+            dsl::opening_hours,
+            dsl::founded_on,
+            dsl::image_url,
+            dsl::image_link_url,
+            dsl::status,
+            dsl::reviewed_at,
+            dsl::reviewed_by,
+            dsl::review_comment,
+            dsl::org_rowid,
+            place_dsl::id,
+            place_dsl::license,
+        ))
+        .order_by(dsl::created_at)
+        .then_order_by(dsl::rowid)
+        .into_boxed();
+
+    query = match filter {
+        PlaceProposalFilter::Id(id) => query.filter(dsl::id.eq(id)),
+        PlaceProposalFilter::Place(place_rowid) => query.filter(dsl::place_rowid.eq(place_rowid)),
+        PlaceProposalFilter::Pending => {
+            query.filter(dsl::status.eq(ProposalStatus::Pending.as_str()))
+        }
+        PlaceProposalFilter::PendingOfPlaces(place_rowids) => query
+            .filter(dsl::status.eq(ProposalStatus::Pending.as_str()))
+            .filter(dsl::place_rowid.eq_any(place_rowids)),
+    };
+
+    // Pagination
+    let offset = pagination.offset.unwrap_or(0);
+    if offset > 0 {
+        query = query.offset(offset as i64);
+    }
+    if let Some(limit) = pagination.limit {
+        query = query.limit(limit as i64);
+    }
+
+    query
+        .load::<models::JoinedPlaceProposal>(conn)?
+        .into_iter()
+        .map(|proposal| load_place_proposal(conn, proposal))
+        .collect()
+}
+
+impl PlaceProposalRepo for Connection {
+    fn add_place_proposal(&self, proposal: &PlaceProposal) -> Result<()> {
+        let PlaceProposal {
+            id,
+            base_revision,
+            place,
+            org_id,
+            ..
+        } = proposal.clone();
+        let Place {
+            id: place_id,
+            created,
+            title,
+            description,
+            location: Location { pos, address },
+            contact,
+            opening_hours,
+            founded_on,
+            links,
+            tags,
+            ..
+        } = place;
+        let place_rowid = resolve_place_rowid(self, &place_id)?;
+        let created_by = if let Some(ref email) = created.by {
+            Some(resolve_user_created_by_email(self, email.as_ref())?)
+        } else {
+            None
+        };
+        let org_rowid = org_id
+            .map(|org_id| resolve_organization_rowid(self, &org_id))
+            .transpose()?;
+        let Contact {
+            name: contact_name,
+            email,
+            phone,
+        } = contact.unwrap_or_default();
+        let Address {
+            street,
+            zip,
+            city,
+            country,
+            state,
+        } = address.unwrap_or_default();
+        let Links {
+            homepage,
+            image: image_url,
+            image_href: image_link_url,
+            custom: custom_links,
+        } = links.unwrap_or_default();
+        let new_proposal = models::NewPlaceProposal {
+            id: id.into(),
+            place_rowid,
+            base_rev: u64::from(base_revision) as i64,
+            created_at: created.at.into_inner(),
+            created_by,
+            title,
+            description,
+            lat: pos.lat().to_deg(),
+            lon: pos.lng().to_deg(),
+            street,
+            zip,
+            city,
+            country,
+            state,
+            contact_name,
+            email: email.map(Into::into),
+            phone,
+            homepage: homepage.map(Url::into_string),
+            opening_hours: opening_hours.map(Into::into),
+            founded_on,
+            image_url: image_url.map(Url::into_string),
+            image_link_url: image_link_url.map(Url::into_string),
+            org_rowid,
+        };
+        diesel::insert_into(schema::place_proposal::table)
+            .values(&new_proposal)
+            .execute(self)?;
+
+        use schema::place_proposal::dsl;
+        let parent_rowid = schema::place_proposal::table
+            .select(dsl::rowid)
+            .filter(dsl::id.eq(&new_proposal.id))
+            .first::<i64>(self)?;
+
+        let insertable_tags: Vec<_> = tags
+            .iter()
+            .map(|tag| models::NewPlaceProposalTag {
+                parent_rowid,
+                tag: tag.as_str(),
+            })
+            .collect();
+        diesel::insert_into(schema::place_proposal_tag::table)
+            .values(&insertable_tags)
+            .execute(self)?;
+
+        let insertable_custom_links: Vec<_> = custom_links
+            .iter()
+            .map(
+                |CustomLink {
+                     url,
+                     title,
+                     description,
+                 }| models::NewPlaceProposalCustomLink {
+                    parent_rowid,
+                    url: url.as_str(),
+                    title: title.as_ref().map(String::as_str),
+                    description: description.as_ref().map(String::as_str),
+                },
+            )
+            .collect();
+        diesel::insert_into(schema::place_proposal_custom_link::table)
+            .values(&insertable_custom_links)
+            .execute(self)?;
+
+        Ok(())
+    }
+
+    fn get_place_proposal(&self, id: &Id) -> Result<PlaceProposal> {
+        load_place_proposals(
+            self,
+            PlaceProposalFilter::Id(id.as_str()),
+            &Default::default(),
+        )?
+        .into_iter()
+        .next()
+        .ok_or(RepoError::NotFound)
+    }
+
+    fn review_place_proposal(
+        &self,
+        id: &Id,
+        status: ProposalStatus,
+        activity: &ActivityLog,
+    ) -> Result<()> {
+        let reviewed_by = if let Some(ref email) = activity.activity.by {
+            Some(resolve_user_created_by_email(self, email.as_ref())?)
+        } else {
+            None
+        };
+        use schema::place_proposal::dsl;
+        let count = diesel::update(schema::place_proposal::table.filter(dsl::id.eq(id.as_str())))
+            .set((
+                dsl::status.eq(status.as_str()),
+                dsl::reviewed_at.eq(activity.activity.at.into_inner()),
+                dsl::reviewed_by.eq(reviewed_by),
+                dsl::review_comment.eq(activity.comment.as_deref()),
+            ))
+            .execute(self)?;
+        if count == 0 {
+            return Err(RepoError::NotFound);
+        }
+        Ok(())
+    }
+
+    fn list_place_proposals_of_place(&self, place_id: &str) -> Result<Vec<PlaceProposal>> {
+        let place_rowid = resolve_place_rowid(self, &Id::from(place_id))?;
+        load_place_proposals(
+            self,
+            PlaceProposalFilter::Place(place_rowid),
+            &Default::default(),
+        )
+    }
+
+    fn count_pending_place_proposals(&self) -> Result<u64> {
+        use schema::place_proposal::dsl;
+        Ok(schema::place_proposal::table
+            .filter(dsl::status.eq(ProposalStatus::Pending.as_str()))
+            .count()
+            .get_result::<i64>(self)? as u64)
+    }
+
+    fn list_pending_place_proposals(&self, pagination: &Pagination) -> Result<Vec<PlaceProposal>> {
+        load_place_proposals(self, PlaceProposalFilter::Pending, pagination)
+    }
+
+    fn count_place_proposals_by_owner(&self, owner: &PlaceOwner) -> Result<u64> {
+        let owned_place_rowids = load_owned_place_rowids(self, owner)?;
+        use schema::place_proposal::dsl;
+        Ok(schema::place_proposal::table
+            .filter(dsl::status.eq(ProposalStatus::Pending.as_str()))
+            .filter(dsl::place_rowid.eq_any(owned_place_rowids))
+            .count()
+            .get_result::<i64>(self)? as u64)
+    }
+
+    fn list_place_proposals_by_owner(
+        &self,
+        owner: &PlaceOwner,
+        pagination: &Pagination,
+    ) -> Result<Vec<PlaceProposal>> {
+        let owned_place_rowids = load_owned_place_rowids(self, owner)?;
+        load_place_proposals(
+            self,
+            PlaceProposalFilter::PendingOfPlaces(owned_place_rowids),
+            pagination,
+        )
+    }
+}
+
+// Categories are stored as tags
+fn insert_bbox_subscription_tags(
+    conn: &Connection,
+    subscription_id: i64,
+    subscription: &BboxSubscription,
+) -> Result<()> {
+    let tags = Category::merge_ids_into_tags(&subscription.categories, subscription.tags.clone());
+    for tag in &tags {
+        let new_tag = models::NewBboxSubscriptionTag {
+            subscription_id,
+            tag,
+        };
+        insert_or_ignore_into!(schema::bbox_subscription_tags::table, &new_tag).execute(conn)?;
+    }
+    Ok(())
+}
+
+fn load_bbox_subscription(
+    conn: &Connection,
+    subscription: models::BboxSubscriptionEntity,
+) -> Result<BboxSubscription> {
+    use schema::bbox_subscription_tags::dsl;
+    let models::BboxSubscriptionEntity {
+        id: rowid,
+        uid,
+        user_id: _,
+        south_west_lat,
+        south_west_lng,
+        north_east_lat,
+        north_east_lng,
+        delivery,
+        last_digest_at,
+        title,
+        unsubscribe_nonce,
+        polygon,
+        user_email,
+    } = subscription;
+    let south_west =
+        MapPoint::try_from_lat_lng_deg(south_west_lat, south_west_lng).unwrap_or_default();
+    let north_east =
+        MapPoint::try_from_lat_lng_deg(north_east_lat, north_east_lng).unwrap_or_default();
+    let delivery = delivery
+        .parse()
+        .map_err(|_| RepoError::Other(anyhow!("Invalid subscription delivery: {}", delivery)))?;
+    let unsubscribe_nonce = unsubscribe_nonce.parse().map_err(|_| {
+        RepoError::Other(anyhow!("Invalid unsubscribe nonce: {}", unsubscribe_nonce))
+    })?;
+    let polygon = polygon
+        .map(|polygon| {
+            polygon
+                .parse()
+                .map_err(|_| RepoError::Other(anyhow!("Invalid polygon: {}", polygon)))
+        })
+        .transpose()?;
+    let tags = schema::bbox_subscription_tags::table
+        .select(dsl::tag)
+        .filter(dsl::subscription_id.eq(rowid))
+        .order_by(dsl::tag)
+        .load::<String>(conn)?;
+    let (tags, categories) = Category::split_from_tags(tags);
+    Ok(BboxSubscription {
+        id: uid.into(),
+        user_email,
+        title,
+        bbox: MapBbox::new(south_west, north_east),
+        polygon,
+        tags,
+        categories: categories.into_iter().map(|c| c.id).collect(),
+        delivery,
+        last_digest_at: last_digest_at.map(TimestampMs::from_inner),
+        unsubscribe_nonce,
+    })
+}
+
+fn load_bbox_subscriptions(
+    conn: &Connection,
+    user_email: Option<&str>,
+    id: Option<&Id>,
+) -> Result<Vec<BboxSubscription>> {
+    use schema::bbox_subscriptions::dsl as s_dsl;
+    use schema::users::dsl as u_dsl;
+    let mut query = s_dsl::bbox_subscriptions
+        .inner_join(u_dsl::users)
+        .select((
+            s_dsl::id,
+            s_dsl::uid,
+            s_dsl::user_id,
+            s_dsl::south_west_lat,
+            s_dsl::south_west_lng,
+            s_dsl::north_east_lat,
+            s_dsl::north_east_lng,
+            s_dsl::delivery,
+            s_dsl::last_digest_at,
+            s_dsl::title,
+            s_dsl::unsubscribe_nonce,
+            s_dsl::polygon,
+            u_dsl::email,
+        ))
+        .order_by(s_dsl::id)
+        .into_boxed();
+    if let Some(user_email) = user_email {
+        query = query.filter(u_dsl::email.eq(user_email));
+    }
+    if let Some(id) = id {
+        query = query.filter(s_dsl::uid.eq(id.as_str()));
+    }
+    query
+        .load::<models::BboxSubscriptionEntity>(conn)?
+        .into_iter()
+        .map(|subscription| load_bbox_subscription(conn, subscription))
+        .collect()
+}
+
+fn load_webhook_subscription(
+    conn: &Connection,
+    webhook: models::OrganizationWebhook,
+) -> Result<WebhookSubscription> {
+    use schema::organization_webhook_tag::dsl;
+    let models::OrganizationWebhook {
+        rowid,
+        id,
+        url,
+        secret,
+        created_at,
+        bbox_south_west_lat,
+        bbox_south_west_lng,
+        bbox_north_east_lat,
+        bbox_north_east_lng,
+        org_id,
+    } = webhook;
+    let url = url
+        .parse()
+        .map_err(|err| RepoError::Other(anyhow!("Invalid webhook URL '{}': {}", url, err)))?;
+    let bbox = match (
+        bbox_south_west_lat,
+        bbox_south_west_lng,
+        bbox_north_east_lat,
+        bbox_north_east_lng,
+    ) {
+        (Some(sw_lat), Some(sw_lng), Some(ne_lat), Some(ne_lng)) => Some(MapBbox::new(
+            MapPoint::try_from_lat_lng_deg(sw_lat, sw_lng).unwrap_or_default(),
+            MapPoint::try_from_lat_lng_deg(ne_lat, ne_lng).unwrap_or_default(),
+        )),
+        _ => None,
+    };
+    let tags = schema::organization_webhook_tag::table
+        .select(dsl::tag)
+        .filter(dsl::webhook_rowid.eq(rowid))
+        .order_by(dsl::tag)
+        .load::<String>(conn)?;
+    Ok(WebhookSubscription {
+        id: id.into(),
+        org_id: org_id.into(),
+        url,
+        secret,
+        bbox,
+        tags,
+        created_at: TimestampMs::from_inner(created_at),
+    })
+}
+
+fn load_webhook_subscriptions(
+    conn: &Connection,
+    org_id: Option<&Id>,
+    id: Option<&Id>,
+) -> Result<Vec<WebhookSubscription>> {
+    use schema::organization::dsl as org_dsl;
+    use schema::organization_webhook::dsl;
+    let mut query = schema::organization_webhook::table
+        .inner_join(schema::organization::table)
+        .select((
+            dsl::rowid,
+            dsl::id,
+            dsl::url,
+            dsl::secret,
+            dsl::created_at,
+            dsl::bbox_south_west_lat,
+            dsl::bbox_south_west_lng,
+            dsl::bbox_north_east_lat,
+            dsl::bbox_north_east_lng,
+            org_dsl::id,
+        ))
+        .order_by(dsl::created_at)
+        .into_boxed();
+    if let Some(org_id) = org_id {
+        query = query.filter(org_dsl::id.eq(org_id.as_str()));
+    }
+    if let Some(id) = id {
+        query = query.filter(dsl::id.eq(id.as_str()));
+    }
+    query
+        .load::<models::OrganizationWebhook>(conn)?
+        .into_iter()
+        .map(|webhook| load_webhook_subscription(conn, webhook))
+        .collect()
+}
+
+impl WebhookRepo for Connection {
+    fn create_webhook_subscription(&self, subscription: &WebhookSubscription) -> Result<()> {
+        let WebhookSubscription {
+            id,
+            org_id,
+            url,
+            secret,
+            bbox,
+            tags,
+            created_at,
+        } = subscription;
+        let org_rowid = resolve_organization_rowid(self, org_id)?;
+        let sw = bbox.map(|bbox| bbox.southwest().to_lat_lng_deg());
+        let ne = bbox.map(|bbox| bbox.northeast().to_lat_lng_deg());
+        let new_webhook = models::NewOrganizationWebhook {
+            id: id.as_str(),
+            org_rowid,
+            url: url.as_str(),
+            secret,
+            created_at: created_at.into_inner(),
+            bbox_south_west_lat: sw.map(|(lat, _)| lat),
+            bbox_south_west_lng: sw.map(|(_, lng)| lng),
+            bbox_north_east_lat: ne.map(|(lat, _)| lat),
+            bbox_north_east_lng: ne.map(|(_, lng)| lng),
+        };
+        diesel::insert_into(schema::organization_webhook::table)
+            .values(&new_webhook)
+            .execute(self)?;
+        let webhook_rowid = resolve_webhook_rowid(self, id)?;
+        for tag in tags {
+            let new_tag = models::NewOrganizationWebhookTag { webhook_rowid, tag };
+            insert_or_ignore_into!(schema::organization_webhook_tag::table, &new_tag)
+                .execute(self)?;
+        }
+        Ok(())
+    }
+
+    fn delete_webhook_subscription(&self, org_id: &Id, id: &Id) -> Result<()> {
+        use schema::organization_webhook::dsl;
+        let org_rowid = resolve_organization_rowid(self, org_id)?;
+        let webhook_rowid = schema::organization_webhook::table
+            .select(dsl::rowid)
+            .filter(dsl::id.eq(id.as_str()))
+            .filter(dsl::org_rowid.eq(org_rowid))
+            .first::<i64>(self)
+            .optional()?
+            .ok_or(RepoError::NotFound)?;
+        diesel::delete(
+            schema::organization_webhook_delivery::table.filter(
+                schema::organization_webhook_delivery::dsl::webhook_rowid.eq(webhook_rowid),
+            ),
+        )
+        .execute(self)?;
+        diesel::delete(
+            schema::organization_webhook_tag::table
+                .filter(schema::organization_webhook_tag::dsl::webhook_rowid.eq(webhook_rowid)),
+        )
+        .execute(self)?;
+        diesel::delete(schema::organization_webhook::table.filter(dsl::rowid.eq(webhook_rowid)))
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn get_webhook_subscription(&self, id: &Id) -> Result<WebhookSubscription> {
+        load_webhook_subscriptions(self, None, Some(id))?
+            .into_iter()
+            .next()
+            .ok_or(RepoError::NotFound)
+    }
+
+    fn all_webhook_subscriptions(&self) -> Result<Vec<WebhookSubscription>> {
+        load_webhook_subscriptions(self, None, None)
+    }
+
+    fn all_webhook_subscriptions_by_org(&self, org_id: &Id) -> Result<Vec<WebhookSubscription>> {
+        load_webhook_subscriptions(self, Some(org_id), None)
+    }
+
+    fn create_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<()> {
+        let WebhookDelivery {
+            id,
+            subscription_id,
+            event,
+            payload,
+            created_at,
+            attempts,
+            ..
+        } = delivery;
+        let new_delivery = models::NewOrganizationWebhookDelivery {
+            id: id.as_str(),
+            webhook_rowid: resolve_webhook_rowid(self, subscription_id)?,
+            event: event.as_str(),
+            payload,
+            created_at: created_at.into_inner(),
+            attempts: i64::from(*attempts),
+        };
+        diesel::insert_into(schema::organization_webhook_delivery::table)
+            .values(&new_delivery)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn update_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<()> {
+        use schema::organization_webhook_delivery::dsl;
+        let WebhookDelivery {
+            id,
+            attempts,
+            last_attempt_at,
+            last_status,
+            last_error,
+            delivered_at,
+            ..
+        } = delivery;
+        let changeset = models::UpdateOrganizationWebhookDelivery {
+            attempts: i64::from(*attempts),
+            last_attempt_at: last_attempt_at.map(TimestampMs::into_inner),
+            last_status: last_status.map(i64::from),
+            last_error: last_error.as_deref(),
+            delivered_at: delivered_at.map(TimestampMs::into_inner),
+        };
+        let count = diesel::update(
+            schema::organization_webhook_delivery::table.filter(dsl::id.eq(id.as_str())),
+        )
+        .set(&changeset)
+        .execute(self)?;
+        if count == 0 {
+            return Err(RepoError::NotFound);
+        }
+        debug_assert_eq!(1, count);
+        Ok(())
+    }
+
+    fn recent_webhook_deliveries(
+        &self,
+        subscription_id: &Id,
+        pagination: &Pagination,
+    ) -> Result<Vec<WebhookDelivery>> {
+        use schema::organization_webhook::dsl as w_dsl;
+        use schema::organization_webhook_delivery::dsl;
+        let mut query = schema::organization_webhook_delivery::table
+            .inner_join(schema::organization_webhook::table)
+            .select((
+                dsl::id,
+                dsl::event,
+                dsl::payload,
+                dsl::created_at,
+                dsl::attempts,
+                dsl::last_attempt_at,
+                dsl::last_status,
+                dsl::last_error,
+                dsl::delivered_at,
+                w_dsl::id,
+            ))
+            .filter(w_dsl::id.eq(subscription_id.as_str()))
+            .order_by((dsl::created_at.desc(), dsl::rowid.desc()))
+            .into_boxed();
+
+        // Pagination
+        let offset = pagination.offset.unwrap_or(0);
+        if offset > 0 {
+            query = query.offset(offset as i64);
+        }
+        if let Some(limit) = pagination.limit {
+            query = query.limit(limit as i64);
+        }
+
+        query
+            .load::<models::OrganizationWebhookDelivery>(self)?
+            .into_iter()
+            .map(load_webhook_delivery)
+            .collect()
+    }
+
+    fn load_webhook_delivery(&self, id: &Id) -> Result<WebhookDelivery> {
+        use schema::organization_webhook::dsl as w_dsl;
+        use schema::organization_webhook_delivery::dsl;
+        let delivery = schema::organization_webhook_delivery::table
+            .inner_join(schema::organization_webhook::table)
+            .select((
+                dsl::id,
+                dsl::event,
+                dsl::payload,
+                dsl::created_at,
+                dsl::attempts,
+                dsl::last_attempt_at,
+                dsl::last_status,
+                dsl::last_error,
+                dsl::delivered_at,
+                w_dsl::id,
+            ))
+            .filter(dsl::id.eq(id.as_str()))
+            .first::<models::OrganizationWebhookDelivery>(self)
+            .optional()?
+            .ok_or(RepoError::NotFound)?;
+        load_webhook_delivery(delivery)
+    }
+}
+
+fn load_webhook_delivery(delivery: models::OrganizationWebhookDelivery) -> Result<WebhookDelivery> {
+    let models::OrganizationWebhookDelivery {
+        id,
+        event,
+        payload,
+        created_at,
+        attempts,
+        last_attempt_at,
+        last_status,
+        last_error,
+        delivered_at,
+        webhook_id,
+    } = delivery;
+    let event = event
+        .parse()
+        .map_err(|_| RepoError::Other(anyhow!("Invalid webhook event: {}", event)))?;
+    Ok(WebhookDelivery {
+        id: id.into(),
+        subscription_id: webhook_id.into(),
+        event,
+        payload,
+        created_at: TimestampMs::from_inner(created_at),
+        attempts: attempts as u32,
+        last_attempt_at: last_attempt_at.map(TimestampMs::from_inner),
+        last_status: last_status.map(|status| status as u16),
+        last_error,
+        delivered_at: delivered_at.map(TimestampMs::from_inner),
+    })
+}
+
+fn load_job(job: models::Job) -> Result<Job> {
+    let models::Job {
+        rowid: _,
+        id,
+        kind,
+        payload,
+        status,
+        attempts,
+        created_at,
+        run_at,
+        last_error,
+    } = job;
+    let status = status
+        .parse()
+        .map_err(|_| RepoError::Other(anyhow!("Invalid job status: {}", status)))?;
+    Ok(Job {
+        id: id.into(),
+        kind,
+        payload,
+        status,
+        attempts: attempts as u32,
+        created_at: TimestampMs::from_inner(created_at),
+        run_at: TimestampMs::from_inner(run_at),
+        last_error,
+    })
+}
+
+impl JobRepo for Connection {
+    fn enqueue_job(&self, job: &Job) -> Result<()> {
+        let Job {
+            id,
+            kind,
+            payload,
+            status,
+            attempts,
+            created_at,
+            run_at,
+            last_error,
+        } = job;
+        let new_job = models::NewJob {
+            id: id.as_str(),
+            kind,
+            payload,
+            status: status.as_str(),
+            attempts: i64::from(*attempts),
+            created_at: created_at.into_inner(),
+            run_at: run_at.into_inner(),
+            last_error: last_error.as_deref(),
+        };
+        diesel::insert_into(schema::job::table)
+            .values(&new_job)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn acquire_next_job(&self, now: TimestampMs) -> Result<Option<Job>> {
+        use schema::job::dsl;
+        loop {
+            let job = schema::job::table
+                .filter(dsl::status.eq(JobStatus::Pending.as_str()))
+                .filter(dsl::run_at.le(now.into_inner()))
+                .order_by((dsl::run_at, dsl::rowid))
+                .first::<models::Job>(self)
+                .optional()?;
+            let job = match job {
+                Some(job) => load_job(job)?,
+                None => return Ok(None),
+            };
+            // The conditional update fails if another worker
+            // acquired the same job in the meantime
+            let count = diesel::update(
+                schema::job::table
+                    .filter(dsl::id.eq(job.id.as_str()))
+                    .filter(dsl::status.eq(JobStatus::Pending.as_str())),
+            )
+            .set(dsl::status.eq(JobStatus::Running.as_str()))
+            .execute(self)?;
+            if count > 0 {
+                debug_assert_eq!(1, count);
+                return Ok(Some(Job {
+                    status: JobStatus::Running,
+                    ..job
+                }));
+            }
+        }
+    }
+
+    fn update_job(&self, job: &Job) -> Result<()> {
+        use schema::job::dsl;
+        let changeset = models::UpdateJob {
+            status: job.status.as_str(),
+            attempts: i64::from(job.attempts),
+            run_at: job.run_at.into_inner(),
+            last_error: job.last_error.as_deref(),
+        };
+        let count = diesel::update(schema::job::table.filter(dsl::id.eq(job.id.as_str())))
+            .set(&changeset)
+            .execute(self)?;
+        if count == 0 {
+            return Err(RepoError::NotFound);
+        }
+        debug_assert_eq!(1, count);
+        Ok(())
+    }
+
+    fn delete_job(&self, id: &Id) -> Result<()> {
+        use schema::job::dsl;
+        let count =
+            diesel::delete(schema::job::table.filter(dsl::id.eq(id.as_str()))).execute(self)?;
+        if count == 0 {
+            return Err(RepoError::NotFound);
+        }
+        debug_assert_eq!(1, count);
+        Ok(())
+    }
+
+    fn load_jobs(&self, status: JobStatus, pagination: &Pagination) -> Result<Vec<Job>> {
+        use schema::job::dsl;
+        let mut query = schema::job::table
+            .filter(dsl::status.eq(status.as_str()))
+            .order_by((dsl::created_at, dsl::rowid))
+            .into_boxed();
+
+        // Pagination
+        let offset = pagination.offset.unwrap_or(0);
+        if offset > 0 {
+            query = query.offset(offset as i64);
+        }
+        if let Some(limit) = pagination.limit {
+            query = query.limit(limit as i64);
+        }
+
+        query
+            .load::<models::Job>(self)?
+            .into_iter()
+            .map(load_job)
+            .collect()
+    }
+
+    fn count_jobs(&self, status: JobStatus) -> Result<usize> {
+        use schema::job::dsl;
+        Ok(schema::job::table
+            .select(diesel::dsl::count(dsl::rowid))
+            .filter(dsl::status.eq(status.as_str()))
+            .first::<i64>(self)? as usize)
+    }
+
+    fn count_jobs_by_kind(&self, kind: &str, status: JobStatus) -> Result<usize> {
+        use schema::job::dsl;
+        Ok(schema::job::table
+            .select(diesel::dsl::count(dsl::rowid))
+            .filter(dsl::kind.eq(kind))
+            .filter(dsl::status.eq(status.as_str()))
+            .first::<i64>(self)? as usize)
+    }
+
+    fn retry_failed_jobs(&self, run_at: TimestampMs) -> Result<usize> {
+        use schema::job::dsl;
+        Ok(
+            diesel::update(schema::job::table.filter(dsl::status.eq(JobStatus::Failed.as_str())))
+                .set((
+                    dsl::status.eq(JobStatus::Pending.as_str()),
+                    dsl::attempts.eq(0),
+                    dsl::run_at.eq(run_at.into_inner()),
+                ))
+                .execute(self)?,
+        )
+    }
+
+    fn release_running_jobs(&self) -> Result<usize> {
+        use schema::job::dsl;
+        Ok(
+            diesel::update(schema::job::table.filter(dsl::status.eq(JobStatus::Running.as_str())))
+                .set(dsl::status.eq(JobStatus::Pending.as_str()))
+                .execute(self)?,
+        )
+    }
+}
+
+impl UserTokenRepo for Connection {
     fn replace_user_token(&self, token: UserToken) -> Result<EmailNonce> {
         use schema::user_tokens::dsl;
         let user_id = resolve_user_created_by_email(self, &token.email_nonce.email)?;
@@ -69,6 +69,10 @@ pub struct JoinedPlaceRevision {
     pub founded_on: Option<NaiveDate>,
     pub image_url: Option<String>,
     pub image_link_url: Option<String>,
+    pub status: String,
+    pub reviewed_at: Option<i64>,
+    pub reviewed_by: Option<i64>,
+    pub review_comment: Option<String>,
     // Joined columns
     pub place_id: String,
     pub place_license: String,
@@ -161,6 +165,121 @@ pub struct NewPlaceRevisionCustomLink<'a> {
     pub description: Option<&'a str>,
 }
 
+#[derive(Queryable)]
+pub struct PlaceOwnerEntity {
+    pub rowid: i64,
+    pub place_rowid: i64,
+    pub user_rowid: Option<i64>,
+    pub org_rowid: Option<i64>,
+}
+
+#[derive(Insertable)]
+#[table_name = "place_owner"]
+pub struct NewPlaceOwner {
+    pub place_rowid: i64,
+    pub user_rowid: Option<i64>,
+    pub org_rowid: Option<i64>,
+}
+
+#[derive(Insertable)]
+#[table_name = "place_locked_field"]
+pub struct NewPlaceLockedField<'a> {
+    pub place_rowid: i64,
+    pub field: &'a str,
+}
+
+#[derive(Insertable)]
+#[table_name = "place_proposal"]
+pub struct NewPlaceProposal {
+    pub id: String,
+    pub place_rowid: i64,
+    pub base_rev: i64,
+    pub created_at: i64,
+    pub created_by: Option<i64>,
+    pub title: String,
+    pub description: String,
+    pub lat: f64,
+    pub lon: f64,
+    pub street: Option<String>,
+    pub zip: Option<String>,
+    pub city: Option<String>,
+    pub country: Option<String>,
+    pub state: Option<String>,
+    pub contact_name: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub homepage: Option<String>,
+    pub opening_hours: Option<String>,
+    pub founded_on: Option<NaiveDate>,
+    pub image_url: Option<String>,
+    pub image_link_url: Option<String>,
+    pub org_rowid: Option<i64>,
+}
+
+#[derive(Queryable)]
+pub struct JoinedPlaceProposal {
+    pub rowid: i64,
+    pub id: String,
+    pub base_rev: i64,
+    pub created_at: i64,
+    pub created_by: Option<i64>,
+    pub title: String,
+    pub description: String,
+    pub lat: f64,
+    pub lon: f64,
+    pub street: Option<String>,
+    pub zip: Option<String>,
+    pub city: Option<String>,
+    pub country: Option<String>,
+    pub state: Option<String>,
+    pub contact_name: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub homepage: Option<String>,
+    pub opening_hours: Option<String>,
+    pub founded_on: Option<NaiveDate>,
+    pub image_url: Option<String>,
+    pub image_link_url: Option<String>,
+    pub status: String,
+    pub reviewed_at: Option<i64>,
+    pub reviewed_by: Option<i64>,
+    pub review_comment: Option<String>,
+    pub org_rowid: Option<i64>,
+    // Joined columns
+    pub place_id: String,
+    pub place_license: String,
+}
+
+#[derive(Queryable)]
+pub struct PlaceProposalTag {
+    pub parent_rowid: i64,
+    pub tag: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "place_proposal_tag"]
+pub struct NewPlaceProposalTag<'a> {
+    pub parent_rowid: i64,
+    pub tag: &'a str,
+}
+
+#[derive(Queryable)]
+pub struct PlaceProposalCustomLink {
+    pub parent_rowid: i64,
+    pub url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Insertable)]
+#[table_name = "place_proposal_custom_link"]
+pub struct NewPlaceProposalCustomLink<'a> {
+    pub parent_rowid: i64,
+    pub url: &'a str,
+    pub title: Option<&'a str>,
+    pub description: Option<&'a str>,
+}
+
 #[derive(Insertable)]
 #[table_name = "place_rating"]
 pub struct NewPlaceRating {
@@ -241,6 +360,11 @@ pub struct NewEvent {
     pub archived: Option<i64>,
     pub image_url: Option<String>,
     pub image_link_url: Option<String>,
+    pub updated_at: i64,
+    // Only set when inserting
+    pub created_at: Option<i64>,
+    pub recurrence: Option<String>,
+    pub time_zone: String,
 }
 
 #[derive(Queryable)]
@@ -267,6 +391,9 @@ pub struct EventEntity {
     pub archived: Option<i64>,
     pub image_url: Option<String>,
     pub image_link_url: Option<String>,
+    pub created_at: Option<i64>,
+    pub recurrence: Option<String>,
+    pub time_zone: Option<String>,
     // Joined columns
     pub created_by_email: Option<String>,
 }
@@ -300,6 +427,33 @@ pub struct NewEventTag<'a> {
     pub tag: &'a str,
 }
 
+#[derive(Insertable)]
+#[table_name = "deleted_events"]
+pub struct DeletedEvent<'a> {
+    pub uid: &'a str,
+    pub deleted_at: i64,
+}
+
+#[derive(Queryable, Insertable)]
+#[table_name = "event_recurrence_override"]
+pub struct EventRecurrenceOverride {
+    pub event_rowid: i64,
+    pub recurrence_id: i64,
+    pub cancelled: i16,
+    pub start: Option<i64>,
+    pub end: Option<i64>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Insertable)]
+#[table_name = "event_import_uid"]
+pub struct NewEventImportUid<'a> {
+    pub org_rowid: i64,
+    pub import_uid: &'a str,
+    pub event_rowid: i64,
+}
+
 #[derive(Queryable)]
 pub struct OrganizationTag {
     pub org_rowid: i64,
@@ -361,6 +515,25 @@ pub struct NewBboxSubscription<'a> {
     pub south_west_lng: f64,
     pub north_east_lat: f64,
     pub north_east_lng: f64,
+    pub delivery: &'a str,
+    pub last_digest_at: Option<i64>,
+    pub title: Option<&'a str>,
+    pub unsubscribe_nonce: String,
+    pub polygon: Option<String>,
+}
+
+#[derive(AsChangeset)]
+#[table_name = "bbox_subscriptions"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct UpdateBboxSubscription<'a> {
+    pub south_west_lat: f64,
+    pub south_west_lng: f64,
+    pub north_east_lat: f64,
+    pub north_east_lng: f64,
+    pub delivery: &'a str,
+    pub last_digest_at: Option<i64>,
+    pub title: Option<&'a str>,
+    pub polygon: Option<String>,
 }
 
 #[derive(Queryable)]
@@ -372,10 +545,22 @@ pub struct BboxSubscriptionEntity {
     pub south_west_lng: f64,
     pub north_east_lat: f64,
     pub north_east_lng: f64,
+    pub delivery: String,
+    pub last_digest_at: Option<i64>,
+    pub title: Option<String>,
+    pub unsubscribe_nonce: String,
+    pub polygon: Option<String>,
     // Joined columns
     pub user_email: String,
 }
 
+#[derive(Insertable)]
+#[table_name = "bbox_subscription_tags"]
+pub struct NewBboxSubscriptionTag<'a> {
+    pub subscription_id: i64,
+    pub tag: &'a str,
+}
+
 #[derive(Insertable, AsChangeset)]
 #[table_name = "user_tokens"]
 pub struct NewUserToken {
@@ -409,3 +594,112 @@ pub struct PendingClearanceForPlace {
     pub created_at: i64,
     pub last_cleared_revision: Option<i64>,
 }
+
+#[derive(Insertable)]
+#[table_name = "organization_webhook"]
+pub struct NewOrganizationWebhook<'a> {
+    pub id: &'a str,
+    pub org_rowid: i64,
+    pub url: &'a str,
+    pub secret: &'a str,
+    pub created_at: i64,
+    pub bbox_south_west_lat: Option<f64>,
+    pub bbox_south_west_lng: Option<f64>,
+    pub bbox_north_east_lat: Option<f64>,
+    pub bbox_north_east_lng: Option<f64>,
+}
+
+#[derive(Queryable)]
+pub struct OrganizationWebhook {
+    pub rowid: i64,
+    pub id: String,
+    pub url: String,
+    pub secret: String,
+    pub created_at: i64,
+    pub bbox_south_west_lat: Option<f64>,
+    pub bbox_south_west_lng: Option<f64>,
+    pub bbox_north_east_lat: Option<f64>,
+    pub bbox_north_east_lng: Option<f64>,
+    // Joined columns
+    pub org_id: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "organization_webhook_tag"]
+pub struct NewOrganizationWebhookTag<'a> {
+    pub webhook_rowid: i64,
+    pub tag: &'a str,
+}
+
+#[derive(Insertable)]
+#[table_name = "organization_webhook_delivery"]
+pub struct NewOrganizationWebhookDelivery<'a> {
+    pub id: &'a str,
+    pub webhook_rowid: i64,
+    pub event: &'a str,
+    pub payload: &'a str,
+    pub created_at: i64,
+    pub attempts: i64,
+}
+
+#[derive(AsChangeset)]
+#[table_name = "organization_webhook_delivery"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct UpdateOrganizationWebhookDelivery<'a> {
+    pub attempts: i64,
+    pub last_attempt_at: Option<i64>,
+    pub last_status: Option<i64>,
+    pub last_error: Option<&'a str>,
+    pub delivered_at: Option<i64>,
+}
+
+#[derive(Queryable)]
+pub struct OrganizationWebhookDelivery {
+    pub id: String,
+    pub event: String,
+    pub payload: String,
+    pub created_at: i64,
+    pub attempts: i64,
+    pub last_attempt_at: Option<i64>,
+    pub last_status: Option<i64>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<i64>,
+    // Joined columns
+    pub webhook_id: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "job"]
+pub struct NewJob<'a> {
+    pub id: &'a str,
+    pub kind: &'a str,
+    pub payload: &'a str,
+    pub status: &'a str,
+    pub attempts: i64,
+    pub created_at: i64,
+    pub run_at: i64,
+    pub last_error: Option<&'a str>,
+}
+
+#[derive(AsChangeset)]
+#[table_name = "job"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct UpdateJob<'a> {
+    pub status: &'a str,
+    pub attempts: i64,
+    pub run_at: i64,
+    pub last_error: Option<&'a str>,
+}
+
+#[derive(Queryable)]
+pub struct Job {
+    pub rowid: i64,
+    pub id: String,
+    pub kind: String,
+    pub payload: String,
+    pub status: String,
+    pub attempts: i64,
+    pub created_at: i64,
+    pub run_at: i64,
+    pub last_error: Option<String>,
+}
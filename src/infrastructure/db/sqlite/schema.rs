@@ -47,6 +47,51 @@ table! {
 joinable!(organization_place_clearance -> organization (org_rowid));
 joinable!(organization_place_clearance -> place (place_rowid));
 
+table! {
+    organization_webhook (rowid) {
+        rowid -> BigInt,
+        id -> Text,
+        org_rowid -> BigInt,
+        url -> Text,
+        secret -> Text,
+        created_at -> BigInt,
+        bbox_south_west_lat -> Nullable<Double>,
+        bbox_south_west_lng -> Nullable<Double>,
+        bbox_north_east_lat -> Nullable<Double>,
+        bbox_north_east_lng -> Nullable<Double>,
+    }
+}
+
+joinable!(organization_webhook -> organization (org_rowid));
+
+table! {
+    organization_webhook_tag (webhook_rowid, tag) {
+        webhook_rowid -> BigInt,
+        tag -> Text,
+    }
+}
+
+joinable!(organization_webhook_tag -> organization_webhook (webhook_rowid));
+
+table! {
+    organization_webhook_delivery (rowid) {
+        rowid -> BigInt,
+        id -> Text,
+        webhook_rowid -> BigInt,
+        event -> Text,
+        payload -> Text,
+        created_at -> BigInt,
+        attempts -> BigInt,
+        last_attempt_at -> Nullable<BigInt>,
+        // HTTP status code of the last response
+        last_status -> Nullable<BigInt>,
+        last_error -> Nullable<Text>,
+        delivered_at -> Nullable<BigInt>,
+    }
+}
+
+joinable!(organization_webhook_delivery -> organization_webhook (webhook_rowid));
+
 ///////////////////////////////////////////////////////////////////////
 // Users
 ///////////////////////////////////////////////////////////////////////
@@ -183,6 +228,83 @@ table! {
 
 joinable!(place_rating_comment -> place_rating (parent_rowid));
 
+table! {
+    place_owner (rowid) {
+        rowid -> BigInt,
+        place_rowid -> BigInt,
+        // either a user or an organization
+        user_rowid -> Nullable<BigInt>,
+        org_rowid -> Nullable<BigInt>,
+    }
+}
+
+joinable!(place_owner -> place (place_rowid));
+
+table! {
+    place_locked_field (place_rowid, field) {
+        place_rowid -> BigInt,
+        // contact, opening_hours, links
+        field -> Text,
+    }
+}
+
+joinable!(place_locked_field -> place (place_rowid));
+
+table! {
+    place_proposal (rowid) {
+        rowid -> BigInt,
+        id -> Text,
+        place_rowid -> BigInt,
+        base_rev -> BigInt,
+        created_at -> BigInt,
+        created_by -> Nullable<BigInt>,
+        title -> Text,
+        description -> Text,
+        lat -> Double,
+        lon -> Double,
+        street -> Nullable<Text>,
+        zip -> Nullable<Text>,
+        city -> Nullable<Text>,
+        country -> Nullable<Text>,
+        state -> Nullable<Text>,
+        contact_name -> Nullable<Text>,
+        email -> Nullable<Text>,
+        phone -> Nullable<Text>,
+        homepage -> Nullable<Text>,
+        opening_hours -> Nullable<Text>,
+        founded_on -> Nullable<Date>,
+        image_url -> Nullable<Text>,
+        image_link_url -> Nullable<Text>,
+        status -> Text,
+        reviewed_at -> Nullable<BigInt>,
+        reviewed_by -> Nullable<BigInt>,
+        review_comment -> Nullable<Text>,
+        org_rowid -> Nullable<BigInt>,
+    }
+}
+
+joinable!(place_proposal -> place (place_rowid));
+
+table! {
+    place_proposal_tag (parent_rowid, tag) {
+        parent_rowid -> BigInt,
+        tag -> Text,
+    }
+}
+
+joinable!(place_proposal_tag -> place_proposal (parent_rowid));
+
+table! {
+    place_proposal_custom_link (parent_rowid, url) {
+        parent_rowid -> BigInt,
+        url -> Text,
+        title -> Nullable<Text>,
+        description -> Nullable<Text>,
+    }
+}
+
+joinable!(place_proposal_custom_link -> place_proposal (parent_rowid));
+
 ///////////////////////////////////////////////////////////////////////
 // Events
 ///////////////////////////////////////////////////////////////////////
@@ -213,6 +335,13 @@ table! {
         archived -> Nullable<BigInt>,
         image_url -> Nullable<Text>,
         image_link_url -> Nullable<Text>,
+        // last time the event has been created, updated or archived
+        updated_at -> Nullable<BigInt>,
+        created_at -> Nullable<BigInt>,
+        recurrence -> Nullable<Text>,
+        // IANA time zone, unknown for events that have been
+        // created before time zones have been recorded
+        time_zone -> Nullable<Text>,
     }
 }
 
@@ -227,6 +356,38 @@ table! {
 
 joinable!(event_tags -> events (event_id));
 
+table! {
+    event_import_uid (org_rowid, import_uid) {
+        org_rowid -> BigInt,
+        import_uid -> Text,
+        event_rowid -> BigInt,
+    }
+}
+
+joinable!(event_import_uid -> organization (org_rowid));
+joinable!(event_import_uid -> events (event_rowid));
+
+table! {
+    event_recurrence_override (event_rowid, recurrence_id) {
+        event_rowid -> BigInt,
+        recurrence_id -> BigInt,
+        cancelled -> SmallInt,
+        start -> Nullable<BigInt>,
+        end -> Nullable<BigInt>,
+        title -> Nullable<Text>,
+        description -> Nullable<Text>,
+    }
+}
+
+joinable!(event_recurrence_override -> events (event_rowid));
+
+table! {
+    deleted_events (uid) {
+        uid -> Text,
+        deleted_at -> BigInt,
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////
 // Subscriptions
 ///////////////////////////////////////////////////////////////////////
@@ -243,18 +404,63 @@ table! {
         south_west_lng -> Double,
         north_east_lat -> Double,
         north_east_lng -> Double,
+        // immediate, daily, weekly
+        delivery -> Text,
+        last_digest_at -> Nullable<BigInt>,
+        title -> Nullable<Text>,
+        unsubscribe_nonce -> Text,
+        // WKT
+        polygon -> Nullable<Text>,
     }
 }
 
 joinable!(bbox_subscriptions -> users (user_id));
 
+table! {
+    bbox_subscription_tags (subscription_id, tag) {
+        subscription_id -> BigInt,
+        tag -> Text,
+    }
+}
+
+joinable!(bbox_subscription_tags -> bbox_subscriptions (subscription_id));
+
+///////////////////////////////////////////////////////////////////////
+// Jobs
+///////////////////////////////////////////////////////////////////////
+
+table! {
+    job (rowid) {
+        rowid -> BigInt,
+        id -> Text,
+        kind -> Text,
+        payload -> Text,
+        // pending, running, failed
+        status -> Text,
+        attempts -> BigInt,
+        created_at -> BigInt,
+        run_at -> BigInt,
+        last_error -> Nullable<Text>,
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////
 
 allow_tables_to_appear_in_same_query!(
     bbox_subscriptions,
+    bbox_subscription_tags,
+    deleted_events,
     events,
     event_tags,
+    event_import_uid,
+    event_recurrence_override,
+    job,
     place,
+    place_locked_field,
+    place_owner,
+    place_proposal,
+    place_proposal_custom_link,
+    place_proposal_tag,
     place_rating,
     place_rating_comment,
     place_revision,
@@ -264,6 +470,9 @@ allow_tables_to_appear_in_same_query!(
     organization,
     organization_tag,
     organization_place_clearance,
+    organization_webhook,
+    organization_webhook_tag,
+    organization_webhook_delivery,
     tags,
     users,
     user_tokens,
@@ -2,11 +2,7 @@ use super::models::*;
 use crate::core::{
     entities as e,
     prelude::{ParameterError, Result},
-    util::{
-        geo::{MapBbox, MapPoint},
-        nonce::Nonce,
-        time::Timestamp,
-    },
+    util::{geo::MapPoint, nonce::Nonce, time::Timestamp},
 };
 use chrono::prelude::*;
 use url::Url;
@@ -22,6 +18,19 @@ pub(crate) fn load_url(url: String) -> Option<Url> {
     }
 }
 
+pub(crate) fn load_time_zone(time_zone: Option<String>) -> e::Tz {
+    match time_zone.map(|tz| tz.parse::<e::Tz>().map_err(|err| (tz, err))) {
+        Some(Ok(tz)) => tz,
+        Some(Err((tz, err))) => {
+            // The database should only contain valid time zones
+            log::error!("Failed to load time zone '{}' from database: {}", tz, err);
+            e::Tz::UTC
+        }
+        // Not yet migrated
+        None => e::Tz::UTC,
+    }
+}
+
 pub(crate) fn registration_type_from_i16(i: i16) -> e::RegistrationType {
     use crate::core::entities::RegistrationType::*;
     match i {
@@ -74,7 +83,69 @@ mod tests {
     }
 }
 
-pub(crate) fn event_from_event_entity_and_tags(e: EventEntity, tag_rels: &[EventTag]) -> e::Event {
+pub(crate) fn load_event_recurrence<'a>(
+    rule: String,
+    overrides: impl IntoIterator<Item = &'a EventRecurrenceOverride>,
+) -> Option<e::EventRecurrence> {
+    let rule = match rule.parse() {
+        Ok(rule) => rule,
+        Err(err) => {
+            // The database should only contain valid rules
+            log::error!(
+                "Failed to load recurrence rule '{}' from database: {}",
+                rule,
+                err
+            );
+            return None;
+        }
+    };
+    let overrides = overrides
+        .into_iter()
+        .map(occurrence_override_from_row)
+        .collect();
+    Some(e::EventRecurrence { rule, overrides })
+}
+
+fn occurrence_override_from_row(row: &EventRecurrenceOverride) -> e::OccurrenceOverride {
+    let EventRecurrenceOverride {
+        recurrence_id,
+        cancelled,
+        start,
+        end,
+        title,
+        description,
+        ..
+    } = row;
+    e::OccurrenceOverride {
+        recurrence_id: NaiveDateTime::from_timestamp(*recurrence_id, 0),
+        cancelled: *cancelled != 0,
+        start: start.map(|x| NaiveDateTime::from_timestamp(x, 0)),
+        end: end.map(|x| NaiveDateTime::from_timestamp(x, 0)),
+        title: title.clone(),
+        description: description.clone(),
+    }
+}
+
+pub(crate) fn occurrence_override_into_row(
+    event_rowid: i64,
+    o: &e::OccurrenceOverride,
+) -> EventRecurrenceOverride {
+    EventRecurrenceOverride {
+        event_rowid,
+        recurrence_id: o.recurrence_id.timestamp(),
+        cancelled: if o.cancelled { 1 } else { 0 },
+        start: o.start.map(|x| x.timestamp()),
+        end: o.end.map(|x| x.timestamp()),
+        title: o.title.clone(),
+        description: o.description.clone(),
+    }
+}
+
+pub(crate) fn event_from_event_entity_and_tags(
+    e: EventEntity,
+    tag_rels: &[EventTag],
+    override_rels: &[EventRecurrenceOverride],
+) -> e::Event {
     let EventEntity {
         id,
         uid,
@@ -97,6 +168,9 @@ pub(crate) fn event_from_event_entity_and_tags(e: EventEntity, tag_rels: &[Event
         archived,
         image_url,
         image_link_url,
+        created_at,
+        recurrence,
+        time_zone,
         created_by_email,
         ..
     } = e;
@@ -149,21 +223,29 @@ pub(crate) fn event_from_event_entity_and_tags(e: EventEntity, tag_rels: &[Event
 
     let registration = registration.map(registration_type_from_i16);
 
+    let recurrence = recurrence.and_then(|rule| {
+        load_event_recurrence(rule, override_rels.iter().filter(|r| r.event_rowid == id))
+    });
+
     e::Event {
         id: uid.into(),
         title,
         description,
         start: NaiveDateTime::from_timestamp(start, 0),
         end: end.map(|x| NaiveDateTime::from_timestamp(x, 0)),
+        time_zone: load_time_zone(time_zone),
         location,
         contact,
         homepage: homepage.and_then(load_url),
         tags,
         created_by: created_by_email,
+        created_at: created_at.map(e::TimestampMs::from_inner),
         registration,
         archived: archived.map(Timestamp::from_inner),
         image_url: image_url.and_then(load_url),
         image_link_url: image_link_url.and_then(load_url),
+        recurrence,
+        recurrence_id: None,
     }
 }
 
@@ -266,30 +348,6 @@ impl From<PlaceRating> for e::Rating {
     }
 }
 
-impl From<BboxSubscriptionEntity> for e::BboxSubscription {
-    fn from(from: BboxSubscriptionEntity) -> Self {
-        let BboxSubscriptionEntity {
-            uid,
-            user_email,
-            south_west_lat,
-            south_west_lng,
-            north_east_lat,
-            north_east_lng,
-            ..
-        } = from;
-        let south_west =
-            MapPoint::try_from_lat_lng_deg(south_west_lat, south_west_lng).unwrap_or_default();
-        let north_east =
-            MapPoint::try_from_lat_lng_deg(north_east_lat, north_east_lng).unwrap_or_default();
-        let bbox = MapBbox::new(south_west, north_east);
-        Self {
-            id: uid.into(),
-            user_email,
-            bbox,
-        }
-    }
-}
-
 impl From<UserTokenEntity> for e::UserToken {
     fn from(from: UserTokenEntity) -> Self {
         Self {
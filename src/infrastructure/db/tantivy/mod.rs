@@ -1,38 +1,60 @@
 use crate::core::{
     db::{
-        EventAndPlaceIndexer, EventIndexer, IdIndex, IdIndexer, IndexQuery, IndexQueryMode,
-        IndexedPlace, Indexer, PlaceIndex, PlaceIndexer,
+        EventAndPlaceIndexer, EventIndex, EventIndexer, IdIndex, IdIndexer, IndexOrder, IndexPage,
+        IndexQuery, IndexQueryMode, IndexedEvent, IndexedPlace, Indexer, PlaceFacets, PlaceIndex,
+        PlaceIndexer, PlaceSuggestion, PlaceSuggestionKind,
     },
     entities::{
-        Address, AvgRatingValue, AvgRatings, Category, Contact, Event, Id, Place, RatingContext,
-        ReviewStatus, ReviewStatusPrimitive,
+        Address, AvgRatingValue, AvgRatings, Category, Contact, Event, Id, OpeningHoursRules,
+        PageCursor, Place, RatingContext, ReviewStatus, ReviewStatusPrimitive, SortKey,
+        TagFrequency,
     },
     util::{
-        geo::{LatCoord, LngCoord, MapPoint},
-        time::Timestamp,
+        geo::{LatCoord, LngCoord, MapBbox, MapCircle, MapMultiPolygon, MapPoint},
+        time::{Timestamp, TimestampMs},
     },
 };
 
 use anyhow::{bail, Result as Fallible};
+use chrono::NaiveDateTime;
 use failure::Fail;
 use num_traits::ToPrimitive;
+use ofdb_core::cluster::{ClusterGrid, PlaceCluster};
 use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+    fs,
     ops::Bound,
     path::Path,
     sync::{Arc, Mutex},
 };
 use strum::IntoEnumIterator;
 use tantivy::{
-    collector::TopDocs,
-    query::{BooleanQuery, Occur, Query, QueryParser, RangeQuery, TermQuery},
+    collector::{Collector, SegmentCollector, TopDocs},
+    fastfield::FastFieldReader,
+    query::{
+        BooleanQuery, FuzzyTermQuery, Occur, Query, QueryParser, RangeQuery, RegexQuery, TermQuery,
+    },
     schema::*,
-    tokenizer::{LowerCaser, RawTokenizer, RemoveLongFilter, SimpleTokenizer, TextAnalyzer},
+    store::StoreReader,
+    tokenizer::{
+        Language, LowerCaser, RawTokenizer, RemoveLongFilter, SimpleTokenizer, Stemmer,
+        TextAnalyzer, TokenStream,
+    },
     DocAddress, DocId, Document, Index, IndexReader, IndexWriter, ReloadPolicy, Score,
-    SegmentReader,
+    SegmentLocalId, SegmentReader,
 };
 
 const OVERALL_INDEX_HEAP_SIZE_IN_BYTES: usize = 50_000_000;
 
+// Needs to be incremented whenever the schema or the tokenizers
+// change. Existing indexes with a different version are discarded
+// and rebuilt from scratch.
+const INDEX_VERSION: u32 = 9;
+
+// The file that indicates that a directory contains an index
+const INDEX_META_FILE_NAME: &str = "meta.json";
+
 const PLACE_KIND_FLAG: i64 = 1;
 const EVENT_KIND_FLAG: i64 = 2;
 const ALL_KINDS_MASK: i64 = PLACE_KIND_FLAG | EVENT_KIND_FLAG;
@@ -52,8 +74,10 @@ struct IndexedFields {
     status: Field,
     lat: Field,
     lng: Field,
-    ts_min: Field, // minimum time stamp with second precision, e.g. event start
-    ts_max: Field, // maximum time stamp with second precision, e.g. event end
+    ts_min: Field,  // minimum time stamp with second precision, e.g. event start
+    ts_max: Field,  // maximum time stamp with second precision, e.g. event end
+    ts_last: Field, // last time stamp with second precision, e.g. start of the last occurrence
+    created: Field, // time stamp with millisecond precision
     title: Field,
     description: Field,
     address_street: Field,
@@ -62,6 +86,8 @@ struct IndexedFields {
     address_country: Field,
     address_state: Field,
     contact_name: Field,
+    text_de: Field, // stemmed title and description (German)
+    text_en: Field, // stemmed title and description (English)
     tag: Field,
     ratings_diversity: Field,
     ratings_fairness: Field,
@@ -70,6 +96,8 @@ struct IndexedFields {
     ratings_solidarity: Field,
     ratings_transparency: Field,
     total_rating: Field,
+    revision: Field,
+    opening_hours: Field, // only stored for filtering
 }
 
 impl IndexedFields {
@@ -97,25 +125,40 @@ impl IndexedFields {
         // Text fields that are returned as part of the search result
         // additionally need to be stored explicitly
         let stored_text_options = indexed_text_options.clone().set_stored();
+        // Stemmed text fields are only used for matching search terms
+        let stemmed_text_options = |tokenizer: &str| {
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer(tokenizer)
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            )
+        };
         let mut schema_builder = SchemaBuilder::default();
         let fields = Self {
             kind: schema_builder.add_i64_field("kind", INDEXED),
             id: schema_builder.add_text_field("id", id_options),
             status: schema_builder.add_i64_field("status", INDEXED | STORED),
-            lat: schema_builder.add_f64_field("lat", INDEXED | STORED),
-            lng: schema_builder.add_f64_field("lon", INDEXED | STORED),
-            ts_min: schema_builder.add_i64_field("ts_min", INDEXED | STORED),
+            lat: schema_builder.add_f64_field("lat", INDEXED | STORED | FAST),
+            lng: schema_builder.add_f64_field("lon", INDEXED | STORED | FAST),
+            ts_min: schema_builder.add_i64_field("ts_min", INDEXED | STORED | FAST),
             ts_max: schema_builder.add_i64_field("ts_max", INDEXED | STORED),
+            ts_last: schema_builder.add_i64_field("ts_last", INDEXED | STORED),
+            created: schema_builder.add_i64_field("created", FAST),
             title: schema_builder.add_text_field("tit", stored_text_options.clone()),
-            description: schema_builder.add_text_field("dsc", stored_text_options),
+            description: schema_builder.add_text_field("dsc", stored_text_options.clone()),
             contact_name: schema_builder.add_text_field("cnt_name", indexed_text_options.clone()),
             address_street: schema_builder
                 .add_text_field("adr_street", indexed_text_options.clone()),
-            address_city: schema_builder.add_text_field("adr_city", indexed_text_options.clone()),
+            // The city is stored for suggesting search terms
+            address_city: schema_builder.add_text_field("adr_city", stored_text_options),
             address_zip: schema_builder.add_text_field("adr_zip", indexed_text_options.clone()),
             address_country: schema_builder
                 .add_text_field("adr_country", indexed_text_options.clone()),
             address_state: schema_builder.add_text_field("adr_state", indexed_text_options),
+            text_de: schema_builder
+                .add_text_field("txt_de", stemmed_text_options(TEXT_DE_TOKENIZER)),
+            text_en: schema_builder
+                .add_text_field("txt_en", stemmed_text_options(TEXT_EN_TOKENIZER)),
             tag: schema_builder.add_text_field("tag", tag_options),
             ratings_diversity: schema_builder.add_f64_field("rat_diversity", STORED),
             ratings_fairness: schema_builder.add_f64_field("rat_fairness", STORED),
@@ -124,6 +167,8 @@ impl IndexedFields {
             ratings_solidarity: schema_builder.add_f64_field("rat_solidarity", STORED),
             ratings_transparency: schema_builder.add_f64_field("rat_transparency", STORED),
             total_rating: schema_builder.add_u64_field("rat_total", STORED | FAST),
+            revision: schema_builder.add_u64_field("rev", STORED),
+            opening_hours: schema_builder.add_text_field("oh", STORED),
         };
         (fields, schema_builder.build())
     }
@@ -203,12 +248,18 @@ impl IndexedFields {
                     place.ratings.transparency = fv.value().f64_value().into();
                 }
                 fv if fv.field() == self.total_rating => (),
-                // Address fields are currently not stored
+                fv if fv.field() == self.revision => {
+                    debug_assert!(place.revision.is_none());
+                    place.revision = Some(fv.value().u64_value().into());
+                }
+                // Address fields are currently not stored, except
+                // for the city that is only used for suggestions
                 //fv if fv.field() == self.address_street => (),
-                //fv if fv.field() == self.address_city => (),
+                fv if fv.field() == self.address_city => (),
                 //fv if fv.field() == self.address_zip => (),
                 //fv if fv.field() == self.address_country => (),
                 //fv if fv.field() == self.address_state => (),
+                fv if fv.field() == self.opening_hours => (),
                 fv => {
                     error!("Unexpected field value: {:?}", fv);
                 }
@@ -221,6 +272,79 @@ impl IndexedFields {
         }
         place
     }
+
+    fn read_indexed_event(&self, doc: &Document) -> IndexedEvent {
+        let mut lat: Option<LatCoord> = Default::default();
+        let mut lng: Option<LngCoord> = Default::default();
+        let mut event = IndexedEvent::default();
+        for field_value in doc.field_values() {
+            match field_value {
+                fv if fv.field() == self.id => {
+                    debug_assert!(event.id.is_empty());
+                    if let Some(id) = fv.value().text() {
+                        event.id = id.into();
+                    } else {
+                        error!("Invalid id value: {:?}", fv.value());
+                    }
+                }
+                fv if fv.field() == self.lat => {
+                    debug_assert!(lat.is_none());
+                    lat = Some(LatCoord::from_deg(fv.value().f64_value()));
+                }
+                fv if fv.field() == self.lng => {
+                    debug_assert!(lng.is_none());
+                    lng = Some(LngCoord::from_deg(fv.value().f64_value()));
+                }
+                fv if fv.field() == self.ts_min => {
+                    debug_assert!(event.start.is_none());
+                    event.start = Some(Timestamp::from_inner(fv.value().i64_value()));
+                }
+                fv if fv.field() == self.ts_max => {
+                    debug_assert!(event.end.is_none());
+                    event.end = Some(Timestamp::from_inner(fv.value().i64_value()));
+                }
+                fv if fv.field() == self.ts_last => {
+                    debug_assert!(event.last_start.is_none());
+                    let ts_last = fv.value().i64_value();
+                    // Infinitely recurring events never end
+                    if ts_last < i64::MAX {
+                        event.last_start = Some(Timestamp::from_inner(ts_last));
+                    }
+                }
+                fv if fv.field() == self.title => {
+                    debug_assert!(event.title.is_empty());
+                    if let Some(title) = fv.value().text() {
+                        event.title = title.into();
+                    } else {
+                        error!("Invalid title value: {:?}", fv.value());
+                    }
+                }
+                fv if fv.field() == self.description => {
+                    debug_assert!(event.description.is_empty());
+                    if let Some(description) = fv.value().text() {
+                        event.description = description.into();
+                    } else {
+                        error!("Invalid description value: {:?}", fv.value());
+                    }
+                }
+                fv if fv.field() == self.tag => {
+                    if let Some(tag) = fv.value().text() {
+                        event.tags.push(tag.into());
+                    } else {
+                        error!("Invalid tag value: {:?}", fv.value());
+                    }
+                }
+                fv if fv.field() == self.address_city => (),
+                fv => {
+                    error!("Unexpected field value: {:?}", fv);
+                }
+            }
+        }
+        if let (Some(lat), Some(lng)) = (lat, lng) {
+            event.pos = Some(MapPoint::new(lat, lng));
+        }
+        event
+    }
 }
 
 pub(crate) struct TantivyIndex {
@@ -228,28 +352,117 @@ pub(crate) struct TantivyIndex {
     index_reader: IndexReader,
     index_writer: IndexWriter,
     text_query_parser: QueryParser,
+    high_water_mark: Option<TimestampMs>,
+    generation: u64,
+}
+
+// Committed together with all changes as the payload of the index
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexCommitPayload {
+    version: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    high_water_mark: Option<i64>,
+}
+
+fn open_or_create_index_in_dir(
+    path: &Path,
+    schema: Schema,
+) -> Fallible<(Index, Option<TimestampMs>)> {
+    if path.join(INDEX_META_FILE_NAME).exists() {
+        let index = Index::open_in_dir(path).map_err(Fail::compat)?;
+        let payload = index
+            .load_metas()
+            .map_err(Fail::compat)?
+            .payload
+            .and_then(|payload| serde_json::from_str::<IndexCommitPayload>(&payload).ok());
+        match payload {
+            Some(payload) if payload.version == INDEX_VERSION => {
+                info!(
+                    "Opening full-text search index in directory: {}",
+                    path.to_string_lossy()
+                );
+                return Ok((index, payload.high_water_mark.map(TimestampMs::from_inner)));
+            }
+            _ => {
+                warn!(
+                    "Discarding outdated full-text search index in directory: {}",
+                    path.to_string_lossy()
+                );
+                drop(index);
+                fs::remove_dir_all(path)?;
+                fs::create_dir_all(path)?;
+            }
+        }
+    }
+    info!(
+        "Creating full-text search index in directory: {}",
+        path.to_string_lossy()
+    );
+    let index = Index::create_in_dir(path, schema).map_err(Fail::compat)?;
+    Ok((index, None))
 }
 
 const ID_TOKENIZER: &str = "raw";
 const TAG_TOKENIZER: &str = "tag";
 const TEXT_TOKENIZER: &str = "default";
+const TEXT_DE_TOKENIZER: &str = "text_de";
+const TEXT_EN_TOKENIZER: &str = "text_en";
 
 const MAX_TOKEN_LEN: usize = 40;
 
+fn text_analyzer() -> TextAnalyzer {
+    TextAnalyzer::from(SimpleTokenizer)
+        .filter(LowerCaser)
+        .filter(RemoveLongFilter::limit(MAX_TOKEN_LEN))
+}
+
 fn register_tokenizers(index: &Index) {
     // Predefined tokenizers
     debug_assert!(index.tokenizers().get(ID_TOKENIZER).is_some());
     debug_assert!(index.tokenizers().get(TEXT_TOKENIZER).is_some());
     // Custom tokenizer(s)
     debug_assert!(index.tokenizers().get(TAG_TOKENIZER).is_none());
+    debug_assert!(index.tokenizers().get(TEXT_DE_TOKENIZER).is_none());
+    debug_assert!(index.tokenizers().get(TEXT_EN_TOKENIZER).is_none());
     let tag_tokenizer = TextAnalyzer::from(RawTokenizer)
         .filter(LowerCaser)
         .filter(RemoveLongFilter::limit(MAX_TOKEN_LEN));
     index.tokenizers().register(TAG_TOKENIZER, tag_tokenizer);
-    let text_tokenizer = TextAnalyzer::from(SimpleTokenizer)
-        .filter(LowerCaser)
-        .filter(RemoveLongFilter::limit(MAX_TOKEN_LEN));
-    index.tokenizers().register(TEXT_TOKENIZER, text_tokenizer);
+    index.tokenizers().register(TEXT_TOKENIZER, text_analyzer());
+    let text_de_tokenizer = text_analyzer().filter(Stemmer::new(Language::German));
+    index
+        .tokenizers()
+        .register(TEXT_DE_TOKENIZER, text_de_tokenizer);
+    let text_en_tokenizer = text_analyzer().filter(Stemmer::new(Language::English));
+    index
+        .tokenizers()
+        .register(TEXT_EN_TOKENIZER, text_en_tokenizer);
+}
+
+// Search terms need to have a minimum length for fuzzy
+// matching. Otherwise too many terms would match.
+const MIN_FUZZY_TERM_LEN: usize = 4;
+
+// Longer search terms may contain more typos
+const MIN_FUZZY_TERM_LEN_WITH_TWO_TYPOS: usize = 8;
+
+fn fuzzy_term_distance(term: &str) -> Option<u8> {
+    let len = term.chars().count();
+    if len >= MIN_FUZZY_TERM_LEN_WITH_TWO_TYPOS {
+        Some(2)
+    } else if len >= MIN_FUZZY_TERM_LEN {
+        Some(1)
+    } else {
+        None
+    }
+}
+
+// Texts with explicit query syntax, e.g. mandatory (+) or
+// excluded (-) terms or phrases, are matched exactly
+fn contains_query_syntax(text: &str) -> bool {
+    text.split_whitespace()
+        .any(|word| word.starts_with('+') || word.starts_with('-'))
+        || text.contains(|c: char| matches!(c, '"' | ':' | '[' | ']' | '{' | '}' | '^' | '*'))
 }
 
 fn f64_to_u64(val: f64, min: f64, max: f64) -> u64 {
@@ -295,11 +508,705 @@ fn u64_to_avg_rating(val: u64) -> AvgRatingValue {
     .into()
 }
 
+fn boost_score_by_rating(original_score: Score, total_rating: u64) -> Score {
+    let total_rating = f64::from(u64_to_avg_rating(total_rating));
+    let boost_factor = if total_rating < f64::from(AvgRatingValue::default()) {
+        // Negative ratings result in a boost factor < 1
+        (total_rating - f64::from(AvgRatingValue::min()))
+            / (f64::from(AvgRatingValue::default()) - f64::from(AvgRatingValue::min()))
+    } else {
+        // Default rating results in a boost factor of 1
+        // Positive ratings result in a boost factor > 1
+        // The total rating is scaled by the number of different rating context
+        // variants to achieve better results by emphasizing the rating factor.
+        1.0 + f64::from(RatingContext::total_count())
+            * (total_rating - f64::from(AvgRatingValue::default()))
+    };
+    // Transform the original score by log2() to narrow the range. Otherwise
+    // the rating boost factor is not powerful enough to promote highly
+    // rated entries over entries that received a much higher score.
+    debug_assert!(original_score >= 0.0);
+    let unboosted_score = (1.0 + original_score).log2();
+    unboosted_score * (boost_factor as f32)
+}
+
 #[derive(Copy, Clone, Debug)]
 enum TopDocsMode {
     Score,
     Rating,
     ScoreBoostedByRating,
+    Distance(MapCircle),
+}
+
+// Only accepts documents that are located within the
+// region of a query and that are open at the requested
+// time. The bounding box of the region is already checked
+// by the query itself.
+#[derive(Clone)]
+struct DocumentFilter {
+    lat_field: Field,
+    lng_field: Field,
+    region: Option<Arc<MapMultiPolygon>>,
+    opening_hours_field: Field,
+    open_at: Option<NaiveDateTime>,
+}
+
+impl DocumentFilter {
+    fn segment_filter(&self, segment_reader: &SegmentReader) -> SegmentDocumentFilter {
+        let readers = self.region.as_ref().map(|region| {
+            let fast_fields = segment_reader.fast_fields();
+            (
+                fast_fields.f64(self.lat_field).unwrap(),
+                fast_fields.f64(self.lng_field).unwrap(),
+                Arc::clone(region),
+            )
+        });
+        let opening_hours = self
+            .open_at
+            .map(|open_at| (segment_reader.get_store_reader(), open_at));
+        SegmentDocumentFilter {
+            readers,
+            opening_hours_field: self.opening_hours_field,
+            opening_hours,
+        }
+    }
+}
+
+struct SegmentDocumentFilter {
+    readers: Option<(
+        FastFieldReader<f64>,
+        FastFieldReader<f64>,
+        Arc<MapMultiPolygon>,
+    )>,
+    opening_hours_field: Field,
+    opening_hours: Option<(StoreReader, NaiveDateTime)>,
+}
+
+impl SegmentDocumentFilter {
+    fn accept(&self, doc: DocId) -> bool {
+        self.accept_region(doc) && self.accept_opening_hours(doc)
+    }
+
+    fn accept_region(&self, doc: DocId) -> bool {
+        match self.readers {
+            None => true,
+            Some((ref lat_reader, ref lng_reader, ref region)) => {
+                MapPoint::try_from_lat_lng_deg(lat_reader.get(doc), lng_reader.get(doc))
+                    .map(|pos| region.contains_point(pos))
+                    .unwrap_or(false)
+            }
+        }
+    }
+
+    // Documents without valid opening hours are never open
+    fn accept_opening_hours(&self, doc: DocId) -> bool {
+        let (store_reader, open_at) = match self.opening_hours {
+            None => return true,
+            Some((ref store_reader, open_at)) => (store_reader, open_at),
+        };
+        let doc = match store_reader.get(doc) {
+            Ok(doc) => doc,
+            Err(err) => {
+                warn!("Failed to load document {}: {}", doc, err);
+                return false;
+            }
+        };
+        doc.get_first(self.opening_hours_field)
+            .and_then(Value::text)
+            .and_then(|text| text.parse::<OpeningHoursRules>().ok())
+            .map(|rules| rules.is_open_at(open_at))
+            .unwrap_or(false)
+    }
+}
+
+// Only accepts documents with a valid position that is
+// located within the region and the circle of a query
+// and that are open at the requested time.
+#[derive(Clone)]
+struct PositionFilter {
+    lat_field: Field,
+    lng_field: Field,
+    document_filter: DocumentFilter,
+    circle: Option<MapCircle>,
+}
+
+impl PositionFilter {
+    fn segment_filter(&self, segment_reader: &SegmentReader) -> SegmentPositionFilter {
+        let fast_fields = segment_reader.fast_fields();
+        SegmentPositionFilter {
+            lat_reader: fast_fields.f64(self.lat_field).unwrap(),
+            lng_reader: fast_fields.f64(self.lng_field).unwrap(),
+            document_filter: self.document_filter.segment_filter(segment_reader),
+            circle: self.circle,
+        }
+    }
+}
+
+struct SegmentPositionFilter {
+    lat_reader: FastFieldReader<f64>,
+    lng_reader: FastFieldReader<f64>,
+    document_filter: SegmentDocumentFilter,
+    circle: Option<MapCircle>,
+}
+
+impl SegmentPositionFilter {
+    fn accept(&self, doc: DocId) -> Option<MapPoint> {
+        let pos =
+            MapPoint::try_from_lat_lng_deg(self.lat_reader.get(doc), self.lng_reader.get(doc))
+                .ok()?;
+        if !self.document_filter.accept(doc) {
+            return None;
+        }
+        if let Some(circle) = self.circle {
+            circle.distance_within(pos)?;
+        }
+        Some(pos)
+    }
+}
+
+// Aggregates the positions of all matching documents into
+// the cells of a clustering grid. Documents are counted either
+// for the total or only for the breakdown of a single category.
+struct ClusterCollector {
+    zoom: u8,
+    category: Option<Id>,
+    position_filter: PositionFilter,
+}
+
+impl Collector for ClusterCollector {
+    type Fruit = ClusterGrid;
+    type Child = ClusterSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: SegmentLocalId,
+        segment_reader: &SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        Ok(ClusterSegmentCollector {
+            grid: ClusterGrid::new(self.zoom),
+            category: self.category.clone(),
+            position_filter: self.position_filter.segment_filter(segment_reader),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_grids: Vec<ClusterGrid>) -> tantivy::Result<ClusterGrid> {
+        let mut grid = ClusterGrid::new(self.zoom);
+        for segment_grid in segment_grids {
+            grid.merge(segment_grid);
+        }
+        Ok(grid)
+    }
+}
+
+struct ClusterSegmentCollector {
+    grid: ClusterGrid,
+    category: Option<Id>,
+    position_filter: SegmentPositionFilter,
+}
+
+impl SegmentCollector for ClusterSegmentCollector {
+    type Fruit = ClusterGrid;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        let pos = match self.position_filter.accept(doc) {
+            Some(pos) => pos,
+            None => return,
+        };
+        match self.category {
+            Some(ref category) => self.grid.add_category_point(pos, category),
+            None => self.grid.add_point(pos),
+        }
+    }
+
+    fn harvest(self) -> ClusterGrid {
+        self.grid
+    }
+}
+
+#[derive(Debug, Default)]
+struct FacetCounts {
+    tags: HashMap<String, u64>,
+    status: HashMap<ReviewStatusPrimitive, u64>,
+}
+
+impl FacetCounts {
+    fn merge(&mut self, other: FacetCounts) {
+        for (tag, count) in other.tags {
+            *self.tags.entry(tag).or_default() += count;
+        }
+        for (status, count) in other.status {
+            *self.status.entry(status).or_default() += count;
+        }
+    }
+}
+
+// Counts the tags and review status of all matching documents
+// by reading their stored fields.
+struct FacetCollector {
+    tag_field: Field,
+    status_field: Field,
+    position_filter: PositionFilter,
+}
+
+impl Collector for FacetCollector {
+    type Fruit = FacetCounts;
+    type Child = FacetSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: SegmentLocalId,
+        segment_reader: &SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        Ok(FacetSegmentCollector {
+            counts: Default::default(),
+            tag_field: self.tag_field,
+            status_field: self.status_field,
+            store_reader: segment_reader.get_store_reader(),
+            position_filter: self.position_filter.segment_filter(segment_reader),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_counts: Vec<FacetCounts>) -> tantivy::Result<FacetCounts> {
+        let mut counts = FacetCounts::default();
+        for segment_count in segment_counts {
+            counts.merge(segment_count);
+        }
+        Ok(counts)
+    }
+}
+
+struct FacetSegmentCollector {
+    counts: FacetCounts,
+    tag_field: Field,
+    status_field: Field,
+    store_reader: StoreReader,
+    position_filter: SegmentPositionFilter,
+}
+
+impl SegmentCollector for FacetSegmentCollector {
+    type Fruit = FacetCounts;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        if self.position_filter.accept(doc).is_none() {
+            return;
+        }
+        let doc = match self.store_reader.get(doc) {
+            Ok(doc) => doc,
+            Err(err) => {
+                warn!("Failed to load document {}: {}", doc, err);
+                return;
+            }
+        };
+        for tag in doc
+            .get_all(self.tag_field)
+            .into_iter()
+            .filter_map(Value::text)
+        {
+            *self.counts.tags.entry(tag.to_owned()).or_default() += 1;
+        }
+        if let Some(status) = doc.get_first(self.status_field) {
+            let status = status.i64_value() as ReviewStatusPrimitive;
+            *self.counts.status.entry(status).or_default() += 1;
+        }
+    }
+
+    fn harvest(self) -> FacetCounts {
+        self.counts
+    }
+}
+
+type SuggestionCounts = HashMap<(PlaceSuggestionKind, String), u64>;
+
+// Suggestions are requested on every keystroke. The stored fields
+// of at most this many matching documents are loaded per request
+// and the counts of suggestions are estimated from this sample.
+pub(crate) const MAX_SUGGESTION_DOCS: usize = 1_000;
+
+// Checks if either the whole text or one of its words
+// starts with the lowercase prefix
+fn completes_prefix(text: &str, prefix: &str) -> bool {
+    let text = text.to_lowercase();
+    text.starts_with(prefix)
+        || text
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|word| word.starts_with(prefix))
+}
+
+// A pseudo-random, but deterministic key for sampling documents
+// independent of their order in the index (SplitMix64)
+fn sample_key(segment_local_id: SegmentLocalId, doc: DocId) -> u64 {
+    let mut key =
+        ((u64::from(segment_local_id) << 32) | u64::from(doc)).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    key = (key ^ (key >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    key = (key ^ (key >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    key ^ (key >> 31)
+}
+
+// All matching documents are counted, but only the documents
+// with the lowest sample keys are selected, i.e. a uniform
+// sample of all matching documents.
+struct SuggestionSample {
+    matching_docs: usize,
+    // Sorted by sample key
+    docs: Vec<(u64, DocAddress)>,
+}
+
+struct SuggestionSampleCollector {
+    max_docs: usize,
+    position_filter: PositionFilter,
+}
+
+impl Collector for SuggestionSampleCollector {
+    type Fruit = SuggestionSample;
+    type Child = SuggestionSampleSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentLocalId,
+        segment_reader: &SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        Ok(SuggestionSampleSegmentCollector {
+            segment_local_id,
+            max_docs: self.max_docs,
+            position_filter: self.position_filter.segment_filter(segment_reader),
+            matching_docs: 0,
+            docs: BinaryHeap::with_capacity(self.max_docs),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_samples: Vec<SuggestionSample>,
+    ) -> tantivy::Result<SuggestionSample> {
+        let mut matching_docs = 0;
+        let mut docs = Vec::with_capacity(self.max_docs);
+        for segment_sample in segment_samples {
+            matching_docs += segment_sample.matching_docs;
+            docs.extend(segment_sample.docs);
+        }
+        docs.sort_unstable_by_key(|&(key, _)| key);
+        docs.truncate(self.max_docs);
+        Ok(SuggestionSample {
+            matching_docs,
+            docs,
+        })
+    }
+}
+
+struct SuggestionSampleSegmentCollector {
+    segment_local_id: SegmentLocalId,
+    max_docs: usize,
+    position_filter: SegmentPositionFilter,
+    matching_docs: usize,
+    // The greatest sample key on top
+    docs: BinaryHeap<(u64, DocId)>,
+}
+
+impl SegmentCollector for SuggestionSampleSegmentCollector {
+    type Fruit = SuggestionSample;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        if self.position_filter.accept(doc).is_none() {
+            return;
+        }
+        self.matching_docs += 1;
+        let key = sample_key(self.segment_local_id, doc);
+        if self.docs.len() < self.max_docs {
+            self.docs.push((key, doc));
+        } else if self
+            .docs
+            .peek()
+            .map_or(false, |&(max_key, _)| key < max_key)
+        {
+            self.docs.pop();
+            self.docs.push((key, doc));
+        }
+    }
+
+    fn harvest(self) -> SuggestionSample {
+        let segment_local_id = self.segment_local_id;
+        let mut docs: Vec<_> = self
+            .docs
+            .into_iter()
+            .map(|(key, doc)| (key, DocAddress(segment_local_id, doc)))
+            .collect();
+        docs.sort_unstable_by_key(|&(key, _)| key);
+        SuggestionSample {
+            matching_docs: self.matching_docs,
+            docs,
+        }
+    }
+}
+
+// Counts the titles, tags and cities of documents
+// that complete the prefix.
+struct SuggestionCounter<'a> {
+    fields: &'a IndexedFields,
+    prefix: &'a str,
+    counts: SuggestionCounts,
+}
+
+impl<'a> SuggestionCounter<'a> {
+    fn count(&mut self, kind: PlaceSuggestionKind, text: &str) {
+        if completes_prefix(text, self.prefix) {
+            *self.counts.entry((kind, text.to_owned())).or_default() += 1;
+        }
+    }
+
+    fn count_doc(&mut self, doc: &Document) {
+        if let Some(title) = doc.get_first(self.fields.title).and_then(Value::text) {
+            self.count(PlaceSuggestionKind::Title, title);
+        }
+        for tag in doc
+            .get_all(self.fields.tag)
+            .into_iter()
+            .filter_map(Value::text)
+        {
+            // Categories are not suggested
+            if tag == Category::TAG_NON_PROFIT || tag == Category::TAG_COMMERCIAL {
+                continue;
+            }
+            self.count(PlaceSuggestionKind::Tag, tag);
+        }
+        if let Some(city) = doc
+            .get_first(self.fields.address_city)
+            .and_then(Value::text)
+        {
+            self.count(PlaceSuggestionKind::City, city);
+        }
+    }
+}
+
+// The position of a document within the order of paged results
+struct PageEntry {
+    key: SortKey,
+    id: String,
+    doc_addr: DocAddress,
+}
+
+fn cmp_sort_keys(order: IndexOrder, lhs: &SortKey, rhs: &SortKey) -> Ordering {
+    let ordering = match (lhs, rhs) {
+        (SortKey::Int(lhs), SortKey::Int(rhs)) => lhs.cmp(rhs),
+        (SortKey::Float(lhs), SortKey::Float(rhs)) | (SortKey::Score(lhs), SortKey::Score(rhs)) => {
+            lhs.partial_cmp(rhs).unwrap_or(Ordering::Equal)
+        }
+        (SortKey::Text(lhs), SortKey::Text(rhs)) => lhs.cmp(rhs),
+        // Cursors with keys that don't match the order
+        // are rejected in advance
+        _ => Ordering::Equal,
+    };
+    match order {
+        IndexOrder::Relevance | IndexOrder::Rating | IndexOrder::Created => ordering.reverse(),
+        IndexOrder::Title | IndexOrder::Distance | IndexOrder::Start => ordering,
+    }
+}
+
+// Documents with equal keys are ordered by their id
+fn cmp_page_positions(
+    order: IndexOrder,
+    (lhs_key, lhs_id): (&SortKey, &str),
+    (rhs_key, rhs_id): (&SortKey, &str),
+) -> Ordering {
+    cmp_sort_keys(order, lhs_key, rhs_key).then_with(|| lhs_id.cmp(rhs_id))
+}
+
+fn sort_and_truncate_page(order: IndexOrder, entries: &mut Vec<PageEntry>, capacity: usize) {
+    entries.sort_unstable_by(|lhs, rhs| {
+        cmp_page_positions(order, (&lhs.key, &lhs.id), (&rhs.key, &rhs.id))
+    });
+    entries.truncate(capacity);
+}
+
+// Collects the documents that follow the cursor in the requested
+// order. Sort keys are read from fast fields if possible. Only the
+// id and the title need to be read from the stored documents.
+struct PageCollector {
+    order: IndexOrder,
+    cursor: Option<PageCursor>,
+    capacity: usize,
+    boost_by_rating: bool,
+    id_field: Field,
+    title_field: Field,
+    lat_field: Field,
+    lng_field: Field,
+    total_rating_field: Field,
+    created_field: Field,
+    ts_min_field: Field,
+    document_filter: DocumentFilter,
+    circle: Option<MapCircle>,
+}
+
+impl Collector for PageCollector {
+    type Fruit = Vec<PageEntry>;
+    type Child = PageSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentLocalId,
+        segment_reader: &SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        let fast_fields = segment_reader.fast_fields();
+        Ok(PageSegmentCollector {
+            order: self.order,
+            cursor: self.cursor.clone(),
+            capacity: self.capacity,
+            boost_by_rating: self.boost_by_rating,
+            segment_local_id,
+            id_field: self.id_field,
+            title_field: self.title_field,
+            lat_reader: fast_fields.f64(self.lat_field).unwrap(),
+            lng_reader: fast_fields.f64(self.lng_field).unwrap(),
+            total_rating_reader: fast_fields.u64(self.total_rating_field).unwrap(),
+            created_reader: fast_fields.i64(self.created_field).unwrap(),
+            ts_min_reader: fast_fields.i64(self.ts_min_field).unwrap(),
+            store_reader: segment_reader.get_store_reader(),
+            document_filter: self.document_filter.segment_filter(segment_reader),
+            circle: self.circle,
+            entries: Vec::with_capacity(self.capacity),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.order == IndexOrder::Relevance
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_entries: Vec<Vec<PageEntry>>,
+    ) -> tantivy::Result<Vec<PageEntry>> {
+        let mut entries: Vec<_> = segment_entries.into_iter().flatten().collect();
+        sort_and_truncate_page(self.order, &mut entries, self.capacity);
+        Ok(entries)
+    }
+}
+
+struct PageSegmentCollector {
+    order: IndexOrder,
+    cursor: Option<PageCursor>,
+    capacity: usize,
+    boost_by_rating: bool,
+    segment_local_id: SegmentLocalId,
+    id_field: Field,
+    title_field: Field,
+    lat_reader: FastFieldReader<f64>,
+    lng_reader: FastFieldReader<f64>,
+    total_rating_reader: FastFieldReader<u64>,
+    created_reader: FastFieldReader<i64>,
+    ts_min_reader: FastFieldReader<i64>,
+    store_reader: StoreReader,
+    document_filter: SegmentDocumentFilter,
+    circle: Option<MapCircle>,
+    entries: Vec<PageEntry>,
+}
+
+impl PageSegmentCollector {
+    // The sort key if it is available without loading the document
+    fn fast_sort_key(&self, doc: DocId, score: Score) -> Option<Option<SortKey>> {
+        let distance = match self.circle {
+            Some(circle) => {
+                let pos = MapPoint::try_from_lat_lng_deg(
+                    self.lat_reader.get(doc),
+                    self.lng_reader.get(doc),
+                )
+                .ok()?;
+                Some(circle.distance_within(pos)?)
+            }
+            None => None,
+        };
+        let key = match self.order {
+            IndexOrder::Relevance => {
+                let score = if self.boost_by_rating {
+                    boost_score_by_rating(score, self.total_rating_reader.get(doc))
+                } else {
+                    score
+                };
+                Some(SortKey::Score(score.into()))
+            }
+            IndexOrder::Rating => Some(SortKey::Float(
+                u64_to_avg_rating(self.total_rating_reader.get(doc)).into(),
+            )),
+            IndexOrder::Created => Some(SortKey::Int(self.created_reader.get(doc))),
+            IndexOrder::Start => Some(SortKey::Int(self.ts_min_reader.get(doc))),
+            IndexOrder::Distance => Some(SortKey::Float(distance?.to_meters())),
+            IndexOrder::Title => None,
+        };
+        Some(key)
+    }
+}
+
+impl SegmentCollector for PageSegmentCollector {
+    type Fruit = Vec<PageEntry>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        if !self.document_filter.accept(doc) {
+            return;
+        }
+        let key = match self.fast_sort_key(doc, score) {
+            Some(key) => key,
+            // Outside of the circle
+            None => return,
+        };
+        // Skip documents before the cursor without loading them
+        if let (Some(key), Some(cursor)) = (&key, &self.cursor) {
+            if cmp_sort_keys(self.order, key, &cursor.key) == Ordering::Less {
+                return;
+            }
+        }
+        let stored_doc = match self.store_reader.get(doc) {
+            Ok(stored_doc) => stored_doc,
+            Err(err) => {
+                warn!("Failed to load document {}: {}", doc, err);
+                return;
+            }
+        };
+        let id = match stored_doc.get_first(self.id_field).and_then(Value::text) {
+            Some(id) => id.to_owned(),
+            None => {
+                error!("Document {} has no id field value", doc);
+                return;
+            }
+        };
+        let key = key.unwrap_or_else(|| {
+            let title = stored_doc
+                .get_first(self.title_field)
+                .and_then(Value::text)
+                .unwrap_or_default();
+            SortKey::Text(title.to_lowercase())
+        });
+        if let Some(ref cursor) = self.cursor {
+            let position =
+                cmp_page_positions(self.order, (&key, &id), (&cursor.key, cursor.id.as_str()));
+            if position != Ordering::Greater {
+                return;
+            }
+        }
+        self.entries.push(PageEntry {
+            key,
+            id,
+            doc_addr: DocAddress(self.segment_local_id, doc),
+        });
+        // Limit the memory consumption
+        if self.entries.len() >= 2 * self.capacity {
+            sort_and_truncate_page(self.order, &mut self.entries, self.capacity);
+        }
+    }
+
+    fn harvest(mut self) -> Vec<PageEntry> {
+        sort_and_truncate_page(self.order, &mut self.entries, self.capacity);
+        self.entries
+    }
 }
 
 impl TantivyIndex {
@@ -312,16 +1219,11 @@ impl TantivyIndex {
     pub fn create<P: AsRef<Path>>(path: Option<P>) -> Fallible<Self> {
         let (fields, schema) = IndexedFields::build_schema();
 
-        // TODO: Open index from existing directory
-        let index = if let Some(path) = path {
-            info!(
-                "Creating full-text search index in directory: {}",
-                path.as_ref().to_string_lossy()
-            );
-            Index::create_in_dir(path, schema).map_err(Fail::compat)?
+        let (index, high_water_mark) = if let Some(path) = path {
+            open_or_create_index_in_dir(path.as_ref(), schema)?
         } else {
             warn!("Creating full-text search index in RAM");
-            Index::create_in_ram(schema)
+            (Index::create_in_ram(schema), None)
         };
 
         register_tokenizers(&index);
@@ -349,6 +1251,8 @@ impl TantivyIndex {
                 fields.address_country,
                 fields.address_state,
                 fields.contact_name,
+                fields.text_de,
+                fields.text_en,
             ],
         );
         Ok(Self {
@@ -356,9 +1260,106 @@ impl TantivyIndex {
             index_reader,
             index_writer,
             text_query_parser,
+            high_water_mark,
+            generation: 0,
         })
     }
 
+    fn push_include_bbox_queries(
+        &self,
+        bbox: &MapBbox,
+        sub_queries: &mut Vec<(Occur, Box<dyn Query>)>,
+    ) {
+        debug_assert!(bbox.is_valid());
+        debug_assert!(!bbox.is_empty());
+        let lat_query = RangeQuery::new_f64_bounds(
+            self.fields.lat,
+            Bound::Included(bbox.southwest().lat().to_deg()),
+            Bound::Included(bbox.northeast().lat().to_deg()),
+        );
+        // Latitude query: Always inclusive
+        sub_queries.push((Occur::Must, Box::new(lat_query)));
+        // Longitude query: Either inclusive or exclusive (wrap around)
+        if bbox.southwest().lng() <= bbox.northeast().lng() {
+            // regular (inclusive)
+            let lng_query = RangeQuery::new_f64_bounds(
+                self.fields.lng,
+                Bound::Included(bbox.southwest().lng().to_deg()),
+                Bound::Included(bbox.northeast().lng().to_deg()),
+            );
+            sub_queries.push((Occur::Must, Box::new(lng_query)));
+        } else {
+            // inverse (exclusive)
+            let lng_query = RangeQuery::new_f64_bounds(
+                self.fields.lng,
+                Bound::Excluded(bbox.northeast().lng().to_deg()),
+                Bound::Excluded(bbox.southwest().lng().to_deg()),
+            );
+            sub_queries.push((Occur::MustNot, Box::new(lng_query)));
+        }
+    }
+
+    // Preselects all documents with a title, tag or city that
+    // might complete the prefix. Only the first word of the
+    // prefix is considered here, the whole prefix is checked
+    // when collecting the suggestions.
+    fn build_prefix_query(&self, prefix: &str) -> Fallible<Option<BooleanQuery>> {
+        let text_analyzer = text_analyzer();
+        let mut token_stream = text_analyzer.token_stream(prefix);
+        if !token_stream.advance() {
+            return Ok(None);
+        }
+        // Tokens only contain alphanumeric characters that
+        // don't need to be escaped
+        let first_word = &token_stream.token().text;
+        let word_pattern = format!("{}.*", first_word);
+        // Tags are not tokenized and may contain multiple words
+        let tag_pattern = format!(".*{}.*", first_word);
+        let title_query =
+            RegexQuery::from_pattern(&word_pattern, self.fields.title).map_err(Fail::compat)?;
+        let city_query = RegexQuery::from_pattern(&word_pattern, self.fields.address_city)
+            .map_err(Fail::compat)?;
+        let tag_query =
+            RegexQuery::from_pattern(&tag_pattern, self.fields.tag).map_err(Fail::compat)?;
+        Ok(Some(BooleanQuery::from(vec![
+            (Occur::Should, Box::new(title_query) as Box<dyn Query>),
+            (Occur::Should, Box::new(city_query)),
+            (Occur::Should, Box::new(tag_query)),
+        ])))
+    }
+
+    // Tolerates typos in the title, city, and contact name
+    fn build_fuzzy_text_query(&self, text: &str) -> Option<BooleanQuery> {
+        if contains_query_syntax(text) {
+            return None;
+        }
+        let fuzzy_fields = [
+            self.fields.title,
+            self.fields.address_city,
+            self.fields.contact_name,
+        ];
+        let mut fuzzy_queries: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        let text_analyzer = text_analyzer();
+        let mut token_stream = text_analyzer.token_stream(text);
+        while token_stream.advance() {
+            let term_text = &token_stream.token().text;
+            let distance = match fuzzy_term_distance(term_text) {
+                Some(distance) => distance,
+                None => continue,
+            };
+            for &field in &fuzzy_fields {
+                let term = Term::from_field_text(field, term_text);
+                let fuzzy_query = FuzzyTermQuery::new(term, distance, true);
+                fuzzy_queries.push((Occur::Should, Box::new(fuzzy_query)));
+            }
+        }
+        if fuzzy_queries.is_empty() {
+            None
+        } else {
+            Some(fuzzy_queries.into())
+        }
+    }
+
     fn build_query(
         &self,
         query_mode: IndexQueryMode,
@@ -459,33 +1460,29 @@ impl TantivyIndex {
         // Bbox (include)
         if let Some(ref bbox) = query.include_bbox {
             debug!("Query bbox (include): {}", bbox);
-            debug_assert!(bbox.is_valid());
-            debug_assert!(!bbox.is_empty());
-            let lat_query = RangeQuery::new_f64_bounds(
-                self.fields.lat,
-                Bound::Included(bbox.southwest().lat().to_deg()),
-                Bound::Included(bbox.northeast().lat().to_deg()),
+            self.push_include_bbox_queries(bbox, &mut sub_queries);
+        }
+
+        // Circle (include)
+        if let Some(ref circle) = query.near {
+            debug!(
+                "Query circle (include): {} ({} m)",
+                circle.center(),
+                circle.radius().to_meters()
             );
-            // Latitude query: Always inclusive
-            sub_queries.push((Occur::Must, Box::new(lat_query)));
-            // Longitude query: Either inclusive or exclusive (wrap around)
-            if bbox.southwest().lng() <= bbox.northeast().lng() {
-                // regular (inclusive)
-                let lng_query = RangeQuery::new_f64_bounds(
-                    self.fields.lng,
-                    Bound::Included(bbox.southwest().lng().to_deg()),
-                    Bound::Included(bbox.northeast().lng().to_deg()),
-                );
-                sub_queries.push((Occur::Must, Box::new(lng_query)));
-            } else {
-                // inverse (exclusive)
-                let lng_query = RangeQuery::new_f64_bounds(
-                    self.fields.lng,
-                    Bound::Excluded(bbox.northeast().lng().to_deg()),
-                    Bound::Excluded(bbox.southwest().lng().to_deg()),
-                );
-                sub_queries.push((Occur::MustNot, Box::new(lng_query)));
-            }
+            debug_assert!(circle.is_valid());
+            // Prefilter by the bounding box, the actual distance
+            // is checked while collecting the results
+            self.push_include_bbox_queries(&circle.bounding_bbox(), &mut sub_queries);
+        }
+
+        // Polygon (include)
+        if let Some(ref polygon) = query.include_polygon {
+            debug!("Query polygon (include): {}", polygon);
+            debug_assert!(polygon.is_valid());
+            // Prefilter by the bounding box, the actual region
+            // is checked while collecting the results
+            self.push_include_bbox_queries(&polygon.bounding_bbox(), &mut sub_queries);
         }
 
         // Inverse Bbox (exclude)
@@ -606,10 +1603,21 @@ impl TantivyIndex {
             let text = text.to_lowercase();
             match self.text_query_parser.parse_query(&text) {
                 Ok(text_query) => {
+                    let text_query: Box<dyn Query> =
+                        if let Some(fuzzy_query) = self.build_fuzzy_text_query(&text) {
+                            // Exact matches are scored higher than fuzzy
+                            // matches, because they match both queries
+                            Box::new(BooleanQuery::from(vec![
+                                (Occur::Should, text_query),
+                                (Occur::Should, Box::new(fuzzy_query) as Box<dyn Query>),
+                            ]))
+                        } else {
+                            text_query
+                        };
                     if query.hash_tags.is_empty() && query.text_tags.is_empty() {
-                        sub_queries.push((Occur::Must, Box::new(text_query)));
+                        sub_queries.push((Occur::Must, text_query));
                     } else {
-                        text_and_tags_queries.push((Occur::Should, Box::new(text_query)));
+                        text_and_tags_queries.push((Occur::Should, text_query));
                     }
                 }
                 Err(err) => {
@@ -655,6 +1663,28 @@ impl TantivyIndex {
             sub_queries.push((Occur::Must, Box::new(ts_max_query)));
         }
 
+        // ts_last
+        if let Some(ts_last_lb) = query.ts_last_lb {
+            let ts_last_query = RangeQuery::new_i64_bounds(
+                self.fields.ts_last,
+                Bound::Included(ts_last_lb.into_inner()),
+                Bound::Unbounded,
+            );
+            sub_queries.push((Occur::Must, Box::new(ts_last_query)));
+        }
+
+        // Entries within a circle are ordered by their distance
+        // from the center instead of their score or rating
+        if let Some(circle) = query.near {
+            if !text_and_tags_queries.is_empty() {
+                sub_queries.push((
+                    Occur::Must,
+                    Box::new(BooleanQuery::from(text_and_tags_queries)),
+                ));
+            }
+            return (sub_queries.into(), TopDocsMode::Distance(circle));
+        }
+
         // Boosting the score by the rating does only make sense if the
         // query actually contains search terms or tags. Otherwise the
         // results are sorted only by their rating, e.g. if the query
@@ -678,6 +1708,55 @@ impl TantivyIndex {
         }
     }
 
+    fn all_ids_of_kind(&self, kind_flag: i64) -> Fallible<Vec<Id>> {
+        let kind_term = Term::from_field_i64(self.fields.kind, kind_flag);
+        let kind_query = TermQuery::new(kind_term, IndexRecordOption::Basic);
+        let collector = AllIdsCollector {
+            id_field: self.fields.id,
+        };
+        Ok(self
+            .index_reader
+            .searcher()
+            .search(&kind_query, &collector)
+            .map_err(Fail::compat)?)
+    }
+
+    fn document_filter(&self, query: &IndexQuery) -> DocumentFilter {
+        DocumentFilter {
+            lat_field: self.fields.lat,
+            lng_field: self.fields.lng,
+            region: query.include_polygon.clone().map(Arc::new),
+            opening_hours_field: self.fields.opening_hours,
+            open_at: query.open_at,
+        }
+    }
+
+    // Events are never aggregated. The position filter
+    // considers both the region and the circle of the query.
+    fn build_places_query(&self, query: &IndexQuery) -> (BooleanQuery, PositionFilter) {
+        let (search_query, top_docs_mode) = self.build_query(IndexQueryMode::WithoutRating, query);
+        let circle = match top_docs_mode {
+            TopDocsMode::Distance(circle) => Some(circle),
+            _ => None,
+        };
+        let place_kind_term = Term::from_field_i64(self.fields.kind, PLACE_KIND_FLAG);
+        let places_query = BooleanQuery::from(vec![
+            (Occur::Must, Box::new(search_query) as Box<dyn Query>),
+            (
+                Occur::Must,
+                Box::new(TermQuery::new(place_kind_term, IndexRecordOption::Basic))
+                    as Box<dyn Query>,
+            ),
+        ]);
+        let position_filter = PositionFilter {
+            lat_field: self.fields.lat,
+            lng_field: self.fields.lng,
+            document_filter: self.document_filter(query),
+            circle,
+        };
+        (places_query, position_filter)
+    }
+
     #[allow(clippy::absurd_extreme_comparisons)]
     fn query_documents<D>(
         &self,
@@ -695,14 +1774,32 @@ impl TantivyIndex {
 
         let (search_query, top_docs_mode) = self.build_query(query_mode, query);
         let searcher = self.index_reader.searcher();
+        // Documents that are outside of the region or closed receive the
+        // lowest possible score and are skipped when collecting the results
+        let document_filter = self.document_filter(query);
         // TODO: Try to combine redundant code from different search strategies
         match top_docs_mode {
             TopDocsMode::Score => {
-                let collector = TopDocs::with_limit(limit);
+                let collector = TopDocs::with_limit(limit).tweak_score(
+                    move |segment_reader: &SegmentReader| {
+                        let segment_filter = document_filter.segment_filter(segment_reader);
+                        move |doc: DocId, original_score: Score| {
+                            if segment_filter.accept(doc) {
+                                original_score
+                            } else {
+                                std::f32::NEG_INFINITY
+                            }
+                        }
+                    },
+                );
                 let top_docs = searcher
                     .search(&search_query, &collector)
                     .map_err(Fail::compat)?;
-                for (_, doc_addr) in top_docs {
+                for (score, doc_addr) in top_docs {
+                    if score.is_infinite() {
+                        // All remaining documents are outside of the region
+                        break;
+                    }
                     match searcher.doc(doc_addr) {
                         Ok(doc) => {
                             doc_collector.collect_document(doc_addr, doc);
@@ -715,15 +1812,36 @@ impl TantivyIndex {
                 Ok(doc_collector)
             }
             TopDocsMode::Rating => {
-                let collector =
-                    TopDocs::with_limit(limit).order_by_u64_field(self.fields.total_rating);
+                let collector = {
+                    let total_rating_field = self.fields.total_rating;
+                    TopDocs::with_limit(limit).custom_score(
+                        move |segment_reader: &SegmentReader| {
+                            let total_rating_reader = segment_reader
+                                .fast_fields()
+                                .u64(total_rating_field)
+                                .unwrap();
+                            let segment_filter = document_filter.segment_filter(segment_reader);
+                            move |doc: DocId| {
+                                if segment_filter.accept(doc) {
+                                    Some(total_rating_reader.get(doc))
+                                } else {
+                                    None
+                                }
+                            }
+                        },
+                    )
+                };
                 searcher
                     .search(&search_query, &collector)
                     .map_err(Fail::compat)?;
                 let top_docs = searcher
                     .search(&search_query, &collector)
                     .map_err(Fail::compat)?;
-                for (_, doc_addr) in top_docs {
+                for (total_rating, doc_addr) in top_docs {
+                    if total_rating.is_none() {
+                        // All remaining documents are outside of the region
+                        break;
+                    }
                     match searcher.doc(doc_addr) {
                         Ok(doc) => {
                             doc_collector.collect_document(doc_addr, doc);
@@ -743,37 +1861,67 @@ impl TantivyIndex {
                             .fast_fields()
                             .u64(total_rating_field)
                             .unwrap();
+                        let segment_filter = document_filter.segment_filter(segment_reader);
 
                         move |doc: DocId, original_score: Score| {
-                            let total_rating =
-                                f64::from(u64_to_avg_rating(total_rating_reader.get(doc)));
-                            let boost_factor =
-                                if total_rating < f64::from(AvgRatingValue::default()) {
-                                    // Negative ratings result in a boost factor < 1
-                                    (total_rating - f64::from(AvgRatingValue::min()))
-                                        / (f64::from(AvgRatingValue::default())
-                                            - f64::from(AvgRatingValue::min()))
-                                } else {
-                                    // Default rating results in a boost factor of 1
-                                    // Positive ratings result in a boost factor > 1
-                                    // The total rating is scaled by the number of different rating context
-                                    // variants to achieve better results by emphasizing the rating factor.
-                                    1.0 + f64::from(RatingContext::total_count())
-                                        * (total_rating - f64::from(AvgRatingValue::default()))
-                                };
-                            // Transform the original score by log2() to narrow the range. Otherwise
-                            // the rating boost factor is not powerful enough to promote highly
-                            // rated entries over entries that received a much higher score.
-                            debug_assert!(original_score >= 0.0);
-                            let unboosted_score = (1.0 + original_score).log2();
-                            unboosted_score * (boost_factor as f32)
+                            if !segment_filter.accept(doc) {
+                                return std::f32::NEG_INFINITY;
+                            }
+                            boost_score_by_rating(original_score, total_rating_reader.get(doc))
                         }
                     })
                 };
                 let top_docs = searcher
                     .search(&search_query, &collector)
                     .map_err(Fail::compat)?;
-                for (_, doc_addr) in top_docs {
+                for (score, doc_addr) in top_docs {
+                    if score.is_infinite() {
+                        // All remaining documents are outside of the region
+                        break;
+                    }
+                    match searcher.doc(doc_addr) {
+                        Ok(doc) => {
+                            doc_collector.collect_document(doc_addr, doc);
+                        }
+                        Err(err) => {
+                            warn!("Failed to load document {:?}: {}", doc_addr, err);
+                        }
+                    }
+                }
+                Ok(doc_collector)
+            }
+            TopDocsMode::Distance(circle) => {
+                let collector = {
+                    let lat_field = self.fields.lat;
+                    let lng_field = self.fields.lng;
+                    TopDocs::with_limit(limit).custom_score(
+                        move |segment_reader: &SegmentReader| {
+                            let lat_reader = segment_reader.fast_fields().f64(lat_field).unwrap();
+                            let lng_reader = segment_reader.fast_fields().f64(lng_field).unwrap();
+                            let segment_filter = document_filter.segment_filter(segment_reader);
+                            move |doc: DocId| {
+                                // The nearest entries receive the highest score
+                                MapPoint::try_from_lat_lng_deg(
+                                    lat_reader.get(doc),
+                                    lng_reader.get(doc),
+                                )
+                                .ok()
+                                .filter(|_| segment_filter.accept(doc))
+                                .and_then(|pos| circle.distance_within(pos))
+                                .map(|distance| -distance.to_meters())
+                                .unwrap_or(std::f64::NEG_INFINITY)
+                            }
+                        },
+                    )
+                };
+                let top_docs = searcher
+                    .search(&search_query, &collector)
+                    .map_err(Fail::compat)?;
+                for (score, doc_addr) in top_docs {
+                    if score.is_infinite() {
+                        // All remaining documents are outside of the circle or region
+                        break;
+                    }
                     match searcher.doc(doc_addr) {
                         Ok(doc) => {
                             doc_collector.collect_document(doc_addr, doc);
@@ -787,6 +1935,75 @@ impl TantivyIndex {
             }
         }
     }
+    fn query_page<D>(
+        &self,
+        query_mode: IndexQueryMode,
+        query: &IndexQuery,
+        order: IndexOrder,
+        cursor: Option<&PageCursor>,
+        limit: usize,
+        mut doc_collector: D,
+    ) -> Fallible<(D, Option<PageCursor>)>
+    where
+        D: DocumentCollector,
+    {
+        if limit == 0 {
+            bail!("Invalid limit: {}", limit);
+        }
+        if let Some(cursor) = cursor {
+            if !order.accepts_key(&cursor.key) {
+                bail!("Invalid cursor for order {:?}: {:?}", order, cursor);
+            }
+        }
+        let (search_query, top_docs_mode) = self.build_query(query_mode, query);
+        let circle = match top_docs_mode {
+            TopDocsMode::Distance(circle) => Some(circle),
+            _ => None,
+        };
+        if order == IndexOrder::Distance && circle.is_none() {
+            bail!("Ordering by distance requires a circle");
+        }
+        let collector = PageCollector {
+            order,
+            cursor: cursor.cloned(),
+            // One additional entry to find out if there is a next page
+            capacity: limit + 1,
+            boost_by_rating: matches!(query_mode, IndexQueryMode::WithRating),
+            id_field: self.fields.id,
+            title_field: self.fields.title,
+            lat_field: self.fields.lat,
+            lng_field: self.fields.lng,
+            total_rating_field: self.fields.total_rating,
+            created_field: self.fields.created,
+            ts_min_field: self.fields.ts_min,
+            document_filter: self.document_filter(query),
+            circle,
+        };
+        let searcher = self.index_reader.searcher();
+        let mut entries = searcher
+            .search(&search_query, &collector)
+            .map_err(Fail::compat)?;
+        let next = if entries.len() > limit {
+            entries.truncate(limit);
+            entries.last().map(|entry| PageCursor {
+                key: entry.key.clone(),
+                id: entry.id.as_str().into(),
+            })
+        } else {
+            None
+        };
+        for PageEntry { doc_addr, .. } in entries {
+            match searcher.doc(doc_addr) {
+                Ok(doc) => {
+                    doc_collector.collect_document(doc_addr, doc);
+                }
+                Err(err) => {
+                    warn!("Failed to load document {:?}: {}", doc_addr, err);
+                }
+            }
+        }
+        Ok((doc_collector, next))
+    }
 }
 
 trait DocumentCollector {
@@ -853,6 +2070,93 @@ impl<'a> DocumentCollector for IndexedPlaceCollector<'a> {
     }
 }
 
+struct IndexedEventCollector<'a> {
+    fields: &'a IndexedFields,
+    collected_events: Vec<IndexedEvent>,
+}
+
+impl<'a> IndexedEventCollector<'a> {
+    fn with_capacity(fields: &'a IndexedFields, capacity: usize) -> Self {
+        Self {
+            fields,
+            collected_events: Vec::with_capacity(capacity),
+        }
+    }
+}
+
+impl<'a> From<IndexedEventCollector<'a>> for Vec<IndexedEvent> {
+    fn from(from: IndexedEventCollector<'a>) -> Self {
+        from.collected_events
+    }
+}
+
+impl<'a> DocumentCollector for IndexedEventCollector<'a> {
+    fn collect_document(&mut self, _doc_addr: DocAddress, doc: Document) {
+        self.collected_events
+            .push(self.fields.read_indexed_event(&doc));
+    }
+}
+
+// Collects the ids of all matching documents without
+// any limit by reading their stored fields.
+struct AllIdsCollector {
+    id_field: Field,
+}
+
+impl Collector for AllIdsCollector {
+    type Fruit = Vec<Id>;
+    type Child = AllIdsSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: SegmentLocalId,
+        segment_reader: &SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        Ok(AllIdsSegmentCollector {
+            ids: Vec::new(),
+            id_field: self.id_field,
+            store_reader: segment_reader.get_store_reader(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_ids: Vec<Vec<Id>>) -> tantivy::Result<Vec<Id>> {
+        Ok(segment_ids.into_iter().flatten().collect())
+    }
+}
+
+struct AllIdsSegmentCollector {
+    ids: Vec<Id>,
+    id_field: Field,
+    store_reader: StoreReader,
+}
+
+impl SegmentCollector for AllIdsSegmentCollector {
+    type Fruit = Vec<Id>;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        let doc = match self.store_reader.get(doc) {
+            Ok(doc) => doc,
+            Err(err) => {
+                warn!("Failed to load document {}: {}", doc, err);
+                return;
+            }
+        };
+        if let Some(id) = doc.get_first(self.id_field).and_then(Value::text) {
+            self.ids.push(Id::from(id));
+        } else {
+            error!("Document has no id field ({:?}) value", self.id_field);
+        }
+    }
+
+    fn harvest(self) -> Vec<Id> {
+        self.ids
+    }
+}
+
 impl IdIndex for TantivyIndex {
     fn query_ids(
         &self,
@@ -864,14 +2168,60 @@ impl IdIndex for TantivyIndex {
         self.query_documents(query_mode, query, limit, collector)
             .map(Into::into)
     }
+
+    fn query_ids_page(
+        &self,
+        query_mode: IndexQueryMode,
+        query: &IndexQuery,
+        order: IndexOrder,
+        cursor: Option<&PageCursor>,
+        limit: usize,
+    ) -> Fallible<IndexPage<Id>> {
+        let collector = IdCollector::with_capacity(self.fields.id, limit);
+        let (collector, next) =
+            self.query_page(query_mode, query, order, cursor, limit, collector)?;
+        Ok(IndexPage {
+            items: collector.into(),
+            next,
+        })
+    }
 }
 
 impl Indexer for TantivyIndex {
     fn flush_index(&mut self) -> Fallible<()> {
-        self.index_writer.commit().map_err(Fail::compat)?;
+        // The payload is replaced on every commit and needs
+        // to be repeated to preserve the high-water mark.
+        let payload = serde_json::to_string(&IndexCommitPayload {
+            version: INDEX_VERSION,
+            high_water_mark: self.high_water_mark.map(TimestampMs::into_inner),
+        })?;
+        let mut commit = self.index_writer.prepare_commit().map_err(Fail::compat)?;
+        commit.set_payload(&payload);
+        commit.commit().map_err(Fail::compat)?;
         // Manually reload the reader to ensure that all committed changes
         // become visible immediately.
         self.index_reader.reload().map_err(Fail::compat)?;
+        self.generation += 1;
+        Ok(())
+    }
+
+    fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    fn high_water_mark(&self) -> Option<TimestampMs> {
+        self.high_water_mark
+    }
+
+    fn set_high_water_mark(&mut self, high_water_mark: TimestampMs) {
+        self.high_water_mark = Some(high_water_mark);
+    }
+
+    fn clear_index(&mut self) -> Fallible<()> {
+        self.index_writer
+            .delete_all_documents()
+            .map_err(Fail::compat)?;
+        self.high_water_mark = None;
         Ok(())
     }
 }
@@ -899,10 +2249,16 @@ impl PlaceIndexer for TantivyIndex {
             doc.add_i64(self.fields.status, status);
         }
         doc.add_text(self.fields.id, place.id.as_ref());
+        doc.add_u64(self.fields.revision, place.revision.into());
+        doc.add_i64(self.fields.created, place.created.at.into_inner());
         doc.add_f64(self.fields.lat, place.location.pos.lat().to_deg());
         doc.add_f64(self.fields.lng, place.location.pos.lng().to_deg());
         doc.add_text(self.fields.title, &place.title);
         doc.add_text(self.fields.description, &place.description);
+        for &field in &[self.fields.text_de, self.fields.text_en] {
+            doc.add_text(field, &place.title);
+            doc.add_text(field, &place.description);
+        }
         if let Some(ref address) = place.location.address {
             let Address {
                 street,
@@ -933,6 +2289,9 @@ impl PlaceIndexer for TantivyIndex {
                 doc.add_text(self.fields.contact_name, contact_name);
             }
         }
+        if let Some(ref opening_hours) = place.opening_hours {
+            doc.add_text(self.fields.opening_hours, opening_hours.as_str());
+        }
         for tag in &place.tags {
             doc.add_text(self.fields.tag, tag);
         }
@@ -951,6 +2310,18 @@ impl PlaceIndexer for TantivyIndex {
     }
 }
 
+impl EventIndex for TantivyIndex {
+    fn query_events(&self, query: &IndexQuery, limit: usize) -> Fallible<Vec<IndexedEvent>> {
+        let collector = IndexedEventCollector::with_capacity(&self.fields, limit);
+        self.query_documents(IndexQueryMode::WithoutRating, query, limit, collector)
+            .map(Into::into)
+    }
+
+    fn all_event_ids(&self) -> Fallible<Vec<Id>> {
+        self.all_ids_of_kind(EVENT_KIND_FLAG)
+    }
+}
+
 impl EventIndexer for TantivyIndex {
     fn add_or_update_event(&self, event: &Event) -> Fallible<()> {
         let id_term = Term::from_field_text(self.fields.id, event.id.as_ref());
@@ -958,6 +2329,9 @@ impl EventIndexer for TantivyIndex {
         let mut doc = Document::default();
         doc.add_i64(self.fields.kind, EVENT_KIND_FLAG);
         doc.add_text(self.fields.id, event.id.as_ref());
+        if let Some(created_at) = event.created_at {
+            doc.add_i64(self.fields.created, created_at.into_inner());
+        }
         if let Some(ref location) = event.location {
             doc.add_f64(self.fields.lat, location.pos.lat().to_deg());
             doc.add_f64(self.fields.lng, location.pos.lng().to_deg());
@@ -994,10 +2368,24 @@ impl EventIndexer for TantivyIndex {
             debug_assert!(event.start <= end);
             doc.add_i64(self.fields.ts_max, Timestamp::from(end).into_inner());
         }
+        // Infinitely recurring events never end
+        doc.add_i64(
+            self.fields.ts_last,
+            event
+                .last_start()
+                .map(|last_start| Timestamp::from(last_start).into_inner())
+                .unwrap_or(i64::MAX),
+        );
         doc.add_text(self.fields.title, &event.title);
         if let Some(ref description) = event.description {
             doc.add_text(self.fields.description, description);
         }
+        for &field in &[self.fields.text_de, self.fields.text_en] {
+            doc.add_text(field, &event.title);
+            if let Some(ref description) = event.description {
+                doc.add_text(field, description);
+            }
+        }
         if let Some(ref contact) = event.contact {
             let Contact { name, .. } = contact;
             if let Some(contact_name) = name {
@@ -1018,6 +2406,163 @@ impl PlaceIndex for TantivyIndex {
         self.query_documents(IndexQueryMode::WithRating, query, limit, collector)
             .map(Into::into)
     }
+
+    fn all_place_ids(&self) -> Fallible<Vec<Id>> {
+        self.all_ids_of_kind(PLACE_KIND_FLAG)
+    }
+
+    fn query_places_page(
+        &self,
+        query: &IndexQuery,
+        order: IndexOrder,
+        cursor: Option<&PageCursor>,
+        limit: usize,
+    ) -> Fallible<IndexPage<IndexedPlace>> {
+        let collector = IndexedPlaceCollector::with_capacity(&self.fields, limit);
+        let (collector, next) = self.query_page(
+            IndexQueryMode::WithRating,
+            query,
+            order,
+            cursor,
+            limit,
+            collector,
+        )?;
+        Ok(IndexPage {
+            items: collector.into(),
+            next,
+        })
+    }
+
+    fn cluster_places(&self, query: &IndexQuery, zoom: u8) -> Fallible<Vec<PlaceCluster>> {
+        let (places_query, position_filter) = self.build_places_query(query);
+        let collector = |category: Option<Id>| ClusterCollector {
+            zoom,
+            category,
+            position_filter: position_filter.clone(),
+        };
+        let searcher = self.index_reader.searcher();
+        let mut grid = searcher
+            .search(&places_query, &collector(None))
+            .map_err(Fail::compat)?;
+        // One additional pass per place category for the breakdown
+        for category in &[Category::new_non_profit(), Category::new_commercial()] {
+            let tag_term = Term::from_field_text(self.fields.tag, &category.tag);
+            let category_query = BooleanQuery::from(vec![
+                (
+                    Occur::Must,
+                    Box::new(places_query.clone()) as Box<dyn Query>,
+                ),
+                (
+                    Occur::Must,
+                    Box::new(TermQuery::new(tag_term, IndexRecordOption::Basic)) as Box<dyn Query>,
+                ),
+            ]);
+            let category_grid = searcher
+                .search(&category_query, &collector(Some(category.id.clone())))
+                .map_err(Fail::compat)?;
+            grid.merge(category_grid);
+        }
+        Ok(grid.into_clusters())
+    }
+
+    fn facet_places(&self, query: &IndexQuery, max_tags: usize) -> Fallible<PlaceFacets> {
+        let (places_query, position_filter) = self.build_places_query(query);
+        let collector = FacetCollector {
+            tag_field: self.fields.tag,
+            status_field: self.fields.status,
+            position_filter,
+        };
+        let FacetCounts { tags, status } = self
+            .index_reader
+            .searcher()
+            .search(&places_query, &collector)
+            .map_err(Fail::compat)?;
+        let mut tag_counts = Vec::with_capacity(tags.len());
+        let mut categories = Vec::with_capacity(2);
+        for (tag, count) in tags {
+            match tag.as_str() {
+                Category::TAG_NON_PROFIT => {
+                    categories.push((Id::from(Category::ID_NON_PROFIT), count));
+                }
+                Category::TAG_COMMERCIAL => {
+                    categories.push((Id::from(Category::ID_COMMERCIAL), count));
+                }
+                _ => tag_counts.push(TagFrequency(tag, count)),
+            }
+        }
+        // Most frequent tags first, equally frequent tags in alphabetical order
+        tag_counts.sort_unstable_by(|lhs, rhs| rhs.1.cmp(&lhs.1).then_with(|| lhs.0.cmp(&rhs.0)));
+        tag_counts.truncate(max_tags);
+        categories.sort_unstable();
+        let mut status: Vec<_> = status
+            .into_iter()
+            .filter_map(|(status, count)| ReviewStatus::try_from(status).map(|s| (s, count)))
+            .collect();
+        status.sort_unstable();
+        Ok(PlaceFacets {
+            tags: tag_counts,
+            categories,
+            status,
+        })
+    }
+
+    fn suggest_places(
+        &self,
+        query: &IndexQuery,
+        prefix: &str,
+        limit: usize,
+    ) -> Fallible<Vec<PlaceSuggestion>> {
+        let prefix = prefix.trim().to_lowercase();
+        let prefix_query = match self.build_prefix_query(&prefix)? {
+            Some(prefix_query) => prefix_query,
+            None => return Ok(vec![]),
+        };
+        let (places_query, position_filter) = self.build_places_query(query);
+        let suggestions_query = BooleanQuery::from(vec![
+            (Occur::Must, Box::new(places_query) as Box<dyn Query>),
+            (Occur::Must, Box::new(prefix_query)),
+        ]);
+        let collector = SuggestionSampleCollector {
+            max_docs: MAX_SUGGESTION_DOCS,
+            position_filter,
+        };
+        let searcher = self.index_reader.searcher();
+        let sample = searcher
+            .search(&suggestions_query, &collector)
+            .map_err(Fail::compat)?;
+        let mut counter = SuggestionCounter {
+            fields: &self.fields,
+            prefix: &prefix,
+            counts: Default::default(),
+        };
+        for &(_, doc_addr) in &sample.docs {
+            match searcher.doc(doc_addr) {
+                Ok(doc) => counter.count_doc(&doc),
+                Err(err) => warn!("Failed to load document {:?}: {}", doc_addr, err),
+            }
+        }
+        // Extrapolate the counts of the sample to all matching documents
+        let sampled_docs = sample.docs.len().max(1) as u64;
+        let matching_docs = sample.matching_docs as u64;
+        let counts = counter.counts.into_iter().map(|(suggestion, count)| {
+            let estimated = (count * matching_docs + sampled_docs / 2) / sampled_docs;
+            (suggestion, estimated.max(1))
+        });
+        let mut suggestions: Vec<_> = counts
+            .into_iter()
+            .map(|((kind, text), count)| PlaceSuggestion { kind, text, count })
+            .collect();
+        // Most frequent suggestions first, equally frequent
+        // suggestions in alphabetical order
+        suggestions.sort_unstable_by(|lhs, rhs| {
+            rhs.count
+                .cmp(&lhs.count)
+                .then_with(|| lhs.text.cmp(&rhs.text))
+                .then_with(|| lhs.kind.cmp(&rhs.kind))
+        });
+        suggestions.truncate(limit);
+        Ok(suggestions)
+    }
 }
 
 impl EventAndPlaceIndexer for TantivyIndex {}
@@ -1046,6 +2591,38 @@ impl Indexer for SearchEngine {
         };
         inner.flush_index()
     }
+
+    fn generation(&self) -> u64 {
+        let inner = match self.0.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        inner.generation()
+    }
+
+    fn high_water_mark(&self) -> Option<TimestampMs> {
+        let inner = match self.0.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        inner.high_water_mark()
+    }
+
+    fn set_high_water_mark(&mut self, high_water_mark: TimestampMs) {
+        let mut inner = match self.0.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        inner.set_high_water_mark(high_water_mark)
+    }
+
+    fn clear_index(&mut self) -> Fallible<()> {
+        let mut inner = match self.0.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        inner.clear_index()
+    }
 }
 
 impl IdIndex for SearchEngine {
@@ -1061,6 +2638,21 @@ impl IdIndex for SearchEngine {
         };
         inner.query_ids(mode, query, limit)
     }
+
+    fn query_ids_page(
+        &self,
+        mode: IndexQueryMode,
+        query: &IndexQuery,
+        order: IndexOrder,
+        cursor: Option<&PageCursor>,
+        limit: usize,
+    ) -> Fallible<IndexPage<Id>> {
+        let inner = match self.0.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        inner.query_ids_page(mode, query, order, cursor, limit)
+    }
 }
 
 impl IdIndexer for SearchEngine {
@@ -1081,6 +2673,57 @@ impl PlaceIndex for SearchEngine {
         };
         inner.query_places(query, limit)
     }
+
+    fn all_place_ids(&self) -> Fallible<Vec<Id>> {
+        let inner = match self.0.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        inner.all_place_ids()
+    }
+
+    fn query_places_page(
+        &self,
+        query: &IndexQuery,
+        order: IndexOrder,
+        cursor: Option<&PageCursor>,
+        limit: usize,
+    ) -> Fallible<IndexPage<IndexedPlace>> {
+        let inner = match self.0.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        inner.query_places_page(query, order, cursor, limit)
+    }
+
+    fn cluster_places(&self, query: &IndexQuery, zoom: u8) -> Fallible<Vec<PlaceCluster>> {
+        let inner = match self.0.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        inner.cluster_places(query, zoom)
+    }
+
+    fn facet_places(&self, query: &IndexQuery, max_tags: usize) -> Fallible<PlaceFacets> {
+        let inner = match self.0.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        inner.facet_places(query, max_tags)
+    }
+
+    fn suggest_places(
+        &self,
+        query: &IndexQuery,
+        prefix: &str,
+        limit: usize,
+    ) -> Fallible<Vec<PlaceSuggestion>> {
+        let inner = match self.0.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        inner.suggest_places(query, prefix, limit)
+    }
 }
 
 impl PlaceIndexer for SearchEngine {
@@ -1098,6 +2741,24 @@ impl PlaceIndexer for SearchEngine {
     }
 }
 
+impl EventIndex for SearchEngine {
+    fn query_events(&self, query: &IndexQuery, limit: usize) -> Fallible<Vec<IndexedEvent>> {
+        let inner = match self.0.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        inner.query_events(query, limit)
+    }
+
+    fn all_event_ids(&self) -> Fallible<Vec<Id>> {
+        let inner = match self.0.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        inner.all_event_ids()
+    }
+}
+
 impl EventIndexer for SearchEngine {
     fn add_or_update_event(&self, event: &Event) -> Fallible<()> {
         let inner = match self.0.lock() {
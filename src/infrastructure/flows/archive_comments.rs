@@ -1,8 +1,7 @@
 use super::*;
-use diesel::connection::Connection;
 
 pub fn archive_comments(
-    connections: &sqlite::Connections,
+    connections: &db::Connections,
     account_email: &str,
     ids: &[&str],
 ) -> Result<usize> {
@@ -1,9 +1,7 @@
 use super::*;
 
-use diesel::connection::Connection;
-
 fn exec_archive_events(
-    connections: &sqlite::Connections,
+    connections: &db::Connections,
     ids: &[&str],
     _archived_by_email: &str,
 ) -> Result<usize> {
@@ -46,7 +44,7 @@ fn post_archive_events(indexer: &mut dyn EventIndexer, ids: &[&str]) -> Result<(
 }
 
 pub fn archive_events(
-    connections: &sqlite::Connections,
+    connections: &db::Connections,
     indexer: &mut dyn EventIndexer,
     ids: &[&str],
     archived_by_email: &str,
@@ -1,9 +1,7 @@
 use super::*;
 
-use diesel::connection::Connection;
-
 pub fn exec_archive_ratings(
-    connections: &sqlite::Connections,
+    connections: &db::Connections,
     account_email: &str,
     ids: &[&str],
 ) -> Result<usize> {
@@ -28,7 +26,7 @@ pub fn exec_archive_ratings(
 }
 
 pub fn post_archive_ratings(
-    connections: &sqlite::Connections,
+    connections: &db::Connections,
     indexer: &mut dyn PlaceIndexer,
     ids: &[&str],
 ) -> Result<()> {
@@ -74,7 +72,7 @@ pub fn post_archive_ratings(
 }
 
 pub fn archive_ratings(
-    connections: &sqlite::Connections,
+    connections: &db::Connections,
     indexer: &mut dyn PlaceIndexer,
     account_email: &str,
     ids: &[&str],
@@ -0,0 +1,27 @@
+use super::*;
+
+pub fn change_place_ownership(
+    connections: &db::Connections,
+    account_email: &str,
+    place_id: &Id,
+    ownership: PlaceOwnership,
+) -> Result<()> {
+    let mut repo_err = None;
+    let connection = connections.exclusive()?;
+    Ok(connection
+        .transaction::<_, diesel::result::Error, _>(|| {
+            usecases::change_place_ownership(&*connection, account_email, place_id, ownership)
+                .map_err(|err| {
+                    warn!("Failed to change owners of place {}: {}", place_id, err);
+                    repo_err = Some(err);
+                    diesel::result::Error::RollbackTransaction
+                })
+        })
+        .map_err(|err| {
+            if let Some(repo_err) = repo_err {
+                repo_err
+            } else {
+                RepoError::from(err).into()
+            }
+        })?)
+}
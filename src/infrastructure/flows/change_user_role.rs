@@ -1,8 +1,7 @@
 use super::*;
-use diesel::connection::Connection;
 
 pub fn change_user_role(
-    connections: &sqlite::Connections,
+    connections: &db::Connections,
     account_email: &str,
     user_email: &str,
     role: Role,
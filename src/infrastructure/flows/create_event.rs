@@ -1,19 +1,42 @@
 use super::*;
 use crate::core::error::RepoError;
-use diesel::Connection;
 use ofdb_core::gateways::notify::NotificationGateway;
 
 pub fn create_event(
-    connections: &sqlite::Connections,
-    indexer: &mut dyn EventIndexer,
+    connections: &db::Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
     notify: &dyn NotificationGateway,
     token: Option<&str>,
     new_event: usecases::NewEvent,
 ) -> Result<Event> {
+    create_event_and_then(
+        connections,
+        indexer,
+        notify,
+        token,
+        new_event,
+        |_, _| Ok(()),
+    )
+}
+
+/// Create a new event and store additional data about the
+/// event within the same transaction.
+pub(super) fn create_event_and_then<F>(
+    connections: &db::Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
+    notify: &dyn NotificationGateway,
+    token: Option<&str>,
+    new_event: usecases::NewEvent,
+    and_then: F,
+) -> Result<Event>
+where
+    F: FnOnce(&dyn Db, &Event) -> Result<()>,
+{
     // Create and add new event
     let event = {
         let connection = connections.exclusive()?;
         let mut prepare_err = None;
+        let mut store_err = None;
         connection
             .transaction::<_, diesel::result::Error, _>(|| {
                 match usecases::import_new_event(
@@ -29,6 +52,11 @@ pub fn create_event(
                                 diesel::result::Error::RollbackTransaction
                             },
                         )?;
+                        and_then(&*connection, &event).map_err(|err| {
+                            warn!("Failed to store newly created event {}: {}", event.id, err);
+                            store_err = Some(err);
+                            diesel::result::Error::RollbackTransaction
+                        })?;
                         Ok(event)
                     }
                     Err(err) => {
@@ -38,7 +66,7 @@ pub fn create_event(
                 }
             })
             .map_err(|err| {
-                if let Some(err) = prepare_err {
+                if let Some(err) = prepare_err.or(store_err) {
                     err
                 } else {
                     RepoError::from(err).into()
@@ -46,35 +74,33 @@ pub fn create_event(
             })
     }?;
 
-    // Index newly added event
-    // TODO: Move to a separate task/thread that doesn't delay this request
-    if let Err(err) = usecases::index_event(indexer, &event).and_then(|_| indexer.flush_index()) {
-        error!("Failed to index newly added event {}: {}", event.id, err);
-    }
-
-    // Send subscription e-mails
-    // TODO: Move to a separate task/thread that doesn't delay this request
-    if let Err(err) = notify_event_created(connections, notify, &event) {
-        error!(
-            "Failed to send notifications for newly added event {}: {}",
-            event.id, err
-        );
-    }
+    // Index the new event, send subscription e-mails and deliver the
+    // change to webhooks of organizations in the background
+    let event_id = event.id.to_string();
+    let mut tasks = if jobs::needs_geocoding(&event) {
+        vec![JobTask::GeocodeEvent {
+            event_id,
+            created: true,
+        }]
+    } else {
+        vec![
+            JobTask::ReindexEvent {
+                event_id: event_id.clone(),
+            },
+            JobTask::NotifyEventCreated { event_id },
+        ]
+    };
+    tasks.extend(webhooks::webhook_deliveries_event_changed(
+        connections,
+        WebhookEvent::EventCreated,
+        &event,
+    ));
+    let mut ctx = JobContext {
+        connections,
+        indexer,
+        notify: Some(notify),
+    };
+    jobs::submit(&mut ctx, tasks);
 
     Ok(event)
 }
-
-fn notify_event_created(
-    connections: &sqlite::Connections,
-    notify: &dyn NotificationGateway,
-    event: &Event,
-) -> Result<()> {
-    if let Some(ref location) = event.location {
-        let email_addresses = {
-            let conn = connections.shared()?;
-            usecases::email_addresses_by_coordinate(&*conn, location.pos)?
-        };
-        notify.event_created(&email_addresses, event);
-    }
-    Ok(())
-}
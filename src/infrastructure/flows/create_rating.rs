@@ -1,14 +1,12 @@
 use super::*;
 
-use diesel::connection::Connection;
-
 pub fn create_rating(
-    connections: &sqlite::Connections,
-    indexer: &mut dyn PlaceIndexer,
+    connections: &db::Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
     rate_entry: usecases::NewPlaceRating,
 ) -> Result<(String, String)> {
     // Add new rating to existing entry
-    let (rating_id, comment_id, place, status, ratings) = {
+    let (rating_id, comment_id, place) = {
         let connection = connections.exclusive()?;
         let mut prepare_err = None;
         connection
@@ -17,12 +15,12 @@ pub fn create_rating(
                     Ok(storable) => {
                         let rating_id = storable.rating_id().to_owned();
                         let comment_id = storable.comment_id().to_owned();
-                        let (place, status, ratings) =
-                            usecases::store_new_rating(&*connection, storable).map_err(|err| {
+                        let (place, _, _) = usecases::store_new_rating(&*connection, storable)
+                            .map_err(|err| {
                                 warn!("Failed to store new rating for entry: {}", err);
                                 diesel::result::Error::RollbackTransaction
                             })?;
-                        Ok((rating_id, comment_id, place, status, ratings))
+                        Ok((rating_id, comment_id, place))
                     }
                     Err(err) => {
                         prepare_err = Some(err);
@@ -39,16 +37,18 @@ pub fn create_rating(
             })
     }?;
 
-    // Reindex entry after adding the new rating
-    // TODO: Move to a separate task/thread that doesn't delay this request
-    if let Err(err) = usecases::reindex_place(indexer, &place, status, &ratings)
-        .and_then(|_| indexer.flush_index())
-    {
-        error!(
-            "Failed to reindex place {} after adding a new rating: {}",
-            place.id, err
-        );
-    }
+    // Reindex entry after adding the new rating in the background
+    let mut ctx = JobContext {
+        connections,
+        indexer,
+        notify: None,
+    };
+    jobs::submit(
+        &mut ctx,
+        vec![JobTask::ReindexPlace {
+            place_id: place.id.into(),
+        }],
+    );
 
     Ok((rating_id, comment_id))
 }
@@ -0,0 +1,49 @@
+use super::*;
+
+fn exec_delete_event(connections: &db::Connections, token: &str, id: &str) -> Result<()> {
+    let connection = connections.exclusive()?;
+    let mut delete_err = None;
+    connection
+        .transaction::<_, diesel::result::Error, _>(|| {
+            usecases::delete_event(&*connection, token, id).map_err(|err| {
+                warn!("Failed to delete event {}: {}", id, err);
+                delete_err = Some(err);
+                diesel::result::Error::RollbackTransaction
+            })
+        })
+        .map_err(|err| {
+            if let Some(err) = delete_err {
+                err
+            } else {
+                RepoError::from(err).into()
+            }
+        })?;
+    Ok(())
+}
+
+fn post_delete_event(indexer: &mut dyn EventIndexer, id: &str) -> Result<()> {
+    // Remove the deleted event from search index
+    if let Err(err) = usecases::unindex_event(indexer, &Id::from(id)) {
+        error!(
+            "Failed to remove deleted event {} from search index: {}",
+            id, err
+        );
+    }
+    if let Err(err) = indexer.flush_index() {
+        error!(
+            "Failed to finish updating the search index after deleting event {}: {}",
+            id, err
+        );
+    }
+    Ok(())
+}
+
+pub fn delete_event(
+    connections: &db::Connections,
+    indexer: &mut dyn EventIndexer,
+    token: &str,
+    id: &str,
+) -> Result<()> {
+    exec_delete_event(connections, token, id)?;
+    post_delete_event(indexer, id)
+}
@@ -0,0 +1,135 @@
+use super::{create_event::create_event_and_then, *};
+use ofdb_core::gateways::notify::NotificationGateway;
+
+#[derive(Debug)]
+pub struct ImportedEvent {
+    pub event: Event,
+    /// `true` if a new event has been created and `false`
+    /// if an existing event has been updated
+    pub created: bool,
+}
+
+/// Create or update an event that has been imported by an
+/// organization from an external calendar.
+///
+/// The `import_uid` identifies the event in the external
+/// calendar. Importing an event with the same UID again
+/// updates the previously created event instead of creating
+/// a duplicate.
+pub fn import_event(
+    connections: &db::Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
+    notify: &dyn NotificationGateway,
+    token: &str,
+    import_uid: &str,
+    new_event: usecases::NewEvent,
+) -> Result<ImportedEvent> {
+    let (org, event_id) = {
+        let connection = connections.shared()?;
+        let org = usecases::authorize_organization_by_api_token(&*connection, token)?;
+        let event_id = connection.get_event_id_by_import_uid(&org.id, import_uid)?;
+        (org, event_id)
+    };
+    if let Some(event_id) = event_id {
+        let event = update_event(
+            connections,
+            indexer,
+            notify,
+            Some(token),
+            event_id,
+            new_event,
+        )?;
+        return Ok(ImportedEvent {
+            event,
+            created: false,
+        });
+    }
+    // The UID is stored within the same transaction, i.e. the
+    // event is not created if the UID could not be stored
+    let event = create_event_and_then(
+        connections,
+        indexer,
+        notify,
+        Some(token),
+        new_event,
+        |db, event| Ok(db.create_event_import_uid(&org.id, import_uid, &event.id)?),
+    )?;
+    Ok(ImportedEvent {
+        event,
+        created: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::prelude::*;
+
+    fn import_event(
+        fixture: &BackendFixture,
+        token: &str,
+        import_uid: &str,
+        title: &str,
+    ) -> super::Result<flows::ImportedEvent> {
+        flows::import_event(
+            &fixture.db_connections,
+            &mut *fixture.search_engine.borrow_mut(),
+            &fixture.notify,
+            token,
+            import_uid,
+            usecases::NewEvent {
+                title: title.into(),
+                start: chrono::Utc::now().naive_utc().timestamp(),
+                created_by: Some("org@example.com".into()),
+                ..Default::default()
+            },
+        )
+    }
+
+    fn create_org(fixture: &BackendFixture, id: &str, tag: &str) {
+        fixture
+            .db_connections
+            .exclusive()
+            .unwrap()
+            .create_org(Organization {
+                id: id.into(),
+                name: id.into(),
+                moderated_tags: vec![tag.into()],
+                api_token: id.into(),
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn should_create_and_update_imported_events_by_uid() {
+        let fixture = BackendFixture::new();
+        create_org(&fixture, "org1", "org1-tag");
+        create_org(&fixture, "org2", "org2-tag");
+
+        let imported = import_event(&fixture, "org1", "uid1", "foo").unwrap();
+        assert!(imported.created);
+        let id = imported.event.id;
+
+        let imported = import_event(&fixture, "org1", "uid1", "bar").unwrap();
+        assert!(!imported.created);
+        assert_eq!(id, imported.event.id);
+
+        let imported = import_event(&fixture, "org1", "uid2", "baz").unwrap();
+        assert!(imported.created);
+        assert_ne!(id, imported.event.id);
+
+        // The same UID of a different organization refers to a different event
+        let imported = import_event(&fixture, "org2", "uid1", "foo").unwrap();
+        assert!(imported.created);
+        assert_ne!(id, imported.event.id);
+
+        let connection = fixture.db_connections.shared().unwrap();
+        assert_eq!(3, connection.count_events().unwrap());
+        assert_eq!("bar", connection.get_event(id.as_str()).unwrap().title);
+    }
+
+    #[test]
+    fn should_reject_import_with_invalid_token() {
+        let fixture = BackendFixture::new();
+        assert!(import_event(&fixture, "invalid", "uid1", "foo").is_err());
+    }
+}
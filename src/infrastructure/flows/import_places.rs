@@ -0,0 +1,320 @@
+use super::{create_place::prepare_and_store_new_place, *};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImportPlacesOptions {
+    /// Only validate the new places and search for duplicates
+    /// without storing anything
+    pub dry_run: bool,
+    /// Also import places that are likely duplicates of
+    /// existing places
+    pub allow_duplicates: bool,
+}
+
+#[derive(Debug)]
+pub struct PlaceImportReport {
+    /// Existing places or preceding places of the same import
+    /// that are likely duplicates. Preceding places that have
+    /// not been created, i.e. on a dry run, don't have an id.
+    pub duplicates: Vec<IndexedPlace>,
+    /// `Ok(Some(place))` if the place has been created and
+    /// `Ok(None)` if the place is valid, but has not been
+    /// created, i.e. on a dry run or if it is a likely
+    /// duplicate.
+    pub result: Result<Option<Place>>,
+}
+
+/// Create multiple new places at once, e.g. when importing
+/// places from a file.
+///
+/// Each place is validated and checked for duplicates before
+/// it is created. Places that could not even be parsed are
+/// passed in as errors and are reported unmodified. Imported
+/// places don't trigger any subscription e-mails and the search
+/// index is only flushed once at the end. The reports are
+/// returned in the order of the given places.
+pub fn import_places(
+    connections: &db::Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
+    new_places: Vec<Result<usecases::NewPlace>>,
+    created_by_email: Option<&str>,
+    created_by_org: Option<&Organization>,
+    options: ImportPlacesOptions,
+) -> Result<Vec<PlaceImportReport>> {
+    let mut reports = Vec::with_capacity(new_places.len());
+    let mut created_count = 0;
+    let mut webhook_tasks = vec![];
+    // Places that have been accepted during this import are
+    // not searchable before the index is flushed
+    let mut accepted_places: Vec<IndexedPlace> = vec![];
+    for new_place in new_places {
+        let new_place = match new_place {
+            Ok(new_place) => new_place,
+            Err(err) => {
+                reports.push(PlaceImportReport {
+                    duplicates: vec![],
+                    result: Err(err),
+                });
+                continue;
+            }
+        };
+        let mut duplicates = usecases::search_duplicates(&*indexer, &new_place)?;
+        duplicates.extend(usecases::retain_duplicates_of(
+            accepted_places.clone(),
+            &new_place,
+        ));
+        let validated = {
+            let connection = connections.shared()?;
+            usecases::prepare_new_place(
+                &*connection,
+                new_place.clone(),
+                created_by_email,
+                created_by_org,
+            )
+        };
+        if let Err(err) = validated {
+            reports.push(PlaceImportReport {
+                duplicates,
+                result: Err(err.into()),
+            });
+            continue;
+        }
+        if !(options.allow_duplicates || duplicates.is_empty()) {
+            reports.push(PlaceImportReport {
+                duplicates,
+                result: Ok(None),
+            });
+            continue;
+        }
+        let accepted_place = IndexedPlace {
+            pos: MapPoint::from_lat_lng_deg(new_place.lat, new_place.lng),
+            title: new_place.title.clone(),
+            description: new_place.description.clone(),
+            tags: new_place.tags.clone(),
+            ..Default::default()
+        };
+        if options.dry_run {
+            accepted_places.push(accepted_place);
+            reports.push(PlaceImportReport {
+                duplicates,
+                result: Ok(None),
+            });
+            continue;
+        }
+        let result =
+            prepare_and_store_new_place(connections, new_place, created_by_email, created_by_org)
+                .map(|(place, ratings)| {
+                    if let Err(err) =
+                        usecases::reindex_place(&*indexer, &place, ReviewStatus::Created, &ratings)
+                    {
+                        error!("Failed to index imported place {}: {}", place.id, err);
+                    }
+                    webhook_tasks.extend(webhooks::webhook_deliveries_place_changed(
+                        connections,
+                        WebhookEvent::PlaceAdded,
+                        &place,
+                        &ratings,
+                    ));
+                    created_count += 1;
+                    accepted_places.push(IndexedPlace {
+                        id: place.id.to_string(),
+                        revision: Some(place.revision),
+                        status: Some(ReviewStatus::Created),
+                        ..accepted_place
+                    });
+                    Some(place)
+                });
+        reports.push(PlaceImportReport { duplicates, result });
+    }
+    if created_count > 0 {
+        indexer.flush_index()?;
+    }
+    // Deliver the imported places to webhooks of organizations
+    // in the background
+    let mut ctx = JobContext {
+        connections,
+        indexer,
+        notify: None,
+    };
+    jobs::submit(&mut ctx, webhook_tasks);
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::prelude::*;
+
+    fn new_place(title: &str, lat: f64) -> usecases::NewPlace {
+        usecases::NewPlace {
+            title: title.into(),
+            description: "bla".into(),
+            lat,
+            lng: 0.0,
+            street: None,
+            zip: None,
+            city: None,
+            country: None,
+            state: None,
+            contact_name: None,
+            email: None,
+            telephone: None,
+            homepage: None,
+            opening_hours: None,
+            founded_on: None,
+            categories: vec![],
+            tags: vec!["imported".into()],
+            license: "CC0-1.0".into(),
+            image_url: None,
+            image_link_url: None,
+            custom_links: vec![],
+        }
+    }
+
+    fn import_places(
+        fixture: &BackendFixture,
+        new_places: Vec<usecases::NewPlace>,
+        options: flows::ImportPlacesOptions,
+    ) -> Vec<flows::PlaceImportReport> {
+        flows::import_places(
+            &fixture.db_connections,
+            &mut *fixture.search_engine.borrow_mut(),
+            new_places.into_iter().map(Ok).collect(),
+            None,
+            None,
+            options,
+        )
+        .unwrap()
+    }
+
+    fn count_places(fixture: &BackendFixture) -> usize {
+        fixture
+            .db_connections
+            .shared()
+            .unwrap()
+            .count_places()
+            .unwrap()
+    }
+
+    #[test]
+    fn should_import_valid_and_skip_invalid_places() {
+        let fixture = BackendFixture::new();
+        let reports = import_places(
+            &fixture,
+            vec![
+                new_place("foo", 1.0),
+                new_place("invalid", 100.0),
+                new_place("bar", 2.0),
+            ],
+            Default::default(),
+        );
+        assert_eq!(3, reports.len());
+        assert!(reports[0].result.as_ref().unwrap().is_some());
+        assert!(reports[1].result.is_err());
+        assert!(reports[2].result.as_ref().unwrap().is_some());
+        assert_eq!(2, count_places(&fixture));
+        let indexed = fixture
+            .search_engine
+            .borrow()
+            .query_ids(
+                IndexQueryMode::WithoutRating,
+                &IndexQuery {
+                    hash_tags: vec!["imported".into()],
+                    ..Default::default()
+                },
+                10,
+            )
+            .unwrap();
+        assert_eq!(2, indexed.len());
+    }
+
+    #[test]
+    fn should_not_store_anything_on_dry_run() {
+        let fixture = BackendFixture::new();
+        let reports = import_places(
+            &fixture,
+            vec![new_place("foo", 1.0), new_place("invalid", 100.0)],
+            flows::ImportPlacesOptions {
+                dry_run: true,
+                ..Default::default()
+            },
+        );
+        assert!(reports[0].result.as_ref().unwrap().is_none());
+        assert!(reports[1].result.is_err());
+        assert_eq!(0, count_places(&fixture));
+    }
+
+    #[test]
+    fn should_report_and_skip_likely_duplicates() {
+        let fixture = BackendFixture::new();
+        let existing_id = flows::create_place(
+            &fixture.db_connections,
+            &mut *fixture.search_engine.borrow_mut(),
+            &fixture.notify,
+            new_place("Bioladen Leipzig", 1.0),
+            None,
+            None,
+        )
+        .unwrap()
+        .id
+        .to_string();
+        let reports = import_places(
+            &fixture,
+            vec![
+                new_place("Bioladen Leipzig", 1.0),
+                new_place("Something else", 1.0),
+            ],
+            Default::default(),
+        );
+        assert_eq!(1, reports[0].duplicates.len());
+        assert_eq!(existing_id, reports[0].duplicates[0].id);
+        assert!(reports[0].result.as_ref().unwrap().is_none());
+        assert!(reports[1].duplicates.is_empty());
+        assert!(reports[1].result.as_ref().unwrap().is_some());
+        assert_eq!(2, count_places(&fixture));
+
+        let reports = import_places(
+            &fixture,
+            vec![new_place("Bioladen Leipzig", 1.0)],
+            flows::ImportPlacesOptions {
+                allow_duplicates: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(1, reports[0].duplicates.len());
+        assert!(reports[0].result.as_ref().unwrap().is_some());
+        assert_eq!(3, count_places(&fixture));
+    }
+
+    #[test]
+    fn should_report_and_skip_likely_duplicates_within_the_same_import() {
+        let fixture = BackendFixture::new();
+        let new_places = vec![
+            new_place("Weltladen Hamburg", 1.0),
+            new_place("Weltladen Hamburg", 1.0),
+        ];
+        let reports = import_places(
+            &fixture,
+            new_places.clone(),
+            flows::ImportPlacesOptions {
+                dry_run: true,
+                ..Default::default()
+            },
+        );
+        assert!(reports[0].duplicates.is_empty());
+        assert_eq!(1, reports[1].duplicates.len());
+        assert!(reports[1].duplicates[0].id.is_empty());
+
+        let reports = import_places(&fixture, new_places, Default::default());
+        let created_id = reports[0]
+            .result
+            .as_ref()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .id
+            .to_string();
+        assert_eq!(1, reports[1].duplicates.len());
+        assert_eq!(created_id, reports[1].duplicates[0].id);
+        assert!(reports[1].result.as_ref().unwrap().is_none());
+        assert_eq!(1, count_places(&fixture));
+    }
+}
@@ -1,26 +1,37 @@
 mod archive_comments;
 mod archive_events;
 mod archive_ratings;
+mod change_place_ownership;
 mod change_user_role;
 mod create_event;
 mod create_place;
 mod create_rating;
+mod delete_event;
+mod import_event;
+mod import_places;
 mod reset_password;
+mod revert_place;
+mod review_place_proposal;
 mod review_places;
 mod update_event;
 mod update_place;
+mod webhooks;
 
 pub mod prelude {
     pub use super::{
-        archive_comments::*, archive_events::*, archive_ratings::*, change_user_role::*,
-        create_event::*, create_place::*, create_rating::*, reset_password::*, review_places::*,
-        update_event::*, update_place::*,
+        archive_comments::*, archive_events::*, archive_ratings::*, change_place_ownership::*,
+        change_user_role::*, create_event::*, create_place::*, create_rating::*, delete_event::*,
+        import_event::*, import_places::*, reset_password::*, revert_place::*,
+        review_place_proposal::*, review_places::*, update_event::*, update_place::*,
     };
 }
 
 pub type Result<T> = std::result::Result<T, error::AppError>;
 
-pub(crate) use super::{db::sqlite, error};
+pub(crate) use super::{
+    db, error,
+    jobs::{self, JobContext, JobTask},
+};
 pub(crate) use crate::core::{prelude::*, usecases};
 
 #[cfg(test)]
@@ -1,9 +1,8 @@
 use super::*;
 use crate::core::error::Error;
-use diesel::connection::Connection;
 use ofdb_core::gateways::notify::NotificationGateway;
 
-fn refresh_user_token(connections: &sqlite::Connections, user: &User) -> Result<EmailNonce> {
+fn refresh_user_token(connections: &db::Connections, user: &User) -> Result<EmailNonce> {
     let mut rollback_err: Option<Error> = None;
     let connection = connections.exclusive()?;
     Ok(connection
@@ -17,7 +16,7 @@ fn refresh_user_token(connections: &sqlite::Connections, user: &User) -> Result<
 }
 
 pub fn reset_password_request(
-    connections: &sqlite::Connections,
+    connections: &db::Connections,
     notify: &dyn NotificationGateway,
     email: &str,
 ) -> Result<EmailNonce> {
@@ -31,7 +30,7 @@ pub fn reset_password_request(
 }
 
 pub fn reset_password_with_email_nonce(
-    connections: &sqlite::Connections,
+    connections: &db::Connections,
     email_nonce: EmailNonce,
     new_password: Password,
 ) -> Result<()> {
@@ -0,0 +1,41 @@
+use super::*;
+use ofdb_core::gateways::notify::NotificationGateway;
+
+pub fn revert_place(
+    connections: &db::Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
+    notify: &dyn NotificationGateway,
+    place_id: &str,
+    revert: usecases::RevertPlace,
+) -> Result<Place> {
+    let (place, ratings) = {
+        let connection = connections.exclusive()?;
+        let mut prepare_err = None;
+        let mut store_err = None;
+        connection
+            .transaction::<_, diesel::result::Error, _>(|| {
+                match usecases::prepare_reverted_place(&*connection, place_id, revert) {
+                    Ok(storable) => {
+                        usecases::store_reverted_place(&*connection, storable).map_err(|err| {
+                            warn!("Failed to store reverted place {}: {}", place_id, err);
+                            store_err = Some(err);
+                            diesel::result::Error::RollbackTransaction
+                        })
+                    }
+                    Err(err) => {
+                        prepare_err = Some(err);
+                        Err(diesel::result::Error::RollbackTransaction)
+                    }
+                }
+            })
+            .map_err(|err| {
+                if let Some(err) = prepare_err.or(store_err) {
+                    err
+                } else {
+                    RepoError::from(err).into()
+                }
+            })
+    }?;
+    super::update_place::after_place_updated(connections, indexer, notify, &place, &ratings);
+    Ok(place)
+}
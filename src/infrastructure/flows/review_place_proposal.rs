@@ -0,0 +1,46 @@
+use super::*;
+use ofdb_core::gateways::notify::NotificationGateway;
+
+pub fn review_place_proposal(
+    connections: &db::Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
+    notify: &dyn NotificationGateway,
+    proposal_id: &Id,
+    review: usecases::ProposalReview,
+) -> Result<Option<Place>> {
+    let reviewed = {
+        let connection = connections.exclusive()?;
+        let mut prepare_err = None;
+        let mut store_err = None;
+        connection
+            .transaction::<_, diesel::result::Error, _>(|| {
+                match usecases::prepare_reviewed_place_proposal(&*connection, proposal_id, review) {
+                    Ok(reviewed) => usecases::store_reviewed_place_proposal(&*connection, reviewed)
+                        .map_err(|err| {
+                            warn!("Failed to store reviewed proposal {}: {}", proposal_id, err);
+                            store_err = Some(err);
+                            diesel::result::Error::RollbackTransaction
+                        }),
+                    Err(err) => {
+                        prepare_err = Some(err);
+                        Err(diesel::result::Error::RollbackTransaction)
+                    }
+                }
+            })
+            .map_err(|err| {
+                if let Some(err) = prepare_err.or(store_err) {
+                    err
+                } else {
+                    RepoError::from(err).into()
+                }
+            })
+    }?;
+    // Rejected proposals don't change the place
+    let (place, ratings) = if let Some(reviewed) = reviewed {
+        reviewed
+    } else {
+        return Ok(None);
+    };
+    super::update_place::after_place_updated(connections, indexer, notify, &place, &ratings);
+    Ok(Some(place))
+}
@@ -1,9 +1,7 @@
 use super::*;
 
-use diesel::connection::Connection;
-
 fn exec_review_places(
-    connections: &sqlite::Connections,
+    connections: &db::Connections,
     ids: &[&str],
     review: usecases::Review,
 ) -> Result<usize> {
@@ -27,48 +25,42 @@ fn exec_review_places(
 }
 
 fn post_review_places(
-    connections: &sqlite::Connections,
-    indexer: &mut dyn PlaceIndexer,
+    connections: &db::Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
     ids: &[&str],
 ) -> Result<()> {
-    let db = connections.shared()?;
-    let places_with_status = db.get_places(ids)?;
-    for (place, status) in places_with_status {
-        let ratings = match db.load_ratings_of_place(place.id.as_str()) {
-            Ok(ratings) => ratings,
-            Err(err) => {
-                log::error!(
-                    "Failed to load ratings of place {} after reviewing: {}",
-                    place.id,
-                    err
-                );
-                continue;
-            }
-        };
-        if let Err(err) = usecases::reindex_place(indexer, &place, status, &ratings) {
-            error!(
-                "Failed to (re-)index place {} after reviewing: {}",
-                place.id, err
-            );
-        }
-    }
-    if let Err(err) = indexer.flush_index() {
-        error!(
-            "Failed to flush search index after reviewing places: {}",
-            err
-        );
+    let places_with_status = connections.shared()?.get_places(ids)?;
+    let mut tasks: Vec<_> = places_with_status
+        .iter()
+        .map(|(place, _)| JobTask::ReindexPlace {
+            place_id: place.id.to_string(),
+        })
+        .collect();
+    for (place, status) in &places_with_status {
+        tasks.extend(webhooks::webhook_deliveries_place_reviewed(
+            connections,
+            place,
+            *status,
+        ));
     }
+    let mut ctx = JobContext {
+        connections,
+        indexer,
+        notify: None,
+    };
+    jobs::submit(&mut ctx, tasks);
     Ok(())
 }
 
 pub fn review_places(
-    connections: &sqlite::Connections,
-    indexer: &mut dyn PlaceIndexer,
+    connections: &db::Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
     ids: &[&str],
     review: usecases::Review,
 ) -> Result<usize> {
     let count = exec_review_places(connections, ids, review)?;
-    // TODO: Move post processing to a separate task/thread that doesn't delay this request?
+    // Reindex the reviewed places and deliver the changes to
+    // webhooks of organizations in the background
     post_review_places(connections, indexer, ids)?;
     Ok(count)
 }
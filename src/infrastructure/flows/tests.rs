@@ -1,8 +1,8 @@
 pub mod prelude {
     pub use crate::core::{prelude::*, usecases};
 
-    pub mod sqlite {
-        pub use super::super::super::sqlite::*;
+    pub mod db {
+        pub use super::super::super::db::*;
     }
 
     pub mod tantivy {
@@ -18,18 +18,17 @@ pub mod prelude {
 
     use std::cell::RefCell;
 
-    embed_migrations!();
-
     pub struct BackendFixture {
-        pub db_connections: sqlite::Connections,
+        pub db_connections: db::Connections,
         pub search_engine: RefCell<tantivy::SearchEngine>,
         pub notify: DummyNotifyGW,
     }
 
     impl BackendFixture {
         pub fn new() -> Self {
-            let db_connections = sqlite::Connections::init(":memory:", 1).unwrap();
-            embedded_migrations::run(&*db_connections.exclusive().unwrap()).unwrap();
+            // Submitted jobs are executed before the flows return
+            crate::infrastructure::jobs::install_inline_executor();
+            let db_connections = db::tests::connections();
             let search_engine = tantivy::SearchEngine::init_in_ram().unwrap();
             Self {
                 db_connections,
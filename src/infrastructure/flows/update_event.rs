@@ -1,11 +1,10 @@
 use super::*;
 use crate::core::error::RepoError;
-use diesel::Connection;
 use ofdb_core::gateways::notify::NotificationGateway;
 
 pub fn update_event(
-    connections: &sqlite::Connections,
-    indexer: &mut dyn EventIndexer,
+    connections: &db::Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
     notify: &dyn NotificationGateway,
     token: Option<&str>,
     id: Id,
@@ -47,35 +46,33 @@ pub fn update_event(
             })
     }?;
 
-    // Index newly added event
-    // TODO: Move to a separate task/thread that doesn't delay this request
-    if let Err(err) = usecases::index_event(indexer, &event).and_then(|_| indexer.flush_index()) {
-        error!("Failed to re-index updated event {}: {}", event.id, err);
-    }
-
-    // Send subscription e-mails
-    // TODO: Move to a separate task/thread that doesn't delay this request
-    if let Err(err) = notify_event_updated(connections, notify, &event) {
-        error!(
-            "Failed to send notifications for updated event {}: {}",
-            event.id, err
-        );
-    }
+    // Reindex the updated event, send subscription e-mails and deliver the
+    // change to webhooks of organizations in the background
+    let event_id = event.id.to_string();
+    let mut tasks = if jobs::needs_geocoding(&event) {
+        vec![JobTask::GeocodeEvent {
+            event_id,
+            created: false,
+        }]
+    } else {
+        vec![
+            JobTask::ReindexEvent {
+                event_id: event_id.clone(),
+            },
+            JobTask::NotifyEventUpdated { event_id },
+        ]
+    };
+    tasks.extend(webhooks::webhook_deliveries_event_changed(
+        connections,
+        WebhookEvent::EventUpdated,
+        &event,
+    ));
+    let mut ctx = JobContext {
+        connections,
+        indexer,
+        notify: Some(notify),
+    };
+    jobs::submit(&mut ctx, tasks);
 
     Ok(event)
 }
-
-fn notify_event_updated(
-    connections: &sqlite::Connections,
-    notify: &dyn NotificationGateway,
-    event: &Event,
-) -> Result<()> {
-    if let Some(ref location) = event.location {
-        let email_addresses = {
-            let conn = connections.shared()?;
-            usecases::email_addresses_by_coordinate(&*conn, location.pos)?
-        };
-        notify.event_updated(&email_addresses, event);
-    }
-    Ok(())
-}
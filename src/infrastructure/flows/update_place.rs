@@ -1,18 +1,18 @@
 use super::*;
-use diesel::connection::Connection;
 use ofdb_core::gateways::notify::NotificationGateway;
 
 pub fn update_place(
-    connections: &sqlite::Connections,
-    indexer: &mut dyn PlaceIndexer,
+    connections: &db::Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
     notify: &dyn NotificationGateway,
     id: Id,
     update_place: usecases::UpdatePlace,
     created_by_email: Option<&str>,
     created_by_org: Option<&Organization>,
-) -> Result<Place> {
+    mode: usecases::PlaceEditMode,
+) -> Result<usecases::StoredPlace> {
     // Update existing entry
-    let (place, ratings) = {
+    let stored = {
         let connection = connections.exclusive()?;
         let mut prepare_err = None;
         connection
@@ -23,16 +23,13 @@ pub fn update_place(
                     update_place,
                     created_by_email,
                     created_by_org,
+                    mode,
                 ) {
                     Ok(storable) => {
-                        let (place, ratings) =
-                            usecases::store_updated_place(&*connection, storable).map_err(
-                                |err| {
-                                    warn!("Failed to store updated place: {}", err);
-                                    diesel::result::Error::RollbackTransaction
-                                },
-                            )?;
-                        Ok((place, ratings))
+                        usecases::store_updated_place(&*connection, storable).map_err(|err| {
+                            warn!("Failed to store updated place: {}", err);
+                            diesel::result::Error::RollbackTransaction
+                        })
                     }
                     Err(err) => {
                         prepare_err = Some(err);
@@ -48,39 +45,44 @@ pub fn update_place(
                 }
             })
     }?;
+    let (place, ratings) = match stored {
+        usecases::StoredPlace::Revision(place, ratings) => (place, ratings),
+        // Proposals don't change the place and are
+        // pending until reviewed by the owners
+        proposal @ usecases::StoredPlace::Proposal(_) => return Ok(proposal),
+    };
 
-    // Reindex updated place
-    // TODO: Move to a separate task/thread that doesn't delay this request
-    if let Err(err) = usecases::reindex_place(indexer, &place, ReviewStatus::Created, &ratings)
-        .and_then(|_| indexer.flush_index())
-    {
-        error!("Failed to reindex updated place {}: {}", place.id, err);
-    }
-
-    // Send subscription e-mails
-    // TODO: Move to a separate task/thread that doesn't delay this request
-    if let Err(err) = notify_place_updated(connections, notify, &place) {
-        error!(
-            "Failed to send notifications for updated place {}: {}",
-            place.id, err
-        );
-    }
+    after_place_updated(connections, indexer, notify, &place, &ratings);
 
-    Ok(place)
+    Ok(usecases::StoredPlace::Revision(place, ratings))
 }
 
-fn notify_place_updated(
-    connections: &sqlite::Connections,
+// Reindex the updated place, send subscription e-mails and deliver
+// the change to webhooks of organizations in the background
+pub(super) fn after_place_updated(
+    connections: &db::Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
     notify: &dyn NotificationGateway,
     place: &Place,
-) -> Result<()> {
-    let (email_addresses, all_categories) = {
-        let connection = connections.shared()?;
-        let email_addresses =
-            usecases::email_addresses_by_coordinate(&*connection, place.location.pos)?;
-        let all_categories = connection.all_categories()?;
-        (email_addresses, all_categories)
+    ratings: &[Rating],
+) {
+    let place_id = place.id.to_string();
+    let mut tasks = vec![
+        JobTask::ReindexPlace {
+            place_id: place_id.clone(),
+        },
+        JobTask::NotifyPlaceUpdated { place_id },
+    ];
+    tasks.extend(webhooks::webhook_deliveries_place_changed(
+        connections,
+        WebhookEvent::PlaceUpdated,
+        place,
+        ratings,
+    ));
+    let mut ctx = JobContext {
+        connections,
+        indexer,
+        notify: Some(notify),
     };
-    notify.place_updated(&email_addresses, &place, all_categories);
-    Ok(())
+    jobs::submit(&mut ctx, tasks);
 }
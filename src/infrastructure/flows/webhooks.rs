@@ -0,0 +1,354 @@
+use super::*;
+use crate::adapters::json;
+
+pub(crate) fn webhook_deliveries_place_changed(
+    connections: &db::Connections,
+    event: WebhookEvent,
+    place: &Place,
+    ratings: &[Rating],
+) -> Vec<JobTask> {
+    let entry = json::entry_from_place_with_ratings(place.clone(), ratings.to_vec());
+    let data = match event {
+        WebhookEvent::PlaceAdded => json::WebhookData::PlaceAdded(entry),
+        WebhookEvent::PlaceUpdated => json::WebhookData::PlaceUpdated(entry),
+        _ => unreachable!("unexpected webhook event for place: {}", event),
+    };
+    webhook_deliveries(connections, Some(place.location.pos), &place.tags, data)
+}
+
+pub(crate) fn webhook_deliveries_place_reviewed(
+    connections: &db::Connections,
+    place: &Place,
+    status: ReviewStatus,
+) -> Vec<JobTask> {
+    let data = json::WebhookData::PlaceReviewed(json::ReviewedPlace {
+        id: place.id.to_string(),
+        rev: place.revision.into(),
+        status: status.into(),
+    });
+    webhook_deliveries(connections, Some(place.location.pos), &place.tags, data)
+}
+
+pub(crate) fn webhook_deliveries_event_changed(
+    connections: &db::Connections,
+    webhook_event: WebhookEvent,
+    event: &Event,
+) -> Vec<JobTask> {
+    let mut event = event.clone();
+    // Don't reveal the e-mail address of the creator
+    event.created_by = None;
+    let pos = event.location.as_ref().map(|l| l.pos);
+    let tags = event.tags.clone();
+    let data = match webhook_event {
+        WebhookEvent::EventCreated => json::WebhookData::EventCreated(event.into()),
+        WebhookEvent::EventUpdated => json::WebhookData::EventUpdated(event.into()),
+        _ => unreachable!("unexpected webhook event for event: {}", webhook_event),
+    };
+    webhook_deliveries(connections, pos, &tags, data)
+}
+
+// Record a pending delivery for each webhook in scope and
+// return the jobs that deliver them.
+fn webhook_deliveries(
+    connections: &db::Connections,
+    pos: Option<MapPoint>,
+    tags: &[String],
+    data: json::WebhookData,
+) -> Vec<JobTask> {
+    let event = webhook_event(&data);
+    match try_create_webhook_deliveries(connections, event, pos, tags, data) {
+        Ok(deliveries) => deliveries
+            .into_iter()
+            .map(|delivery| JobTask::DeliverWebhook {
+                delivery_id: delivery.id.into(),
+            })
+            .collect(),
+        Err(err) => {
+            error!("Failed to notify webhooks about {}: {}", event, err);
+            vec![]
+        }
+    }
+}
+
+fn webhook_event(data: &json::WebhookData) -> WebhookEvent {
+    match data {
+        json::WebhookData::PlaceAdded(_) => WebhookEvent::PlaceAdded,
+        json::WebhookData::PlaceUpdated(_) => WebhookEvent::PlaceUpdated,
+        json::WebhookData::PlaceReviewed(_) => WebhookEvent::PlaceReviewed,
+        json::WebhookData::EventCreated(_) => WebhookEvent::EventCreated,
+        json::WebhookData::EventUpdated(_) => WebhookEvent::EventUpdated,
+    }
+}
+
+fn try_create_webhook_deliveries(
+    connections: &db::Connections,
+    event: WebhookEvent,
+    pos: Option<MapPoint>,
+    tags: &[String],
+    data: json::WebhookData,
+) -> Result<Vec<WebhookDelivery>> {
+    let subscriptions = {
+        let connection = connections.shared()?;
+        usecases::webhook_subscriptions_in_scope(
+            &*connection,
+            pos,
+            tags.iter().map(String::as_str),
+        )?
+    };
+    if subscriptions.is_empty() {
+        return Ok(vec![]);
+    }
+    let created_at = TimestampMs::now();
+    let payload = serde_json::to_string(&json::WebhookPayload {
+        id: Id::new().into(),
+        created_at: created_at.into_inner(),
+        data,
+    })?;
+    let connection = connections.exclusive()?;
+    let mut deliveries = Vec::with_capacity(subscriptions.len());
+    for subscription in subscriptions {
+        let delivery = WebhookDelivery {
+            id: Id::new(),
+            subscription_id: subscription.id,
+            event,
+            payload: payload.clone(),
+            created_at,
+            attempts: 0,
+            last_attempt_at: None,
+            last_status: None,
+            last_error: None,
+            delivered_at: None,
+        };
+        connection.create_webhook_delivery(&delivery)?;
+        deliveries.push(delivery);
+    }
+    Ok(deliveries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::prelude::*;
+    use crate::{
+        adapters::json, infrastructure::jobs::JobWorkerPool, ports::web::tests::DummyNotifyGW,
+    };
+    use ofdb_gateways::webhook::{signature, DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER};
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::{mpsc, Arc},
+        thread,
+        time::Duration,
+    };
+
+    struct ReceivedRequest {
+        headers: Vec<(String, String)>,
+        body: String,
+    }
+
+    impl ReceivedRequest {
+        fn header(&self, name: &str) -> Option<&str> {
+            self.headers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.as_str())
+        }
+    }
+
+    // A local HTTP server that stands in for the webhook of an
+    // organization and answers requests with the given status codes.
+    fn start_webhook_stand_in(statuses: Vec<u16>) -> (String, mpsc::Receiver<ReceivedRequest>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for status in statuses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut headers = vec![];
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some(pos) = line.find(':') {
+                        let (key, value) = line.split_at(pos);
+                        headers.push((key.trim().to_string(), value[1..].trim().to_string()));
+                    }
+                }
+                let content_length = headers
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case("content-length"))
+                    .map(|(_, value)| value.parse().unwrap())
+                    .unwrap_or(0);
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Stand-In\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                )
+                .unwrap();
+                let body = String::from_utf8(body).unwrap();
+                if tx.send(ReceivedRequest { headers, body }).is_err() {
+                    break;
+                }
+            }
+        });
+        (url, rx)
+    }
+
+    fn create_org(fixture: &BackendFixture, id: &str) -> Organization {
+        let org = Organization {
+            id: id.into(),
+            name: id.into(),
+            moderated_tags: vec![],
+            api_token: id.into(),
+        };
+        fixture
+            .db_connections
+            .exclusive()
+            .unwrap()
+            .create_org(org.clone())
+            .unwrap();
+        org
+    }
+
+    // The local stand-in would be rejected when creating
+    // the subscription by the usecase.
+    fn create_subscription(
+        fixture: &BackendFixture,
+        org: &Organization,
+        url: String,
+        bbox: Option<MapBbox>,
+        tags: Vec<String>,
+    ) -> WebhookSubscription {
+        let subscription = WebhookSubscription {
+            id: Id::new(),
+            org_id: org.id.clone(),
+            url: url.parse().unwrap(),
+            secret: Nonce::new().to_string(),
+            bbox,
+            tags,
+            created_at: TimestampMs::now(),
+        };
+        fixture
+            .db_connections
+            .exclusive()
+            .unwrap()
+            .create_webhook_subscription(&subscription)
+            .unwrap();
+        subscription
+    }
+
+    fn recent_deliveries(fixture: &BackendFixture, id: &Id) -> Vec<WebhookDelivery> {
+        fixture
+            .db_connections
+            .shared()
+            .unwrap()
+            .recent_webhook_deliveries(id, &Default::default())
+            .unwrap()
+    }
+
+    #[test]
+    fn should_retry_and_log_signed_deliveries_of_new_places() {
+        let fixture = BackendFixture::new();
+        let org = create_org(&fixture, "org");
+        let (url, requests) = start_webhook_stand_in(vec![500, 200]);
+        let new_place = NewPlace::from(1);
+        let bbox = MapBbox::centered_around(
+            new_place.pos,
+            Distance::from_meters(1_000.0),
+            Distance::from_meters(1_000.0),
+        );
+        let in_scope = create_subscription(&fixture, &org, url.clone(), Some(bbox), vec![]);
+        let out_of_scope =
+            create_subscription(&fixture, &org, url, Some(bbox), vec!["other-tag".into()]);
+
+        // Failed deliveries are retried by the background workers
+        let _job_workers = JobWorkerPool::start(
+            fixture.db_connections.clone(),
+            fixture.search_engine.borrow().clone(),
+            Arc::new(DummyNotifyGW),
+            1,
+        );
+
+        let place_id = fixture.create_place(new_place, None);
+
+        assert!(recent_deliveries(&fixture, &out_of_scope.id).is_empty());
+        let deliveries = recent_deliveries(&fixture, &in_scope.id);
+        assert_eq!(1, deliveries.len());
+        let delivery_id = deliveries[0].id.clone();
+
+        // The first attempt fails...
+        let request = requests.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(Some("place_added"), request.header(EVENT_HEADER));
+        assert_eq!(Some(delivery_id.as_str()), request.header(DELIVERY_HEADER));
+        // ...and the delivery is retried with the same payload
+        let retried_request = requests.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(request.body, retried_request.body);
+
+        assert_eq!(
+            Some(signature(&in_scope.secret, &request.body).as_str()),
+            request.header(SIGNATURE_HEADER)
+        );
+        let payload: json::WebhookPayload = serde_json::from_str(&request.body).unwrap();
+        match payload.data {
+            json::WebhookData::PlaceAdded(entry) => assert_eq!(place_id, entry.id),
+            _ => panic!("unexpected payload"),
+        }
+
+        // Wait until the successful delivery has been logged
+        let mut delivery = None;
+        for _ in 0..100 {
+            let d = recent_deliveries(&fixture, &in_scope.id).remove(0);
+            if d.delivered_at.is_some() {
+                delivery = Some(d);
+                break;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        let delivery = delivery.unwrap();
+        assert_eq!(delivery_id, delivery.id);
+        assert_eq!(WebhookEvent::PlaceAdded, delivery.event);
+        assert_eq!(2, delivery.attempts);
+        assert_eq!(Some(200), delivery.last_status);
+        assert!(delivery.last_error.is_none());
+        assert_eq!(request.body, delivery.payload);
+    }
+
+    #[test]
+    fn should_only_accept_https_webhooks_of_public_hosts() {
+        let fixture = BackendFixture::new();
+        let org = create_org(&fixture, "org");
+        let db = fixture.db_connections.exclusive().unwrap();
+        let new_subscription = |url: &str| usecases::NewWebhookSubscription {
+            url: url.into(),
+            bbox: None,
+            tags: vec![],
+        };
+        for url in &[
+            "http://example.com/hook",
+            "https://localhost/hook",
+            "https://127.0.0.1/hook",
+            "https://192.168.1.1/hook",
+        ] {
+            assert!(
+                usecases::create_webhook_subscription(&*db, &org, new_subscription(url)).is_err()
+            );
+        }
+        let subscription = usecases::create_webhook_subscription(
+            &*db,
+            &org,
+            new_subscription("https://example.com/hook"),
+        )
+        .unwrap();
+        assert_eq!(
+            vec![subscription.clone()],
+            db.all_webhook_subscriptions_by_org(&org.id).unwrap()
+        );
+        usecases::delete_webhook_subscription(&*db, &org, &subscription.id).unwrap();
+        assert!(db.all_webhook_subscriptions().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,504 @@
+//! A persistent queue of background jobs.
+//!
+//! Jobs are stored in the database and executed by a pool of
+//! worker threads outside of the request path. Failed jobs are
+//! retried with an exponential backoff and finally marked as
+//! failed for later inspection.
+//!
+//! Submitted jobs are always enqueued as pending and then handed
+//! to the installed executor. The server refuses to start without
+//! an installed worker pool. Jobs that are submitted by commands
+//! remain pending until the workers of the server pick them up.
+
+use super::{db, error::AppError, GEO_CODING_GW, WEBHOOK_GW};
+use crate::core::{prelude::*, usecases};
+use anyhow::anyhow;
+use ofdb_core::gateways::{
+    geocode::GeoCodingGateway,
+    notify::NotificationGateway,
+    webhook::{WebhookGateway, WebhookRequest},
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+mod pool;
+
+pub use pool::JobWorkerPool;
+
+type Result<T> = std::result::Result<T, AppError>;
+
+// Failed jobs are retried with an exponential backoff,
+// i.e. after 1, 2, 4, 8 seconds.
+const MAX_ATTEMPTS: u32 = 5;
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+// Subscriptions are checked periodically for due digests.
+const SUBSCRIPTION_DIGEST_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JobTask {
+    ReindexPlace {
+        place_id: String,
+    },
+    ReindexEvent {
+        event_id: String,
+    },
+    NotifyPlaceAdded {
+        place_id: String,
+    },
+    NotifyPlaceUpdated {
+        place_id: String,
+    },
+    NotifyEventCreated {
+        event_id: String,
+    },
+    NotifyEventUpdated {
+        event_id: String,
+    },
+    /// Resolve the location of an event from its address
+    /// before indexing it and sending subscription e-mails.
+    GeocodeEvent {
+        event_id: String,
+        created: bool,
+    },
+    DeliverWebhook {
+        delivery_id: String,
+    },
+    /// Send the digests of all subscriptions that are due and
+    /// reschedule itself.
+    SendSubscriptionDigests,
+}
+
+impl JobTask {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::ReindexPlace { .. } => "reindex_place",
+            Self::ReindexEvent { .. } => "reindex_event",
+            Self::NotifyPlaceAdded { .. } => "notify_place_added",
+            Self::NotifyPlaceUpdated { .. } => "notify_place_updated",
+            Self::NotifyEventCreated { .. } => "notify_event_created",
+            Self::NotifyEventUpdated { .. } => "notify_event_updated",
+            Self::GeocodeEvent { .. } => "geocode_event",
+            Self::DeliverWebhook { .. } => "deliver_webhook",
+            Self::SendSubscriptionDigests => "send_subscription_digests",
+        }
+    }
+}
+
+/// The resources that are needed for executing jobs.
+pub struct JobContext<'a> {
+    pub connections: &'a db::Connections,
+    pub indexer: &'a mut dyn EventAndPlaceIndexer,
+    /// Jobs that send e-mails fail without a gateway
+    pub notify: Option<&'a dyn NotificationGateway>,
+}
+
+/// Executes the submitted jobs.
+#[derive(Clone)]
+enum JobExecutor {
+    /// The workers of a pool pick up the jobs
+    WorkerPool(Arc<pool::Signal>),
+    /// All due jobs are executed immediately by the caller
+    Inline,
+}
+
+lazy_static! {
+    static ref INSTALLED_EXECUTOR: Mutex<Option<JobExecutor>> = Mutex::new(None);
+}
+
+fn installed_executor() -> Option<JobExecutor> {
+    INSTALLED_EXECUTOR.lock().unwrap().clone()
+}
+
+fn install_executor(executor: JobExecutor) {
+    *INSTALLED_EXECUTOR.lock().unwrap() = Some(executor);
+}
+
+/// Execute submitted jobs immediately by the caller instead
+/// of waiting for the workers of a pool, e.g. for testing.
+pub fn install_inline_executor() {
+    install_executor(JobExecutor::Inline);
+}
+
+/// Submit tasks for execution in the background.
+///
+/// Errors are only logged and never affect the caller.
+pub fn submit(ctx: &mut JobContext, tasks: Vec<JobTask>) {
+    submit_to(installed_executor().as_ref(), ctx, tasks);
+}
+
+fn submit_to(executor: Option<&JobExecutor>, ctx: &mut JobContext, tasks: Vec<JobTask>) {
+    if tasks.is_empty() {
+        return;
+    }
+    for task in &tasks {
+        if let Err(err) = enqueue(ctx.connections, task, JobStatus::Pending) {
+            error!("Failed to enqueue {} job: {}", task.kind(), err);
+        }
+    }
+    match executor {
+        Some(JobExecutor::WorkerPool(signal)) => signal.wake(),
+        Some(JobExecutor::Inline) => run_due_jobs(ctx),
+        None => info!(
+            "No background job workers installed: {} submitted jobs remain pending",
+            tasks.len()
+        ),
+    }
+}
+
+/// Check that submitted jobs will be executed.
+pub fn is_worker_pool_installed() -> bool {
+    matches!(installed_executor(), Some(JobExecutor::WorkerPool(_)))
+}
+
+// Pending jobs that have not been submitted by the caller
+// are only picked up by the workers of a pool.
+fn wake_installed_worker_pool() {
+    if let Some(JobExecutor::WorkerPool(signal)) = installed_executor() {
+        signal.wake();
+    }
+}
+
+/// Reschedule all failed jobs and return their number.
+pub fn retry_failed_jobs(connections: &db::Connections) -> Result<usize> {
+    let count = connections
+        .exclusive()?
+        .retry_failed_jobs(TimestampMs::now())?;
+    wake_installed_worker_pool();
+    Ok(count)
+}
+
+/// Schedule the periodic sending of subscription digests
+/// unless it is already pending.
+pub fn schedule_subscription_digests(connections: &db::Connections) -> Result<()> {
+    let task = JobTask::SendSubscriptionDigests;
+    let pending = connections
+        .shared()?
+        .count_jobs_by_kind(task.kind(), JobStatus::Pending)?;
+    if pending == 0 {
+        enqueue(connections, &task, JobStatus::Pending)?;
+        wake_installed_worker_pool();
+    }
+    Ok(())
+}
+
+fn enqueue(connections: &db::Connections, task: &JobTask, status: JobStatus) -> Result<Job> {
+    enqueue_at(connections, task, status, TimestampMs::now())
+}
+
+fn enqueue_at(
+    connections: &db::Connections,
+    task: &JobTask,
+    status: JobStatus,
+    run_at: TimestampMs,
+) -> Result<Job> {
+    let job = Job {
+        id: Id::new(),
+        kind: task.kind().to_string(),
+        payload: serde_json::to_string(task)?,
+        status,
+        attempts: 0,
+        created_at: TimestampMs::now(),
+        run_at,
+        last_error: None,
+    };
+    connections.exclusive()?.enqueue_job(&job)?;
+    Ok(job)
+}
+
+/// Acquire and execute the next job that is due.
+///
+/// Returns `false` if no job is waiting for execution.
+fn run_next_job(ctx: &mut JobContext) -> Result<bool> {
+    let job = match ctx
+        .connections
+        .exclusive()?
+        .acquire_next_job(TimestampMs::now())?
+    {
+        Some(job) => job,
+        None => return Ok(false),
+    };
+    let result = serde_json::from_str::<JobTask>(&job.payload)
+        .map_err(AppError::from)
+        .and_then(|task| run_task(ctx, &task));
+    finish_job(ctx.connections, job, result);
+    Ok(true)
+}
+
+fn run_due_jobs(ctx: &mut JobContext) {
+    loop {
+        match run_next_job(ctx) {
+            Ok(true) => continue,
+            Ok(false) => break,
+            Err(err) => {
+                error!("Failed to run next job: {}", err);
+                break;
+            }
+        }
+    }
+}
+
+fn finish_job(connections: &db::Connections, mut job: Job, result: Result<()>) {
+    let finished = match result {
+        Ok(()) => delete_job(connections, &job.id),
+        Err(err) => {
+            job.attempts += 1;
+            job.last_error = Some(err.to_string());
+            if job.attempts < MAX_ATTEMPTS {
+                warn!(
+                    "Failed to execute {} job {} (attempt {}): {}",
+                    job.kind, job.id, job.attempts, err
+                );
+                let retry_delay = INITIAL_RETRY_DELAY * 2u32.pow(job.attempts - 1);
+                job.status = JobStatus::Pending;
+                job.run_at = TimestampMs::from_inner(
+                    TimestampMs::now().into_inner() + retry_delay.as_millis() as i64,
+                );
+            } else {
+                error!(
+                    "Giving up {} job {} after {} attempts: {}",
+                    job.kind, job.id, job.attempts, err
+                );
+                job.status = JobStatus::Failed;
+            }
+            update_job(connections, &job)
+        }
+    };
+    if let Err(err) = finished {
+        error!("Failed to finish {} job {}: {}", job.kind, job.id, err);
+    }
+}
+
+fn delete_job(connections: &db::Connections, id: &Id) -> Result<()> {
+    connections.exclusive()?.delete_job(id)?;
+    Ok(())
+}
+
+fn update_job(connections: &db::Connections, job: &Job) -> Result<()> {
+    connections.exclusive()?.update_job(job)?;
+    Ok(())
+}
+
+fn run_task(ctx: &mut JobContext, task: &JobTask) -> Result<()> {
+    match task {
+        JobTask::ReindexPlace { place_id } => reindex_place(ctx, place_id),
+        JobTask::ReindexEvent { event_id } => reindex_event(ctx, event_id),
+        JobTask::NotifyPlaceAdded { place_id } => notify_place_changed(ctx, place_id, true),
+        JobTask::NotifyPlaceUpdated { place_id } => notify_place_changed(ctx, place_id, false),
+        JobTask::NotifyEventCreated { event_id } => notify_event_changed(ctx, event_id, true),
+        JobTask::NotifyEventUpdated { event_id } => notify_event_changed(ctx, event_id, false),
+        JobTask::GeocodeEvent { event_id, created } => {
+            geocode_event(ctx, &*GEO_CODING_GW, event_id, *created)
+        }
+        JobTask::DeliverWebhook { delivery_id } => deliver_webhook(ctx, &*WEBHOOK_GW, delivery_id),
+        JobTask::SendSubscriptionDigests => send_subscription_digests(ctx),
+    }
+}
+
+fn notification_gateway<'a>(ctx: &JobContext<'a>) -> Result<&'a dyn NotificationGateway> {
+    ctx.notify
+        .ok_or_else(|| anyhow!("No notification gateway available").into())
+}
+
+fn reindex_place(ctx: &mut JobContext, place_id: &str) -> Result<()> {
+    let (place, status, ratings) = {
+        let db = ctx.connections.shared()?;
+        let (place, status) = match db.get_place(place_id) {
+            Ok(place) => place,
+            Err(RepoError::NotFound) => {
+                info!("Place {} not found for reindexing", place_id);
+                return Ok(());
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let ratings = db.load_ratings_of_place(place_id)?;
+        (place, status, ratings)
+    };
+    usecases::reindex_place(&*ctx.indexer, &place, status, &ratings)?;
+    ctx.indexer.flush_index()?;
+    Ok(())
+}
+
+fn reindex_event(ctx: &mut JobContext, event_id: &str) -> Result<()> {
+    let event = match ctx.connections.shared()?.get_event(event_id) {
+        Ok(event) => event,
+        Err(RepoError::NotFound) => {
+            info!("Event {} not found for reindexing", event_id);
+            return Ok(());
+        }
+        Err(err) => return Err(err.into()),
+    };
+    usecases::index_event(&*ctx.indexer, &event)?;
+    ctx.indexer.flush_index()?;
+    Ok(())
+}
+
+fn notify_place_changed(ctx: &JobContext, place_id: &str, added: bool) -> Result<()> {
+    let notify = notification_gateway(ctx)?;
+    let (place, subscriptions, all_categories) = {
+        let db = ctx.connections.shared()?;
+        let (place, _) = db.get_place(place_id)?;
+        let subscriptions = usecases::subscriptions_for_place(&*db, &place)?;
+        let all_categories = db.all_categories()?;
+        (place, subscriptions, all_categories)
+    };
+    if added {
+        notify.place_added(&subscriptions, &place, all_categories);
+    } else {
+        notify.place_updated(&subscriptions, &place, all_categories);
+    }
+    Ok(())
+}
+
+fn notify_event_changed(ctx: &JobContext, event_id: &str, created: bool) -> Result<()> {
+    let notify = notification_gateway(ctx)?;
+    let (event, subscriptions) = {
+        let db = ctx.connections.shared()?;
+        let event = db.get_event(event_id)?;
+        let subscriptions = usecases::subscriptions_for_event(&*db, &event)?;
+        (event, subscriptions)
+    };
+    if created {
+        notify.event_created(&subscriptions, &event);
+    } else {
+        notify.event_updated(&subscriptions, &event);
+    }
+    Ok(())
+}
+
+fn send_subscription_digests(ctx: &JobContext) -> Result<()> {
+    let notify = notification_gateway(ctx)?;
+    let now = TimestampMs::now();
+    let subscriptions = usecases::due_digest_subscriptions(&*ctx.connections.shared()?, now)?;
+    for subscription in subscriptions {
+        let digest =
+            usecases::collect_subscription_digest(&*ctx.connections.shared()?, subscription)?;
+        if !digest.is_empty() {
+            notify.subscription_digest(&digest.subscription, &digest.places, &digest.events);
+        }
+        usecases::finish_subscription_digest(
+            &*ctx.connections.exclusive()?,
+            digest.subscription,
+            now,
+        )?;
+    }
+    let run_at =
+        TimestampMs::from_inner(now.into_inner() + SUBSCRIPTION_DIGEST_INTERVAL.as_millis() as i64);
+    enqueue_at(
+        ctx.connections,
+        &JobTask::SendSubscriptionDigests,
+        JobStatus::Pending,
+        run_at,
+    )?;
+    Ok(())
+}
+
+/// Check if the location of an event needs to be resolved
+/// from its address.
+pub fn needs_geocoding(event: &Event) -> bool {
+    event
+        .location
+        .as_ref()
+        .map(|location| !location.pos.is_valid() && location.address.is_some())
+        .unwrap_or(false)
+}
+
+fn geocode_event(
+    ctx: &mut JobContext,
+    geocoding: &dyn GeoCodingGateway,
+    event_id: &str,
+    created: bool,
+) -> Result<()> {
+    let mut event = ctx.connections.shared()?.get_event(event_id)?;
+    if needs_geocoding(&event) {
+        let location = event.location.as_mut().expect("location");
+        let resolved = location
+            .address
+            .as_ref()
+            .and_then(|addr| geocoding.resolve_address_lat_lng(addr))
+            .and_then(|(lat, lng)| MapPoint::try_from_lat_lng_deg(lat, lng).ok());
+        if let Some(pos) = resolved {
+            log::debug!("Updating location of event {} -> {:?}", event.id, pos);
+            location.pos = pos;
+            ctx.connections.exclusive()?.update_event(&event)?;
+        }
+    }
+    // Only index the event and send subscription e-mails
+    // after the location has been resolved
+    let event_id = event.id.to_string();
+    let notify_task = if created {
+        JobTask::NotifyEventCreated {
+            event_id: event_id.clone(),
+        }
+    } else {
+        JobTask::NotifyEventUpdated {
+            event_id: event_id.clone(),
+        }
+    };
+    reindex_event(ctx, &event_id)?;
+    submit(ctx, vec![notify_task]);
+    Ok(())
+}
+
+fn deliver_webhook(
+    ctx: &JobContext,
+    gateway: &dyn WebhookGateway,
+    delivery_id: &str,
+) -> Result<()> {
+    let (subscription, mut delivery) = {
+        let db = ctx.connections.shared()?;
+        let mut delivery = db.load_webhook_delivery(&delivery_id.into())?;
+        if delivery.delivered_at.is_some() {
+            return Ok(());
+        }
+        let subscription = match db.get_webhook_subscription(&delivery.subscription_id) {
+            Ok(subscription) => subscription,
+            Err(RepoError::NotFound) => {
+                info!("Webhook of delivery {} has been deleted", delivery.id);
+                return Ok(());
+            }
+            Err(err) => return Err(err.into()),
+        };
+        delivery.last_error = None;
+        (subscription, delivery)
+    };
+    let request = WebhookRequest {
+        url: &subscription.url,
+        secret: &subscription.secret,
+        event: delivery.event.as_str(),
+        delivery_id: delivery.id.as_str(),
+        payload: &delivery.payload,
+    };
+    let result = gateway.deliver(&request);
+    let now = TimestampMs::now();
+    delivery.attempts += 1;
+    delivery.last_attempt_at = Some(now);
+    match &result {
+        Ok(status) => {
+            delivery.last_status = Some(*status);
+            delivery.delivered_at = Some(now);
+        }
+        Err(err) => {
+            delivery.last_status = err.status();
+            delivery.last_error = Some(err.to_string());
+        }
+    }
+    ctx.connections
+        .exclusive()?
+        .update_webhook_delivery(&delivery)?;
+    result.map(|_| ()).map_err(|err| {
+        anyhow!(
+            "Failed to deliver {} to webhook {}: {}",
+            delivery.event,
+            subscription.url,
+            err
+        )
+        .into()
+    })
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,118 @@
+use super::*;
+use crate::infrastructure::db::tantivy::SearchEngine;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+// Pending jobs are polled periodically to pick up
+// retries that have become due.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Default)]
+pub(super) struct Signal {
+    woken: Mutex<bool>,
+    condvar: Condvar,
+    stopped: AtomicBool,
+}
+
+impl Signal {
+    pub(super) fn wake(&self) {
+        *self.woken.lock().unwrap() = true;
+        self.condvar.notify_all();
+    }
+
+    fn wait(&self, timeout: Duration) {
+        let mut woken = self.woken.lock().unwrap();
+        if !*woken {
+            woken = self.condvar.wait_timeout(woken, timeout).unwrap().0;
+        }
+        *woken = false;
+    }
+
+    fn stop(&self) {
+        self.stopped.store(true, Ordering::Release);
+        self.wake();
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Acquire)
+    }
+}
+
+/// A pool of worker threads that execute the jobs
+/// of the queue.
+///
+/// The workers are stopped when the pool is dropped.
+pub struct JobWorkerPool {
+    signal: Arc<Signal>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl JobWorkerPool {
+    pub fn start(
+        connections: db::Connections,
+        search_engine: SearchEngine,
+        notify: Arc<dyn NotificationGateway + Send + Sync>,
+        worker_count: usize,
+    ) -> Self {
+        let signal = Arc::new(Signal::default());
+        let workers = (0..worker_count.max(1))
+            .map(|_| {
+                let connections = connections.clone();
+                let mut search_engine = search_engine.clone();
+                let notify = Arc::clone(&notify);
+                let signal = Arc::clone(&signal);
+                thread::spawn(move || {
+                    let mut ctx = JobContext {
+                        connections: &connections,
+                        indexer: &mut search_engine,
+                        notify: Some(&*notify),
+                    };
+                    while !signal.is_stopped() {
+                        match run_next_job(&mut ctx) {
+                            Ok(true) => continue,
+                            Ok(false) => {}
+                            Err(err) => error!("Failed to run next job: {}", err),
+                        }
+                        signal.wait(POLL_INTERVAL);
+                    }
+                })
+            })
+            .collect();
+        Self { signal, workers }
+    }
+
+    /// Route all submitted jobs to this pool instead of
+    /// executing them immediately.
+    pub fn install(&self) {
+        install_executor(JobExecutor::WorkerPool(Arc::clone(&self.signal)));
+    }
+
+    /// Look for pending jobs without waiting for the next poll.
+    pub fn wake(&self) {
+        self.signal.wake();
+    }
+}
+
+impl Drop for JobWorkerPool {
+    fn drop(&mut self) {
+        {
+            let mut installed = INSTALLED_EXECUTOR.lock().unwrap();
+            if let Some(JobExecutor::WorkerPool(signal)) = &*installed {
+                if Arc::ptr_eq(signal, &self.signal) {
+                    *installed = None;
+                }
+            }
+        }
+        self.signal.stop();
+        for worker in self.workers.drain(..) {
+            if worker.join().is_err() {
+                error!("Job worker panicked");
+            }
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use super::*;
+use crate::{
+    core::util::geo::MapBbox,
+    infrastructure::flows::tests::prelude::{BackendFixture, NewPlace},
+    ports::web::tests::DummyNotifyGW,
+};
+use std::{cell::RefCell, sync::Arc, thread};
+
+fn count_jobs(fixture: &BackendFixture, status: JobStatus) -> usize {
+    fixture
+        .db_connections
+        .shared()
+        .unwrap()
+        .count_jobs(status)
+        .unwrap()
+}
+
+fn load_jobs(fixture: &BackendFixture, status: JobStatus) -> Vec<Job> {
+    fixture
+        .db_connections
+        .shared()
+        .unwrap()
+        .load_jobs(status, &Default::default())
+        .unwrap()
+}
+
+#[test]
+fn should_enqueue_submitted_jobs_as_pending_without_executor() {
+    let fixture = BackendFixture::new();
+    let place_id = {
+        let db = fixture.db_connections.exclusive().unwrap();
+        let storable =
+            usecases::prepare_new_place(&*db, NewPlace::from(1).into(), None, None).unwrap();
+        usecases::store_new_place(&*db, storable).unwrap().0.id
+    };
+    let mut search_engine = fixture.search_engine.borrow().clone();
+    let mut ctx = JobContext {
+        connections: &fixture.db_connections,
+        indexer: &mut search_engine,
+        notify: None,
+    };
+    submit_to(
+        None,
+        &mut ctx,
+        vec![JobTask::ReindexPlace {
+            place_id: place_id.to_string(),
+        }],
+    );
+    assert!(fixture.query_places_by_tag("tag-1").is_empty());
+    let pending = load_jobs(&fixture, JobStatus::Pending);
+    assert_eq!(1, pending.len());
+    assert_eq!("reindex_place", pending[0].kind);
+    assert!(pending.iter().all(|job| job.attempts == 0));
+    assert_eq!(0, count_jobs(&fixture, JobStatus::Running));
+    assert_eq!(0, count_jobs(&fixture, JobStatus::Failed));
+}
+
+#[test]
+fn should_execute_submitted_jobs_with_inline_executor() {
+    let fixture = BackendFixture::new();
+    fixture.create_place(NewPlace::from(1), None);
+    assert_eq!(1, fixture.query_places_by_tag("tag-1").len());
+    assert_eq!(0, count_jobs(&fixture, JobStatus::Pending));
+    assert_eq!(0, count_jobs(&fixture, JobStatus::Running));
+    assert_eq!(0, count_jobs(&fixture, JobStatus::Failed));
+}
+
+#[test]
+fn should_retry_failed_jobs_with_backoff_until_giving_up() {
+    let fixture = BackendFixture::new();
+    let mut search_engine = fixture.search_engine.borrow().clone();
+    let mut ctx = JobContext {
+        connections: &fixture.db_connections,
+        indexer: &mut search_engine,
+        notify: None,
+    };
+    submit(
+        &mut ctx,
+        vec![JobTask::DeliverWebhook {
+            delivery_id: "unknown".into(),
+        }],
+    );
+
+    let pending = load_jobs(&fixture, JobStatus::Pending);
+    assert_eq!(1, pending.len());
+    assert_eq!("deliver_webhook", pending[0].kind);
+    assert_eq!(1, pending[0].attempts);
+    assert!(pending[0].last_error.is_some());
+    assert!(pending[0].run_at > pending[0].created_at);
+    // Not yet due
+    assert!(fixture
+        .db_connections
+        .exclusive()
+        .unwrap()
+        .acquire_next_job(pending[0].created_at)
+        .unwrap()
+        .is_none());
+
+    for attempt in 2..=MAX_ATTEMPTS {
+        let job = fixture
+            .db_connections
+            .exclusive()
+            .unwrap()
+            .acquire_next_job(TimestampMs::from_inner(i64::MAX))
+            .unwrap()
+            .unwrap();
+        assert_eq!(JobStatus::Running, job.status);
+        assert_eq!(attempt - 1, job.attempts);
+        let task = serde_json::from_str(&job.payload).unwrap();
+        let result = run_task(&mut ctx, &task);
+        assert!(result.is_err());
+        finish_job(&fixture.db_connections, job, result);
+    }
+    assert_eq!(0, count_jobs(&fixture, JobStatus::Pending));
+    let failed = load_jobs(&fixture, JobStatus::Failed);
+    assert_eq!(1, failed.len());
+    assert_eq!(MAX_ATTEMPTS, failed[0].attempts);
+
+    assert_eq!(1, retry_failed_jobs(&fixture.db_connections).unwrap());
+    assert_eq!(0, count_jobs(&fixture, JobStatus::Failed));
+    let pending = load_jobs(&fixture, JobStatus::Pending);
+    assert_eq!(1, pending.len());
+    assert_eq!(0, pending[0].attempts);
+}
+
+#[test]
+fn should_execute_pending_jobs_in_worker_pool() {
+    let fixture = BackendFixture::new();
+    let place = {
+        let db = fixture.db_connections.exclusive().unwrap();
+        let storable =
+            usecases::prepare_new_place(&*db, NewPlace::from(1).into(), None, None).unwrap();
+        usecases::store_new_place(&*db, storable).unwrap().0
+    };
+    enqueue(
+        &fixture.db_connections,
+        &JobTask::ReindexPlace {
+            place_id: place.id.to_string(),
+        },
+        JobStatus::Pending,
+    )
+    .unwrap();
+    assert!(fixture.query_places_by_tag("tag-1").is_empty());
+
+    let pool = JobWorkerPool::start(
+        fixture.db_connections.clone(),
+        fixture.search_engine.borrow().clone(),
+        Arc::new(DummyNotifyGW),
+        1,
+    );
+    pool.wake();
+    for _ in 0..100 {
+        if count_jobs(&fixture, JobStatus::Pending) + count_jobs(&fixture, JobStatus::Running) == 0
+        {
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    drop(pool);
+    assert_eq!(0, count_jobs(&fixture, JobStatus::Pending));
+    assert_eq!(1, fixture.query_places_by_tag("tag-1").len());
+}
+
+#[derive(Default)]
+struct DigestRecorder {
+    digests: RefCell<Vec<(String, Vec<String>, usize)>>,
+}
+
+impl NotificationGateway for DigestRecorder {
+    fn place_added(&self, _: &[BboxSubscription], _: &Place, _: Vec<Category>) {}
+    fn place_updated(&self, _: &[BboxSubscription], _: &Place, _: Vec<Category>) {}
+    fn event_created(&self, _: &[BboxSubscription], _: &Event) {}
+    fn event_updated(&self, _: &[BboxSubscription], _: &Event) {}
+    fn subscription_digest(
+        &self,
+        subscription: &BboxSubscription,
+        places: &[Place],
+        events: &[Event],
+    ) {
+        self.digests.borrow_mut().push((
+            subscription.user_email.clone(),
+            places.iter().map(|p| p.title.clone()).collect(),
+            events.len(),
+        ));
+    }
+    fn user_registered_kvm(&self, _: &User) {}
+    fn user_registered_ofdb(&self, _: &User) {}
+    fn user_registered(&self, _: &User, _: &str) {}
+    fn user_reset_password_requested(&self, _: &EmailNonce) {}
+}
+
+#[test]
+fn should_send_and_reschedule_subscription_digests() {
+    let fixture = BackendFixture::new();
+    let email = "digest@example.com";
+    fixture.create_user(
+        usecases::NewUser {
+            email: email.into(),
+            password: "secret123".into(),
+        },
+        None,
+    );
+    let bbox = MapBbox::new(
+        MapPoint::from_lat_lng_deg(0.0, -10.0),
+        MapPoint::from_lat_lng_deg(10.0, 0.0),
+    );
+    {
+        let db = fixture.db_connections.exclusive().unwrap();
+        usecases::subscribe_to_bbox(
+            &*db,
+            email.into(),
+            bbox,
+            usecases::BboxSubscriptionOptions {
+                tags: vec!["tag-1".into()],
+                delivery: SubscriptionDelivery::DailyDigest,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    }
+    let mut subscription = fixture
+        .db_connections
+        .shared()
+        .unwrap()
+        .all_bbox_subscriptions_by_email(email)
+        .unwrap()
+        .remove(0);
+    assert_eq!(vec!["tag-1".to_string()], subscription.tags);
+    let period = subscription.delivery.digest_period_ms().unwrap();
+    let last_digest_at = subscription.last_digest_at.unwrap();
+    subscription.last_digest_at = Some(TimestampMs::from_inner(
+        last_digest_at.into_inner() - period,
+    ));
+    fixture
+        .db_connections
+        .exclusive()
+        .unwrap()
+        .update_bbox_subscription(&subscription)
+        .unwrap();
+
+    // Inside the bbox with and without a matching tag
+    fixture.create_place(NewPlace::from(1), None);
+    fixture.create_place(NewPlace::from(2), None);
+
+    let recorder = DigestRecorder::default();
+    let mut search_engine = fixture.search_engine.borrow().clone();
+    let mut ctx = JobContext {
+        connections: &fixture.db_connections,
+        indexer: &mut search_engine,
+        notify: Some(&recorder),
+    };
+    run_task(&mut ctx, &JobTask::SendSubscriptionDigests).unwrap();
+    assert_eq!(
+        vec![(email.to_string(), vec!["Title 1".to_string()], 0)],
+        *recorder.digests.borrow()
+    );
+    let pending = load_jobs(&fixture, JobStatus::Pending);
+    assert_eq!(1, pending.len());
+    assert_eq!("send_subscription_digests", pending[0].kind);
+    assert!(pending[0].run_at > pending[0].created_at);
+
+    // The next digest is not yet due
+    run_task(&mut ctx, &JobTask::SendSubscriptionDigests).unwrap();
+    assert_eq!(1, recorder.digests.borrow().len());
+}
@@ -1,9 +1,10 @@
 pub mod db;
 pub mod error;
 pub mod flows;
+pub mod jobs;
 
 use ofdb_entities::email::*;
-use ofdb_gateways::{mailgun::*, opencage::*, sendmail::*};
+use ofdb_gateways::{mailgun::*, opencage::*, sendmail::*, webhook::*};
 use std::env;
 
 lazy_static! {
@@ -48,6 +49,8 @@ lazy_static! {
             None
         }
     };
+
+    pub static ref WEBHOOK_GW: Webhooks = Webhooks::new();
 }
 
 #[cfg(test)]
@@ -27,6 +27,13 @@ pub struct PlaceClearanceFixture {
     organization_with_add_remove_clearance_tag: Organization,
 }
 
+fn updated_revision(stored: usecases::StoredPlace) -> Place {
+    match stored {
+        usecases::StoredPlace::Revision(place, _) => place,
+        usecases::StoredPlace::Proposal(_) => panic!("Unexpected proposal"),
+    }
+}
+
 impl PlaceClearanceFixture {
     pub fn new() -> Self {
         let backend = flows::BackendFixture::new();
@@ -319,7 +326,7 @@ fn should_create_pending_clearance_once_when_updating_place_with_moderated_tags(
     let mut update_place = usecases::UpdatePlace::from(old_place.clone());
     update_place.version = new_revision.into();
     update_place.tags.push(tag.clone());
-    let new_place = flows::update_place(
+    let new_place = updated_revision(flows::update_place(
         &fixture.backend.db_connections,
         fixture.backend.search_engine.get_mut(),
         &fixture.backend.notify,
@@ -327,7 +334,8 @@ fn should_create_pending_clearance_once_when_updating_place_with_moderated_tags(
         update_place,
         None,
         None,
-    )?;
+        usecases::PlaceEditMode::Direct,
+    )?);
 
     assert_eq!(new_revision, new_place.revision);
     assert!(new_place.tags.contains(tag));
@@ -346,7 +354,7 @@ fn should_create_pending_clearance_once_when_updating_place_with_moderated_tags(
     let new_revision = new_revision.next();
     update_place.version = new_revision.into();
     update_place.tags = vec![];
-    let new_place = flows::update_place(
+    let new_place = updated_revision(flows::update_place(
         &fixture.backend.db_connections,
         fixture.backend.search_engine.get_mut(),
         &fixture.backend.notify,
@@ -354,7 +362,8 @@ fn should_create_pending_clearance_once_when_updating_place_with_moderated_tags(
         update_place,
         None,
         None,
-    )?;
+        usecases::PlaceEditMode::Direct,
+    )?);
     assert_eq!(new_revision, new_place.revision);
     assert!(new_place.tags.is_empty());
     let pending_clearances = usecases::clearance::place::list_pending_clearances(
@@ -392,6 +401,7 @@ fn should_deny_adding_of_moderated_tag_to_place_if_not_allowed() -> flows::Resul
         update_place,
         None,
         None,
+        usecases::PlaceEditMode::Direct,
     )
     .is_err());
     // No pending clearances created
@@ -435,6 +445,7 @@ fn should_deny_removing_of_moderated_tag_from_place_if_not_allowed() -> flows::R
         update_place,
         None,
         None,
+        usecases::PlaceEditMode::Direct,
     )
     .is_err());
     // No pending clearances created
@@ -466,7 +477,7 @@ fn should_create_pending_clearance_when_updating_an_archived_place_with_moderate
     let mut update_place = usecases::UpdatePlace::from(old_place.clone());
     update_place.version = new_revision.into();
     update_place.tags.push(tag.clone());
-    let new_place = flows::update_place(
+    let new_place = updated_revision(flows::update_place(
         &fixture.backend.db_connections,
         fixture.backend.search_engine.get_mut(),
         &fixture.backend.notify,
@@ -474,7 +485,8 @@ fn should_create_pending_clearance_when_updating_an_archived_place_with_moderate
         update_place,
         None,
         None,
-    )?;
+        usecases::PlaceEditMode::Direct,
+    )?);
 
     assert_eq!(new_revision, new_place.revision);
     assert!(new_place.tags.contains(tag));
@@ -517,7 +529,7 @@ fn should_return_the_last_cleared_revision_when_loading_or_searching_cleared_pla
     let mut update_place = usecases::UpdatePlace::from(old_place.clone());
     update_place.title = new_title.clone();
     update_place.version = new_revision.into();
-    let new_place = flows::update_place(
+    let new_place = updated_revision(flows::update_place(
         &fixture.backend.db_connections,
         fixture.backend.search_engine.get_mut(),
         &fixture.backend.notify,
@@ -525,7 +537,8 @@ fn should_return_the_last_cleared_revision_when_loading_or_searching_cleared_pla
         update_place,
         None,
         None,
-    )?;
+        usecases::PlaceEditMode::Direct,
+    )?);
 
     assert_eq!(new_revision, new_place.revision);
     let pending_clearances = usecases::clearance::place::list_pending_clearances(
@@ -726,7 +739,7 @@ fn should_hide_untagged_cleared_revision_when_loading_or_searching_for_cleared_p
     update_place.title = new_title.clone();
     update_place.tags = new_tags.clone();
     update_place.version = new_revision.into();
-    let new_place = flows::update_place(
+    let new_place = updated_revision(flows::update_place(
         &fixture.backend.db_connections,
         fixture.backend.search_engine.get_mut(),
         &fixture.backend.notify,
@@ -734,7 +747,8 @@ fn should_hide_untagged_cleared_revision_when_loading_or_searching_for_cleared_p
         update_place,
         None,
         None,
-    )?;
+        usecases::PlaceEditMode::Direct,
+    )?);
 
     assert_eq!(new_revision, new_place.revision);
     assert!(new_place.tags.contains(tag));
@@ -815,7 +829,7 @@ fn should_fail_when_trying_to_clear_future_revisions_of_places() -> flows::Resul
     update_place.title = new_title.clone();
     update_place.tags = new_tags.clone();
     update_place.version = new_revision.into();
-    let new_place = flows::update_place(
+    let new_place = updated_revision(flows::update_place(
         &fixture.backend.db_connections,
         fixture.backend.search_engine.get_mut(),
         &fixture.backend.notify,
@@ -823,7 +837,8 @@ fn should_fail_when_trying_to_clear_future_revisions_of_places() -> flows::Resul
         update_place,
         None,
         None,
-    )?;
+        usecases::PlaceEditMode::Direct,
+    )?);
 
     assert_eq!(new_revision, new_place.revision);
     assert!(new_place.tags.contains(tag));
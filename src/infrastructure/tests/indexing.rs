@@ -0,0 +1,332 @@
+use super::*;
+use crate::infrastructure::flows::tests::prelude::NewPlace;
+
+fn store_place_without_indexing(fixture: &flows::BackendFixture, new_place: NewPlace) -> Id {
+    let db = fixture.db_connections.exclusive().unwrap();
+    let storable = usecases::prepare_new_place(&*db, new_place.into(), None, None).unwrap();
+    usecases::store_new_place(&*db, storable).unwrap().0.id
+}
+
+fn synchronize_index(fixture: &flows::BackendFixture) {
+    usecases::synchronize_index(
+        &*fixture.db_connections.shared().unwrap(),
+        &mut *fixture.search_engine.borrow_mut(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn should_rebuild_index_without_high_water_mark() {
+    let fixture = flows::BackendFixture::new();
+    store_place_without_indexing(&fixture, NewPlace::from(1));
+    store_place_without_indexing(&fixture, NewPlace::from(2));
+    assert!(fixture.search_engine.borrow().high_water_mark().is_none());
+    assert!(fixture.query_places_by_tag("tag-1").is_empty());
+
+    synchronize_index(&fixture);
+
+    assert!(fixture.search_engine.borrow().high_water_mark().is_some());
+    assert_eq!(1, fixture.query_places_by_tag("tag-1").len());
+    assert_eq!(1, fixture.query_places_by_tag("tag-2").len());
+}
+
+#[test]
+fn should_only_reindex_places_changed_since_high_water_mark() {
+    let fixture = flows::BackendFixture::new();
+    synchronize_index(&fixture);
+    let high_water_mark = fixture.search_engine.borrow().high_water_mark().unwrap();
+
+    store_place_without_indexing(&fixture, NewPlace::from(1));
+    synchronize_index(&fixture);
+    assert_eq!(1, fixture.query_places_by_tag("tag-1").len());
+    assert!(fixture.search_engine.borrow().high_water_mark().unwrap() >= high_water_mark);
+
+    // Changes before the high-water mark are not reindexed
+    store_place_without_indexing(&fixture, NewPlace::from(2));
+    fixture
+        .search_engine
+        .borrow_mut()
+        .set_high_water_mark(TimestampMs::from_inner(
+            TimestampMs::now().into_inner() + 60_000,
+        ));
+    synchronize_index(&fixture);
+    assert!(fixture.query_places_by_tag("tag-2").is_empty());
+}
+
+#[test]
+fn should_detect_places_rated_since_high_water_mark() {
+    let fixture = flows::BackendFixture::new();
+    let place_id = store_place_without_indexing(&fixture, NewPlace::from(1));
+    let since = TimestampMs::from_inner(TimestampMs::now().into_inner() + 1);
+    let changed_place_ids = || {
+        fixture
+            .db_connections
+            .shared()
+            .unwrap()
+            .changed_place_ids_since(since)
+            .unwrap()
+    };
+    assert!(changed_place_ids().is_empty());
+
+    let db = fixture.db_connections.exclusive().unwrap();
+    let storable = usecases::prepare_new_rating(
+        &*db,
+        usecases::NewPlaceRating {
+            entry: place_id.to_string(),
+            title: "title".into(),
+            value: 1.into(),
+            context: ofdb_boundary::RatingContext::Diversity,
+            comment: "comment".into(),
+            source: None,
+            user: None,
+        },
+    )
+    .unwrap();
+    usecases::store_new_rating(&*db, storable).unwrap();
+    drop(db);
+
+    assert_eq!(vec![place_id], changed_place_ids());
+}
+
+#[test]
+fn should_remove_archived_events_when_updating_index() {
+    let fixture = flows::BackendFixture::new();
+    let event = Event {
+        id: Id::new(),
+        title: "event".into(),
+        description: None,
+        start: chrono::Utc::now().naive_utc(),
+        end: None,
+        time_zone: Tz::UTC,
+        location: None,
+        contact: None,
+        tags: vec![],
+        homepage: None,
+        created_by: None,
+        created_at: None,
+        registration: None,
+        archived: None,
+        image_url: None,
+        image_link_url: None,
+        recurrence: None,
+        recurrence_id: None,
+    };
+    fixture
+        .db_connections
+        .exclusive()
+        .unwrap()
+        .create_event(event.clone())
+        .unwrap();
+    synchronize_index(&fixture);
+    let query = IndexQuery {
+        text: Some("event".into()),
+        ..Default::default()
+    };
+    let query_event_ids = || {
+        fixture
+            .search_engine
+            .borrow()
+            .query_ids(IndexQueryMode::WithoutRating, &query, 10)
+            .unwrap()
+    };
+    assert_eq!(vec![event.id.clone()], query_event_ids());
+
+    fixture
+        .db_connections
+        .exclusive()
+        .unwrap()
+        .archive_events(&[event.id.as_str()], Timestamp::now())
+        .unwrap();
+    synchronize_index(&fixture);
+    assert!(query_event_ids().is_empty());
+}
+
+#[test]
+fn should_remove_deleted_events_when_updating_index() {
+    let fixture = flows::BackendFixture::new();
+    let event = Event {
+        id: Id::new(),
+        title: "event".into(),
+        description: None,
+        start: chrono::Utc::now().naive_utc(),
+        end: None,
+        time_zone: Tz::UTC,
+        location: None,
+        contact: None,
+        tags: vec![],
+        homepage: None,
+        created_by: None,
+        created_at: None,
+        registration: None,
+        archived: None,
+        image_url: None,
+        image_link_url: None,
+        recurrence: None,
+        recurrence_id: None,
+    };
+    fixture
+        .db_connections
+        .exclusive()
+        .unwrap()
+        .create_event(event.clone())
+        .unwrap();
+    synchronize_index(&fixture);
+    let query = IndexQuery {
+        text: Some("event".into()),
+        ..Default::default()
+    };
+    let query_event_ids = || {
+        fixture
+            .search_engine
+            .borrow()
+            .query_ids(IndexQueryMode::WithoutRating, &query, 10)
+            .unwrap()
+    };
+    assert_eq!(vec![event.id.clone()], query_event_ids());
+
+    // Deleted without removing the event from the index
+    assert_eq!(
+        Some(()),
+        fixture
+            .db_connections
+            .exclusive()
+            .unwrap()
+            .delete_event_with_matching_tags(event.id.as_str(), &[])
+            .unwrap()
+    );
+    assert_eq!(vec![event.id.clone()], query_event_ids());
+    synchronize_index(&fixture);
+    assert!(query_event_ids().is_empty());
+}
+
+#[test]
+fn should_detect_and_repair_inconsistent_places() {
+    let fixture = flows::BackendFixture::new();
+    let place_id = fixture.create_place(NewPlace::from(1), None);
+    let check_index = || {
+        usecases::check_index(
+            &*fixture.db_connections.shared().unwrap(),
+            &*fixture.search_engine.borrow(),
+        )
+        .unwrap()
+    };
+    assert!(check_index().is_empty());
+
+    let missing_place_id = store_place_without_indexing(&fixture, NewPlace::from(2));
+    fixture
+        .db_connections
+        .exclusive()
+        .unwrap()
+        .review_places(
+            &[place_id.as_str()],
+            ReviewStatus::Confirmed,
+            &ActivityLog {
+                activity: Activity::now(None),
+                context: None,
+                comment: None,
+            },
+        )
+        .unwrap();
+    let inconsistencies = check_index();
+    assert_eq!(2, inconsistencies.len());
+    assert!(
+        inconsistencies.contains(&usecases::IndexInconsistency::MissingPlace {
+            place_id: missing_place_id,
+        })
+    );
+    assert!(
+        inconsistencies.contains(&usecases::IndexInconsistency::WrongPlaceReviewStatus {
+            place_id: place_id.into(),
+            indexed: Some(ReviewStatus::Created),
+            current: ReviewStatus::Confirmed,
+        })
+    );
+
+    usecases::repair_index(
+        &*fixture.db_connections.shared().unwrap(),
+        &mut *fixture.search_engine.borrow_mut(),
+        &inconsistencies,
+    )
+    .unwrap();
+    assert!(check_index().is_empty());
+}
+
+#[test]
+fn should_detect_and_repair_inconsistent_events() {
+    let fixture = flows::BackendFixture::new();
+    let new_event = |title: &str| Event {
+        id: Id::new(),
+        title: title.into(),
+        description: None,
+        start: chrono::Utc::now().naive_utc(),
+        end: None,
+        time_zone: Tz::UTC,
+        location: None,
+        contact: None,
+        tags: vec![],
+        homepage: None,
+        created_by: None,
+        created_at: None,
+        registration: None,
+        archived: None,
+        image_url: None,
+        image_link_url: None,
+        recurrence: None,
+        recurrence_id: None,
+    };
+    let mut stale_event = new_event("stale");
+    let orphaned_event = new_event("orphaned");
+    for event in &[&stale_event, &orphaned_event] {
+        fixture
+            .db_connections
+            .exclusive()
+            .unwrap()
+            .create_event((*event).clone())
+            .unwrap();
+    }
+    synchronize_index(&fixture);
+    let check_index = || {
+        usecases::check_index(
+            &*fixture.db_connections.shared().unwrap(),
+            &*fixture.search_engine.borrow(),
+        )
+        .unwrap()
+    };
+    assert!(check_index().is_empty());
+
+    // Modify the database without updating the index
+    stale_event.title = "modified".into();
+    stale_event.end = Some(stale_event.start + chrono::Duration::hours(1));
+    fixture
+        .db_connections
+        .exclusive()
+        .unwrap()
+        .update_event(&stale_event)
+        .unwrap();
+    fixture
+        .db_connections
+        .exclusive()
+        .unwrap()
+        .archive_events(&[orphaned_event.id.as_str()], Timestamp::now())
+        .unwrap();
+    let inconsistencies = check_index();
+    assert_eq!(2, inconsistencies.len());
+    assert!(
+        inconsistencies.contains(&usecases::IndexInconsistency::StaleEvent {
+            event_id: stale_event.id.clone(),
+        })
+    );
+    assert!(
+        inconsistencies.contains(&usecases::IndexInconsistency::OrphanedEvent {
+            event_id: orphaned_event.id.clone(),
+        })
+    );
+
+    usecases::repair_index(
+        &*fixture.db_connections.shared().unwrap(),
+        &mut *fixture.search_engine.borrow_mut(),
+        &inconsistencies,
+    )
+    .unwrap();
+    assert!(check_index().is_empty());
+}
@@ -5,6 +5,7 @@ mod flows {
 }
 
 mod clearance;
+mod indexing;
 mod search;
 
 pub fn default_new_place() -> usecases::NewPlace {
@@ -39,11 +40,14 @@ fn default_search_request<'a>() -> usecases::SearchRequest<'a> {
             MapPoint::from_lat_lng_deg(-90, -180),
             MapPoint::from_lat_lng_deg(90, 180),
         ),
+        near: None,
+        polygon: None,
         org_tag: None,
         categories: vec![],
         hash_tags: vec![],
         ids: vec![],
         status: vec![],
         text: None,
+        open_at: None,
     }
 }
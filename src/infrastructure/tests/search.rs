@@ -146,3 +146,373 @@ fn should_find_places_by_tags() -> flows::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn should_find_places_near_a_position_ordered_by_distance() -> flows::Result<()> {
+    let fixture = flows::BackendFixture::new();
+
+    let create_place = |title: &str, lat: f64, lng: f64| {
+        flows::create_place(
+            &fixture.db_connections,
+            &mut *fixture.search_engine.borrow_mut(),
+            &fixture.notify,
+            usecases::NewPlace {
+                title: title.into(),
+                description: title.into(),
+                lat,
+                lng,
+                ..default_new_place()
+            },
+            None,
+            None,
+        )
+        .unwrap()
+    };
+    let far = create_place("far", 48.52, 9.0);
+    let near = create_place("near", 48.501, 9.0);
+    let nearer = create_place("nearer", 48.5, 9.001);
+    let outside = create_place("outside", 48.6, 9.0);
+
+    let circle = MapCircle::new(
+        MapPoint::from_lat_lng_deg(48.5, 9.0),
+        Distance::from_meters(5_000.0),
+    );
+    let (visible, invisible) = usecases::search(
+        &*fixture.db_connections.shared()?,
+        &*fixture.search_engine.borrow(),
+        usecases::SearchRequest {
+            bbox: circle.bounding_bbox(),
+            near: Some(circle),
+            ..default_search_request()
+        },
+        100,
+    )?;
+    let ids: Vec<Id> = visible.into_iter().map(|p| p.id.into()).collect();
+    assert_eq!(vec![nearer.id, near.id, far.id], ids);
+    assert!(!ids.contains(&outside.id));
+    assert!(invisible.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn should_find_places_within_a_polygon() -> flows::Result<()> {
+    let fixture = flows::BackendFixture::new();
+
+    let create_place = |title: &str, lat: f64, lng: f64| {
+        flows::create_place(
+            &fixture.db_connections,
+            &mut *fixture.search_engine.borrow_mut(),
+            &fixture.notify,
+            usecases::NewPlace {
+                title: title.into(),
+                description: title.into(),
+                lat,
+                lng,
+                ..default_new_place()
+            },
+            None,
+            None,
+        )
+        .unwrap()
+    };
+    let inside = create_place("inside", 48.2, 9.2);
+    // Within the bounding box of the triangle, but outside of it
+    let outside_triangle = create_place("outside triangle", 48.8, 9.8);
+    let in_hole = create_place("in hole", 48.28, 9.28);
+    let in_second_polygon = create_place("in second polygon", 50.5, 10.5);
+    let outside = create_place("outside", 47.0, 9.5);
+
+    let polygon: MapMultiPolygon = "MULTIPOLYGON (\
+        ((9 48, 10 48, 9 49, 9 48), (9.25 48.25, 9.35 48.25, 9.25 48.35, 9.25 48.25)), \
+        ((10 50, 11 50, 11 51, 10 51, 10 50)))"
+        .parse()
+        .unwrap();
+    let (visible, invisible) = usecases::search(
+        &*fixture.db_connections.shared()?,
+        &*fixture.search_engine.borrow(),
+        usecases::SearchRequest {
+            polygon: Some(polygon),
+            ..default_search_request()
+        },
+        100,
+    )?;
+    let ids: Vec<Id> = visible.into_iter().map(|p| p.id.into()).collect();
+    assert_eq!(2, ids.len());
+    assert!(ids.contains(&inside.id));
+    assert!(ids.contains(&in_second_polygon.id));
+    assert!(!ids.contains(&outside_triangle.id));
+    assert!(!ids.contains(&in_hole.id));
+    assert!(!ids.contains(&outside.id));
+    assert!(invisible.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn should_cluster_places_with_category_breakdown() -> flows::Result<()> {
+    let fixture = flows::BackendFixture::new();
+
+    let create_place = |title: &str, category: &str, lat: f64, lng: f64| {
+        flows::create_place(
+            &fixture.db_connections,
+            &mut *fixture.search_engine.borrow_mut(),
+            &fixture.notify,
+            usecases::NewPlace {
+                title: title.into(),
+                description: title.into(),
+                categories: vec![category.into()],
+                lat,
+                lng,
+                ..default_new_place()
+            },
+            None,
+            None,
+        )
+        .unwrap()
+    };
+    create_place("foo", Category::ID_NON_PROFIT, 48.78, 9.18);
+    create_place("bar", Category::ID_COMMERCIAL, 48.79, 9.19);
+    create_place("foo", Category::ID_NON_PROFIT, 52.52, 13.40);
+
+    let clusters = usecases::cluster_places(
+        &*fixture.search_engine.borrow(),
+        default_search_request(),
+        6,
+    )?;
+    assert_eq!(2, clusters.len());
+    // Ordered from north to south
+    assert_eq!(1, clusters[0].count);
+    assert_eq!(
+        vec![(Id::from(Category::ID_NON_PROFIT), 1)],
+        clusters[0].categories
+    );
+    assert_eq!(2, clusters[1].count);
+    assert_eq!(2, clusters[1].categories.len());
+    assert!(clusters[1]
+        .bbox
+        .contains_point(MapPoint::from_lat_lng_deg(48.785, 9.185)));
+
+    // Respect the filters of the search request
+    let clusters = usecases::cluster_places(
+        &*fixture.search_engine.borrow(),
+        usecases::SearchRequest {
+            text: Some("foo"),
+            ..default_search_request()
+        },
+        6,
+    )?;
+    assert_eq!(
+        vec![1, 1],
+        clusters.iter().map(|c| c.count).collect::<Vec<_>>()
+    );
+
+    assert!(usecases::cluster_places(
+        &*fixture.search_engine.borrow(),
+        default_search_request(),
+        23,
+    )
+    .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn should_find_places_despite_typos_in_title_and_city() -> flows::Result<()> {
+    let fixture = flows::BackendFixture::new();
+
+    let create_place = |title: &str, city: &str| {
+        flows::create_place(
+            &fixture.db_connections,
+            &mut *fixture.search_engine.borrow_mut(),
+            &fixture.notify,
+            usecases::NewPlace {
+                title: title.into(),
+                description: title.into(),
+                city: Some(city.into()),
+                ..default_new_place()
+            },
+            None,
+            None,
+        )
+        .unwrap()
+    };
+    let bioladen_leipzig = create_place("Bioladen", "Leipzig");
+    let bioladen_berlin = create_place("Bioladen", "Berlin");
+    let kiosk_leipzig = create_place("Kiosk", "Leipzig");
+
+    let search = |text: &str| -> flows::Result<Vec<Id>> {
+        let (visible, _) = usecases::search(
+            &*fixture.db_connections.shared()?,
+            &*fixture.search_engine.borrow(),
+            usecases::SearchRequest {
+                text: Some(text),
+                ..default_search_request()
+            },
+            100,
+        )?;
+        Ok(visible.into_iter().map(|p| p.id.into()).collect())
+    };
+
+    let ids = search("Bioladn Lepzig")?;
+    assert_eq!(3, ids.len());
+    // Places that match all terms come first
+    assert_eq!(bioladen_leipzig.id, ids[0]);
+
+    let ids = search("Bioladn")?;
+    assert_eq!(2, ids.len());
+    assert!(ids.contains(&bioladen_leipzig.id));
+    assert!(ids.contains(&bioladen_berlin.id));
+
+    // Short terms are matched exactly
+    assert!(search("Kio")?.is_empty());
+
+    // Explicit query syntax disables fuzzy matching
+    assert!(search("+Bioladn")?.is_empty());
+
+    let ids = search("Kiosk Lepzig")?;
+    assert_eq!(kiosk_leipzig.id, ids[0]);
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct PlaceFixture {
+    title: String,
+    description: String,
+    lat: f64,
+    lng: f64,
+    categories: Vec<String>,
+    tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct SearchResultsFixture {
+    visible: Vec<PlaceFixture>,
+    invisible: Vec<PlaceFixture>,
+}
+
+// Search terms and the title of the place that is expected
+// among the best matches
+type SearchTermsRegression<'a> = (&'a str, &'a str);
+
+fn assert_search_regressions(fixture_json: &str, regressions: &[SearchTermsRegression]) {
+    let fixture = flows::BackendFixture::new();
+    let SearchResultsFixture { visible, invisible } = serde_json::from_str(fixture_json).unwrap();
+    for place in visible.into_iter().chain(invisible.into_iter()) {
+        let PlaceFixture {
+            title,
+            description,
+            lat,
+            lng,
+            categories,
+            tags,
+        } = place;
+        flows::create_place(
+            &fixture.db_connections,
+            &mut *fixture.search_engine.borrow_mut(),
+            &fixture.notify,
+            usecases::NewPlace {
+                title,
+                description,
+                lat,
+                lng,
+                categories,
+                tags,
+                ..default_new_place()
+            },
+            None,
+            None,
+        )
+        .unwrap();
+    }
+
+    for &(text, expected_title) in regressions {
+        let (visible, _) = usecases::search(
+            &*fixture.db_connections.shared().unwrap(),
+            &*fixture.search_engine.borrow(),
+            usecases::SearchRequest {
+                text: Some(text),
+                ..default_search_request()
+            },
+            5,
+        )
+        .unwrap();
+        assert!(
+            visible.iter().any(|p| p.title == expected_title),
+            "'{}' not found when searching for '{}'",
+            expected_title,
+            text
+        );
+    }
+}
+
+#[test]
+fn should_find_places_of_search_results_fixture() {
+    assert_search_regressions(
+        include_str!("../../../search_results.json"),
+        &[
+            // Exact
+            ("Bioladen", "Bioladen Kollektiv onkel emma"),
+            ("Kartoffelkombinat", "Kartoffelkombinat eG"),
+            // Typos
+            ("Bioladn", "Bioladen Kollektiv onkel emma"),
+            ("Kartofelkombinat", "Kartoffelkombinat eG"),
+            ("Grundeinkomen Lepzig", "Initiative Grundeinkommen Leipzig"),
+            // German stemming
+            ("Bioläden", "Bioladen Kollektiv onkel emma"),
+            ("Teikei Gemeinschaften", "Teikei Gemeinschaft Münster"),
+            // English stemming
+            ("festivals", "Auerworld Festival"),
+        ],
+    );
+}
+
+#[test]
+fn should_find_places_of_tantivy_search_results_fixture() {
+    assert_search_regressions(
+        include_str!("../../../search_results_tantivy.json"),
+        &[
+            ("slowtec", "slowtec GmbH"),
+            ("slowtek", "slowtec GmbH"),
+            // Description contains "Lösungen"
+            ("Lösung", "slowtec GmbH"),
+        ],
+    );
+}
+
+#[test]
+fn should_suggest_frequent_terms_beyond_the_first_matching_places() {
+    use crate::infrastructure::db::tantivy::MAX_SUGGESTION_DOCS;
+
+    let fixture = flows::BackendFixture::new();
+    let mut search_engine = fixture.search_engine.borrow_mut();
+    for i in 0..MAX_SUGGESTION_DOCS {
+        let place = Place::build().title(&format!("Bioladen {}", i)).finish();
+        search_engine
+            .add_or_update_place(&place, ReviewStatus::Created, &Default::default())
+            .unwrap();
+    }
+    // Indexed after all places with a matching title
+    for i in 0..200 {
+        let place = Place::build()
+            .title(&format!("Hofladen {}", i))
+            .tags(vec!["biogemuese"])
+            .finish();
+        search_engine
+            .add_or_update_place(&place, ReviewStatus::Created, &Default::default())
+            .unwrap();
+    }
+    search_engine.flush_index().unwrap();
+    drop(search_engine);
+
+    let suggestions = usecases::suggest_search_terms(
+        &*fixture.search_engine.borrow(),
+        default_search_request(),
+        "bio",
+        5,
+    )
+    .unwrap();
+    assert_eq!(PlaceSuggestionKind::Tag, suggestions[0].kind);
+    assert_eq!("biogemuese", suggestions[0].text);
+}
@@ -1,22 +1,25 @@
 use crate::{
-    core::prelude::*,
+    adapters::{self, geojson},
+    core::{prelude::*, usecases},
     infrastructure::{
-        db::{sqlite, tantivy},
+        db::{self, tantivy},
+        error::AppError,
+        flows::prelude as flows,
         GEO_CODING_GW,
     },
     ports::web,
 };
 
-use clap::{crate_authors, App, Arg};
+use anyhow::Result as Fallible;
+use clap::{crate_authors, App, Arg, SubCommand};
 use dotenv::dotenv;
 use ofdb_core::gateways::geocode::GeoCodingGateway;
-use std::{env, path::Path};
+use std::{convert::TryFrom, env, fs, path::Path};
 
 const DEFAULT_DB_URL: &str = "openfair.db";
+const DEFAULT_TIME_ZONE: &str = "Europe/Berlin";
 const DB_CONNECTION_POOL_SIZE: u32 = 10;
 
-embed_migrations!();
-
 fn update_event_locations<D: Db>(db: &mut D) -> Result<()> {
     let events = db.all_events_chronologically()?;
     for mut e in events {
@@ -39,6 +42,192 @@ fn update_event_locations<D: Db>(db: &mut D) -> Result<()> {
     Ok(())
 }
 
+fn import_geojson_places(
+    connections: &db::Connections,
+    search_engine: &mut tantivy::SearchEngine,
+    path: &Path,
+    created_by_email: Option<&str>,
+    options: flows::ImportPlacesOptions,
+) -> Fallible<()> {
+    let json = fs::read_to_string(path)?;
+    let collection: geojson::FeatureCollection<geojson::PlaceProperties> =
+        serde_json::from_str(&json)?;
+    let new_places = collection
+        .features
+        .into_iter()
+        .map(|feature| {
+            usecases::NewPlace::try_from(feature)
+                .map_err(|err| AppError::from(Error::Parameter(err)))
+        })
+        .collect();
+    let reports = flows::import_places(
+        connections,
+        search_engine,
+        new_places,
+        created_by_email,
+        None,
+        options,
+    )?;
+    let labels = (0..reports.len()).map(|index| format!("Feature #{}", index));
+    print_import_reports(labels, reports);
+    Ok(())
+}
+
+fn import_csv_places(
+    connections: &db::Connections,
+    search_engine: &mut tantivy::SearchEngine,
+    path: &Path,
+    created_by_email: Option<&str>,
+    options: flows::ImportPlacesOptions,
+) -> Fallible<()> {
+    let (lines, new_places): (Vec<_>, Vec<_>) =
+        adapters::csv::read_place_records(fs::File::open(path)?)?
+            .into_iter()
+            .map(|(line, record)| {
+                let new_place = record.map_err(AppError::from).and_then(|record| {
+                    usecases::NewPlace::try_from(record)
+                        .map_err(|err| AppError::from(Error::Parameter(err)))
+                });
+                (line, new_place)
+            })
+            .unzip();
+    let reports = flows::import_places(
+        connections,
+        search_engine,
+        new_places,
+        created_by_email,
+        None,
+        options,
+    )?;
+    let labels = lines.into_iter().map(|line| format!("Line {}", line));
+    print_import_reports(labels, reports);
+    Ok(())
+}
+
+fn check_index(
+    connections: &db::Connections,
+    search_engine: &mut tantivy::SearchEngine,
+    repair: bool,
+) -> Fallible<()> {
+    let db = connections.shared()?;
+    let inconsistencies = usecases::check_index(&*db, search_engine)?;
+    for inconsistency in &inconsistencies {
+        match inconsistency {
+            usecases::IndexInconsistency::MissingPlace { place_id } => {
+                println!("Place {}: Missing", place_id);
+            }
+            usecases::IndexInconsistency::StalePlaceRevision {
+                place_id,
+                indexed,
+                current,
+            } => {
+                println!(
+                    "Place {}: Stale revision {:?} instead of {}",
+                    place_id,
+                    indexed.map(u64::from),
+                    u64::from(*current)
+                );
+            }
+            usecases::IndexInconsistency::WrongPlaceReviewStatus {
+                place_id,
+                indexed,
+                current,
+            } => {
+                println!(
+                    "Place {}: Wrong review status {:?} instead of {:?}",
+                    place_id, indexed, current
+                );
+            }
+            usecases::IndexInconsistency::OutdatedPlaceRatings {
+                place_id,
+                indexed,
+                current,
+            } => {
+                println!(
+                    "Place {}: Outdated ratings {:?} instead of {:?}",
+                    place_id, indexed, current
+                );
+            }
+            usecases::IndexInconsistency::OrphanedPlace { place_id } => {
+                println!("Place {}: Orphaned", place_id);
+            }
+            usecases::IndexInconsistency::MissingEvent { event_id } => {
+                println!("Event {}: Missing", event_id);
+            }
+            usecases::IndexInconsistency::StaleEvent { event_id } => {
+                println!("Event {}: Stale", event_id);
+            }
+            usecases::IndexInconsistency::OrphanedEvent { event_id } => {
+                println!("Event {}: Orphaned", event_id);
+            }
+        }
+    }
+    println!("Found {} inconsistencies", inconsistencies.len());
+    if repair && !inconsistencies.is_empty() {
+        usecases::repair_index(&*db, search_engine, &inconsistencies)?;
+        println!("Repaired the search index");
+    }
+    Ok(())
+}
+
+fn print_import_reports(
+    labels: impl Iterator<Item = String>,
+    reports: Vec<flows::PlaceImportReport>,
+) {
+    let mut imported_count = 0;
+    let mut skipped_count = 0;
+    let mut failed_count = 0;
+    for (label, report) in labels.zip(reports) {
+        let flows::PlaceImportReport { duplicates, result } = report;
+        match result {
+            Ok(Some(place)) => {
+                println!("{}: Imported place {}", label, place.id);
+                imported_count += 1;
+            }
+            Ok(None) => {
+                println!("{}: Not imported", label);
+                skipped_count += 1;
+            }
+            Err(err) => {
+                eprintln!("{}: {}", label, err);
+                failed_count += 1;
+            }
+        }
+        for duplicate in duplicates {
+            println!(
+                "{}: Likely duplicate of place {} ({})",
+                label, duplicate.id, duplicate.title
+            );
+        }
+    }
+    println!(
+        "Imported {} place(s), {} not imported, {} failed",
+        imported_count, skipped_count, failed_count
+    );
+}
+
+fn import_options(args: &clap::ArgMatches) -> flows::ImportPlacesOptions {
+    flows::ImportPlacesOptions {
+        dry_run: args.is_present("dry-run"),
+        allow_duplicates: args.is_present("allow-duplicates"),
+    }
+}
+
+fn import_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("created-by")
+            .long("created-by")
+            .value_name("EMAIL")
+            .help("E-mail address of the user that is recorded as the creator"),
+        Arg::with_name("dry-run")
+            .long("dry-run")
+            .help("Only validate the places and search for duplicates without storing anything"),
+        Arg::with_name("allow-duplicates")
+            .long("allow-duplicates")
+            .help("Also import places that are likely duplicates of existing places"),
+    ]
+}
+
 #[allow(deprecated)]
 pub fn run() {
     dotenv().ok();
@@ -49,7 +238,7 @@ pub fn run() {
             Arg::with_name("db-url")
                 .long("db-url")
                 .value_name("DATABASE_URL")
-                .help("URL to the database"),
+                .help("URL to the database, i.e. a file path for SQLite or postgres://... for PostgreSQL"),
         )
         .arg(
             Arg::with_name("idx-dir")
@@ -57,16 +246,60 @@ pub fn run() {
                 .value_name("INDEX_DIR")
                 .help("File system directory for the full-text search index"),
         )
+        .arg(
+            Arg::with_name("default-time-zone")
+                .long("default-time-zone")
+                .value_name("DEFAULT_TIME_ZONE")
+                .help("IANA time zone of events that are stored or submitted without a time zone"),
+        )
         .arg(
             Arg::with_name("enable-cors")
                 .long("enable-cors")
                 .help("Allow requests from any origin"),
         )
+        .arg(
+            Arg::with_name("propose-edits")
+                .long("propose-edits")
+                .help("Only store edits of places by anonymous visitors and unprivileged users as proposals that need to be reviewed"),
+        )
         .arg(
             Arg::with_name("fix-event-address-location")
                 .long("fix-event-address-location")
                 .help("Update the location of ALL events by resolving their address"),
         )
+        .subcommand(
+            SubCommand::with_name("import-geojson")
+                .about("Import new places from a GeoJSON FeatureCollection")
+                .arg(
+                    Arg::with_name("FILE")
+                        .required(true)
+                        .help("GeoJSON file with one Point feature per place"),
+                )
+                .args(&import_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("import-csv")
+                .about("Import new places from a CSV file")
+                .arg(
+                    Arg::with_name("FILE")
+                        .required(true)
+                        .help("CSV file with the same columns as the CSV export"),
+                )
+                .args(&import_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("rebuild-index")
+                .about("Rebuild the full-text search index from scratch"),
+        )
+        .subcommand(
+            SubCommand::with_name("check-index")
+                .about("Compare the full-text search index with the database")
+                .arg(
+                    Arg::with_name("repair")
+                        .long("repair")
+                        .help("Reindex all places and events that are inconsistent"),
+                ),
+        )
         .get_matches();
 
     let db_url = matches
@@ -74,13 +307,33 @@ pub fn run() {
         .map(ToString::to_string)
         .unwrap_or_else(|| env::var("DATABASE_URL").unwrap_or_else(|_| DEFAULT_DB_URL.to_string()));
     info!(
-        "Connecting to SQLite database '{}' (pool size = {})",
+        "Connecting to database '{}' (pool size = {})",
         db_url, DB_CONNECTION_POOL_SIZE
     );
-    let connections = sqlite::Connections::init(&db_url, DB_CONNECTION_POOL_SIZE).unwrap();
+    let connections = db::Connections::init(&db_url, DB_CONNECTION_POOL_SIZE).unwrap();
 
     info!("Running embedded database migrations");
-    embedded_migrations::run(&*connections.exclusive().unwrap()).unwrap();
+    connections.run_embedded_migrations().unwrap();
+
+    let default_time_zone = matches
+        .value_of("default-time-zone")
+        .map(ToString::to_string)
+        .unwrap_or_else(|| {
+            env::var("DEFAULT_TIME_ZONE").unwrap_or_else(|_| DEFAULT_TIME_ZONE.to_string())
+        });
+    let default_time_zone: Tz = default_time_zone.parse().unwrap_or_else(|err| {
+        error!("Invalid default time zone: {}", err);
+        std::process::exit(1);
+    });
+    let migrated_events = connections
+        .migrate_event_time_zones(default_time_zone)
+        .unwrap();
+    if migrated_events > 0 {
+        info!(
+            "Converted the local times of {} event(s) from time zone {} into UTC",
+            migrated_events, default_time_zone
+        );
+    }
 
     let idx_dir = matches
         .value_of("idx-dir")
@@ -88,10 +341,45 @@ pub fn run() {
         .or_else(|| env::var("INDEX_DIR").map(Option::Some).unwrap_or(None));
     let idx_path = idx_dir.as_ref().map(|dir| Path::new(dir));
     info!("Initializing Tantivy full-text search engine");
-    let search_engine = tantivy::SearchEngine::init_with_path(idx_path).unwrap();
+    let mut search_engine = tantivy::SearchEngine::init_with_path(idx_path).unwrap();
 
-    #[allow(clippy::match_single_binding)]
     match matches.subcommand() {
+        (subcommand @ "import-geojson", Some(args)) | (subcommand @ "import-csv", Some(args)) => {
+            let path = Path::new(args.value_of("FILE").unwrap());
+            let created_by_email = args.value_of("created-by");
+            let import_places = if subcommand == "import-csv" {
+                import_csv_places
+            } else {
+                import_geojson_places
+            };
+            if let Err(err) = import_places(
+                &connections,
+                &mut search_engine,
+                path,
+                created_by_email,
+                import_options(args),
+            ) {
+                error!("Failed to import places from {}: {}", path.display(), err);
+                std::process::exit(1);
+            }
+        }
+        ("rebuild-index", Some(_)) => {
+            info!("Rebuilding the search index...");
+            if let Err(err) =
+                usecases::rebuild_index(&*connections.shared().unwrap(), &mut search_engine)
+            {
+                error!("Failed to rebuild the search index: {}", err);
+                std::process::exit(1);
+            }
+        }
+        ("check-index", Some(args)) => {
+            if let Err(err) =
+                check_index(&connections, &mut search_engine, args.is_present("repair"))
+            {
+                error!("Failed to check the search index: {}", err);
+                std::process::exit(1);
+            }
+        }
         _ => {
             if matches.is_present("fix-event-address-location") {
                 info!("Updating all event locations...");
@@ -101,6 +389,12 @@ pub fn run() {
                 connections,
                 search_engine,
                 matches.is_present("enable-cors"),
+                if matches.is_present("propose-edits") {
+                    usecases::PlaceEditMode::Proposal
+                } else {
+                    usecases::PlaceEditMode::Direct
+                },
+                usecases::DefaultEventTimeZone(default_time_zone),
             );
         }
     }
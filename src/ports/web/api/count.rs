@@ -1,11 +1,11 @@
 use super::*;
 
 #[get("/count/entries")]
-pub fn get_count_entries(db: sqlite::Connections) -> Result<usize> {
+pub fn get_count_entries(db: db::Connections) -> Result<usize> {
     Ok(Json(db.shared()?.count_places()?))
 }
 
 #[get("/count/tags")]
-pub fn get_count_tags(db: sqlite::Connections) -> Result<usize> {
+pub fn get_count_tags(db: db::Connections) -> Result<usize> {
     Ok(Json(db.shared()?.count_tags()?))
 }
@@ -3,11 +3,14 @@ use crate::{
     adapters,
     core::{
         prelude::Result as CoreResult,
-        util::{geo::MapBbox, validate},
+        util::{
+            geo::{MapBbox, MapCircle},
+            parse::{parse_near_params, parse_page_cursor},
+            validate,
+        },
     },
-    infrastructure::{flows::prelude as flows, GEO_CODING_GW},
+    infrastructure::flows::prelude as flows,
 };
-use ofdb_core::gateways::geocode::GeoCodingGateway;
 
 use rocket::{
     http::{RawStr, Status as HttpStatus},
@@ -17,60 +20,21 @@ use rocket::{
 #[cfg(test)]
 mod tests;
 
-fn check_and_set_address_location(e: &mut usecases::NewEvent) -> Option<MapPoint> {
-    let pos = if let (Some(lat), Some(lng)) = (e.lat, e.lng) {
-        MapPoint::try_from_lat_lng_deg(lat, lng)
-            .map(Some)
-            .unwrap_or_default()
-    } else {
-        None
-    };
-    if pos.unwrap_or_default().is_valid() {
-        // Preserve valid geo locations
-        return pos;
-    }
-    // TODO: Parse logical parts of NewEvent earlier
-    let addr = Address {
-        street: e.street.clone(),
-        zip: e.zip.clone(),
-        city: e.city.clone(),
-        country: e.country.clone(),
-        state: e.state.clone(),
-    };
-
-    GEO_CODING_GW
-        .resolve_address_lat_lng(&addr)
-        .and_then(|(lat, lng)| {
-            if let Ok(pos) = MapPoint::try_from_lat_lng_deg(lat, lng) {
-                log::debug!(
-                    "Updating event location: ({:?}, {:?}) -> {:?}",
-                    e.lat,
-                    e.lng,
-                    pos
-                );
-                e.lat = Some(lat);
-                e.lng = Some(lng);
-            }
-            pos
-        })
-}
-
 #[post("/events", format = "application/json", data = "<e>")]
 pub fn post_event_with_token(
-    connections: sqlite::Connections,
+    connections: db::Connections,
     mut search_engine: tantivy::SearchEngine,
     notify: Notify,
+    default_time_zone: State<usecases::DefaultEventTimeZone>,
     token: Bearer,
     e: Json<usecases::NewEvent>,
 ) -> Result<String> {
-    let mut e = e.into_inner();
-    check_and_set_address_location(&mut e);
     let event = flows::create_event(
         &connections,
         &mut search_engine,
         &*notify,
         Some(&token.0),
-        e,
+        e.into_inner().with_default_time_zone(*default_time_zone),
     )?;
     Ok(Json(event.id.to_string()))
 }
@@ -79,12 +43,12 @@ pub fn post_event_with_token(
 // NOTE:
 // At the moment we don't want to allow anonymous event creation.
 // So for now we assure that it's blocked:
-pub fn post_event(mut _db: sqlite::Connections, _e: Json<usecases::NewEvent>) -> HttpStatus {
+pub fn post_event(mut _db: db::Connections, _e: Json<usecases::NewEvent>) -> HttpStatus {
     HttpStatus::Unauthorized
 }
 // But in the future we might allow anonymous event creation:
 //
-// pub fn post_event(mut db: sqlite::Connections, e: Json<usecases::NewEvent>) -> Result<String> {
+// pub fn post_event(mut db: db::Connections, e: Json<usecases::NewEvent>) -> Result<String> {
 //     let mut e = e.into_inner();
 //     e.created_by = None; // ignore because of missing authorization
 //     e.token = None; // ignore token
@@ -93,7 +57,7 @@ pub fn post_event(mut _db: sqlite::Connections, _e: Json<usecases::NewEvent>) ->
 // }
 
 #[get("/events/<id>")]
-pub fn get_event(db: sqlite::Connections, id: String) -> Result<json::Event> {
+pub fn get_event(db: db::Connections, id: String) -> Result<json::Event> {
     let mut ev = usecases::get_event(&*db.shared()?, &id)?;
     ev.created_by = None; // don't show creators email to unregistered users
     Ok(Json(ev.into()))
@@ -103,7 +67,7 @@ pub fn get_event(db: sqlite::Connections, id: String) -> Result<json::Event> {
 // At the moment we don't want to allow anonymous event creation.
 // So for now we assure that it's blocked:
 pub fn put_event(
-    mut _db: sqlite::Connections,
+    mut _db: db::Connections,
     _id: &RawStr,
     _e: Json<usecases::NewEvent>,
 ) -> HttpStatus {
@@ -112,22 +76,21 @@ pub fn put_event(
 
 #[put("/events/<id>", format = "application/json", data = "<e>")]
 pub fn put_event_with_token(
-    connections: sqlite::Connections,
+    connections: db::Connections,
     mut search_engine: tantivy::SearchEngine,
     notify: Notify,
+    default_time_zone: State<usecases::DefaultEventTimeZone>,
     token: Bearer,
     id: &RawStr,
     e: Json<usecases::NewEvent>,
 ) -> Result<()> {
-    let mut e = e.into_inner();
-    check_and_set_address_location(&mut e);
     flows::update_event(
         &connections,
         &mut search_engine,
         &*notify,
         Some(&token.0),
         id.to_string().into(),
-        e,
+        e.into_inner().with_default_time_zone(*default_time_zone),
     )?;
     Ok(Json(()))
 }
@@ -160,6 +123,50 @@ impl<'q> FromQuery<'q> for usecases::EventQuery {
             None
         };
 
+        let near = if let Some(near) = query
+            .clone()
+            .filter(|i| i.key == "near")
+            .map(|i| i.value.url_decode_lossy())
+            .find(|v| !v.is_empty())
+        {
+            let radius = query
+                .clone()
+                .filter(|i| i.key == "radius")
+                .map(|i| i.value.url_decode_lossy())
+                .find(|v| !v.is_empty())
+                .unwrap_or_default();
+            Some(parse_near_params(&near, &radius)?)
+        } else {
+            None
+        };
+
+        let sort = query
+            .clone()
+            .filter(|i| i.key == "sort")
+            .map(|i| i.value.url_decode_lossy())
+            .find(|v| !v.is_empty());
+        let order = match sort.as_deref() {
+            None | Some("chronological") | Some("start") => usecases::EventOrder::Chronological,
+            // Sorting by distance requires a center
+            Some("distance") if near.is_some() => usecases::EventOrder::Distance,
+            Some("distance") => return Err(ParameterError::InvalidPosition.into()),
+            Some("created") => usecases::EventOrder::Created,
+            Some("relevance") => usecases::EventOrder::Relevance,
+            Some(_) => return Err(ParameterError::InvalidEventOrder.into()),
+        };
+
+        let cursor = query
+            .clone()
+            .filter(|i| i.key == "cursor")
+            .map(|i| i.value.url_decode_lossy())
+            .find(|v| !v.is_empty())
+            .map(|cursor| parse_page_cursor(&cursor))
+            .transpose()?;
+
+        // Results are returned page by page if either a sort
+        // order or a cursor has been requested
+        let paginate = sort.is_some() || cursor.is_some();
+
         let limit = if let Some(limit) = query
             .clone()
             .filter(|i| i.key == "limit")
@@ -210,12 +217,16 @@ impl<'q> FromQuery<'q> for usecases::EventQuery {
         drop(query); // silence clippy warning
         Ok(usecases::EventQuery {
             bbox,
+            near,
             created_by,
             limit,
             start_max,
             start_min,
             tags,
             text,
+            order,
+            paginate,
+            cursor,
         })
     }
 }
@@ -238,16 +249,54 @@ fn validate_and_adjust_query_limit(limit: usize) -> CoreResult<usize> {
     }
 }
 
+// Contains the cursor for requesting the next page of events
+const NEXT_CURSOR_HEADER: &str = "X-Next-Cursor";
+
+/// A page of events with an optional cursor for
+/// requesting the next page.
+pub struct EventsPage(Json<Vec<json::Event>>, Option<PageCursor>);
+
+impl<'r> Responder<'r> for EventsPage {
+    fn respond_to(self, request: &rocket::Request) -> result::Result<Response<'r>, Status> {
+        let Self(events, next_cursor) = self;
+        let mut response = events.respond_to(request)?;
+        if let Some(next_cursor) = next_cursor {
+            response.set_raw_header(NEXT_CURSOR_HEADER, next_cursor.encode_to_string());
+        }
+        Ok(response)
+    }
+}
+
+fn event_with_distance(event: Event, near: Option<MapCircle>) -> json::Event {
+    let distance = near
+        .and_then(|circle| {
+            event
+                .location
+                .as_ref()
+                .and_then(|location| MapPoint::distance(circle.center(), location.pos))
+        })
+        .map(Distance::to_meters);
+    json::Event {
+        distance,
+        ..event.into()
+    }
+}
+
 #[get("/events?<query..>")]
 pub fn get_events_with_token(
-    connections: sqlite::Connections,
+    connections: db::Connections,
     search_engine: tantivy::SearchEngine,
     token: Bearer,
     query: usecases::EventQuery,
-) -> Result<Vec<json::Event>> {
+) -> result::Result<EventsPage, AppError> {
     let db = connections.shared()?;
     let org = usecases::authorize_organization_by_api_token(&*db, &token.0)?;
-    let events = usecases::query_events(&*db, &search_engine, query)?;
+    let near = query.near;
+    let (events, next_cursor) = if query.paginate {
+        usecases::query_events_page(&*db, &search_engine, query)?
+    } else {
+        (usecases::query_events(&*db, &search_engine, query)?, None)
+    };
     // Release the database connection asap
     drop(db);
 
@@ -262,24 +311,29 @@ pub fn get_events_with_token(
                     .map(|moderated_tag| moderated_tag.label.as_str()),
             )
         })
-        .map(json::Event::from)
+        .map(|e| event_with_distance(e, near))
         .collect();
 
-    Ok(Json(events))
+    Ok(EventsPage(Json(events), next_cursor))
 }
 
 #[get("/events?<query..>", rank = 2)]
 pub fn get_events_chronologically(
-    connections: sqlite::Connections,
+    connections: db::Connections,
     search_engine: tantivy::SearchEngine,
     query: usecases::EventQuery,
-) -> Result<Vec<json::Event>> {
+) -> result::Result<EventsPage, AppError> {
     if query.created_by.is_some() {
         return Err(Error::Parameter(ParameterError::Unauthorized).into());
     }
 
     let db = connections.shared()?;
-    let events = usecases::query_events(&*db, &search_engine, query)?;
+    let near = query.near;
+    let (events, next_cursor) = if query.paginate {
+        usecases::query_events_page(&*db, &search_engine, query)?
+    } else {
+        (usecases::query_events(&*db, &search_engine, query)?, None)
+    };
     // Release the database connection asap
     drop(db);
 
@@ -287,20 +341,116 @@ pub fn get_events_chronologically(
     let events: Vec<_> = events
         .into_iter()
         .map(|e| usecases::filter_event(e, moderated_tags.iter().map(String::as_str)))
-        .map(json::Event::from)
+        .map(|e| event_with_distance(e, near))
         .collect();
 
-    Ok(Json(events))
+    Ok(EventsPage(Json(events), next_cursor))
 }
 
-#[get("/export/events.csv?<query..>")]
-pub fn csv_export(
-    connections: sqlite::Connections,
+#[post(
+    "/import/events.ics?<created_by>",
+    format = "text/calendar",
+    data = "<data>"
+)]
+pub fn ical_import(
+    connections: db::Connections,
+    mut search_engine: tantivy::SearchEngine,
+    notify: Notify,
+    default_time_zone: State<usecases::DefaultEventTimeZone>,
+    token: Bearer,
+    created_by: Option<String>,
+    data: Data,
+) -> Result<Vec<json::EventImportResult>> {
+    let default_time_zone = *default_time_zone;
+    usecases::authorize_organization_by_api_token(&*connections.shared()?, &token.0)?;
+    let ics = read_import_data(data)?;
+    let vevents = adapters::ical::parse_events(&ics).map_err(Error::Parameter)?;
+    // Modified occurrences are imported together with their recurring
+    // event, i.e. occurrences without a recurring event are ignored
+    let (occurrences, vevents): (Vec<_>, Vec<_>) = vevents
+        .into_iter()
+        .partition(adapters::ical::VEvent::is_modified_occurrence);
+    let mut results = Vec::with_capacity(vevents.len());
+    for vevent in vevents {
+        let uid = vevent.uid();
+        let imported = uid
+            .as_ref()
+            .ok_or(ParameterError::InvalidCalendar)
+            .and_then(|_| vevent.to_new_event(default_time_zone))
+            .and_then(|mut new_event| {
+                if new_event.recurrence.is_some() {
+                    for occurrence in occurrences.iter().filter(|o| o.uid() == uid) {
+                        new_event
+                            .recurrence_overrides
+                            .get_or_insert_with(Vec::new)
+                            .push(occurrence.to_occurrence_override(default_time_zone)?);
+                    }
+                }
+                Ok(new_event)
+            })
+            .map_err(|err| AppError::from(Error::Parameter(err)))
+            .and_then(|mut new_event| {
+                // Fall back to the organizer as creator
+                new_event.created_by = created_by.clone().or_else(|| new_event.email.clone());
+                flows::import_event(
+                    &connections,
+                    &mut search_engine,
+                    &*notify,
+                    &token.0,
+                    uid.as_deref().unwrap_or_default(),
+                    new_event,
+                )
+            });
+        let (id, created, error) = match imported {
+            Ok(flows::ImportedEvent { event, created }) => (Some(event.id.into()), created, None),
+            Err(err) => (None, false, Some(err.to_string())),
+        };
+        results.push(json::EventImportResult {
+            uid: uid.unwrap_or_default(),
+            id,
+            created,
+            error,
+        });
+    }
+    Ok(Json(results))
+}
+
+#[get("/events.ics?<query..>")]
+pub fn get_events_ical(
+    connections: db::Connections,
     search_engine: tantivy::SearchEngine,
+    query: usecases::EventQuery,
+) -> result::Result<Content<String>, AppError> {
+    if query.created_by.is_some() {
+        return Err(Error::Parameter(ParameterError::Unauthorized).into());
+    }
+
+    let db = connections.shared()?;
+    // Recurring events are exported with their recurrence rule
+    let mut events = usecases::query_event_series(&*db, &search_engine, query.clone())?;
+    // Archived events are exported as cancelled to remove them
+    // from subscribed calendars
+    events.extend(usecases::query_archived_event_series(&*db, query)?);
+    // Release the database connection asap
+    drop(db);
+
+    let moderated_tags = vec![];
+    let events = events
+        .into_iter()
+        .map(|e| usecases::filter_event(e, moderated_tags.iter().map(String::as_str)));
+    let data = adapters::ical::calendar_from_events(events, Timestamp::now());
+
+    let (top, sub) = adapters::ical::MEDIA_TYPE;
+    Ok(Content(ContentType::new(top, sub), data))
+}
+
+fn export_events(
+    connections: &db::Connections,
+    search_engine: &tantivy::SearchEngine,
     bearer: Option<Bearer>,
     login: Login,
     query: usecases::EventQuery,
-) -> result::Result<Content<String>, AppError> {
+) -> result::Result<Vec<Event>, AppError> {
     let db = connections.shared()?;
 
     let moderated_tags = if let Some(bearer) = bearer {
@@ -324,21 +474,37 @@ pub fn csv_export(
         limit: Some(limit),
         ..query
     };
-    let events = usecases::query_events(&*db, &search_engine, query)?;
+    let events = usecases::query_events(&*db, search_engine, query)?;
     // Release the database connection asap
     drop(db);
 
-    let events = events.into_iter().map(|e| {
-        usecases::export_event(
-            e,
-            user.role,
-            moderated_tags
-                .iter()
-                .map(|moderated_tag| moderated_tag.label.as_str()),
-        )
-    });
+    let events = events
+        .into_iter()
+        .map(|e| {
+            usecases::export_event(
+                e,
+                user.role,
+                moderated_tags
+                    .iter()
+                    .map(|moderated_tag| moderated_tag.label.as_str()),
+            )
+        })
+        .collect();
+    Ok(events)
+}
 
-    let records: Vec<_> = events.map(adapters::csv::EventRecord::from).collect();
+#[get("/export/events.csv?<query..>")]
+pub fn csv_export(
+    connections: db::Connections,
+    search_engine: tantivy::SearchEngine,
+    bearer: Option<Bearer>,
+    login: Login,
+    query: usecases::EventQuery,
+) -> result::Result<Content<String>, AppError> {
+    let records: Vec<_> = export_events(&connections, &search_engine, bearer, login, query)?
+        .into_iter()
+        .map(adapters::csv::EventRecord::from)
+        .collect();
 
     let buff: Vec<u8> = vec![];
     let mut wtr = csv::Writer::from_writer(buff);
@@ -352,10 +518,25 @@ pub fn csv_export(
     Ok(Content(ContentType::CSV, data))
 }
 
+#[get("/export/events.geojson?<query..>")]
+pub fn geojson_export(
+    connections: db::Connections,
+    search_engine: tantivy::SearchEngine,
+    bearer: Option<Bearer>,
+    login: Login,
+    query: usecases::EventQuery,
+) -> result::Result<Content<Json<geojson::FeatureCollection<geojson::EventProperties>>>, AppError> {
+    let features = export_events(&connections, &search_engine, bearer, login, query)?
+        .into_iter()
+        .map(geojson::EventFeature::from)
+        .collect();
+    Ok(Content(geojson_content_type(), Json(features)))
+}
+
 #[post("/events/<ids>/archive")]
 pub fn post_events_archive(
     login: Login,
-    db: sqlite::Connections,
+    db: db::Connections,
     mut search_engine: tantivy::SearchEngine,
     ids: String,
 ) -> StatusResult {
@@ -381,17 +562,18 @@ pub fn post_events_archive(
 }
 
 #[delete("/events/<_id>", rank = 2)]
-pub fn delete_event(mut _db: sqlite::Connections, _id: &RawStr) -> HttpStatus {
+pub fn delete_event(mut _db: db::Connections, _id: &RawStr) -> HttpStatus {
     HttpStatus::Unauthorized
 }
 
 #[delete("/events/<id>")]
 pub fn delete_event_with_token(
-    db: sqlite::Connections,
+    db: db::Connections,
+    mut search_engine: tantivy::SearchEngine,
     token: Bearer,
     id: &RawStr,
 ) -> StatusResult {
-    usecases::delete_event(&mut *db.exclusive()?, &token.0, &id.to_string())?;
+    flows::delete_event(&db, &mut search_engine, &token.0, id.as_str())?;
     // TODO: Replace with HttpStatus::NoContent
     Ok(HttpStatus::Ok)
 }
@@ -100,13 +100,13 @@ fn export_csv() {
     assert_eq!(response.status(), Status::Ok);
     let body_str = response.body().and_then(|b| b.into_string()).unwrap();
     eprintln!("{}", body_str);
-    assert!(body_str.starts_with("id,created_by,organizer,title,description,start,end,lat,lng,street,zip,city,country,state,email,phone,homepage,image_url,image_link_url,tags\n"));
+    assert!(body_str.starts_with("id,created_by,organizer,title,description,start,end,time_zone,lat,lng,street,zip,city,country,state,email,phone,homepage,image_url,image_link_url,tags,recurrence,recurrence_id\n"));
     assert!(body_str.contains(&format!(
-        "{},,,title1,,{},,,,,,,,state,email1@example.com,phone1,,,,\"bla,tag\"\n",
+        "{},,,title1,,{},,UTC,,,,,,,state,email1@example.com,phone1,,,,\"bla,tag\",,\n",
         id1, start1
     )));
     assert!(body_str.contains(&format!(
-        "{},,,title2,,{},,,,,,,,,email2@example.com,phone2,,,,\"bli,tag2\"\n",
+        "{},,,title2,,{},,UTC,,,,,,,,email2@example.com,phone2,,,,\"bli,tag2\",,\n",
         id2, start2
     )));
     assert!(!body_str.contains("createdby1@example.com"));
@@ -125,10 +125,10 @@ fn export_csv() {
         .dispatch();
     assert_eq!(response.status(), Status::Ok);
     let body_str = response.body().and_then(|b| b.into_string()).unwrap();
-    assert!(body_str.starts_with("id,created_by,organizer,title,description,start,end,lat,lng,street,zip,city,country,state,email,phone,homepage,image_url,image_link_url,tags\n"));
-    assert!(body_str.contains(&format!("{},createdby1@example.com,,title1,,{},,,,,,,,state,email1@example.com,phone1,,,,\"bla,tag\"\n", id1, start1)));
+    assert!(body_str.starts_with("id,created_by,organizer,title,description,start,end,time_zone,lat,lng,street,zip,city,country,state,email,phone,homepage,image_url,image_link_url,tags,recurrence,recurrence_id\n"));
+    assert!(body_str.contains(&format!("{},createdby1@example.com,,title1,,{},,UTC,,,,,,,state,email1@example.com,phone1,,,,\"bla,tag\",,\n", id1, start1)));
     assert!(body_str.contains(&format!(
-        "{},,,title2,,{},,,,,,,,,email2@example.com,phone2,,,,\"bli,tag2\"\n",
+        "{},,,title2,,{},,UTC,,,,,,,,email2@example.com,phone2,,,,\"bli,tag2\",,\n",
         id2, start2
     )));
     assert!(!body_str.contains("createdby2@example.com"));
@@ -143,10 +143,10 @@ fn export_csv() {
     let mut response = client.get("/export/events.csv").dispatch();
     assert_eq!(response.status(), Status::Ok);
     let body_str = response.body().and_then(|b| b.into_string()).unwrap();
-    assert!(body_str.starts_with("id,created_by,organizer,title,description,start,end,lat,lng,street,zip,city,country,state,email,phone,homepage,image_url,image_link_url,tags\n"));
-    assert!(body_str.contains(&format!("{},createdby1@example.com,,title1,,{},,,,,,,,state,email1@example.com,phone1,,,,\"bla,tag\"\n", id1, start1)));
+    assert!(body_str.starts_with("id,created_by,organizer,title,description,start,end,time_zone,lat,lng,street,zip,city,country,state,email,phone,homepage,image_url,image_link_url,tags,recurrence,recurrence_id\n"));
+    assert!(body_str.contains(&format!("{},createdby1@example.com,,title1,,{},,UTC,,,,,,,state,email1@example.com,phone1,,,,\"bla,tag\",,\n", id1, start1)));
     assert!(body_str.contains(&format!(
-        "{},createdby2@example.com,,title2,,{},,,,,,,,,email2@example.com,phone2,,,,\"bli,tag2\"\n",
+        "{},createdby2@example.com,,title2,,{},,UTC,,,,,,,,email2@example.com,phone2,,,,\"bli,tag2\",,\n",
         id2, start2
     )));
 }
@@ -0,0 +1,79 @@
+use super::*;
+
+#[test]
+fn export_geojson() {
+    let (client, db, mut search_engine, notify) = setup2();
+
+    db.exclusive()
+        .unwrap()
+        .create_user(&User {
+            email: "scout@example.com".into(),
+            email_confirmed: true,
+            password: "secret".parse::<Password>().unwrap(),
+            role: Role::Scout,
+        })
+        .unwrap();
+    let start = Utc::now().naive_utc().timestamp();
+    let located = usecases::NewEvent {
+        title: "located".into(),
+        start,
+        lat: Some(48.5),
+        lng: Some(9.25),
+        tags: Some(vec!["bla".into()]),
+        ..Default::default()
+    };
+    let located_id = flows::create_event(&db, &mut search_engine, &notify, None, located)
+        .unwrap()
+        .id;
+    let unlocated = usecases::NewEvent {
+        title: "unlocated".into(),
+        start,
+        city: Some("Stuttgart".into()),
+        ..Default::default()
+    };
+    let unlocated_id = flows::create_event(&db, &mut search_engine, &notify, None, unlocated)
+        .unwrap()
+        .id;
+
+    let response = client.get("/export/events.geojson").dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+
+    let login = client
+        .post("/login")
+        .header(ContentType::JSON)
+        .body(r#"{"email": "scout@example.com", "password": "secret"}"#)
+        .dispatch();
+    assert_eq!(login.status(), Status::Ok);
+
+    let mut response = client.get("/export/events.geojson").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.headers().get("Content-Type").collect::<Vec<_>>()[0],
+        "application/geo+json"
+    );
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let collection: serde_json::Value = serde_json::from_str(&body_str).unwrap();
+    assert_eq!(collection["type"], "FeatureCollection");
+    let features = collection["features"].as_array().unwrap();
+    assert_eq!(2, features.len());
+    let located = features
+        .iter()
+        .find(|f| f["id"] == located_id.as_str())
+        .unwrap();
+    assert_eq!(located["geometry"]["type"], "Point");
+    assert_eq!(located["properties"]["title"], "located");
+    assert_eq!(located["properties"]["start"], start);
+    assert_eq!(located["properties"]["tags"], serde_json::json!(["bla"]));
+    let unlocated = features
+        .iter()
+        .find(|f| f["id"] == unlocated_id.as_str())
+        .unwrap();
+    assert!(unlocated["geometry"].is_null());
+    assert_eq!(unlocated["properties"]["city"], "Stuttgart");
+
+    let mut response = client.get("/export/events.geojson?tag=bla").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let collection: serde_json::Value = serde_json::from_str(&body_str).unwrap();
+    assert_eq!(1, collection["features"].as_array().unwrap().len());
+}
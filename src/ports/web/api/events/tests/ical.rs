@@ -0,0 +1,216 @@
+use super::*;
+
+#[test]
+fn ical_feed() {
+    let (client, db, mut search_engine, notify) = setup2();
+    let start = Utc::now().naive_utc().timestamp();
+    let e1 = usecases::NewEvent {
+        title: "Repair Café".into(),
+        start,
+        lat: Some(48.5),
+        lng: Some(9.25),
+        city: Some("Tübingen".into()),
+        email: Some("jane@example.com".into()),
+        organizer: Some("Jane Doe".into()),
+        homepage: Some("https://example.com/repair".into()),
+        tags: Some(vec!["repair".into()]),
+        created_by: Some("creator@example.com".into()),
+        ..Default::default()
+    };
+    let e1_id = flows::create_event(&db, &mut search_engine, &notify, None, e1)
+        .unwrap()
+        .id;
+    let e2 = usecases::NewEvent {
+        title: "Other".into(),
+        start,
+        lat: Some(52.5),
+        lng: Some(13.4),
+        tags: Some(vec!["other".into()]),
+        ..Default::default()
+    };
+    flows::create_event(&db, &mut search_engine, &notify, None, e2).unwrap();
+
+    let mut response = client.get("/events.ics?tag=repair").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.headers().get("Content-Type").collect::<Vec<_>>()[0],
+        "text/calendar"
+    );
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    assert!(body_str.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(body_str.ends_with("END:VCALENDAR\r\n"));
+    assert_eq!(1, body_str.matches("BEGIN:VEVENT").count());
+    assert!(body_str.contains(&format!("\r\nUID:{}\r\n", e1_id)));
+    assert!(body_str.contains("\r\nSUMMARY:Repair Café\r\n"));
+    assert!(body_str.contains("\r\nLOCATION:Tübingen\r\n"));
+    assert!(body_str.contains("\r\nURL:https://example.com/repair\r\n"));
+    assert!(body_str.contains("\r\nSTATUS:CONFIRMED\r\n"));
+    // Activity details are not published
+    assert!(!body_str.contains("creator@example.com"));
+
+    let mut response = client.get("/events.ics?bbox=52,13,53,14").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    assert_eq!(1, body_str.matches("BEGIN:VEVENT").count());
+    assert!(body_str.contains("\r\nSUMMARY:Other\r\n"));
+
+    let response = client
+        .get("/events.ics?created_by=creator%40example.com")
+        .dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+}
+
+#[test]
+fn ical_feed_with_archived_events() {
+    let (client, db, mut search_engine, notify) = setup2();
+    let now = Utc::now().naive_utc().timestamp();
+    let new_event = |title: &str, start: i64, tag: &str| usecases::NewEvent {
+        title: title.into(),
+        start,
+        tags: Some(vec![tag.into()]),
+        ..Default::default()
+    };
+    let upcoming_id = flows::create_event(
+        &db,
+        &mut search_engine,
+        &notify,
+        None,
+        new_event("Upcoming", now + 86_400, "repair"),
+    )
+    .unwrap()
+    .id;
+    let past_id = flows::create_event(
+        &db,
+        &mut search_engine,
+        &notify,
+        None,
+        new_event("Past", now - 2 * 86_400, "repair"),
+    )
+    .unwrap()
+    .id;
+    let other_id = flows::create_event(
+        &db,
+        &mut search_engine,
+        &notify,
+        None,
+        new_event("Other", now + 86_400, "other"),
+    )
+    .unwrap()
+    .id;
+    flows::archive_events(
+        &db,
+        &mut search_engine,
+        &[upcoming_id.as_str(), past_id.as_str(), other_id.as_str()],
+        "scout@example.com",
+    )
+    .unwrap();
+
+    let mut response = client.get("/events.ics?tag=repair").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    assert_eq!(1, body_str.matches("BEGIN:VEVENT").count());
+    assert!(body_str.contains(&format!("\r\nUID:{}\r\n", upcoming_id)));
+    assert!(body_str.contains("\r\nSTATUS:CANCELLED\r\n"));
+
+    let mut response = client
+        .get(format!(
+            "/events.ics?tag=repair&start_min={}",
+            now - 3 * 86_400
+        ))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    assert_eq!(2, body_str.matches("BEGIN:VEVENT").count());
+    assert_eq!(2, body_str.matches("\r\nSTATUS:CANCELLED\r\n").count());
+    assert!(body_str.contains(&format!("\r\nUID:{}\r\n", past_id)));
+    assert!(!body_str.contains(&format!("\r\nUID:{}\r\n", other_id)));
+}
+
+#[test]
+fn ical_import() {
+    let (client, db, _, _) = setup2();
+    db.exclusive()
+        .unwrap()
+        .create_org(Organization {
+            id: "foo".into(),
+            name: "bar".into(),
+            moderated_tags: vec!["org-tag".into()],
+            api_token: "foo".into(),
+        })
+        .unwrap();
+    let ics = |title: &str| {
+        format!(
+            "BEGIN:VCALENDAR\r\n\
+             VERSION:2.0\r\n\
+             PRODID:-//Example//Example//EN\r\n\
+             BEGIN:VEVENT\r\n\
+             UID:abc@example.com\r\n\
+             DTSTART:20301001T160000Z\r\n\
+             SUMMARY:{}\r\n\
+             GEO:48.5;9.25\r\n\
+             ORGANIZER;CN=Jane:mailto:jane@example.com\r\n\
+             CATEGORIES:repair\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VEVENT\r\n\
+             SUMMARY:Without UID\r\n\
+             DTSTART:20301001T160000Z\r\n\
+             END:VEVENT\r\n\
+             END:VCALENDAR\r\n",
+            title
+        )
+    };
+
+    let response = client
+        .post("/import/events.ics")
+        .header(ContentType::new("text", "calendar"))
+        .body(ics("Repair Café"))
+        .dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+
+    let response = client
+        .post("/import/events.ics")
+        .header(ContentType::new("text", "calendar"))
+        .header(Header::new("Authorization", "Bearer foo"))
+        .body("no calendar")
+        .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+
+    let mut response = client
+        .post("/import/events.ics")
+        .header(ContentType::new("text", "calendar"))
+        .header(Header::new("Authorization", "Bearer foo"))
+        .body(ics("Repair Café"))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let results: Vec<json::EventImportResult> = serde_json::from_str(&body_str).unwrap();
+    assert_eq!(2, results.len());
+    assert_eq!("abc@example.com", results[0].uid);
+    assert!(results[0].created);
+    assert!(results[0].error.is_none());
+    let id = results[0].id.clone().unwrap();
+    assert!(results[1].id.is_none());
+    assert!(results[1].error.is_some());
+
+    let event = db.shared().unwrap().get_event(&id).unwrap();
+    assert_eq!("Repair Café", event.title);
+    assert_eq!(Some("jane@example.com".into()), event.created_by);
+    assert!(event.tags.contains(&"repair".to_string()));
+    assert!(event.tags.contains(&"org-tag".to_string()));
+
+    // Importing the same UID again updates the event
+    let mut response = client
+        .post("/import/events.ics")
+        .header(ContentType::new("text", "calendar"))
+        .header(Header::new("Authorization", "Bearer foo"))
+        .body(ics("Repair Café (updated)"))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let results: Vec<json::EventImportResult> = serde_json::from_str(&body_str).unwrap();
+    assert!(!results[0].created);
+    assert_eq!(Some(id.clone()), results[0].id);
+    assert_eq!(1, db.shared().unwrap().count_events().unwrap());
+    let event = db.shared().unwrap().get_event(&id).unwrap();
+    assert_eq!("Repair Café (updated)", event.title);
+}
@@ -7,5 +7,7 @@ mod archive;
 mod create;
 mod delete;
 mod export_csv;
+mod export_geojson;
+mod ical;
 mod read;
 mod update;
@@ -40,15 +40,19 @@ fn all() {
                 description: None,
                 start: Utc::now().naive_utc(),
                 end: None,
+                time_zone: Tz::UTC,
                 location: None,
                 contact: None,
                 tags: vec![],
                 homepage: None,
                 created_by: None,
+                created_at: None,
                 registration: None,
                 archived: None,
                 image_url: None,
                 image_link_url: None,
+                recurrence: None,
+                recurrence_id: None,
             })
             .unwrap();
     }
@@ -87,6 +91,66 @@ fn sorted_by_start() {
     assert!(objects[4].contains(&format!("\"start\":{}", now + 300)));
 }
 
+#[test]
+fn paginated_by_cursor() {
+    let (client, db, mut search_engine, notify) = setup2();
+    let now = Utc::now().naive_utc().timestamp();
+    let start_offsets = vec![100, 0, 300, 50, 200];
+    for start_offset in start_offsets {
+        let e = usecases::NewEvent {
+            title: start_offset.to_string(),
+            start: now + start_offset,
+            created_by: Some("test@example.com".into()),
+            ..Default::default()
+        };
+        flows::create_event(&db, &mut search_engine, &notify, None, e).unwrap();
+    }
+
+    let mut titles = vec![];
+    let mut url = "/events?sort=start&limit=2".to_string();
+    loop {
+        let mut res = client
+            .get(url.as_str())
+            .header(ContentType::JSON)
+            .dispatch();
+        assert_eq!(res.status(), HttpStatus::Ok);
+        let next_cursor = res
+            .headers()
+            .get_one("X-Next-Cursor")
+            .map(ToOwned::to_owned);
+        let events: Vec<json::Event> = serde_json::from_str(&res.body_string().unwrap()).unwrap();
+        assert!(events.len() <= 2);
+        titles.extend(events.into_iter().map(|e| e.title));
+        if let Some(next_cursor) = next_cursor {
+            url = format!("/events?sort=start&limit=2&cursor={}", next_cursor);
+        } else {
+            break;
+        }
+    }
+    assert_eq!(vec!["0", "50", "100", "200", "300"], titles);
+
+    let mut res = client
+        .get("/events?sort=created&limit=10")
+        .header(ContentType::JSON)
+        .dispatch();
+    assert_eq!(res.status(), HttpStatus::Ok);
+    assert!(res.headers().get_one("X-Next-Cursor").is_none());
+    let events: Vec<json::Event> = serde_json::from_str(&res.body_string().unwrap()).unwrap();
+    assert_eq!(5, events.len());
+
+    let res = client
+        .get("/events?sort=start&cursor=invalid")
+        .header(ContentType::JSON)
+        .dispatch();
+    assert_eq!(res.status(), HttpStatus::BadRequest);
+
+    let res = client
+        .get("/events?sort=unknown")
+        .header(ContentType::JSON)
+        .dispatch();
+    assert_eq!(res.status(), HttpStatus::BadRequest);
+}
+
 #[test]
 fn filtered_by_tags() {
     let (client, db, mut search_engine, notify) = setup2();
@@ -268,6 +332,113 @@ fn filtered_by_start_max() {
     assert!(objects[3].contains(&format!("\"start\":{}", now + 200)));
 }
 
+#[test]
+fn recurring_events_expanded_within_start_range() {
+    let (client, db, mut search_engine, notify) = setup2();
+    let now = Utc::now().naive_utc().timestamp();
+    let week = 7 * 24 * 60 * 60;
+    let e = usecases::NewEvent {
+        title: "weekly".into(),
+        start: now,
+        end: Some(now + 3600),
+        created_by: Some("test@example.com".into()),
+        recurrence: Some("FREQ=WEEKLY".into()),
+        recurrence_overrides: Some(vec![usecases::NewOccurrenceOverride {
+            recurrence_id: now + 2 * week,
+            cancelled: true,
+            ..Default::default()
+        }]),
+        ..Default::default()
+    };
+    flows::create_event(&db, &mut search_engine, &notify, None, e).unwrap();
+    let e = usecases::NewEvent {
+        title: "once".into(),
+        start: now + week + 100,
+        created_by: Some("test@example.com".into()),
+        ..Default::default()
+    };
+    flows::create_event(&db, &mut search_engine, &notify, None, e).unwrap();
+
+    let mut res = client
+        .get(format!(
+            "/events?start_min={}&start_max={}",
+            now + 100,
+            now + 3 * week
+        ))
+        .header(ContentType::JSON)
+        .dispatch();
+    assert_eq!(res.status(), HttpStatus::Ok);
+    let events: Vec<json::Event> = serde_json::from_str(&res.body_string().unwrap()).unwrap();
+    let starts: Vec<_> = events.iter().map(|e| (e.title.as_str(), e.start)).collect();
+    assert_eq!(
+        vec![
+            ("weekly", now + week),
+            ("once", now + week + 100),
+            ("weekly", now + 3 * week),
+        ],
+        starts
+    );
+    assert_eq!(Some(now + week), events[0].recurrence_id);
+    assert_eq!(Some(now + week + 3600), events[0].end);
+    assert_eq!(Some("FREQ=WEEKLY"), events[0].recurrence.as_deref());
+    assert_eq!(None, events[1].recurrence_id);
+}
+
+#[test]
+fn recurring_events_keep_local_time_across_dst_changes() {
+    let (client, db, mut search_engine, notify) = setup2();
+    let tz = chrono_tz::Europe::Berlin;
+    let start = Utc::now()
+        .with_timezone(&tz)
+        .date()
+        .succ()
+        .and_hms(18, 0, 0)
+        .timestamp();
+    let e = usecases::NewEvent {
+        title: "weekly".into(),
+        start,
+        end: Some(start + 7200),
+        time_zone: Some("Europe/Berlin".into()),
+        created_by: Some("test@example.com".into()),
+        // Spans at least one change of daylight saving time
+        recurrence: Some("FREQ=WEEKLY;COUNT=53".into()),
+        ..Default::default()
+    };
+    flows::create_event(&db, &mut search_engine, &notify, None, e).unwrap();
+
+    let mut res = client
+        .get(format!("/events?start_min={}&limit=100", start))
+        .header(ContentType::JSON)
+        .dispatch();
+    assert_eq!(res.status(), HttpStatus::Ok);
+    let events: Vec<json::Event> = serde_json::from_str(&res.body_string().unwrap()).unwrap();
+    assert_eq!(53, events.len());
+    assert!(events.iter().all(|e| e.time_zone == "Europe/Berlin"));
+    assert!(events.iter().all(|e| e.start_local.contains("T18:00:00+")));
+    assert!(events
+        .iter()
+        .all(|e| e.end_local.as_ref().unwrap().contains("T20:00:00+")));
+    let offset_changed = events
+        .windows(2)
+        .position(|w| (w[1].start - w[0].start) % (7 * 24 * 60 * 60) != 0)
+        .unwrap()
+        + 1;
+
+    // The first occurrence after the change matches exactly
+    let start_min = events[offset_changed].start;
+    let mut res = client
+        .get(format!(
+            "/events?start_min={}&start_max={}",
+            start_min, start_min
+        ))
+        .header(ContentType::JSON)
+        .dispatch();
+    assert_eq!(res.status(), HttpStatus::Ok);
+    let events: Vec<json::Event> = serde_json::from_str(&res.body_string().unwrap()).unwrap();
+    assert_eq!(1, events.len());
+    assert_eq!(start_min, events[0].start);
+}
+
 #[test]
 fn filtered_by_bounding_box() {
     let (client, db, mut search_engine, notify) = setup2();
@@ -307,3 +478,53 @@ fn filtered_by_bounding_box() {
     assert!(!body_str.contains("\"title\":\"0.3-5\""));
     assert!(body_str.contains("\"title\":\"12-0\""));
 }
+
+#[test]
+fn filtered_and_sorted_by_distance() {
+    let (client, db, mut search_engine, notify) = setup2();
+    let now = Utc::now().naive_utc().timestamp();
+    let events = &[
+        ("far", 48.52, now),
+        ("near", 48.501, now + 100),
+        ("outside", 48.6, now + 200),
+    ];
+    for &(title, lat, start) in events {
+        let e = usecases::NewEvent {
+            title: title.into(),
+            start,
+            lat: Some(lat),
+            lng: Some(9.0),
+            created_by: Some("test@example.com".into()),
+            ..Default::default()
+        };
+        flows::create_event(&db, &mut search_engine, &notify, None, e).unwrap();
+    }
+
+    let mut res = client
+        .get("/events?near=48.5,9.0&radius=5000")
+        .header(ContentType::JSON)
+        .dispatch();
+    assert_eq!(res.status(), HttpStatus::Ok);
+    test_json(&res);
+    let events: Vec<json::Event> = serde_json::from_str(&res.body_string().unwrap()).unwrap();
+    let titles: Vec<_> = events.iter().map(|e| e.title.as_str()).collect();
+    assert_eq!(vec!["far", "near"], titles);
+    assert!(events.iter().all(|e| e.distance.is_some()));
+
+    let mut res = client
+        .get("/events?near=48.5,9.0&radius=5000&sort=distance")
+        .header(ContentType::JSON)
+        .dispatch();
+    assert_eq!(res.status(), HttpStatus::Ok);
+    let events: Vec<json::Event> = serde_json::from_str(&res.body_string().unwrap()).unwrap();
+    let titles: Vec<_> = events.iter().map(|e| e.title.as_str()).collect();
+    assert_eq!(vec!["near", "far"], titles);
+    let distance = events[0].distance.unwrap();
+    assert!(distance > 100.0 && distance < 120.0);
+
+    let res = client
+        .get("/events?sort=distance")
+        .header(ContentType::JSON)
+        .dispatch();
+    assert_eq!(res.status(), HttpStatus::BadRequest);
+}
@@ -0,0 +1,27 @@
+use super::*;
+use crate::infrastructure::jobs;
+
+#[get("/jobs/failed?<offset>&<limit>")]
+pub fn get_failed_jobs(
+    db: db::Connections,
+    login: Login,
+    offset: Option<u64>,
+    limit: Option<u64>,
+) -> Result<Vec<json::Job>> {
+    let pagination = Pagination { offset, limit };
+    let db = db.shared()?;
+    // Only admins are entitled to inspect the job queue
+    usecases::authorize_user_by_email(&*db, &login.0, Role::Admin)?;
+    let jobs = db.load_jobs(JobStatus::Failed, &pagination)?;
+    Ok(Json(jobs.into_iter().map(Into::into).collect()))
+}
+
+#[post("/jobs/failed/retry")]
+pub fn post_retry_failed_jobs(connections: db::Connections, login: Login) -> Result<usize> {
+    {
+        let db = connections.shared()?;
+        usecases::authorize_user_by_email(&*db, &login.0, Role::Admin)?;
+    }
+    let count = jobs::retry_failed_jobs(&connections)?;
+    Ok(Json(count))
+}
@@ -1,13 +1,13 @@
 use super::guards::*;
 use crate::{
-    adapters::{self, json},
+    adapters::{self, geojson, json},
     core::{
         prelude::*,
         usecases::{self, DuplicateType},
         util::{self, geo},
     },
     infrastructure::{
-        db::{sqlite, tantivy},
+        db::{self, tantivy},
         error::AppError,
         flows::prelude as flows,
     },
@@ -17,20 +17,26 @@ use rocket::{
     self,
     http::{ContentType, Cookie, Cookies, Status},
     request::Form,
-    response::{content::Content, Responder, Response},
-    Route,
+    response::{content::Content, status, Responder, Response},
+    Data, Route, State,
 };
 use rocket_contrib::json::Json;
-use std::result;
+use std::{convert::TryFrom, io::Read, result};
 
 mod count;
 pub mod events;
+mod jobs;
 mod places;
 mod ratings;
 mod search;
+mod subscriptions;
 #[cfg(test)]
 pub mod tests;
+mod tiles;
 mod users;
+mod webhooks;
+
+pub use self::tiles::TileCache;
 
 type Result<T> = result::Result<Json<T>, AppError>;
 type StatusResult = result::Result<Status, AppError>;
@@ -40,9 +46,13 @@ pub fn routes() -> Vec<Route> {
         post_login,
         post_logout,
         confirm_email_address,
-        subscribe_to_bbox,
-        get_bbox_subscriptions,
-        unsubscribe_all_bboxes,
+        subscriptions::subscribe_to_bbox,
+        subscriptions::get_bbox_subscriptions,
+        subscriptions::post_bbox_subscription,
+        subscriptions::put_bbox_subscription,
+        subscriptions::delete_bbox_subscription,
+        subscriptions::post_unsubscribe_bbox,
+        subscriptions::unsubscribe_all_bboxes,
         get_entry,
         get_entries_recently_changed,
         get_entries_most_popular_tags,
@@ -57,12 +67,15 @@ pub fn routes() -> Vec<Route> {
         events::get_event,
         events::get_events_chronologically,
         events::get_events_with_token,
+        events::get_events_ical,
+        events::ical_import,
         events::put_event,
         events::put_event_with_token,
         events::post_events_archive,
         events::delete_event,
         events::delete_event_with_token,
         events::csv_export,
+        events::geojson_export,
         users::post_request_password_reset,
         users::post_reset_password,
         users::post_user,
@@ -75,6 +88,9 @@ pub fn routes() -> Vec<Route> {
         get_category,
         get_tags,
         search::get_search,
+        search::get_search_clusters,
+        search::get_search_suggestions,
+        tiles::get_tile,
         get_duplicates,
         search::post_search_duplicates,
         count::get_count_entries,
@@ -82,9 +98,24 @@ pub fn routes() -> Vec<Route> {
         get_version,
         get_api,
         entries_csv_export,
+        places_geojson_export,
+        places_csv_import,
         places::count_pending_clearances,
         places::list_pending_clearances,
         places::update_pending_clearances,
+        places::get_place_owners,
+        places::put_place_owners,
+        places::count_place_proposals,
+        places::list_place_proposals,
+        places::get_place_proposals,
+        places::post_place_proposal_review,
+        places::post_place_revert,
+        webhooks::post_webhook,
+        webhooks::get_webhooks,
+        webhooks::delete_webhook,
+        webhooks::get_webhook_deliveries,
+        jobs::get_failed_jobs,
+        jobs::post_retry_failed_jobs,
     ]
 }
 
@@ -95,7 +126,7 @@ pub struct GetEntryQuery {
 
 #[get("/entries/<ids>?<query..>")]
 fn get_entry(
-    db: sqlite::Connections,
+    db: db::Connections,
     ids: String,
     query: Form<GetEntryQuery>,
 ) -> Result<Vec<json::Entry>> {
@@ -130,7 +161,7 @@ const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
 
 #[get("/entries/recently-changed?<since>&<until>&<with_ratings>&<offset>&<limit>")]
 fn get_entries_recently_changed(
-    db: sqlite::Connections,
+    db: db::Connections,
     since: Option<i64>, // in seconds
     until: Option<i64>, // in seconds
     with_ratings: Option<bool>,
@@ -207,7 +238,7 @@ const ENTRIES_MOST_POPULAR_TAGS_PAGINATION_LIMIT_MAX: u64 = 1000;
 
 #[get("/entries/most-popular-tags?<min_count>&<max_count>&<offset>&<limit>")]
 pub fn get_entries_most_popular_tags(
-    db: sqlite::Connections,
+    db: db::Connections,
     min_count: Option<u64>,
     max_count: Option<u64>,
     offset: Option<u64>,
@@ -232,7 +263,7 @@ pub fn get_entries_most_popular_tags(
 
 #[get("/places/<id>")]
 pub fn get_place(
-    db: sqlite::Connections,
+    db: db::Connections,
     id: String,
 ) -> Result<(json::PlaceRoot, json::PlaceRevision, json::ReviewStatus)> {
     let (place, status) = {
@@ -249,7 +280,7 @@ pub fn get_place(
 
 #[get("/places/<id>/history/<revision>")]
 pub fn get_place_history_revision(
-    db: sqlite::Connections,
+    db: db::Connections,
     login: Option<Login>,
     bearer: Option<Bearer>,
     id: String,
@@ -276,7 +307,7 @@ pub fn get_place_history_revision(
 
 #[get("/places/<id>/history", rank = 2)]
 pub fn get_place_history(
-    db: sqlite::Connections,
+    db: db::Connections,
     login: Option<Login>,
     bearer: Option<Bearer>,
     id: String,
@@ -303,7 +334,7 @@ pub fn get_place_history(
 #[post("/places/<ids>/review", data = "<review>")]
 pub fn post_places_review(
     login: Login,
-    db: sqlite::Connections,
+    db: db::Connections,
     mut search_engine: tantivy::SearchEngine,
     ids: String,
     review: Json<json::Review>,
@@ -340,7 +371,7 @@ pub fn post_places_review(
 
 #[get("/duplicates/<ids>")]
 pub fn get_duplicates(
-    connections: sqlite::Connections,
+    connections: db::Connections,
     search_engine: tantivy::SearchEngine,
     ids: String,
 ) -> Result<Vec<(String, String, DuplicateType)>> {
@@ -372,7 +403,7 @@ fn get_api() -> Content<&'static str> {
 
 #[post("/login", format = "application/json", data = "<login>")]
 fn post_login(
-    db: sqlite::Connections,
+    db: db::Connections,
     mut cookies: Cookies,
     login: Json<json::Credentials>,
 ) -> Result<()> {
@@ -408,67 +439,17 @@ struct ConfirmationToken {
     format = "application/json",
     data = "<token>"
 )]
-fn confirm_email_address(db: sqlite::Connections, token: Json<ConfirmationToken>) -> Result<()> {
+fn confirm_email_address(db: db::Connections, token: Json<ConfirmationToken>) -> Result<()> {
     let token = token.into_inner().token;
     usecases::confirm_email_address(&*db.exclusive()?, &token)?;
     Ok(Json(()))
 }
 
-#[post(
-    "/subscribe-to-bbox",
-    format = "application/json",
-    data = "<coordinates>"
-)]
-fn subscribe_to_bbox(
-    db: sqlite::Connections,
-    user: Login,
-    coordinates: Json<Vec<json::Coordinate>>,
-) -> Result<()> {
-    let sw_ne: Vec<_> = coordinates
-        .into_inner()
-        .into_iter()
-        .map(MapPoint::from)
-        .collect();
-    if sw_ne.len() != 2 {
-        return Err(Error::Parameter(ParameterError::Bbox).into());
-    }
-    let bbox = geo::MapBbox::new(sw_ne[0], sw_ne[1]);
-    let Login(email) = user;
-    usecases::subscribe_to_bbox(&*db.exclusive()?, email, bbox)?;
-    Ok(Json(()))
-}
-
-#[delete("/unsubscribe-all-bboxes")]
-fn unsubscribe_all_bboxes(db: sqlite::Connections, user: Login) -> Result<()> {
-    let Login(email) = user;
-    usecases::unsubscribe_all_bboxes(&*db.exclusive()?, &email)?;
-    Ok(Json(()))
-}
-
-#[get("/bbox-subscriptions")]
-fn get_bbox_subscriptions(
-    db: sqlite::Connections,
-    user: Login,
-) -> Result<Vec<json::BboxSubscription>> {
-    let Login(email) = user;
-    let user_subscriptions = usecases::get_bbox_subscriptions(&*db.shared()?, &email)?
-        .into_iter()
-        .map(|s| json::BboxSubscription {
-            id: s.id.into(),
-            south_west_lat: s.bbox.southwest().lat().to_deg(),
-            south_west_lng: s.bbox.southwest().lng().to_deg(),
-            north_east_lat: s.bbox.northeast().lat().to_deg(),
-            north_east_lng: s.bbox.northeast().lng().to_deg(),
-        })
-        .collect();
-    Ok(Json(user_subscriptions))
-}
-
 #[post("/entries", format = "application/json", data = "<body>")]
 fn post_entry(
     bearer: Option<Bearer>,
     created_by_account: Option<Account>,
-    connections: sqlite::Connections,
+    connections: db::Connections,
     notify: Notify,
     mut search_engine: tantivy::SearchEngine,
     body: Json<json::NewPlace>,
@@ -500,13 +481,15 @@ fn post_entry(
 #[put("/entries/<id>", format = "application/json", data = "<data>")]
 fn put_entry(
     bearer: Option<Bearer>,
+    login: Option<Login>,
     created_by_account: Option<Account>,
-    connections: sqlite::Connections,
+    connections: db::Connections,
     mut search_engine: tantivy::SearchEngine,
     notify: Notify,
+    place_edit_mode: State<usecases::PlaceEditMode>,
     id: String,
     data: Json<json::UpdatePlace>,
-) -> Result<String> {
+) -> result::Result<status::Custom<Json<String>>, AppError> {
     let created_by_org = if let Some(bearer) = bearer {
         let api_token = bearer.0;
         Some(usecases::authorize_organization_by_api_token(
@@ -516,29 +499,36 @@ fn put_entry(
     } else {
         None
     };
-    Ok(Json(
-        flows::update_place(
-            &connections,
-            &mut search_engine,
-            &*notify,
-            id.into(),
-            data.into_inner().into(),
-            created_by_account.as_ref().map(|a| a.email()),
-            created_by_org.as_ref(),
-        )?
-        .id
-        .into(),
-    ))
+    let stored = flows::update_place(
+        &connections,
+        &mut search_engine,
+        &*notify,
+        id.into(),
+        data.into_inner().into(),
+        created_by_account
+            .as_ref()
+            .map(Account::email)
+            .or_else(|| login.as_ref().map(|login| login.0.as_str())),
+        created_by_org.as_ref(),
+        *place_edit_mode,
+    )?;
+    // Proposed changes are accepted, but only applied
+    // after being reviewed by scouts, admins or the owners
+    let (response_status, place_id) = match stored {
+        usecases::StoredPlace::Revision(place, _) => (Status::Ok, place.id),
+        usecases::StoredPlace::Proposal(proposal) => (Status::Accepted, proposal.place.id),
+    };
+    Ok(status::Custom(response_status, Json(place_id.into())))
 }
 
 #[get("/tags")]
-fn get_tags(connections: sqlite::Connections) -> Result<Vec<String>> {
+fn get_tags(connections: db::Connections) -> Result<Vec<String>> {
     let tags = connections.shared()?.all_tags()?;
     Ok(Json(tags.into_iter().map(|t| t.id).collect()))
 }
 
 #[get("/categories")]
-fn get_categories(connections: sqlite::Connections) -> Result<Vec<json::Category>> {
+fn get_categories(connections: db::Connections) -> Result<Vec<json::Category>> {
     let categories = connections
         .shared()?
         .all_categories()?
@@ -549,7 +539,7 @@ fn get_categories(connections: sqlite::Connections) -> Result<Vec<json::Category
 }
 
 #[get("/categories/<ids>")]
-fn get_category(connections: sqlite::Connections, ids: String) -> Result<Vec<json::Category>> {
+fn get_category(connections: db::Connections, ids: String) -> Result<Vec<json::Category>> {
     // TODO: Only lookup and return a single entity
     // TODO: Add a new method for searching multiple ids
     let uids = util::split_ids(&ids);
@@ -566,14 +556,13 @@ fn get_category(connections: sqlite::Connections, ids: String) -> Result<Vec<jso
     Ok(Json(categories))
 }
 
-#[get("/export/entries.csv?<query..>")]
-fn entries_csv_export(
-    connections: sqlite::Connections,
-    search_engine: tantivy::SearchEngine,
+fn export_places(
+    connections: &db::Connections,
+    search_engine: &tantivy::SearchEngine,
     bearer: Option<Bearer>,
     login: Login,
-    query: Form<search::SearchQuery>,
-) -> result::Result<Content<String>, AppError> {
+    query: &search::SearchQuery,
+) -> result::Result<Vec<(Place, Vec<Category>, AvgRatingValue)>, AppError> {
     let db = connections.shared()?;
 
     let moderated_tags = if let Some(bearer) = bearer {
@@ -586,7 +575,7 @@ fn entries_csv_export(
 
     let user = usecases::authorize_user_by_email(&*db, &login.0, Role::Scout)?;
 
-    let (req, limit) = search::parse_search_query(&query)?;
+    let (req, limit) = search::parse_search_query(query)?;
     let limit = if let Some(limit) = limit {
         // Limited
         limit
@@ -597,7 +586,7 @@ fn entries_csv_export(
 
     let entries_categories_and_ratings = {
         let all_categories: Vec<_> = db.all_categories()?;
-        usecases::search(&*db, &search_engine, req, limit)?
+        usecases::search(&*db, search_engine, req, limit)?
             .0
             .into_iter()
             .filter_map(|indexed_entry| {
@@ -631,7 +620,18 @@ fn entries_csv_export(
     // Release the database connection asap
     drop(db);
 
-    let records: Vec<_> = entries_categories_and_ratings
+    Ok(entries_categories_and_ratings)
+}
+
+#[get("/export/entries.csv?<query..>")]
+fn entries_csv_export(
+    connections: db::Connections,
+    search_engine: tantivy::SearchEngine,
+    bearer: Option<Bearer>,
+    login: Login,
+    query: Form<search::SearchQuery>,
+) -> result::Result<Content<String>, AppError> {
+    let records: Vec<_> = export_places(&connections, &search_engine, bearer, login, &query)?
         .into_iter()
         .map(adapters::csv::CsvRecord::from)
         .collect();
@@ -648,10 +648,120 @@ fn entries_csv_export(
     Ok(Content(ContentType::CSV, data))
 }
 
+#[get("/export/places.geojson?<query..>")]
+fn places_geojson_export(
+    connections: db::Connections,
+    search_engine: tantivy::SearchEngine,
+    bearer: Option<Bearer>,
+    login: Login,
+    query: Form<search::SearchQuery>,
+) -> result::Result<Content<Json<geojson::FeatureCollection<geojson::PlaceProperties>>>, AppError> {
+    let features = export_places(&connections, &search_engine, bearer, login, &query)?
+        .into_iter()
+        .map(geojson::PlaceFeature::from)
+        .collect();
+    Ok(Content(geojson_content_type(), Json(features)))
+}
+
+// Limit the size of imported files
+const MAX_IMPORT_SIZE: u64 = 10 * 1024 * 1024;
+
+// Larger files are rejected instead of silently truncated
+fn read_import_data(data: Data) -> result::Result<String, AppError> {
+    let mut text = String::new();
+    data.open()
+        .take(MAX_IMPORT_SIZE + 1)
+        .read_to_string(&mut text)?;
+    if text.len() as u64 > MAX_IMPORT_SIZE {
+        return Err(Error::Parameter(ParameterError::PayloadTooLarge).into());
+    }
+    Ok(text)
+}
+
+#[post(
+    "/import/places.csv?<dry_run>&<allow_duplicates>",
+    format = "text/csv",
+    data = "<data>"
+)]
+fn places_csv_import(
+    connections: db::Connections,
+    mut search_engine: tantivy::SearchEngine,
+    bearer: Option<Bearer>,
+    login: Login,
+    dry_run: Option<bool>,
+    allow_duplicates: Option<bool>,
+    data: Data,
+) -> Result<Vec<json::PlaceImportResult>> {
+    let created_by_org = {
+        let db = connections.shared()?;
+        usecases::authorize_user_by_email(&*db, &login.0, Role::Scout)?;
+        if let Some(bearer) = bearer {
+            let api_token = bearer.0;
+            Some(usecases::authorize_organization_by_api_token(
+                &*db, &api_token,
+            )?)
+        } else {
+            None
+        }
+    };
+    let csv = read_import_data(data)?;
+    let (lines, new_places): (Vec<_>, Vec<_>) = adapters::csv::read_place_records(csv.as_bytes())?
+        .into_iter()
+        .map(|(line, record)| {
+            let new_place = record.map_err(AppError::from).and_then(|record| {
+                usecases::NewPlace::try_from(record)
+                    .map_err(|err| AppError::from(Error::Parameter(err)))
+            });
+            (line, new_place)
+        })
+        .unzip();
+    let options = flows::ImportPlacesOptions {
+        dry_run: dry_run.unwrap_or(false),
+        allow_duplicates: allow_duplicates.unwrap_or(false),
+    };
+    let reports = flows::import_places(
+        &connections,
+        &mut search_engine,
+        new_places,
+        Some(&login.0),
+        created_by_org.as_ref(),
+        options,
+    )?;
+    let results = lines
+        .into_iter()
+        .zip(reports)
+        .map(|(line, report)| {
+            let flows::PlaceImportReport { duplicates, result } = report;
+            let (id, error) = match result {
+                Ok(place) => (place.map(|p| p.id.into()), None),
+                Err(err) => (None, Some(err.to_string())),
+            };
+            json::PlaceImportResult {
+                line,
+                id,
+                error,
+                duplicates: duplicates.into_iter().map(Into::into).collect(),
+            }
+        })
+        .collect();
+    Ok(Json(results))
+}
+
+fn geojson_content_type() -> ContentType {
+    let (top, sub) = geojson::MEDIA_TYPE;
+    ContentType::new(top, sub)
+}
+
 impl<'r> Responder<'r> for AppError {
-    fn respond_to(self, _: &rocket::Request) -> result::Result<Response<'r>, Status> {
+    fn respond_to(self, request: &rocket::Request) -> result::Result<Response<'r>, Status> {
         if let AppError::Business(ref err) = self {
             match *err {
+                Error::Parameter(ParameterError::InvalidOpeningHours(err)) => {
+                    let mut response =
+                        Json(json::OpeningHoursError::from(err)).respond_to(request)?;
+                    response.set_status(Status::BadRequest);
+                    return Ok(response);
+                }
                 Error::Parameter(ref err) => {
                     return Err(match *err {
                         ParameterError::Credentials | ParameterError::Unauthorized => {
@@ -664,6 +774,7 @@ impl<'r> Responder<'r> for AppError {
                         ParameterError::Forbidden | ParameterError::ModeratedTag => {
                             Status::Forbidden
                         }
+                        ParameterError::PayloadTooLarge => Status::PayloadTooLarge,
                         _ => Status::BadRequest,
                     });
                 }
@@ -2,7 +2,7 @@ use super::*;
 
 #[get("/places/clearance/count")]
 pub fn count_pending_clearances(
-    db: sqlite::Connections,
+    db: db::Connections,
     org_token: Bearer,
 ) -> Result<json::ResultCount> {
     let count = usecases::clearance::place::count_pending_clearances(&*db.shared()?, &org_token.0)?;
@@ -11,7 +11,7 @@ pub fn count_pending_clearances(
 
 #[get("/places/clearance?<offset>&<limit>")]
 pub fn list_pending_clearances(
-    db: sqlite::Connections,
+    db: db::Connections,
     org_token: Bearer,
     offset: Option<u64>,
     limit: Option<u64>,
@@ -29,7 +29,7 @@ pub fn list_pending_clearances(
 
 #[post("/places/clearance", data = "<clearances>")]
 pub fn update_pending_clearances(
-    db: sqlite::Connections,
+    db: db::Connections,
     org_token: Bearer,
     clearances: Json<Vec<json::ClearanceForPlace>>,
 ) -> Result<json::ResultCount> {
@@ -47,3 +47,136 @@ pub fn update_pending_clearances(
         count: count as u64,
     }))
 }
+
+#[get("/places/<id>/owners")]
+pub fn get_place_owners(
+    db: db::Connections,
+    login: Login,
+    id: String,
+) -> Result<json::PlaceOwnership> {
+    let ownership = usecases::load_place_ownership(&*db.shared()?, &login.0, &id.into())?;
+    Ok(Json(ownership.into()))
+}
+
+#[put(
+    "/places/<id>/owners",
+    format = "application/json",
+    data = "<ownership>"
+)]
+pub fn put_place_owners(
+    db: db::Connections,
+    login: Login,
+    id: String,
+    ownership: Json<json::PlaceOwnership>,
+) -> Result<()> {
+    flows::change_place_ownership(&db, &login.0, &id.into(), ownership.into_inner().into())?;
+    Ok(Json(()))
+}
+
+// Proposals are listed for the places that are owned either
+// by the logged in user or by the organization of the API token.
+// Scouts and admins get the pending proposals of all places.
+fn place_owner(
+    db: &db::DbConnection,
+    login: Option<Login>,
+    org_token: Option<Bearer>,
+) -> result::Result<PlaceOwner, AppError> {
+    if let Some(login) = login {
+        return Ok(PlaceOwner::User(login.0.into()));
+    }
+    if let Some(org_token) = org_token {
+        let org = usecases::authorize_organization_by_api_token(db, &org_token.0)?;
+        return Ok(PlaceOwner::Organization(org.id));
+    }
+    Err(Error::Parameter(ParameterError::Unauthorized).into())
+}
+
+#[get("/places/proposals/count")]
+pub fn count_place_proposals(
+    db: db::Connections,
+    login: Option<Login>,
+    org_token: Option<Bearer>,
+) -> Result<json::ResultCount> {
+    let db = db.shared()?;
+    let owner = place_owner(&*db, login, org_token)?;
+    let count = usecases::count_place_proposals(&*db, &owner)?;
+    Ok(Json(json::ResultCount { count }))
+}
+
+#[get("/places/proposals?<offset>&<limit>")]
+pub fn list_place_proposals(
+    db: db::Connections,
+    login: Option<Login>,
+    org_token: Option<Bearer>,
+    offset: Option<u64>,
+    limit: Option<u64>,
+) -> Result<Vec<json::PlaceProposal>> {
+    let pagination = Pagination { offset, limit };
+    let db = db.shared()?;
+    let owner = place_owner(&*db, login, org_token)?;
+    let proposals = usecases::list_place_proposals(&*db, &owner, &pagination)?;
+    Ok(Json(proposals.into_iter().map(Into::into).collect()))
+}
+
+#[get("/places/<id>/proposals")]
+pub fn get_place_proposals(
+    db: db::Connections,
+    login: Login,
+    id: String,
+) -> Result<Vec<json::PlaceProposal>> {
+    let proposals = usecases::load_place_proposals(&*db.shared()?, &login.0, &id.into())?;
+    Ok(Json(proposals.into_iter().map(Into::into).collect()))
+}
+
+#[post(
+    "/places/proposals/<id>/review",
+    format = "application/json",
+    data = "<review>"
+)]
+pub fn post_place_proposal_review(
+    db: db::Connections,
+    mut search_engine: tantivy::SearchEngine,
+    notify: Notify,
+    login: Login,
+    id: String,
+    review: Json<json::ReviewPlaceProposal>,
+) -> Result<()> {
+    let json::ReviewPlaceProposal {
+        decision,
+        fields,
+        comment,
+    } = review.into_inner();
+    let decision = match decision {
+        json::ProposalDecision::Accept => usecases::ProposalDecision::Accept,
+        json::ProposalDecision::Merge => {
+            usecases::ProposalDecision::Merge(fields.into_iter().map(Into::into).collect())
+        }
+        json::ProposalDecision::Reject => usecases::ProposalDecision::Reject,
+    };
+    let review = usecases::ProposalReview {
+        reviewer_email: login.0.into(),
+        decision,
+        comment,
+    };
+    flows::review_place_proposal(&db, &mut search_engine, &*notify, &id.into(), review)?;
+    Ok(Json(()))
+}
+
+#[post("/places/<id>/revert", format = "application/json", data = "<revert>")]
+pub fn post_place_revert(
+    db: db::Connections,
+    mut search_engine: tantivy::SearchEngine,
+    notify: Notify,
+    login: Login,
+    id: String,
+    revert: Json<json::RevertPlace>,
+) -> Result<()> {
+    let json::RevertPlace { revision, comment } = revert.into_inner();
+    let revert = usecases::RevertPlace {
+        reviewer_email: login.0.into(),
+        revision: revision.into(),
+        comment,
+    };
+    flows::revert_place(&db, &mut search_engine, &*notify, &id, revert)?;
+    Ok(Json(()))
+}
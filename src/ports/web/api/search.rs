@@ -1,23 +1,31 @@
 use crate::{
-    adapters::json,
+    adapters::{geojson, json},
     core::{
         prelude::*,
         usecases,
-        util::{self, geo},
+        util::{
+            self, geo,
+            parse::{local_date_time, parse_near_params, parse_page_cursor, parse_time_zone},
+        },
     },
     infrastructure::{
-        db::{sqlite, tantivy},
+        db::{self, tantivy},
         error::AppError,
     },
 };
 
+use chrono::Utc;
+use chrono_tz::Tz;
 use rocket::{self, request::Form};
 use rocket_contrib::json::Json;
 use std::result;
 
 #[derive(FromForm, Clone)]
 pub struct SearchQuery {
-    bbox: String,
+    bbox: Option<String>,
+    near: Option<String>,
+    radius: Option<String>,
+    polygon: Option<String>,
     categories: Option<String>,
     ids: Option<String>,
     org_tag: Option<String>,
@@ -25,13 +33,25 @@ pub struct SearchQuery {
     text: Option<String>,
     status: Option<String>,
     limit: Option<usize>,
+    sort: Option<String>,
+    cursor: Option<String>,
+    open_now: Option<bool>,
+    open_at: Option<i64>,
+    tz: Option<String>,
 }
 
+// Opening hours are evaluated in this time zone
+// unless requested otherwise
+const DEFAULT_TIME_ZONE: Tz = chrono_tz::Europe::Berlin;
+
 pub fn parse_search_query(
     query: &'_ SearchQuery,
 ) -> result::Result<(usecases::SearchRequest<'_>, Option<usize>), AppError> {
     let SearchQuery {
         bbox,
+        near,
+        radius,
+        polygon,
         ids,
         categories,
         org_tag,
@@ -39,14 +59,47 @@ pub fn parse_search_query(
         text,
         status,
         limit,
+        sort: _,
+        cursor: _,
+        open_now,
+        open_at,
+        tz,
     } = query;
 
-    let bbox = bbox
-        .parse::<geo::MapBbox>()
-        .map_err(|_| ParameterError::Bbox)
+    let near = near
+        .as_deref()
+        .map(|near| parse_near_params(near, radius.as_deref().unwrap_or_default()))
+        .transpose()
+        .map_err(Error::Parameter)
+        .map_err(AppError::Business)?;
+
+    let polygon = polygon
+        .as_deref()
+        .map(geojson::parse_polygon)
+        .transpose()
+        .and_then(|polygon| {
+            if let Some(ref polygon) = polygon {
+                util::validate::polygon(polygon)?;
+            }
+            Ok(polygon)
+        })
         .map_err(Error::Parameter)
         .map_err(AppError::Business)?;
 
+    let bbox = match (bbox, near, &polygon) {
+        (Some(bbox), _, _) => bbox
+            .parse::<geo::MapBbox>()
+            .map_err(|_| ParameterError::Bbox)
+            .map_err(Error::Parameter)
+            .map_err(AppError::Business)?,
+        // Searching within a circle or a region doesn't require a bbox
+        (None, Some(circle), _) => circle.bounding_bbox(),
+        (None, None, Some(polygon)) => polygon.bounding_bbox(),
+        (None, None, None) => {
+            return Err(AppError::Business(Error::Parameter(ParameterError::Bbox)));
+        }
+    };
+
     let ids = ids.as_deref().map(util::split_ids).unwrap_or_default();
 
     let categories = categories
@@ -80,34 +133,90 @@ pub fn parse_search_query(
         })
         .collect();
 
+    let open_at = if open_at.is_some() {
+        *open_at
+    } else if open_now.unwrap_or(false) {
+        Some(Utc::now().timestamp())
+    } else {
+        None
+    };
+    let open_at = open_at
+        .map(|seconds| {
+            let tz = tz
+                .as_deref()
+                .map(parse_time_zone)
+                .transpose()?
+                .unwrap_or(DEFAULT_TIME_ZONE);
+            local_date_time(seconds, tz)
+        })
+        .transpose()
+        .map_err(Error::Parameter)
+        .map_err(AppError::Business)?;
+
     Ok((
         usecases::SearchRequest {
             bbox,
+            near,
+            polygon,
             ids,
             categories,
             org_tag: org_tag.as_ref().map(String::as_str),
             hash_tags,
             text,
             status,
+            open_at,
         },
         *limit,
     ))
 }
 
+// Results are returned page by page if either a sort
+// order or a cursor has been requested
+fn parse_search_page(
+    query: &SearchQuery,
+) -> result::Result<Option<(IndexOrder, Option<PageCursor>)>, AppError> {
+    let cursor = query
+        .cursor
+        .as_deref()
+        .filter(|cursor| !cursor.is_empty())
+        .map(parse_page_cursor)
+        .transpose()
+        .map_err(Error::Parameter)?;
+    let order = match query.sort.as_deref() {
+        None | Some("") if cursor.is_none() => return Ok(None),
+        None | Some("") | Some("relevance") => IndexOrder::Relevance,
+        Some("rating") => IndexOrder::Rating,
+        Some("title") => IndexOrder::Title,
+        Some("created") => IndexOrder::Created,
+        Some("distance") => IndexOrder::Distance,
+        Some(_) => {
+            return Err(AppError::Business(Error::Parameter(
+                ParameterError::InvalidSearchOrder,
+            )));
+        }
+    };
+    Ok(Some((order, cursor)))
+}
+
 type Result<T> = result::Result<Json<T>, AppError>;
 
 const DEFAULT_RESULT_LIMIT: usize = 100;
 const MAX_RESULT_LIMIT: usize = 500;
 
-#[get("/search?<query..>")]
+// The number of the most frequent tags in facets
+const FACET_TAGS_LIMIT: usize = 20;
+
+#[get("/search?<facets>&<query..>")]
 #[allow(clippy::absurd_extreme_comparisons)]
 pub fn get_search(
-    connections: sqlite::Connections,
+    connections: db::Connections,
     search_engine: tantivy::SearchEngine,
+    facets: Option<bool>,
     query: Form<SearchQuery>,
 ) -> Result<json::SearchResponse> {
     let query = query.into_inner();
     let (req, limit) = parse_search_query(&query)?;
+    let page = parse_search_page(&query)?;
 
     let limit = if let Some(limit) = limit {
         if limit > MAX_RESULT_LIMIT {
@@ -132,14 +241,82 @@ pub fn get_search(
         DEFAULT_RESULT_LIMIT
     };
 
-    let (visible, invisible) =
-        usecases::search(&*connections.shared()?, &search_engine, req, limit)?;
+    let facets = if facets.unwrap_or(false) {
+        let facets = usecases::search_facets(&search_engine, req.clone(), FACET_TAGS_LIMIT)?;
+        Some(facets.into())
+    } else {
+        None
+    };
+
+    let near = req.near;
+    let (visible, invisible, next_cursor) = if let Some((order, cursor)) = page {
+        let (visible, next_cursor) = usecases::search_page(
+            &*connections.shared()?,
+            &search_engine,
+            req,
+            order,
+            cursor.as_ref(),
+            limit,
+        )?;
+        (visible, vec![], next_cursor)
+    } else {
+        let (visible, invisible) =
+            usecases::search(&*connections.shared()?, &search_engine, req, limit)?;
+        (visible, invisible, None)
+    };
+
+    let into_search_result = |place: IndexedPlace| {
+        let distance = near
+            .and_then(|circle| MapPoint::distance(circle.center(), place.pos))
+            .map(Distance::to_meters);
+        json::PlaceSearchResult {
+            distance,
+            ..place.into()
+        }
+    };
+
+    let visible: Vec<json::PlaceSearchResult> =
+        visible.into_iter().map(into_search_result).collect();
 
-    let visible: Vec<json::PlaceSearchResult> = visible.into_iter().map(Into::into).collect();
+    let invisible: Vec<json::PlaceSearchResult> =
+        invisible.into_iter().map(into_search_result).collect();
 
-    let invisible: Vec<json::PlaceSearchResult> = invisible.into_iter().map(Into::into).collect();
+    Ok(Json(json::SearchResponse {
+        visible,
+        invisible,
+        facets,
+        next_cursor: next_cursor.as_ref().map(PageCursor::encode_to_string),
+    }))
+}
+
+#[get("/search/clusters?<zoom>&<query..>")]
+pub fn get_search_clusters(
+    search_engine: tantivy::SearchEngine,
+    zoom: u8,
+    query: Form<SearchQuery>,
+) -> Result<Vec<json::PlaceCluster>> {
+    let query = query.into_inner();
+    let (req, _) = parse_search_query(&query)?;
+    let clusters = usecases::cluster_places(&search_engine, req, zoom)?;
+    Ok(Json(clusters.into_iter().map(Into::into).collect()))
+}
 
-    Ok(Json(json::SearchResponse { visible, invisible }))
+const DEFAULT_SUGGESTION_LIMIT: usize = 10;
+const MAX_SUGGESTION_LIMIT: usize = 100;
+
+#[get("/search/suggest?<q>&<query..>")]
+pub fn get_search_suggestions(
+    search_engine: tantivy::SearchEngine,
+    q: String,
+    query: Form<SearchQuery>,
+) -> Result<Vec<json::SearchSuggestion>> {
+    let query = query.into_inner();
+    let (req, limit) = parse_search_query(&query)?;
+    let limit = limit
+        .unwrap_or(DEFAULT_SUGGESTION_LIMIT)
+        .min(MAX_SUGGESTION_LIMIT);
+    let suggestions = usecases::suggest_search_terms(&search_engine, req, &q, limit)?;
+    Ok(Json(suggestions.into_iter().map(Into::into).collect()))
 }
 
 #[post("/search/duplicates", data = "<body>")]
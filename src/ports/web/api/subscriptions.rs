@@ -0,0 +1,159 @@
+use super::*;
+
+fn parse_delivery(delivery: &str) -> result::Result<SubscriptionDelivery, Error> {
+    delivery
+        .parse()
+        .map_err(|_| Error::Parameter(ParameterError::InvalidSubscriptionDelivery))
+}
+
+fn split_new_subscription(
+    new_subscription: json::NewBboxSubscription,
+) -> result::Result<(geo::MapBbox, usecases::BboxSubscriptionOptions), Error> {
+    let json::NewBboxSubscription {
+        title,
+        bbox,
+        polygon,
+        tags,
+        categories,
+        delivery,
+    } = new_subscription;
+    let polygon = polygon.as_deref().map(geojson::parse_polygon).transpose()?;
+    let bbox = match (bbox, &polygon) {
+        (Some(bbox), _) => {
+            geo::MapBbox::try_from(bbox).map_err(|_| Error::Parameter(ParameterError::Bbox))?
+        }
+        (None, Some(polygon)) => polygon.bounding_bbox(),
+        (None, None) => return Err(Error::Parameter(ParameterError::Bbox)),
+    };
+    let options = usecases::BboxSubscriptionOptions {
+        title,
+        polygon,
+        tags,
+        categories: categories.into_iter().map(Id::from).collect(),
+        delivery: parse_delivery(&delivery)?,
+    };
+    Ok((bbox, options))
+}
+
+/// Replaces all existing subscriptions of the user.
+#[post(
+    "/subscribe-to-bbox?<title>&<tags>&<categories>&<delivery>",
+    format = "application/json",
+    data = "<coordinates>"
+)]
+pub fn subscribe_to_bbox(
+    db: db::Connections,
+    user: Login,
+    coordinates: Json<Vec<json::Coordinate>>,
+    title: Option<String>,
+    tags: Option<String>,
+    categories: Option<String>,
+    delivery: Option<String>,
+) -> Result<()> {
+    let sw_ne: Vec<_> = coordinates
+        .into_inner()
+        .into_iter()
+        .map(MapPoint::from)
+        .collect();
+    if sw_ne.len() != 2 {
+        return Err(Error::Parameter(ParameterError::Bbox).into());
+    }
+    let bbox = geo::MapBbox::new(sw_ne[0], sw_ne[1]);
+    let delivery = delivery
+        .as_deref()
+        .map(parse_delivery)
+        .transpose()?
+        .unwrap_or_default();
+    let options = usecases::BboxSubscriptionOptions {
+        title,
+        polygon: None,
+        tags: tags
+            .as_deref()
+            .map(util::split_ids)
+            .unwrap_or_default()
+            .into_iter()
+            .map(ToString::to_string)
+            .collect(),
+        categories: categories
+            .as_deref()
+            .map(util::split_ids)
+            .unwrap_or_default()
+            .into_iter()
+            .map(Id::from)
+            .collect(),
+        delivery,
+    };
+    let Login(email) = user;
+    usecases::subscribe_to_bbox(&*db.exclusive()?, email, bbox, options)?;
+    Ok(Json(()))
+}
+
+#[delete("/unsubscribe-all-bboxes")]
+pub fn unsubscribe_all_bboxes(db: db::Connections, user: Login) -> Result<()> {
+    let Login(email) = user;
+    usecases::unsubscribe_all_bboxes(&*db.exclusive()?, &email)?;
+    Ok(Json(()))
+}
+
+#[get("/bbox-subscriptions")]
+pub fn get_bbox_subscriptions(
+    db: db::Connections,
+    user: Login,
+) -> Result<Vec<json::BboxSubscription>> {
+    let Login(email) = user;
+    let user_subscriptions = usecases::get_bbox_subscriptions(&*db.shared()?, &email)?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    Ok(Json(user_subscriptions))
+}
+
+#[post(
+    "/bbox-subscriptions",
+    format = "application/json",
+    data = "<new_subscription>"
+)]
+pub fn post_bbox_subscription(
+    db: db::Connections,
+    user: Login,
+    new_subscription: Json<json::NewBboxSubscription>,
+) -> Result<json::BboxSubscription> {
+    let Login(email) = user;
+    let (bbox, options) = split_new_subscription(new_subscription.into_inner())?;
+    let subscription = usecases::create_bbox_subscription(&*db.exclusive()?, email, bbox, options)?;
+    Ok(Json(subscription.into()))
+}
+
+#[put(
+    "/bbox-subscriptions/<id>",
+    format = "application/json",
+    data = "<subscription>"
+)]
+pub fn put_bbox_subscription(
+    db: db::Connections,
+    user: Login,
+    id: String,
+    subscription: Json<json::NewBboxSubscription>,
+) -> Result<json::BboxSubscription> {
+    let Login(email) = user;
+    let (bbox, options) = split_new_subscription(subscription.into_inner())?;
+    let subscription =
+        usecases::update_bbox_subscription(&*db.exclusive()?, &email, &id.into(), bbox, options)?;
+    Ok(Json(subscription.into()))
+}
+
+#[delete("/bbox-subscriptions/<id>")]
+pub fn delete_bbox_subscription(db: db::Connections, user: Login, id: String) -> StatusResult {
+    let Login(email) = user;
+    usecases::delete_bbox_subscription(&*db.exclusive()?, &email, &id.into())?;
+    Ok(Status::NoContent)
+}
+
+/// Cancels a single subscription without logging in,
+/// e.g. with the token from a notification e-mail.
+#[post("/unsubscribe-bbox", format = "application/json", data = "<token>")]
+pub fn post_unsubscribe_bbox(db: db::Connections, token: Json<ConfirmationToken>) -> Result<()> {
+    let token = token.into_inner().token;
+    usecases::unsubscribe_bbox_with_token(&*db.exclusive()?, &token)?;
+    Ok(Json(()))
+}
@@ -7,19 +7,19 @@ pub mod prelude {
         core::db::*, infrastructure::flows::prelude as flows, ports::web::tests::prelude::*,
     };
     use crate::{
-        infrastructure::db::{sqlite, tantivy},
+        infrastructure::db::{self, tantivy},
         ports::web::{self, api},
     };
     use ofdb_core::gateways::notify::NotificationGateway;
 
-    pub fn setup() -> (Client, sqlite::Connections) {
+    pub fn setup() -> (Client, db::Connections) {
         let (client, conn, _) = web::tests::setup(vec![("/", api::routes())]);
         (client, conn)
     }
 
     pub fn setup2() -> (
         Client,
-        sqlite::Connections,
+        db::Connections,
         tantivy::SearchEngine,
         impl NotificationGateway,
     ) {
@@ -54,6 +54,22 @@ fn create_place() {
     assert_eq!(body_str, format!("\"{}\"", eid));
 }
 
+#[test]
+fn create_place_with_invalid_opening_hours() {
+    let (client, db) = setup();
+    let mut response = client.post("/entries")
+                    .header(ContentType::JSON)
+                    .body(r#"{"title":"foo","description":"blablabla","lat":0.0,"lng":0.0,"categories":["x"],"license":"CC0-1.0","tags":[],"opening_hours":"Mo-Fr 08:00-18:61"}"#)
+                    .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+    test_json(&response);
+    let err: json::OpeningHoursError =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(12, err.position);
+    assert_eq!(json::OpeningHoursErrorReason::InvalidTime, err.reason);
+    assert!(db.shared().unwrap().all_places().unwrap().is_empty());
+}
+
 #[test]
 fn create_place_with_reserved_tag() {
     let (client, db) = setup();
@@ -343,6 +359,465 @@ fn search_with_categories_and_bbox() {
     assert!(!body_str.contains(&format!("\"{}\"", place_ids[2])));
 }
 
+#[test]
+fn search_near_position_with_radius() {
+    let entries = vec![
+        new_entry_with_category(Category::ID_NON_PROFIT, 48.52, 9.0),
+        new_entry_with_category(Category::ID_NON_PROFIT, 48.501, 9.0),
+        new_entry_with_category(Category::ID_NON_PROFIT, 48.6, 9.0),
+    ];
+    let (client, connections, mut search_engine, notify) = setup2();
+    let place_ids: Vec<_> = entries
+        .into_iter()
+        .map(|e| {
+            flows::create_place(&connections, &mut search_engine, &notify, e, None, None)
+                .unwrap()
+                .id
+                .to_string()
+        })
+        .collect();
+
+    let mut response = client.get("/search?near=48.5,9.0&radius=5000").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    test_json(&response);
+    let search_response: json::SearchResponse =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    let ids: Vec<_> = search_response
+        .visible
+        .iter()
+        .map(|p| p.id.as_str())
+        .collect();
+    assert_eq!(vec![place_ids[1].as_str(), place_ids[0].as_str()], ids);
+    assert!(search_response.invisible.is_empty());
+    let distance = search_response.visible[0].distance.unwrap();
+    assert!(distance > 100.0 && distance < 120.0);
+
+    let response = client.get("/search?near=48.5,9.0").dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+    let response = client.get("/search?near=48.5&radius=5000").dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+    let response = client.get("/search?text=foo").dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
+#[test]
+fn search_within_polygon() {
+    let entries = vec![
+        new_entry_with_category(Category::ID_NON_PROFIT, 48.2, 9.2),
+        new_entry_with_category(Category::ID_NON_PROFIT, 48.8, 9.8),
+    ];
+    let (client, connections, mut search_engine, notify) = setup2();
+    let place_ids: Vec<_> = entries
+        .into_iter()
+        .map(|e| {
+            flows::create_place(&connections, &mut search_engine, &notify, e, None, None)
+                .unwrap()
+                .id
+                .to_string()
+        })
+        .collect();
+
+    // POLYGON ((9 48, 10 48, 9 49, 9 48))
+    let mut response = client
+        .get("/search?polygon=POLYGON%20((9%2048,%2010%2048,%209%2049,%209%2048))")
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    test_json(&response);
+    let search_response: json::SearchResponse =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    let ids: Vec<_> = search_response
+        .visible
+        .iter()
+        .map(|p| p.id.as_str())
+        .collect();
+    assert_eq!(vec![place_ids[0].as_str()], ids);
+
+    let response = client
+        .get("/search?polygon=POLYGON%20((9%2048,%2010%2048))")
+        .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
+#[test]
+fn search_open_at() {
+    let new_place = |opening_hours: Option<&str>| usecases::NewPlace {
+        opening_hours: opening_hours.map(ToString::to_string),
+        ..new_entry_with_category(Category::ID_NON_PROFIT, 1.0, 1.0)
+    };
+    let entries = vec![
+        new_place(Some("Mo-Fr 08:00-18:00")),
+        new_place(Some("Sa,Su 10:00-16:00")),
+        new_place(None),
+    ];
+    let (client, connections, mut search_engine, notify) = setup2();
+    let place_ids: Vec<_> = entries
+        .into_iter()
+        .map(|e| {
+            flows::create_place(&connections, &mut search_engine, &notify, e, None, None)
+                .unwrap()
+                .id
+                .to_string()
+        })
+        .collect();
+
+    let search_ids = |query: &str| {
+        let mut response = client
+            .get(format!("/search?bbox=-10,-10,10,10&{}", query))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let search_response: json::SearchResponse =
+            serde_json::from_str(&response.body_string().unwrap()).unwrap();
+        let mut ids: Vec<_> = search_response.visible.into_iter().map(|p| p.id).collect();
+        ids.sort_unstable();
+        ids
+    };
+
+    assert_eq!(3, search_ids("").len());
+    // Wednesday, 2020-10-14 14:00 in Europe/Berlin
+    assert_eq!(vec![place_ids[0].clone()], search_ids("open_at=1602676800"));
+    // Saturday, 2020-10-17 14:00 in Europe/Berlin
+    assert_eq!(vec![place_ids[1].clone()], search_ids("open_at=1602936000"));
+    // Wednesday, 2020-10-14 19:30 in Europe/Berlin
+    assert!(search_ids("open_at=1602696600").is_empty());
+    assert_eq!(
+        vec![place_ids[0].clone()],
+        search_ids("open_at=1602696600&tz=UTC")
+    );
+
+    let response = client
+        .get("/search?bbox=-10,-10,10,10&open_now=true&tz=Europe/Nowhere")
+        .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
+#[test]
+fn search_clusters() {
+    let entries = vec![
+        new_entry_with_category(Category::ID_NON_PROFIT, 48.78, 9.18),
+        new_entry_with_category(Category::ID_COMMERCIAL, 48.79, 9.19),
+        new_entry_with_category(Category::ID_NON_PROFIT, 52.52, 13.40),
+    ];
+    let (client, connections, mut search_engine, notify) = setup2();
+    for e in entries {
+        flows::create_place(&connections, &mut search_engine, &notify, e, None, None).unwrap();
+    }
+
+    let mut response = client
+        .get("/search/clusters?zoom=6&bbox=48,9,49,10")
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    test_json(&response);
+    let clusters: Vec<json::PlaceCluster> =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(1, clusters.len());
+    assert_eq!(2, clusters[0].count);
+    assert_eq!(
+        Some(&1),
+        clusters[0].categories.get(Category::ID_NON_PROFIT)
+    );
+    assert_eq!(
+        Some(&1),
+        clusters[0].categories.get(Category::ID_COMMERCIAL)
+    );
+
+    let mut response = client
+        .get(format!(
+            "/search/clusters?zoom=0&bbox=-90,-180,90,180&categories={}",
+            Category::ID_COMMERCIAL
+        ))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let clusters: Vec<json::PlaceCluster> =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(1, clusters.len());
+    assert_eq!(1, clusters[0].count);
+
+    let response = client
+        .get("/search/clusters?zoom=23&bbox=48,9,49,10")
+        .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
+#[test]
+fn get_vector_tiles() {
+    let (client, connections, mut search_engine, notify) = setup2();
+    let create_place = |search_engine: &mut tantivy::SearchEngine, lat, lng| {
+        flows::create_place(
+            &connections,
+            search_engine,
+            &notify,
+            new_entry_with_category(Category::ID_NON_PROFIT, lat, lng),
+            None,
+            None,
+        )
+        .unwrap()
+        .id
+        .to_string()
+    };
+    let stuttgart = create_place(&mut search_engine, 48.78, 9.18);
+
+    let get_tile = |url: &str| {
+        let mut response = client.get(url).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.headers().get("Content-Type").collect::<Vec<_>>()[0],
+            "application/vnd.mapbox-vector-tile"
+        );
+        response.body_bytes().unwrap()
+    };
+    let contains = |data: &[u8], id: &str| data.windows(id.len()).any(|w| w == id.as_bytes());
+
+    let world = get_tile("/tiles/0/0/0.mvt");
+    assert!(contains(&world, &stuttgart));
+    // Tile 8/134/88 contains Stuttgart
+    assert!(contains(&get_tile("/tiles/8/134/88.mvt"), &stuttgart));
+    assert!(get_tile("/tiles/8/134/89.mvt").is_empty());
+
+    // Cached tiles are invalidated after the index has changed
+    assert_eq!(world, get_tile("/tiles/0/0/0.mvt"));
+    let berlin = create_place(&mut search_engine, 52.52, 13.40);
+    let world = get_tile("/tiles/0/0/0.mvt");
+    assert!(contains(&world, &stuttgart));
+    assert!(contains(&world, &berlin));
+
+    for url in &["/tiles/1/2/0.mvt", "/tiles/0/0/0.png", "/tiles/23/0/0.mvt"] {
+        let response = client.get(*url).dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+}
+
+#[test]
+fn search_with_facets() {
+    let new_place = |category: &str, tags: Vec<&str>| usecases::NewPlace {
+        tags: tags.into_iter().map(ToString::to_string).collect(),
+        ..new_entry_with_category(category, 1.0, 1.0)
+    };
+    let entries = vec![
+        new_place(Category::ID_NON_PROFIT, vec!["foo", "bar"]),
+        new_place(Category::ID_NON_PROFIT, vec!["foo"]),
+        new_place(Category::ID_COMMERCIAL, vec!["foo", "baz"]),
+    ];
+    let (client, connections, mut search_engine, notify) = setup2();
+    for e in entries {
+        flows::create_place(&connections, &mut search_engine, &notify, e, None, None).unwrap();
+    }
+
+    let mut response = client.get("/search?bbox=-10,-10,10,10").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let search_response: json::SearchResponse =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert!(search_response.facets.is_none());
+
+    let mut response = client
+        .get("/search?bbox=-10,-10,10,10&facets=true")
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let search_response: json::SearchResponse =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(3, search_response.visible.len());
+    let facets = search_response.facets.unwrap();
+    let tags: Vec<_> = facets
+        .tags
+        .iter()
+        .map(|json::TagFrequency(tag, count)| (tag.as_str(), *count))
+        .collect();
+    assert_eq!(vec![("foo", 3), ("bar", 1), ("baz", 1)], tags);
+    assert_eq!(Some(&2), facets.categories.get(Category::ID_NON_PROFIT));
+    assert_eq!(Some(&1), facets.categories.get(Category::ID_COMMERCIAL));
+    assert_eq!(1, facets.status.len());
+    assert_eq!(3, facets.status[0].1);
+
+    // Facets are counted for the current query
+    let mut response = client
+        .get(format!(
+            "/search?bbox=-10,-10,10,10&facets=true&categories={}",
+            Category::ID_COMMERCIAL
+        ))
+        .dispatch();
+    let search_response: json::SearchResponse =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    let facets = search_response.facets.unwrap();
+    assert_eq!(2, facets.tags.len());
+    assert_eq!(None, facets.categories.get(Category::ID_NON_PROFIT));
+}
+
+#[test]
+fn search_suggestions() {
+    let new_place = |title: &str, city: &str, tags: Vec<&str>, lat: f64| usecases::NewPlace {
+        title: title.into(),
+        city: Some(city.into()),
+        tags: tags.into_iter().map(ToString::to_string).collect(),
+        lat,
+        lng: 1.0,
+        ..default_new_entry()
+    };
+    let entries = vec![
+        new_place(
+            "Bioladen Kollektiv",
+            "Leipzig",
+            vec!["bio", "biokiste"],
+            1.0,
+        ),
+        new_place("Hofladen", "Bielefeld", vec!["bio"], 2.0),
+        new_place("Café Mitte", "Berlin", vec!["kaffee"], 3.0),
+        // Outside of the bounding box
+        new_place("Biomarkt", "Leipzig", vec!["bio"], 20.0),
+    ];
+    let (client, connections, mut search_engine, notify) = setup2();
+    for e in entries {
+        flows::create_place(&connections, &mut search_engine, &notify, e, None, None).unwrap();
+    }
+
+    let mut response = client
+        .get("/search/suggest?q=Bio&bbox=-10,-10,10,10")
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    test_json(&response);
+    let suggestions: Vec<json::SearchSuggestion> =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    let suggestions: Vec<_> = suggestions
+        .iter()
+        .map(|s| (s.kind, s.text.as_str(), s.count))
+        .collect();
+    // Most popular first
+    assert_eq!(
+        vec![
+            (json::SearchSuggestionKind::Tag, "bio", 2),
+            (json::SearchSuggestionKind::Title, "Bioladen Kollektiv", 1),
+            (json::SearchSuggestionKind::Tag, "biokiste", 1),
+        ],
+        suggestions
+    );
+
+    // Cities and words within titles are completed
+    let mut response = client
+        .get("/search/suggest?q=lei&bbox=-10,-10,10,10")
+        .dispatch();
+    let suggestions: Vec<json::SearchSuggestion> =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(1, suggestions.len());
+    assert_eq!(json::SearchSuggestionKind::City, suggestions[0].kind);
+    assert_eq!("Leipzig", suggestions[0].text);
+    let mut response = client
+        .get("/search/suggest?q=mit&bbox=-10,-10,10,10")
+        .dispatch();
+    let suggestions: Vec<json::SearchSuggestion> =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(1, suggestions.len());
+    assert_eq!("Café Mitte", suggestions[0].text);
+
+    // Hash tags and limit
+    let mut response = client
+        .get("/search/suggest?q=%23bio&bbox=-10,-10,10,10&limit=1")
+        .dispatch();
+    let suggestions: Vec<json::SearchSuggestion> =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(1, suggestions.len());
+    assert_eq!("bio", suggestions[0].text);
+
+    let response = client.get("/search/suggest?q=bio").dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
+#[test]
+fn search_paginated_by_cursor() {
+    let (client, connections, mut search_engine, notify) = setup2();
+    for title in &["bbb", "Aaa", "ccc"] {
+        let e = usecases::NewPlace {
+            title: (*title).into(),
+            ..new_entry_with_category(Category::ID_NON_PROFIT, 1.0, 1.0)
+        };
+        flows::create_place(&connections, &mut search_engine, &notify, e, None, None).unwrap();
+    }
+
+    let mut titles = vec![];
+    let mut url = "/search?bbox=-10,-10,10,10&sort=title&limit=2".to_string();
+    loop {
+        let mut response = client.get(url.as_str()).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let search_response: json::SearchResponse =
+            serde_json::from_str(&response.body_string().unwrap()).unwrap();
+        assert!(search_response.visible.len() <= 2);
+        assert!(search_response.invisible.is_empty());
+        titles.extend(search_response.visible.into_iter().map(|e| e.title));
+        if let Some(next_cursor) = search_response.next_cursor {
+            url = format!(
+                "/search?bbox=-10,-10,10,10&sort=title&limit=2&cursor={}",
+                next_cursor
+            );
+        } else {
+            break;
+        }
+    }
+    assert_eq!(vec!["Aaa", "bbb", "ccc"], titles);
+
+    // Without sort order or cursor results are not paginated
+    let mut response = client.get("/search?bbox=-10,-10,10,10").dispatch();
+    let search_response: json::SearchResponse =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert!(search_response.next_cursor.is_none());
+
+    let response = client
+        .get("/search?bbox=-10,-10,10,10&sort=unknown")
+        .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+
+    // A title cursor cannot be used for sorting by rating
+    let mut response = client
+        .get("/search?bbox=-10,-10,10,10&sort=title&limit=1")
+        .dispatch();
+    let search_response: json::SearchResponse =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    let response = client
+        .get(format!(
+            "/search?bbox=-10,-10,10,10&sort=rating&cursor={}",
+            search_response.next_cursor.unwrap()
+        ))
+        .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+
+    // Pages ordered by relevance are continued after the match
+    // score and the id of the last place. Without a search text
+    // all places match equally and are ordered by their id.
+    let mut ids = vec![];
+    let mut url = "/search?bbox=-10,-10,10,10&sort=relevance&limit=2".to_string();
+    loop {
+        let mut response = client.get(url.as_str()).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let search_response: json::SearchResponse =
+            serde_json::from_str(&response.body_string().unwrap()).unwrap();
+        assert!(search_response.visible.len() <= 2);
+        ids.extend(search_response.visible.into_iter().map(|e| e.id));
+        if let Some(next_cursor) = search_response.next_cursor {
+            url = format!(
+                "/search?bbox=-10,-10,10,10&sort=relevance&limit=2&cursor={}",
+                next_cursor
+            );
+        } else {
+            break;
+        }
+    }
+    assert_eq!(3, ids.len());
+    let mut sorted_ids = ids.clone();
+    sorted_ids.sort_unstable();
+    sorted_ids.dedup();
+    assert_eq!(sorted_ids, ids);
+
+    // A rating cursor cannot be used for sorting by relevance
+    let mut response = client
+        .get("/search?bbox=-10,-10,10,10&sort=rating&limit=1")
+        .dispatch();
+    let search_response: json::SearchResponse =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    let response = client
+        .get(format!(
+            "/search?bbox=-10,-10,10,10&sort=relevance&cursor={}",
+            search_response.next_cursor.unwrap()
+        ))
+        .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
 fn new_entry_with_text(title: &str, description: &str, lat: f64, lng: f64) -> usecases::NewPlace {
     usecases::NewPlace {
         title: title.into(),
@@ -1446,42 +1921,274 @@ fn subscribe_to_bbox() {
 }
 
 #[test]
-fn recently_changed_entries() {
-    // Check that the requests succeeds on an empty database just
-    // to verify that the literal SQL query that is not verified
-    // at compile-time still matches the current database schema!
+fn subscribe_to_bbox_with_filters_and_digest() {
     let (client, db) = setup();
+    db.exclusive()
+        .unwrap()
+        .create_user(&User {
+            email: "foo@bar".into(),
+            email_confirmed: true,
+            password: "secret".parse::<Password>().unwrap(),
+            role: Role::Guest,
+        })
+        .unwrap();
+    let response = client
+        .post("/login")
+        .header(ContentType::JSON)
+        .body(r#"{"email": "foo@bar", "password": "secret"}"#)
+        .dispatch();
+    let cookie = user_id_cookie(&response).unwrap();
+    let body = r#"[{"lat":-10.0,"lng":-10.0},{"lat":10.0,"lng":10.0}]"#;
 
-    let old_entries = vec![Place::build().id("old").finish()];
-    for e in old_entries {
-        db.exclusive().unwrap().create_or_update_place(e).unwrap();
-    }
+    let response = client
+        .post("/subscribe-to-bbox?delivery=monthly")
+        .header(ContentType::JSON)
+        .cookie(cookie.clone())
+        .body(body)
+        .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
 
-    // Resolution of time stamps in the query is 1 sec
-    // TODO: Don't waste time by sleeping
-    std::thread::sleep(std::time::Duration::from_millis(1001));
-    let since_inclusive = Timestamp::now();
-    let recent_entries = vec![Place::build().id("recent").finish()];
-    for e in recent_entries {
-        db.exclusive().unwrap().create_or_update_place(e).unwrap();
-    }
+    let response = client
+        .post(format!(
+            "/subscribe-to-bbox?tags=organic,fair&categories={}&delivery=weekly",
+            Category::ID_NON_PROFIT
+        ))
+        .header(ContentType::JSON)
+        .cookie(cookie.clone())
+        .body(body)
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
 
-    // Resolution of time stamps in the query is 1 sec
-    // TODO: Don't waste time by sleeping
-    std::thread::sleep(std::time::Duration::from_millis(1001));
+    let mut response = client.get("/bbox-subscriptions").cookie(cookie).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let subscriptions: Vec<json::BboxSubscription> =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(1, subscriptions.len());
+    assert_eq!(vec!["fair", "organic"], subscriptions[0].tags);
+    assert_eq!(vec![Category::ID_NON_PROFIT], subscriptions[0].categories);
+    assert_eq!("weekly", subscriptions[0].delivery);
+}
 
-    let until_exclusive = Timestamp::now();
-    assert!(since_inclusive < until_exclusive);
-    let new_entries = vec![Place::build().id("new").finish()];
-    for e in new_entries {
-        db.exclusive().unwrap().create_or_update_place(e).unwrap();
+#[test]
+fn manage_multiple_bbox_subscriptions() {
+    let (client, db) = setup();
+    for email in &["foo@bar", "baz@bar"] {
+        db.exclusive()
+            .unwrap()
+            .create_user(&User {
+                email: (*email).into(),
+                email_confirmed: true,
+                password: "secret".parse::<Password>().unwrap(),
+                role: Role::Guest,
+            })
+            .unwrap();
     }
+    let login = |email: &str| {
+        let response = client
+            .post("/login")
+            .header(ContentType::JSON)
+            .body(format!(r#"{{"email": "{}", "password": "secret"}}"#, email))
+            .dispatch();
+        user_id_cookie(&response).unwrap()
+    };
+    let cookie = login("foo@bar");
 
-    let mut response_since = client
-        .get(format!(
-            "/entries/recently-changed?since={}",
-            since_inclusive.into_inner(),
-        ))
+    let mut ids = vec![];
+    for title in &["Home", "Work"] {
+        let mut response = client
+            .post("/bbox-subscriptions")
+            .header(ContentType::JSON)
+            .cookie(cookie.clone())
+            .body(format!(
+                r#"{{"title":"{}","bbox":{{"sw":{{"lat":-10.0,"lng":-10.0}},"ne":{{"lat":10.0,"lng":10.0}}}}}}"#,
+                title
+            ))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let subscription: json::BboxSubscription =
+            serde_json::from_str(&response.body_string().unwrap()).unwrap();
+        assert_eq!(Some(*title), subscription.title.as_deref());
+        ids.push(subscription.id);
+    }
+
+    let mut response = client
+        .get("/bbox-subscriptions")
+        .cookie(cookie.clone())
+        .dispatch();
+    let subscriptions: Vec<json::BboxSubscription> =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(2, subscriptions.len());
+
+    let body = r#"{"title":"Office","bbox":{"sw":{"lat":0.0,"lng":0.0},"ne":{"lat":5.0,"lng":5.0}},"delivery":"daily"}"#;
+    let response = client
+        .put(format!("/bbox-subscriptions/{}", ids[1]))
+        .header(ContentType::JSON)
+        .cookie(login("baz@bar"))
+        .body(body)
+        .dispatch();
+    assert_eq!(response.status(), Status::Forbidden);
+    let mut response = client
+        .put(format!("/bbox-subscriptions/{}", ids[1]))
+        .header(ContentType::JSON)
+        .cookie(cookie.clone())
+        .body(body)
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let subscription: json::BboxSubscription =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(Some("Office"), subscription.title.as_deref());
+    assert_eq!("daily", subscription.delivery);
+    assert_eq!(5.0, subscription.north_east_lat);
+
+    let response = client
+        .delete(format!("/bbox-subscriptions/{}", ids[0]))
+        .cookie(cookie.clone())
+        .dispatch();
+    assert_eq!(response.status(), Status::NoContent);
+    let mut response = client.get("/bbox-subscriptions").cookie(cookie).dispatch();
+    let subscriptions: Vec<json::BboxSubscription> =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(1, subscriptions.len());
+    assert_eq!(ids[1], subscriptions[0].id);
+}
+
+#[test]
+fn subscribe_to_polygon() {
+    let (client, db) = setup();
+    db.exclusive()
+        .unwrap()
+        .create_user(&User {
+            email: "foo@bar".into(),
+            email_confirmed: true,
+            password: "secret".parse::<Password>().unwrap(),
+            role: Role::Guest,
+        })
+        .unwrap();
+    let response = client
+        .post("/login")
+        .header(ContentType::JSON)
+        .body(r#"{"email": "foo@bar", "password": "secret"}"#)
+        .dispatch();
+    let cookie = user_id_cookie(&response).unwrap();
+
+    let mut response = client
+        .post("/bbox-subscriptions")
+        .header(ContentType::JSON)
+        .cookie(cookie.clone())
+        .body(r#"{"title":"Town","polygon":"POLYGON ((9 48, 10 48, 9 49, 9 48))"}"#)
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let subscription: json::BboxSubscription =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert!(subscription
+        .polygon
+        .as_deref()
+        .unwrap()
+        .starts_with("MULTIPOLYGON"));
+    assert!((subscription.south_west_lat - 48.0).abs() < 1e-6);
+    assert!((subscription.north_east_lng - 10.0).abs() < 1e-6);
+
+    let response = client
+        .post("/bbox-subscriptions")
+        .header(ContentType::JSON)
+        .cookie(cookie.clone())
+        .body(r#"{"polygon":"POLYGON ((9 48, 10 48))"}"#)
+        .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+    let response = client
+        .post("/bbox-subscriptions")
+        .header(ContentType::JSON)
+        .cookie(cookie)
+        .body(r#"{"title":"Nothing"}"#)
+        .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
+#[test]
+fn unsubscribe_bbox_with_token() {
+    let (client, db) = setup();
+    db.exclusive()
+        .unwrap()
+        .create_user(&User {
+            email: "foo@bar".into(),
+            email_confirmed: true,
+            password: "secret".parse::<Password>().unwrap(),
+            role: Role::Guest,
+        })
+        .unwrap();
+    let subscription = usecases::create_bbox_subscription(
+        &*db.exclusive().unwrap(),
+        "foo@bar".into(),
+        geo::MapBbox::new(
+            MapPoint::from_lat_lng_deg(-10.0, -10.0),
+            MapPoint::from_lat_lng_deg(10.0, 10.0),
+        ),
+        Default::default(),
+    )
+    .unwrap();
+    let body = format!(
+        r#"{{"token":"{}"}}"#,
+        subscription.unsubscribe_token().encode_to_string()
+    );
+
+    let response = client
+        .post("/unsubscribe-bbox")
+        .header(ContentType::JSON)
+        .body(&body)
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert!(db
+        .shared()
+        .unwrap()
+        .all_bbox_subscriptions()
+        .unwrap()
+        .is_empty());
+
+    let response = client
+        .post("/unsubscribe-bbox")
+        .header(ContentType::JSON)
+        .body(&body)
+        .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
+#[test]
+fn recently_changed_entries() {
+    // Check that the requests succeeds on an empty database just
+    // to verify that the literal SQL query that is not verified
+    // at compile-time still matches the current database schema!
+    let (client, db) = setup();
+
+    let old_entries = vec![Place::build().id("old").finish()];
+    for e in old_entries {
+        db.exclusive().unwrap().create_or_update_place(e).unwrap();
+    }
+
+    // Resolution of time stamps in the query is 1 sec
+    // TODO: Don't waste time by sleeping
+    std::thread::sleep(std::time::Duration::from_millis(1001));
+    let since_inclusive = Timestamp::now();
+    let recent_entries = vec![Place::build().id("recent").finish()];
+    for e in recent_entries {
+        db.exclusive().unwrap().create_or_update_place(e).unwrap();
+    }
+
+    // Resolution of time stamps in the query is 1 sec
+    // TODO: Don't waste time by sleeping
+    std::thread::sleep(std::time::Duration::from_millis(1001));
+
+    let until_exclusive = Timestamp::now();
+    assert!(since_inclusive < until_exclusive);
+    let new_entries = vec![Place::build().id("new").finish()];
+    for e in new_entries {
+        db.exclusive().unwrap().create_or_update_place(e).unwrap();
+    }
+
+    let mut response_since = client
+        .get(format!(
+            "/entries/recently-changed?since={}",
+            since_inclusive.into_inner(),
+        ))
         .dispatch();
     assert_eq!(response_since.status(), Status::Ok);
     let body_since_str = response_since.body().and_then(|b| b.into_string()).unwrap();
@@ -1644,7 +2351,7 @@ fn entries_export_csv() {
             "http://custom-link.org".parse().unwrap(),
         )],
     });
-    entries[0].opening_hours = Some("24/7".parse().unwrap());
+    entries[0].opening_hours = Some("Mo-Fr 9:00-17:00".parse().unwrap());
     entries[0].founded_on = Some("1945-10-24".parse().unwrap());
     entries[1].created.at = TimestampMs::from_seconds(2222);
 
@@ -1731,10 +2438,10 @@ fn entries_export_csv() {
     }
     let body_str = response.body().and_then(|b| b.into_string()).unwrap();
     //eprintln!("{}", body_str);
-    assert!(body_str.starts_with("id,created_at,created_by,version,title,description,lat,lng,street,zip,city,country,state,homepage,contact_name,contact_email,contact_phone,opening_hours,founded_on,categories,tags,license,image_url,image_link_url,avg_rating\n"));
-    assert!(body_str.contains(&format!("entry1,1111,user@example.com,0,title1,desc1,{lat},{lng},street1,zip1,city1,country1,state1,http://homepage1/,John Smith,john.smith@example.com,0123456789,24/7,1945-10-24,\"{cat1},{cat2}\",\"bla,bli\",license1,https://img/,\"https://img,link/\",0.25\n", lat = LatCoord::from_deg(0.1).to_deg(), lng = LngCoord::from_deg(0.2).to_deg(), cat1 = Category::ID_NON_PROFIT, cat2 = Category::ID_COMMERCIAL)));
+    assert!(body_str.starts_with("id,created_at,created_by,version,title,description,lat,lng,street,zip,city,country,state,homepage,contact_name,contact_email,contact_phone,opening_hours,opening_hours_normalized,founded_on,categories,tags,license,image_url,image_link_url,avg_rating\n"));
+    assert!(body_str.contains(&format!("entry1,1111,user@example.com,0,title1,desc1,{lat},{lng},street1,zip1,city1,country1,state1,http://homepage1/,John Smith,john.smith@example.com,0123456789,Mo-Fr 9:00-17:00,Mo-Fr 09:00-17:00,1945-10-24,\"{cat1},{cat2}\",\"bla,bli\",license1,https://img/,\"https://img,link/\",0.25\n", lat = LatCoord::from_deg(0.1).to_deg(), lng = LngCoord::from_deg(0.2).to_deg(), cat1 = Category::ID_NON_PROFIT, cat2 = Category::ID_COMMERCIAL)));
     assert!(body_str.contains(&format!(
-        "entry2,2222,,0,,,0.0,0.0,,,,,,,,,,,,{cat},,,,,0.0\n",
+        "entry2,2222,,0,,,0.0,0.0,,,,,,,,,,,,,{cat},,,,,0.0\n",
         cat = Category::ID_NON_PROFIT
     )));
     assert!(!body_str.contains("entry3"));
@@ -1757,10 +2464,10 @@ fn entries_export_csv() {
     }
     let body_str = response.body().and_then(|b| b.into_string()).unwrap();
     //eprintln!("{}", body_str);
-    assert!(body_str.starts_with("id,created_at,created_by,version,title,description,lat,lng,street,zip,city,country,state,homepage,contact_name,contact_email,contact_phone,opening_hours,founded_on,categories,tags,license,image_url,image_link_url,avg_rating\n"));
-    assert!(body_str.contains(&format!("entry1,1111,,0,title1,desc1,{lat},{lng},street1,zip1,city1,country1,state1,http://homepage1/,John Smith,john.smith@example.com,0123456789,24/7,1945-10-24,\"{cat1},{cat2}\",\"bla,bli\",license1,https://img/,\"https://img,link/\",0.25\n", lat = LatCoord::from_deg(0.1).to_deg(), lng = LngCoord::from_deg(0.2).to_deg(), cat1 = Category::ID_NON_PROFIT, cat2 = Category::ID_COMMERCIAL)));
+    assert!(body_str.starts_with("id,created_at,created_by,version,title,description,lat,lng,street,zip,city,country,state,homepage,contact_name,contact_email,contact_phone,opening_hours,opening_hours_normalized,founded_on,categories,tags,license,image_url,image_link_url,avg_rating\n"));
+    assert!(body_str.contains(&format!("entry1,1111,,0,title1,desc1,{lat},{lng},street1,zip1,city1,country1,state1,http://homepage1/,John Smith,john.smith@example.com,0123456789,Mo-Fr 9:00-17:00,Mo-Fr 09:00-17:00,1945-10-24,\"{cat1},{cat2}\",\"bla,bli\",license1,https://img/,\"https://img,link/\",0.25\n", lat = LatCoord::from_deg(0.1).to_deg(), lng = LngCoord::from_deg(0.2).to_deg(), cat1 = Category::ID_NON_PROFIT, cat2 = Category::ID_COMMERCIAL)));
     assert!(body_str.contains(&format!(
-        "entry2,2222,,0,,,0.0,0.0,,,,,,,,,,,,{cat},,,,,0.0\n",
+        "entry2,2222,,0,,,0.0,0.0,,,,,,,,,,,,,{cat},,,,,0.0\n",
         cat = Category::ID_NON_PROFIT
     )));
     assert!(!body_str.contains("entry3"));
@@ -1806,3 +2513,615 @@ fn search_duplicates() {
     assert_eq!(1, duplicate_places.len());
     assert_eq!(place.id.to_string(), duplicate_places.first().unwrap().id);
 }
+
+#[test]
+fn places_export_geojson() {
+    let (client, db) = setup();
+    db.exclusive()
+        .unwrap()
+        .create_user(&User {
+            email: "scout@example.com".into(),
+            email_confirmed: true,
+            password: "secret".parse::<Password>().unwrap(),
+            role: Role::Scout,
+        })
+        .unwrap();
+    let res = client.post("/entries")
+                    .header(ContentType::JSON)
+                    .body(r#"{"title":"foo","description":"bla","lat":0.5,"lng":0.25,"categories":["2cd00bebec0c48ba9db761da48678134"],"license":"CC0-1.0","tags":["bar"]}"#)
+                    .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let req = client.get("/export/places.geojson?bbox=-1,-1,1,1");
+    let response = req.dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+
+    let response = client
+        .post("/login")
+        .header(ContentType::JSON)
+        .body(r#"{"email": "scout@example.com", "password": "secret"}"#)
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+
+    let mut response = client
+        .get("/export/places.geojson?bbox=-1,-1,1,1")
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.headers().get("Content-Type").collect::<Vec<_>>()[0],
+        "application/geo+json"
+    );
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let collection: serde_json::Value = serde_json::from_str(&body_str).unwrap();
+    assert_eq!(collection["type"], "FeatureCollection");
+    let features = collection["features"].as_array().unwrap();
+    assert_eq!(1, features.len());
+    let feature = &features[0];
+    assert_eq!(feature["type"], "Feature");
+    assert_eq!(feature["geometry"]["type"], "Point");
+    assert_eq!(
+        feature["geometry"]["coordinates"],
+        serde_json::json!([
+            LngCoord::from_deg(0.25).to_deg(),
+            LatCoord::from_deg(0.5).to_deg()
+        ])
+    );
+    assert_eq!(feature["properties"]["title"], "foo");
+    assert_eq!(
+        feature["properties"]["categories"],
+        serde_json::json!([Category::ID_NON_PROFIT])
+    );
+    assert_eq!(feature["properties"]["tags"], serde_json::json!(["bar"]));
+
+    let mut response = client
+        .get("/export/places.geojson?bbox=10,10,11,11")
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let collection: serde_json::Value = serde_json::from_str(&body_str).unwrap();
+    assert!(collection["features"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn places_import_csv() {
+    let (client, db) = setup();
+    db.exclusive()
+        .unwrap()
+        .create_user(&User {
+            email: "scout@example.com".into(),
+            email_confirmed: true,
+            password: "secret".parse::<Password>().unwrap(),
+            role: Role::Scout,
+        })
+        .unwrap();
+    let csv = "\
+title,description,lat,lng,street,zip,city,country,state,homepage,contact_name,contact_email,contact_phone,opening_hours,founded_on,categories,tags,license,image_url,image_link_url
+foo,bar,0.5,0.25,,,,,,,,,,,,2cd00bebec0c48ba9db761da48678134,\"bio,fair\",CC0-1.0,,
+invalid,bar,100,0.25,,,,,,,,,,,,,,CC0-1.0,,
+";
+
+    let response = client
+        .post("/import/places.csv")
+        .header(ContentType::CSV)
+        .body(csv)
+        .dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+
+    let response = client
+        .post("/login")
+        .header(ContentType::JSON)
+        .body(r#"{"email": "scout@example.com", "password": "secret"}"#)
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+
+    let mut response = client
+        .post("/import/places.csv?dry_run=true")
+        .header(ContentType::CSV)
+        .body(csv)
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    test_json(&response);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let results: Vec<json::PlaceImportResult> = serde_json::from_str(&body_str).unwrap();
+    assert_eq!(2, results.len());
+    assert_eq!(2, results[0].line);
+    assert!(results[0].id.is_none());
+    assert!(results[0].error.is_none());
+    assert_eq!(3, results[1].line);
+    assert!(results[1].error.is_some());
+    assert_eq!(0, db.shared().unwrap().count_places().unwrap());
+
+    let mut response = client
+        .post("/import/places.csv")
+        .header(ContentType::CSV)
+        .body(csv)
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let results: Vec<json::PlaceImportResult> = serde_json::from_str(&body_str).unwrap();
+    let id = results[0].id.as_ref().unwrap();
+    assert!(results[1].id.is_none());
+    let (place, _) = db.shared().unwrap().get_place(id).unwrap();
+    assert_eq!("foo", place.title);
+    assert_eq!(Some("scout@example.com".into()), place.created.by);
+
+    // Importing the same place again reports it as a likely duplicate
+    let mut response = client
+        .post("/import/places.csv")
+        .header(ContentType::CSV)
+        .body(csv)
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let results: Vec<json::PlaceImportResult> = serde_json::from_str(&body_str).unwrap();
+    assert!(results[0].id.is_none());
+    assert_eq!(1, results[0].duplicates.len());
+    assert_eq!(id, &results[0].duplicates[0].id);
+    assert_eq!(1, db.shared().unwrap().count_places().unwrap());
+
+    // Files that exceed the size limit are rejected
+    let mut large_csv = csv.to_string();
+    while large_csv.len() as u64 <= MAX_IMPORT_SIZE {
+        large_csv.push_str(&csv.lines().nth(1).unwrap().replace("foo", "baz"));
+        large_csv.push('\n');
+    }
+    let response = client
+        .post("/import/places.csv")
+        .header(ContentType::CSV)
+        .body(large_csv)
+        .dispatch();
+    assert_eq!(response.status(), Status::PayloadTooLarge);
+    assert_eq!(1, db.shared().unwrap().count_places().unwrap());
+}
+
+#[test]
+fn register_list_and_delete_webhooks() {
+    use rocket::http::Header;
+    let (client, db) = setup();
+    db.exclusive()
+        .unwrap()
+        .create_org(Organization {
+            id: "a".into(),
+            name: "a".into(),
+            moderated_tags: vec![],
+            api_token: "a".into(),
+        })
+        .unwrap();
+
+    let res = client
+        .post("/webhooks")
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", "Bearer b"))
+        .body(r#"{"url":"https://example.com/hook"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Unauthorized);
+
+    let res = client
+        .post("/webhooks")
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", "Bearer a"))
+        .body(r#"{"url":"http://example.com/hook"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+
+    let mut res = client
+        .post("/webhooks")
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", "Bearer a"))
+        .body(r#"{"url":"https://example.com/hook","bbox":{"sw":{"lat":0.0,"lng":0.0},"ne":{"lat":10.0,"lng":10.0}},"tags":["Foo"]}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    test_json(&res);
+    let body_str = res.body().and_then(|b| b.into_string()).unwrap();
+    let created: json::WebhookSubscription = serde_json::from_str(&body_str).unwrap();
+    assert_eq!("https://example.com/hook", created.url);
+    assert_eq!(vec!["foo"], created.tags);
+    assert!(!created.secret.is_empty());
+
+    let mut res = client
+        .get("/webhooks")
+        .header(Header::new("Authorization", "Bearer a"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body_str = res.body().and_then(|b| b.into_string()).unwrap();
+    let listed: Vec<json::WebhookSubscription> = serde_json::from_str(&body_str).unwrap();
+    assert_eq!(1, listed.len());
+    assert_eq!(created.id, listed[0].id);
+
+    let mut res = client
+        .get(format!("/webhooks/{}/deliveries", created.id))
+        .header(Header::new("Authorization", "Bearer a"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert_eq!("[]", res.body().and_then(|b| b.into_string()).unwrap());
+
+    let res = client
+        .delete(format!("/webhooks/{}", created.id))
+        .header(Header::new("Authorization", "Bearer a"))
+        .dispatch();
+    assert_eq!(res.status(), Status::NoContent);
+    assert!(db
+        .shared()
+        .unwrap()
+        .all_webhook_subscriptions()
+        .unwrap()
+        .is_empty());
+
+    let res = client
+        .delete(format!("/webhooks/{}", created.id))
+        .header(Header::new("Authorization", "Bearer a"))
+        .dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
+
+#[test]
+fn only_admins_can_inspect_and_retry_failed_jobs() {
+    let (client, db) = setup();
+    for (email, role) in &[
+        ("admin@example.com", Role::Admin),
+        ("scout@example.com", Role::Scout),
+    ] {
+        db.exclusive()
+            .unwrap()
+            .create_user(&User {
+                email: (*email).into(),
+                email_confirmed: true,
+                password: "secret".parse::<Password>().unwrap(),
+                role: *role,
+            })
+            .unwrap();
+    }
+    let failed_job = Job {
+        id: "job".into(),
+        kind: "reindex_place".into(),
+        payload: r#"{"kind":"reindex_place","place_id":"foo"}"#.into(),
+        status: JobStatus::Failed,
+        attempts: 5,
+        created_at: TimestampMs::now(),
+        run_at: TimestampMs::now(),
+        last_error: Some("failed".into()),
+    };
+    db.exclusive().unwrap().enqueue_job(&failed_job).unwrap();
+
+    let response = client.get("/jobs/failed").dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+
+    let response = client
+        .post("/login")
+        .header(ContentType::JSON)
+        .body(r#"{"email": "scout@example.com", "password": "secret"}"#)
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let response = client.get("/jobs/failed").dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+    let response = client.post("/jobs/failed/retry").dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+
+    let response = client
+        .post("/login")
+        .header(ContentType::JSON)
+        .body(r#"{"email": "admin@example.com", "password": "secret"}"#)
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let mut response = client.get("/jobs/failed").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let jobs: Vec<json::Job> = serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(1, jobs.len());
+    assert_eq!("job", jobs[0].id);
+    assert_eq!("failed", jobs[0].status);
+    assert_eq!(Some("failed"), jobs[0].last_error.as_deref());
+
+    let mut response = client.post("/jobs/failed/retry").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!("1", response.body_string().unwrap());
+    let mut response = client.get("/jobs/failed").dispatch();
+    let jobs: Vec<json::Job> = serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert!(jobs.is_empty());
+}
+
+#[test]
+fn propose_changes_of_locked_place_fields() {
+    let (client, db) = setup();
+    for &(email, role) in &[
+        ("scout@example.com", Role::Scout),
+        ("owner@example.com", Role::User),
+    ] {
+        db.exclusive()
+            .unwrap()
+            .create_user(&User {
+                email: email.into(),
+                email_confirmed: true,
+                password: "secret".parse::<Password>().unwrap(),
+                role,
+            })
+            .unwrap();
+    }
+    let place = Place::build().id("locked").title("foo").finish();
+    db.exclusive()
+        .unwrap()
+        .create_or_update_place(place)
+        .unwrap();
+    let update = |version: u64, email: &str| {
+        format!(
+            r#"{{"version":{},"title":"foo","description":"bar","lat":0.0,"lng":0.0,"categories":["x"],"tags":[],"email":"{}"}}"#,
+            version, email
+        )
+    };
+
+    // Only scouts are allowed to assign owners
+    let ownership = r#"{"owners":[{"user":"owner@example.com"}],"locked_fields":["contact"]}"#;
+    let response = client
+        .put("/places/locked/owners")
+        .header(ContentType::JSON)
+        .body(ownership)
+        .dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+    let response = client
+        .post("/login")
+        .header(ContentType::JSON)
+        .body(r#"{"email": "scout@example.com", "password": "secret"}"#)
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let response = client
+        .put("/places/locked/owners")
+        .header(ContentType::JSON)
+        .body(ownership)
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let mut response = client.get("/places/locked/owners").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let ownership: json::PlaceOwnership =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(
+        vec![json::PlaceOwner::User("owner@example.com".into())],
+        ownership.owners
+    );
+    let response = client.post("/logout").header(ContentType::JSON).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+
+    // Anonymous changes of locked fields are only proposed
+    let response = client
+        .put("/entries/locked")
+        .header(ContentType::JSON)
+        .body(update(1, "other@example.com"))
+        .dispatch();
+    assert_eq!(response.status(), Status::Accepted);
+    let (place, _) = db.shared().unwrap().get_place("locked").unwrap();
+    assert_eq!(Revision::initial(), place.revision);
+
+    // The owner reviews the proposals...
+    let response = client
+        .post("/login")
+        .header(ContentType::JSON)
+        .body(r#"{"email": "owner@example.com", "password": "secret"}"#)
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let mut response = client.get("/places/proposals/count").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(r#"{"count":1}"#, response.body_string().unwrap());
+    let mut response = client.get("/places/proposals").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let proposals: Vec<json::PlaceProposal> =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(1, proposals.len());
+    assert_eq!("locked", proposals[0].place.id);
+    assert_eq!(0, proposals[0].base_revision);
+    assert_eq!(
+        Some("other@example.com"),
+        proposals[0].revision.contact.email.as_deref()
+    );
+
+    // ...and is allowed to change locked fields
+    let response = client
+        .put("/entries/locked")
+        .header(ContentType::JSON)
+        .body(update(1, "owner@example.com"))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let (place, _) = db.shared().unwrap().get_place("locked").unwrap();
+    assert_eq!(Revision::from(1), place.revision);
+}
+
+#[test]
+fn review_proposed_place_changes() {
+    let (client, db) = setup();
+    db.exclusive()
+        .unwrap()
+        .create_user(&User {
+            email: "scout@example.com".into(),
+            email_confirmed: true,
+            password: "secret".parse::<Password>().unwrap(),
+            role: Role::Scout,
+        })
+        .unwrap();
+    let place = Place::build().id("locked").title("foo").finish();
+    db.exclusive()
+        .unwrap()
+        .create_or_update_place(place)
+        .unwrap();
+    db.exclusive()
+        .unwrap()
+        .replace_place_ownership(
+            &"locked".into(),
+            &PlaceOwnership {
+                owners: vec![PlaceOwner::User("scout@example.com".into())],
+                locked_fields: vec![LockableField::Contact],
+            },
+        )
+        .unwrap();
+    let propose = |email: &str| {
+        let body = format!(
+            r#"{{"version":1,"title":"foo","description":"bar","lat":0.0,"lng":0.0,"categories":["x"],"tags":[],"email":"{}"}}"#,
+            email
+        );
+        let response = client
+            .put("/entries/locked")
+            .header(ContentType::JSON)
+            .body(body)
+            .dispatch();
+        assert_eq!(response.status(), Status::Accepted);
+    };
+    propose("spam@example.com");
+    propose("other@example.com");
+
+    let response = client
+        .post("/login")
+        .header(ContentType::JSON)
+        .body(r#"{"email": "scout@example.com", "password": "secret"}"#)
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let mut response = client.get("/places/locked/proposals").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let proposals: Vec<json::PlaceProposal> =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(2, proposals.len());
+    assert!(proposals
+        .iter()
+        .all(|p| p.status == json::ProposalStatus::Pending));
+
+    // Reject the first proposal...
+    let response = client
+        .post(format!("/places/proposals/{}/review", proposals[0].id))
+        .header(ContentType::JSON)
+        .body(r#"{"decision":"reject","comment":"spam"}"#)
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let (place, _) = db.shared().unwrap().get_place("locked").unwrap();
+    assert_eq!(Revision::initial(), place.revision);
+
+    // ...and accept the second one
+    let response = client
+        .post(format!("/places/proposals/{}/review", proposals[1].id))
+        .header(ContentType::JSON)
+        .body(r#"{"decision":"accept"}"#)
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let (place, _) = db.shared().unwrap().get_place("locked").unwrap();
+    assert_eq!(Revision::from(1), place.revision);
+    assert_eq!(
+        Some("other@example.com"),
+        place
+            .contact
+            .as_ref()
+            .and_then(|c| c.email.as_ref())
+            .map(|email| email.as_str())
+    );
+
+    // Reviewed proposals are not pending anymore
+    let mut response = client.get("/places/proposals/count").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(r#"{"count":0}"#, response.body_string().unwrap());
+    let mut response = client.get("/places/locked/proposals").dispatch();
+    let proposals: Vec<json::PlaceProposal> =
+        serde_json::from_str(&response.body_string().unwrap()).unwrap();
+    assert_eq!(json::ProposalStatus::Rejected, proposals[0].status);
+    assert_eq!(
+        Some("spam"),
+        proposals[0]
+            .reviewed
+            .as_ref()
+            .and_then(|r| r.comment.as_deref())
+    );
+    assert_eq!(json::ProposalStatus::Accepted, proposals[1].status);
+    let response = client
+        .post(format!("/places/proposals/{}/review", proposals[1].id))
+        .header(ContentType::JSON)
+        .body(r#"{"decision":"reject"}"#)
+        .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
+#[test]
+fn revert_place_to_previous_revision() {
+    let (client, db) = setup();
+    db.exclusive()
+        .unwrap()
+        .create_org(Organization {
+            id: "a".into(),
+            name: "a".into(),
+            moderated_tags: vec!["a".into()],
+            api_token: "a".into(),
+        })
+        .unwrap();
+    db.exclusive()
+        .unwrap()
+        .create_user(&User {
+            email: "scout@example.com".into(),
+            email_confirmed: true,
+            password: "secret".parse::<Password>().unwrap(),
+            role: Role::Scout,
+        })
+        .unwrap();
+    let revisions = vec![
+        Place::build()
+            .id("reverted")
+            .revision(0)
+            .title("original")
+            .tags(vec!["a"])
+            .finish(),
+        Place::build()
+            .id("reverted")
+            .revision(1)
+            .title("vandalized")
+            .tags(vec!["a"])
+            .finish(),
+    ];
+    for place in revisions {
+        db.exclusive()
+            .unwrap()
+            .create_or_update_place(place)
+            .unwrap();
+    }
+    let revert = |revision: u64| {
+        client
+            .post("/places/reverted/revert")
+            .header(ContentType::JSON)
+            .body(format!(
+                r#"{{"revision":{},"comment":"vandalism"}}"#,
+                revision
+            ))
+            .dispatch()
+            .status()
+    };
+
+    // Only scouts and admins are allowed to revert places
+    assert_eq!(Status::Unauthorized, revert(0));
+    let response = client
+        .post("/login")
+        .header(ContentType::JSON)
+        .body(r#"{"email": "scout@example.com", "password": "secret"}"#)
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+
+    assert_eq!(Status::Ok, revert(0));
+    let (place, _) = db.shared().unwrap().get_place("reverted").unwrap();
+    assert_eq!(Revision::from(2), place.revision);
+    assert_eq!("original", place.title);
+    assert_eq!(
+        Some("scout@example.com"),
+        place.created.by.as_ref().map(|email| email.as_str())
+    );
+    let history = db
+        .shared()
+        .unwrap()
+        .get_place_history("reverted", Some(Revision::from(2)))
+        .unwrap();
+    let (_, logs) = &history.revisions[0];
+    assert_eq!(Some("revert"), logs[0].activity.context.as_deref());
+    assert_eq!(Some("vandalism"), logs[0].activity.comment.as_deref());
+
+    // The current revision could not be restored
+    assert_eq!(Status::BadRequest, revert(2));
+
+    // Moderated tags must not be added by reverting a place
+    let mut untagged = place;
+    untagged.revision = Revision::from(3);
+    untagged.tags = vec![];
+    db.exclusive()
+        .unwrap()
+        .create_or_update_place(untagged)
+        .unwrap();
+    assert_eq!(Status::Forbidden, revert(0));
+    let (place, _) = db.shared().unwrap().get_place("reverted").unwrap();
+    assert_eq!(Revision::from(3), place.revision);
+}
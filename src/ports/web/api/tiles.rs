@@ -0,0 +1,202 @@
+use super::*;
+
+use ofdb_core::tile::TileId;
+use rocket::State;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+};
+
+// Bounds the memory that is occupied by the cache
+const MAX_CACHED_BYTES: usize = 64 * 1024 * 1024;
+
+// Events disappear from tiles after they ended without
+// any changes of the search index
+const EVENT_TILE_TTL_SECONDS: i64 = 5 * 60;
+
+struct CachedTile {
+    generation: u64,
+    period: i64,
+    data: Vec<u8>,
+    last_access: u64,
+}
+
+struct CachedTiles {
+    tiles: HashMap<TileId, CachedTile>,
+    // Least recently used tiles first
+    access_order: BTreeMap<u64, TileId>,
+    access_count: u64,
+    size: usize,
+    max_size: usize,
+}
+
+impl CachedTiles {
+    fn new(max_size: usize) -> Self {
+        Self {
+            tiles: HashMap::new(),
+            access_order: BTreeMap::new(),
+            access_count: 0,
+            size: 0,
+            max_size,
+        }
+    }
+
+    fn next_access(&mut self) -> u64 {
+        self.access_count += 1;
+        self.access_count
+    }
+
+    fn remove(&mut self, tile: TileId) {
+        if let Some(cached) = self.tiles.remove(&tile) {
+            self.access_order.remove(&cached.last_access);
+            self.size -= cached.data.len();
+        }
+    }
+
+    fn remove_least_recently_used(&mut self) {
+        if let Some(tile) = self.access_order.values().next().copied() {
+            self.remove(tile);
+        }
+    }
+}
+
+/// Encoded tiles together with the generation of the
+/// search index and the period of time that they have
+/// been created from.
+///
+/// The least recently used tiles are evicted if the total
+/// size of all cached tiles exceeds the limit.
+pub struct TileCache(Mutex<CachedTiles>);
+
+impl Default for TileCache {
+    fn default() -> Self {
+        Self(Mutex::new(CachedTiles::new(MAX_CACHED_BYTES)))
+    }
+}
+
+impl TileCache {
+    fn get(&self, tile: TileId, generation: u64, period: i64) -> Option<Vec<u8>> {
+        let mut cache = match self.0.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let is_current = cache
+            .tiles
+            .get(&tile)
+            .map(|cached| cached.generation == generation && cached.period == period)?;
+        if !is_current {
+            cache.remove(tile);
+            return None;
+        }
+        let last_access = cache.next_access();
+        let cached = cache.tiles.get_mut(&tile)?;
+        let prev_access = std::mem::replace(&mut cached.last_access, last_access);
+        let data = cached.data.clone();
+        cache.access_order.remove(&prev_access);
+        cache.access_order.insert(last_access, tile);
+        Some(data)
+    }
+
+    fn insert(&self, tile: TileId, generation: u64, period: i64, data: Vec<u8>) {
+        let mut cache = match self.0.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        cache.remove(tile);
+        if data.len() > cache.max_size {
+            return;
+        }
+        while cache.size + data.len() > cache.max_size {
+            cache.remove_least_recently_used();
+        }
+        let last_access = cache.next_access();
+        cache.size += data.len();
+        cache.access_order.insert(last_access, tile);
+        cache.tiles.insert(
+            tile,
+            CachedTile {
+                generation,
+                period,
+                data,
+                last_access,
+            },
+        );
+    }
+}
+
+fn parse_tile_id(z: u8, x: u32, y: &str) -> result::Result<TileId, Error> {
+    y.strip_suffix(".mvt")
+        .and_then(|y| y.parse().ok())
+        .and_then(|y| TileId::new(z, x, y))
+        .ok_or(Error::Parameter(ParameterError::InvalidTile))
+}
+
+#[get("/tiles/<z>/<x>/<y>")]
+pub fn get_tile(
+    connections: db::Connections,
+    search_engine: tantivy::SearchEngine,
+    cache: State<TileCache>,
+    z: u8,
+    x: u32,
+    y: String,
+) -> result::Result<Content<Vec<u8>>, AppError> {
+    let tile = parse_tile_id(z, x, &y)?;
+    let (media_type, media_subtype) = adapters::mvt::MEDIA_TYPE;
+    let content_type = ContentType::new(media_type, media_subtype);
+    // The generation must be obtained before querying the index
+    // to prevent caching outdated results as more recent ones
+    let generation = search_engine.generation();
+    // All tiles of the same period contain the same events
+    let period = Timestamp::now().into_seconds() / EVENT_TILE_TTL_SECONDS;
+    if let Some(data) = cache.get(tile, generation, period) {
+        return Ok(Content(content_type, data));
+    }
+    let places = usecases::load_tile_places(&search_engine, tile)?;
+    let events = usecases::load_tile_events(
+        &*connections.shared()?,
+        &search_engine,
+        tile,
+        Timestamp::from_seconds(period * EVENT_TILE_TTL_SECONDS),
+    )?;
+    let data = adapters::mvt::encode_tile(tile, places, events);
+    cache.insert(tile, generation, period, data.clone());
+    Ok(Content(content_type, data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tile(x: u32) -> TileId {
+        TileId::new(8, x, 0).unwrap()
+    }
+
+    #[test]
+    fn evict_least_recently_used_tiles() {
+        let cache = TileCache(Mutex::new(CachedTiles::new(3)));
+        cache.insert(tile(0), 1, 1, vec![0]);
+        cache.insert(tile(1), 1, 1, vec![1]);
+        cache.insert(tile(2), 1, 1, vec![2]);
+        assert_eq!(Some(vec![0]), cache.get(tile(0), 1, 1));
+        cache.insert(tile(3), 1, 1, vec![3, 3]);
+        assert_eq!(Some(vec![0]), cache.get(tile(0), 1, 1));
+        assert!(cache.get(tile(1), 1, 1).is_none());
+        assert!(cache.get(tile(2), 1, 1).is_none());
+        assert_eq!(Some(vec![3, 3]), cache.get(tile(3), 1, 1));
+        // Too large to be cached at all
+        cache.insert(tile(4), 1, 1, vec![4; 4]);
+        assert!(cache.get(tile(4), 1, 1).is_none());
+        assert_eq!(Some(vec![0]), cache.get(tile(0), 1, 1));
+    }
+
+    #[test]
+    fn ignore_tiles_of_other_generations_or_periods() {
+        let cache = TileCache::default();
+        cache.insert(tile(0), 1, 1, vec![0]);
+        assert!(cache.get(tile(0), 2, 1).is_none());
+        cache.insert(tile(0), 1, 1, vec![0]);
+        assert!(cache.get(tile(0), 1, 2).is_none());
+        // Outdated tiles are removed
+        assert!(cache.get(tile(0), 1, 1).is_none());
+    }
+}
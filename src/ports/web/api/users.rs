@@ -1,7 +1,7 @@
 use super::*;
 
 #[post("/users", format = "application/json", data = "<u>")]
-pub fn post_user(db: sqlite::Connections, n: Notify, u: Json<usecases::NewUser>) -> Result<()> {
+pub fn post_user(db: db::Connections, n: Notify, u: Json<usecases::NewUser>) -> Result<()> {
     let new_user = u.into_inner();
     let user = {
         let db = db.exclusive()?;
@@ -18,7 +18,7 @@ pub fn post_user(db: sqlite::Connections, n: Notify, u: Json<usecases::NewUser>)
     data = "<data>"
 )]
 pub fn post_request_password_reset(
-    connections: sqlite::Connections,
+    connections: db::Connections,
     notify: Notify,
     data: Json<json::RequestPasswordReset>,
 ) -> Result<()> {
@@ -30,7 +30,7 @@ pub fn post_request_password_reset(
 
 #[post("/users/reset-password", format = "application/json", data = "<data>")]
 pub fn post_reset_password(
-    connections: sqlite::Connections,
+    connections: db::Connections,
     data: Json<json::ResetPassword>,
 ) -> Result<()> {
     let req = data.into_inner();
@@ -43,19 +43,19 @@ pub fn post_reset_password(
 }
 
 #[delete("/users/<email>")]
-pub fn delete_user(db: sqlite::Connections, user: Login, email: String) -> Result<()> {
+pub fn delete_user(db: db::Connections, user: Login, email: String) -> Result<()> {
     usecases::delete_user(&*db.exclusive()?, &user.0, &email)?;
     Ok(Json(()))
 }
 
 #[get("/users/current", format = "application/json")]
-pub fn get_current_user(db: sqlite::Connections, user: Login) -> Result<json::User> {
+pub fn get_current_user(db: db::Connections, user: Login) -> Result<json::User> {
     let user = usecases::get_user(&*db.shared()?, &user.0, &user.0)?;
     Ok(Json(user.into()))
 }
 
 #[get("/users/<email>", format = "application/json", rank = 2)]
-pub fn get_user(db: sqlite::Connections, user: Login, email: String) -> Result<json::User> {
+pub fn get_user(db: db::Connections, user: Login, email: String) -> Result<json::User> {
     let user = usecases::get_user(&*db.shared()?, &user.0, &email)?;
     Ok(Json(user.into()))
 }
@@ -0,0 +1,56 @@
+use super::*;
+
+#[post("/webhooks", format = "application/json", data = "<new_subscription>")]
+pub fn post_webhook(
+    db: db::Connections,
+    org_token: Bearer,
+    new_subscription: Json<json::NewWebhookSubscription>,
+) -> Result<json::WebhookSubscription> {
+    let json::NewWebhookSubscription { url, bbox, tags } = new_subscription.into_inner();
+    let bbox = bbox
+        .map(geo::MapBbox::try_from)
+        .transpose()
+        .map_err(|_| Error::Parameter(ParameterError::Bbox))?;
+    let db = db.exclusive()?;
+    let org = usecases::authorize_organization_by_api_token(&*db, &org_token.0)?;
+    let subscription = usecases::create_webhook_subscription(
+        &*db,
+        &org,
+        usecases::NewWebhookSubscription { url, bbox, tags },
+    )?;
+    Ok(Json(subscription.into()))
+}
+
+#[get("/webhooks")]
+pub fn get_webhooks(
+    db: db::Connections,
+    org_token: Bearer,
+) -> Result<Vec<json::WebhookSubscription>> {
+    let db = db.shared()?;
+    let org = usecases::authorize_organization_by_api_token(&*db, &org_token.0)?;
+    let subscriptions = db.all_webhook_subscriptions_by_org(&org.id)?;
+    Ok(Json(subscriptions.into_iter().map(Into::into).collect()))
+}
+
+#[delete("/webhooks/<id>")]
+pub fn delete_webhook(db: db::Connections, org_token: Bearer, id: String) -> StatusResult {
+    let db = db.exclusive()?;
+    let org = usecases::authorize_organization_by_api_token(&*db, &org_token.0)?;
+    usecases::delete_webhook_subscription(&*db, &org, &id.into())?;
+    Ok(Status::NoContent)
+}
+
+#[get("/webhooks/<id>/deliveries?<offset>&<limit>")]
+pub fn get_webhook_deliveries(
+    db: db::Connections,
+    org_token: Bearer,
+    id: String,
+    offset: Option<u64>,
+    limit: Option<u64>,
+) -> Result<Vec<json::WebhookDelivery>> {
+    let pagination = Pagination { offset, limit };
+    let db = db.shared()?;
+    let org = usecases::authorize_organization_by_api_token(&*db, &org_token.0)?;
+    let deliveries = usecases::load_webhook_deliveries(&*db, &org, &id.into(), &pagination)?;
+    Ok(Json(deliveries.into_iter().map(Into::into).collect()))
+}
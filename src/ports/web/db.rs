@@ -1,4 +1,4 @@
-pub use crate::infrastructure::db::sqlite::*;
+pub use crate::infrastructure::db::*;
 
 use rocket::{
     request::{self, FromRequest},
@@ -2,7 +2,7 @@ use super::super::guards::*;
 use super::view;
 use crate::{
     core::{prelude::*, usecases},
-    ports::web::sqlite::Connections,
+    ports::web::db::Connections,
 };
 use maud::Markup;
 use rocket::{
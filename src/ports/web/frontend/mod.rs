@@ -4,8 +4,8 @@ use crate::{
         prelude::*,
         usecases,
     },
-    infrastructure::{db::sqlite, error::*, flows::prelude::*},
-    ports::web::{guards::*, tantivy::SearchEngine},
+    infrastructure::{db, error::*, flows::prelude::*},
+    ports::web::{guards::*, notify::Notify, tantivy::SearchEngine},
 };
 use maud::Markup;
 use num_traits::FromPrimitive;
@@ -23,6 +23,7 @@ use rocket::{
 mod login;
 mod password;
 mod register;
+mod subscriptions;
 #[cfg(test)]
 mod tests;
 mod view;
@@ -74,11 +75,7 @@ pub fn get_search(search_engine: SearchEngine, q: &RawStr, limit: Option<usize>)
 }
 
 #[get("/search-users?<email>")]
-pub fn get_search_users(
-    pool: sqlite::Connections,
-    email: &RawStr,
-    account: Account,
-) -> Result<Markup> {
+pub fn get_search_users(pool: db::Connections, email: &RawStr, account: Account) -> Result<Markup> {
     let email = email.url_decode()?;
     {
         let db = pool.shared()?;
@@ -96,7 +93,7 @@ pub struct ChangeUserRoleAction {
 
 #[post("/change-user-role", data = "<data>")]
 pub fn post_change_user_role(
-    db: sqlite::Connections,
+    db: db::Connections,
     account: Account,
     data: Form<ChangeUserRoleAction>,
 ) -> std::result::Result<Redirect, Flash<Redirect>> {
@@ -127,7 +124,7 @@ pub fn get_main_css() -> Css<&'static str> {
 }
 
 #[get("/places/<id>/history")]
-pub fn get_place_history(db: sqlite::Connections, id: &RawStr, account: Account) -> Result<Markup> {
+pub fn get_place_history(db: db::Connections, id: &RawStr, account: Account) -> Result<Markup> {
     let db = db.shared()?;
     let user = db
         .try_get_user_by_email(account.email())?
@@ -139,17 +136,24 @@ pub fn get_place_history(db: sqlite::Connections, id: &RawStr, account: Account)
 
         db.get_place_history(&id, None)?
     };
-    Ok(view::place_history(&user, &place_history))
+    let proposals = usecases::load_place_proposals(&*db, account.email(), &id.as_str().into())?;
+    Ok(view::place_history(&user, &place_history, &proposals))
 }
 
 #[get("/places/<id>/review")]
-pub fn get_place_review(db: sqlite::Connections, id: &RawStr, account: Account) -> Result<Markup> {
+pub fn get_place_review(db: db::Connections, id: &RawStr, account: Account) -> Result<Markup> {
     let db = db.shared()?;
     // Only scouts and admins are entitled to review places
     let reviewer_email =
         usecases::authorize_user_by_email(&*db, &account.email(), Role::Scout)?.email;
     let (place, review_status) = db.get_place(&id)?;
-    Ok(view::place_review(&reviewer_email, &place, review_status))
+    let proposals = usecases::load_place_proposals(&*db, &reviewer_email, &place.id)?;
+    Ok(view::place_review(
+        &reviewer_email,
+        &place,
+        review_status,
+        &proposals,
+    ))
 }
 
 #[derive(FromForm)]
@@ -160,7 +164,7 @@ pub struct Review {
 
 #[post("/places/<id>/review", data = "<review>")]
 pub fn post_place_review(
-    db: sqlite::Connections,
+    db: db::Connections,
     search_engine: SearchEngine,
     id: &RawStr,
     review: Form<Review>,
@@ -179,7 +183,7 @@ pub fn post_place_review(
 }
 
 fn review_place(
-    db: &sqlite::Connections,
+    db: &db::Connections,
     email: &str,
     status: i16,
     comment: String,
@@ -207,12 +211,120 @@ fn review_place(
     Ok(())
 }
 
-#[get("/entries/<id>")]
-pub fn get_entry(
-    pool: sqlite::Connections,
+#[derive(FromForm)]
+pub struct ProposalReview {
+    pub comment: String,
+    pub decision: String,
+    pub title: bool,
+    pub description: bool,
+    pub position: bool,
+    pub address: bool,
+    pub contact: bool,
+    pub opening_hours: bool,
+    pub founded_on: bool,
+    pub links: bool,
+    pub tags: bool,
+}
+
+impl ProposalReview {
+    fn selected_fields(&self) -> Vec<PlaceField> {
+        let selected = [
+            (PlaceField::Title, self.title),
+            (PlaceField::Description, self.description),
+            (PlaceField::Position, self.position),
+            (PlaceField::Address, self.address),
+            (PlaceField::Contact, self.contact),
+            (PlaceField::OpeningHours, self.opening_hours),
+            (PlaceField::FoundedOn, self.founded_on),
+            (PlaceField::Links, self.links),
+            (PlaceField::Tags, self.tags),
+        ];
+        selected
+            .iter()
+            .filter(|(_, selected)| *selected)
+            .map(|(field, _)| *field)
+            .collect()
+    }
+}
+
+#[post("/places/<id>/proposals/<proposal_id>/review", data = "<review>")]
+pub fn post_place_proposal_review(
+    db: db::Connections,
+    mut search_engine: SearchEngine,
+    notify: Notify,
     id: &RawStr,
-    account: Option<Account>,
-) -> Result<Markup> {
+    proposal_id: &RawStr,
+    review: Form<ProposalReview>,
+    account: Account,
+) -> std::result::Result<Redirect, Flash<Redirect>> {
+    let id = id.as_str();
+    let review = review.into_inner();
+    let decision = match review.decision.as_str() {
+        "accept" => usecases::ProposalDecision::Accept,
+        "merge" => usecases::ProposalDecision::Merge(review.selected_fields()),
+        "reject" => usecases::ProposalDecision::Reject,
+        _ => {
+            return Err(Flash::error(
+                Redirect::to(uri!(get_place_review: id)),
+                "Invalid review decision.",
+            ));
+        }
+    };
+    let review = usecases::ProposalReview {
+        reviewer_email: account.email().into(),
+        decision,
+        comment: Some(review.comment).filter(|c| !c.is_empty()),
+    };
+    review_place_proposal(
+        &db,
+        &mut search_engine,
+        &*notify,
+        &proposal_id.as_str().into(),
+        review,
+    )
+    .map(|_| Redirect::to(uri!(get_entry: id)))
+    .map_err(|_| {
+        Flash::error(
+            Redirect::to(uri!(get_place_review: id)),
+            "Failed to review the proposal.",
+        )
+    })
+}
+
+#[derive(FromForm)]
+pub struct Revert {
+    pub comment: String,
+    pub revision: u64,
+}
+
+#[post("/places/<id>/revert", data = "<revert>")]
+pub fn post_place_revert(
+    db: db::Connections,
+    mut search_engine: SearchEngine,
+    notify: Notify,
+    id: &RawStr,
+    revert: Form<Revert>,
+    account: Account,
+) -> std::result::Result<Redirect, Flash<Redirect>> {
+    let Revert { comment, revision } = revert.into_inner();
+    let id = id.as_str();
+    let revert = usecases::RevertPlace {
+        reviewer_email: account.email().into(),
+        revision: revision.into(),
+        comment: Some(comment).filter(|c| !c.is_empty()),
+    };
+    revert_place(&db, &mut search_engine, &*notify, id, revert)
+        .map(|_| Redirect::to(uri!(get_entry: id)))
+        .map_err(|_| {
+            Flash::error(
+                Redirect::to(uri!(get_place_history: id)),
+                "Failed to revert the place.",
+            )
+        })
+}
+
+#[get("/entries/<id>")]
+pub fn get_entry(pool: db::Connections, id: &RawStr, account: Option<Account>) -> Result<Markup> {
     //TODO: dry out
     let (user, place, ratings): (Option<User>, _, _) = {
         let db = pool.shared()?;
@@ -233,11 +345,7 @@ pub fn get_entry(
 }
 
 #[get("/events/<id>")]
-pub fn get_event(
-    pool: sqlite::Connections,
-    id: &RawStr,
-    account: Option<Account>,
-) -> Result<Markup> {
+pub fn get_event(pool: db::Connections, id: &RawStr, account: Option<Account>) -> Result<Markup> {
     let (user, mut ev): (Option<User>, _) = {
         let db = pool.shared()?;
         let ev = usecases::get_event(&*db, &id)?;
@@ -259,7 +367,7 @@ pub fn get_event(
 #[post("/events/<id>/archive")]
 pub fn post_archive_event(
     account: Account,
-    pool: sqlite::Connections,
+    pool: db::Connections,
     mut search_engine: SearchEngine,
     id: &RawStr,
 ) -> std::result::Result<Redirect, Flash<Redirect>> {
@@ -293,7 +401,7 @@ pub fn post_archive_event(
 
 #[get("/events?<query..>")]
 pub fn get_events_chronologically(
-    db: sqlite::Connections,
+    db: db::Connections,
     search_engine: SearchEngine,
     mut query: usecases::EventQuery,
     account: Option<Account>,
@@ -316,7 +424,7 @@ pub fn get_events_chronologically(
 }
 
 #[get("/dashboard")]
-pub fn get_dashboard(db: sqlite::Connections, account: Account) -> Result<Markup> {
+pub fn get_dashboard(db: db::Connections, account: Account) -> Result<Markup> {
     let db = db.shared()?;
     let tag_count = db.count_tags()?;
     let place_count = db.count_places()?;
@@ -346,7 +454,7 @@ pub struct ArchiveAction {
 #[post("/comments/actions/archive", data = "<data>")]
 pub fn post_comments_archive(
     account: Account,
-    db: sqlite::Connections,
+    db: db::Connections,
     data: Form<ArchiveAction>,
 ) -> std::result::Result<Redirect, Flash<Redirect>> {
     //TODO: dry out
@@ -364,7 +472,7 @@ pub fn post_comments_archive(
 #[post("/ratings/actions/archive", data = "<data>")]
 pub fn post_ratings_archive(
     account: Account,
-    db: sqlite::Connections,
+    db: db::Connections,
     mut search_engine: SearchEngine,
     data: Form<ArchiveAction>,
 ) -> std::result::Result<Redirect, Flash<Redirect>> {
@@ -393,6 +501,8 @@ pub fn routes() -> Vec<Route> {
         get_place_history,
         get_place_review,
         post_place_review,
+        post_place_proposal_review,
+        post_place_revert,
         get_events_chronologically,
         get_event,
         get_main_css,
@@ -412,5 +522,7 @@ pub fn routes() -> Vec<Route> {
         password::get_reset_password,
         password::post_reset_password_request,
         password::post_reset_password,
+        subscriptions::get_unsubscribe_bbox,
+        subscriptions::post_unsubscribe_bbox,
     ]
 }
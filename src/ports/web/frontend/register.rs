@@ -2,7 +2,7 @@ use super::login::LoginCredentials;
 use super::view;
 use crate::{
     core::{prelude::*, usecases},
-    ports::web::{notify::*, sqlite::Connections},
+    ports::web::{db::Connections, notify::*},
 };
 use maud::Markup;
 use rocket::{
@@ -0,0 +1,56 @@
+use super::view;
+use crate::{core::usecases, ports::web::db::Connections};
+use maud::Markup;
+use rocket::{self, http::RawStr, request::Form};
+
+const ONE_CLICK_UNSUBSCRIBE: &str = "One-Click";
+
+/// The form data of one-click unsubscribe requests (RFC 8058).
+#[derive(FromForm)]
+pub struct Unsubscribe {
+    #[form(field = "List-Unsubscribe")]
+    list_unsubscribe: String,
+}
+
+// Links in e-mails might be opened automatically, e.g. by
+// virus scanners. The subscription is only cancelled after
+// the confirmation has been submitted.
+#[get("/subscriptions/unsubscribe?<token>")]
+pub fn get_unsubscribe_bbox(db: Connections, token: &RawStr) -> Markup {
+    let db = match db.shared() {
+        Ok(db) => db,
+        Err(err) => {
+            error!("Failed to load subscription: {}", err);
+            return view::unsubscribe_bbox_failed();
+        }
+    };
+    match usecases::get_bbox_subscription_by_unsubscribe_token(&*db, token.as_str()) {
+        Ok(subscription) => view::unsubscribe_bbox_confirm(token.as_str(), &subscription),
+        Err(err) => {
+            info!("Failed to load subscription with token: {}", err);
+            view::unsubscribe_bbox_failed()
+        }
+    }
+}
+
+#[post("/subscriptions/unsubscribe?<token>", data = "<form>")]
+pub fn post_unsubscribe_bbox(db: Connections, token: &RawStr, form: Form<Unsubscribe>) -> Markup {
+    if form.list_unsubscribe != ONE_CLICK_UNSUBSCRIBE {
+        info!("Invalid unsubscribe request: {}", form.list_unsubscribe);
+        return view::unsubscribe_bbox_failed();
+    }
+    let db = match db.exclusive() {
+        Ok(db) => db,
+        Err(err) => {
+            error!("Failed to unsubscribe: {}", err);
+            return view::unsubscribe_bbox_failed();
+        }
+    };
+    match usecases::unsubscribe_bbox_with_token(&*db, token.as_str()) {
+        Ok(subscription) => view::unsubscribe_bbox_ack(&subscription),
+        Err(err) => {
+            info!("Failed to unsubscribe with token: {}", err);
+            view::unsubscribe_bbox_failed()
+        }
+    }
+}
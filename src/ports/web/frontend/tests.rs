@@ -1,12 +1,12 @@
 use super::*;
 use crate::{
-    infrastructure::db::{sqlite::Connections, tantivy},
+    infrastructure::db::{tantivy, Connections},
     ports::web::tests::{prelude::*, register_user},
 };
 
 fn setup() -> (
     rocket::local::Client,
-    sqlite::Connections,
+    db::Connections,
     tantivy::SearchEngine,
 ) {
     crate::ports::web::tests::setup(vec![("/", super::routes())])
@@ -259,15 +259,19 @@ mod events {
             description: Some("Foo bar baz".into()),
             start: NaiveDateTime::from_timestamp(0, 0),
             end: None,
+            time_zone: Tz::UTC,
             location: None,
             contact: None,
             tags: vec!["bla".into()],
             homepage: None,
             created_by: None,
+            created_at: None,
             registration: Some(RegistrationType::Email),
             archived: None,
             image_url: None,
             image_link_url: None,
+            recurrence: None,
+            recurrence_id: None,
         }];
 
         {
@@ -310,7 +314,7 @@ mod entry {
     use crate::{core::usecases, infrastructure::flows};
 
     fn create_place_with_rating(
-        db: &sqlite::Connections,
+        db: &db::Connections,
         search: &mut tantivy::SearchEngine,
     ) -> (String, String, String) {
         let e = usecases::NewPlace {
@@ -608,3 +612,76 @@ mod pw_reset {
         assert_eq!(h.value, "/");
     }
 }
+
+mod subscriptions {
+    use super::*;
+
+    #[test]
+    fn unsubscribe_with_link_from_email() {
+        let (client, db, _) = setup();
+        register_user(&db, "user@example.com", "secret", true);
+        let bbox = crate::core::util::geo::MapBbox::new(
+            MapPoint::from_lat_lng_deg(0.0, 0.0),
+            MapPoint::from_lat_lng_deg(10.0, 10.0),
+        );
+        let subscription = usecases::create_bbox_subscription(
+            &*db.exclusive().unwrap(),
+            "user@example.com".into(),
+            bbox,
+            usecases::BboxSubscriptionOptions {
+                title: Some("Home".into()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let token = subscription.unsubscribe_token().encode_to_string();
+        let url = format!("/subscriptions/unsubscribe?token={}", token);
+
+        // Opening the link only asks for a confirmation
+        let mut res = client.get(&url).dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        let body_str = res.body().and_then(|b| b.into_string()).unwrap();
+        assert!(body_str.contains("Home"));
+        assert!(body_str.contains("List-Unsubscribe"));
+        assert_eq!(
+            1,
+            db.shared().unwrap().all_bbox_subscriptions().unwrap().len()
+        );
+
+        // Invalid one-click requests are rejected
+        let mut res = client
+            .post(&url)
+            .header(ContentType::Form)
+            .body("List-Unsubscribe=Two-Clicks")
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        let body_str = res.body().and_then(|b| b.into_string()).unwrap();
+        assert!(body_str.contains("invalid"));
+        assert_eq!(
+            1,
+            db.shared().unwrap().all_bbox_subscriptions().unwrap().len()
+        );
+
+        // One-click unsubscribe (RFC 8058)
+        let mut res = client
+            .post(&url)
+            .header(ContentType::Form)
+            .body("List-Unsubscribe=One-Click")
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        let body_str = res.body().and_then(|b| b.into_string()).unwrap();
+        assert!(body_str.contains("Home"));
+        assert!(db
+            .shared()
+            .unwrap()
+            .all_bbox_subscriptions()
+            .unwrap()
+            .is_empty());
+
+        // The link can only be used once
+        let mut res = client.get(&url).dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        let body_str = res.body().and_then(|b| b.into_string()).unwrap();
+        assert!(body_str.contains("invalid"));
+    }
+}
@@ -18,8 +18,8 @@ pub fn event(user: Option<User>, ev: Event) -> Markup {
                 div class="entity-type" { "Event"  }
                 h2{ (ev.title) }
                 p class="time" {
-                    (ev.start.format("%d.%m.%Y %H:%M"))
-                        @if let Some(end) = ev.end{
+                    (ev.local_start().format("%d.%m.%Y %H:%M"))
+                        @if let Some(end) = ev.local_end(){
                             " - "
                             (end.format("%d.%m.%Y %H:%M"))
                         }
@@ -143,7 +143,7 @@ pub fn events(email: Option<&str>, events: &[Event]) -> Markup {
                                             span class="title" { (e.title) }
                                             " "
                                             span class="date" {
-                                                (e.start.format("%d.%m.%y"))
+                                                (e.local_start().format("%d.%m.%y"))
                                             }
                                         }
                                         p {
@@ -16,6 +16,7 @@ mod page;
 mod password;
 mod place;
 mod register;
+mod subscription;
 
 pub use dashboard::*;
 pub use entry::*;
@@ -25,6 +26,7 @@ use page::*;
 pub use password::*;
 pub use place::*;
 pub use register::*;
+pub use subscription::*;
 
 pub fn index(email: Option<&str>) -> Markup {
     page(
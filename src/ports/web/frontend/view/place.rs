@@ -2,7 +2,17 @@ use super::page;
 use crate::core::prelude::*;
 use maud::{html, Markup};
 
-pub fn place_history(user: &User, h: &PlaceHistory) -> Markup {
+pub fn place_history(user: &User, h: &PlaceHistory, proposals: &[PlaceProposal]) -> Markup {
+    // Revisions are ordered from the newest to the oldest
+    let places: Vec<Place> = h
+        .revisions
+        .iter()
+        .map(|(r, _)| Place::from((h.place.clone(), r.clone())))
+        .collect();
+    let changed_fields = |i: usize| match places.get(i + 1) {
+        Some(previous) => PlaceField::changed_fields(previous, &places[i]),
+        None => vec![],
+    };
     page(
         "Place History",
         Some(&user.email),
@@ -15,6 +25,7 @@ pub fn place_history(user: &User, h: &PlaceHistory) -> Markup {
                         tr {
                             th{ "Revision" }
                             th{ "Log"  }
+                            th{ "Changes" }
 
                             th{ "Title" }
                             th{ "Description" }
@@ -34,10 +45,11 @@ pub fn place_history(user: &User, h: &PlaceHistory) -> Markup {
                             th{ "Image Link" }
 
                             th{ "Tags" }
+                            th{ "Revert" }
                         }
                     }
                     tbody {
-                        @for (r,logs) in &h.revisions {
+                        @for (i, (r,logs)) in h.revisions.iter().enumerate() {
                             tr {
                                 td{ (u64::from(r.revision)) }
                                 td{
@@ -47,6 +59,13 @@ pub fn place_history(user: &User, h: &PlaceHistory) -> Markup {
                                         }
                                     }
                                 }
+                                td{
+                                    ul class="changes" {
+                                        @for field in changed_fields(i) {
+                                            li { (place_field_label(field)) }
+                                        }
+                                    }
+                                }
 
                                 td{ (r.title) }
                                 td{ (r.description) }
@@ -91,6 +110,44 @@ pub fn place_history(user: &User, h: &PlaceHistory) -> Markup {
                                         }
                                     }
                                 }
+                                td{
+                                    // The newest revision is the current one
+                                    @if i > 0 {
+                                        (revert_form(&h.place.id, r.revision))
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                @if !proposals.is_empty() {
+                    h3 { "Proposals" }
+                    table {
+                        thead {
+                            tr {
+                                th{ "Proposed" }
+                                th{ "Base Revision" }
+                                th{ "Status" }
+                                th{ "Review" }
+                            }
+                        }
+                        tbody {
+                            @for p in proposals {
+                                tr {
+                                    td{ (activity(&p.place.created)) }
+                                    td{ (u64::from(p.base_revision)) }
+                                    td{ span class="status" { (p.status) } }
+                                    td{
+                                        @if let Some(r) = &p.reviewed {
+                                            (activity(&r.activity))
+                                            @if let Some(c) = &r.comment {
+                                                span class="comment" { " \"" (c) "\"" }
+                                            }
+                                        } @else {
+                                            a href=(format!("/places/{}/review", h.place.id)) { "review" }
+                                        }
+                                    }
+                                }
                             }
                         }
                     }
@@ -100,6 +157,18 @@ pub fn place_history(user: &User, h: &PlaceHistory) -> Markup {
     )
 }
 
+fn activity(a: &Activity) -> Markup {
+    html! {
+        (a.at)
+        " by "
+        @if let Some(email) = &a.by {
+            (email)
+        } @else {
+            "anonymous visitor"
+        }
+    }
+}
+
 fn review_status_log(place_rev: Revision, l: &ReviewStatusLog) -> Markup {
     use ReviewStatus as S;
     let status = match l.status {
@@ -117,13 +186,7 @@ fn review_status_log(place_rev: Revision, l: &ReviewStatusLog) -> Markup {
     html! {
         span class="status" { (status) }
         " at "
-        (l.activity.activity.at)
-        " by "
-        @if let Some(email) = &l.activity.activity.by {
-            (email)
-        } @else {
-            "anonymous visitor"
-        }
+        (activity(&l.activity.activity))
         @if let Some(c) = &l.activity.comment {
             span class="comment" { " \"" (c) "\"" }
         }
@@ -133,7 +196,22 @@ fn review_status_log(place_rev: Revision, l: &ReviewStatusLog) -> Markup {
     }
 }
 
-pub fn place_review(email: &str, place: &Place, status: ReviewStatus) -> Markup {
+fn revert_form(place_id: &Id, revision: Revision) -> Markup {
+    html! {
+        form class="revert" action=(format!("/places/{}/revert", place_id)) method="POST" {
+            input type="hidden" name="revision" value=(u64::from(revision));
+            input name="comment" placeholder="Comment";
+            input type="submit" value="revert";
+        }
+    }
+}
+
+pub fn place_review(
+    email: &str,
+    place: &Place,
+    status: ReviewStatus,
+    proposals: &[PlaceProposal],
+) -> Markup {
     use ReviewStatus as S;
     let options = [
         (S::Rejected, "reject"),
@@ -167,7 +245,156 @@ pub fn place_review(email: &str, place: &Place, status: ReviewStatus) -> Markup
                     }
                     input type="submit" value="change";
                 }
+                @for p in proposals.iter().filter(|p| p.status.is_pending()) {
+                    (place_proposal_review(place, p))
+                }
             }
         },
     )
 }
+
+fn place_proposal_review(place: &Place, proposal: &PlaceProposal) -> Markup {
+    let outdated = place.revision != proposal.base_revision;
+    html! {
+        div class="proposal" {
+            h3 { "Proposal from " (activity(&proposal.place.created)) }
+            @if outdated {
+                p class="warning" {
+                    (format!(
+                        "The place has been changed since revision {}. Select the fields that should be merged.",
+                        u64::from(proposal.base_revision)
+                    ))
+                }
+            }
+            form action=(format!("/places/{}/proposals/{}/review", place.id, proposal.id)) method="POST" {
+                table class="diff" {
+                    thead {
+                        tr {
+                            th{ "Merge" }
+                            th{ "Field" }
+                            th{ "Current" }
+                            th{ "Proposed" }
+                        }
+                    }
+                    tbody {
+                        @for field in PlaceField::ALL.iter().copied() {
+                            @let changed = field.is_changed(place, &proposal.place);
+                            tr.changed[changed] {
+                                td{
+                                    @if changed {
+                                        input type="checkbox" name=(field.as_str()) checked?[!outdated];
+                                    }
+                                }
+                                td{ (place_field_label(field)) }
+                                @if changed {
+                                    td{ del { (place_field_value(field, place)) } }
+                                    td{ ins { (place_field_value(field, &proposal.place)) } }
+                                } @else {
+                                    td colspan="2" { (place_field_value(field, place)) }
+                                }
+                            }
+                        }
+                    }
+                }
+                fieldset {
+                    label {
+                        "Comment:"
+                        br;
+                        input name="comment" placeholder="Comment";
+                    }
+                    br;
+                    label { "Action:"
+                        br;
+                        select name="decision" {
+                            option value="accept" disabled?[outdated] { "accept" }
+                            option value="merge" { "merge selected fields" }
+                            option value="reject" { "reject" }
+                        }
+                    }
+                }
+                input type="submit" value="review";
+            }
+        }
+    }
+}
+
+fn place_field_label(field: PlaceField) -> &'static str {
+    match field {
+        PlaceField::Title => "Title",
+        PlaceField::Description => "Description",
+        PlaceField::Position => "Position",
+        PlaceField::Address => "Address",
+        PlaceField::Contact => "Contact",
+        PlaceField::OpeningHours => "Opening Hours",
+        PlaceField::FoundedOn => "Founded",
+        PlaceField::Links => "Links",
+        PlaceField::Tags => "Tags",
+    }
+}
+
+fn place_field_value(field: PlaceField, place: &Place) -> String {
+    fn join<'a>(values: impl IntoIterator<Item = Option<&'a str>>) -> String {
+        values
+            .into_iter()
+            .flatten()
+            .filter(|v| !v.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+    match field {
+        PlaceField::Title => place.title.clone(),
+        PlaceField::Description => place.description.clone(),
+        PlaceField::Position => format!(
+            "{:.5}/{:.5}",
+            place.location.pos.lat().to_deg(),
+            place.location.pos.lng().to_deg()
+        ),
+        PlaceField::Address => place
+            .location
+            .address
+            .as_ref()
+            .map(|a| {
+                join(vec![
+                    a.street.as_deref(),
+                    a.zip.as_deref(),
+                    a.city.as_deref(),
+                    a.country.as_deref(),
+                    a.state.as_deref(),
+                ])
+            })
+            .unwrap_or_default(),
+        PlaceField::Contact => place
+            .contact
+            .as_ref()
+            .map(|c| {
+                join(vec![
+                    c.name.as_deref(),
+                    c.email.as_ref().map(|email| email.as_str()),
+                    c.phone.as_deref(),
+                ])
+            })
+            .unwrap_or_default(),
+        PlaceField::OpeningHours => place
+            .opening_hours
+            .as_ref()
+            .map(|oh| oh.as_str().to_string())
+            .unwrap_or_default(),
+        PlaceField::FoundedOn => place
+            .founded_on
+            .map(|date| date.to_string())
+            .unwrap_or_default(),
+        PlaceField::Links => place
+            .links
+            .as_ref()
+            .map(|l| {
+                join(
+                    vec![l.homepage.as_ref(), l.image.as_ref(), l.image_href.as_ref()]
+                        .into_iter()
+                        .chain(l.custom.iter().map(|c| Some(&c.url)))
+                        .map(|url| url.map(|url| url.as_str())),
+                )
+            })
+            .unwrap_or_default(),
+        PlaceField::Tags => place.tags.join(", "),
+    }
+}
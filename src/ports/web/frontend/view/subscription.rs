@@ -0,0 +1,66 @@
+use super::page::*;
+use crate::core::prelude::*;
+use maud::{html, Markup};
+
+fn subscription_area(subscription: &BboxSubscription) -> Markup {
+    html! {
+      @if let Some(ref title) = subscription.title {
+        "in \"" (title) "\""
+      } @else {
+        "in the subscribed map area"
+      }
+    }
+}
+
+pub fn unsubscribe_bbox_confirm(token: &str, subscription: &BboxSubscription) -> Markup {
+    page(
+        "Unsubscribe",
+        None,
+        None,
+        None,
+        html! {
+          h2 { "Unsubscribe" }
+          p {
+            "Do you no longer want to receive notifications about changes "
+            (subscription_area(subscription))
+            "?"
+          }
+          form action=(format!("/subscriptions/unsubscribe?token={}", token)) method="POST" {
+            input type="hidden" name="List-Unsubscribe" value="One-Click";
+            input type="submit" value="Unsubscribe";
+          }
+        },
+    )
+}
+
+pub fn unsubscribe_bbox_ack(subscription: &BboxSubscription) -> Markup {
+    page(
+        "Unsubscribed",
+        None,
+        None,
+        None,
+        html! {
+          h2 { "Unsubscribed" }
+          p {
+            "You will no longer receive notifications about changes "
+            (subscription_area(subscription))
+            "."
+          }
+        },
+    )
+}
+
+pub fn unsubscribe_bbox_failed() -> Markup {
+    page(
+        "Unsubscribe",
+        None,
+        None,
+        None,
+        html! {
+          h2 { "Unsubscribe" }
+          p {
+            "The link is invalid or the subscription has already been cancelled."
+          }
+        },
+    )
+}
@@ -1,80 +1,40 @@
 use crate::{
-    core::{
-        db::{EventIndexer, PlaceIndexer},
-        prelude::*,
-        usecases,
-    },
-    infrastructure::error::AppError,
+    core::{prelude::*, usecases},
+    infrastructure::jobs::{self, JobWorkerPool},
 };
-use ofdb_core::rating::Rated;
-use rocket::{config::Config, Rocket, Route};
-use rocket_contrib::json::Json;
-use std::result;
+use rocket::{config::Config, fairing::AdHoc, Rocket, Route};
+use std::sync::Arc;
 
 pub mod api;
+mod db;
 #[cfg(feature = "frontend")]
 mod frontend;
 mod guards;
 #[cfg(test)]
 mod mockdb;
 pub mod notify;
-mod sqlite;
 mod tantivy;
 #[cfg(test)]
 pub mod tests;
 
-type Result<T> = result::Result<Json<T>, AppError>;
-
-fn index_all_places<D: PlaceRepo + RatingRepository>(
-    db: &D,
-    indexer: &mut dyn PlaceIndexer,
-) -> Result<()> {
-    // TODO: Split into chunks with fixed size instead of
-    // loading all places at once!
-    let places = db.all_places()?;
-    for (place, status) in places {
-        let ratings = db.load_ratings_of_place(place.id.as_ref())?;
-        if let Err(err) =
-            indexer.add_or_update_place(&place, status, &place.avg_ratings(&ratings[..]))
-        {
-            error!("Failed to index place {:?}: {}", place, err);
-        }
-    }
-    if let Err(err) = indexer.flush_index() {
-        error!("Failed to build place index: {}", err);
-    }
-    Ok(Json(()))
-}
-
-fn index_all_events_chronologically<D: EventGateway>(
-    db: &D,
-    indexer: &mut dyn EventIndexer,
-) -> Result<()> {
-    // TODO: Split into chunks with fixed size instead of
-    // loading all events at once!
-    let events = db.all_events_chronologically()?;
-    for event in events {
-        if let Err(err) = indexer.add_or_update_event(&event) {
-            error!("Failed to index event {:?}: {}", event, err);
-        }
-    }
-    if let Err(err) = indexer.flush_index() {
-        error!("Failed to build event index: {}", err);
-    }
-    Ok(Json(()))
-}
+const BACKGROUND_JOB_WORKERS: usize = 4;
 
 pub(crate) fn rocket_instance(
-    connections: sqlite::Connections,
+    connections: db::Connections,
     mut search_engine: tantivy::SearchEngine,
     mounts: Vec<(&str, Vec<Route>)>,
+    place_edit_mode: usecases::PlaceEditMode,
+    default_time_zone: usecases::DefaultEventTimeZone,
     cfg: Option<Config>,
 ) -> Rocket {
-    info!("Indexing all places...");
-    index_all_places(&*connections.exclusive().unwrap(), &mut search_engine).unwrap();
+    info!("Updating the search index...");
+    usecases::synchronize_index(&*connections.shared().unwrap(), &mut search_engine).unwrap();
 
-    info!("Indexing all events...");
-    index_all_events_chronologically(&*connections.exclusive().unwrap(), &mut search_engine)
+    info!("Rescheduling interrupted background jobs...");
+    connections
+        .exclusive()
+        .unwrap()
+        .release_running_jobs()
         .unwrap();
 
     info!("Deleting expired user e-mail tokens...");
@@ -85,7 +45,12 @@ pub(crate) fn rocket_instance(
         Some(cfg) => rocket::custom(cfg),
         None => rocket::ignite(),
     };
-    let mut instance = r.manage(connections).manage(search_engine);
+    let mut instance = r
+        .manage(connections)
+        .manage(search_engine)
+        .manage(api::TileCache::default())
+        .manage(place_edit_mode)
+        .manage(default_time_zone);
 
     for (m, r) in mounts {
         instance = instance.mount(m, r);
@@ -104,20 +69,48 @@ fn mounts() -> Vec<(&'static str, Vec<Route>)> {
 }
 
 pub fn run(
-    connections: sqlite::Connections,
+    connections: db::Connections,
     search_engine: tantivy::SearchEngine,
     enable_cors: bool,
+    place_edit_mode: usecases::PlaceEditMode,
+    default_time_zone: usecases::DefaultEventTimeZone,
 ) {
+    let rocket = rocket_instance(
+        connections.clone(),
+        search_engine.clone(),
+        mounts(),
+        place_edit_mode,
+        default_time_zone,
+        None,
+    );
+    info!("Starting background job workers...");
+    let job_workers = JobWorkerPool::start(
+        connections.clone(),
+        search_engine,
+        Arc::new(notify::notification_gateway()),
+        BACKGROUND_JOB_WORKERS,
+    );
+    job_workers.install();
+    if let Err(err) = jobs::schedule_subscription_digests(&connections) {
+        error!("Failed to schedule subscription digests: {}", err);
+    }
+    // Submitted jobs are only enqueued and would never be executed
+    let rocket = rocket.attach(AdHoc::on_attach("Background job workers", |rocket| {
+        if jobs::is_worker_pool_installed() {
+            Ok(rocket)
+        } else {
+            error!("No background job workers installed");
+            Err(rocket)
+        }
+    }));
     if enable_cors {
         let cors = rocket_cors::CorsOptions {
             ..Default::default()
         }
         .to_cors()
         .unwrap();
-        rocket_instance(connections, search_engine, mounts(), None)
-            .attach(cors)
-            .launch();
+        rocket.attach(cors).launch();
     } else {
-        rocket_instance(connections, search_engine, mounts(), None).launch();
+        rocket.launch();
     }
 }
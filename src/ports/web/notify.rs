@@ -33,24 +33,37 @@ impl Deref for Notify {
     }
 }
 
+/// Create the notification gateway for the configured
+/// e-mail gateway.
+#[cfg(not(test))]
+pub fn notification_gateway() -> notify::Notify {
+    let notify = if let Some(gw) = &*MAILGUN_GW {
+        info!("Use Mailgun gateway");
+        notify::Notify::new(gw.clone())
+    } else if let Some(gw) = &*SENDMAIL_GW {
+        warn!("Mailgun gateway was not configured: use sendmail as fallback");
+        notify::Notify::new(gw.clone())
+    } else {
+        warn!("No eMail gateway was not configured");
+        notify::Notify::new(DummyMailGw)
+    };
+    // The unsubscribe page is served by the web frontend
+    if cfg!(feature = "frontend") {
+        notify.with_unsubscribe_url("https://openfairdb.org/subscriptions/unsubscribe")
+    } else {
+        notify
+    }
+}
+
+#[cfg(test)]
+pub fn notification_gateway() -> DummyNotifyGW {
+    DummyNotifyGW
+}
+
 impl<'a, 'r> FromRequest<'a, 'r> for Notify {
     type Error = ();
 
-    #[cfg(not(test))]
-    fn from_request(_: &'a Request<'r>) -> request::Outcome<Self, ()> {
-        if let Some(gw) = &*MAILGUN_GW {
-            info!("Use Mailgun gateway");
-            Outcome::Success(Notify(notify::Notify::new(gw.clone())))
-        } else if let Some(gw) = &*SENDMAIL_GW {
-            warn!("Mailgun gateway was not configured: use sendmail as fallback");
-            Outcome::Success(Notify(notify::Notify::new(gw.clone())))
-        } else {
-            warn!("No eMail gateway was not configured");
-            Outcome::Success(Notify(notify::Notify::new(DummyMailGw)))
-        }
-    }
-    #[cfg(test)]
     fn from_request(_: &'a Request<'r>) -> request::Outcome<Self, ()> {
-        Outcome::Success(Notify(DummyNotifyGW))
+        Outcome::Success(Notify(notification_gateway()))
     }
 }
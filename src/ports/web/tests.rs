@@ -1,6 +1,6 @@
 use crate::{
     core::{prelude::*, usecases},
-    infrastructure::db::{sqlite, tantivy},
+    infrastructure::db::{self, tantivy},
 };
 use rocket::{
     config::{Config, Environment},
@@ -19,33 +19,34 @@ pub mod prelude {
     };
 }
 
-embed_migrations!();
-
 pub fn setup(
     mounts: Vec<(&'static str, Vec<Route>)>,
 ) -> (
     rocket::local::Client,
-    sqlite::Connections,
+    db::Connections,
     tantivy::SearchEngine,
 ) {
     let cfg = Config::build(Environment::Development)
         .log_level(LoggingLevel::Debug)
         .finalize()
         .unwrap();
-    let connections = sqlite::Connections::init(":memory:", 1).unwrap();
-    embedded_migrations::run(&*connections.exclusive().unwrap()).unwrap();
+    // Submitted jobs are executed before the requests are answered
+    crate::infrastructure::jobs::install_inline_executor();
+    let connections = db::tests::connections();
     let search_engine = tantivy::SearchEngine::init_in_ram().unwrap();
     let rocket = super::rocket_instance(
         connections.clone(),
         search_engine.clone(),
         mounts,
+        Default::default(),
+        usecases::DefaultEventTimeZone(Tz::UTC),
         Some(cfg),
     );
     let client = Client::new(rocket).unwrap();
     (client, connections, search_engine)
 }
 
-pub fn register_user(pool: &sqlite::Connections, email: &str, pw: &str, confirmed: bool) {
+pub fn register_user(pool: &db::Connections, email: &str, pw: &str, confirmed: bool) {
     let db = pool.exclusive().unwrap();
     usecases::create_new_user(
         &*db,
@@ -68,10 +69,11 @@ pub fn register_user(pool: &sqlite::Connections, email: &str, pw: &str, confirme
 pub struct DummyNotifyGW;
 
 impl ofdb_core::gateways::notify::NotificationGateway for DummyNotifyGW {
-    fn place_added(&self, _: &[String], _: &Place, _: Vec<Category>) {}
-    fn place_updated(&self, _: &[String], _: &Place, _: Vec<Category>) {}
-    fn event_created(&self, _: &[String], _: &Event) {}
-    fn event_updated(&self, _: &[String], _: &Event) {}
+    fn place_added(&self, _: &[BboxSubscription], _: &Place, _: Vec<Category>) {}
+    fn place_updated(&self, _: &[BboxSubscription], _: &Place, _: Vec<Category>) {}
+    fn event_created(&self, _: &[BboxSubscription], _: &Event) {}
+    fn event_updated(&self, _: &[BboxSubscription], _: &Event) {}
+    fn subscription_digest(&self, _: &BboxSubscription, _: &[Place], _: &[Event]) {}
     fn user_registered_kvm(&self, _: &User) {}
     fn user_registered_ofdb(&self, _: &User) {}
     fn user_registered(&self, _: &User, _: &str) {}